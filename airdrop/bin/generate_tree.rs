@@ -0,0 +1,70 @@
+//! Generates the Merkle tree of an airdrop from a CSV file of `address,amount` lines and
+//! prints its root and the proof of every claim as JSON.
+//!
+//! Addresses are formatted keys, e.g. `account-hash-…` or `hash-…`, and amounts are decimal
+//! token amounts. Empty lines and lines starting with `#` are skipped, e.g.:
+//! `cargo run --bin airdrop_generate_tree -- recipients.csv > claims.json`
+use airdrop::merkle::MerkleTree;
+use clap::Parser;
+use odra::casper_types::U256;
+use odra::Address;
+use serde_json::json;
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+#[derive(Parser)]
+#[command(about = "Generates the Merkle tree and proofs of an airdrop from a CSV file.")]
+struct Args {
+    /// CSV file with one `address,amount` line per claim.
+    csv: PathBuf,
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+    let csv = fs::read_to_string(&args.csv)?;
+
+    let mut lines = vec![];
+    let mut claims = vec![];
+    for (number, line) in csv.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (address, amount) = line
+            .split_once(',')
+            .ok_or_else(|| format!("Line {}: expected `address,amount`", number + 1))?;
+        let (address, amount) = (address.trim(), amount.trim());
+        let account = Address::from_str(address)
+            .map_err(|_| format!("Line {}: invalid address `{}`", number + 1, address))?;
+        let amount = U256::from_dec_str(amount)
+            .map_err(|_| format!("Line {}: invalid amount `{}`", number + 1, amount))?;
+        lines.push((address.to_string(), amount));
+        claims.push((account, amount));
+    }
+
+    let tree = MerkleTree::new(&claims).map_err(|e| format!("Serialization failed: {:?}", e))?;
+    // The index of a claim is its position among the claims, not its line number
+    let claims = lines
+        .iter()
+        .enumerate()
+        .map(|(index, (address, amount))| {
+            json!({
+                "index": index,
+                "account": address,
+                "amount": amount.to_string(),
+                "proof": tree.proof(index).iter().map(|node| to_hex(node)).collect::<Vec<_>>(),
+            })
+        })
+        .collect::<Vec<_>>();
+    let output = json!({
+        "merkle_root": to_hex(&tree.root()),
+        "claims": claims,
+    });
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
@@ -0,0 +1,222 @@
+use crate::merkle::{leaf_preimage, node_preimage};
+use odra::casper_types::U256;
+use odra::module::SubModule;
+use odra::prelude::*;
+use odra::{Address, ContractRef, Mapping, Var};
+use odra_modules::access::Ownable;
+use odra_modules::cep18_token::Cep18ContractRef;
+
+#[odra::odra_error]
+/// Errors that may occur during the contract execution.
+pub enum Error {
+    /// The claim at this index has already been made.
+    AlreadyClaimed = 1,
+    /// The proof doesn't lead to the Merkle root.
+    InvalidProof = 2,
+}
+
+#[odra::event]
+/// Emitted when tokens are claimed.
+pub struct Claimed {
+    pub index: u32,
+    pub account: Address,
+    pub amount: U256,
+}
+
+#[odra::module(events = [Claimed], errors = Error)]
+/// Distributes CEP-18 tokens to the recipients committed to by a Merkle root.
+pub struct Airdrop {
+    token: Var<Address>,
+    merkle_root: Var<[u8; 32]>,
+    /// Bit `index % 64` of word `index / 64` is set once the claim at `index` is made.
+    claimed: Mapping<u32, u64>,
+    ownable: SubModule<Ownable>,
+}
+
+#[odra::module]
+impl Airdrop {
+    /// Initializes the airdrop of `token` with the root of the claims tree; the deployer
+    /// becomes the owner. The airdropped tokens are transferred to the contract separately.
+    pub fn init(&mut self, token: Address, merkle_root: [u8; 32]) {
+        self.token.set(token);
+        self.merkle_root.set(merkle_root);
+        self.ownable.init();
+    }
+
+    /**********
+     * TRANSACTIONS
+     **********/
+
+    /// Sends `amount` tokens to `account` if the claim is part of the tree. Anyone can
+    /// submit a claim, the tokens always go to the account it was made for.
+    pub fn claim(&mut self, index: u32, account: Address, amount: U256, proof: Vec<[u8; 32]>) {
+        if self.is_claimed(index) {
+            self.env().revert(Error::AlreadyClaimed);
+        }
+        let leaf = leaf_preimage(index, &account, &amount).unwrap_or_revert(&self.env());
+        let root = proof.iter().fold(self.env().hash(leaf), |node, sibling| {
+            self.env().hash(node_preimage(&node, sibling))
+        });
+        if root != self.get_merkle_root() {
+            self.env().revert(Error::InvalidProof);
+        }
+
+        let word = self.claimed.get_or_default(&(index / 64));
+        self.claimed
+            .set(&(index / 64), word | (1u64 << (index % 64)));
+        Cep18ContractRef::new(self.env(), self.get_token()).transfer(&account, &amount);
+        self.env().emit_event(Claimed {
+            index,
+            account,
+            amount,
+        });
+    }
+
+    /// Sends `amount` of the airdropped tokens back to the owner, e.g. those left unclaimed.
+    pub fn withdraw(&mut self, amount: U256) {
+        let caller = self.env().caller();
+        self.ownable.assert_owner(&caller);
+        Cep18ContractRef::new(self.env(), self.get_token()).transfer(&caller, &amount);
+    }
+
+    /**********
+     * QUERIES
+     **********/
+
+    /// Returns whether the claim at `index` has been made.
+    pub fn is_claimed(&self, index: u32) -> bool {
+        self.claimed.get_or_default(&(index / 64)) & (1u64 << (index % 64)) != 0
+    }
+
+    /// Returns the root of the claims tree.
+    pub fn get_merkle_root(&self) -> [u8; 32] {
+        self.merkle_root.get_or_default()
+    }
+
+    /// Returns the airdropped token.
+    pub fn get_token(&self) -> Address {
+        self.token.get().unwrap_or_revert(&self.env())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::merkle::MerkleTree;
+    use odra::host::{Deployer, HostEnv, HostRef};
+    use odra_modules::cep18_token::{Cep18HostRef, Cep18InitArgs};
+
+    /// Deploys a token and an airdrop of the claims, funded with all of their tokens.
+    fn setup(env: &HostEnv, claims: &[(Address, U256)]) -> (AirdropHostRef, Cep18HostRef) {
+        let mut token = Cep18HostRef::deploy(
+            env,
+            Cep18InitArgs {
+                symbol: "DROP".to_string(),
+                name: "Airdropped Token".to_string(),
+                decimals: 0,
+                initial_supply: U256::from(1_000_000),
+                admin_list: vec![],
+                minter_list: vec![],
+                modality: None,
+            },
+        );
+        let tree = MerkleTree::new(claims).unwrap();
+        let airdrop = AirdropHostRef::deploy(
+            env,
+            AirdropInitArgs {
+                token: *token.address(),
+                merkle_root: tree.root(),
+            },
+        );
+        let total = claims
+            .iter()
+            .fold(U256::zero(), |sum, (_, amount)| sum + *amount);
+        token.transfer(airdrop.address(), &total);
+        (airdrop, token)
+    }
+
+    #[test]
+    fn claims() {
+        let env = odra_test::env();
+        let claims: Vec<_> = (1..=5)
+            .map(|i| (env.get_account(i), U256::from(i * 100)))
+            .collect();
+        let tree = MerkleTree::new(&claims).unwrap();
+        let (mut airdrop, token) = setup(&env, &claims);
+        let carol = env.get_account(3);
+
+        env.set_caller(carol);
+        airdrop.claim(2, carol, U256::from(300), tree.proof(2));
+        assert_eq!(token.balance_of(&carol), U256::from(300));
+        assert!(airdrop.is_claimed(2));
+        assert!(env.emitted_event(
+            airdrop.address(),
+            &Claimed {
+                index: 2,
+                account: carol,
+                amount: U256::from(300)
+            }
+        ));
+        assert_eq!(
+            airdrop.try_claim(2, carol, U256::from(300), tree.proof(2)),
+            Err(Error::AlreadyClaimed.into())
+        );
+
+        // A claim has to match the tree exactly
+        let alice = env.get_account(1);
+        assert_eq!(
+            airdrop.try_claim(0, alice, U256::from(1_000), tree.proof(0)),
+            Err(Error::InvalidProof.into())
+        );
+        assert_eq!(
+            airdrop.try_claim(0, carol, U256::from(100), tree.proof(0)),
+            Err(Error::InvalidProof.into())
+        );
+        assert_eq!(
+            airdrop.try_claim(0, alice, U256::from(100), tree.proof(1)),
+            Err(Error::InvalidProof.into())
+        );
+
+        // Anyone can submit a claim, but only its account receives the tokens
+        airdrop.claim(0, alice, U256::from(100), tree.proof(0));
+        assert_eq!(token.balance_of(&alice), U256::from(100));
+        assert_eq!(token.balance_of(&carol), U256::from(300));
+
+        // The last leaf has no sibling and is moved up the tree unchanged
+        let eve = env.get_account(5);
+        airdrop.claim(4, eve, U256::from(500), tree.proof(4));
+        assert_eq!(token.balance_of(&eve), U256::from(500));
+    }
+
+    #[test]
+    fn claim_bitmap() {
+        let env = odra_test::env();
+        let claims: Vec<_> = (0..130)
+            .map(|i| (env.get_account(1 + i % 5), U256::from(i + 1)))
+            .collect();
+        let tree = MerkleTree::new(&claims).unwrap();
+        let (mut airdrop, token) = setup(&env, &claims);
+
+        // The claims are spread over three words of the bitmap
+        for index in [0, 63, 64, 129] {
+            let (account, amount) = claims[index];
+            airdrop.claim(index as u32, account, amount, tree.proof(index));
+        }
+        for index in [0, 63, 64, 129] {
+            assert!(airdrop.is_claimed(index));
+        }
+        for index in [1, 62, 65, 128] {
+            assert!(!airdrop.is_claimed(index));
+        }
+
+        // The owner takes back what's left
+        let owner = env.get_account(0);
+        let left = token.balance_of(airdrop.address());
+        let balance = token.balance_of(&owner);
+        env.set_caller(env.get_account(1));
+        assert!(airdrop.try_withdraw(left).is_err());
+        env.set_caller(owner);
+        airdrop.withdraw(left);
+        assert_eq!(token.balance_of(&owner), balance + left);
+    }
+}
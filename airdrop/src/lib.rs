@@ -0,0 +1,6 @@
+#![cfg_attr(not(test), no_std)]
+#![cfg_attr(not(test), no_main)]
+extern crate alloc;
+
+pub mod airdrop;
+pub mod merkle;
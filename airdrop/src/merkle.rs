@@ -0,0 +1,94 @@
+//! Merkle tree encoding shared by the contract and the off-chain tree generator.
+use odra::casper_types::bytesrepr::{self, ToBytes};
+use odra::casper_types::U256;
+use odra::prelude::*;
+use odra::Address;
+
+/// Prepended to leaves, so a leaf can never be passed off as an inner node.
+const LEAF_PREFIX: u8 = 0;
+/// Prepended to inner nodes.
+const NODE_PREFIX: u8 = 1;
+
+/// Returns the bytes hashed into the leaf of a claim.
+pub fn leaf_preimage(
+    index: u32,
+    account: &Address,
+    amount: &U256,
+) -> Result<Vec<u8>, bytesrepr::Error> {
+    let mut bytes = vec![LEAF_PREFIX];
+    bytes.extend(index.to_bytes()?);
+    bytes.extend(account.to_bytes()?);
+    bytes.extend(amount.to_bytes()?);
+    Ok(bytes)
+}
+
+/// Returns the bytes hashed into the parent of two nodes. The nodes are sorted,
+/// so a proof doesn't need to say on which side each sibling is.
+pub fn node_preimage(a: &[u8; 32], b: &[u8; 32]) -> Vec<u8> {
+    let (left, right) = if a <= b { (a, b) } else { (b, a) };
+    let mut bytes = vec![NODE_PREFIX];
+    bytes.extend(left);
+    bytes.extend(right);
+    bytes
+}
+
+/// A Merkle tree built off-chain, hashing with the same Blake2b as `env().hash`.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct MerkleTree {
+    /// Every level of the tree, from the leaves up to the root.
+    levels: Vec<Vec<[u8; 32]>>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl MerkleTree {
+    /// Builds the tree of the given claims, indexed by their position.
+    pub fn new(claims: &[(Address, U256)]) -> Result<Self, bytesrepr::Error> {
+        use odra::casper_types::crypto::blake2b;
+
+        let mut level = claims
+            .iter()
+            .enumerate()
+            .map(|(index, (account, amount))| {
+                leaf_preimage(index as u32, account, amount).map(blake2b)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut levels = vec![];
+        while level.len() > 1 {
+            // A node without a sibling is moved up unchanged
+            let parent = level
+                .chunks(2)
+                .map(|pair| match pair {
+                    [a, b] => blake2b(node_preimage(a, b)),
+                    [a] => *a,
+                    _ => unreachable!(),
+                })
+                .collect();
+            levels.push(level);
+            level = parent;
+        }
+        levels.push(level);
+        Ok(Self { levels })
+    }
+
+    /// Returns the root of the tree; the root of an empty tree is all zeros.
+    pub fn root(&self) -> [u8; 32] {
+        self.levels
+            .last()
+            .and_then(|level| level.first())
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Returns the siblings on the path from the leaf at `index` to the root.
+    pub fn proof(&self, index: usize) -> Vec<[u8; 32]> {
+        let mut index = index;
+        let mut proof = vec![];
+        for level in &self.levels[..self.levels.len() - 1] {
+            if let Some(sibling) = level.get(index ^ 1) {
+                proof.push(*sibling);
+            }
+            index /= 2;
+        }
+        proof
+    }
+}
@@ -0,0 +1,319 @@
+use odra::module::SubModule;
+use odra::prelude::*;
+use odra::{Address, Mapping};
+use odra_modules::access::{AccessControl, Role, DEFAULT_ADMIN_ROLE};
+
+/// Role allowed to attest claims about subjects. Its id is arbitrary, it just can't be the
+/// all-zero `DEFAULT_ADMIN_ROLE`.
+pub const ISSUER_ROLE: Role = [1; 32];
+
+#[odra::odra_error]
+/// Errors that may occur during the contract execution.
+pub enum Error {
+    /// The attestation would expire before it's issued.
+    InvalidExpiry = 1,
+    /// There's no attestation by the issuer about the subject under the schema.
+    AttestationNotFound = 2,
+    /// Only the issuer or the subject of an attestation can revoke it.
+    NotIssuerOrSubject = 3,
+    /// The attestation has already been revoked.
+    AlreadyRevoked = 4,
+}
+
+#[odra::odra_type]
+pub struct Attestation {
+    /// Hash of the off-chain data the claim is based on.
+    pub data_hash: [u8; 32],
+    pub issued_at: u64,
+    /// Block time the attestation expires at, or `None` if it never does.
+    pub expires_at: Option<u64>,
+    pub revoked: bool,
+}
+
+#[odra::event]
+/// Emitted when an issuer attests a claim about a subject.
+pub struct Attested {
+    pub issuer: Address,
+    pub subject: Address,
+    pub schema_id: u32,
+    pub data_hash: [u8; 32],
+    pub expires_at: Option<u64>,
+}
+
+#[odra::event]
+/// Emitted when an attestation is revoked.
+pub struct Revoked {
+    pub issuer: Address,
+    pub subject: Address,
+    pub schema_id: u32,
+    pub revoked_by: Address,
+}
+
+#[odra::module(events = [Attested, Revoked], errors = Error)]
+/// A registry of claims issuers attest about subjects, e.g. that an account passed a KYC check.
+pub struct Attestations {
+    access_control: SubModule<AccessControl>,
+    /// The latest attestation of each issuer about each subject, per schema.
+    attestations: Mapping<(Address, Address, u32), Attestation>,
+}
+
+#[odra::module]
+impl Attestations {
+    /// Initializes the registry; the deployer becomes the admin who can grant and revoke the
+    /// issuer role.
+    pub fn init(&mut self) {
+        self.access_control
+            .unchecked_grant_role(&DEFAULT_ADMIN_ROLE, &self.env().caller());
+    }
+
+    delegate! {
+        to self.access_control {
+            fn has_role(&self, role: &Role, address: &Address) -> bool;
+            fn grant_role(&mut self, role: &Role, address: &Address);
+            fn revoke_role(&mut self, role: &Role, address: &Address);
+            fn renounce_role(&mut self, role: &Role, address: &Address);
+        }
+    }
+
+    /**********
+     * TRANSACTIONS
+     **********/
+
+    /// Attests a claim about `subject`, described by the off-chain data hashed to `data_hash`.
+    /// `schema_id` identifies what kind of claim it is. Replaces the caller's previous
+    /// attestation about the subject under the same schema, if any. Only issuers can call it.
+    pub fn attest(
+        &mut self,
+        subject: Address,
+        schema_id: u32,
+        data_hash: [u8; 32],
+        expiry: Option<u64>,
+    ) {
+        let issuer = self.env().caller();
+        self.access_control.check_role(&ISSUER_ROLE, &issuer);
+        let now = self.env().get_block_time();
+        if matches!(expiry, Some(expires_at) if expires_at <= now) {
+            self.env().revert(Error::InvalidExpiry);
+        }
+
+        self.attestations.set(
+            &(issuer, subject, schema_id),
+            Attestation {
+                data_hash,
+                issued_at: now,
+                expires_at: expiry,
+                revoked: false,
+            },
+        );
+        self.env().emit_event(Attested {
+            issuer,
+            subject,
+            schema_id,
+            data_hash,
+            expires_at: expiry,
+        });
+    }
+
+    /// Revokes an attestation. Only its issuer, even one who lost the issuer role, or its
+    /// subject can call it.
+    pub fn revoke(&mut self, issuer: Address, subject: Address, schema_id: u32) {
+        let key = (issuer, subject, schema_id);
+        let mut attestation = self
+            .attestations
+            .get(&key)
+            .unwrap_or_revert_with(&self.env(), Error::AttestationNotFound);
+        let caller = self.env().caller();
+        if caller != issuer && caller != subject {
+            self.env().revert(Error::NotIssuerOrSubject);
+        }
+        if attestation.revoked {
+            self.env().revert(Error::AlreadyRevoked);
+        }
+
+        attestation.revoked = true;
+        self.attestations.set(&key, attestation);
+        self.env().emit_event(Revoked {
+            issuer,
+            subject,
+            schema_id,
+            revoked_by: caller,
+        });
+    }
+
+    /**********
+     * QUERIES
+     **********/
+
+    /// Returns the latest attestation of `issuer` about `subject` under a schema, valid or not.
+    pub fn get_attestation(
+        &self,
+        issuer: Address,
+        subject: Address,
+        schema_id: u32,
+    ) -> Option<Attestation> {
+        self.attestations.get(&(issuer, subject, schema_id))
+    }
+
+    /// Returns whether `issuer` attests a claim about `subject` under a schema: the attestation
+    /// exists, hasn't been revoked or expired, and its issuer still has the issuer role.
+    pub fn is_valid(&self, issuer: Address, subject: Address, schema_id: u32) -> bool {
+        self.valid_attestation(issuer, subject, schema_id).is_some()
+    }
+
+    /// Returns whether `issuer` attests a claim about `subject` under a schema, based on the
+    /// off-chain data hashed to `data_hash`.
+    pub fn verify(
+        &self,
+        issuer: Address,
+        subject: Address,
+        schema_id: u32,
+        data_hash: [u8; 32],
+    ) -> bool {
+        matches!(
+            self.valid_attestation(issuer, subject, schema_id),
+            Some(attestation) if attestation.data_hash == data_hash
+        )
+    }
+}
+
+impl Attestations {
+    fn valid_attestation(
+        &self,
+        issuer: Address,
+        subject: Address,
+        schema_id: u32,
+    ) -> Option<Attestation> {
+        let attestation = self.attestations.get(&(issuer, subject, schema_id))?;
+        let now = self.env().get_block_time();
+        let expired = matches!(attestation.expires_at, Some(expires_at) if expires_at <= now);
+        let trusted = self.access_control.has_role(&ISSUER_ROLE, &issuer);
+        (!attestation.revoked && !expired && trusted).then_some(attestation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use odra::casper_types::crypto::blake2b;
+    use odra::host::{Deployer, HostEnv, HostRef, NoArgs};
+
+    const KYC: u32 = 1;
+    const DIPLOMA: u32 = 2;
+
+    /// Deploys the registry, administered by account 0, with accounts 1 and 2 as issuers.
+    fn setup(env: &HostEnv) -> AttestationsHostRef {
+        let mut registry = AttestationsHostRef::deploy(env, NoArgs);
+        registry.grant_role(&ISSUER_ROLE, &env.get_account(1));
+        registry.grant_role(&ISSUER_ROLE, &env.get_account(2));
+        registry
+    }
+
+    #[test]
+    fn attesting_and_verifying() {
+        let env = odra_test::env();
+        let mut registry = setup(&env);
+        let (bank, university, alice) =
+            (env.get_account(1), env.get_account(2), env.get_account(3));
+        let passport = blake2b(b"Alice's passport scan");
+
+        // Only issuers can attest
+        env.set_caller(alice);
+        assert!(registry.try_attest(alice, KYC, passport, None).is_err());
+
+        env.set_caller(bank);
+        registry.attest(alice, KYC, passport, Some(10_000));
+        assert!(registry.is_valid(bank, alice, KYC));
+        assert!(registry.verify(bank, alice, KYC, passport));
+        assert!(!registry.verify(bank, alice, KYC, blake2b(b"Someone else's passport")));
+
+        // Attestations are keyed by issuer, subject and schema
+        assert!(!registry.is_valid(university, alice, KYC));
+        assert!(!registry.is_valid(bank, alice, DIPLOMA));
+        assert!(!registry.is_valid(bank, university, KYC));
+        env.set_caller(university);
+        registry.attest(alice, DIPLOMA, blake2b(b"BSc in Computer Science"), None);
+        assert!(registry.is_valid(university, alice, DIPLOMA));
+        assert_eq!(
+            registry.get_attestation(university, alice, DIPLOMA),
+            Some(Attestation {
+                data_hash: blake2b(b"BSc in Computer Science"),
+                issued_at: 0,
+                expires_at: None,
+                revoked: false,
+            })
+        );
+
+        // Expired attestations aren't valid, until the issuer renews them
+        env.advance_block_time(10_000);
+        assert!(!registry.is_valid(bank, alice, KYC));
+        assert!(registry.is_valid(university, alice, DIPLOMA));
+        env.set_caller(bank);
+        assert_eq!(
+            registry.try_attest(alice, KYC, passport, Some(10_000)),
+            Err(Error::InvalidExpiry.into())
+        );
+        registry.attest(alice, KYC, passport, Some(20_000));
+        assert!(registry.verify(bank, alice, KYC, passport));
+
+        // An issuer who loses the role can't attest anymore, and their attestations lose
+        // their value
+        env.set_caller(env.get_account(0));
+        registry.revoke_role(&ISSUER_ROLE, &bank);
+        assert!(!registry.is_valid(bank, alice, KYC));
+        env.set_caller(bank);
+        assert!(registry.try_attest(alice, KYC, passport, None).is_err());
+    }
+
+    #[test]
+    fn revoking() {
+        let env = odra_test::env();
+        let mut registry = setup(&env);
+        let (bank, alice, bob) = (env.get_account(1), env.get_account(3), env.get_account(4));
+        env.set_caller(bank);
+        registry.attest(alice, KYC, blake2b(b"Alice's passport scan"), None);
+        registry.attest(bob, KYC, blake2b(b"Bob's passport scan"), None);
+        assert_eq!(
+            registry.try_revoke(bank, alice, DIPLOMA),
+            Err(Error::AttestationNotFound.into())
+        );
+
+        // Nobody else can revoke an attestation
+        env.set_caller(bob);
+        assert_eq!(
+            registry.try_revoke(bank, alice, KYC),
+            Err(Error::NotIssuerOrSubject.into())
+        );
+
+        // The subject revokes their own attestation
+        env.set_caller(alice);
+        registry.revoke(bank, alice, KYC);
+        assert!(!registry.is_valid(bank, alice, KYC));
+        assert!(registry.get_attestation(bank, alice, KYC).unwrap().revoked);
+        assert_eq!(
+            registry.try_revoke(bank, alice, KYC),
+            Err(Error::AlreadyRevoked.into())
+        );
+
+        // The issuer revokes theirs, even after losing the role
+        env.set_caller(env.get_account(0));
+        registry.revoke_role(&ISSUER_ROLE, &bank);
+        env.set_caller(bank);
+        registry.revoke(bank, bob, KYC);
+        assert!(registry.get_attestation(bank, bob, KYC).unwrap().revoked);
+
+        // Once the role is granted back, revoked attestations stay revoked
+        env.set_caller(env.get_account(0));
+        registry.grant_role(&ISSUER_ROLE, &bank);
+        assert!(!registry.is_valid(bank, alice, KYC));
+        assert!(!registry.is_valid(bank, bob, KYC));
+        assert!(env.emitted_event(
+            registry.address(),
+            &Revoked {
+                issuer: bank,
+                subject: alice,
+                schema_id: KYC,
+                revoked_by: alice,
+            }
+        ));
+    }
+}
@@ -0,0 +1,286 @@
+use crate::curve::LinearCurve;
+use odra::casper_types::{U256, U512};
+use odra::module::SubModule;
+use odra::prelude::*;
+use odra::uints::{ToU256, ToU512};
+use odra::{Address, Var};
+use odra_modules::cep18_token::Cep18;
+
+// Numbered past the error codes of the embedded CEP-18 module.
+#[odra::odra_error]
+/// Errors that may occur during the contract execution.
+pub enum Error {
+    /// The base price and the slope must be positive.
+    InvalidCurve = 40_000,
+    /// The payment doesn't buy a single token, or no tokens are sold.
+    ZeroAmount = 40_001,
+    /// The price moved past the limit set by the caller.
+    SlippageExceeded = 40_002,
+}
+
+#[odra::event]
+/// Emitted when tokens are bought from the curve.
+pub struct Bought {
+    pub buyer: Address,
+    pub amount: U256,
+    pub cost: U512,
+}
+
+#[odra::event]
+/// Emitted when tokens are sold back to the curve.
+pub struct Sold {
+    pub seller: Address,
+    pub amount: U256,
+    pub proceeds: U512,
+}
+
+#[odra::module(events = [Bought, Sold], errors = Error)]
+/// A CEP-18 token minted and burned against a CSPR reserve at prices set by a linear curve.
+pub struct BondingCurve {
+    token: SubModule<Cep18>,
+    curve: Var<LinearCurve>,
+}
+
+#[odra::module]
+impl BondingCurve {
+    /// Initializes the token with no supply and the curve it's priced on. Tokens are whole, with
+    /// no decimals, and prices are in motes.
+    pub fn init(&mut self, name: String, symbol: String, base_price: U512, slope: U512) {
+        if base_price.is_zero() || slope.is_zero() {
+            self.env().revert(Error::InvalidCurve);
+        }
+        self.token
+            .init(symbol, name, 0, U256::zero(), vec![], vec![], None);
+        self.curve.set(LinearCurve { base_price, slope });
+    }
+
+    /**********
+     * TRANSACTIONS
+     **********/
+
+    /// Mints as many tokens as the attached CSPR buys, and returns the change to the buyer.
+    /// Reverts if that's fewer than `min_tokens`. Returns the number of tokens minted.
+    #[odra(payable)]
+    pub fn buy(&mut self, min_tokens: U256) -> U256 {
+        let payment = self.env().attached_value();
+        let curve = self.curve();
+        let supply = self.supply();
+        let amount = curve.tokens_for(supply, payment);
+        if amount.is_zero() {
+            self.env().revert(Error::ZeroAmount);
+        }
+        let minted = amount.to_u256().unwrap_or_revert(&self.env());
+        if minted < min_tokens {
+            self.env().revert(Error::SlippageExceeded);
+        }
+
+        let buyer = self.env().caller();
+        let cost = curve.buy_cost(supply, amount);
+        self.token.raw_mint(&buyer, &minted);
+        if payment > cost {
+            self.env().transfer_tokens(&buyer, &(payment - cost));
+        }
+        self.env().emit_event(Bought {
+            buyer,
+            amount: minted,
+            cost,
+        });
+        minted
+    }
+
+    /// Burns `amount` of the caller's tokens and sends them the CSPR they release.
+    /// Reverts if that's less than `min_proceeds`. Returns the CSPR sent.
+    pub fn sell(&mut self, amount: U256, min_proceeds: U512) -> U512 {
+        if amount.is_zero() {
+            self.env().revert(Error::ZeroAmount);
+        }
+        let seller = self.env().caller();
+        let supply = self.supply();
+        // Burning first reverts if the seller doesn't hold enough tokens
+        self.token.raw_burn(&seller, &amount);
+        let proceeds = self.curve().sell_return(supply, amount.to_u512());
+        if proceeds < min_proceeds {
+            self.env().revert(Error::SlippageExceeded);
+        }
+
+        self.env().transfer_tokens(&seller, &proceeds);
+        self.env().emit_event(Sold {
+            seller,
+            amount,
+            proceeds,
+        });
+        proceeds
+    }
+
+    delegate! {
+        to self.token {
+            fn transfer(&mut self, recipient: &Address, amount: &U256);
+            fn transfer_from(&mut self, owner: &Address, recipient: &Address, amount: &U256);
+            fn approve(&mut self, spender: &Address, amount: &U256);
+            fn increase_allowance(&mut self, spender: &Address, inc_by: &U256);
+            fn decrease_allowance(&mut self, spender: &Address, decr_by: &U256);
+            fn name(&self) -> String;
+            fn symbol(&self) -> String;
+            fn decimals(&self) -> u8;
+            fn total_supply(&self) -> U256;
+            fn balance_of(&self, address: &Address) -> U256;
+            fn allowance(&self, owner: &Address, spender: &Address) -> U256;
+        }
+    }
+
+    /**********
+     * QUERIES
+     **********/
+
+    /// Returns the CSPR it takes to buy `amount` tokens now.
+    pub fn buy_cost(&self, amount: U256) -> U512 {
+        self.curve().buy_cost(self.supply(), amount.to_u512())
+    }
+
+    /// Returns the CSPR selling `amount` tokens releases now.
+    pub fn sell_proceeds(&self, amount: U256) -> U512 {
+        self.curve().sell_return(self.supply(), amount.to_u512())
+    }
+
+    /// Returns the price on the curve at the current supply.
+    pub fn spot_price(&self) -> U512 {
+        self.curve().spot_price(self.supply())
+    }
+
+    /// Returns the CSPR backing the current supply.
+    pub fn reserve(&self) -> U512 {
+        self.curve().reserve_at(self.supply())
+    }
+
+    /// Returns the curve the token is priced on.
+    pub fn get_curve(&self) -> LinearCurve {
+        self.curve()
+    }
+}
+
+impl BondingCurve {
+    fn curve(&self) -> LinearCurve {
+        self.curve.get_or_revert_with(Error::InvalidCurve)
+    }
+
+    fn supply(&self) -> U512 {
+        self.token.total_supply().to_u512()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use odra::host::{Deployer, HostEnv, HostRef};
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+
+    fn setup(env: &HostEnv) -> BondingCurveHostRef {
+        BondingCurveHostRef::deploy(
+            env,
+            BondingCurveInitArgs {
+                name: "Curve Token".to_string(),
+                symbol: "CRV".to_string(),
+                base_price: U512::from(1_000),
+                slope: U512::from(10),
+            },
+        )
+    }
+
+    /// Checks that the contract holds exactly the reserve of the current supply.
+    fn assert_backed(env: &HostEnv, token: &BondingCurveHostRef) {
+        assert_eq!(token.reserve(), env.balance_of(token.address()));
+    }
+
+    #[test]
+    fn buy_and_sell() {
+        let env = odra_test::env();
+        let mut token = setup(&env);
+        let alice = env.get_account(1);
+
+        // 100,000 motes buy 73 tokens for 99,645, the change goes back
+        env.set_caller(alice);
+        let balance = env.balance_of(&alice);
+        assert_eq!(
+            token
+                .with_tokens(U512::from(100_000))
+                .try_buy(U256::from(74)),
+            Err(Error::SlippageExceeded.into())
+        );
+        let minted = token.with_tokens(U512::from(100_000)).buy(U256::from(73));
+        assert_eq!(minted, U256::from(73));
+        assert_eq!(token.balance_of(&alice), U256::from(73));
+        assert_eq!(env.balance_of(&alice), balance - U512::from(99_645));
+        assert_eq!(token.spot_price(), U512::from(1_730));
+        assert_backed(&env, &token);
+        assert_eq!(
+            token.with_tokens(U512::from(1_000)).try_buy(U256::zero()),
+            Err(Error::ZeroAmount.into())
+        );
+
+        // Selling moves back down the curve
+        assert_eq!(
+            token.try_sell(U256::from(23), U512::from(37_146)),
+            Err(Error::SlippageExceeded.into())
+        );
+        let proceeds = token.sell(U256::from(23), U512::from(37_145));
+        assert_eq!(proceeds, U512::from(37_145));
+        assert_eq!(token.total_supply(), U256::from(50));
+        assert_backed(&env, &token);
+        assert!(token.try_sell(U256::from(51), U512::zero()).is_err());
+    }
+
+    /// What one of the accounts does with the curve. Sales take a per mille of the account's
+    /// balance, at least one token.
+    #[derive(Debug, Clone)]
+    enum Action {
+        Buy(u64),
+        Sell(u64),
+    }
+
+    fn action() -> impl Strategy<Value = (usize, Action)> {
+        let action = prop_oneof![
+            (1_000..201_000u64).prop_map(Action::Buy),
+            (1..1_001u64).prop_map(Action::Sell),
+        ];
+        (0..3usize, action)
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(16))]
+
+        #[test]
+        fn reserve_invariant(actions in vec(action(), 1..60)) {
+            let env = odra_test::env();
+            let mut token = setup(&env);
+            let accounts = [env.get_account(1), env.get_account(2), env.get_account(3)];
+
+            for (account, action) in actions {
+                let account = accounts[account];
+                env.set_caller(account);
+                let balance = token.balance_of(&account);
+                match action {
+                    Action::Buy(payment) => {
+                        token.with_tokens(U512::from(payment)).buy(U256::zero());
+                    }
+                    Action::Sell(per_mille) if !balance.is_zero() => {
+                        let amount = (balance * per_mille / 1_000).max(U256::one());
+                        token.sell(amount, U512::zero());
+                    }
+                    Action::Sell(_) => {}
+                }
+                assert_backed(&env, &token);
+            }
+
+            // Every token can always be sold back
+            for account in accounts {
+                env.set_caller(account);
+                let balance = token.balance_of(&account);
+                if !balance.is_zero() {
+                    token.sell(balance, U512::zero());
+                }
+            }
+            assert_eq!(env.balance_of(token.address()), U512::zero());
+        }
+    }
+}
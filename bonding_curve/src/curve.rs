@@ -0,0 +1,100 @@
+//! Pricing along a linear bonding curve.
+use odra::casper_types::U512;
+
+#[odra::odra_type]
+/// A price that starts at `base_price` and grows by `slope` with every token minted.
+///
+/// The reserve backing a supply is the area under the curve up to that supply, so buying and
+/// selling cost the difference between two reserves, whatever the path between them.
+pub struct LinearCurve {
+    pub base_price: U512,
+    pub slope: U512,
+}
+
+impl LinearCurve {
+    /// Returns the CSPR that backs `supply` tokens: `base_price * s + slope * s² / 2`.
+    pub fn reserve_at(&self, supply: U512) -> U512 {
+        self.base_price * supply + self.slope * supply * supply / 2
+    }
+
+    /// Returns the price on the curve at `supply`.
+    pub fn spot_price(&self, supply: U512) -> U512 {
+        self.base_price + self.slope * supply
+    }
+
+    /// Returns the CSPR it takes to mint `amount` tokens at `supply`.
+    pub fn buy_cost(&self, supply: U512, amount: U512) -> U512 {
+        self.reserve_at(supply + amount) - self.reserve_at(supply)
+    }
+
+    /// Returns the CSPR released by burning `amount` of `supply` tokens.
+    pub fn sell_return(&self, supply: U512, amount: U512) -> U512 {
+        self.reserve_at(supply) - self.reserve_at(supply - amount)
+    }
+
+    /// Returns the most tokens `payment` can mint at `supply`.
+    pub fn tokens_for(&self, supply: U512, payment: U512) -> U512 {
+        self.supply_for(self.reserve_at(supply) + payment) - supply
+    }
+
+    /// Returns the largest supply backed by at most `reserve`.
+    fn supply_for(&self, reserve: U512) -> U512 {
+        // Solves slope * s² / 2 + base_price * s = reserve with the quadratic formula
+        let b = self.base_price;
+        let root = (b * b + self.slope * reserve * 2).integer_sqrt();
+        let mut supply = (root - b) / self.slope;
+        // The integer square root and the rounding of the reserve can put the estimate one off
+        while self.reserve_at(supply + 1) <= reserve {
+            supply += U512::one();
+        }
+        while self.reserve_at(supply) > reserve {
+            supply -= U512::one();
+        }
+        supply
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn curve(base_price: u64, slope: u64) -> LinearCurve {
+        LinearCurve {
+            base_price: U512::from(base_price),
+            slope: U512::from(slope),
+        }
+    }
+
+    #[test]
+    fn reserve_is_the_area_under_the_curve() {
+        let curve = curve(1_000, 10);
+        assert_eq!(curve.reserve_at(U512::zero()), U512::zero());
+        assert_eq!(curve.reserve_at(U512::from(10)), U512::from(10_500));
+        assert_eq!(curve.spot_price(U512::from(10)), U512::from(1_100));
+
+        // Buying in steps costs the same as buying at once
+        let (a, b) = (U512::from(7), U512::from(13));
+        assert_eq!(
+            curve.buy_cost(U512::zero(), a) + curve.buy_cost(a, b),
+            curve.buy_cost(U512::zero(), a + b)
+        );
+        assert_eq!(curve.sell_return(a + b, b), curve.buy_cost(a, b));
+    }
+
+    #[test]
+    fn tokens_for_is_exact() {
+        // An odd slope makes the reserve round down
+        for curve in [curve(1_000, 10), curve(3, 7), curve(1, 1)] {
+            for supply in [0u64, 1, 50, 999] {
+                let supply = U512::from(supply);
+                for payment in [0u64, 1, 999, 1_000, 123_456, 10_000_000] {
+                    let payment = U512::from(payment);
+                    let tokens = curve.tokens_for(supply, payment);
+                    // The payment covers the tokens, but not one more
+                    assert!(curve.buy_cost(supply, tokens) <= payment);
+                    assert!(curve.buy_cost(supply, tokens + U512::one()) > payment);
+                }
+            }
+        }
+    }
+}
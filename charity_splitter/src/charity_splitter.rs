@@ -0,0 +1,411 @@
+use odra::casper_types::U512;
+use odra::prelude::*;
+use odra::{Address, Mapping, Var};
+
+#[odra::odra_error]
+/// Errors that may occur during the contract execution.
+pub enum Error {
+    /// At least one beneficiary is needed.
+    NoBeneficiaries = 1,
+    /// Every beneficiary needs exactly one weight.
+    LengthMismatch = 2,
+    /// At least one beneficiary needs a positive weight.
+    ZeroTotalWeight = 3,
+    /// Each beneficiary can be listed only once.
+    DuplicateBeneficiary = 4,
+    /// Only beneficiaries can propose and vote.
+    NotBeneficiary = 5,
+    /// Donations need some CSPR attached.
+    ZeroDonation = 6,
+    /// There's no proposal with the given ID.
+    ProposalNotFound = 7,
+    /// The proposal's voting period is over.
+    VotingEnded = 8,
+    /// The beneficiary has already voted for the proposal.
+    AlreadyVoted = 9,
+    /// The proposal's voting period isn't over yet.
+    VotingNotEnded = 10,
+    /// The proposal wasn't voted for by a majority of the beneficiaries.
+    NotEnoughVotes = 11,
+    /// The proposal has already been executed.
+    AlreadyExecuted = 12,
+}
+
+#[odra::odra_type]
+pub struct Proposal {
+    pub proposer: Address,
+    /// New weights, in the order of the beneficiaries.
+    pub weights: Vec<u64>,
+    /// Block time voting ends at.
+    pub ends_at: u64,
+    pub votes: u32,
+    pub executed: bool,
+}
+
+#[odra::event]
+/// Emitted when a donation is split among the beneficiaries.
+pub struct DonationSplit {
+    pub from: Address,
+    pub amount: U512,
+}
+
+#[odra::event]
+/// Emitted when a beneficiary proposes new weights.
+pub struct WeightsProposed {
+    pub proposal_id: u32,
+    pub proposer: Address,
+    pub weights: Vec<u64>,
+}
+
+#[odra::event]
+/// Emitted when a beneficiary votes for a proposal.
+pub struct Voted {
+    pub proposal_id: u32,
+    pub voter: Address,
+}
+
+#[odra::event]
+/// Emitted when a proposal's weights replace the current ones.
+pub struct WeightsChanged {
+    pub proposal_id: u32,
+    pub weights: Vec<u64>,
+}
+
+#[odra::module(
+    events = [DonationSplit, WeightsProposed, Voted, WeightsChanged],
+    errors = Error
+)]
+/// Splits donations among beneficiaries by weights the beneficiaries vote on.
+pub struct CharitySplitter {
+    beneficiaries: Var<Vec<Address>>,
+    /// Weights, in the order of the beneficiaries.
+    weights: Var<Vec<u64>>,
+    /// How long proposals are open for voting.
+    voting_period: Var<u64>,
+    proposals: Mapping<u32, Proposal>,
+    proposal_count: Var<u32>,
+    /// Whether each beneficiary has voted for each proposal.
+    voters: Mapping<(u32, Address), bool>,
+}
+
+#[odra::module]
+impl CharitySplitter {
+    /// Initializes the splitter with the beneficiaries, who can't change afterwards, and their
+    /// initial weights.
+    pub fn init(&mut self, beneficiaries: Vec<Address>, weights: Vec<u64>, voting_period: u64) {
+        if beneficiaries.is_empty() {
+            self.env().revert(Error::NoBeneficiaries);
+        }
+        for (i, beneficiary) in beneficiaries.iter().enumerate() {
+            if beneficiaries[..i].contains(beneficiary) {
+                self.env().revert(Error::DuplicateBeneficiary);
+            }
+        }
+        self.beneficiaries.set(beneficiaries);
+        self.check_weights(&weights);
+        self.weights.set(weights);
+        self.voting_period.set(voting_period);
+    }
+
+    /**********
+     * TRANSACTIONS
+     **********/
+
+    /// Splits the attached CSPR among the beneficiaries by their current weights, and sends
+    /// each of them their part right away.
+    #[odra(payable)]
+    pub fn donate(&mut self) {
+        let amount = self.env().attached_value();
+        if amount.is_zero() {
+            self.env().revert(Error::ZeroDonation);
+        }
+
+        let beneficiaries = self.beneficiaries.get_or_default();
+        for (beneficiary, share) in beneficiaries.iter().zip(self.split(amount)) {
+            if !share.is_zero() {
+                self.env().transfer_tokens(beneficiary, &share);
+            }
+        }
+        self.env().emit_event(DonationSplit {
+            from: self.env().caller(),
+            amount,
+        });
+    }
+
+    /// Proposes new weights, in the order of the beneficiaries. Only beneficiaries can call
+    /// it. Returns the ID of the proposal.
+    pub fn propose_weights(&mut self, weights: Vec<u64>) -> u32 {
+        let proposer = self.env().caller();
+        self.check_beneficiary(proposer);
+        self.check_weights(&weights);
+
+        let proposal_id = self.proposal_count.get_or_default();
+        self.proposals.set(
+            &proposal_id,
+            Proposal {
+                proposer,
+                weights: weights.clone(),
+                ends_at: self.env().get_block_time() + self.voting_period.get_or_default(),
+                votes: 0,
+                executed: false,
+            },
+        );
+        self.proposal_count.set(proposal_id + 1);
+        self.env().emit_event(WeightsProposed {
+            proposal_id,
+            proposer,
+            weights,
+        });
+        proposal_id
+    }
+
+    /// Votes for a proposal, once per beneficiary, until its voting period ends.
+    pub fn vote(&mut self, proposal_id: u32) {
+        let mut proposal = self.proposal(proposal_id);
+        if self.env().get_block_time() > proposal.ends_at {
+            self.env().revert(Error::VotingEnded);
+        }
+        let voter = self.env().caller();
+        self.check_beneficiary(voter);
+        if self.voters.get_or_default(&(proposal_id, voter)) {
+            self.env().revert(Error::AlreadyVoted);
+        }
+
+        proposal.votes += 1;
+        self.proposals.set(&proposal_id, proposal);
+        self.voters.set(&(proposal_id, voter), true);
+        self.env().emit_event(Voted { proposal_id, voter });
+    }
+
+    /// Replaces the weights with the proposal's, once its voting period has ended with votes
+    /// from more than half of the beneficiaries. Anyone can call it.
+    pub fn execute(&mut self, proposal_id: u32) {
+        let mut proposal = self.proposal(proposal_id);
+        if self.env().get_block_time() <= proposal.ends_at {
+            self.env().revert(Error::VotingNotEnded);
+        }
+        if proposal.executed {
+            self.env().revert(Error::AlreadyExecuted);
+        }
+        let beneficiaries = self.beneficiaries.get_or_default().len() as u32;
+        if proposal.votes * 2 <= beneficiaries {
+            self.env().revert(Error::NotEnoughVotes);
+        }
+
+        proposal.executed = true;
+        self.weights.set(proposal.weights.clone());
+        self.env().emit_event(WeightsChanged {
+            proposal_id,
+            weights: proposal.weights.clone(),
+        });
+        self.proposals.set(&proposal_id, proposal);
+    }
+
+    /**********
+     * QUERIES
+     **********/
+
+    /// Returns the beneficiaries.
+    pub fn get_beneficiaries(&self) -> Vec<Address> {
+        self.beneficiaries.get_or_default()
+    }
+
+    /// Returns the current weights, in the order of the beneficiaries.
+    pub fn get_weights(&self) -> Vec<u64> {
+        self.weights.get_or_default()
+    }
+
+    /// Returns how a donation of `amount` would be split, in the order of the beneficiaries.
+    pub fn split(&self, amount: U512) -> Vec<U512> {
+        let weights = self.weights.get_or_default();
+        let total = U512::from(weights.iter().sum::<u64>());
+        let mut shares: Vec<U512> = weights
+            .iter()
+            .map(|weight| amount * U512::from(*weight) / total)
+            .collect();
+
+        // The rounding dust goes to the first beneficiary with the largest weight
+        let dust = amount - shares.iter().fold(U512::zero(), |sum, share| sum + share);
+        let largest = (0..weights.len()).fold(0, |largest, i| {
+            if weights[i] > weights[largest] {
+                i
+            } else {
+                largest
+            }
+        });
+        shares[largest] += dust;
+        shares
+    }
+
+    /// Returns a proposal.
+    pub fn get_proposal(&self, proposal_id: u32) -> Option<Proposal> {
+        self.proposals.get(&proposal_id)
+    }
+
+    /// Returns the number of proposals made so far.
+    pub fn get_proposal_count(&self) -> u32 {
+        self.proposal_count.get_or_default()
+    }
+
+    /// Returns whether a beneficiary has voted for a proposal.
+    pub fn has_voted(&self, proposal_id: u32, beneficiary: Address) -> bool {
+        self.voters.get_or_default(&(proposal_id, beneficiary))
+    }
+}
+
+impl CharitySplitter {
+    fn proposal(&self, proposal_id: u32) -> Proposal {
+        self.proposals
+            .get(&proposal_id)
+            .unwrap_or_revert_with(&self.env(), Error::ProposalNotFound)
+    }
+
+    fn check_beneficiary(&self, address: Address) {
+        if !self.beneficiaries.get_or_default().contains(&address) {
+            self.env().revert(Error::NotBeneficiary);
+        }
+    }
+
+    fn check_weights(&self, weights: &[u64]) {
+        if weights.len() != self.beneficiaries.get_or_default().len() {
+            self.env().revert(Error::LengthMismatch);
+        }
+        if weights.iter().all(|weight| *weight == 0) {
+            self.env().revert(Error::ZeroTotalWeight);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use odra::host::{Deployer, HostEnv, HostRef};
+
+    const VOTING_PERIOD: u64 = 24 * 60 * 60 * 1_000; // one day
+
+    fn setup(env: &HostEnv, weights: Vec<u64>) -> CharitySplitterHostRef {
+        let beneficiaries = (1..=weights.len()).map(|i| env.get_account(i)).collect();
+        CharitySplitterHostRef::deploy(
+            env,
+            CharitySplitterInitArgs {
+                beneficiaries,
+                weights,
+                voting_period: VOTING_PERIOD,
+            },
+        )
+    }
+
+    #[test]
+    fn donations_are_split() {
+        let env = odra_test::env();
+        let mut splitter = setup(&env, vec![1, 3]);
+        let (shelter, food_bank) = (env.get_account(1), env.get_account(2));
+        let balances = (env.balance_of(&shelter), env.balance_of(&food_bank));
+
+        splitter.with_tokens(U512::from(1_000)).donate();
+        assert_eq!(env.balance_of(&shelter), balances.0 + U512::from(250));
+        assert_eq!(env.balance_of(&food_bank), balances.1 + U512::from(750));
+        assert_eq!(env.balance_of(splitter.address()), U512::zero());
+        assert_eq!(splitter.try_donate(), Err(Error::ZeroDonation.into()));
+
+        // Nothing is left behind by rounding
+        let splitter = setup(&env, vec![1, 2, 2]);
+        assert_eq!(
+            splitter.split(U512::from(101)),
+            vec![U512::from(20), U512::from(41), U512::from(40)]
+        );
+        let splitter = setup(&env, vec![0, 1]);
+        assert_eq!(
+            splitter.split(U512::from(7)),
+            vec![U512::zero(), U512::from(7)]
+        );
+    }
+
+    #[test]
+    fn weights_change_by_vote() {
+        let env = odra_test::env();
+        let mut splitter = setup(&env, vec![1, 1, 1]);
+        let (shelter, food_bank, clinic) =
+            (env.get_account(1), env.get_account(2), env.get_account(3));
+
+        // Only beneficiaries propose and vote
+        assert_eq!(
+            splitter.try_propose_weights(vec![1, 1, 2]),
+            Err(Error::NotBeneficiary.into())
+        );
+        env.set_caller(clinic);
+        assert_eq!(
+            splitter.try_propose_weights(vec![1, 1]),
+            Err(Error::LengthMismatch.into())
+        );
+        let id = splitter.propose_weights(vec![1, 1, 2]);
+        splitter.vote(id);
+        assert_eq!(splitter.try_vote(id), Err(Error::AlreadyVoted.into()));
+        env.set_caller(env.get_account(0));
+        assert_eq!(splitter.try_vote(id), Err(Error::NotBeneficiary.into()));
+
+        // One vote of three isn't a majority
+        env.advance_block_time(VOTING_PERIOD);
+        assert_eq!(splitter.try_execute(id), Err(Error::VotingNotEnded.into()));
+        env.advance_block_time(1);
+        assert_eq!(splitter.try_execute(id), Err(Error::NotEnoughVotes.into()));
+        env.set_caller(shelter);
+        assert_eq!(splitter.try_vote(id), Err(Error::VotingEnded.into()));
+
+        // Two votes are
+        env.set_caller(clinic);
+        let id = splitter.propose_weights(vec![0, 1, 3]);
+        for voter in [clinic, food_bank] {
+            env.set_caller(voter);
+            splitter.vote(id);
+        }
+        env.advance_block_time(VOTING_PERIOD + 1);
+        splitter.execute(id);
+        assert_eq!(splitter.try_execute(id), Err(Error::AlreadyExecuted.into()));
+        assert_eq!(splitter.get_weights(), vec![0, 1, 3]);
+        assert!(splitter.get_proposal(id).unwrap().executed);
+
+        // A beneficiary weighted down to zero gets nothing, but keeps their vote
+        let balance = env.balance_of(&shelter);
+        splitter.with_tokens(U512::from(400)).donate();
+        assert_eq!(env.balance_of(&shelter), balance);
+        assert_eq!(
+            splitter.split(U512::from(400)),
+            vec![U512::zero(), U512::from(100), U512::from(300)]
+        );
+        env.set_caller(shelter);
+        let id = splitter.propose_weights(vec![1, 1, 1]);
+        splitter.vote(id);
+        assert!(splitter.has_voted(id, shelter));
+    }
+
+    #[test]
+    fn invalid_setup() {
+        let env = odra_test::env();
+        let (shelter, food_bank) = (env.get_account(1), env.get_account(2));
+        let deploy = |beneficiaries: Vec<Address>, weights: Vec<u64>| {
+            CharitySplitterHostRef::try_deploy(
+                &env,
+                CharitySplitterInitArgs {
+                    beneficiaries,
+                    weights,
+                    voting_period: VOTING_PERIOD,
+                },
+            )
+            .err()
+        };
+        assert_eq!(deploy(vec![], vec![]), Some(Error::NoBeneficiaries.into()));
+        assert_eq!(
+            deploy(vec![shelter, food_bank], vec![1]),
+            Some(Error::LengthMismatch.into())
+        );
+        assert_eq!(
+            deploy(vec![shelter, food_bank], vec![0, 0]),
+            Some(Error::ZeroTotalWeight.into())
+        );
+        assert_eq!(
+            deploy(vec![shelter, shelter], vec![1, 1]),
+            Some(Error::DuplicateBeneficiary.into())
+        );
+    }
+}
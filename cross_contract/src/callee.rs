@@ -0,0 +1,82 @@
+use odra::prelude::*;
+use odra::{Address, Mapping};
+
+#[odra::odra_error]
+/// Errors that may occur during the contract execution.
+pub enum Error {
+    /// The sum doesn't fit in a `u32`.
+    Overflow = 1,
+}
+
+#[odra::event]
+/// Emitted when a value is stored.
+pub struct ValueSet {
+    pub owner: Address,
+    pub value: u32,
+}
+
+#[odra::module(events = [ValueSet], errors = Error)]
+/// A contract meant to be called by other contracts. It stores a value for each caller and does
+/// some arithmetic.
+pub struct Callee {
+    values: Mapping<Address, u32>,
+}
+
+#[odra::module]
+impl Callee {
+    /**********
+     * TRANSACTIONS
+     **********/
+
+    /// Stores `value` for the caller, which may be an account or a contract.
+    pub fn set_value(&mut self, value: u32) {
+        let owner = self.env().caller();
+        self.values.set(&owner, value);
+        self.env().emit_event(ValueSet { owner, value });
+    }
+
+    /**********
+     * QUERIES
+     **********/
+
+    pub fn get_value(&self, owner: Address) -> Option<u32> {
+        self.values.get(&owner)
+    }
+
+    /// Returns `a + b`, reverting if the sum overflows.
+    pub fn add(&self, a: u32, b: u32) -> u32 {
+        a.checked_add(b)
+            .unwrap_or_revert_with(&self.env(), Error::Overflow)
+    }
+
+    /// Returns `a + b`, or `None` if the sum overflows.
+    pub fn checked_add(&self, a: u32, b: u32) -> Option<u32> {
+        a.checked_add(b)
+    }
+
+    /// Returns the address that called this entry point.
+    pub fn whoami(&self) -> Address {
+        self.env().caller()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use odra::host::{Deployer, HostRef, NoArgs};
+
+    #[test]
+    fn called_by_an_account() {
+        let env = odra_test::env();
+        let account = env.get_account(1);
+        let mut callee = CalleeHostRef::deploy(&env, NoArgs);
+
+        env.set_caller(account);
+        callee.set_value(7);
+        assert_eq!(callee.get_value(account), Some(7));
+        assert_eq!(callee.whoami(), account);
+        assert_eq!(callee.add(2, 3), 5);
+        assert_eq!(callee.try_add(u32::MAX, 1), Err(Error::Overflow.into()));
+        assert_eq!(callee.checked_add(u32::MAX, 1), None);
+    }
+}
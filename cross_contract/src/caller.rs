@@ -0,0 +1,144 @@
+use crate::callee::CalleeContractRef;
+use odra::prelude::*;
+use odra::{Address, ContractRef, Var};
+
+#[odra::odra_error]
+/// Errors that may occur during the contract execution.
+pub enum Error {
+    /// The callee couldn't add the numbers.
+    SumTooLarge = 1,
+}
+
+#[odra::module(errors = Error)]
+/// A contract that calls a `Callee` through its generated contract reference.
+pub struct Caller {
+    /// Address of the `Callee` contract.
+    callee: Var<Address>,
+    /// Number of sums computed with `sum`.
+    sums_computed: Var<u32>,
+}
+
+#[odra::module]
+impl Caller {
+    /// Initializes the contract with the address of a deployed `Callee`.
+    pub fn init(&mut self, callee: Address) {
+        self.callee.set(callee);
+    }
+
+    /**********
+     * TRANSACTIONS
+     **********/
+
+    /// Stores `value` in the callee, under the address of this contract.
+    #[odra(non_reentrant)]
+    pub fn store(&mut self, value: u32) {
+        self.callee_ref().set_value(value);
+    }
+
+    /// Adds the numbers in the callee. If the callee reverts, the whole call reverts, including
+    /// the update of `sums_computed`.
+    pub fn sum(&mut self, a: u32, b: u32) -> u32 {
+        self.sums_computed
+            .set(self.sums_computed.get_or_default() + 1);
+        self.callee_ref().add(a, b)
+    }
+
+    /// Adds the numbers in the callee, reverting with this contract's own error if they overflow.
+    pub fn safe_sum(&self, a: u32, b: u32) -> u32 {
+        self.callee_ref()
+            .checked_add(a, b)
+            .unwrap_or_revert_with(&self.env(), Error::SumTooLarge)
+    }
+
+    /**********
+     * QUERIES
+     **********/
+
+    /// Returns the value this contract stored in the callee.
+    pub fn stored_value(&self) -> Option<u32> {
+        self.callee_ref().get_value(self.env().self_address())
+    }
+
+    /// Returns the address the callee sees as its caller.
+    pub fn callee_sees(&self) -> Address {
+        self.callee_ref().whoami()
+    }
+
+    pub fn sums_computed(&self) -> u32 {
+        self.sums_computed.get_or_default()
+    }
+
+    pub fn callee(&self) -> Address {
+        self.callee.get().unwrap_or_revert(&self.env())
+    }
+}
+
+impl Caller {
+    /// A reference to the callee, whose methods call its entry points.
+    fn callee_ref(&self) -> CalleeContractRef {
+        CalleeContractRef::new(self.env(), self.callee())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::callee::{self, CalleeHostRef, ValueSet};
+    use odra::host::{Deployer, HostRef, NoArgs};
+
+    fn setup() -> (odra::host::HostEnv, CalleeHostRef, CallerHostRef) {
+        let env = odra_test::env();
+        let callee = CalleeHostRef::deploy(&env, NoArgs);
+        let caller = CallerHostRef::deploy(
+            &env,
+            CallerInitArgs {
+                callee: *callee.address(),
+            },
+        );
+        (env, callee, caller)
+    }
+
+    #[test]
+    fn callee_sees_the_contract() {
+        let (env, callee, mut caller) = setup();
+        let account = env.get_account(1);
+
+        env.set_caller(account);
+        caller.store(42);
+        assert_eq!(caller.stored_value(), Some(42));
+        // The value belongs to the contract that called, not to the account behind it
+        assert_eq!(callee.get_value(*caller.address()), Some(42));
+        assert_eq!(callee.get_value(account), None);
+        assert!(env.emitted_event(
+            callee.address(),
+            &ValueSet {
+                owner: *caller.address(),
+                value: 42,
+            }
+        ));
+        assert_eq!(caller.callee_sees(), *caller.address());
+        assert_eq!(caller.callee(), *callee.address());
+    }
+
+    #[test]
+    fn errors_propagate() {
+        let (_env, _callee, mut caller) = setup();
+
+        assert_eq!(caller.sum(2, 3), 5);
+        assert_eq!(caller.sums_computed(), 1);
+
+        // The callee's error reverts the caller, and its state changes with it
+        assert_eq!(
+            caller.try_sum(u32::MAX, 1),
+            Err(callee::Error::Overflow.into())
+        );
+        assert_eq!(caller.sums_computed(), 1);
+
+        // An expected failure comes back as a value the caller can handle
+        assert_eq!(caller.safe_sum(2, 3), 5);
+        assert_eq!(
+            caller.try_safe_sum(u32::MAX, 1),
+            Err(Error::SumTooLarge.into())
+        );
+    }
+}
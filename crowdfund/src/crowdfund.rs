@@ -0,0 +1,586 @@
+use odra::casper_types::U512;
+use odra::prelude::*;
+use odra::{Address, Mapping, Var};
+
+// Tranches are expressed in basis points, 10_000 being all the raised funds
+const MAX_BPS: u16 = 10_000;
+
+#[odra::odra_error]
+/// Errors that may occur during the contract execution.
+pub enum Error {
+    /// Tranches must be positive and add up to all the raised funds.
+    InvalidTranches = 1,
+    /// Goals and contributions must be positive.
+    ZeroAmount = 2,
+    /// There's no campaign with the given ID.
+    CampaignNotFound = 3,
+    /// The campaign isn't in a state that allows this.
+    InvalidState = 4,
+    /// Only the creator of the campaign can start milestone votes.
+    NotCreator = 5,
+    /// Only backers of the campaign can vote.
+    NotBacker = 6,
+    /// The backer has already voted on this milestone.
+    AlreadyVoted = 7,
+    /// The vote has ended.
+    VotingClosed = 8,
+    /// The vote is still running.
+    VoteNotOver = 9,
+    /// The backer has no contribution left to refund.
+    NothingToRefund = 10,
+}
+
+#[odra::odra_type]
+pub enum CampaignState {
+    /// Accepting contributions until the deadline.
+    Funding,
+    /// The goal wasn't reached; backers can take their contributions back.
+    Failed,
+    /// Funded, waiting for the creator to put the next milestone to a vote within the
+    /// inactivity period.
+    Active,
+    /// Backers are voting on a milestone.
+    Voting,
+    /// A milestone was rejected, or the creator let the inactivity period pass; backers can
+    /// take back what hasn't been released.
+    Refunding,
+    /// Every tranche has been released.
+    Completed,
+}
+
+#[odra::odra_type]
+pub struct Campaign {
+    pub creator: Address,
+    pub goal: U512,
+    /// Block time contributions are accepted until.
+    pub deadline: u64,
+    /// Part of the raised funds released by each milestone, in basis points.
+    pub tranches: Vec<u16>,
+    pub raised: U512,
+    pub released: U512,
+    /// Index of the next milestone to be approved.
+    pub milestone: u32,
+    /// Block time the running milestone vote ends at.
+    pub vote_end: Option<u64>,
+    /// Block time the creator last had a milestone approved at, or the deadline before that.
+    pub last_approval: u64,
+    /// Contributions of the backers who approved the milestone.
+    pub votes_for: U512,
+    /// Contributions of the backers who rejected the milestone.
+    pub votes_against: U512,
+    pub rejected: bool,
+}
+
+#[odra::event]
+/// Emitted when a campaign is created.
+pub struct CampaignCreated {
+    pub campaign_id: u32,
+    pub creator: Address,
+    pub goal: U512,
+    pub deadline: u64,
+}
+
+#[odra::event]
+/// Emitted when a backer contributes.
+pub struct Contributed {
+    pub campaign_id: u32,
+    pub backer: Address,
+    pub amount: U512,
+}
+
+#[odra::event]
+/// Emitted when the creator puts a milestone to a vote.
+pub struct MilestoneVoteStarted {
+    pub campaign_id: u32,
+    pub milestone: u32,
+    pub vote_end: u64,
+}
+
+#[odra::event]
+/// Emitted when a backer votes on a milestone.
+pub struct Voted {
+    pub campaign_id: u32,
+    pub milestone: u32,
+    pub backer: Address,
+    pub approve: bool,
+    pub weight: U512,
+}
+
+#[odra::event]
+/// Emitted when a milestone is approved and its tranche is released.
+pub struct MilestoneApproved {
+    pub campaign_id: u32,
+    pub milestone: u32,
+    pub amount: U512,
+}
+
+#[odra::event]
+/// Emitted when a milestone is rejected.
+pub struct MilestoneRejected {
+    pub campaign_id: u32,
+    pub milestone: u32,
+}
+
+#[odra::event]
+/// Emitted when a backer is refunded.
+pub struct Refunded {
+    pub campaign_id: u32,
+    pub backer: Address,
+    pub amount: U512,
+}
+
+#[odra::module(
+    events = [
+        CampaignCreated,
+        Contributed,
+        MilestoneVoteStarted,
+        Voted,
+        MilestoneApproved,
+        MilestoneRejected,
+        Refunded
+    ],
+    errors = Error
+)]
+/// Raises CSPR for campaigns and releases it in tranches approved by the backers.
+pub struct Crowdfund {
+    /// How long backers can vote on a milestone.
+    voting_period: Var<u64>,
+    /// How long a funded campaign can go without a milestone vote before it's refunded.
+    inactivity_period: Var<u64>,
+    campaigns: Mapping<u32, Campaign>,
+    campaign_count: Var<u32>,
+    /// Contribution of each backer to each campaign.
+    contributions: Mapping<(u32, Address), U512>,
+    /// Whether a backer has voted on a milestone of a campaign.
+    voted: Mapping<(u32, u32, Address), bool>,
+}
+
+#[odra::module]
+impl Crowdfund {
+    /// Initializes the platform with the length of milestone votes and how long creators
+    /// have to start the next one.
+    pub fn init(&mut self, voting_period: u64, inactivity_period: u64) {
+        self.voting_period.set(voting_period);
+        self.inactivity_period.set(inactivity_period);
+    }
+
+    /**********
+     * TRANSACTIONS
+     **********/
+
+    /// Creates a campaign raising at least `goal` within `duration`, released in `tranches`.
+    /// Returns the ID of the campaign.
+    pub fn create_campaign(&mut self, goal: U512, duration: u64, tranches: Vec<u16>) -> u32 {
+        if goal.is_zero() {
+            self.env().revert(Error::ZeroAmount);
+        }
+        if tranches.is_empty()
+            || tranches.contains(&0)
+            || tranches.iter().map(|bps| *bps as u32).sum::<u32>() != MAX_BPS as u32
+        {
+            self.env().revert(Error::InvalidTranches);
+        }
+
+        let campaign_id = self.campaign_count.get_or_default();
+        let creator = self.env().caller();
+        let deadline = self.env().get_block_time() + duration;
+        self.campaigns.set(
+            &campaign_id,
+            Campaign {
+                creator,
+                goal,
+                deadline,
+                tranches,
+                raised: U512::zero(),
+                released: U512::zero(),
+                milestone: 0,
+                vote_end: None,
+                last_approval: deadline,
+                votes_for: U512::zero(),
+                votes_against: U512::zero(),
+                rejected: false,
+            },
+        );
+        self.campaign_count.set(campaign_id + 1);
+        self.env().emit_event(CampaignCreated {
+            campaign_id,
+            creator,
+            goal,
+            deadline,
+        });
+        campaign_id
+    }
+
+    /// Contributes the attached CSPR to a campaign that's still raising funds.
+    #[odra(payable)]
+    pub fn contribute(&mut self, campaign_id: u32) {
+        let mut campaign = self.campaign(campaign_id);
+        self.assert_state(&campaign, CampaignState::Funding);
+        let amount = self.env().attached_value();
+        if amount.is_zero() {
+            self.env().revert(Error::ZeroAmount);
+        }
+
+        let backer = self.env().caller();
+        campaign.raised += amount;
+        self.campaigns.set(&campaign_id, campaign);
+        self.contributions.add(&(campaign_id, backer), amount);
+        self.env().emit_event(Contributed {
+            campaign_id,
+            backer,
+            amount,
+        });
+    }
+
+    /// Puts the next milestone of a funded campaign to a vote of its backers.
+    pub fn start_milestone_vote(&mut self, campaign_id: u32) {
+        let mut campaign = self.campaign(campaign_id);
+        if campaign.creator != self.env().caller() {
+            self.env().revert(Error::NotCreator);
+        }
+        self.assert_state(&campaign, CampaignState::Active);
+
+        let vote_end = self.env().get_block_time() + self.voting_period.get_or_default();
+        campaign.vote_end = Some(vote_end);
+        campaign.votes_for = U512::zero();
+        campaign.votes_against = U512::zero();
+        let milestone = campaign.milestone;
+        self.campaigns.set(&campaign_id, campaign);
+        self.env().emit_event(MilestoneVoteStarted {
+            campaign_id,
+            milestone,
+            vote_end,
+        });
+    }
+
+    /// Votes on the running milestone vote with the weight of the caller's contribution.
+    pub fn vote(&mut self, campaign_id: u32, approve: bool) {
+        let mut campaign = self.campaign(campaign_id);
+        self.assert_state(&campaign, CampaignState::Voting);
+        if self.env().get_block_time() >= campaign.vote_end.unwrap_or_default() {
+            self.env().revert(Error::VotingClosed);
+        }
+        let backer = self.env().caller();
+        let weight = self.contributions.get_or_default(&(campaign_id, backer));
+        if weight.is_zero() {
+            self.env().revert(Error::NotBacker);
+        }
+        let key = (campaign_id, campaign.milestone, backer);
+        if self.voted.get_or_default(&key) {
+            self.env().revert(Error::AlreadyVoted);
+        }
+
+        self.voted.set(&key, true);
+        if approve {
+            campaign.votes_for += weight;
+        } else {
+            campaign.votes_against += weight;
+        }
+        let milestone = campaign.milestone;
+        self.campaigns.set(&campaign_id, campaign);
+        self.env().emit_event(Voted {
+            campaign_id,
+            milestone,
+            backer,
+            approve,
+            weight,
+        });
+    }
+
+    /// Counts the votes once the vote has ended. An approved milestone releases its tranche
+    /// to the creator, a rejected one lets the backers take back the remaining funds.
+    pub fn finalize_vote(&mut self, campaign_id: u32) {
+        let mut campaign = self.campaign(campaign_id);
+        self.assert_state(&campaign, CampaignState::Voting);
+        if self.env().get_block_time() < campaign.vote_end.unwrap_or_default() {
+            self.env().revert(Error::VoteNotOver);
+        }
+
+        campaign.vote_end = None;
+        let milestone = campaign.milestone;
+        // A tie, or a vote nobody took part in, rejects the milestone
+        if campaign.votes_for > campaign.votes_against {
+            let amount = if milestone as usize + 1 == campaign.tranches.len() {
+                // The last tranche takes whatever rounding left behind
+                campaign.raised - campaign.released
+            } else {
+                campaign.raised * campaign.tranches[milestone as usize] / MAX_BPS
+            };
+            campaign.released += amount;
+            campaign.milestone += 1;
+            campaign.last_approval = self.env().get_block_time();
+            let creator = campaign.creator;
+            self.campaigns.set(&campaign_id, campaign);
+            self.env().transfer_tokens(&creator, &amount);
+            self.env().emit_event(MilestoneApproved {
+                campaign_id,
+                milestone,
+                amount,
+            });
+        } else {
+            campaign.rejected = true;
+            self.campaigns.set(&campaign_id, campaign);
+            self.env().emit_event(MilestoneRejected {
+                campaign_id,
+                milestone,
+            });
+        }
+    }
+
+    /// Refunds the caller's part of the funds that haven't been released, once the
+    /// campaign has failed or a milestone has been rejected.
+    pub fn refund(&mut self, campaign_id: u32) {
+        let campaign = self.campaign(campaign_id);
+        let state = self.state_of(&campaign);
+        if state != CampaignState::Failed && state != CampaignState::Refunding {
+            self.env().revert(Error::InvalidState);
+        }
+        let backer = self.env().caller();
+        let contribution = self.contributions.get_or_default(&(campaign_id, backer));
+        if contribution.is_zero() {
+            self.env().revert(Error::NothingToRefund);
+        }
+
+        // Every backer gets the same fraction of their contribution back, whatever the order
+        let amount = contribution * (campaign.raised - campaign.released) / campaign.raised;
+        self.contributions.set(&(campaign_id, backer), U512::zero());
+        self.env().transfer_tokens(&backer, &amount);
+        self.env().emit_event(Refunded {
+            campaign_id,
+            backer,
+            amount,
+        });
+    }
+
+    /**********
+     * QUERIES
+     **********/
+
+    /// Returns a campaign.
+    pub fn get_campaign(&self, campaign_id: u32) -> Option<Campaign> {
+        self.campaigns.get(&campaign_id)
+    }
+
+    /// Returns the state of a campaign.
+    pub fn get_state(&self, campaign_id: u32) -> CampaignState {
+        self.state_of(&self.campaign(campaign_id))
+    }
+
+    /// Returns the contribution of a backer that hasn't been refunded.
+    pub fn contribution_of(&self, campaign_id: u32, backer: Address) -> U512 {
+        self.contributions.get_or_default(&(campaign_id, backer))
+    }
+
+    /// Returns the number of campaigns.
+    pub fn get_campaign_count(&self) -> u32 {
+        self.campaign_count.get_or_default()
+    }
+}
+
+impl Crowdfund {
+    fn campaign(&self, campaign_id: u32) -> Campaign {
+        self.campaigns
+            .get(&campaign_id)
+            .unwrap_or_revert_with(&self.env(), Error::CampaignNotFound)
+    }
+
+    // Funding and inactivity end with time, so the state is derived rather than stored
+    fn state_of(&self, campaign: &Campaign) -> CampaignState {
+        let now = self.env().get_block_time();
+        if now < campaign.deadline {
+            CampaignState::Funding
+        } else if campaign.raised < campaign.goal {
+            CampaignState::Failed
+        } else if campaign.rejected {
+            CampaignState::Refunding
+        } else if campaign.milestone as usize == campaign.tranches.len() {
+            CampaignState::Completed
+        } else if campaign.vote_end.is_some() {
+            CampaignState::Voting
+        } else if now >= campaign.last_approval + self.inactivity_period.get_or_default() {
+            CampaignState::Refunding
+        } else {
+            CampaignState::Active
+        }
+    }
+
+    fn assert_state(&self, campaign: &Campaign, state: CampaignState) {
+        if self.state_of(campaign) != state {
+            self.env().revert(Error::InvalidState);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use odra::host::{Deployer, HostEnv, HostRef};
+
+    const VOTING_PERIOD: u64 = 1_000;
+    const DURATION: u64 = 10_000;
+    const INACTIVITY_PERIOD: u64 = 5_000;
+
+    /// Deploys the platform and creates a campaign with a goal of 1,000 motes.
+    fn setup(env: &HostEnv, tranches: Vec<u16>) -> (CrowdfundHostRef, u32) {
+        let mut crowdfund = CrowdfundHostRef::deploy(
+            env,
+            CrowdfundInitArgs {
+                voting_period: VOTING_PERIOD,
+                inactivity_period: INACTIVITY_PERIOD,
+            },
+        );
+        let id = crowdfund.create_campaign(U512::from(1_000), DURATION, tranches);
+        (crowdfund, id)
+    }
+
+    fn contribute(
+        env: &HostEnv,
+        crowdfund: &mut CrowdfundHostRef,
+        id: u32,
+        account: usize,
+        amount: u64,
+    ) {
+        env.set_caller(env.get_account(account));
+        crowdfund.with_tokens(U512::from(amount)).contribute(id);
+    }
+
+    /// Runs a milestone vote in which each `(account, approve)` pair votes.
+    fn run_vote(env: &HostEnv, crowdfund: &mut CrowdfundHostRef, id: u32, votes: &[(usize, bool)]) {
+        env.set_caller(env.get_account(0));
+        crowdfund.start_milestone_vote(id);
+        for (account, approve) in votes {
+            env.set_caller(env.get_account(*account));
+            crowdfund.vote(id, *approve);
+        }
+        env.advance_block_time(VOTING_PERIOD);
+        crowdfund.finalize_vote(id);
+    }
+
+    #[test]
+    fn approved_milestones() {
+        let env = odra_test::env();
+        let (mut crowdfund, id) = setup(&env, vec![3_000, 7_000]);
+        let creator = env.get_account(0);
+        contribute(&env, &mut crowdfund, id, 1, 600);
+        contribute(&env, &mut crowdfund, id, 2, 600);
+        assert_eq!(crowdfund.get_state(id), CampaignState::Funding);
+        assert_eq!(
+            crowdfund.try_start_milestone_vote(id),
+            Err(Error::NotCreator.into())
+        );
+
+        // Once funded, each tranche is a share of everything raised, not of the goal
+        env.advance_block_time(DURATION);
+        assert_eq!(crowdfund.get_state(id), CampaignState::Active);
+        let balance = env.balance_of(&creator);
+        run_vote(&env, &mut crowdfund, id, &[(1, true)]);
+        assert_eq!(env.balance_of(&creator), balance + U512::from(360));
+        assert!(env.emitted_event(
+            crowdfund.address(),
+            &MilestoneApproved {
+                campaign_id: id,
+                milestone: 0,
+                amount: U512::from(360)
+            }
+        ));
+
+        run_vote(&env, &mut crowdfund, id, &[(1, true), (2, true)]);
+        assert_eq!(env.balance_of(&creator), balance + U512::from(1_200));
+        assert_eq!(crowdfund.get_state(id), CampaignState::Completed);
+        assert_eq!(env.balance_of(crowdfund.address()), U512::zero());
+    }
+
+    #[test]
+    fn failed_funding() {
+        let env = odra_test::env();
+        let (mut crowdfund, id) = setup(&env, vec![10_000]);
+        let alice = env.get_account(1);
+        contribute(&env, &mut crowdfund, id, 1, 300);
+        contribute(&env, &mut crowdfund, id, 1, 200);
+        assert_eq!(crowdfund.try_refund(id), Err(Error::InvalidState.into()));
+
+        // A campaign that misses its goal refunds every contribution in full
+        env.advance_block_time(DURATION);
+        assert_eq!(crowdfund.get_state(id), CampaignState::Failed);
+        assert_eq!(
+            crowdfund.with_tokens(U512::from(500)).try_contribute(id),
+            Err(Error::InvalidState.into())
+        );
+        let balance = env.balance_of(&alice);
+        crowdfund.refund(id);
+        assert_eq!(env.balance_of(&alice), balance + U512::from(500));
+        assert_eq!(crowdfund.try_refund(id), Err(Error::NothingToRefund.into()));
+    }
+
+    #[test]
+    fn rejected_milestone() {
+        let env = odra_test::env();
+        let (mut crowdfund, id) = setup(&env, vec![5_000, 5_000]);
+        let (alice, bob) = (env.get_account(1), env.get_account(2));
+        contribute(&env, &mut crowdfund, id, 1, 750);
+        contribute(&env, &mut crowdfund, id, 2, 250);
+        env.advance_block_time(DURATION);
+        run_vote(&env, &mut crowdfund, id, &[(1, true), (2, false)]);
+
+        // Only backers vote, once per milestone, and only while the vote runs
+        env.set_caller(env.get_account(0));
+        crowdfund.start_milestone_vote(id);
+        env.set_caller(env.get_account(3));
+        assert_eq!(crowdfund.try_vote(id, true), Err(Error::NotBacker.into()));
+        env.set_caller(alice);
+        crowdfund.vote(id, false);
+        assert_eq!(
+            crowdfund.try_vote(id, true),
+            Err(Error::AlreadyVoted.into())
+        );
+        assert_eq!(
+            crowdfund.try_finalize_vote(id),
+            Err(Error::VoteNotOver.into())
+        );
+        env.advance_block_time(VOTING_PERIOD);
+        env.set_caller(bob);
+        assert_eq!(
+            crowdfund.try_vote(id, true),
+            Err(Error::VotingClosed.into())
+        );
+        crowdfund.finalize_vote(id);
+        assert_eq!(crowdfund.get_state(id), CampaignState::Refunding);
+
+        // The half that wasn't released is split in proportion to the contributions
+        let balance = env.balance_of(&bob);
+        crowdfund.refund(id);
+        assert_eq!(env.balance_of(&bob), balance + U512::from(125));
+        env.set_caller(alice);
+        let balance = env.balance_of(&alice);
+        crowdfund.refund(id);
+        assert_eq!(env.balance_of(&alice), balance + U512::from(375));
+        assert_eq!(env.balance_of(crowdfund.address()), U512::zero());
+    }
+
+    #[test]
+    fn inactive_creator() {
+        let env = odra_test::env();
+        let (mut crowdfund, id) = setup(&env, vec![5_000, 5_000]);
+        let alice = env.get_account(1);
+        contribute(&env, &mut crowdfund, id, 1, 1_000);
+        env.advance_block_time(DURATION);
+        run_vote(&env, &mut crowdfund, id, &[(1, true)]);
+
+        // The inactivity period restarts with each approved milestone
+        env.advance_block_time(INACTIVITY_PERIOD - 1);
+        assert_eq!(crowdfund.get_state(id), CampaignState::Active);
+        env.advance_block_time(1);
+        assert_eq!(crowdfund.get_state(id), CampaignState::Refunding);
+        env.set_caller(env.get_account(0));
+        assert_eq!(
+            crowdfund.try_start_milestone_vote(id),
+            Err(Error::InvalidState.into())
+        );
+
+        env.set_caller(alice);
+        let balance = env.balance_of(&alice);
+        crowdfund.refund(id);
+        assert_eq!(env.balance_of(&alice), balance + U512::from(500));
+        assert_eq!(env.balance_of(crowdfund.address()), U512::zero());
+    }
+}
@@ -0,0 +1,40 @@
+//! Deploys the Donation contract, donates to it and withdraws the donations.
+use donation::DonationHostRef;
+use odra::casper_types::U512;
+use odra::host::{Deployer, HostEnv, HostRef, NoArgs};
+use tutorial_utils::gas::{self, with_gas};
+use tutorial_utils::manifest::record_deployment;
+use tutorial_utils::network;
+
+fn main() {
+    network::configure_from_args();
+    let env = odra_casper_livenet_env::env();
+    let owner = env.caller();
+    let donor = env.get_account(1);
+
+    let mut donation = deploy_contract(&env);
+    println!("Donation address: {}", donation.address().to_string());
+
+    env.set_caller(donor);
+    // casper contract may return a result or not, so deserialization may fail and it's better to use `try_` methods
+    let _ = with_gas(&env, gas::TRANSFER, || {
+        donation
+            .with_tokens(U512::from(5_000_000_000u64))
+            .try_donate()
+    });
+    println!("Balance after donation: {}", donation.get_balance());
+
+    env.set_caller(owner);
+    let _ = with_gas(&env, gas::TRANSFER, || donation.try_withdraw());
+    println!("Balance after withdrawal: {}", donation.get_balance());
+}
+
+/// Deploys a Donation contract.
+pub fn deploy_contract(env: &HostEnv) -> DonationHostRef {
+    let donation = with_gas(env, gas::DEPLOY, || {
+        DonationHostRef::try_deploy(env, NoArgs)
+    })
+    .expect("Should deploy the contract");
+    record_deployment("Donation", &donation, None, &[]);
+    donation
+}
@@ -0,0 +1,55 @@
+//! Deploys the Election contract, casts a vote from two accounts and prints the results.
+use odra::host::{Deployer, HostEnv};
+use project::{ElectionHostRef, ElectionInitArgs};
+use tutorial_utils::gas::{self, with_gas};
+use tutorial_utils::manifest::record_deployment;
+use tutorial_utils::network;
+
+const CANDIDATES: [&str; 2] = ["Alice", "Bob"];
+const VOTING_PERIOD: u64 = 60 * 60 * 1000; // one hour
+
+fn main() {
+    network::configure_from_args();
+    let env = odra_casper_livenet_env::env();
+    // The deployer cannot vote, the voters are the accounts configured with `ODRA_CASPER_LIVENET_KEY_<n>`
+    let voters = [env.get_account(1), env.get_account(2)];
+
+    let mut election = deploy_contract(&env);
+
+    for (voter, candidate) in voters.iter().zip(CANDIDATES) {
+        env.set_caller(*voter);
+        // casper contract may return a result or not, so deserialization may fail and it's better to use `try_` methods
+        let _ = with_gas(&env, gas::CALL, || election.try_vote(candidate.to_string()));
+    }
+
+    for candidate in CANDIDATES {
+        println!(
+            "{}: {} vote(s)",
+            candidate,
+            election.get_candidate_votes(candidate.to_string())
+        );
+    }
+}
+
+/// Deploys an Election contract accepting votes for the next hour.
+pub fn deploy_contract(env: &HostEnv) -> ElectionHostRef {
+    let end_block = env.block_time() + VOTING_PERIOD;
+    let init_args = || ElectionInitArgs {
+        end_block,
+        candidates: CANDIDATES.iter().map(|c| c.to_string()).collect(),
+    };
+    let election = with_gas(env, gas::DEPLOY, || {
+        ElectionHostRef::try_deploy(env, init_args())
+    })
+    .expect("Should deploy the contract");
+    record_deployment(
+        "Election",
+        &election,
+        None,
+        &[
+            ("end_block", end_block.to_string()),
+            ("candidates", CANDIDATES.join(",")),
+        ],
+    );
+    election
+}
@@ -0,0 +1,69 @@
+//! Deploys the Escrow contract and walks a CSPR escrow from opening to settlement.
+use escrow::escrow::{EscrowHostRef, EscrowInitArgs};
+use odra::casper_types::U512;
+use odra::host::{Deployer, HostEnv, HostRef};
+use tutorial_utils::gas::{self, with_gas};
+use tutorial_utils::manifest::record_deployment;
+use tutorial_utils::network;
+
+const DEPOSIT: u64 = 5_000_000_000; // 5 CSPR
+const DEADLINE: u64 = 24 * 60 * 60 * 1000; // one day
+
+fn main() {
+    network::configure_from_args();
+    let env = odra_casper_livenet_env::env();
+    // The opener, arbiter, depositor and beneficiary must be four different accounts
+    let arbiter = env.get_account(1);
+    let depositor = env.get_account(2);
+    let beneficiary = env.get_account(3);
+
+    let mut escrow = deploy_contract(&env);
+    println!("Escrow address: {}", escrow.address().to_string());
+
+    let deadline = env.block_time() + DEADLINE;
+    // casper contract may return a result or not, so deserialization may fail and it's better to use `try_` methods
+    let _ = with_gas(&env, gas::CALL, || {
+        escrow.try_open_escrow(
+            arbiter,
+            depositor,
+            beneficiary,
+            U512::from(DEPOSIT),
+            deadline,
+            vec![],
+            0,
+            0,
+        )
+    });
+    let escrow_id = escrow.escrow_count() - 1;
+    println!(
+        "Opened escrow #{}: {:?}",
+        escrow_id,
+        escrow.get_state(escrow_id)
+    );
+
+    env.set_caller(depositor);
+    let _ = with_gas(&env, gas::TRANSFER, || {
+        escrow
+            .with_tokens(U512::from(DEPOSIT))
+            .try_deposit(escrow_id)
+    });
+    println!("After deposit: {:?}", escrow.get_state(escrow_id));
+
+    env.set_caller(beneficiary);
+    let _ = with_gas(&env, gas::CALL, || escrow.try_provided_good(escrow_id));
+    println!("After delivery: {:?}", escrow.get_state(escrow_id));
+
+    env.set_caller(arbiter);
+    let _ = with_gas(&env, gas::TRANSFER, || escrow.try_settle(escrow_id));
+    println!("After settlement: {:?}", escrow.get_state(escrow_id));
+}
+
+/// Deploys an Escrow contract paid in CSPR.
+pub fn deploy_contract(env: &HostEnv) -> EscrowHostRef {
+    let escrow = with_gas(env, gas::DEPLOY, || {
+        EscrowHostRef::try_deploy(env, EscrowInitArgs { token: None })
+    })
+    .expect("Should deploy the contract");
+    record_deployment("Escrow", &escrow, None, &[("token", "None".to_string())]);
+    escrow
+}
@@ -1,9 +1,10 @@
 //extern crate std;
 //use std::collections::HashSet;
 
-use odra::casper_types::U512;
+use odra::casper_types::{U256, U512};
 use odra::prelude::*;
-use odra::{Address, Var};
+use odra::{Address, ContractRef, Mapping, Var};
+use odra_modules::cep18_token::Cep18ContractRef;
 
 #[odra::odra_error]
 pub enum Error {
@@ -14,7 +15,30 @@ pub enum Error {
     IllegalAccounts = 4,
     FundsAlreadyDeposited = 5,
     IncorrectDepositAmount = 6,
+    InvalidDeadline = 7,
+    DeadlineNotReached = 8,
+    DeadlinePassed = 9,
+    EscrowNotFound = 10,
+    InvalidMilestones = 11,
+    MilestoneNotFound = 12,
+    MilestoneAlreadySettled = 13,
+    InvalidFee = 14,
+    NotBeneficiary = 15,
+    EscrowClosed = 16,
+    NotAParty = 17,
+    NoPendingCancellation = 18,
+    CannotAcceptOwnProposal = 19,
+    DisputeWindowClosed = 20,
+    DisputeWindowOpen = 21,
+    DisputeAlreadyRaised = 22,
+    DisputeInProgress = 23,
+    NoDisputeRaised = 24,
+    InvalidResolution = 25,
+    AmountTooLarge = 26,
 }
+
+// Fees are expressed in basis points, 10_000 being the whole amount
+const MAX_FEE_BPS: u16 = 10_000;
 #[odra::odra_type]
 pub enum Account {
     Depositor,
@@ -22,53 +46,183 @@ pub enum Account {
     Arbiter,
 }
 
+#[odra::odra_type]
+pub enum EscrowState {
+    AwaitingDeposit,
+    AwaitingDelivery,
+    AwaitingSettlement,
+    Closed,
+}
+
+#[odra::odra_type]
+pub struct EscrowData {
+    pub arbiter: Address,
+    pub depositor: Address,
+    pub beneficiary: Address,
+    pub deposit_amount: U512,
+    pub balance: U512,
+    pub good_provided: bool,
+    pub deadline: u64,
+    pub milestones: Vec<U512>,
+    pub milestones_settled: Vec<bool>,
+    pub arbiter_fee_bps: u16,
+    pub closed: bool,
+    pub cancel_proposed_by: Option<Address>,
+    pub dispute_window: u64,
+    pub delivered_at: Option<u64>,
+    pub dispute_reason: Option<String>,
+}
+
+impl EscrowData {
+    // Sum of the milestones that have already been paid out to the beneficiary
+    fn released(&self) -> U512 {
+        self.milestones
+            .iter()
+            .zip(self.milestones_settled.iter())
+            .filter(|(_, settled)| **settled)
+            .fold(U512::zero(), |total, (amount, _)| total + *amount)
+    }
+
+    // Holds exactly the part of the deposit that hasn't been released yet
+    fn is_funded(&self) -> bool {
+        !self.balance.is_zero() && self.balance == self.deposit_amount - self.released()
+    }
+}
+
+#[odra::event]
+pub struct EscrowOpened {
+    pub escrow_id: u64,
+    pub arbiter: Address,
+    pub depositor: Address,
+    pub beneficiary: Address,
+    pub deposit_amount: U512,
+}
+
 #[odra::event]
 pub struct DepositMade {
+    pub escrow_id: u64,
     pub depositor: Address,
     pub amount: U512,
 }
 
 #[odra::event]
 pub struct GoodProvided {
+    escrow_id: u64,
     beneficiary: Address,
 }
 
 #[odra::event]
 pub struct EscrowSettled {
+    pub escrow_id: u64,
     pub depositor: Address,
     pub beneficiary: Address,
     pub amount_paid: U512,
 }
 
+#[odra::event]
+pub struct MilestoneSettled {
+    pub escrow_id: u64,
+    pub index: u32,
+    pub beneficiary: Address,
+    pub amount_paid: U512,
+}
+
+#[odra::event]
+pub struct ArbiterFeePaid {
+    pub escrow_id: u64,
+    pub arbiter: Address,
+    pub amount: U512,
+}
+
 #[odra::event]
 pub struct EscrowRejected {
+    pub escrow_id: u64,
+    pub depositor: Address,
+    pub beneficiary: Address,
+    pub amount_returned: U512,
+}
+
+#[odra::event]
+pub struct DisputeRaised {
+    pub escrow_id: u64,
+    pub depositor: Address,
+    pub reason: String,
+}
+
+#[odra::event]
+pub struct DisputeResolved {
+    pub escrow_id: u64,
+    pub in_favor_of: Account,
+}
+
+#[odra::event]
+pub struct CancellationProposed {
+    pub escrow_id: u64,
+    pub proposed_by: Address,
+}
+
+#[odra::event]
+pub struct EscrowCancelled {
+    pub escrow_id: u64,
+    pub depositor: Address,
+    pub beneficiary: Address,
+    pub amount_returned: U512,
+}
+
+#[odra::event]
+pub struct EscrowExpired {
+    pub escrow_id: u64,
     pub depositor: Address,
     pub beneficiary: Address,
     pub amount_returned: U512,
 }
 
 #[odra::module(
-    events = [DepositMade, GoodProvided,EscrowSettled,EscrowRejected],
+    events = [EscrowOpened, DepositMade, GoodProvided, EscrowSettled, MilestoneSettled, ArbiterFeePaid, EscrowRejected, DisputeRaised, DisputeResolved, CancellationProposed, EscrowCancelled, EscrowExpired],
     errors = Error
 )]
 pub struct Escrow {
-    arbiter: Var<Address>,
-    depositor: Var<Address>,
-    beneficiary: Var<Address>,
-    balance: Var<U512>,
-    good_provided: Var<bool>,
-    deposit_amount: Var<U512>,
+    escrows: Mapping<u64, EscrowData>,
+    escrow_count: Var<u64>,
+    token: Var<Option<Address>>,
 }
 
 #[odra::module]
 impl Escrow {
-    pub fn init(
+    // With a CEP-18 token address every escrow is paid in that token instead of CSPR.
+    pub fn init(&mut self, token: Option<Address>) {
+        self.token.set(token);
+    }
+
+    pub fn open_escrow(
         &mut self,
         arbiter: Address,
         depositor: Address,
         beneficiary: Address,
         deposit_amount: U512,
-    ) {
+        deadline: u64,
+        milestones: Vec<U512>,
+        arbiter_fee_bps: u16,
+        dispute_window: u64,
+    ) -> u64 {
+        if deadline <= self.env().get_block_time() {
+            self.env().revert(Error::InvalidDeadline);
+        }
+        if arbiter_fee_bps > MAX_FEE_BPS {
+            self.env().revert(Error::InvalidFee);
+        }
+        // Without explicit milestones the whole deposit is released at once
+        let milestones = if milestones.is_empty() {
+            vec![deposit_amount]
+        } else {
+            milestones
+        };
+        let total = milestones
+            .iter()
+            .fold(U512::zero(), |total, amount| total + *amount);
+        if total != deposit_amount || milestones.contains(&U512::zero()) {
+            self.env().revert(Error::InvalidMilestones);
+        }
         let all_accounts = vec![self.env().caller(), arbiter, depositor, beneficiary];
         for i in 0..all_accounts.len() {
             for j in (i + 1)..all_accounts.len() {
@@ -77,80 +231,406 @@ impl Escrow {
                 }
             }
         }
-        self.arbiter.set(arbiter);
-        self.depositor.set(depositor);
-        self.beneficiary.set(beneficiary);
-        self.good_provided.set(false);
-        self.deposit_amount.set(deposit_amount);
-        self.balance.set(0.into());
+        let escrow_id = self.escrow_count.get_or_default();
+        self.escrows.set(
+            &escrow_id,
+            EscrowData {
+                arbiter,
+                depositor,
+                beneficiary,
+                deposit_amount,
+                balance: U512::zero(),
+                good_provided: false,
+                deadline,
+                milestones_settled: vec![false; milestones.len()],
+                milestones,
+                arbiter_fee_bps,
+                closed: false,
+                cancel_proposed_by: None,
+                dispute_window,
+                delivered_at: None,
+                dispute_reason: None,
+            },
+        );
+        self.escrow_count.set(escrow_id + 1);
+        self.env().emit_event(EscrowOpened {
+            escrow_id,
+            arbiter,
+            depositor,
+            beneficiary,
+            deposit_amount,
+        });
+        escrow_id
     }
 
     #[odra(payable)]
-    pub fn deposit(&mut self) {
-        self.assert_caller(Account::Depositor);
-        if self.balance.get().unwrap() != U512::from(0) {
+    pub fn deposit(&mut self, escrow_id: u64) {
+        let mut escrow = self.get_open_escrow(escrow_id);
+        self.assert_caller(&escrow, Account::Depositor);
+        if escrow.balance != U512::from(0) || escrow.milestones_settled.contains(&true) {
             self.env().revert(Error::FundsAlreadyDeposited);
         }
-        if self.env().attached_value() != self.deposit_amount.get().unwrap() {
-            self.env().revert(Error::IncorrectDepositAmount);
+        match self.token.get_or_default() {
+            // The depositor has to approve the escrow to spend the deposit amount
+            Some(token) => {
+                if !self.env().attached_value().is_zero() {
+                    self.env().revert(Error::IncorrectDepositAmount);
+                }
+                Cep18ContractRef::new(self.env(), token).transfer_from(
+                    &escrow.depositor,
+                    &self.env().self_address(),
+                    &self.to_token_amount(escrow.deposit_amount),
+                );
+            }
+            None => {
+                if self.env().attached_value() != escrow.deposit_amount {
+                    self.env().revert(Error::IncorrectDepositAmount);
+                }
+            }
         }
-        self.balance.add(self.env().attached_value());
+        escrow.balance += escrow.deposit_amount;
+        self.escrows.set(&escrow_id, escrow.clone());
         self.env().emit_event(DepositMade {
+            escrow_id,
             depositor: self.env().caller(),
-            amount: self.env().attached_value(),
+            amount: escrow.deposit_amount,
         });
     }
 
-    pub fn provided_good(&mut self) {
-        self.assert_caller(Account::Beneficiary);
-        self.good_provided.set(true);
+    pub fn provided_good(&mut self, escrow_id: u64) {
+        let mut escrow = self.get_open_escrow(escrow_id);
+        self.assert_caller(&escrow, Account::Beneficiary);
+        escrow.good_provided = true;
+        escrow.delivered_at = Some(self.env().get_block_time());
+        self.escrows.set(&escrow_id, escrow);
         self.env().emit_event(GoodProvided {
+            escrow_id,
             beneficiary: self.env().caller(),
         });
     }
 
-    pub fn settle(&mut self) {
-        self.assert_caller(Account::Arbiter);
-        if !self.good_provided.get().unwrap() {
+    pub fn settle(&mut self, escrow_id: u64) {
+        let mut escrow = self.get_open_escrow(escrow_id);
+        self.assert_caller(&escrow, Account::Arbiter);
+        if self.env().get_block_time() > escrow.deadline {
+            self.env().revert(Error::DeadlinePassed);
+        }
+        if !escrow.good_provided {
             self.env().revert(Error::GoodNotProvided);
         }
-        if self.balance.get().unwrap() != self.deposit_amount.get().unwrap() {
+        if !escrow.is_funded() {
+            self.env().revert(Error::FundsNotDeposited);
+        }
+        if escrow.dispute_reason.is_some() {
+            self.env().revert(Error::DisputeInProgress);
+        }
+        if self.dispute_window_open(&escrow) {
+            self.env().revert(Error::DisputeWindowOpen);
+        }
+        self.release_to_beneficiary(escrow_id, escrow);
+    }
+
+    pub fn settle_milestone(&mut self, escrow_id: u64, index: u32) {
+        let mut escrow = self.get_open_escrow(escrow_id);
+        self.assert_caller(&escrow, Account::Arbiter);
+        if self.env().get_block_time() > escrow.deadline {
+            self.env().revert(Error::DeadlinePassed);
+        }
+        let amount = escrow
+            .milestones
+            .get(index as usize)
+            .copied()
+            .unwrap_or_revert_with(&self.env(), Error::MilestoneNotFound);
+        if escrow.milestones_settled[index as usize] {
+            self.env().revert(Error::MilestoneAlreadySettled);
+        }
+        if !escrow.is_funded() {
             self.env().revert(Error::FundsNotDeposited);
         }
-        let contract_balance = self.balance.get_or_default();
-        self.balance.set(0.into());
-        self.good_provided.set(false);
-        self.env()
-            .transfer_tokens(&self.beneficiary.get().unwrap(), &contract_balance);
+        if escrow.dispute_reason.is_some() {
+            self.env().revert(Error::DisputeInProgress);
+        }
+        // Milestones can pay for progress before the good is provided, but once it is,
+        // the depositor gets the same window to object as for `settle`.
+        if self.dispute_window_open(&escrow) {
+            self.env().revert(Error::DisputeWindowOpen);
+        }
+        escrow.balance -= amount;
+        escrow.milestones_settled[index as usize] = true;
+        escrow.closed = escrow.balance.is_zero();
+        self.escrows.set(&escrow_id, escrow.clone());
+        let amount_paid = self.pay_arbiter_fee(escrow_id, &escrow, amount);
+        self.transfer_funds(&escrow.beneficiary, &amount_paid);
+        self.env().emit_event(MilestoneSettled {
+            escrow_id,
+            index,
+            beneficiary: escrow.beneficiary,
+            amount_paid,
+        });
+    }
+
+    pub fn reject(&mut self, escrow_id: u64) {
+        let escrow = self.get_open_escrow(escrow_id);
+        self.assert_caller(&escrow, Account::Arbiter);
+        self.refund_depositor(escrow_id, escrow);
+    }
+
+    // Once the good is provided, the depositor has `dispute_window` to object
+    // before the arbiter is allowed to settle.
+    pub fn raise_dispute(&mut self, escrow_id: u64, reason: String) {
+        let mut escrow = self.get_open_escrow(escrow_id);
+        self.assert_caller(&escrow, Account::Depositor);
+        if !escrow.good_provided {
+            self.env().revert(Error::GoodNotProvided);
+        }
+        if escrow.dispute_reason.is_some() {
+            self.env().revert(Error::DisputeAlreadyRaised);
+        }
+        if !self.dispute_window_open(&escrow) {
+            self.env().revert(Error::DisputeWindowClosed);
+        }
+        escrow.dispute_reason = Some(reason.clone());
+        self.escrows.set(&escrow_id, escrow.clone());
+        self.env().emit_event(DisputeRaised {
+            escrow_id,
+            depositor: escrow.depositor,
+            reason,
+        });
+    }
+
+    pub fn resolve_dispute(&mut self, escrow_id: u64, in_favor_of: Account) {
+        let mut escrow = self.get_open_escrow(escrow_id);
+        self.assert_caller(&escrow, Account::Arbiter);
+        if escrow.dispute_reason.is_none() {
+            self.env().revert(Error::NoDisputeRaised);
+        }
+        escrow.dispute_reason = None;
+        match in_favor_of {
+            Account::Beneficiary => {
+                if !escrow.is_funded() {
+                    self.env().revert(Error::FundsNotDeposited);
+                }
+                self.release_to_beneficiary(escrow_id, escrow);
+            }
+            Account::Depositor => self.refund_depositor(escrow_id, escrow),
+            Account::Arbiter => self.env().revert(Error::InvalidResolution),
+        }
+        self.env().emit_event(DisputeResolved {
+            escrow_id,
+            in_favor_of,
+        });
+    }
+
+    // Either the depositor or the beneficiary can propose to call the deal off.
+    pub fn propose_cancel(&mut self, escrow_id: u64) {
+        let mut escrow = self.get_open_escrow(escrow_id);
+        let caller = self.env().caller();
+        if caller != escrow.depositor && caller != escrow.beneficiary {
+            self.env().revert(Error::NotAParty);
+        }
+        escrow.cancel_proposed_by = Some(caller);
+        self.escrows.set(&escrow_id, escrow);
+        self.env().emit_event(CancellationProposed {
+            escrow_id,
+            proposed_by: caller,
+        });
+    }
+
+    // Once the counterparty agrees, the deposit goes back to the depositor
+    // without the arbiter being involved.
+    pub fn accept_cancel(&mut self, escrow_id: u64) {
+        let mut escrow = self.get_open_escrow(escrow_id);
+        let caller = self.env().caller();
+        if caller != escrow.depositor && caller != escrow.beneficiary {
+            self.env().revert(Error::NotAParty);
+        }
+        match escrow.cancel_proposed_by {
+            None => self.env().revert(Error::NoPendingCancellation),
+            Some(proposer) if proposer == caller => {
+                self.env().revert(Error::CannotAcceptOwnProposal)
+            }
+            Some(_) => {}
+        }
+        let escrow_balance = escrow.balance;
+        escrow.balance = U512::zero();
+        escrow.good_provided = false;
+        escrow.cancel_proposed_by = None;
+        escrow.closed = true;
+        self.escrows.set(&escrow_id, escrow.clone());
+        if !escrow_balance.is_zero() {
+            self.transfer_funds(&escrow.depositor, &escrow_balance);
+        }
+        self.env().emit_event(EscrowCancelled {
+            escrow_id,
+            depositor: escrow.depositor,
+            beneficiary: escrow.beneficiary,
+            amount_returned: escrow_balance,
+        });
+    }
+
+    // Anyone can return the deposit to the depositor once the deadline has passed
+    // without the arbiter settling the escrow. A raised dispute is only ever ended by
+    // the arbiter, even after the deadline, so it blocks the refund.
+    pub fn reclaim_expired(&mut self, escrow_id: u64) {
+        let mut escrow = self.get_open_escrow(escrow_id);
+        if self.env().get_block_time() <= escrow.deadline {
+            self.env().revert(Error::DeadlineNotReached);
+        }
+        if escrow.dispute_reason.is_some() {
+            self.env().revert(Error::DisputeInProgress);
+        }
+        let escrow_balance = escrow.balance;
+        if escrow_balance == U512::from(0) {
+            self.env().revert(Error::FundsNotDeposited);
+        }
+        escrow.balance = U512::zero();
+        escrow.good_provided = false;
+        escrow.closed = true;
+        self.escrows.set(&escrow_id, escrow.clone());
+        self.transfer_funds(&escrow.depositor, &escrow_balance);
+        self.env().emit_event(EscrowExpired {
+            escrow_id,
+            depositor: escrow.depositor,
+            beneficiary: escrow.beneficiary,
+            amount_returned: escrow_balance,
+        });
+    }
+
+    pub fn token(&self) -> Option<Address> {
+        self.token.get_or_default()
+    }
+
+    pub fn get_escrow(&self, escrow_id: u64) -> Option<EscrowData> {
+        self.escrows.get(&escrow_id)
+    }
+
+    pub fn get_state(&self, escrow_id: u64) -> EscrowState {
+        let escrow = self.get_or_revert(escrow_id);
+        if escrow.closed {
+            EscrowState::Closed
+        } else if escrow.balance.is_zero() {
+            EscrowState::AwaitingDeposit
+        } else if !escrow.good_provided {
+            EscrowState::AwaitingDelivery
+        } else {
+            EscrowState::AwaitingSettlement
+        }
+    }
+
+    pub fn get_milestones(&self, escrow_id: u64) -> Vec<(U512, bool)> {
+        let escrow = self.get_or_revert(escrow_id);
+        escrow
+            .milestones
+            .into_iter()
+            .zip(escrow.milestones_settled)
+            .collect()
+    }
+
+    pub fn escrow_count(&self) -> u64 {
+        self.escrow_count.get_or_default()
+    }
+
+    // Settling the escrow releases every milestone that is still outstanding
+    fn release_to_beneficiary(&mut self, escrow_id: u64, mut escrow: EscrowData) {
+        let escrow_balance = escrow.balance;
+        escrow.balance = U512::zero();
+        escrow.good_provided = false;
+        escrow.milestones_settled = vec![true; escrow.milestones.len()];
+        escrow.closed = true;
+        self.escrows.set(&escrow_id, escrow.clone());
+        let amount_paid = self.pay_arbiter_fee(escrow_id, &escrow, escrow_balance);
+        self.transfer_funds(&escrow.beneficiary, &amount_paid);
         self.env().emit_event(EscrowSettled {
-            depositor: self.depositor.get().unwrap(),
-            beneficiary: self.beneficiary.get().unwrap(),
-            amount_paid: contract_balance,
+            escrow_id,
+            depositor: escrow.depositor,
+            beneficiary: escrow.beneficiary,
+            amount_paid,
         });
     }
 
-    pub fn reject(&mut self) {
-        self.assert_caller(Account::Arbiter);
-        let contract_balance = self.balance.get_or_default();
-        self.balance.set(0.into());
-        self.good_provided.set(false);
-        self.env()
-            .transfer_tokens(&self.depositor.get().unwrap(), &contract_balance);
+    fn refund_depositor(&mut self, escrow_id: u64, mut escrow: EscrowData) {
+        let escrow_balance = escrow.balance;
+        escrow.balance = U512::zero();
+        escrow.good_provided = false;
+        escrow.closed = true;
+        self.escrows.set(&escrow_id, escrow.clone());
+        // The arbiter is paid for their work even if the escrow is rejected
+        let amount_returned = self.pay_arbiter_fee(escrow_id, &escrow, escrow_balance);
+        self.transfer_funds(&escrow.depositor, &amount_returned);
         self.env().emit_event(EscrowRejected {
-            depositor: self.depositor.get().unwrap(),
-            beneficiary: self.beneficiary.get().unwrap(),
-            amount_returned: contract_balance,
+            escrow_id,
+            depositor: escrow.depositor,
+            beneficiary: escrow.beneficiary,
+            amount_returned,
+        });
+    }
+
+    fn dispute_window_open(&self, escrow: &EscrowData) -> bool {
+        match escrow.delivered_at {
+            Some(delivered_at) => {
+                self.env().get_block_time() < delivered_at + escrow.dispute_window
+            }
+            None => false,
+        }
+    }
+
+    // Transfers the arbiter's cut of `amount` and returns what is left of it
+    fn pay_arbiter_fee(&self, escrow_id: u64, escrow: &EscrowData, amount: U512) -> U512 {
+        let fee = amount * escrow.arbiter_fee_bps / MAX_FEE_BPS;
+        if fee.is_zero() {
+            return amount;
+        }
+        self.transfer_funds(&escrow.arbiter, &fee);
+        self.env().emit_event(ArbiterFeePaid {
+            escrow_id,
+            arbiter: escrow.arbiter,
+            amount: fee,
         });
+        amount - fee
+    }
+
+    // Pays out either CSPR or the configured CEP-18 token
+    fn transfer_funds(&self, to: &Address, amount: &U512) {
+        match self.token.get_or_default() {
+            Some(token) => {
+                Cep18ContractRef::new(self.env(), token)
+                    .transfer(to, &self.to_token_amount(*amount));
+            }
+            None => self.env().transfer_tokens(to, amount),
+        }
+    }
+
+    fn to_token_amount(&self, amount: U512) -> U256 {
+        if amount.bits() > 256 {
+            self.env().revert(Error::AmountTooLarge);
+        }
+        let mut bytes = [0u8; 64];
+        amount.to_little_endian(&mut bytes);
+        U256::from_little_endian(&bytes[..32])
+    }
+
+    fn get_or_revert(&self, escrow_id: u64) -> EscrowData {
+        self.escrows
+            .get(&escrow_id)
+            .unwrap_or_revert_with(&self.env(), Error::EscrowNotFound)
     }
 
-    fn assert_caller(&self, account: Account) {
-        let target_account = match account {
-            Account::Depositor => self.depositor.get().unwrap(),
-            Account::Arbiter => self.arbiter.get().unwrap(),
-            Account::Beneficiary => self.beneficiary.get().unwrap(),
+    fn get_open_escrow(&self, escrow_id: u64) -> EscrowData {
+        let escrow = self.get_or_revert(escrow_id);
+        if escrow.closed {
+            self.env().revert(Error::EscrowClosed);
+        }
+        escrow
+    }
+
+    fn assert_caller(&self, escrow: &EscrowData, account: Account) {
+        let (target_account, error) = match account {
+            Account::Depositor => (escrow.depositor, Error::NotDepositor),
+            Account::Arbiter => (escrow.arbiter, Error::NotArbiter),
+            Account::Beneficiary => (escrow.beneficiary, Error::NotBeneficiary),
         };
         if target_account != self.env().caller() {
-            self.env().revert(Error::NotDepositor);
+            self.env().revert(error);
         }
     }
 }
@@ -159,6 +639,9 @@ impl Escrow {
 mod tests {
     use super::*;
     use odra::host::{Deployer, HostRef};
+    use odra_modules::cep18_token::{Cep18HostRef, Cep18InitArgs};
+
+    const DEADLINE: u64 = 7 * 24 * 60 * 60 * 1000;
 
     #[test]
     fn successful_escrow() {
@@ -167,14 +650,19 @@ mod tests {
         let depositor = env.get_account(2);
         let beneficiary = env.get_account(3);
         let deposit_amount = U512::from(10_000_000_000u64);
-        let init_args = EscrowInitArgs {
-            arbiter: arbiter,
-            depositor: depositor,
-            beneficiary: beneficiary,
-            deposit_amount: deposit_amount,
-        };
-        // Account 0 Deploys Contract
-        let mut contract = EscrowHostRef::deploy(&env, init_args);
+        // Account 0 Deploys Contract and opens an escrow
+        let mut contract = EscrowHostRef::deploy(&env, EscrowInitArgs { token: None });
+        let escrow_id = contract.open_escrow(
+            arbiter,
+            depositor,
+            beneficiary,
+            deposit_amount,
+            DEADLINE,
+            vec![],
+            0,
+            0,
+        );
+        assert_eq!(escrow_id, 0);
 
         // Get initial balances
         let depositor_initial_balance = env.balance_of(&depositor);
@@ -184,11 +672,12 @@ mod tests {
         env.set_caller(depositor);
         contract
             .with_tokens(deposit_amount)
-            .try_deposit()
+            .try_deposit(escrow_id)
             .expect("Deposit should be successful");
         env.emitted_event(
             contract.address(),
             &DepositMade {
+                escrow_id,
                 depositor: depositor,
                 amount: deposit_amount,
             },
@@ -197,11 +686,12 @@ mod tests {
         // Beneficiary provides good
         env.set_caller(beneficiary);
         contract
-            .try_provided_good()
+            .try_provided_good(escrow_id)
             .expect("Beneficiary should be able to provide good");
         env.emitted_event(
             contract.address(),
             &GoodProvided {
+                escrow_id,
                 beneficiary: beneficiary,
             },
         );
@@ -209,11 +699,12 @@ mod tests {
         // Arbiter settles escrow
         env.set_caller(arbiter);
         contract
-            .try_settle()
+            .try_settle(escrow_id)
             .expect("Arbiter should be able to settle escrow");
         env.emitted_event(
             contract.address(),
             &EscrowSettled {
+                escrow_id,
                 depositor: depositor,
                 beneficiary: beneficiary,
                 amount_paid: deposit_amount,
@@ -231,4 +722,767 @@ mod tests {
             depositor_initial_balance - deposit_amount
         );
     }
+
+    #[test]
+    fn expired_escrow() {
+        let env = odra_test::env();
+        let arbiter = env.get_account(1);
+        let depositor = env.get_account(2);
+        let beneficiary = env.get_account(3);
+        let deposit_amount = U512::from(10_000_000_000u64);
+        let mut contract = EscrowHostRef::deploy(&env, EscrowInitArgs { token: None });
+        let escrow_id = contract.open_escrow(
+            arbiter,
+            depositor,
+            beneficiary,
+            deposit_amount,
+            DEADLINE,
+            vec![],
+            0,
+            0,
+        );
+        let depositor_initial_balance = env.balance_of(&depositor);
+
+        env.set_caller(depositor);
+        contract
+            .with_tokens(deposit_amount)
+            .try_deposit(escrow_id)
+            .expect("Deposit should be successful");
+
+        // Nobody can reclaim the deposit before the deadline
+        env.set_caller(beneficiary);
+        assert_eq!(
+            contract.try_reclaim_expired(escrow_id),
+            Err(Error::DeadlineNotReached.into())
+        );
+
+        // The arbiter can no longer settle after the deadline
+        env.advance_block_time(DEADLINE + 1);
+        env.set_caller(arbiter);
+        assert_eq!(
+            contract.try_settle(escrow_id),
+            Err(Error::DeadlinePassed.into())
+        );
+
+        // Anyone can return the deposit to the depositor
+        env.set_caller(beneficiary);
+        contract
+            .try_reclaim_expired(escrow_id)
+            .expect("Expired escrow should be reclaimable");
+        assert!(env.emitted_event(
+            contract.address(),
+            &EscrowExpired {
+                escrow_id,
+                depositor,
+                beneficiary,
+                amount_returned: deposit_amount,
+            },
+        ));
+        assert_eq!(env.balance_of(&depositor), depositor_initial_balance);
+    }
+
+    #[test]
+    fn deadline_in_the_past() {
+        let env = odra_test::env();
+        env.advance_block_time(DEADLINE);
+        let mut contract = EscrowHostRef::deploy(&env, EscrowInitArgs { token: None });
+        assert_eq!(
+            contract.try_open_escrow(
+                env.get_account(1),
+                env.get_account(2),
+                env.get_account(3),
+                U512::from(10_000_000_000u64),
+                DEADLINE,
+                vec![],
+                0,
+                0,
+            ),
+            Err(Error::InvalidDeadline.into())
+        );
+    }
+
+    #[test]
+    fn escrows_are_independent() {
+        let env = odra_test::env();
+        let arbiter = env.get_account(1);
+        let depositor = env.get_account(2);
+        let beneficiary = env.get_account(3);
+        let other_beneficiary = env.get_account(4);
+        let first_amount = U512::from(10_000_000_000u64);
+        let second_amount = U512::from(5_000_000_000u64);
+        let mut contract = EscrowHostRef::deploy(&env, EscrowInitArgs { token: None });
+        let first = contract.open_escrow(
+            arbiter,
+            depositor,
+            beneficiary,
+            first_amount,
+            DEADLINE,
+            vec![],
+            0,
+            0,
+        );
+        let second = contract.open_escrow(
+            arbiter,
+            depositor,
+            other_beneficiary,
+            second_amount,
+            DEADLINE,
+            vec![],
+            0,
+            0,
+        );
+        assert_eq!((first, second), (0, 1));
+        assert_eq!(contract.escrow_count(), 2);
+
+        env.set_caller(depositor);
+        contract.with_tokens(first_amount).deposit(first);
+        contract.with_tokens(second_amount).deposit(second);
+
+        // Only the beneficiary of the second escrow can mark it as provided
+        env.set_caller(beneficiary);
+        assert_eq!(
+            contract.try_provided_good(second),
+            Err(Error::NotBeneficiary.into())
+        );
+        contract.provided_good(first);
+
+        // Rejecting the second escrow leaves the first one untouched
+        let depositor_balance = env.balance_of(&depositor);
+        env.set_caller(arbiter);
+        contract.reject(second);
+        assert_eq!(
+            env.balance_of(&depositor),
+            depositor_balance + second_amount
+        );
+        assert_eq!(contract.get_escrow(first).unwrap().balance, first_amount);
+        assert!(contract.get_escrow(first).unwrap().good_provided);
+
+        contract.settle(first);
+        assert_eq!(contract.get_escrow(first).unwrap().balance, U512::zero());
+        assert_eq!(contract.try_settle(2), Err(Error::EscrowNotFound.into()));
+        assert_eq!(contract.get_escrow(2), None);
+    }
+
+    #[test]
+    fn milestone_escrow() {
+        let env = odra_test::env();
+        let arbiter = env.get_account(1);
+        let depositor = env.get_account(2);
+        let beneficiary = env.get_account(3);
+        let milestones = vec![
+            U512::from(2_000_000_000u64),
+            U512::from(3_000_000_000u64),
+            U512::from(5_000_000_000u64),
+        ];
+        let deposit_amount = U512::from(10_000_000_000u64);
+        let mut contract = EscrowHostRef::deploy(&env, EscrowInitArgs { token: None });
+
+        // Milestones have to add up to the deposit amount
+        assert_eq!(
+            contract.try_open_escrow(
+                arbiter,
+                depositor,
+                beneficiary,
+                deposit_amount + 1,
+                DEADLINE,
+                milestones.clone(),
+                0,
+                0,
+            ),
+            Err(Error::InvalidMilestones.into())
+        );
+        let escrow_id = contract.open_escrow(
+            arbiter,
+            depositor,
+            beneficiary,
+            deposit_amount,
+            DEADLINE,
+            milestones.clone(),
+            0,
+            0,
+        );
+
+        // Nothing can be released before the deposit is made
+        env.set_caller(arbiter);
+        assert_eq!(
+            contract.try_settle_milestone(escrow_id, 0),
+            Err(Error::FundsNotDeposited.into())
+        );
+        env.set_caller(depositor);
+        contract.with_tokens(deposit_amount).deposit(escrow_id);
+
+        // The arbiter releases the second milestone first
+        let beneficiary_initial_balance = env.balance_of(&beneficiary);
+        env.set_caller(arbiter);
+        contract.settle_milestone(escrow_id, 1);
+        assert!(env.emitted_event(
+            contract.address(),
+            &MilestoneSettled {
+                escrow_id,
+                index: 1,
+                beneficiary,
+                amount_paid: milestones[1],
+            },
+        ));
+        assert_eq!(
+            env.balance_of(&beneficiary),
+            beneficiary_initial_balance + milestones[1]
+        );
+        assert_eq!(
+            contract.try_settle_milestone(escrow_id, 1),
+            Err(Error::MilestoneAlreadySettled.into())
+        );
+        assert_eq!(
+            contract.try_settle_milestone(escrow_id, 3),
+            Err(Error::MilestoneNotFound.into())
+        );
+        assert_eq!(
+            contract.get_escrow(escrow_id).unwrap().balance,
+            milestones[0] + milestones[2]
+        );
+
+        // Settling the escrow pays out the remaining milestones
+        env.set_caller(beneficiary);
+        contract.provided_good(escrow_id);
+        env.set_caller(arbiter);
+        contract.settle(escrow_id);
+        assert!(env.emitted_event(
+            contract.address(),
+            &EscrowSettled {
+                escrow_id,
+                depositor,
+                beneficiary,
+                amount_paid: milestones[0] + milestones[2],
+            },
+        ));
+        assert_eq!(
+            env.balance_of(&beneficiary),
+            beneficiary_initial_balance + deposit_amount
+        );
+        assert_eq!(
+            contract.get_milestones(escrow_id),
+            milestones
+                .into_iter()
+                .map(|m| (m, true))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn arbiter_fee() {
+        let env = odra_test::env();
+        let arbiter = env.get_account(1);
+        let depositor = env.get_account(2);
+        let beneficiary = env.get_account(3);
+        let deposit_amount = U512::from(10_000_000_000u64);
+        let mut contract = EscrowHostRef::deploy(&env, EscrowInitArgs { token: None });
+
+        // The fee can't exceed 100%
+        assert_eq!(
+            contract.try_open_escrow(
+                arbiter,
+                depositor,
+                beneficiary,
+                deposit_amount,
+                DEADLINE,
+                vec![],
+                10_001,
+                0,
+            ),
+            Err(Error::InvalidFee.into())
+        );
+
+        // 2.5% fee on a settled escrow
+        let settled = contract.open_escrow(
+            arbiter,
+            depositor,
+            beneficiary,
+            deposit_amount,
+            DEADLINE,
+            vec![],
+            250,
+            0,
+        );
+        // 1% fee on a rejected escrow
+        let rejected = contract.open_escrow(
+            arbiter,
+            depositor,
+            beneficiary,
+            deposit_amount,
+            DEADLINE,
+            vec![],
+            100,
+            0,
+        );
+        env.set_caller(depositor);
+        contract.with_tokens(deposit_amount).deposit(settled);
+        contract.with_tokens(deposit_amount).deposit(rejected);
+        env.set_caller(beneficiary);
+        contract.provided_good(settled);
+
+        let arbiter_balance = env.balance_of(&arbiter);
+        let beneficiary_balance = env.balance_of(&beneficiary);
+        let depositor_balance = env.balance_of(&depositor);
+        env.set_caller(arbiter);
+        contract.settle(settled);
+        let settle_fee = U512::from(250_000_000u64);
+        assert!(env.emitted_event(
+            contract.address(),
+            &ArbiterFeePaid {
+                escrow_id: settled,
+                arbiter,
+                amount: settle_fee,
+            },
+        ));
+        assert_eq!(
+            env.balance_of(&beneficiary),
+            beneficiary_balance + deposit_amount - settle_fee
+        );
+
+        // The fee is taken from the deposit before it is returned
+        contract.reject(rejected);
+        let reject_fee = U512::from(100_000_000u64);
+        assert!(env.emitted_event(
+            contract.address(),
+            &EscrowRejected {
+                escrow_id: rejected,
+                depositor,
+                beneficiary,
+                amount_returned: deposit_amount - reject_fee,
+            },
+        ));
+        assert_eq!(
+            env.balance_of(&depositor),
+            depositor_balance + deposit_amount - reject_fee
+        );
+        assert_eq!(
+            env.balance_of(&arbiter),
+            arbiter_balance + settle_fee + reject_fee
+        );
+    }
+
+    #[test]
+    fn escrow_state_and_roles() {
+        let env = odra_test::env();
+        let arbiter = env.get_account(1);
+        let depositor = env.get_account(2);
+        let beneficiary = env.get_account(3);
+        let deposit_amount = U512::from(10_000_000_000u64);
+        let mut contract = EscrowHostRef::deploy(&env, EscrowInitArgs { token: None });
+        let escrow_id = contract.open_escrow(
+            arbiter,
+            depositor,
+            beneficiary,
+            deposit_amount,
+            DEADLINE,
+            vec![],
+            0,
+            0,
+        );
+        assert_eq!(contract.get_state(escrow_id), EscrowState::AwaitingDeposit);
+
+        // Every role reports its own error
+        env.set_caller(beneficiary);
+        assert_eq!(
+            contract.with_tokens(deposit_amount).try_deposit(escrow_id),
+            Err(Error::NotDepositor.into())
+        );
+        assert_eq!(
+            contract.try_settle(escrow_id),
+            Err(Error::NotArbiter.into())
+        );
+        env.set_caller(depositor);
+        assert_eq!(
+            contract.try_provided_good(escrow_id),
+            Err(Error::NotBeneficiary.into())
+        );
+
+        contract.with_tokens(deposit_amount).deposit(escrow_id);
+        assert_eq!(contract.get_state(escrow_id), EscrowState::AwaitingDelivery);
+        env.set_caller(beneficiary);
+        contract.provided_good(escrow_id);
+        assert_eq!(
+            contract.get_state(escrow_id),
+            EscrowState::AwaitingSettlement
+        );
+        env.set_caller(arbiter);
+        contract.settle(escrow_id);
+        assert_eq!(contract.get_state(escrow_id), EscrowState::Closed);
+
+        // A closed escrow can't be reused
+        env.set_caller(depositor);
+        assert_eq!(
+            contract.with_tokens(deposit_amount).try_deposit(escrow_id),
+            Err(Error::EscrowClosed.into())
+        );
+    }
+
+    #[test]
+    fn mutual_cancellation() {
+        let env = odra_test::env();
+        let arbiter = env.get_account(1);
+        let depositor = env.get_account(2);
+        let beneficiary = env.get_account(3);
+        let deposit_amount = U512::from(10_000_000_000u64);
+        let mut contract = EscrowHostRef::deploy(&env, EscrowInitArgs { token: None });
+        let escrow_id = contract.open_escrow(
+            arbiter,
+            depositor,
+            beneficiary,
+            deposit_amount,
+            DEADLINE,
+            vec![],
+            100,
+            0,
+        );
+        env.set_caller(depositor);
+        contract.with_tokens(deposit_amount).deposit(escrow_id);
+        let depositor_balance = env.balance_of(&depositor);
+        let arbiter_balance = env.balance_of(&arbiter);
+
+        // Nothing to accept yet
+        env.set_caller(beneficiary);
+        assert_eq!(
+            contract.try_accept_cancel(escrow_id),
+            Err(Error::NoPendingCancellation.into())
+        );
+
+        // The arbiter is not a party to the cancellation
+        env.set_caller(arbiter);
+        assert_eq!(
+            contract.try_propose_cancel(escrow_id),
+            Err(Error::NotAParty.into())
+        );
+
+        // A proposal can't be accepted by the party that made it
+        env.set_caller(depositor);
+        contract.propose_cancel(escrow_id);
+        assert!(env.emitted_event(
+            contract.address(),
+            &CancellationProposed {
+                escrow_id,
+                proposed_by: depositor,
+            },
+        ));
+        assert_eq!(
+            contract.try_accept_cancel(escrow_id),
+            Err(Error::CannotAcceptOwnProposal.into())
+        );
+        env.set_caller(arbiter);
+        assert_eq!(
+            contract.try_accept_cancel(escrow_id),
+            Err(Error::NotAParty.into())
+        );
+
+        // The counterparty agrees and the full deposit is refunded
+        env.set_caller(beneficiary);
+        contract.accept_cancel(escrow_id);
+        assert!(env.emitted_event(
+            contract.address(),
+            &EscrowCancelled {
+                escrow_id,
+                depositor,
+                beneficiary,
+                amount_returned: deposit_amount,
+            },
+        ));
+        assert_eq!(
+            env.balance_of(&depositor),
+            depositor_balance + deposit_amount
+        );
+        assert_eq!(env.balance_of(&arbiter), arbiter_balance);
+        assert_eq!(contract.get_state(escrow_id), EscrowState::Closed);
+    }
+
+    #[test]
+    fn dispute_window() {
+        let env = odra_test::env();
+        let arbiter = env.get_account(1);
+        let depositor = env.get_account(2);
+        let beneficiary = env.get_account(3);
+        let deposit_amount = U512::from(10_000_000_000u64);
+        let window = 24 * 60 * 60 * 1000;
+        let mut contract = EscrowHostRef::deploy(&env, EscrowInitArgs { token: None });
+        let open = |contract: &mut EscrowHostRef| {
+            contract.open_escrow(
+                arbiter,
+                depositor,
+                beneficiary,
+                deposit_amount,
+                DEADLINE,
+                vec![],
+                0,
+                window,
+            )
+        };
+        let disputed = open(&mut contract);
+        let undisputed = open(&mut contract);
+        env.set_caller(depositor);
+        contract.with_tokens(deposit_amount).deposit(disputed);
+        contract.with_tokens(deposit_amount).deposit(undisputed);
+
+        // There is nothing to dispute before the good is provided
+        assert_eq!(
+            contract.try_raise_dispute(disputed, "Not delivered".to_string()),
+            Err(Error::GoodNotProvided.into())
+        );
+        env.set_caller(beneficiary);
+        contract.provided_good(disputed);
+        contract.provided_good(undisputed);
+
+        // The arbiter has to wait for the window to pass
+        env.set_caller(arbiter);
+        assert_eq!(
+            contract.try_settle(undisputed),
+            Err(Error::DisputeWindowOpen.into())
+        );
+
+        env.set_caller(depositor);
+        contract.raise_dispute(disputed, "Wrong item".to_string());
+        assert!(env.emitted_event(
+            contract.address(),
+            &DisputeRaised {
+                escrow_id: disputed,
+                depositor,
+                reason: "Wrong item".to_string(),
+            },
+        ));
+        assert_eq!(
+            contract.try_raise_dispute(disputed, "Still wrong".to_string()),
+            Err(Error::DisputeAlreadyRaised.into())
+        );
+
+        env.advance_block_time(window);
+        assert_eq!(
+            contract.try_raise_dispute(undisputed, "Too late".to_string()),
+            Err(Error::DisputeWindowClosed.into())
+        );
+
+        // The undisputed escrow settles normally, the disputed one is blocked
+        env.set_caller(arbiter);
+        contract.settle(undisputed);
+        assert_eq!(
+            contract.try_settle(disputed),
+            Err(Error::DisputeInProgress.into())
+        );
+        assert_eq!(
+            contract.try_resolve_dispute(undisputed, Account::Depositor),
+            Err(Error::EscrowClosed.into())
+        );
+        assert_eq!(
+            contract.try_resolve_dispute(disputed, Account::Arbiter),
+            Err(Error::InvalidResolution.into())
+        );
+
+        // The arbiter sides with the depositor, who gets the deposit back
+        let depositor_balance = env.balance_of(&depositor);
+        contract.resolve_dispute(disputed, Account::Depositor);
+        assert!(env.emitted_event(
+            contract.address(),
+            &DisputeResolved {
+                escrow_id: disputed,
+                in_favor_of: Account::Depositor,
+            },
+        ));
+        assert_eq!(
+            env.balance_of(&depositor),
+            depositor_balance + deposit_amount
+        );
+        assert_eq!(contract.get_state(disputed), EscrowState::Closed);
+    }
+
+    #[test]
+    fn milestones_wait_for_the_dispute_window() {
+        let env = odra_test::env();
+        let arbiter = env.get_account(1);
+        let depositor = env.get_account(2);
+        let beneficiary = env.get_account(3);
+        let milestones = vec![U512::from(4_000_000_000u64), U512::from(6_000_000_000u64)];
+        let deposit_amount = U512::from(10_000_000_000u64);
+        let window = 24 * 60 * 60 * 1000;
+        let mut contract = EscrowHostRef::deploy(&env, EscrowInitArgs { token: None });
+        let escrow_id = contract.open_escrow(
+            arbiter,
+            depositor,
+            beneficiary,
+            deposit_amount,
+            DEADLINE,
+            milestones,
+            0,
+            window,
+        );
+        env.set_caller(depositor);
+        contract.with_tokens(deposit_amount).deposit(escrow_id);
+
+        // Before delivery, milestones pay for progress
+        env.set_caller(arbiter);
+        contract.settle_milestone(escrow_id, 0);
+
+        // Once the good is provided, the depositor can object to the rest first
+        env.set_caller(beneficiary);
+        contract.provided_good(escrow_id);
+        env.set_caller(arbiter);
+        assert_eq!(
+            contract.try_settle_milestone(escrow_id, 1),
+            Err(Error::DisputeWindowOpen.into())
+        );
+        env.set_caller(depositor);
+        contract.raise_dispute(escrow_id, "Half finished".to_string());
+        env.advance_block_time(window);
+        env.set_caller(arbiter);
+        assert_eq!(
+            contract.try_settle_milestone(escrow_id, 1),
+            Err(Error::DisputeInProgress.into())
+        );
+    }
+
+    #[test]
+    fn expired_dispute_waits_for_the_arbiter() {
+        let env = odra_test::env();
+        let arbiter = env.get_account(1);
+        let depositor = env.get_account(2);
+        let beneficiary = env.get_account(3);
+        let deposit_amount = U512::from(10_000_000_000u64);
+        let mut contract = EscrowHostRef::deploy(&env, EscrowInitArgs { token: None });
+        let escrow_id = contract.open_escrow(
+            arbiter,
+            depositor,
+            beneficiary,
+            deposit_amount,
+            DEADLINE,
+            vec![],
+            0,
+            DEADLINE,
+        );
+        env.set_caller(depositor);
+        contract.with_tokens(deposit_amount).deposit(escrow_id);
+        env.set_caller(beneficiary);
+        contract.provided_good(escrow_id);
+        env.set_caller(depositor);
+        contract.raise_dispute(escrow_id, "Wrong item".to_string());
+
+        // The deadline doesn't end the dispute, so nobody can take the deposit back
+        env.advance_block_time(DEADLINE + 1);
+        assert_eq!(
+            contract.try_reclaim_expired(escrow_id),
+            Err(Error::DisputeInProgress.into())
+        );
+
+        // The arbiter can still resolve it
+        env.set_caller(arbiter);
+        let beneficiary_balance = env.balance_of(&beneficiary);
+        contract.resolve_dispute(escrow_id, Account::Beneficiary);
+        assert_eq!(
+            env.balance_of(&beneficiary),
+            beneficiary_balance + deposit_amount
+        );
+        assert_eq!(contract.get_state(escrow_id), EscrowState::Closed);
+    }
+
+    #[test]
+    fn token_escrow() {
+        let env = odra_test::env();
+        let arbiter = env.get_account(1);
+        let depositor = env.get_account(2);
+        let beneficiary = env.get_account(3);
+        let deposit_amount = U512::from(400);
+        let mut token = Cep18HostRef::deploy(
+            &env,
+            Cep18InitArgs {
+                symbol: "TKN".to_string(),
+                name: "Token".to_string(),
+                decimals: 9,
+                initial_supply: U256::from(1_000),
+                admin_list: vec![],
+                minter_list: vec![],
+                modality: None,
+            },
+        );
+        token.transfer(&depositor, &U256::from(1_000));
+        let mut contract = EscrowHostRef::deploy(
+            &env,
+            EscrowInitArgs {
+                token: Some(*token.address()),
+            },
+        );
+        assert_eq!(contract.token(), Some(*token.address()));
+        let settled = contract.open_escrow(
+            arbiter,
+            depositor,
+            beneficiary,
+            deposit_amount,
+            DEADLINE,
+            vec![],
+            250,
+            0,
+        );
+        let rejected = contract.open_escrow(
+            arbiter,
+            depositor,
+            beneficiary,
+            deposit_amount,
+            DEADLINE,
+            vec![],
+            0,
+            0,
+        );
+
+        // Deposits are pulled from the depositor's token allowance, not attached CSPR
+        env.set_caller(depositor);
+        assert_eq!(
+            contract.with_tokens(deposit_amount).try_deposit(settled),
+            Err(Error::IncorrectDepositAmount.into())
+        );
+        token.approve(contract.address(), &U256::from(800));
+        contract.deposit(settled);
+        contract.deposit(rejected);
+        assert_eq!(token.balance_of(&depositor), U256::from(200));
+        assert_eq!(token.balance_of(contract.address()), U256::from(800));
+
+        env.set_caller(beneficiary);
+        contract.provided_good(settled);
+
+        // Settlement and rejection move tokens, including the arbiter fee
+        env.set_caller(arbiter);
+        contract.settle(settled);
+        contract.reject(rejected);
+        assert_eq!(token.balance_of(&beneficiary), U256::from(390));
+        assert_eq!(token.balance_of(&arbiter), U256::from(10));
+        assert_eq!(token.balance_of(&depositor), U256::from(600));
+        assert_eq!(token.balance_of(contract.address()), U256::zero());
+    }
+
+    #[test]
+    fn schema_includes_events_and_errors() {
+        use odra::schema::{SchemaErrors, SchemaEvents};
+
+        let events: Vec<String> = Escrow::schema_events()
+            .into_iter()
+            .map(|event| event.name)
+            .collect();
+        for event in [
+            "EscrowOpened",
+            "DepositMade",
+            "GoodProvided",
+            "EscrowSettled",
+            "MilestoneSettled",
+            "ArbiterFeePaid",
+            "EscrowRejected",
+            "DisputeRaised",
+            "DisputeResolved",
+            "CancellationProposed",
+            "EscrowCancelled",
+            "EscrowExpired",
+        ] {
+            assert!(events.contains(&event.to_string()), "missing {}", event);
+        }
+
+        let errors = Escrow::schema_errors();
+        assert!(errors
+            .iter()
+            .any(|error| error.name == "NotDepositor" && error.discriminant == 0));
+        assert!(errors
+            .iter()
+            .any(|error| error.name == "AmountTooLarge" && error.discriminant == 26));
+    }
 }
@@ -3,3 +3,4 @@
 extern crate alloc;
 
 pub mod escrow;
+pub mod nft_escrow;
@@ -0,0 +1,362 @@
+use odra::args::Maybe;
+use odra::casper_types::U512;
+use odra::prelude::*;
+use odra::{Address, ContractRef, Mapping, Var};
+use odra_modules::cep78::token::Cep78ContractRef;
+
+#[odra::odra_error]
+pub enum Error {
+    NotDepositor = 0,
+    NotBeneficiary = 1,
+    NotArbiter = 2,
+    IllegalAccounts = 3,
+    EscrowNotFound = 4,
+    PaymentAlreadyDeposited = 5,
+    IncorrectPaymentAmount = 6,
+    NftAlreadyDeposited = 7,
+    PaymentNotDeposited = 8,
+    NftNotDeposited = 9,
+    EscrowClosed = 10,
+}
+
+// The beneficiary sells a CEP-78 token to the depositor for a fixed CSPR price.
+#[odra::odra_type]
+pub struct NftEscrowData {
+    pub arbiter: Address,
+    pub depositor: Address,
+    pub beneficiary: Address,
+    pub nft_contract: Address,
+    pub token_id: u64,
+    pub price: U512,
+    pub payment_deposited: bool,
+    pub nft_deposited: bool,
+    pub closed: bool,
+}
+
+#[odra::event]
+pub struct NftEscrowOpened {
+    pub escrow_id: u64,
+    pub nft_contract: Address,
+    pub token_id: u64,
+    pub price: U512,
+}
+
+#[odra::event]
+pub struct PaymentDeposited {
+    pub escrow_id: u64,
+    pub depositor: Address,
+    pub amount: U512,
+}
+
+#[odra::event]
+pub struct NftDeposited {
+    pub escrow_id: u64,
+    pub beneficiary: Address,
+    pub token_id: u64,
+}
+
+#[odra::event]
+pub struct NftEscrowSettled {
+    pub escrow_id: u64,
+    pub depositor: Address,
+    pub beneficiary: Address,
+    pub token_id: u64,
+    pub amount_paid: U512,
+}
+
+#[odra::event]
+pub struct NftEscrowRejected {
+    pub escrow_id: u64,
+    pub depositor: Address,
+    pub beneficiary: Address,
+}
+
+#[odra::module(
+    events = [NftEscrowOpened, PaymentDeposited, NftDeposited, NftEscrowSettled, NftEscrowRejected],
+    errors = Error
+)]
+pub struct NftEscrow {
+    escrows: Mapping<u64, NftEscrowData>,
+    escrow_count: Var<u64>,
+}
+
+#[odra::module]
+impl NftEscrow {
+    pub fn open_escrow(
+        &mut self,
+        arbiter: Address,
+        depositor: Address,
+        beneficiary: Address,
+        nft_contract: Address,
+        token_id: u64,
+        price: U512,
+    ) -> u64 {
+        let all_accounts = vec![self.env().caller(), arbiter, depositor, beneficiary];
+        for i in 0..all_accounts.len() {
+            for j in (i + 1)..all_accounts.len() {
+                if all_accounts[i] == all_accounts[j] {
+                    self.env().revert(Error::IllegalAccounts);
+                }
+            }
+        }
+        let escrow_id = self.escrow_count.get_or_default();
+        self.escrows.set(
+            &escrow_id,
+            NftEscrowData {
+                arbiter,
+                depositor,
+                beneficiary,
+                nft_contract,
+                token_id,
+                price,
+                payment_deposited: false,
+                nft_deposited: false,
+                closed: false,
+            },
+        );
+        self.escrow_count.set(escrow_id + 1);
+        self.env().emit_event(NftEscrowOpened {
+            escrow_id,
+            nft_contract,
+            token_id,
+            price,
+        });
+        escrow_id
+    }
+
+    #[odra(payable)]
+    pub fn deposit(&mut self, escrow_id: u64) {
+        let mut escrow = self.get_open_escrow(escrow_id);
+        if self.env().caller() != escrow.depositor {
+            self.env().revert(Error::NotDepositor);
+        }
+        if escrow.payment_deposited {
+            self.env().revert(Error::PaymentAlreadyDeposited);
+        }
+        if self.env().attached_value() != escrow.price {
+            self.env().revert(Error::IncorrectPaymentAmount);
+        }
+        escrow.payment_deposited = true;
+        self.escrows.set(&escrow_id, escrow.clone());
+        self.env().emit_event(PaymentDeposited {
+            escrow_id,
+            depositor: escrow.depositor,
+            amount: escrow.price,
+        });
+    }
+
+    // The beneficiary has to approve this contract for the token beforehand,
+    // so it can pull the NFT into custody.
+    pub fn deposit_nft(&mut self, escrow_id: u64) {
+        let mut escrow = self.get_open_escrow(escrow_id);
+        if self.env().caller() != escrow.beneficiary {
+            self.env().revert(Error::NotBeneficiary);
+        }
+        if escrow.nft_deposited {
+            self.env().revert(Error::NftAlreadyDeposited);
+        }
+        escrow.nft_deposited = true;
+        self.escrows.set(&escrow_id, escrow.clone());
+        Cep78ContractRef::new(self.env(), escrow.nft_contract).transfer(
+            Maybe::Some(escrow.token_id),
+            Maybe::None,
+            escrow.beneficiary,
+            self.env().self_address(),
+        );
+        self.env().emit_event(NftDeposited {
+            escrow_id,
+            beneficiary: escrow.beneficiary,
+            token_id: escrow.token_id,
+        });
+    }
+
+    // Swaps the NFT for the payment in a single call, so neither party can end up
+    // with both assets.
+    pub fn settle(&mut self, escrow_id: u64) {
+        let mut escrow = self.get_open_escrow(escrow_id);
+        self.assert_arbiter(&escrow);
+        if !escrow.payment_deposited {
+            self.env().revert(Error::PaymentNotDeposited);
+        }
+        if !escrow.nft_deposited {
+            self.env().revert(Error::NftNotDeposited);
+        }
+        escrow.closed = true;
+        self.escrows.set(&escrow_id, escrow.clone());
+        Cep78ContractRef::new(self.env(), escrow.nft_contract).transfer(
+            Maybe::Some(escrow.token_id),
+            Maybe::None,
+            self.env().self_address(),
+            escrow.depositor,
+        );
+        self.env()
+            .transfer_tokens(&escrow.beneficiary, &escrow.price);
+        self.env().emit_event(NftEscrowSettled {
+            escrow_id,
+            depositor: escrow.depositor,
+            beneficiary: escrow.beneficiary,
+            token_id: escrow.token_id,
+            amount_paid: escrow.price,
+        });
+    }
+
+    // Returns whatever has been deposited to its original owner.
+    pub fn reject(&mut self, escrow_id: u64) {
+        let mut escrow = self.get_open_escrow(escrow_id);
+        self.assert_arbiter(&escrow);
+        escrow.closed = true;
+        self.escrows.set(&escrow_id, escrow.clone());
+        if escrow.nft_deposited {
+            Cep78ContractRef::new(self.env(), escrow.nft_contract).transfer(
+                Maybe::Some(escrow.token_id),
+                Maybe::None,
+                self.env().self_address(),
+                escrow.beneficiary,
+            );
+        }
+        if escrow.payment_deposited {
+            self.env().transfer_tokens(&escrow.depositor, &escrow.price);
+        }
+        self.env().emit_event(NftEscrowRejected {
+            escrow_id,
+            depositor: escrow.depositor,
+            beneficiary: escrow.beneficiary,
+        });
+    }
+
+    pub fn get_escrow(&self, escrow_id: u64) -> Option<NftEscrowData> {
+        self.escrows.get(&escrow_id)
+    }
+
+    fn get_open_escrow(&self, escrow_id: u64) -> NftEscrowData {
+        let escrow = self
+            .escrows
+            .get(&escrow_id)
+            .unwrap_or_revert_with(&self.env(), Error::EscrowNotFound);
+        if escrow.closed {
+            self.env().revert(Error::EscrowClosed);
+        }
+        escrow
+    }
+
+    fn assert_arbiter(&self, escrow: &NftEscrowData) {
+        if self.env().caller() != escrow.arbiter {
+            self.env().revert(Error::NotArbiter);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use odra::host::{Deployer, HostRef, NoArgs};
+    use odra_modules::cep78::modalities::{
+        MetadataMutability, NFTIdentifierMode, NFTKind, NFTMetadataKind, OwnershipMode,
+    };
+    use odra_modules::cep78::token::Cep78HostRef;
+    use odra_modules::cep78::utils::InitArgsBuilder;
+
+    const CEP78_METADATA: &str = r#"{
+        "name": "John Doe",
+        "token_uri": "https://www.barfoo.com",
+        "checksum": "940bffb3f2bba35f84313aa26da09ece3ad47045c6a1292c2bbd2df4ab1a55fb"
+    }"#;
+
+    fn deploy_nft(env: &odra::host::HostEnv) -> Cep78HostRef {
+        let init_args = InitArgsBuilder::default()
+            .collection_name("Escrowed Collection".to_string())
+            .collection_symbol("ESC".to_string())
+            .total_token_supply(10)
+            .ownership_mode(OwnershipMode::Transferable)
+            .nft_metadata_kind(NFTMetadataKind::CEP78)
+            .identifier_mode(NFTIdentifierMode::Ordinal)
+            .nft_kind(NFTKind::Digital)
+            .metadata_mutability(MetadataMutability::Immutable)
+            .receipt_name("escrow_receipt".to_string())
+            .build();
+        Cep78HostRef::deploy(env, init_args)
+    }
+
+    #[test]
+    fn nft_swap() {
+        let env = odra_test::env();
+        let arbiter = env.get_account(1);
+        let depositor = env.get_account(2);
+        let beneficiary = env.get_account(3);
+        let price = U512::from(10_000_000_000u64);
+
+        let mut nft = deploy_nft(&env);
+        nft.mint(beneficiary, CEP78_METADATA.to_string(), Maybe::None);
+        let mut contract = NftEscrowHostRef::deploy(&env, NoArgs);
+        let escrow_id =
+            contract.open_escrow(arbiter, depositor, beneficiary, *nft.address(), 0, price);
+
+        // The arbiter can't settle before both sides have deposited
+        env.set_caller(arbiter);
+        assert_eq!(
+            contract.try_settle(escrow_id),
+            Err(Error::PaymentNotDeposited.into())
+        );
+
+        env.set_caller(depositor);
+        contract.with_tokens(price).deposit(escrow_id);
+        env.set_caller(beneficiary);
+        nft.approve(*contract.address(), Maybe::Some(0), Maybe::None);
+        contract.deposit_nft(escrow_id);
+        assert_eq!(
+            nft.owner_of(Maybe::Some(0), Maybe::None),
+            *contract.address()
+        );
+
+        let beneficiary_balance = env.balance_of(&beneficiary);
+        env.set_caller(arbiter);
+        contract.settle(escrow_id);
+        assert!(env.emitted_event(
+            contract.address(),
+            &NftEscrowSettled {
+                escrow_id,
+                depositor,
+                beneficiary,
+                token_id: 0,
+                amount_paid: price,
+            },
+        ));
+        assert_eq!(nft.owner_of(Maybe::Some(0), Maybe::None), depositor);
+        assert_eq!(env.balance_of(&beneficiary), beneficiary_balance + price);
+        assert_eq!(
+            contract.try_reject(escrow_id),
+            Err(Error::EscrowClosed.into())
+        );
+    }
+
+    #[test]
+    fn rejected_nft_swap() {
+        let env = odra_test::env();
+        let arbiter = env.get_account(1);
+        let depositor = env.get_account(2);
+        let beneficiary = env.get_account(3);
+        let price = U512::from(10_000_000_000u64);
+
+        let mut nft = deploy_nft(&env);
+        nft.mint(beneficiary, CEP78_METADATA.to_string(), Maybe::None);
+        let mut contract = NftEscrowHostRef::deploy(&env, NoArgs);
+        let escrow_id =
+            contract.open_escrow(arbiter, depositor, beneficiary, *nft.address(), 0, price);
+
+        // Only the beneficiary can deposit the token
+        env.set_caller(depositor);
+        assert_eq!(
+            contract.try_deposit_nft(escrow_id),
+            Err(Error::NotBeneficiary.into())
+        );
+        env.set_caller(beneficiary);
+        nft.approve(*contract.address(), Maybe::Some(0), Maybe::None);
+        contract.deposit_nft(escrow_id);
+
+        // Rejecting returns the token to the beneficiary
+        env.set_caller(arbiter);
+        contract.reject(escrow_id);
+        assert_eq!(nft.owner_of(Maybe::Some(0), Maybe::None), beneficiary);
+        assert!(contract.get_escrow(escrow_id).unwrap().closed);
+    }
+}
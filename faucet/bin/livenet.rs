@@ -0,0 +1,137 @@
+//! Deploys a Faucet contract, refills it and sends CSPR to the accounts the other livenet
+//! tutorials use.
+//!
+//! Each run performs a single action, e.g.:
+//! `cargo run --bin livenet --features=livenet -- --action request --recipient account-hash-...`
+use std::str::FromStr;
+
+use clap::{Parser, ValueEnum};
+use faucet::faucet::{FaucetHostRef, FaucetInitArgs};
+use odra::casper_types::U512;
+use odra::host::{Deployer, HostEnv, HostRef, HostRefLoader};
+use odra::Address;
+use tutorial_utils::gas::{self, with_gas};
+use tutorial_utils::manifest::{load_from_manifest, record_deployment};
+use tutorial_utils::network;
+
+const CONTRACT_NAME: &str = "Faucet";
+const CSPR: u64 = 1_000_000_000;
+const DRIP_AMOUNT: u64 = 100 * CSPR;
+const COOLDOWN: u64 = 60 * 60 * 1000; // one hour
+const DAILY_CAP: u64 = 2_000 * CSPR;
+
+#[derive(Parser)]
+#[command(about = "Deploys a Faucet contract, refills it and sends CSPR from it.")]
+struct Args {
+    /// What to do on the network.
+    #[arg(long, value_enum, default_value_t = Action::Deploy)]
+    action: Action,
+    /// Address of a deployed contract, defaults to the latest deployment recorded in `deployments.json`.
+    #[arg(long)]
+    contract: Option<String>,
+    /// Address receiving the CSPR, defaults to the caller.
+    #[arg(long)]
+    recipient: Option<String>,
+    /// CSPR to refill the faucet with.
+    #[arg(long, default_value_t = 1_000)]
+    amount: u64,
+    /// Gas limit, defaults to a budget suited to the action.
+    #[arg(long)]
+    gas: Option<u64>,
+    /// Network from `networks.toml` to use instead of the `.env` configuration.
+    #[arg(long)]
+    network: Option<String>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Action {
+    /// Deploys a new contract.
+    Deploy,
+    /// Loads a deployed contract and prints its balance and limits.
+    Load,
+    /// Sends `--amount` CSPR from the caller to the faucet.
+    Refill,
+    /// Sends a drip to the recipient.
+    Request,
+}
+
+fn main() {
+    let args = Args::parse();
+    if let Some(name) = &args.network {
+        network::configure(name);
+    }
+    let env = odra_casper_livenet_env::env();
+
+    match args.action {
+        Action::Deploy => {
+            let faucet = deploy_contract(&env, args.gas.unwrap_or(gas::DEPLOY));
+            println!("Faucet address: {}", faucet.address().to_string());
+        }
+        Action::Load => {
+            let faucet = load(&env, &args);
+            println!("Faucet balance: {}", faucet.balance());
+            println!("Drip amount: {}", faucet.drip_amount());
+            println!("Left for today: {}", faucet.remaining_today());
+        }
+        Action::Refill => {
+            let mut faucet = load(&env, &args);
+            let amount = U512::from(args.amount) * CSPR;
+            // casper contract may return a result or not, so deserialization may fail and it's better to use `try_` methods
+            let _ = with_gas(&env, args.gas.unwrap_or(gas::TRANSFER), || {
+                faucet.with_tokens(amount).try_refill()
+            });
+            println!("Faucet balance: {}", faucet.balance());
+        }
+        Action::Request => {
+            let mut faucet = load(&env, &args);
+            let recipient = match &args.recipient {
+                Some(recipient) => {
+                    Address::from_str(recipient).expect("Should be a valid recipient address")
+                }
+                None => env.caller(),
+            };
+            let _ = with_gas(&env, args.gas.unwrap_or(gas::TRANSFER), || {
+                faucet.try_request(recipient)
+            });
+            println!("Recipient balance: {}", env.balance_of(&recipient));
+            println!(
+                "Next request possible at: {}",
+                faucet.next_request_at(recipient)
+            );
+        }
+    }
+}
+
+/// Loads the contract passed with `--contract`, or the latest one recorded in the manifest.
+fn load(env: &HostEnv, args: &Args) -> FaucetHostRef {
+    match &args.contract {
+        Some(address) => {
+            let address = Address::from_str(address).expect("Should be a valid contract address");
+            FaucetHostRef::load(env, address)
+        }
+        None => load_from_manifest(env, CONTRACT_NAME)
+            .expect("Pass --contract or deploy the contract first"),
+    }
+}
+
+/// Deploys a Faucet contract, retrying with more gas if `gas` isn't enough.
+pub fn deploy_contract(env: &HostEnv, gas: u64) -> FaucetHostRef {
+    let init_args = || FaucetInitArgs {
+        drip_amount: U512::from(DRIP_AMOUNT),
+        cooldown: COOLDOWN,
+        daily_cap: U512::from(DAILY_CAP),
+    };
+    let faucet = with_gas(env, gas, || FaucetHostRef::try_deploy(env, init_args()))
+        .expect("Should deploy the contract");
+    record_deployment(
+        CONTRACT_NAME,
+        &faucet,
+        None,
+        &[
+            ("drip_amount", DRIP_AMOUNT.to_string()),
+            ("cooldown", COOLDOWN.to_string()),
+            ("daily_cap", DAILY_CAP.to_string()),
+        ],
+    );
+    faucet
+}
@@ -0,0 +1,273 @@
+use odra::casper_types::U512;
+use odra::prelude::*;
+use odra::{Address, Mapping, Var};
+
+/// Length of the daily cap's window in milliseconds.
+pub const DAY: u64 = 24 * 60 * 60 * 1_000;
+
+#[odra::odra_error]
+/// Errors that may occur during the contract execution.
+pub enum Error {
+    /// The drip has to be positive and fit within the daily cap.
+    InvalidConfig = 1,
+    /// The recipient received CSPR too recently.
+    CooldownActive = 2,
+    /// The faucet has dispensed its daily cap.
+    DailyCapReached = 3,
+    /// The faucet doesn't hold enough CSPR for a drip.
+    FaucetEmpty = 4,
+    /// Refills need some CSPR attached.
+    ZeroRefill = 5,
+}
+
+#[odra::event]
+/// Emitted when the faucet sends CSPR to a recipient.
+pub struct Dispensed {
+    pub recipient: Address,
+    pub requested_by: Address,
+    pub amount: U512,
+}
+
+#[odra::event]
+/// Emitted when someone refills the faucet.
+pub struct Refilled {
+    pub by: Address,
+    pub amount: U512,
+}
+
+#[odra::module(events = [Dispensed, Refilled], errors = Error)]
+/// A faucet sending a fixed amount of CSPR per request, rate limited per recipient and per day.
+pub struct Faucet {
+    /// Motes sent per request.
+    drip_amount: Var<U512>,
+    /// How long a recipient waits between requests.
+    cooldown: Var<u64>,
+    /// Most motes dispensed per day.
+    daily_cap: Var<U512>,
+    /// Block time of each recipient's last drip.
+    last_drip: Mapping<Address, u64>,
+    /// Day the `dispensed_today` counter belongs to, counted from the Unix epoch.
+    current_day: Var<u64>,
+    dispensed_today: Var<U512>,
+}
+
+#[odra::module]
+impl Faucet {
+    /// Initializes the faucet. Send it CSPR with `refill` before anyone can use it.
+    pub fn init(&mut self, drip_amount: U512, cooldown: u64, daily_cap: U512) {
+        if drip_amount.is_zero() || drip_amount > daily_cap {
+            self.env().revert(Error::InvalidConfig);
+        }
+        self.drip_amount.set(drip_amount);
+        self.cooldown.set(cooldown);
+        self.daily_cap.set(daily_cap);
+    }
+
+    /**********
+     * TRANSACTIONS
+     **********/
+
+    /// Adds the attached CSPR to the faucet. Anyone can call it.
+    #[odra(payable)]
+    pub fn refill(&mut self) {
+        let amount = self.env().attached_value();
+        if amount.is_zero() {
+            self.env().revert(Error::ZeroRefill);
+        }
+        self.env().emit_event(Refilled {
+            by: self.env().caller(),
+            amount,
+        });
+    }
+
+    /// Sends the drip amount to `recipient`. Anyone can request CSPR for any account, since
+    /// the accounts that need it most have none to pay for the request.
+    pub fn request(&mut self, recipient: Address) {
+        let now = self.env().get_block_time();
+        if now < self.next_request_at(recipient) {
+            self.env().revert(Error::CooldownActive);
+        }
+        let amount = self.drip_amount.get_or_default();
+        if self.remaining_today() < amount {
+            self.env().revert(Error::DailyCapReached);
+        }
+        if self.env().self_balance() < amount {
+            self.env().revert(Error::FaucetEmpty);
+        }
+
+        let today = now / DAY;
+        if self.current_day.get_or_default() != today {
+            self.current_day.set(today);
+            self.dispensed_today.set(U512::zero());
+        }
+        self.dispensed_today
+            .set(self.dispensed_today.get_or_default() + amount);
+        self.last_drip.set(&recipient, now);
+        self.env().transfer_tokens(&recipient, &amount);
+        self.env().emit_event(Dispensed {
+            recipient,
+            requested_by: self.env().caller(),
+            amount,
+        });
+    }
+
+    /**********
+     * QUERIES
+     **********/
+
+    /// Returns the motes sent per request.
+    pub fn drip_amount(&self) -> U512 {
+        self.drip_amount.get_or_default()
+    }
+
+    /// Returns how long a recipient waits between requests.
+    pub fn cooldown(&self) -> u64 {
+        self.cooldown.get_or_default()
+    }
+
+    /// Returns the most motes dispensed per day.
+    pub fn daily_cap(&self) -> U512 {
+        self.daily_cap.get_or_default()
+    }
+
+    /// Returns the block time from which `recipient` can receive CSPR again.
+    pub fn next_request_at(&self, recipient: Address) -> u64 {
+        match self.last_drip.get(&recipient) {
+            Some(last_drip) => last_drip + self.cooldown.get_or_default(),
+            None => 0,
+        }
+    }
+
+    /// Returns the motes the faucet can still dispense today, before its balance is considered.
+    pub fn remaining_today(&self) -> U512 {
+        let today = self.env().get_block_time() / DAY;
+        let dispensed = if self.current_day.get_or_default() == today {
+            self.dispensed_today.get_or_default()
+        } else {
+            U512::zero()
+        };
+        self.daily_cap.get_or_default().saturating_sub(dispensed)
+    }
+
+    /// Returns the CSPR the faucet holds.
+    pub fn balance(&self) -> U512 {
+        self.env().self_balance()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use odra::host::{Deployer, HostEnv, HostRef};
+
+    const DRIP: u64 = 100;
+    const COOLDOWN: u64 = 60 * 60 * 1_000; // one hour
+
+    /// Deploys a faucet with a daily cap of three drips, refilled with ten.
+    fn setup(env: &HostEnv) -> FaucetHostRef {
+        let mut faucet = FaucetHostRef::deploy(
+            env,
+            FaucetInitArgs {
+                drip_amount: U512::from(DRIP),
+                cooldown: COOLDOWN,
+                daily_cap: U512::from(3 * DRIP),
+            },
+        );
+        faucet.with_tokens(U512::from(10 * DRIP)).refill();
+        faucet
+    }
+
+    #[test]
+    fn cooldown_per_recipient() {
+        let env = odra_test::env();
+        let mut faucet = setup(&env);
+        let (alice, bob) = (env.get_account(1), env.get_account(2));
+        let alice_balance = env.balance_of(&alice);
+
+        // Account 0 pays for the request, Alice gets the CSPR
+        faucet.request(alice);
+        assert_eq!(env.balance_of(&alice), alice_balance + U512::from(DRIP));
+        assert!(env.emitted_event(
+            faucet.address(),
+            &Dispensed {
+                recipient: alice,
+                requested_by: env.get_account(0),
+                amount: U512::from(DRIP),
+            }
+        ));
+
+        // Whoever asks, Alice has to wait for the cooldown
+        env.set_caller(alice);
+        assert_eq!(faucet.try_request(alice), Err(Error::CooldownActive.into()));
+        assert_eq!(faucet.next_request_at(alice), COOLDOWN);
+        faucet.request(bob);
+
+        env.advance_block_time(COOLDOWN - 1);
+        assert_eq!(faucet.try_request(alice), Err(Error::CooldownActive.into()));
+        env.advance_block_time(1);
+        faucet.request(alice);
+        assert_eq!(faucet.balance(), U512::from(7 * DRIP));
+    }
+
+    #[test]
+    fn daily_cap() {
+        let env = odra_test::env();
+        let mut faucet = setup(&env);
+        for i in 1..=3 {
+            faucet.request(env.get_account(i));
+        }
+        assert_eq!(faucet.remaining_today(), U512::zero());
+        assert_eq!(
+            faucet.try_request(env.get_account(4)),
+            Err(Error::DailyCapReached.into())
+        );
+
+        // The cap resets at the start of the next day
+        env.advance_block_time(DAY);
+        assert_eq!(faucet.remaining_today(), U512::from(3 * DRIP));
+        faucet.request(env.get_account(4));
+        assert_eq!(faucet.remaining_today(), U512::from(2 * DRIP));
+    }
+
+    #[test]
+    fn refills() {
+        let env = odra_test::env();
+        let mut faucet = FaucetHostRef::deploy(
+            &env,
+            FaucetInitArgs {
+                drip_amount: U512::from(DRIP),
+                cooldown: COOLDOWN,
+                daily_cap: U512::from(3 * DRIP),
+            },
+        );
+        assert_eq!(
+            faucet.try_request(env.get_account(1)),
+            Err(Error::FaucetEmpty.into())
+        );
+        assert_eq!(faucet.try_refill(), Err(Error::ZeroRefill.into()));
+
+        // Anyone can refill the faucet, with any amount
+        env.set_caller(env.get_account(5));
+        faucet.with_tokens(U512::from(DRIP + 1)).refill();
+        assert_eq!(faucet.balance(), U512::from(DRIP + 1));
+        faucet.request(env.get_account(1));
+        assert_eq!(
+            faucet.try_request(env.get_account(2)),
+            Err(Error::FaucetEmpty.into())
+        );
+
+        // A drip larger than the daily cap could never be sent
+        assert_eq!(
+            FaucetHostRef::try_deploy(
+                &env,
+                FaucetInitArgs {
+                    drip_amount: U512::from(DRIP),
+                    cooldown: COOLDOWN,
+                    daily_cap: U512::from(DRIP - 1),
+                },
+            )
+            .err(),
+            Some(Error::InvalidConfig.into())
+        );
+    }
+}
@@ -1,35 +1,190 @@
-use reqwest::blocking::Client; // Use blocking client for simplicity
-use serde_json::Value;
-use std::fs::{create_dir_all, File};
-use std::io::Write;
-use std::path::Path;
+//! Fetches the secret keys of the Fondant users and saves them as PEM files, along with
+//! their public keys and an `accounts.json` manifest listing every account.
+//!
+//! Keys are fetched concurrently and failed requests are retried with an exponential backoff.
+//! Every option falls back to an environment variable, e.g.:
+//! `cargo run --bin fetch_keys -- --url http://localhost:3001 --from 1 --to 5 --out-dir .keys`
+use casper_types::{PublicKey, SecretKey};
+use clap::Parser;
+use reqwest::Client;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let base_url = "http://localhost:3001";
-    let start_id = 1;
-    let end_id = 5;
+/// Manifest of the fetched accounts, written to the output directory.
+const ACCOUNTS_FILE: &str = "accounts.json";
+/// Delay before the first retry, doubled after every failed attempt.
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
 
+type Error = Box<dyn std::error::Error + Send + Sync>;
+
+#[derive(Parser)]
+#[command(about = "Fetches the secret keys of the Fondant users.")]
+struct Args {
+    /// Address of the Fondant API.
+    #[arg(long, env = "FONDANT_URL", default_value = "http://localhost:3001")]
+    url: String,
+    /// Id of the first user to fetch.
+    #[arg(long, env = "FONDANT_FROM", default_value_t = 1)]
+    from: u32,
+    /// Id of the last user to fetch.
+    #[arg(long, env = "FONDANT_TO", default_value_t = 5)]
+    to: u32,
+    /// Directory the keys are saved to.
+    #[arg(long, env = "FONDANT_KEYS_DIR", default_value = ".keys")]
+    out_dir: PathBuf,
+    /// Maximum number of keys fetched at the same time.
+    #[arg(long, env = "FONDANT_CONCURRENCY", default_value_t = 4)]
+    concurrency: usize,
+    /// Number of times a failed request is retried.
+    #[arg(long, env = "FONDANT_RETRIES", default_value_t = 3)]
+    retries: u32,
+}
+
+/// How a single key was obtained.
+enum Outcome {
+    Fetched(SecretKey),
+    Skipped(SecretKey),
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    let args = Args::parse();
     let client = Client::new();
-    let key_dir = Path::new(".keys");
-    create_dir_all(key_dir)?;
-
-    for id in start_id..=end_id {
-        let url = format!("{}/users/{}/private_key", base_url, id);
-        let filename = key_dir.join(format!("secret_key_{}.pem", id));
-
-        // Fetch the JSON data
-        let response = client.get(&url).send()?;
-        let json_response: Value = response.json()?;
-
-        // Extract and save the private key
-        if let Some(message) = json_response.get("message").and_then(|v| v.as_str()) {
-            let mut file = File::create(&filename)?; // Borrow filename with &
-            file.write_all(message.as_bytes())?;
-            println!("Saved key {} to {}", id, filename.display());
-        } else {
-            eprintln!("Error: Private key not found in response for {}", url);
-        }
+    tokio::fs::create_dir_all(&args.out_dir).await?;
+
+    let permits = Arc::new(Semaphore::new(args.concurrency.max(1)));
+    let mut tasks = JoinSet::new();
+    for id in args.from..=args.to {
+        let (client, permits) = (client.clone(), permits.clone());
+        let (url, filename) = (
+            args.url.clone(),
+            args.out_dir.join(format!("secret_key_{}.pem", id)),
+        );
+        let retries = args.retries;
+        tasks.spawn(async move {
+            let _permit = permits.acquire_owned().await?;
+            let outcome = load_or_fetch_key(&client, &url, id, &filename, retries).await;
+            Ok::<_, Error>((id, filename, outcome))
+        });
+    }
+
+    // Results are keyed by id, so the manifest keeps the order of the users
+    let mut results = BTreeMap::new();
+    while let Some(task) = tasks.join_next().await {
+        let (id, filename, outcome) = task??;
+        results.insert(id, (filename, outcome));
+    }
+
+    let (mut fetched, mut skipped, mut failed) = (0, 0, Vec::new());
+    let mut accounts = Vec::new();
+    for (id, (filename, outcome)) in results {
+        let secret_key = match outcome {
+            Ok(Outcome::Fetched(secret_key)) => {
+                println!("Saved key {} to {}", id, filename.display());
+                fetched += 1;
+                secret_key
+            }
+            Ok(Outcome::Skipped(secret_key)) => {
+                println!("Skipped key {}, {} already exists", id, filename.display());
+                skipped += 1;
+                secret_key
+            }
+            Err(error) => {
+                eprintln!("Error: Couldn't fetch key {}: {}", id, error);
+                failed.push(id);
+                continue;
+            }
+        };
+
+        // The public key and account hash are derived from the secret key
+        let public_key = PublicKey::from(&secret_key);
+        let public_key_file = args.out_dir.join(format!("public_key_{}.pem", id));
+        public_key.to_file(&public_key_file)?;
+        accounts.push(json!({
+            "index": accounts.len(),
+            "public_key": public_key.to_hex(),
+            "account_hash": public_key.to_account_hash().to_formatted_string(),
+            "pem_path": filename,
+        }));
     }
 
+    // Accounts are indexed like the livenet env does: the first key is account 0
+    let manifest = args.out_dir.join(ACCOUNTS_FILE);
+    tokio::fs::write(&manifest, serde_json::to_string_pretty(&accounts)?).await?;
+    println!(
+        "Saved {} account(s) to {}",
+        accounts.len(),
+        manifest.display()
+    );
+
+    println!(
+        "Fetched: {}, skipped: {}, failed: {}",
+        fetched,
+        skipped,
+        failed.len()
+    );
+    if !failed.is_empty() {
+        return Err(format!("Couldn't fetch the keys of users {:?}", failed).into());
+    }
     Ok(())
 }
+
+/// Reads a previously saved key, or fetches it with retries.
+async fn load_or_fetch_key(
+    client: &Client,
+    base_url: &str,
+    id: u32,
+    filename: &Path,
+    retries: u32,
+) -> Result<Outcome, Error> {
+    if filename.exists() {
+        return Ok(Outcome::Skipped(SecretKey::from_file(filename)?));
+    }
+
+    let mut backoff = INITIAL_BACKOFF;
+    let mut attempt = 0;
+    loop {
+        match fetch_key(client, base_url, id, filename).await {
+            Ok(secret_key) => return Ok(Outcome::Fetched(secret_key)),
+            Err(error) if attempt < retries => {
+                attempt += 1;
+                eprintln!(
+                    "Fetching key {} failed ({}), retrying in {:?}",
+                    id, error, backoff
+                );
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+            }
+            Err(error) => return Err(error),
+        }
+    }
+}
+
+/// Fetches a user's secret key and saves it to `filename` if it's valid.
+async fn fetch_key(
+    client: &Client,
+    base_url: &str,
+    id: u32,
+    filename: &Path,
+) -> Result<SecretKey, Error> {
+    let url = format!("{}/users/{}/private_key", base_url, id);
+
+    // Fetch the JSON data
+    let response = client.get(&url).send().await?.error_for_status()?;
+    let json_response: Value = response.json().await?;
+
+    // Extract, validate and save the private key
+    let pem = json_response
+        .get("message")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| format!("Private key not found in response for {}", url))?;
+    let secret_key = SecretKey::from_pem(pem)
+        .map_err(|_| format!("Invalid secret key in response for {}", url))?;
+    tokio::fs::write(filename, pem).await?;
+    Ok(secret_key)
+}
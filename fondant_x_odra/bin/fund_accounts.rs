@@ -0,0 +1,151 @@
+//! Tops up the accounts fetched by `fetch_keys` using the Fondant faucet and waits until
+//! every transfer is finalized.
+//!
+//! Every option falls back to an environment variable, e.g.:
+//! `cargo run --bin fund_accounts -- --amount 1000 --keys-dir .keys`
+use clap::Parser;
+use reqwest::blocking::Client;
+use serde_json::{json, Value};
+use std::fs;
+use std::path::PathBuf;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+/// Number of motes in one CSPR.
+const MOTES_PER_CSPR: u64 = 1_000_000_000;
+/// Manifest of the fetched accounts, written by `fetch_keys`.
+const ACCOUNTS_FILE: &str = "accounts.json";
+/// How often the node is asked about a pending transfer.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Parser)]
+#[command(about = "Tops up the fetched accounts using the Fondant faucet.")]
+struct Args {
+    /// Address of the Fondant API.
+    #[arg(long, env = "FONDANT_URL", default_value = "http://localhost:3001")]
+    url: String,
+    /// Address of the node the transfers are tracked on.
+    #[arg(
+        long,
+        env = "ODRA_CASPER_LIVENET_NODE_ADDRESS",
+        default_value = "http://localhost/node-1"
+    )]
+    node: String,
+    /// Amount of CSPR sent to every account.
+    #[arg(long, env = "FONDANT_FUND_AMOUNT", default_value_t = 1_000)]
+    amount: u64,
+    /// Directory `fetch_keys` saved the keys and the accounts manifest to.
+    #[arg(long, env = "FONDANT_KEYS_DIR", default_value = ".keys")]
+    keys_dir: PathBuf,
+    /// How long to wait for the transfers to finalize, in seconds.
+    #[arg(long, default_value_t = 120)]
+    timeout: u64,
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+    let client = Client::new();
+
+    let manifest = args.keys_dir.join(ACCOUNTS_FILE);
+    let accounts: Vec<Value> = serde_json::from_str(
+        &fs::read_to_string(&manifest)
+            .map_err(|_| format!("{} not found, run fetch_keys first", manifest.display()))?,
+    )?;
+    let motes = args.amount * MOTES_PER_CSPR;
+
+    // Request all transfers first, so they can be finalized in the same blocks
+    let mut pending = Vec::new();
+    for account in &accounts {
+        let public_key = account["public_key"].as_str().unwrap_or_default();
+        match request_funds(&client, &args.url, public_key, motes) {
+            Ok(deploy_hash) => {
+                println!(
+                    "Requested {} CSPR for {} ({})",
+                    args.amount, public_key, deploy_hash
+                );
+                pending.push((public_key, deploy_hash));
+            }
+            Err(error) => eprintln!("Error: Couldn't fund {}: {}", public_key, error),
+        }
+    }
+
+    let deadline = Instant::now() + Duration::from_secs(args.timeout);
+    let mut failed = accounts.len() - pending.len();
+    while !pending.is_empty() {
+        if Instant::now() > deadline {
+            eprintln!(
+                "Error: {} transfer(s) weren't finalized in time",
+                pending.len()
+            );
+            failed += pending.len();
+            break;
+        }
+        sleep(POLL_INTERVAL);
+        let mut still_pending = Vec::new();
+        for (public_key, deploy_hash) in pending {
+            match execution_result(&client, &args.node, &deploy_hash) {
+                Some(Ok(())) => println!("Funded {}", public_key),
+                Some(Err(error)) => {
+                    eprintln!("Error: Transfer to {} failed: {}", public_key, error);
+                    failed += 1;
+                }
+                None => still_pending.push((public_key, deploy_hash)),
+            }
+        }
+        pending = still_pending;
+    }
+
+    println!(
+        "Funded {} of {} account(s)",
+        accounts.len() - failed,
+        accounts.len()
+    );
+    if failed > 0 {
+        return Err(format!("{} account(s) weren't funded", failed).into());
+    }
+    Ok(())
+}
+
+/// Asks the faucet to send `motes` to the account, returning the hash of the transfer deploy.
+fn request_funds(
+    client: &Client,
+    base_url: &str,
+    public_key: &str,
+    motes: u64,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let url = format!("{}/faucet", base_url);
+    let response: Value = client
+        .post(&url)
+        .json(&json!({ "public_key": public_key, "amount": motes.to_string() }))
+        .send()?
+        .error_for_status()?
+        .json()?;
+    response
+        .get("deploy_hash")
+        .and_then(|v| v.as_str())
+        .map(String::from)
+        .ok_or_else(|| format!("Deploy hash not found in response for {}", url).into())
+}
+
+/// Returns the outcome of an executed deploy, or `None` if it's still pending.
+fn execution_result(client: &Client, node: &str, deploy_hash: &str) -> Option<Result<(), String>> {
+    let response: Value = client
+        .post(format!("{}/rpc", node))
+        .json(&json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "info_get_deploy",
+            "params": { "deploy_hash": deploy_hash }
+        }))
+        .send()
+        .and_then(|response| response.json())
+        .ok()?;
+    let result = response["result"]["execution_results"].get(0)?;
+    match result["result"].get("Failure") {
+        Some(failure) => Some(Err(failure["error_message"]
+            .as_str()
+            .unwrap_or("unknown error")
+            .to_string())),
+        None => Some(Ok(())),
+    }
+}
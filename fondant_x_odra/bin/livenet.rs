@@ -1,25 +1,77 @@
+//! Deploys the Flipper contract to a local Fondant network and flips its value.
+//!
+//! Each run performs a single action, e.g.:
+//! `cargo run --bin livenet --features=livenet -- --action flip --contract hash-...`
+use clap::{Parser, ValueEnum};
 use fondant_x_odra::flipper::FlipperHostRef;
-use odra::args::Maybe;
-use odra::casper_types::U256;
 use odra::host::{Deployer, HostEnv, HostRef, HostRefLoader, NoArgs};
 use odra::Address;
 use std::str::FromStr;
+use tutorial_utils::gas::{self, with_gas};
+use tutorial_utils::manifest::{load_from_manifest, record_deployment};
+use tutorial_utils::network;
+
+const CONTRACT_NAME: &str = "Flipper";
+
+#[derive(Parser)]
+#[command(about = "Deploys the Flipper contract and flips its value.")]
+struct Args {
+    /// What to do on the network.
+    #[arg(long, value_enum, default_value_t = Action::Deploy)]
+    action: Action,
+    /// Address of a deployed contract, defaults to the latest deployment recorded in `deployments.json`.
+    #[arg(long)]
+    contract: Option<String>,
+    /// Gas limit, defaults to a budget suited to the action.
+    #[arg(long)]
+    gas: Option<u64>,
+    /// Network from `networks.toml` to use instead of the `.env` configuration.
+    #[arg(long)]
+    network: Option<String>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Action {
+    /// Deploys a new contract.
+    Deploy,
+    /// Loads a deployed contract and prints its value.
+    Load,
+    /// Flips the value of a deployed contract.
+    Flip,
+}
 
 fn main() {
+    let args = Args::parse();
+    if let Some(name) = &args.network {
+        network::configure(name);
+    }
     let env = odra_casper_livenet_env::env();
 
-    // Deploy new contract.
-    let mut flipper = deploy_contract(&env);
-    println!("flipper current value: {}", flipper.get().to_string());
-
-    // Uncomment to load existing contract.
-    // let mut token = load_contract(&env, CASPER_CONTRACT_ADDRESS);
-    // println!("Token name: {}", token.get_collection_name());
+    match args.action {
+        Action::Deploy => {
+            let flipper = deploy_contract(&env, args.gas.unwrap_or(gas::DEPLOY));
+            println!("flipper address: {}", flipper.address().to_string());
+            println!("flipper current value: {}", flipper.get());
+        }
+        Action::Load => {
+            let flipper = load(&env, &args);
+            println!("flipper current value: {}", flipper.get());
+        }
+        Action::Flip => {
+            let mut flipper = load(&env, &args);
+            let _ = with_gas(&env, args.gas.unwrap_or(gas::CALL), || flipper.try_flip());
+            println!("flipper after flip value: {}", flipper.get());
+        }
+    }
+}
 
-    env.set_gas(3_000_000_000u64);
-    let owner = env.caller();
-    let _ = flipper.flip();
-    println!("flipper after flip value: {}", flipper.get().to_string());
+/// Loads the contract passed with `--contract`, or the latest one recorded in the manifest.
+fn load(env: &HostEnv, args: &Args) -> FlipperHostRef {
+    match &args.contract {
+        Some(address) => load_contract(env, address),
+        None => load_from_manifest(env, CONTRACT_NAME)
+            .expect("Pass --contract or deploy the contract first"),
+    }
 }
 
 pub fn load_contract(env: &HostEnv, address: &str) -> FlipperHostRef {
@@ -27,7 +79,9 @@ pub fn load_contract(env: &HostEnv, address: &str) -> FlipperHostRef {
     FlipperHostRef::load(env, address)
 }
 
-pub fn deploy_contract(env: &HostEnv) -> FlipperHostRef {
-    env.set_gas(400_000_000_000u64);
-    FlipperHostRef::deploy(env, NoArgs)
+pub fn deploy_contract(env: &HostEnv, gas: u64) -> FlipperHostRef {
+    let flipper = with_gas(env, gas, || FlipperHostRef::try_deploy(env, NoArgs))
+        .expect("Should deploy the contract");
+    record_deployment(CONTRACT_NAME, &flipper, None, &[]);
+    flipper
 }
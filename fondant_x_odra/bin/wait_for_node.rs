@@ -0,0 +1,73 @@
+//! Waits until the local network is ready, i.e. the node reports a new block.
+//!
+//! Exits with an error if the chain doesn't advance in time, e.g.:
+//! `cargo run --bin wait_for_node -- --timeout 300 && cargo run --bin livenet --features=livenet`
+use clap::Parser;
+use reqwest::blocking::Client;
+use serde_json::{json, Value};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+#[derive(Parser)]
+#[command(about = "Waits until the node's chain height advances.")]
+struct Args {
+    /// Address of the node to poll.
+    #[arg(
+        long,
+        env = "ODRA_CASPER_LIVENET_NODE_ADDRESS",
+        default_value = "http://localhost/node-1"
+    )]
+    node: String,
+    /// How long to wait for the node, in seconds.
+    #[arg(long, default_value_t = 180)]
+    timeout: u64,
+    /// How often the node is polled, in seconds.
+    #[arg(long, default_value_t = 2)]
+    interval: u64,
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+    let client = Client::new();
+    let deadline = Instant::now() + Duration::from_secs(args.timeout);
+
+    // A node that's still booting may be unreachable or have no blocks yet
+    let mut first_height = None;
+    while Instant::now() < deadline {
+        match (block_height(&client, &args.node), first_height) {
+            (Some(height), None) => {
+                println!("Node is up at height {}, waiting for a new block", height);
+                first_height = Some(height);
+            }
+            (Some(height), Some(first)) if height > first => {
+                println!("Node is ready at height {}", height);
+                return Ok(());
+            }
+            (Some(_), Some(_)) => {}
+            (None, _) => println!("Waiting for {}", args.node),
+        }
+        sleep(Duration::from_secs(args.interval));
+    }
+
+    Err(format!(
+        "The node at {} wasn't ready within {} seconds",
+        args.node, args.timeout
+    )
+    .into())
+}
+
+/// Returns the height of the last block the node added, if it can tell.
+fn block_height(client: &Client, node: &str) -> Option<u64> {
+    let response: Value = client
+        .post(format!("{}/rpc", node))
+        .json(&json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "info_get_status",
+            "params": []
+        }))
+        .send()
+        .and_then(|response| response.json())
+        .ok()?;
+    response["result"]["last_added_block_info"]["height"].as_u64()
+}
@@ -1,49 +1,122 @@
+use odra::module::SubModule;
 use odra::prelude::*;
 use odra::Var;
+use odra_modules::access::Ownable;
 
 /// A module definition. Each module struct consists Vars and Mappings
 /// or/and another modules.
-#[odra::module]
+#[odra::module(events = [Flipped, Changed], errors = Error)]
 pub struct Flipper {
     /// The module itself does not store the value,
     /// it's a proxy that writes/reads value to/from the host.
     value: Var<bool>,
+    /// A counter changed by `increment`, `decrement` and `reset`.
+    count: Var<u32>,
+    /// Number of state changes made so far.
+    history_len: Var<u32>,
+    /// Other modules can be nested, here to restrict `reset` to the owner.
+    ownable: SubModule<Ownable>,
+}
+
+/// Errors the contract may revert with.
+#[odra::odra_error]
+pub enum Error {
+    /// The counter can't go below zero.
+    CounterUnderflow = 1,
+}
+
+/// Emitted whenever the boolean value changes.
+#[odra::event]
+pub struct Flipped {
+    pub value: bool,
+}
+
+/// Emitted whenever the counter changes.
+#[odra::event]
+pub struct Changed {
+    pub count: u32,
 }
 
 /// Module implementation.
-/// 
+///
 /// To generate entrypoints,
 /// an implementation block must be marked as #[odra::module].
 #[odra::module]
 impl Flipper {
     /// Odra constructor.
-    /// 
+    ///
     /// Initializes the contract.
+    /// The deployer becomes the owner.
     pub fn init(&mut self) {
         self.value.set(false);
+        self.ownable.init();
     }
 
     /// Replaces the current value with the passed argument.
     pub fn set(&mut self, value: bool) {
-        self.value.set(value);
+        self.set_value(value);
     }
 
     /// Replaces the current value with the opposite value.
     pub fn flip(&mut self) {
-        self.value.set(!self.get());
+        self.set_value(!self.get());
     }
 
-    /// Retrieves value from the storage. 
+    /// Increases the counter by one.
+    pub fn increment(&mut self) {
+        self.set_count(self.get_count() + 1);
+    }
+
+    /// Decreases the counter by one, reverting if it's already zero.
+    pub fn decrement(&mut self) {
+        let count = self.get_count();
+        if count == 0 {
+            self.env().revert(Error::CounterUnderflow);
+        }
+        self.set_count(count - 1);
+    }
+
+    /// Sets the counter back to zero. Only the owner can call it.
+    pub fn reset(&mut self) {
+        self.ownable.assert_owner(&self.env().caller());
+        self.set_count(0);
+    }
+
+    /// Retrieves value from the storage.
     /// If the value has never been set, the default value is returned.
     pub fn get(&self) -> bool {
         self.value.get_or_default()
     }
+
+    /// Retrieves the counter value.
+    pub fn get_count(&self) -> u32 {
+        self.count.get_or_default()
+    }
+
+    /// Retrieves the number of state changes made so far.
+    pub fn get_history_len(&self) -> u32 {
+        self.history_len.get_or_default()
+    }
+
+    /// Stores the value, records the change and emits an event.
+    fn set_value(&mut self, value: bool) {
+        self.value.set(value);
+        self.history_len.add(1);
+        self.env().emit_event(Flipped { value });
+    }
+
+    /// Stores the counter, records the change and emits an event.
+    fn set_count(&mut self, count: u32) {
+        self.count.set(count);
+        self.history_len.add(1);
+        self.env().emit_event(Changed { count });
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::flipper::FlipperHostRef;
-    use odra::host::{Deployer, NoArgs};
+    use crate::flipper::{Changed, Error, FlipperHostRef};
+    use odra::host::{Deployer, HostRef, NoArgs};
 
     #[test]
     fn flipping() {
@@ -55,4 +128,29 @@ mod tests {
         contract.flip();
         assert!(contract.get());
     }
+
+    #[test]
+    fn counting() {
+        let env = odra_test::env();
+        let mut contract = FlipperHostRef::deploy(&env, NoArgs);
+        contract.increment();
+        contract.increment();
+        contract.decrement();
+        assert_eq!(contract.get_count(), 1);
+        assert!(env.emitted_event(contract.address(), &Changed { count: 1 }));
+
+        // Only the owner can reset the counter.
+        env.set_caller(env.get_account(1));
+        assert!(contract.try_reset().is_err());
+        env.set_caller(env.get_account(0));
+        contract.reset();
+        assert_eq!(contract.get_count(), 0);
+        assert_eq!(
+            contract.try_decrement(),
+            Err(Error::CounterUnderflow.into())
+        );
+
+        contract.flip();
+        assert_eq!(contract.get_history_len(), 5);
+    }
 }
@@ -0,0 +1,391 @@
+use odra::args::Maybe;
+use odra::casper_types::{U256, U512};
+use odra::module::SubModule;
+use odra::prelude::*;
+use odra::uints::ToU512;
+use odra::{Address, ContractRef, Var};
+use odra_modules::cep18_token::Cep18;
+use odra_modules::cep78::token::Cep78ContractRef;
+
+// Numbered past the error codes of the embedded CEP-18 module.
+#[odra::odra_error]
+/// Errors that may occur during the contract execution.
+pub enum Error {
+    /// The vault already holds or held an NFT.
+    AlreadyFractionalized = 40_000,
+    /// The vault doesn't hold an NFT.
+    NotLocked = 40_001,
+    /// The NFT hasn't been bought out yet.
+    NotBoughtOut = 40_002,
+    /// The attached CSPR doesn't match the reserve price.
+    IncorrectPayment = 40_003,
+    /// Share supply and reserve price must be positive.
+    InvalidParameters = 40_004,
+    /// The caller holds no shares.
+    NoShares = 40_005,
+    /// Only an account holding every share can reclaim the NFT.
+    NotAllShares = 40_006,
+}
+
+#[odra::odra_type]
+/// Lifecycle of the vault.
+pub enum VaultState {
+    /// No NFT has been locked yet.
+    Empty,
+    /// The NFT is locked and can be bought out.
+    Locked,
+    /// The NFT was bought, and shareholders redeem the proceeds.
+    BoughtOut,
+    /// A holder of every share took the NFT out.
+    Reclaimed,
+}
+
+#[odra::event]
+/// Emitted when an NFT is locked and its shares are minted.
+pub struct Fractionalized {
+    pub nft_contract: Address,
+    pub token_id: u64,
+    pub curator: Address,
+    pub supply: U256,
+    pub reserve_price: U512,
+}
+
+#[odra::event]
+/// Emitted when the NFT is bought out of the vault.
+pub struct BoughtOut {
+    pub buyer: Address,
+    pub price: U512,
+}
+
+#[odra::event]
+/// Emitted when a shareholder redeems shares for a part of the proceeds.
+pub struct Redeemed {
+    pub holder: Address,
+    pub shares: U256,
+    pub amount: U512,
+}
+
+#[odra::event]
+/// Emitted when a holder of every share takes the NFT out.
+pub struct Reclaimed {
+    pub holder: Address,
+}
+
+#[odra::module(events = [Fractionalized, BoughtOut, Redeemed, Reclaimed], errors = Error)]
+/// Locks a CEP-78 token and issues CEP-18 shares of it.
+pub struct Fractionalizer {
+    shares: SubModule<Cep18>,
+    state: Var<VaultState>,
+    nft_contract: Var<Address>,
+    token_id: Var<u64>,
+    reserve_price: Var<U512>,
+    /// Buyout proceeds that haven't been redeemed yet.
+    proceeds: Var<U512>,
+}
+
+#[odra::module]
+impl Fractionalizer {
+    /// Initializes the share token with no supply.
+    pub fn init(&mut self, name: String, symbol: String, decimals: u8) {
+        self.shares
+            .init(symbol, name, decimals, U256::zero(), vec![], vec![], None);
+    }
+
+    /**********
+     * TRANSACTIONS
+     **********/
+
+    /// Locks the caller's NFT in the vault and mints `supply` shares to the caller.
+    ///
+    /// The caller has to approve the vault for the token beforehand.
+    pub fn fractionalize(
+        &mut self,
+        nft_contract: Address,
+        token_id: u64,
+        supply: U256,
+        reserve_price: U512,
+    ) {
+        if self.state() != VaultState::Empty {
+            self.env().revert(Error::AlreadyFractionalized);
+        }
+        if supply.is_zero() || reserve_price.is_zero() {
+            self.env().revert(Error::InvalidParameters);
+        }
+
+        let curator = self.env().caller();
+        let vault = self.env().self_address();
+        Cep78ContractRef::new(self.env(), nft_contract).transfer(
+            Maybe::Some(token_id),
+            Maybe::None,
+            curator,
+            vault,
+        );
+        self.nft_contract.set(nft_contract);
+        self.token_id.set(token_id);
+        self.reserve_price.set(reserve_price);
+        self.state.set(VaultState::Locked);
+        self.shares.raw_mint(&curator, &supply);
+        self.env().emit_event(Fractionalized {
+            nft_contract,
+            token_id,
+            curator,
+            supply,
+            reserve_price,
+        });
+    }
+
+    /// Buys the NFT for the attached reserve price, which shareholders can then redeem.
+    #[odra(payable)]
+    pub fn buyout(&mut self) {
+        if self.state() != VaultState::Locked {
+            self.env().revert(Error::NotLocked);
+        }
+        let price = self.env().attached_value();
+        if price != self.reserve_price.get_or_default() {
+            self.env().revert(Error::IncorrectPayment);
+        }
+
+        let buyer = self.env().caller();
+        self.state.set(VaultState::BoughtOut);
+        self.proceeds.set(price);
+        self.release_nft(buyer);
+        self.env().emit_event(BoughtOut { buyer, price });
+    }
+
+    /// Burns all of the caller's shares and sends them their part of the buyout proceeds.
+    pub fn redeem(&mut self) {
+        if self.state() != VaultState::BoughtOut {
+            self.env().revert(Error::NotBoughtOut);
+        }
+        let holder = self.env().caller();
+        let shares = self.shares.balance_of(&holder);
+        if shares.is_zero() {
+            self.env().revert(Error::NoShares);
+        }
+
+        // Proceeds and supply shrink together, so every share is worth the same until the last one
+        let proceeds = self.proceeds.get_or_default();
+        let amount = proceeds * shares.to_u512() / self.shares.total_supply().to_u512();
+        self.shares.raw_burn(&holder, &shares);
+        self.proceeds.set(proceeds - amount);
+        self.env().transfer_tokens(&holder, &amount);
+        self.env().emit_event(Redeemed {
+            holder,
+            shares,
+            amount,
+        });
+    }
+
+    /// Burns every share and gives the NFT to the caller, who has to hold all of them.
+    pub fn reclaim(&mut self) {
+        if self.state() != VaultState::Locked {
+            self.env().revert(Error::NotLocked);
+        }
+        let holder = self.env().caller();
+        let supply = self.shares.total_supply();
+        if self.shares.balance_of(&holder) != supply {
+            self.env().revert(Error::NotAllShares);
+        }
+
+        self.shares.raw_burn(&holder, &supply);
+        self.state.set(VaultState::Reclaimed);
+        self.release_nft(holder);
+        self.env().emit_event(Reclaimed { holder });
+    }
+
+    delegate! {
+        to self.shares {
+            fn transfer(&mut self, recipient: &Address, amount: &U256);
+            fn transfer_from(&mut self, owner: &Address, recipient: &Address, amount: &U256);
+            fn approve(&mut self, spender: &Address, amount: &U256);
+            fn increase_allowance(&mut self, spender: &Address, inc_by: &U256);
+            fn decrease_allowance(&mut self, spender: &Address, decr_by: &U256);
+            fn name(&self) -> String;
+            fn symbol(&self) -> String;
+            fn decimals(&self) -> u8;
+            fn total_supply(&self) -> U256;
+            fn balance_of(&self, address: &Address) -> U256;
+            fn allowance(&self, owner: &Address, spender: &Address) -> U256;
+        }
+    }
+
+    /**********
+     * QUERIES
+     **********/
+
+    /// Returns the state of the vault.
+    pub fn state(&self) -> VaultState {
+        self.state.get().unwrap_or(VaultState::Empty)
+    }
+
+    /// Returns the locked NFT's contract and token ID.
+    pub fn get_nft(&self) -> Option<(Address, u64)> {
+        self.nft_contract
+            .get()
+            .map(|nft_contract| (nft_contract, self.token_id.get_or_default()))
+    }
+
+    /// Returns the price at which anyone can buy the NFT out.
+    pub fn get_reserve_price(&self) -> U512 {
+        self.reserve_price.get_or_default()
+    }
+
+    /// Returns the buyout proceeds that haven't been redeemed.
+    pub fn get_proceeds(&self) -> U512 {
+        self.proceeds.get_or_default()
+    }
+}
+
+impl Fractionalizer {
+    fn release_nft(&self, to: Address) {
+        let vault = self.env().self_address();
+        Cep78ContractRef::new(
+            self.env(),
+            self.nft_contract.get_or_revert_with(Error::NotLocked),
+        )
+        .transfer(
+            Maybe::Some(self.token_id.get_or_default()),
+            Maybe::None,
+            vault,
+            to,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use odra::host::{Deployer, HostEnv, HostRef};
+    use odra_modules::cep78::modalities::{
+        MetadataMutability, NFTIdentifierMode, NFTKind, NFTMetadataKind, OwnershipMode,
+    };
+    use odra_modules::cep78::token::Cep78HostRef;
+    use odra_modules::cep78::utils::InitArgsBuilder;
+
+    const CEP78_METADATA: &str = r#"{
+        "name": "John Doe",
+        "token_uri": "https://www.barfoo.com",
+        "checksum": "940bffb3f2bba35f84313aa26da09ece3ad47045c6a1292c2bbd2df4ab1a55fb"
+    }"#;
+    const RESERVE_PRICE: u64 = 10_000;
+
+    /// Deploys a vault and a collection, and has account 1 lock its token in the vault for
+    /// `supply` shares.
+    fn setup(env: &HostEnv, supply: u64) -> (FractionalizerHostRef, Cep78HostRef) {
+        let mut vault = FractionalizerHostRef::deploy(
+            env,
+            FractionalizerInitArgs {
+                name: "Shares of John Doe".to_string(),
+                symbol: "JOHN".to_string(),
+                decimals: 0,
+            },
+        );
+        let init_args = InitArgsBuilder::default()
+            .collection_name("Vaulted Collection".to_string())
+            .collection_symbol("VLT".to_string())
+            .total_token_supply(10)
+            .ownership_mode(OwnershipMode::Transferable)
+            .nft_metadata_kind(NFTMetadataKind::CEP78)
+            .identifier_mode(NFTIdentifierMode::Ordinal)
+            .nft_kind(NFTKind::Digital)
+            .metadata_mutability(MetadataMutability::Immutable)
+            .receipt_name("vault_receipt".to_string())
+            .build();
+        let mut nft = Cep78HostRef::deploy(env, init_args);
+        let curator = env.get_account(1);
+        nft.mint(curator, CEP78_METADATA.to_string(), Maybe::None);
+
+        env.set_caller(curator);
+        nft.approve(*vault.address(), Maybe::Some(0), Maybe::None);
+        vault.fractionalize(
+            *nft.address(),
+            0,
+            U256::from(supply),
+            U512::from(RESERVE_PRICE),
+        );
+        (vault, nft)
+    }
+
+    #[test]
+    fn buyout_and_redemption() {
+        let env = odra_test::env();
+        let (mut vault, nft) = setup(&env, 1_000);
+        let (curator, alice, buyer) = (env.get_account(1), env.get_account(2), env.get_account(3));
+        assert_eq!(nft.owner_of(Maybe::Some(0), Maybe::None), *vault.address());
+        assert_eq!(vault.balance_of(&curator), U256::from(1_000));
+        assert_eq!(
+            vault.try_fractionalize(*nft.address(), 0, U256::one(), U512::one()),
+            Err(Error::AlreadyFractionalized.into())
+        );
+        vault.transfer(&alice, &U256::from(250));
+        assert_eq!(vault.try_redeem(), Err(Error::NotBoughtOut.into()));
+
+        env.set_caller(buyer);
+        assert_eq!(
+            vault
+                .with_tokens(U512::from(RESERVE_PRICE - 1))
+                .try_buyout(),
+            Err(Error::IncorrectPayment.into())
+        );
+        vault.with_tokens(U512::from(RESERVE_PRICE)).buyout();
+        assert_eq!(nft.owner_of(Maybe::Some(0), Maybe::None), buyer);
+        assert_eq!(vault.state(), VaultState::BoughtOut);
+        assert_eq!(vault.try_redeem(), Err(Error::NoShares.into()));
+
+        // Each shareholder gets their part of the price
+        env.set_caller(alice);
+        let balance = env.balance_of(&alice);
+        vault.redeem();
+        assert_eq!(env.balance_of(&alice), balance + U512::from(2_500));
+        assert_eq!(vault.balance_of(&alice), U256::zero());
+
+        env.set_caller(curator);
+        let balance = env.balance_of(&curator);
+        vault.redeem();
+        assert_eq!(env.balance_of(&curator), balance + U512::from(7_500));
+        assert_eq!(vault.total_supply(), U256::zero());
+        assert_eq!(env.balance_of(vault.address()), U512::zero());
+    }
+
+    #[test]
+    fn rounding_leaves_nothing_behind() {
+        let env = odra_test::env();
+        let (mut vault, _) = setup(&env, 3);
+        let (curator, alice, bob) = (env.get_account(1), env.get_account(2), env.get_account(3));
+        vault.transfer(&alice, &U256::one());
+        vault.transfer(&bob, &U256::one());
+
+        env.set_caller(env.get_account(4));
+        vault.with_tokens(U512::from(RESERVE_PRICE)).buyout();
+
+        // 10,000 motes don't split evenly into 3 shares, the last redeemer gets the remainder
+        for (account, amount) in [(alice, 3_333), (bob, 3_333), (curator, 3_334)] {
+            env.set_caller(account);
+            let balance = env.balance_of(&account);
+            vault.redeem();
+            assert_eq!(env.balance_of(&account), balance + U512::from(amount));
+        }
+        assert_eq!(vault.get_proceeds(), U512::zero());
+    }
+
+    #[test]
+    fn reclaim_with_every_share() {
+        let env = odra_test::env();
+        let (mut vault, nft) = setup(&env, 1_000);
+        let (curator, alice) = (env.get_account(1), env.get_account(2));
+
+        vault.transfer(&alice, &U256::one());
+        assert_eq!(vault.try_reclaim(), Err(Error::NotAllShares.into()));
+
+        env.set_caller(alice);
+        vault.transfer(&curator, &U256::one());
+        env.set_caller(curator);
+        vault.reclaim();
+        assert_eq!(nft.owner_of(Maybe::Some(0), Maybe::None), curator);
+        assert_eq!(vault.total_supply(), U256::zero());
+        assert_eq!(
+            vault.with_tokens(U512::from(RESERVE_PRICE)).try_buyout(),
+            Err(Error::NotLocked.into())
+        );
+    }
+}
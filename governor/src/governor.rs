@@ -0,0 +1,502 @@
+use odra::casper_types::bytesrepr::Bytes;
+use odra::casper_types::{U256, U512};
+use odra::prelude::*;
+use odra::{Address, ContractRef, Mapping, Var};
+use odra_modules::cep18_token::Cep18ContractRef;
+
+/// The entry points of the timelock controller the governor uses.
+#[odra::external_contract]
+pub trait TimelockController {
+    fn queue(
+        &mut self,
+        target: Address,
+        entry_point: String,
+        args: Bytes,
+        value: U512,
+        delay: u64,
+    ) -> u32;
+    fn execute(&mut self, id: u32);
+    fn get_min_delay(&self) -> u64;
+}
+
+#[odra::odra_error]
+/// Errors that may occur during the contract execution.
+pub enum Error {
+    /// There's no proposal with the given ID.
+    ProposalNotFound = 1,
+    /// Votes can only be cast while the proposal is active.
+    NotActive = 2,
+    /// Each holder votes once per proposal.
+    AlreadyVoted = 3,
+    /// Only succeeded proposals can be queued.
+    NotSucceeded = 4,
+    /// Only queued proposals can be executed.
+    NotQueued = 5,
+    /// Votes stay locked until voting ends.
+    VotingNotEnded = 6,
+    /// The caller has no locked votes on the proposal.
+    NothingToWithdraw = 7,
+    /// Votes must carry some weight.
+    ZeroWeight = 8,
+}
+
+#[odra::odra_type]
+pub enum ProposalState {
+    /// Created, voting hasn't started yet.
+    Pending,
+    /// Voting is open.
+    Active,
+    /// Voting ended with a majority in favour and the quorum reached.
+    Succeeded,
+    /// Voting ended without a majority in favour or the quorum.
+    Defeated,
+    /// Waiting in the timelock.
+    Queued,
+    /// Executed through the timelock.
+    Executed,
+}
+
+#[odra::odra_type]
+/// A call to make on behalf of the DAO, together with the votes on it.
+pub struct Proposal {
+    pub proposer: Address,
+    pub target: Address,
+    pub entry_point: String,
+    /// Serialized `RuntimeArgs` of the call.
+    pub args: Bytes,
+    /// CSPR attached to the call, paid from the timelock's balance.
+    pub value: U512,
+    pub description: String,
+    /// Block time voting starts at.
+    pub vote_start: u64,
+    /// Block time voting ends at.
+    pub vote_end: u64,
+    pub for_votes: U256,
+    pub against_votes: U256,
+    /// ID of the call in the timelock, once queued.
+    pub timelock_id: Option<u32>,
+    pub executed: bool,
+}
+
+#[odra::event]
+/// Emitted when a proposal is created.
+pub struct ProposalCreated {
+    pub id: u32,
+    pub proposer: Address,
+    pub description: String,
+}
+
+#[odra::event]
+/// Emitted when a holder votes.
+pub struct VoteCast {
+    pub id: u32,
+    pub voter: Address,
+    pub support: bool,
+    pub weight: U256,
+}
+
+#[odra::event]
+/// Emitted when a succeeded proposal is queued in the timelock.
+pub struct ProposalQueued {
+    pub id: u32,
+    pub timelock_id: u32,
+}
+
+#[odra::event]
+/// Emitted when a proposal is executed.
+pub struct ProposalExecuted {
+    pub id: u32,
+}
+
+#[odra::module(events = [ProposalCreated, VoteCast, ProposalQueued, ProposalExecuted], errors = Error)]
+/// Lets the holders of a CEP-18 token decide on calls that are executed through a timelock.
+pub struct Governor {
+    /// CEP-18 token votes are cast with.
+    token: Var<Address>,
+    /// Timelock controller the governor is a proposer and executor of.
+    timelock: Var<Address>,
+    /// Time between creating a proposal and the start of voting, in milliseconds.
+    voting_delay: Var<u64>,
+    /// How long voting lasts, in milliseconds.
+    voting_period: Var<u64>,
+    /// Votes, for and against, needed for a proposal to succeed.
+    quorum: Var<U256>,
+    proposals: Mapping<u32, Proposal>,
+    /// Number of created proposals, used as the next proposal ID.
+    proposal_count: Var<u32>,
+    /// Tokens each holder locked by voting on a proposal.
+    votes: Mapping<(u32, Address), U256>,
+}
+
+#[odra::module]
+impl Governor {
+    /// Initializes the governor with the voting token, the timelock and the voting parameters.
+    pub fn init(
+        &mut self,
+        token: Address,
+        timelock: Address,
+        voting_delay: u64,
+        voting_period: u64,
+        quorum: U256,
+    ) {
+        self.token.set(token);
+        self.timelock.set(timelock);
+        self.voting_delay.set(voting_delay);
+        self.voting_period.set(voting_period);
+        self.quorum.set(quorum);
+    }
+
+    /**********
+     * TRANSACTIONS
+     **********/
+
+    /// Creates a proposal to call `entry_point` of `target`; anyone can propose.
+    pub fn propose(
+        &mut self,
+        target: Address,
+        entry_point: String,
+        args: Bytes,
+        value: U512,
+        description: String,
+    ) -> u32 {
+        let proposer = self.env().caller();
+        let vote_start = self.env().get_block_time() + self.voting_delay.get_or_default();
+        let id = self.proposal_count.get_or_default();
+        self.proposal_count.set(id + 1);
+        self.proposals.set(
+            &id,
+            Proposal {
+                proposer,
+                target,
+                entry_point,
+                args,
+                value,
+                description: description.clone(),
+                vote_start,
+                vote_end: vote_start + self.voting_period.get_or_default(),
+                for_votes: U256::zero(),
+                against_votes: U256::zero(),
+                timelock_id: None,
+                executed: false,
+            },
+        );
+        self.env().emit_event(ProposalCreated {
+            id,
+            proposer,
+            description,
+        });
+        id
+    }
+
+    /// Votes with `weight` tokens, which are locked in the governor until voting ends.
+    /// The voter has to approve the governor to spend them beforehand.
+    pub fn cast_vote(&mut self, id: u32, support: bool, weight: U256) {
+        let mut proposal = self.get_or_revert(id);
+        if self.state_of(&proposal) != ProposalState::Active {
+            self.env().revert(Error::NotActive);
+        }
+        if weight.is_zero() {
+            self.env().revert(Error::ZeroWeight);
+        }
+        let voter = self.env().caller();
+        if !self.votes.get_or_default(&(id, voter)).is_zero() {
+            self.env().revert(Error::AlreadyVoted);
+        }
+
+        // Locking the tokens keeps them from being used to vote twice
+        self.token_ref()
+            .transfer_from(&voter, &self.env().self_address(), &weight);
+        self.votes.set(&(id, voter), weight);
+        if support {
+            proposal.for_votes += weight;
+        } else {
+            proposal.against_votes += weight;
+        }
+        self.proposals.set(&id, proposal);
+        self.env().emit_event(VoteCast {
+            id,
+            voter,
+            support,
+            weight,
+        });
+    }
+
+    /// Returns the tokens the caller locked by voting, once voting has ended.
+    pub fn withdraw_votes(&mut self, id: u32) {
+        let proposal = self.get_or_revert(id);
+        if self.env().get_block_time() < proposal.vote_end {
+            self.env().revert(Error::VotingNotEnded);
+        }
+        let voter = self.env().caller();
+        let weight = self.votes.get_or_default(&(id, voter));
+        if weight.is_zero() {
+            self.env().revert(Error::NothingToWithdraw);
+        }
+        self.votes.set(&(id, voter), U256::zero());
+        self.token_ref().transfer(&voter, &weight);
+    }
+
+    /// Queues a succeeded proposal in the timelock; anyone can call it.
+    pub fn queue(&mut self, id: u32) {
+        let mut proposal = self.get_or_revert(id);
+        if self.state_of(&proposal) != ProposalState::Succeeded {
+            self.env().revert(Error::NotSucceeded);
+        }
+        let mut timelock = self.timelock_ref();
+        let delay = timelock.get_min_delay();
+        let timelock_id = timelock.queue(
+            proposal.target,
+            proposal.entry_point.clone(),
+            proposal.args.clone(),
+            proposal.value,
+            delay,
+        );
+        proposal.timelock_id = Some(timelock_id);
+        self.proposals.set(&id, proposal);
+        self.env().emit_event(ProposalQueued { id, timelock_id });
+    }
+
+    /// Executes a queued proposal once the timelock delay has passed; anyone can call it.
+    pub fn execute(&mut self, id: u32) {
+        let mut proposal = self.get_or_revert(id);
+        if self.state_of(&proposal) != ProposalState::Queued {
+            self.env().revert(Error::NotQueued);
+        }
+        proposal.executed = true;
+        let timelock_id = proposal.timelock_id.unwrap_or_revert(&self.env());
+        self.proposals.set(&id, proposal);
+        self.timelock_ref().execute(timelock_id);
+        self.env().emit_event(ProposalExecuted { id });
+    }
+
+    /**********
+     * QUERIES
+     **********/
+
+    /// Returns a proposal.
+    pub fn get_proposal(&self, id: u32) -> Option<Proposal> {
+        self.proposals.get(&id)
+    }
+
+    /// Returns the current stage of a proposal.
+    pub fn state(&self, id: u32) -> ProposalState {
+        self.state_of(&self.get_or_revert(id))
+    }
+
+    /// Returns the tokens a holder has locked by voting on a proposal.
+    pub fn votes_of(&self, id: u32, voter: Address) -> U256 {
+        self.votes.get_or_default(&(id, voter))
+    }
+
+    /// Returns the number of created proposals.
+    pub fn get_proposal_count(&self) -> u32 {
+        self.proposal_count.get_or_default()
+    }
+
+    /**********
+     * INTERNAL
+     **********/
+
+    /// Derives the stage of a proposal from the block time, its votes and its progress.
+    fn state_of(&self, proposal: &Proposal) -> ProposalState {
+        let now = self.env().get_block_time();
+        if proposal.executed {
+            ProposalState::Executed
+        } else if proposal.timelock_id.is_some() {
+            ProposalState::Queued
+        } else if now < proposal.vote_start {
+            ProposalState::Pending
+        } else if now < proposal.vote_end {
+            ProposalState::Active
+        } else if proposal.for_votes > proposal.against_votes
+            && proposal.for_votes + proposal.against_votes >= self.quorum.get_or_default()
+        {
+            ProposalState::Succeeded
+        } else {
+            ProposalState::Defeated
+        }
+    }
+
+    fn get_or_revert(&self, id: u32) -> Proposal {
+        self.proposals
+            .get(&id)
+            .unwrap_or_revert_with(&self.env(), Error::ProposalNotFound)
+    }
+
+    fn token_ref(&self) -> Cep18ContractRef {
+        Cep18ContractRef::new(self.env(), self.token.get().unwrap_or_revert(&self.env()))
+    }
+
+    fn timelock_ref(&self) -> TimelockControllerContractRef {
+        TimelockControllerContractRef::new(
+            self.env(),
+            self.timelock.get().unwrap_or_revert(&self.env()),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use odra::casper_types::bytesrepr::ToBytes;
+    use odra::casper_types::runtime_args;
+    use odra::host::{Deployer, HostEnv, HostRef};
+    use odra_modules::cep18_token::{Cep18HostRef, Cep18InitArgs};
+    use timelock::timelock::{TimelockHostRef, TimelockInitArgs, EXECUTOR_ROLE, PROPOSER_ROLE};
+
+    const VOTING_DELAY: u64 = 100;
+    const VOTING_PERIOD: u64 = 1_000;
+    const TIMELOCK_DELAY: u64 = 1_000;
+
+    /// Deploys the voting token held by accounts 1 to 3 (600, 300 and 100 tokens),
+    /// a timelock and a governor with a quorum of 500 tokens.
+    fn setup(env: &HostEnv) -> (GovernorHostRef, Cep18HostRef, TimelockHostRef) {
+        let mut token = Cep18HostRef::deploy(
+            env,
+            Cep18InitArgs {
+                symbol: "VOTE".to_string(),
+                name: "Voting Token".to_string(),
+                decimals: 0,
+                initial_supply: U256::from(1_000),
+                admin_list: vec![],
+                minter_list: vec![],
+                modality: None,
+            },
+        );
+        token.transfer(&env.get_account(1), &U256::from(600));
+        token.transfer(&env.get_account(2), &U256::from(300));
+        token.transfer(&env.get_account(3), &U256::from(100));
+
+        let mut timelock = TimelockHostRef::deploy(
+            env,
+            TimelockInitArgs {
+                min_delay: TIMELOCK_DELAY,
+                proposers: vec![],
+                executors: vec![],
+            },
+        );
+        let governor = GovernorHostRef::deploy(
+            env,
+            GovernorInitArgs {
+                token: *token.address(),
+                timelock: *timelock.address(),
+                voting_delay: VOTING_DELAY,
+                voting_period: VOTING_PERIOD,
+                quorum: U256::from(500),
+            },
+        );
+        timelock.grant_role(&PROPOSER_ROLE, governor.address());
+        timelock.grant_role(&EXECUTOR_ROLE, governor.address());
+        (governor, token, timelock)
+    }
+
+    /// Proposes to change the timelock's minimum delay.
+    fn propose_update_delay(governor: &mut GovernorHostRef, timelock: &TimelockHostRef) -> u32 {
+        let args = runtime_args! { "min_delay" => 2_000u64 };
+        governor.propose(
+            *timelock.address(),
+            "update_delay".to_string(),
+            Bytes::from(args.to_bytes().unwrap()),
+            U512::zero(),
+            "Double the timelock delay".to_string(),
+        )
+    }
+
+    fn vote(
+        env: &HostEnv,
+        governor: &mut GovernorHostRef,
+        token: &mut Cep18HostRef,
+        account: usize,
+        id: u32,
+        support: bool,
+        weight: u64,
+    ) {
+        env.set_caller(env.get_account(account));
+        token.approve(governor.address(), &U256::from(weight));
+        governor.cast_vote(id, support, U256::from(weight));
+    }
+
+    #[test]
+    fn proposal_lifecycle() {
+        let env = odra_test::env();
+        let (mut governor, mut token, timelock) = setup(&env);
+        let id = propose_update_delay(&mut governor, &timelock);
+        assert_eq!(governor.state(id), ProposalState::Pending);
+        assert_eq!(
+            governor.try_cast_vote(id, true, U256::one()),
+            Err(Error::NotActive.into())
+        );
+
+        env.advance_block_time(VOTING_DELAY);
+        assert_eq!(governor.state(id), ProposalState::Active);
+        vote(&env, &mut governor, &mut token, 1, id, true, 600);
+        vote(&env, &mut governor, &mut token, 2, id, false, 300);
+        assert_eq!(
+            governor.try_cast_vote(id, true, U256::one()),
+            Err(Error::AlreadyVoted.into())
+        );
+        assert!(env.emitted_event(
+            governor.address(),
+            &VoteCast {
+                id,
+                voter: env.get_account(2),
+                support: false,
+                weight: U256::from(300)
+            }
+        ));
+
+        // Votes stay locked while voting is open
+        assert_eq!(token.balance_of(&env.get_account(1)), U256::zero());
+        env.set_caller(env.get_account(1));
+        assert_eq!(
+            governor.try_withdraw_votes(id),
+            Err(Error::VotingNotEnded.into())
+        );
+        env.advance_block_time(VOTING_PERIOD);
+        assert_eq!(governor.state(id), ProposalState::Succeeded);
+        governor.withdraw_votes(id);
+        assert_eq!(token.balance_of(&env.get_account(1)), U256::from(600));
+
+        governor.queue(id);
+        assert_eq!(governor.state(id), ProposalState::Queued);
+        assert!(governor.try_execute(id).is_err());
+        env.advance_block_time(TIMELOCK_DELAY);
+        governor.execute(id);
+        assert_eq!(governor.state(id), ProposalState::Executed);
+        assert_eq!(timelock.get_min_delay(), 2_000);
+        assert!(env.emitted_event(governor.address(), &ProposalExecuted { id }));
+    }
+
+    #[test]
+    fn defeated_proposals() {
+        let env = odra_test::env();
+        let (mut governor, mut token, timelock) = setup(&env);
+
+        // Without the quorum
+        let id = propose_update_delay(&mut governor, &timelock);
+        env.advance_block_time(VOTING_DELAY);
+        vote(&env, &mut governor, &mut token, 3, id, true, 100);
+        env.advance_block_time(VOTING_PERIOD);
+        assert_eq!(governor.state(id), ProposalState::Defeated);
+        assert_eq!(governor.try_queue(id), Err(Error::NotSucceeded.into()));
+        assert_eq!(governor.try_execute(id), Err(Error::NotQueued.into()));
+
+        // Without a majority
+        let id = propose_update_delay(&mut governor, &timelock);
+        env.advance_block_time(VOTING_DELAY);
+        vote(&env, &mut governor, &mut token, 1, id, false, 600);
+        vote(&env, &mut governor, &mut token, 2, id, true, 300);
+        env.advance_block_time(VOTING_PERIOD);
+        assert_eq!(governor.state(id), ProposalState::Defeated);
+
+        env.set_caller(env.get_account(3));
+        assert_eq!(
+            governor.try_withdraw_votes(id),
+            Err(Error::NothingToWithdraw.into())
+        );
+        assert_eq!(
+            governor.try_withdraw_votes(7),
+            Err(Error::ProposalNotFound.into())
+        );
+    }
+}
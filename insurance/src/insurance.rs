@@ -0,0 +1,458 @@
+use odra::casper_types::U512;
+use odra::module::SubModule;
+use odra::prelude::*;
+use odra::{Address, Mapping, Var};
+use odra_modules::access::{AccessControl, Role, DEFAULT_ADMIN_ROLE};
+
+/// Role allowed to approve and reject claims. Assessors are told apart from the admin, whose
+/// role id is all zeros, by any other id.
+pub const ASSESSOR_ROLE: Role = [1; 32];
+
+#[odra::odra_error]
+/// Errors that may occur during the contract execution.
+pub enum Error {
+    /// Cover has to be positive and at most the pool's limit per policy.
+    InvalidCover = 1,
+    /// The attached CSPR doesn't match the premium.
+    IncorrectPremium = 2,
+    /// The pool couldn't pay out all of its policies anymore.
+    InsufficientCapital = 3,
+    /// There's no policy with the given ID.
+    PolicyNotFound = 4,
+    /// Only the policyholder can file a claim.
+    NotPolicyholder = 5,
+    /// Claims can't be filed after the policy expires.
+    PolicyExpired = 6,
+    /// The policy isn't active.
+    PolicyNotActive = 7,
+    /// No claim has been filed under the policy.
+    NoClaimFiled = 8,
+    /// Payouts can't exceed the policy's cover.
+    ExceedsCover = 9,
+    /// The policy hasn't expired yet.
+    PolicyNotExpired = 10,
+    /// Only the admin can withdraw capital.
+    NotAdmin = 11,
+}
+
+#[odra::odra_type]
+/// Lifecycle of a policy.
+pub enum PolicyStatus {
+    /// The policy covers its holder until it expires.
+    Active,
+    /// The holder filed a claim, waiting for an assessor.
+    ClaimFiled,
+    /// An assessor approved the claim and the holder was paid.
+    PaidOut,
+    /// The policy expired without a payout.
+    Expired,
+}
+
+#[odra::odra_type]
+pub struct Policy {
+    pub holder: Address,
+    /// Most the policy pays out.
+    pub cover: U512,
+    pub premium: U512,
+    pub expires_at: u64,
+    pub status: PolicyStatus,
+}
+
+#[odra::event]
+/// Emitted when a policy is sold.
+pub struct PolicyIssued {
+    pub policy_id: u32,
+    pub holder: Address,
+    pub cover: U512,
+    pub expires_at: u64,
+}
+
+#[odra::event]
+/// Emitted when a policyholder files a claim.
+pub struct ClaimFiled {
+    pub policy_id: u32,
+}
+
+#[odra::event]
+/// Emitted when an assessor approves a claim.
+pub struct ClaimApproved {
+    pub policy_id: u32,
+    pub assessor: Address,
+    pub payout: U512,
+}
+
+#[odra::event]
+/// Emitted when an assessor rejects a claim.
+pub struct ClaimRejected {
+    pub policy_id: u32,
+    pub assessor: Address,
+}
+
+#[odra::module(
+    events = [PolicyIssued, ClaimFiled, ClaimApproved, ClaimRejected],
+    errors = Error
+)]
+/// A pool selling cover against a parametric event, paid for by premiums and the admin's capital.
+pub struct InsurancePool {
+    access_control: SubModule<AccessControl>,
+    /// What the policies cover, e.g. "Less than 50 mm of rain in Nakuru in April".
+    covered_event: Var<String>,
+    /// Premium per policy, in basis points of its cover.
+    premium_rate_bps: Var<u32>,
+    /// How long policies last.
+    policy_period: Var<u64>,
+    /// Most cover a single policy can have.
+    max_cover: Var<U512>,
+    policies: Mapping<u32, Policy>,
+    policy_count: Var<u32>,
+    /// Cover of all policies that may still pay out.
+    locked_cover: Var<U512>,
+}
+
+#[odra::module]
+impl InsurancePool {
+    /// Initializes the pool; the deployer becomes the admin, who provides its capital and can
+    /// grant and revoke the assessor role.
+    pub fn init(
+        &mut self,
+        covered_event: String,
+        premium_rate_bps: u32,
+        policy_period: u64,
+        max_cover: U512,
+    ) {
+        self.covered_event.set(covered_event);
+        self.premium_rate_bps.set(premium_rate_bps);
+        self.policy_period.set(policy_period);
+        self.max_cover.set(max_cover);
+        self.access_control
+            .unchecked_grant_role(&DEFAULT_ADMIN_ROLE, &self.env().caller());
+    }
+
+    delegate! {
+        to self.access_control {
+            fn has_role(&self, role: &Role, address: &Address) -> bool;
+            fn grant_role(&mut self, role: &Role, address: &Address);
+            fn revoke_role(&mut self, role: &Role, address: &Address);
+            fn renounce_role(&mut self, role: &Role, address: &Address);
+        }
+    }
+
+    /**********
+     * TRANSACTIONS
+     **********/
+
+    /// Adds the attached CSPR to the pool's capital.
+    #[odra(payable)]
+    pub fn fund(&mut self) {}
+
+    /// Sends `amount` of capital to the admin, as long as the rest still covers every policy.
+    pub fn withdraw(&mut self, amount: U512) {
+        let admin = self.env().caller();
+        if !self.access_control.has_role(&DEFAULT_ADMIN_ROLE, &admin) {
+            self.env().revert(Error::NotAdmin);
+        }
+        if amount > self.free_capital() {
+            self.env().revert(Error::InsufficientCapital);
+        }
+        self.env().transfer_tokens(&admin, &amount);
+    }
+
+    /// Buys a policy paying out up to `cover` if the covered event happens before it expires.
+    /// The premium has to be attached. Returns the ID of the policy.
+    #[odra(payable)]
+    pub fn buy_policy(&mut self, cover: U512) -> u32 {
+        if cover.is_zero() || cover > self.max_cover.get_or_default() {
+            self.env().revert(Error::InvalidCover);
+        }
+        let premium = self.premium_for(cover);
+        if self.env().attached_value() != premium {
+            self.env().revert(Error::IncorrectPremium);
+        }
+        // The premium is already part of the balance
+        let locked_cover = self.locked_cover.get_or_default() + cover;
+        if self.env().self_balance() < locked_cover {
+            self.env().revert(Error::InsufficientCapital);
+        }
+
+        let holder = self.env().caller();
+        let expires_at = self.env().get_block_time() + self.policy_period.get_or_default();
+        let policy_id = self.policy_count.get_or_default();
+        self.policies.set(
+            &policy_id,
+            Policy {
+                holder,
+                cover,
+                premium,
+                expires_at,
+                status: PolicyStatus::Active,
+            },
+        );
+        self.policy_count.set(policy_id + 1);
+        self.locked_cover.set(locked_cover);
+        self.env().emit_event(PolicyIssued {
+            policy_id,
+            holder,
+            cover,
+            expires_at,
+        });
+        policy_id
+    }
+
+    /// Files a claim under an active policy, for an assessor to approve or reject. Only the
+    /// policyholder can call it, before the policy expires.
+    pub fn file_claim(&mut self, policy_id: u32) {
+        let mut policy = self.policy(policy_id);
+        if self.env().caller() != policy.holder {
+            self.env().revert(Error::NotPolicyholder);
+        }
+        if self.env().get_block_time() >= policy.expires_at {
+            self.env().revert(Error::PolicyExpired);
+        }
+        if policy.status != PolicyStatus::Active {
+            self.env().revert(Error::PolicyNotActive);
+        }
+
+        policy.status = PolicyStatus::ClaimFiled;
+        self.policies.set(&policy_id, policy);
+        self.env().emit_event(ClaimFiled { policy_id });
+    }
+
+    /// Approves a filed claim and pays the policyholder `payout`, up to the policy's cover.
+    /// Only assessors can call it.
+    pub fn approve_claim(&mut self, policy_id: u32, payout: U512) {
+        let assessor = self.env().caller();
+        self.access_control.check_role(&ASSESSOR_ROLE, &assessor);
+        let mut policy = self.policy(policy_id);
+        if policy.status != PolicyStatus::ClaimFiled {
+            self.env().revert(Error::NoClaimFiled);
+        }
+        if payout > policy.cover {
+            self.env().revert(Error::ExceedsCover);
+        }
+
+        policy.status = PolicyStatus::PaidOut;
+        self.release_cover(policy.cover);
+        let holder = policy.holder;
+        self.policies.set(&policy_id, policy);
+        if !payout.is_zero() {
+            self.env().transfer_tokens(&holder, &payout);
+        }
+        self.env().emit_event(ClaimApproved {
+            policy_id,
+            assessor,
+            payout,
+        });
+    }
+
+    /// Rejects a filed claim. The policy becomes active again, so the holder can claim if the
+    /// event happens later. Only assessors can call it.
+    pub fn reject_claim(&mut self, policy_id: u32) {
+        let assessor = self.env().caller();
+        self.access_control.check_role(&ASSESSOR_ROLE, &assessor);
+        let mut policy = self.policy(policy_id);
+        if policy.status != PolicyStatus::ClaimFiled {
+            self.env().revert(Error::NoClaimFiled);
+        }
+
+        policy.status = PolicyStatus::Active;
+        self.policies.set(&policy_id, policy);
+        self.env().emit_event(ClaimRejected {
+            policy_id,
+            assessor,
+        });
+    }
+
+    /// Marks an active policy that expired without a claim, releasing its cover. Anyone can
+    /// call it.
+    pub fn expire(&mut self, policy_id: u32) {
+        let mut policy = self.policy(policy_id);
+        if policy.status != PolicyStatus::Active {
+            self.env().revert(Error::PolicyNotActive);
+        }
+        if self.env().get_block_time() < policy.expires_at {
+            self.env().revert(Error::PolicyNotExpired);
+        }
+
+        policy.status = PolicyStatus::Expired;
+        self.release_cover(policy.cover);
+        self.policies.set(&policy_id, policy);
+    }
+
+    /**********
+     * QUERIES
+     **********/
+
+    /// Returns what the policies cover.
+    pub fn get_covered_event(&self) -> String {
+        self.covered_event.get_or_default()
+    }
+
+    /// Returns the premium of a policy with the given cover.
+    pub fn premium_for(&self, cover: U512) -> U512 {
+        cover * U512::from(self.premium_rate_bps.get_or_default()) / U512::from(10_000)
+    }
+
+    /// Returns a policy.
+    pub fn get_policy(&self, policy_id: u32) -> Option<Policy> {
+        self.policies.get(&policy_id)
+    }
+
+    /// Returns the cover of all policies that may still pay out.
+    pub fn get_locked_cover(&self) -> U512 {
+        self.locked_cover.get_or_default()
+    }
+
+    /// Returns the CSPR the pool holds beyond the cover of its policies.
+    pub fn free_capital(&self) -> U512 {
+        self.env()
+            .self_balance()
+            .saturating_sub(self.locked_cover.get_or_default())
+    }
+}
+
+impl InsurancePool {
+    fn policy(&self, policy_id: u32) -> Policy {
+        self.policies
+            .get(&policy_id)
+            .unwrap_or_revert_with(&self.env(), Error::PolicyNotFound)
+    }
+
+    fn release_cover(&mut self, cover: U512) {
+        self.locked_cover
+            .set(self.locked_cover.get_or_default() - cover);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use odra::host::{Deployer, HostEnv, HostRef};
+
+    const POLICY_PERIOD: u64 = 30 * 24 * 60 * 60 * 1_000; // thirty days
+    const RATE_BPS: u32 = 500;
+    const MAX_COVER: u64 = 10_000;
+
+    /// Deploys a pool administered by account 0, with account 1 as the assessor and 20,000 of
+    /// capital.
+    fn setup(env: &HostEnv) -> InsurancePoolHostRef {
+        let mut pool = InsurancePoolHostRef::deploy(
+            env,
+            InsurancePoolInitArgs {
+                covered_event: "Less than 50 mm of rain in Nakuru in April".to_string(),
+                premium_rate_bps: RATE_BPS,
+                policy_period: POLICY_PERIOD,
+                max_cover: U512::from(MAX_COVER),
+            },
+        );
+        pool.grant_role(&ASSESSOR_ROLE, &env.get_account(1));
+        pool.with_tokens(U512::from(20_000)).fund();
+        pool
+    }
+
+    fn buy(env: &HostEnv, pool: &mut InsurancePoolHostRef, holder: usize, cover: u64) -> u32 {
+        env.set_caller(env.get_account(holder));
+        let premium = pool.premium_for(U512::from(cover));
+        pool.with_tokens(premium).buy_policy(U512::from(cover))
+    }
+
+    #[test]
+    fn claims_are_assessed() {
+        let env = odra_test::env();
+        let mut pool = setup(&env);
+        let (assessor, farmer) = (env.get_account(1), env.get_account(2));
+
+        // 5% of the cover
+        assert_eq!(pool.premium_for(U512::from(10_000)), U512::from(500));
+        env.set_caller(farmer);
+        assert_eq!(
+            pool.with_tokens(U512::from(499))
+                .try_buy_policy(U512::from(10_000)),
+            Err(Error::IncorrectPremium.into())
+        );
+        assert_eq!(
+            pool.with_tokens(U512::from(550))
+                .try_buy_policy(U512::from(11_000)),
+            Err(Error::InvalidCover.into())
+        );
+        let id = buy(&env, &mut pool, 2, 10_000);
+        assert_eq!(pool.get_locked_cover(), U512::from(10_000));
+
+        // The farmer claims, an assessor checks the rainfall and rejects the claim
+        pool.file_claim(id);
+        assert_eq!(pool.try_file_claim(id), Err(Error::PolicyNotActive.into()));
+        assert!(pool.try_approve_claim(id, U512::from(10_000)).is_err());
+        env.set_caller(assessor);
+        pool.reject_claim(id);
+        assert_eq!(pool.get_policy(id).unwrap().status, PolicyStatus::Active);
+
+        // A later claim is approved, for part of the cover
+        env.set_caller(farmer);
+        pool.file_claim(id);
+        env.set_caller(assessor);
+        assert_eq!(
+            pool.try_approve_claim(id, U512::from(10_001)),
+            Err(Error::ExceedsCover.into())
+        );
+        let balance = env.balance_of(&farmer);
+        pool.approve_claim(id, U512::from(6_000));
+        assert_eq!(env.balance_of(&farmer), balance + U512::from(6_000));
+        assert_eq!(pool.get_policy(id).unwrap().status, PolicyStatus::PaidOut);
+        assert_eq!(pool.get_locked_cover(), U512::zero());
+        assert_eq!(
+            pool.try_approve_claim(id, U512::one()),
+            Err(Error::NoClaimFiled.into())
+        );
+        assert!(env.emitted_event(
+            pool.address(),
+            &ClaimApproved {
+                policy_id: id,
+                assessor,
+                payout: U512::from(6_000),
+            }
+        ));
+    }
+
+    #[test]
+    fn pool_stays_solvent() {
+        let env = odra_test::env();
+        let mut pool = setup(&env);
+
+        // 20,000 of capital and the premiums cover two full policies, but not three
+        let first = buy(&env, &mut pool, 2, 10_000);
+        buy(&env, &mut pool, 3, 10_000);
+        assert_eq!(pool.free_capital(), U512::from(1_000));
+        env.set_caller(env.get_account(4));
+        assert_eq!(
+            pool.with_tokens(U512::from(500))
+                .try_buy_policy(U512::from(10_000)),
+            Err(Error::InsufficientCapital.into())
+        );
+        buy(&env, &mut pool, 4, 1_000);
+
+        // Capital backing policies can't be withdrawn, and only the admin withdraws
+        assert_eq!(
+            pool.try_withdraw(U512::from(50)),
+            Err(Error::NotAdmin.into())
+        );
+        env.set_caller(env.get_account(0));
+        assert_eq!(
+            pool.try_withdraw(U512::from(51)),
+            Err(Error::InsufficientCapital.into())
+        );
+        pool.withdraw(U512::from(50));
+
+        // Expired policies release their cover
+        assert_eq!(pool.try_expire(first), Err(Error::PolicyNotExpired.into()));
+        env.advance_block_time(POLICY_PERIOD);
+        env.set_caller(env.get_account(2));
+        assert_eq!(pool.try_file_claim(first), Err(Error::PolicyExpired.into()));
+        pool.expire(first);
+        assert_eq!(
+            pool.get_policy(first).unwrap().status,
+            PolicyStatus::Expired
+        );
+        assert_eq!(pool.get_locked_cover(), U512::from(11_000));
+        assert_eq!(pool.free_capital(), U512::from(10_000));
+        assert_eq!(pool.try_expire(first), Err(Error::PolicyNotActive.into()));
+    }
+}
@@ -0,0 +1,621 @@
+use odra::casper_types::bytesrepr::{Bytes, ToBytes};
+use odra::casper_types::U512;
+use odra::module::SubModule;
+use odra::prelude::*;
+use odra::{Address, Mapping, Var};
+use odra_modules::access::Ownable;
+
+// Fees are expressed in basis points, 10_000 being the whole pot
+const MAX_FEE_BPS: u16 = 10_000;
+
+#[odra::odra_error]
+/// Errors that may occur during the contract execution.
+pub enum Error {
+    /// Tickets can only be bought in an open round before its deadline.
+    RoundNotOpen = 1,
+    /// The attached CSPR doesn't match the price of the tickets.
+    IncorrectPayment = 2,
+    /// The round is still selling tickets.
+    RoundStillOpen = 3,
+    /// The revealed seed doesn't match the commitment.
+    InvalidSeed = 4,
+    /// Only the winner can claim the prize.
+    NotWinner = 5,
+    /// The prize can no longer be claimed.
+    ClaimPeriodOver = 6,
+    /// The prize can still be claimed.
+    ClaimPeriodNotOver = 7,
+    /// There's no round with the given ID.
+    RoundNotFound = 8,
+    /// The winner of the round hasn't been drawn, or the prize is already gone.
+    NotDrawn = 9,
+    /// At least one ticket has to be bought.
+    ZeroTickets = 10,
+    /// The fee can't exceed the whole pot.
+    InvalidFee = 11,
+    /// The seed wasn't revealed in time, so the round can only be refunded.
+    RevealPeriodOver = 12,
+    /// The owner can still reveal the seed.
+    RevealPeriodNotOver = 13,
+    /// The caller has no tickets to refund in the round.
+    NothingToRefund = 14,
+}
+
+#[odra::odra_type]
+pub enum RoundState {
+    /// Selling tickets until the deadline.
+    Open,
+    /// The winner can claim the prize.
+    Drawn,
+    /// The winner has claimed the prize.
+    Claimed,
+    /// The prize was added to the jackpot of the next round.
+    RolledOver,
+    /// The seed wasn't revealed in time, buyers can get their tickets refunded.
+    Cancelled,
+}
+
+#[odra::odra_type]
+pub struct Round {
+    /// Hash of the seed the owner reveals to draw the winner.
+    pub commitment: [u8; 32],
+    /// Hash of all ticket purchases, mixed into the seed.
+    pub entropy: [u8; 32],
+    /// Block time ticket sales end at.
+    pub deadline: u64,
+    /// Block time the owner has to reveal the seed by.
+    pub reveal_deadline: u64,
+    /// Ticket sales plus the jackpot carried over from previous rounds.
+    pub pot: U512,
+    pub ticket_count: u32,
+    pub winner: Option<Address>,
+    /// Pot minus the owner's fee.
+    pub prize: U512,
+    /// Block time the prize can be claimed until.
+    pub claim_deadline: u64,
+    pub state: RoundState,
+}
+
+#[odra::event]
+/// Emitted when the owner starts a round.
+pub struct RoundStarted {
+    pub round_id: u32,
+    pub deadline: u64,
+    pub jackpot: U512,
+}
+
+#[odra::event]
+/// Emitted when tickets are bought.
+pub struct TicketsBought {
+    pub round_id: u32,
+    pub buyer: Address,
+    pub count: u32,
+}
+
+#[odra::event]
+/// Emitted when the winner of a round is drawn.
+pub struct WinnerDrawn {
+    pub round_id: u32,
+    pub winner: Address,
+    pub prize: U512,
+}
+
+#[odra::event]
+/// Emitted when the winner claims the prize.
+pub struct PrizeClaimed {
+    pub round_id: u32,
+    pub winner: Address,
+    pub prize: U512,
+}
+
+#[odra::event]
+/// Emitted when a prize is carried over to the next round.
+pub struct PrizeRolledOver {
+    pub round_id: u32,
+    pub amount: U512,
+}
+
+#[odra::event]
+/// Emitted when a round is cancelled because its seed wasn't revealed in time.
+pub struct RoundCancelled {
+    pub round_id: u32,
+}
+
+#[odra::event]
+/// Emitted when a buyer gets their tickets of a cancelled round refunded.
+pub struct TicketsRefunded {
+    pub round_id: u32,
+    pub buyer: Address,
+    pub amount: U512,
+}
+
+#[odra::module(
+    events = [
+        RoundStarted,
+        TicketsBought,
+        WinnerDrawn,
+        PrizeClaimed,
+        PrizeRolledOver,
+        RoundCancelled,
+        TicketsRefunded
+    ],
+    errors = Error
+)]
+/// A lottery selling tickets in rounds, with the winner drawn by the owner using commit-reveal.
+pub struct Lottery {
+    ticket_price: Var<U512>,
+    /// Owner's fee in basis points.
+    fee_bps: Var<u16>,
+    /// How long a round sells tickets, in milliseconds.
+    round_duration: Var<u64>,
+    /// How long the owner has to reveal the seed after ticket sales end, in milliseconds.
+    reveal_period: Var<u64>,
+    /// How long the winner has to claim the prize, in milliseconds.
+    claim_period: Var<u64>,
+    rounds: Mapping<u32, Round>,
+    /// Number of started rounds, the last one being the current one.
+    round_count: Var<u32>,
+    /// Buyer of each ticket, indexed by round and ticket number.
+    tickets: Mapping<(u32, u32), Address>,
+    /// Number of tickets each buyer holds in a round, to refund cancelled rounds.
+    tickets_bought: Mapping<(u32, Address), u32>,
+    /// Prizes rolled over to the next round.
+    jackpot: Var<U512>,
+    ownable: SubModule<Ownable>,
+}
+
+#[odra::module]
+impl Lottery {
+    /// Initializes the lottery; the deployer becomes the owner who runs the rounds and collects the fee.
+    pub fn init(
+        &mut self,
+        ticket_price: U512,
+        fee_bps: u16,
+        round_duration: u64,
+        reveal_period: u64,
+        claim_period: u64,
+    ) {
+        if fee_bps > MAX_FEE_BPS {
+            self.env().revert(Error::InvalidFee);
+        }
+        self.ticket_price.set(ticket_price);
+        self.fee_bps.set(fee_bps);
+        self.round_duration.set(round_duration);
+        self.reveal_period.set(reveal_period);
+        self.claim_period.set(claim_period);
+        self.ownable.init();
+    }
+
+    /**********
+     * TRANSACTIONS
+     **********/
+
+    /// Starts a new round, committing to the hash of the seed that will draw its winner.
+    /// A previous round whose seed wasn't revealed in time is cancelled first.
+    pub fn start_round(&mut self, commitment: [u8; 32]) -> u32 {
+        self.ownable.assert_owner(&self.env().caller());
+        let round_id = self.round_count.get_or_default();
+        if round_id > 0 {
+            let previous = self.get_or_revert(round_id - 1);
+            if previous.state == RoundState::Open {
+                if self.env().get_block_time() < previous.reveal_deadline {
+                    self.env().revert(Error::RoundStillOpen);
+                }
+                self.cancel(round_id - 1, previous);
+            }
+        }
+
+        let jackpot = self.jackpot.get_or_default();
+        self.jackpot.set(U512::zero());
+        let deadline = self.env().get_block_time() + self.round_duration.get_or_default();
+        self.rounds.set(
+            &round_id,
+            Round {
+                commitment,
+                entropy: [0u8; 32],
+                deadline,
+                reveal_deadline: deadline + self.reveal_period.get_or_default(),
+                pot: jackpot,
+                ticket_count: 0,
+                winner: None,
+                prize: U512::zero(),
+                claim_deadline: 0,
+                state: RoundState::Open,
+            },
+        );
+        self.round_count.set(round_id + 1);
+        self.env().emit_event(RoundStarted {
+            round_id,
+            deadline,
+            jackpot,
+        });
+        round_id
+    }
+
+    /// Buys `count` tickets in the current round; the attached CSPR must match their price.
+    #[odra(payable)]
+    pub fn buy_tickets(&mut self, count: u32) {
+        if count == 0 {
+            self.env().revert(Error::ZeroTickets);
+        }
+        let round_id = self.round_count.get_or_default().checked_sub(1);
+        let round_id = round_id.unwrap_or_revert_with(&self.env(), Error::RoundNotOpen);
+        let mut round = self.get_or_revert(round_id);
+        if round.state != RoundState::Open || self.env().get_block_time() >= round.deadline {
+            self.env().revert(Error::RoundNotOpen);
+        }
+        let price = self.ticket_price.get_or_default() * U512::from(count);
+        if self.env().attached_value() != price {
+            self.env().revert(Error::IncorrectPayment);
+        }
+
+        let buyer = self.env().caller();
+        for ticket in round.ticket_count..round.ticket_count + count {
+            self.tickets.set(&(round_id, ticket), buyer);
+        }
+        let bought = self.tickets_bought.get_or_default(&(round_id, buyer));
+        self.tickets_bought.set(&(round_id, buyer), bought + count);
+        round.ticket_count += count;
+        round.pot += price;
+
+        // Every purchase changes the outcome, so whoever knows the seed can't predict it alone
+        let mut entropy = round.entropy.to_vec();
+        entropy.extend(buyer.to_bytes().unwrap_or_revert(&self.env()));
+        entropy.extend(self.env().get_block_time().to_le_bytes());
+        round.entropy = self.env().hash(entropy);
+        self.rounds.set(&round_id, round);
+        self.env().emit_event(TicketsBought {
+            round_id,
+            buyer,
+            count,
+        });
+    }
+
+    /// Reveals the committed seed after the deadline, draws the winner and pays the owner's fee.
+    /// The seed has to be revealed before the reveal deadline, afterwards the round is refunded.
+    pub fn draw(&mut self, seed: Bytes) {
+        let owner = self.env().caller();
+        self.ownable.assert_owner(&owner);
+        let round_id = self.round_count.get_or_default().checked_sub(1);
+        let round_id = round_id.unwrap_or_revert_with(&self.env(), Error::RoundNotFound);
+        let mut round = self.get_or_revert(round_id);
+        if round.state != RoundState::Open {
+            self.env().revert(Error::RoundNotOpen);
+        }
+        if self.env().get_block_time() < round.deadline {
+            self.env().revert(Error::RoundStillOpen);
+        }
+        if self.env().get_block_time() >= round.reveal_deadline {
+            self.env().revert(Error::RevealPeriodOver);
+        }
+        if self.env().hash(&seed) != round.commitment {
+            self.env().revert(Error::InvalidSeed);
+        }
+
+        // Without tickets, the whole pot goes to the next round
+        if round.ticket_count == 0 {
+            let pot = round.pot;
+            self.roll_over(round_id, round, pot);
+            return;
+        }
+
+        let mut randomness = seed.to_vec();
+        randomness.extend(round.entropy);
+        let randomness = self.env().hash(randomness);
+        let mut number = [0u8; 8];
+        number.copy_from_slice(&randomness[..8]);
+        let ticket = (u64::from_le_bytes(number) % round.ticket_count as u64) as u32;
+        let winner = self
+            .tickets
+            .get(&(round_id, ticket))
+            .unwrap_or_revert(&self.env());
+
+        let fee = round.pot * U512::from(self.fee_bps.get_or_default()) / U512::from(MAX_FEE_BPS);
+        if !fee.is_zero() {
+            self.env().transfer_tokens(&owner, &fee);
+        }
+        round.winner = Some(winner);
+        round.prize = round.pot - fee;
+        round.claim_deadline = self.env().get_block_time() + self.claim_period.get_or_default();
+        round.state = RoundState::Drawn;
+        self.rounds.set(&round_id, round.clone());
+        self.env().emit_event(WinnerDrawn {
+            round_id,
+            winner,
+            prize: round.prize,
+        });
+    }
+
+    /// Pays the prize to the winner of a round, within the claim period.
+    pub fn claim(&mut self, round_id: u32) {
+        let mut round = self.drawn_round(round_id);
+        let winner = self.env().caller();
+        if round.winner != Some(winner) {
+            self.env().revert(Error::NotWinner);
+        }
+        if self.env().get_block_time() > round.claim_deadline {
+            self.env().revert(Error::ClaimPeriodOver);
+        }
+        round.state = RoundState::Claimed;
+        self.rounds.set(&round_id, round.clone());
+        self.env().transfer_tokens(&winner, &round.prize);
+        self.env().emit_event(PrizeClaimed {
+            round_id,
+            winner,
+            prize: round.prize,
+        });
+    }
+
+    /// Adds a prize that wasn't claimed in time to the next round's pot; anyone can call it.
+    pub fn rollover(&mut self, round_id: u32) {
+        let round = self.drawn_round(round_id);
+        if self.env().get_block_time() <= round.claim_deadline {
+            self.env().revert(Error::ClaimPeriodNotOver);
+        }
+        let prize = round.prize;
+        self.roll_over(round_id, round, prize);
+    }
+
+    /// Refunds the caller's tickets of a round whose seed wasn't revealed in time, cancelling
+    /// the round on the first refund.
+    pub fn refund(&mut self, round_id: u32) {
+        let mut round = self.get_or_revert(round_id);
+        if round.state == RoundState::Open {
+            if self.env().get_block_time() < round.reveal_deadline {
+                self.env().revert(Error::RevealPeriodNotOver);
+            }
+            round = self.cancel(round_id, round);
+        }
+        let buyer = self.env().caller();
+        let count = self.tickets_bought.get_or_default(&(round_id, buyer));
+        if round.state != RoundState::Cancelled || count == 0 {
+            self.env().revert(Error::NothingToRefund);
+        }
+        self.tickets_bought.set(&(round_id, buyer), 0);
+        let amount = self.ticket_price.get_or_default() * U512::from(count);
+        self.env().transfer_tokens(&buyer, &amount);
+        self.env().emit_event(TicketsRefunded {
+            round_id,
+            buyer,
+            amount,
+        });
+    }
+
+    /**********
+     * QUERIES
+     **********/
+
+    /// Returns a round.
+    pub fn get_round(&self, round_id: u32) -> Option<Round> {
+        self.rounds.get(&round_id)
+    }
+
+    /// Returns the number of started rounds.
+    pub fn get_round_count(&self) -> u32 {
+        self.round_count.get_or_default()
+    }
+
+    /// Returns the buyer of a ticket.
+    pub fn get_ticket(&self, round_id: u32, ticket: u32) -> Option<Address> {
+        self.tickets.get(&(round_id, ticket))
+    }
+
+    /// Returns the prizes waiting for the next round.
+    pub fn get_jackpot(&self) -> U512 {
+        self.jackpot.get_or_default()
+    }
+
+    /**********
+     * INTERNAL
+     **********/
+
+    fn get_or_revert(&self, round_id: u32) -> Round {
+        self.rounds
+            .get(&round_id)
+            .unwrap_or_revert_with(&self.env(), Error::RoundNotFound)
+    }
+
+    /// Returns a round whose prize is waiting to be claimed.
+    fn drawn_round(&self, round_id: u32) -> Round {
+        let round = self.get_or_revert(round_id);
+        if round.state != RoundState::Drawn {
+            self.env().revert(Error::NotDrawn);
+        }
+        round
+    }
+
+    /// Cancels a round whose seed wasn't revealed in time. Ticket sales stay in the contract
+    /// for the refunds, the jackpot it started with goes back to the jackpot.
+    fn cancel(&mut self, round_id: u32, mut round: Round) -> Round {
+        let sales = self.ticket_price.get_or_default() * U512::from(round.ticket_count);
+        self.jackpot.add(round.pot - sales);
+        round.state = RoundState::Cancelled;
+        self.rounds.set(&round_id, round.clone());
+        self.env().emit_event(RoundCancelled { round_id });
+        round
+    }
+
+    /// Moves `amount` from the round to the jackpot.
+    fn roll_over(&mut self, round_id: u32, mut round: Round, amount: U512) {
+        self.jackpot.add(amount);
+        round.state = RoundState::RolledOver;
+        self.rounds.set(&round_id, round);
+        self.env().emit_event(PrizeRolledOver { round_id, amount });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use odra::casper_types::crypto::blake2b;
+    use odra::host::{Deployer, HostEnv, HostRef};
+
+    const PRICE: u64 = 100;
+    const ROUND_DURATION: u64 = 1_000;
+    const REVEAL_PERIOD: u64 = 2_000;
+    const CLAIM_PERIOD: u64 = 5_000;
+    const SEED: &[u8] = b"owner's secret seed";
+
+    /// Deploys a lottery with a 10% fee and starts the first round.
+    fn setup(env: &HostEnv) -> LotteryHostRef {
+        let mut lottery = LotteryHostRef::deploy(
+            env,
+            LotteryInitArgs {
+                ticket_price: U512::from(PRICE),
+                fee_bps: 1_000,
+                round_duration: ROUND_DURATION,
+                reveal_period: REVEAL_PERIOD,
+                claim_period: CLAIM_PERIOD,
+            },
+        );
+        lottery.start_round(blake2b(SEED));
+        lottery
+    }
+
+    fn buy(env: &HostEnv, lottery: &mut LotteryHostRef, account: usize, count: u32) {
+        env.set_caller(env.get_account(account));
+        lottery
+            .with_tokens(U512::from(PRICE * count as u64))
+            .buy_tickets(count);
+    }
+
+    #[test]
+    fn draw_and_claim() {
+        let env = odra_test::env();
+        let mut lottery = setup(&env);
+        let owner = env.get_account(0);
+        buy(&env, &mut lottery, 1, 2);
+        buy(&env, &mut lottery, 2, 1);
+        assert_eq!(lottery.get_ticket(0, 2), Some(env.get_account(2)));
+
+        env.set_caller(owner);
+        assert_eq!(
+            lottery.try_draw(Bytes::from(SEED.to_vec())),
+            Err(Error::RoundStillOpen.into())
+        );
+        env.advance_block_time(ROUND_DURATION);
+        assert_eq!(
+            lottery.try_draw(Bytes::from(b"another seed".to_vec())),
+            Err(Error::InvalidSeed.into())
+        );
+        let owner_balance = env.balance_of(&owner);
+        lottery.draw(Bytes::from(SEED.to_vec()));
+        assert_eq!(env.balance_of(&owner), owner_balance + U512::from(30));
+
+        let round = lottery.get_round(0).unwrap();
+        let winner = round.winner.unwrap();
+        assert!(winner == env.get_account(1) || winner == env.get_account(2));
+        assert_eq!(round.prize, U512::from(270));
+
+        // Only the winner gets the prize
+        let loser = if winner == env.get_account(1) {
+            env.get_account(2)
+        } else {
+            env.get_account(1)
+        };
+        env.set_caller(loser);
+        assert_eq!(lottery.try_claim(0), Err(Error::NotWinner.into()));
+        env.set_caller(winner);
+        let winner_balance = env.balance_of(&winner);
+        lottery.claim(0);
+        assert_eq!(env.balance_of(&winner), winner_balance + U512::from(270));
+        assert_eq!(lottery.try_claim(0), Err(Error::NotDrawn.into()));
+    }
+
+    #[test]
+    fn unclaimed_prize_rolls_over() {
+        let env = odra_test::env();
+        let mut lottery = setup(&env);
+        buy(&env, &mut lottery, 1, 1);
+        env.advance_block_time(ROUND_DURATION);
+        env.set_caller(env.get_account(0));
+        lottery.draw(Bytes::from(SEED.to_vec()));
+
+        env.set_caller(env.get_account(2));
+        assert_eq!(
+            lottery.try_rollover(0),
+            Err(Error::ClaimPeriodNotOver.into())
+        );
+        env.advance_block_time(CLAIM_PERIOD + 1);
+        env.set_caller(env.get_account(1));
+        assert_eq!(lottery.try_claim(0), Err(Error::ClaimPeriodOver.into()));
+        lottery.rollover(0);
+        assert_eq!(lottery.get_jackpot(), U512::from(90));
+
+        // The next round starts with the jackpot in its pot
+        env.set_caller(env.get_account(0));
+        let round_id = lottery.start_round(blake2b(SEED));
+        assert_eq!(lottery.get_round(round_id).unwrap().pot, U512::from(90));
+        assert_eq!(lottery.get_jackpot(), U512::zero());
+    }
+
+    #[test]
+    fn invalid_purchases() {
+        let env = odra_test::env();
+        let mut lottery = setup(&env);
+        env.set_caller(env.get_account(1));
+        assert_eq!(
+            lottery.with_tokens(U512::from(99)).try_buy_tickets(1),
+            Err(Error::IncorrectPayment.into())
+        );
+        assert_eq!(lottery.try_buy_tickets(0), Err(Error::ZeroTickets.into()));
+
+        env.advance_block_time(ROUND_DURATION);
+        assert_eq!(
+            lottery.with_tokens(U512::from(PRICE)).try_buy_tickets(1),
+            Err(Error::RoundNotOpen.into())
+        );
+
+        // A round without tickets rolls over entirely
+        env.set_caller(env.get_account(0));
+        assert_eq!(
+            lottery.try_start_round(blake2b(SEED)),
+            Err(Error::RoundStillOpen.into())
+        );
+        lottery.draw(Bytes::from(SEED.to_vec()));
+        assert_eq!(lottery.get_round(0).unwrap().state, RoundState::RolledOver);
+    }
+
+    #[test]
+    fn unrevealed_round_is_refunded() {
+        let env = odra_test::env();
+        let mut lottery = setup(&env);
+        let (alice, bob) = (env.get_account(1), env.get_account(2));
+        buy(&env, &mut lottery, 1, 2);
+        buy(&env, &mut lottery, 2, 1);
+
+        // Refunds only open once the owner has missed the reveal deadline
+        env.advance_block_time(ROUND_DURATION);
+        assert_eq!(
+            lottery.try_refund(0),
+            Err(Error::RevealPeriodNotOver.into())
+        );
+        env.advance_block_time(REVEAL_PERIOD);
+        env.set_caller(env.get_account(0));
+        assert_eq!(
+            lottery.try_draw(Bytes::from(SEED.to_vec())),
+            Err(Error::RevealPeriodOver.into())
+        );
+
+        env.set_caller(alice);
+        let alice_balance = env.balance_of(&alice);
+        lottery.refund(0);
+        assert_eq!(env.balance_of(&alice), alice_balance + U512::from(200));
+        assert_eq!(lottery.get_round(0).unwrap().state, RoundState::Cancelled);
+        assert_eq!(lottery.try_refund(0), Err(Error::NothingToRefund.into()));
+
+        // The owner can start the next round while the remaining buyers collect their refunds
+        env.set_caller(env.get_account(0));
+        lottery.start_round(blake2b(SEED));
+        env.set_caller(bob);
+        lottery.refund(0);
+        assert!(env.emitted_event(
+            lottery.address(),
+            &TicketsRefunded {
+                round_id: 0,
+                buyer: bob,
+                amount: U512::from(PRICE),
+            }
+        ));
+    }
+}
@@ -0,0 +1,535 @@
+use odra::args::Maybe;
+use odra::casper_types::U512;
+use odra::module::SubModule;
+use odra::prelude::*;
+use odra::{Address, ContractRef, Mapping, Var};
+use odra_modules::access::{AccessControl, Role, DEFAULT_ADMIN_ROLE};
+use odra_modules::cep78::token::Cep78ContractRef;
+
+/// Role allowed to award points, accept discounts and offer rewards. The admin role is all
+/// zeros, so any other id keeps merchants apart from it.
+pub const MERCHANT_ROLE: Role = [1; 32];
+
+#[odra::odra_error]
+/// Errors that may occur during the contract execution.
+pub enum Error {
+    /// Discounts can only be paid to merchants.
+    NotMerchant = 1,
+    /// Awards and rewards need at least one point.
+    ZeroPoints = 2,
+    /// The customer doesn't have enough points that haven't expired.
+    InsufficientPoints = 3,
+    /// The points are worth more than the price.
+    DiscountTooLarge = 4,
+    /// The attached CSPR doesn't match the price minus the discount.
+    IncorrectPayment = 5,
+    /// There's no reward with the given ID.
+    RewardNotFound = 6,
+    /// The reward has already been redeemed or removed.
+    RewardUnavailable = 7,
+    /// Only the merchant who offered the reward can remove it.
+    NotRewardMerchant = 8,
+    /// The customer's points haven't expired.
+    NothingToExpire = 9,
+}
+
+#[odra::odra_type]
+#[derive(Default)]
+pub struct PointsAccount {
+    pub balance: u64,
+    /// Block time the whole balance expires at, unless more points are earned before.
+    pub expires_at: u64,
+}
+
+#[odra::odra_type]
+pub struct Reward {
+    pub merchant: Address,
+    pub nft_contract: Address,
+    pub token_id: u64,
+    /// Points the reward costs.
+    pub cost: u64,
+    pub available: bool,
+}
+
+#[odra::event]
+/// Emitted when a merchant awards points to a customer.
+pub struct PointsAwarded {
+    pub merchant: Address,
+    pub customer: Address,
+    pub points: u64,
+    pub expires_at: u64,
+}
+
+#[odra::event]
+/// Emitted when a customer pays a merchant with a discount.
+pub struct DiscountRedeemed {
+    pub customer: Address,
+    pub merchant: Address,
+    pub points: u64,
+    pub discount: U512,
+}
+
+#[odra::event]
+/// Emitted when a merchant offers an NFT as a reward.
+pub struct RewardAdded {
+    pub reward_id: u32,
+    pub merchant: Address,
+    pub cost: u64,
+}
+
+#[odra::event]
+/// Emitted when a customer redeems points for a reward.
+pub struct RewardRedeemed {
+    pub reward_id: u32,
+    pub customer: Address,
+}
+
+#[odra::event]
+/// Emitted when an expired balance is removed.
+pub struct PointsExpired {
+    pub customer: Address,
+    pub points: u64,
+}
+
+#[odra::module(
+    events = [PointsAwarded, DiscountRedeemed, RewardAdded, RewardRedeemed, PointsExpired],
+    errors = Error
+)]
+/// A loyalty program whose points merchants award and customers spend, but nobody can transfer.
+pub struct Loyalty {
+    access_control: SubModule<AccessControl>,
+    /// Discount a point is worth, in motes.
+    point_value: Var<U512>,
+    /// How long points last after the customer last earned any.
+    expiry_period: Var<u64>,
+    accounts: Mapping<Address, PointsAccount>,
+    /// Points that haven't been spent, or removed after expiring.
+    outstanding: Var<u64>,
+    rewards: Mapping<u32, Reward>,
+    reward_count: Var<u32>,
+}
+
+#[odra::module]
+impl Loyalty {
+    /// Initializes the program; the deployer becomes the admin who can grant and revoke the
+    /// merchant role.
+    pub fn init(&mut self, point_value: U512, expiry_period: u64) {
+        self.point_value.set(point_value);
+        self.expiry_period.set(expiry_period);
+        self.access_control
+            .unchecked_grant_role(&DEFAULT_ADMIN_ROLE, &self.env().caller());
+    }
+
+    delegate! {
+        to self.access_control {
+            fn has_role(&self, role: &Role, address: &Address) -> bool;
+            fn grant_role(&mut self, role: &Role, address: &Address);
+            fn revoke_role(&mut self, role: &Role, address: &Address);
+            fn renounce_role(&mut self, role: &Role, address: &Address);
+        }
+    }
+
+    /**********
+     * TRANSACTIONS
+     **********/
+
+    /// Awards points to a customer, and restarts the expiry period of their whole balance.
+    /// Only merchants can call it.
+    pub fn award(&mut self, customer: Address, points: u64) {
+        let merchant = self.env().caller();
+        self.access_control.check_role(&MERCHANT_ROLE, &merchant);
+        if points == 0 {
+            self.env().revert(Error::ZeroPoints);
+        }
+
+        // Points that expired before this award don't come back
+        let mut account = self.accounts.get_or_default(&customer);
+        if self.is_expired(&account) {
+            self.remove_expired(customer, &mut account);
+        }
+        account.balance += points;
+        account.expires_at = self.env().get_block_time() + self.expiry_period.get_or_default();
+        let expires_at = account.expires_at;
+        self.accounts.set(&customer, account);
+        self.outstanding
+            .set(self.outstanding.get_or_default() + points);
+        self.env().emit_event(PointsAwarded {
+            merchant,
+            customer,
+            points,
+            expires_at,
+        });
+    }
+
+    /// Pays `merchant` for a purchase of `price`, with `points` of the caller's taking
+    /// `points * point_value` off. The caller attaches the rest.
+    #[odra(payable)]
+    pub fn pay(&mut self, merchant: Address, price: U512, points: u64) {
+        if !self.access_control.has_role(&MERCHANT_ROLE, &merchant) {
+            self.env().revert(Error::NotMerchant);
+        }
+        let discount = self.point_value.get_or_default() * points;
+        if discount > price {
+            self.env().revert(Error::DiscountTooLarge);
+        }
+        let amount = self.env().attached_value();
+        if amount != price - discount {
+            self.env().revert(Error::IncorrectPayment);
+        }
+
+        let customer = self.env().caller();
+        self.spend(customer, points);
+        if !amount.is_zero() {
+            self.env().transfer_tokens(&merchant, &amount);
+        }
+        self.env().emit_event(DiscountRedeemed {
+            customer,
+            merchant,
+            points,
+            discount,
+        });
+    }
+
+    /// Offers an NFT as a reward costing `cost` points. The merchant has to approve the
+    /// contract to transfer the token first. Returns the ID of the reward.
+    pub fn add_reward(&mut self, nft_contract: Address, token_id: u64, cost: u64) -> u32 {
+        let merchant = self.env().caller();
+        self.access_control.check_role(&MERCHANT_ROLE, &merchant);
+        if cost == 0 {
+            self.env().revert(Error::ZeroPoints);
+        }
+
+        Cep78ContractRef::new(self.env(), nft_contract).transfer(
+            Maybe::Some(token_id),
+            Maybe::None,
+            merchant,
+            self.env().self_address(),
+        );
+        let reward_id = self.reward_count.get_or_default();
+        self.rewards.set(
+            &reward_id,
+            Reward {
+                merchant,
+                nft_contract,
+                token_id,
+                cost,
+                available: true,
+            },
+        );
+        self.reward_count.set(reward_id + 1);
+        self.env().emit_event(RewardAdded {
+            reward_id,
+            merchant,
+            cost,
+        });
+        reward_id
+    }
+
+    /// Takes back a reward nobody has redeemed. Only the merchant who offered it can.
+    pub fn remove_reward(&mut self, reward_id: u32) {
+        let mut reward = self.available_reward(reward_id);
+        if self.env().caller() != reward.merchant {
+            self.env().revert(Error::NotRewardMerchant);
+        }
+        reward.available = false;
+        self.rewards.set(&reward_id, reward.clone());
+        self.send_nft(&reward, reward.merchant);
+    }
+
+    /// Spends the caller's points on a reward, and transfers its NFT to them.
+    pub fn redeem_reward(&mut self, reward_id: u32) {
+        let mut reward = self.available_reward(reward_id);
+        let customer = self.env().caller();
+        self.spend(customer, reward.cost);
+        reward.available = false;
+        self.rewards.set(&reward_id, reward.clone());
+        self.send_nft(&reward, customer);
+        self.env().emit_event(RewardRedeemed {
+            reward_id,
+            customer,
+        });
+    }
+
+    /// Removes a customer's expired balance from the outstanding points. Anyone can call it.
+    pub fn expire(&mut self, customer: Address) {
+        let mut account = self.accounts.get_or_default(&customer);
+        if account.balance == 0 || !self.is_expired(&account) {
+            self.env().revert(Error::NothingToExpire);
+        }
+        self.remove_expired(customer, &mut account);
+        self.accounts.set(&customer, account);
+    }
+
+    /**********
+     * QUERIES
+     **********/
+
+    /// Returns the points a customer can spend.
+    pub fn balance_of(&self, customer: Address) -> u64 {
+        let account = self.accounts.get_or_default(&customer);
+        if self.is_expired(&account) {
+            0
+        } else {
+            account.balance
+        }
+    }
+
+    /// Returns the block time a customer's points expire at.
+    pub fn expires_at(&self, customer: Address) -> u64 {
+        self.accounts.get_or_default(&customer).expires_at
+    }
+
+    /// Returns the points that haven't been spent, or removed after expiring.
+    pub fn outstanding_points(&self) -> u64 {
+        self.outstanding.get_or_default()
+    }
+
+    /// Returns a reward.
+    pub fn get_reward(&self, reward_id: u32) -> Option<Reward> {
+        self.rewards.get(&reward_id)
+    }
+
+    /// Returns the number of rewards ever offered.
+    pub fn get_reward_count(&self) -> u32 {
+        self.reward_count.get_or_default()
+    }
+
+    /// Returns the discount a point is worth, in motes.
+    pub fn get_point_value(&self) -> U512 {
+        self.point_value.get_or_default()
+    }
+}
+
+impl Loyalty {
+    fn is_expired(&self, account: &PointsAccount) -> bool {
+        self.env().get_block_time() >= account.expires_at
+    }
+
+    fn remove_expired(&mut self, customer: Address, account: &mut PointsAccount) {
+        let points = account.balance;
+        account.balance = 0;
+        self.outstanding
+            .set(self.outstanding.get_or_default() - points);
+        if points > 0 {
+            self.env().emit_event(PointsExpired { customer, points });
+        }
+    }
+
+    fn spend(&mut self, customer: Address, points: u64) {
+        if points == 0 {
+            return;
+        }
+        let mut account = self.accounts.get_or_default(&customer);
+        if self.is_expired(&account) || account.balance < points {
+            self.env().revert(Error::InsufficientPoints);
+        }
+        account.balance -= points;
+        self.accounts.set(&customer, account);
+        self.outstanding
+            .set(self.outstanding.get_or_default() - points);
+    }
+
+    fn available_reward(&self, reward_id: u32) -> Reward {
+        let reward = self
+            .rewards
+            .get(&reward_id)
+            .unwrap_or_revert_with(&self.env(), Error::RewardNotFound);
+        if !reward.available {
+            self.env().revert(Error::RewardUnavailable);
+        }
+        reward
+    }
+
+    fn send_nft(&self, reward: &Reward, to: Address) {
+        Cep78ContractRef::new(self.env(), reward.nft_contract).transfer(
+            Maybe::Some(reward.token_id),
+            Maybe::None,
+            self.env().self_address(),
+            to,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use odra::host::{Deployer, HostEnv, HostRef};
+    use odra_modules::cep78::modalities::{
+        MetadataMutability, NFTIdentifierMode, NFTKind, NFTMetadataKind, OwnershipMode,
+    };
+    use odra_modules::cep78::token::Cep78HostRef;
+    use odra_modules::cep78::utils::InitArgsBuilder;
+
+    const CEP78_METADATA: &str = r#"{
+        "name": "Gold Member",
+        "token_uri": "https://www.barfoo.com",
+        "checksum": "940bffb3f2bba35f84313aa26da09ece3ad47045c6a1292c2bbd2df4ab1a55fb"
+    }"#;
+    const POINT_VALUE: u64 = 10;
+    const EXPIRY_PERIOD: u64 = 100_000;
+
+    /// Deploys the program, administered by account 0, with account 1 as a merchant.
+    fn setup(env: &HostEnv) -> LoyaltyHostRef {
+        let mut loyalty = LoyaltyHostRef::deploy(
+            env,
+            LoyaltyInitArgs {
+                point_value: U512::from(POINT_VALUE),
+                expiry_period: EXPIRY_PERIOD,
+            },
+        );
+        loyalty.grant_role(&MERCHANT_ROLE, &env.get_account(1));
+        loyalty
+    }
+
+    #[test]
+    fn earning_and_spending_discounts() {
+        let env = odra_test::env();
+        let mut loyalty = setup(&env);
+        let (merchant, customer) = (env.get_account(1), env.get_account(2));
+
+        env.set_caller(customer);
+        assert!(loyalty.try_award(customer, 500).is_err());
+        env.set_caller(merchant);
+        assert_eq!(
+            loyalty.try_award(customer, 0),
+            Err(Error::ZeroPoints.into())
+        );
+        loyalty.award(customer, 500);
+        assert_eq!(loyalty.balance_of(customer), 500);
+
+        // 30 points take 300 motes off a price of 1000
+        env.set_caller(customer);
+        assert_eq!(
+            loyalty
+                .with_tokens(U512::from(1_000))
+                .try_pay(merchant, U512::from(1_000), 30),
+            Err(Error::IncorrectPayment.into())
+        );
+        assert_eq!(
+            loyalty
+                .with_tokens(U512::from(700))
+                .try_pay(env.get_account(3), U512::from(1_000), 30),
+            Err(Error::NotMerchant.into())
+        );
+        assert_eq!(
+            loyalty.try_pay(merchant, U512::from(1_000), 101),
+            Err(Error::DiscountTooLarge.into())
+        );
+        let balance = env.balance_of(&merchant);
+        loyalty
+            .with_tokens(U512::from(700))
+            .pay(merchant, U512::from(1_000), 30);
+        assert_eq!(env.balance_of(&merchant), balance + U512::from(700));
+        assert_eq!(loyalty.balance_of(customer), 470);
+        assert_eq!(loyalty.outstanding_points(), 470);
+
+        // A whole purchase paid in points
+        loyalty.pay(merchant, U512::from(1_000), 100);
+        assert_eq!(
+            loyalty.try_pay(merchant, U512::from(4_000), 400),
+            Err(Error::InsufficientPoints.into())
+        );
+
+        // Once the role is revoked, the merchant can't award points anymore
+        env.set_caller(env.get_account(0));
+        loyalty.revoke_role(&MERCHANT_ROLE, &merchant);
+        env.set_caller(merchant);
+        assert!(loyalty.try_award(customer, 10).is_err());
+    }
+
+    #[test]
+    fn points_expire() {
+        let env = odra_test::env();
+        let mut loyalty = setup(&env);
+        let (merchant, customer) = (env.get_account(1), env.get_account(2));
+
+        env.set_caller(merchant);
+        loyalty.award(customer, 100);
+        env.advance_block_time(EXPIRY_PERIOD - 1);
+        assert_eq!(
+            loyalty.try_expire(customer),
+            Err(Error::NothingToExpire.into())
+        );
+
+        // Earning more restarts the period for the whole balance
+        loyalty.award(customer, 50);
+        env.advance_block_time(EXPIRY_PERIOD - 1);
+        assert_eq!(loyalty.balance_of(customer), 150);
+        env.advance_block_time(1);
+        assert_eq!(loyalty.balance_of(customer), 0);
+        assert_eq!(loyalty.outstanding_points(), 150);
+
+        env.set_caller(customer);
+        assert_eq!(
+            loyalty.try_pay(merchant, U512::from(1_000), 100),
+            Err(Error::InsufficientPoints.into())
+        );
+        env.set_caller(env.get_account(3));
+        loyalty.expire(customer);
+        assert_eq!(loyalty.outstanding_points(), 0);
+        assert_eq!(
+            loyalty.try_expire(customer),
+            Err(Error::NothingToExpire.into())
+        );
+
+        // Expired points don't come back with the next award
+        env.set_caller(merchant);
+        loyalty.award(customer, 20);
+        assert_eq!(loyalty.balance_of(customer), 20);
+        assert_eq!(loyalty.outstanding_points(), 20);
+    }
+
+    #[test]
+    fn nft_rewards() {
+        let env = odra_test::env();
+        let mut loyalty = setup(&env);
+        let (merchant, customer) = (env.get_account(1), env.get_account(2));
+        let init_args = InitArgsBuilder::default()
+            .collection_name("Rewards".to_string())
+            .collection_symbol("RWD".to_string())
+            .total_token_supply(10)
+            .ownership_mode(OwnershipMode::Transferable)
+            .nft_metadata_kind(NFTMetadataKind::CEP78)
+            .identifier_mode(NFTIdentifierMode::Ordinal)
+            .nft_kind(NFTKind::Digital)
+            .metadata_mutability(MetadataMutability::Immutable)
+            .receipt_name("rewards_receipt".to_string())
+            .build();
+        let mut nft = Cep78HostRef::deploy(&env, init_args);
+        for _ in 0..2 {
+            nft.mint(merchant, CEP78_METADATA.to_string(), Maybe::None);
+        }
+
+        env.set_caller(merchant);
+        for token_id in 0..2 {
+            nft.approve(*loyalty.address(), Maybe::Some(token_id), Maybe::None);
+            loyalty.add_reward(*nft.address(), token_id, 250);
+        }
+        loyalty.award(customer, 300);
+
+        env.set_caller(customer);
+        loyalty.redeem_reward(0);
+        assert_eq!(nft.owner_of(Maybe::Some(0), Maybe::None), customer);
+        assert_eq!(loyalty.balance_of(customer), 50);
+        assert_eq!(
+            loyalty.try_redeem_reward(0),
+            Err(Error::RewardUnavailable.into())
+        );
+        assert_eq!(
+            loyalty.try_redeem_reward(1),
+            Err(Error::InsufficientPoints.into())
+        );
+        assert_eq!(
+            loyalty.try_remove_reward(1),
+            Err(Error::NotRewardMerchant.into())
+        );
+
+        env.set_caller(merchant);
+        loyalty.remove_reward(1);
+        assert_eq!(nft.owner_of(Maybe::Some(1), Maybe::None), merchant);
+        assert_eq!(
+            loyalty.try_remove_reward(2),
+            Err(Error::RewardNotFound.into())
+        );
+    }
+}
@@ -0,0 +1,453 @@
+use odra::casper_types::bytesrepr::{deserialize_from_slice, Bytes};
+use odra::casper_types::{RuntimeArgs, U512};
+use odra::prelude::*;
+use odra::{Address, CallDef, Mapping, Var};
+
+#[odra::odra_error]
+/// Errors that may occur during the contract execution.
+pub enum Error {
+    /// Only owners can submit, confirm and revoke transactions.
+    NotOwner = 1,
+    /// The threshold must be between one and the number of owners.
+    InvalidThreshold = 2,
+    /// Each owner can be listed only once.
+    DuplicateOwner = 3,
+    /// There's no transaction with the given ID.
+    TransactionNotFound = 4,
+    /// The transaction has already been executed.
+    AlreadyExecuted = 5,
+    /// The owner has already confirmed the transaction.
+    AlreadyConfirmed = 6,
+    /// The owner hasn't confirmed the transaction.
+    NotConfirmed = 7,
+    /// The treasury doesn't hold enough CSPR to execute the transaction.
+    InsufficientBalance = 8,
+    /// The call arguments aren't serialized `RuntimeArgs`.
+    InvalidArgs = 9,
+}
+
+#[odra::odra_type]
+/// What a transaction does once it's executed.
+pub enum Operation {
+    /// Sends CSPR from the treasury.
+    Transfer { to: Address, amount: U512 },
+    /// Calls an entry point returning nothing, attaching `amount` CSPR.
+    /// `args` are the serialized `RuntimeArgs` of the call.
+    Call {
+        contract: Address,
+        entry_point: String,
+        args: Bytes,
+        amount: U512,
+    },
+}
+
+#[odra::odra_type]
+pub struct Transaction {
+    pub operation: Operation,
+    pub confirmations: u8,
+    pub executed: bool,
+}
+
+#[odra::event]
+/// Emitted when CSPR is deposited into the treasury.
+pub struct Deposited {
+    pub sender: Address,
+    pub amount: U512,
+}
+
+#[odra::event]
+/// Emitted when an owner submits a transaction.
+pub struct Submitted {
+    pub tx_id: u32,
+    pub owner: Address,
+    pub operation: Operation,
+}
+
+#[odra::event]
+/// Emitted when an owner confirms a transaction.
+pub struct Confirmed {
+    pub tx_id: u32,
+    pub owner: Address,
+}
+
+#[odra::event]
+/// Emitted when an owner withdraws their confirmation.
+pub struct Revoked {
+    pub tx_id: u32,
+    pub owner: Address,
+}
+
+#[odra::event]
+/// Emitted when a transaction reaches the threshold and is executed.
+pub struct Executed {
+    pub tx_id: u32,
+}
+
+#[odra::module(events = [Deposited, Submitted, Confirmed, Revoked, Executed], errors = Error)]
+/// A treasury controlled by a group of owners, M of which have to confirm every transaction.
+pub struct Multisig {
+    owners: Var<Vec<Address>>,
+    /// Number of confirmations needed to execute a transaction.
+    threshold: Var<u8>,
+    transactions: Mapping<u32, Transaction>,
+    /// Number of submitted transactions, used as the next transaction ID.
+    transaction_count: Var<u32>,
+    /// Whether an owner has confirmed a transaction.
+    confirmed: Mapping<(u32, Address), bool>,
+}
+
+#[odra::module]
+impl Multisig {
+    /// Initializes the treasury with its owners and the number of confirmations a transaction needs.
+    pub fn init(&mut self, owners: Vec<Address>, threshold: u8) {
+        if threshold == 0 || threshold as usize > owners.len() {
+            self.env().revert(Error::InvalidThreshold);
+        }
+        for (i, owner) in owners.iter().enumerate() {
+            if owners[..i].contains(owner) {
+                self.env().revert(Error::DuplicateOwner);
+            }
+        }
+        self.owners.set(owners);
+        self.threshold.set(threshold);
+    }
+
+    /**********
+     * TRANSACTIONS
+     **********/
+
+    /// Adds the attached CSPR to the treasury.
+    #[odra(payable)]
+    pub fn deposit(&mut self) {
+        self.env().emit_event(Deposited {
+            sender: self.env().caller(),
+            amount: self.env().attached_value(),
+        });
+    }
+
+    /// Submits a transaction and confirms it on behalf of the submitting owner.
+    pub fn submit(&mut self, operation: Operation) -> u32 {
+        let owner = self.assert_owner();
+        let tx_id = self.transaction_count.get_or_default();
+        self.transaction_count.set(tx_id + 1);
+        self.transactions.set(
+            &tx_id,
+            Transaction {
+                operation: operation.clone(),
+                confirmations: 0,
+                executed: false,
+            },
+        );
+        self.env().emit_event(Submitted {
+            tx_id,
+            owner,
+            operation,
+        });
+        self.confirm(tx_id);
+        tx_id
+    }
+
+    /// Confirms a transaction, executing it once it reaches the threshold.
+    pub fn confirm(&mut self, tx_id: u32) {
+        let owner = self.assert_owner();
+        let mut transaction = self.pending_transaction(tx_id);
+        if self.confirmed.get_or_default(&(tx_id, owner)) {
+            self.env().revert(Error::AlreadyConfirmed);
+        }
+        self.confirmed.set(&(tx_id, owner), true);
+        transaction.confirmations += 1;
+        self.env().emit_event(Confirmed { tx_id, owner });
+
+        if transaction.confirmations >= self.threshold.get_or_default() {
+            // Mark the transaction as executed before calling out
+            transaction.executed = true;
+            self.transactions.set(&tx_id, transaction.clone());
+            self.execute(transaction.operation);
+            self.env().emit_event(Executed { tx_id });
+        } else {
+            self.transactions.set(&tx_id, transaction);
+        }
+    }
+
+    /// Withdraws the caller's confirmation of a transaction that hasn't been executed yet.
+    pub fn revoke(&mut self, tx_id: u32) {
+        let owner = self.assert_owner();
+        let mut transaction = self.pending_transaction(tx_id);
+        if !self.confirmed.get_or_default(&(tx_id, owner)) {
+            self.env().revert(Error::NotConfirmed);
+        }
+        self.confirmed.set(&(tx_id, owner), false);
+        transaction.confirmations -= 1;
+        self.transactions.set(&tx_id, transaction);
+        self.env().emit_event(Revoked { tx_id, owner });
+    }
+
+    /**********
+     * QUERIES
+     **********/
+
+    /// Returns the owners of the treasury.
+    pub fn get_owners(&self) -> Vec<Address> {
+        self.owners.get_or_default()
+    }
+
+    /// Returns the number of confirmations a transaction needs.
+    pub fn get_threshold(&self) -> u8 {
+        self.threshold.get_or_default()
+    }
+
+    /// Returns a submitted transaction.
+    pub fn get_transaction(&self, tx_id: u32) -> Option<Transaction> {
+        self.transactions.get(&tx_id)
+    }
+
+    /// Returns the number of submitted transactions.
+    pub fn get_transaction_count(&self) -> u32 {
+        self.transaction_count.get_or_default()
+    }
+
+    /// Returns whether an owner has confirmed a transaction.
+    pub fn is_confirmed_by(&self, tx_id: u32, owner: Address) -> bool {
+        self.confirmed.get_or_default(&(tx_id, owner))
+    }
+
+    /**********
+     * INTERNAL
+     **********/
+
+    /// Reverts unless the caller is an owner, returning the caller.
+    fn assert_owner(&self) -> Address {
+        let caller = self.env().caller();
+        if !self.owners.get_or_default().contains(&caller) {
+            self.env().revert(Error::NotOwner);
+        }
+        caller
+    }
+
+    /// Returns a transaction that can still be confirmed or revoked.
+    fn pending_transaction(&self, tx_id: u32) -> Transaction {
+        let transaction = self
+            .transactions
+            .get(&tx_id)
+            .unwrap_or_revert_with(&self.env(), Error::TransactionNotFound);
+        if transaction.executed {
+            self.env().revert(Error::AlreadyExecuted);
+        }
+        transaction
+    }
+
+    fn execute(&self, operation: Operation) {
+        match operation {
+            Operation::Transfer { to, amount } => {
+                self.assert_balance(amount);
+                self.env().transfer_tokens(&to, &amount);
+            }
+            Operation::Call {
+                contract,
+                entry_point,
+                args,
+                amount,
+            } => {
+                self.assert_balance(amount);
+                let args: RuntimeArgs = deserialize_from_slice(args)
+                    .unwrap_or_revert_with(&self.env(), Error::InvalidArgs);
+                self.env().call_contract::<()>(
+                    contract,
+                    CallDef::new(entry_point, true, args).with_amount(amount),
+                );
+            }
+        }
+    }
+
+    fn assert_balance(&self, amount: U512) {
+        if self.env().self_balance() < amount {
+            self.env().revert(Error::InsufficientBalance);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use odra::casper_types::bytesrepr::ToBytes;
+    use odra::casper_types::{runtime_args, U256};
+    use odra::host::{Deployer, HostEnv, HostRef};
+    use odra_modules::cep18_token::{Cep18HostRef, Cep18InitArgs};
+
+    /// Deploys a 2-of-3 treasury owned by accounts 1 to 3, holding 1000 motes.
+    fn setup(env: &HostEnv) -> MultisigHostRef {
+        let mut multisig = MultisigHostRef::deploy(
+            env,
+            MultisigInitArgs {
+                owners: vec![env.get_account(1), env.get_account(2), env.get_account(3)],
+                threshold: 2,
+            },
+        );
+        multisig.with_tokens(U512::from(1_000)).deposit();
+        multisig
+    }
+
+    fn transfer(to: Address, amount: u64) -> Operation {
+        Operation::Transfer {
+            to,
+            amount: U512::from(amount),
+        }
+    }
+
+    #[test]
+    fn transfer_at_threshold() {
+        let env = odra_test::env();
+        let mut multisig = setup(&env);
+        let recipient = env.get_account(4);
+        let balance = env.balance_of(&recipient);
+
+        env.set_caller(env.get_account(1));
+        let tx_id = multisig.submit(transfer(recipient, 400));
+        assert!(multisig.is_confirmed_by(tx_id, env.get_account(1)));
+        assert_eq!(env.balance_of(&recipient), balance);
+
+        env.set_caller(env.get_account(2));
+        multisig.confirm(tx_id);
+        assert_eq!(env.balance_of(&recipient), balance + U512::from(400));
+        assert_eq!(env.balance_of(multisig.address()), U512::from(600));
+        assert!(env.emitted_event(multisig.address(), &Executed { tx_id }));
+        assert!(multisig.get_transaction(tx_id).unwrap().executed);
+
+        // Executed transactions are final
+        env.set_caller(env.get_account(3));
+        assert_eq!(
+            multisig.try_confirm(tx_id),
+            Err(Error::AlreadyExecuted.into())
+        );
+        env.set_caller(env.get_account(1));
+        assert_eq!(
+            multisig.try_revoke(tx_id),
+            Err(Error::AlreadyExecuted.into())
+        );
+    }
+
+    #[test]
+    fn revoked_confirmations_dont_count() {
+        let env = odra_test::env();
+        let mut multisig = setup(&env);
+        let recipient = env.get_account(4);
+        let balance = env.balance_of(&recipient);
+
+        env.set_caller(env.get_account(1));
+        let tx_id = multisig.submit(transfer(recipient, 400));
+        multisig.revoke(tx_id);
+        assert!(env.emitted_event(
+            multisig.address(),
+            &Revoked {
+                tx_id,
+                owner: env.get_account(1)
+            }
+        ));
+        assert_eq!(multisig.try_revoke(tx_id), Err(Error::NotConfirmed.into()));
+
+        env.set_caller(env.get_account(2));
+        multisig.confirm(tx_id);
+        assert_eq!(
+            multisig.try_confirm(tx_id),
+            Err(Error::AlreadyConfirmed.into())
+        );
+        assert_eq!(env.balance_of(&recipient), balance);
+        assert_eq!(multisig.get_transaction(tx_id).unwrap().confirmations, 1);
+    }
+
+    #[test]
+    fn contract_call() {
+        let env = odra_test::env();
+        let mut multisig = setup(&env);
+        let recipient = env.get_account(4);
+        let mut token = Cep18HostRef::deploy(
+            &env,
+            Cep18InitArgs {
+                symbol: "TRS".to_string(),
+                name: "Treasury Token".to_string(),
+                decimals: 9,
+                initial_supply: U256::from(1_000),
+                admin_list: vec![],
+                minter_list: vec![],
+                modality: None,
+            },
+        );
+        token.transfer(multisig.address(), &U256::from(1_000));
+
+        // The treasury transfers its tokens with a call to the token contract
+        let args = runtime_args! {
+            "recipient" => recipient,
+            "amount" => U256::from(250)
+        };
+        let operation = Operation::Call {
+            contract: *token.address(),
+            entry_point: "transfer".to_string(),
+            args: Bytes::from(args.to_bytes().unwrap()),
+            amount: U512::zero(),
+        };
+        env.set_caller(env.get_account(1));
+        let tx_id = multisig.submit(operation);
+        env.set_caller(env.get_account(3));
+        multisig.confirm(tx_id);
+        assert_eq!(token.balance_of(&recipient), U256::from(250));
+        assert_eq!(token.balance_of(multisig.address()), U256::from(750));
+    }
+
+    #[test]
+    fn invalid_transactions() {
+        let env = odra_test::env();
+        let mut multisig = setup(&env);
+        let recipient = env.get_account(4);
+
+        assert_eq!(
+            multisig.try_submit(transfer(recipient, 1)),
+            Err(Error::NotOwner.into())
+        );
+        env.set_caller(env.get_account(1));
+        assert_eq!(
+            multisig.try_confirm(7),
+            Err(Error::TransactionNotFound.into())
+        );
+
+        // Execution fails, and with it the confirmation, until the treasury is topped up
+        let tx_id = multisig.submit(transfer(recipient, 2_000));
+        env.set_caller(env.get_account(2));
+        assert_eq!(
+            multisig.try_confirm(tx_id),
+            Err(Error::InsufficientBalance.into())
+        );
+        multisig.with_tokens(U512::from(1_000)).deposit();
+        multisig.confirm(tx_id);
+        assert!(multisig.get_transaction(tx_id).unwrap().executed);
+
+        let tx_id = multisig.submit(Operation::Call {
+            contract: *multisig.address(),
+            entry_point: "deposit".to_string(),
+            args: Bytes::from(vec![1, 2, 3]),
+            amount: U512::zero(),
+        });
+        env.set_caller(env.get_account(3));
+        assert_eq!(multisig.try_confirm(tx_id), Err(Error::InvalidArgs.into()));
+    }
+
+    #[test]
+    fn invalid_setup() {
+        let env = odra_test::env();
+        let (alice, bob) = (env.get_account(1), env.get_account(2));
+        let deploy = |owners: Vec<Address>, threshold| {
+            MultisigHostRef::try_deploy(&env, MultisigInitArgs { owners, threshold }).err()
+        };
+        assert_eq!(
+            deploy(vec![alice, bob], 0),
+            Some(Error::InvalidThreshold.into())
+        );
+        assert_eq!(
+            deploy(vec![alice, bob], 3),
+            Some(Error::InvalidThreshold.into())
+        );
+        assert_eq!(
+            deploy(vec![alice, alice], 2),
+            Some(Error::DuplicateOwner.into())
+        );
+    }
+}
@@ -0,0 +1,446 @@
+use odra::casper_types::U512;
+use odra::module::SubModule;
+use odra::prelude::*;
+use odra::{Address, Mapping, Var};
+use odra_modules::access::Ownable;
+
+/// Length of a registration period in milliseconds.
+const YEAR: u64 = 365 * 24 * 60 * 60 * 1_000;
+/// Longest name that can be registered, in characters.
+const MAX_NAME_LENGTH: usize = 64;
+
+#[odra::odra_error]
+/// Errors that may occur during the contract execution.
+pub enum Error {
+    /// Names consist of lowercase letters, digits and hyphens.
+    InvalidName = 1,
+    /// The name is registered or in its grace period.
+    NameUnavailable = 2,
+    /// The attached CSPR doesn't match the fee.
+    IncorrectPayment = 3,
+    /// The name isn't registered.
+    NameNotFound = 4,
+    /// Only the owner of the name can manage it.
+    NotNameOwner = 5,
+    /// The name has expired and has to be renewed first.
+    NameExpired = 6,
+    /// Names are registered and renewed for at least a year.
+    InvalidDuration = 7,
+    /// A primary name has to resolve to the account setting it.
+    NotTarget = 8,
+}
+
+#[odra::odra_type]
+pub enum NameState {
+    /// Nobody owns the name, anyone can register it.
+    Available,
+    /// The name is registered and resolves to its target.
+    Active,
+    /// The registration has expired. The name doesn't resolve, but only its owner can renew it.
+    GracePeriod,
+}
+
+#[odra::odra_type]
+pub struct NameRecord {
+    pub owner: Address,
+    /// The address the name resolves to.
+    pub target: Address,
+    /// Block time the registration expires at.
+    pub expires_at: u64,
+}
+
+#[odra::event]
+/// Emitted when a name is registered.
+pub struct NameRegistered {
+    pub name: String,
+    pub owner: Address,
+    pub target: Address,
+    pub expires_at: u64,
+}
+
+#[odra::event]
+/// Emitted when a registration is extended.
+pub struct NameRenewed {
+    pub name: String,
+    pub expires_at: u64,
+}
+
+#[odra::event]
+/// Emitted when a name is given to a new owner.
+pub struct NameTransferred {
+    pub name: String,
+    pub from: Address,
+    pub to: Address,
+}
+
+#[odra::event]
+/// Emitted when a name is pointed at a new address.
+pub struct TargetChanged {
+    pub name: String,
+    pub target: Address,
+}
+
+#[odra::event]
+/// Emitted when an account sets its primary name.
+pub struct PrimaryNameSet {
+    pub account: Address,
+    pub name: String,
+}
+
+#[odra::module(
+    events = [NameRegistered, NameRenewed, NameTransferred, TargetChanged, PrimaryNameSet],
+    errors = Error
+)]
+/// Maps names to addresses for a yearly fee, and addresses back to their primary names.
+pub struct NameRegistry {
+    /// Fee for a year of registration.
+    annual_fee: Var<U512>,
+    /// Time after expiry during which only the owner can renew a name.
+    grace_period: Var<u64>,
+    records: Mapping<String, NameRecord>,
+    /// Primary name of each account. Only valid while the name resolves to the account.
+    primary_names: Mapping<Address, String>,
+    ownable: SubModule<Ownable>,
+}
+
+#[odra::module]
+impl NameRegistry {
+    /// Initializes the registry with its fee and grace period; the deployer becomes the owner.
+    pub fn init(&mut self, annual_fee: U512, grace_period: u64) {
+        self.annual_fee.set(annual_fee);
+        self.grace_period.set(grace_period);
+        self.ownable.init();
+    }
+
+    /**********
+     * TRANSACTIONS
+     **********/
+
+    /// Registers an available name for `years`, paying the fee with attached CSPR.
+    /// The caller becomes the owner of the name.
+    #[odra(payable)]
+    pub fn register(&mut self, name: String, target: Address, years: u8) {
+        if !Self::is_valid_name(&name) {
+            self.env().revert(Error::InvalidName);
+        }
+        if self.state_of(name.clone()) != NameState::Available {
+            self.env().revert(Error::NameUnavailable);
+        }
+        self.collect_fee(years);
+
+        let owner = self.env().caller();
+        let expires_at = self.env().get_block_time() + years as u64 * YEAR;
+        self.records.set(
+            &name,
+            NameRecord {
+                owner,
+                target,
+                expires_at,
+            },
+        );
+        self.env().emit_event(NameRegistered {
+            name,
+            owner,
+            target,
+            expires_at,
+        });
+    }
+
+    /// Extends the registration by `years`, paying the fee with attached CSPR. Anyone can
+    /// renew an active name, but only its owner can renew it during the grace period.
+    #[odra(payable)]
+    pub fn renew(&mut self, name: String, years: u8) {
+        let mut record = self.record(&name);
+        match self.state_of(name.clone()) {
+            NameState::Available => self.env().revert(Error::NameNotFound),
+            NameState::GracePeriod if record.owner != self.env().caller() => {
+                self.env().revert(Error::NotNameOwner)
+            }
+            _ => {}
+        }
+        self.collect_fee(years);
+
+        // The time spent in the grace period counts towards the renewal
+        record.expires_at += years as u64 * YEAR;
+        let expires_at = record.expires_at;
+        self.records.set(&name, record);
+        self.env().emit_event(NameRenewed { name, expires_at });
+    }
+
+    /// Points the name at a new address.
+    pub fn set_target(&mut self, name: String, target: Address) {
+        let mut record = self.owned_active_record(&name);
+        record.target = target;
+        self.records.set(&name, record);
+        self.env().emit_event(TargetChanged { name, target });
+    }
+
+    /// Gives the name to a new owner; it keeps resolving to the same address.
+    pub fn transfer(&mut self, name: String, new_owner: Address) {
+        let mut record = self.owned_active_record(&name);
+        let from = record.owner;
+        record.owner = new_owner;
+        self.records.set(&name, record);
+        self.env().emit_event(NameTransferred {
+            name,
+            from,
+            to: new_owner,
+        });
+    }
+
+    /// Sets the name the caller's address resolves back to.
+    pub fn set_primary_name(&mut self, name: String) {
+        let account = self.env().caller();
+        if self.resolve(name.clone()) != Some(account) {
+            self.env().revert(Error::NotTarget);
+        }
+        self.primary_names.set(&account, name.clone());
+        self.env().emit_event(PrimaryNameSet { account, name });
+    }
+
+    /// Changes the fee of future registrations and renewals.
+    pub fn set_annual_fee(&mut self, annual_fee: U512) {
+        self.ownable.assert_owner(&self.env().caller());
+        self.annual_fee.set(annual_fee);
+    }
+
+    /// Sends the collected fees to the owner.
+    pub fn withdraw_fees(&mut self) {
+        let caller = self.env().caller();
+        self.ownable.assert_owner(&caller);
+        self.env()
+            .transfer_tokens(&caller, &self.env().self_balance());
+    }
+
+    /**********
+     * QUERIES
+     **********/
+
+    /// Returns the address an active name resolves to.
+    pub fn resolve(&self, name: String) -> Option<Address> {
+        match self.state_of(name.clone()) {
+            NameState::Active => self.records.get(&name).map(|record| record.target),
+            _ => None,
+        }
+    }
+
+    /// Returns the primary name of an account, as long as the name still resolves to it.
+    pub fn reverse_lookup(&self, account: Address) -> Option<String> {
+        // Names expire and change targets, so the forward record is checked every time
+        self.primary_names
+            .get(&account)
+            .filter(|name| self.resolve(name.clone()) == Some(account))
+    }
+
+    /// Returns the state of a name, derived from its expiry and the block time.
+    pub fn state_of(&self, name: String) -> NameState {
+        let now = self.env().get_block_time();
+        match self.records.get(&name) {
+            Some(record) if now < record.expires_at => NameState::Active,
+            Some(record) if now < record.expires_at + self.grace_period.get_or_default() => {
+                NameState::GracePeriod
+            }
+            _ => NameState::Available,
+        }
+    }
+
+    /// Returns the registration of a name, including expired ones.
+    pub fn get_record(&self, name: String) -> Option<NameRecord> {
+        self.records.get(&name)
+    }
+
+    /// Returns the fee for a year of registration.
+    pub fn get_annual_fee(&self) -> U512 {
+        self.annual_fee.get_or_default()
+    }
+
+    /// Returns the time after expiry during which only the owner can renew a name.
+    pub fn get_grace_period(&self) -> u64 {
+        self.grace_period.get_or_default()
+    }
+}
+
+impl NameRegistry {
+    fn is_valid_name(name: &str) -> bool {
+        // Lowercase only, so names that look the same can't be registered twice
+        !name.is_empty()
+            && name.len() <= MAX_NAME_LENGTH
+            && name
+                .bytes()
+                .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-')
+    }
+
+    /// Reverts unless the attached CSPR pays for `years` of registration.
+    fn collect_fee(&self, years: u8) {
+        if years == 0 {
+            self.env().revert(Error::InvalidDuration);
+        }
+        if self.env().attached_value() != self.annual_fee.get_or_default() * years {
+            self.env().revert(Error::IncorrectPayment);
+        }
+    }
+
+    fn record(&self, name: &String) -> NameRecord {
+        self.records
+            .get(name)
+            .unwrap_or_revert_with(&self.env(), Error::NameNotFound)
+    }
+
+    /// Returns the record of an active name owned by the caller.
+    fn owned_active_record(&self, name: &String) -> NameRecord {
+        let record = self.record(name);
+        if record.owner != self.env().caller() {
+            self.env().revert(Error::NotNameOwner);
+        }
+        if self.state_of(name.clone()) != NameState::Active {
+            self.env().revert(Error::NameExpired);
+        }
+        record
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use odra::host::{Deployer, HostEnv, HostRef};
+
+    const FEE: u64 = 1_000;
+    const GRACE_PERIOD: u64 = 30 * 24 * 60 * 60 * 1_000;
+
+    fn setup(env: &HostEnv) -> NameRegistryHostRef {
+        NameRegistryHostRef::deploy(
+            env,
+            NameRegistryInitArgs {
+                annual_fee: U512::from(FEE),
+                grace_period: GRACE_PERIOD,
+            },
+        )
+    }
+
+    #[test]
+    fn registration() {
+        let env = odra_test::env();
+        let mut registry = setup(&env);
+        let (alice, bob) = (env.get_account(1), env.get_account(2));
+        let name = "alice".to_string();
+
+        env.set_caller(alice);
+        assert_eq!(
+            registry
+                .with_tokens(U512::from(FEE))
+                .try_register(name.clone(), alice, 2),
+            Err(Error::IncorrectPayment.into())
+        );
+        for invalid in ["", "Alice", "alice.cspr"] {
+            assert_eq!(
+                registry
+                    .with_tokens(U512::from(FEE))
+                    .try_register(invalid.to_string(), alice, 1),
+                Err(Error::InvalidName.into())
+            );
+        }
+        registry
+            .with_tokens(U512::from(2 * FEE))
+            .register(name.clone(), alice, 2);
+        assert_eq!(registry.resolve(name.clone()), Some(alice));
+        assert_eq!(registry.state_of(name.clone()), NameState::Active);
+        assert!(env.emitted_event(
+            registry.address(),
+            &NameRegistered {
+                name: name.clone(),
+                owner: alice,
+                target: alice,
+                expires_at: 2 * YEAR
+            }
+        ));
+
+        env.set_caller(bob);
+        assert_eq!(
+            registry
+                .with_tokens(U512::from(FEE))
+                .try_register(name.clone(), bob, 1),
+            Err(Error::NameUnavailable.into())
+        );
+
+        // Reverse lookups only work while the name resolves to the account
+        assert_eq!(
+            registry.try_set_primary_name(name.clone()),
+            Err(Error::NotTarget.into())
+        );
+        env.set_caller(alice);
+        registry.set_primary_name(name.clone());
+        assert_eq!(registry.reverse_lookup(alice), Some(name.clone()));
+
+        // A new owner can point the name elsewhere, which ends the reverse record
+        registry.transfer(name.clone(), bob);
+        assert_eq!(registry.resolve(name.clone()), Some(alice));
+        assert_eq!(
+            registry.try_set_target(name.clone(), alice),
+            Err(Error::NotNameOwner.into())
+        );
+        env.set_caller(bob);
+        registry.set_target(name.clone(), bob);
+        assert_eq!(registry.resolve(name.clone()), Some(bob));
+        assert_eq!(registry.reverse_lookup(alice), None);
+
+        // The fees go to the owner of the registry
+        let owner = env.get_account(0);
+        let balance = env.balance_of(&owner);
+        env.set_caller(owner);
+        registry.withdraw_fees();
+        assert_eq!(env.balance_of(&owner), balance + U512::from(2 * FEE));
+    }
+
+    #[test]
+    fn expiry() {
+        let env = odra_test::env();
+        let mut registry = setup(&env);
+        let (alice, bob) = (env.get_account(1), env.get_account(2));
+        let name = "alice".to_string();
+        env.set_caller(alice);
+        registry
+            .with_tokens(U512::from(FEE))
+            .register(name.clone(), alice, 1);
+
+        // An expired name stops resolving, but only its owner can renew it
+        env.advance_block_time(YEAR);
+        assert_eq!(registry.state_of(name.clone()), NameState::GracePeriod);
+        assert_eq!(registry.resolve(name.clone()), None);
+        assert_eq!(
+            registry.try_set_target(name.clone(), bob),
+            Err(Error::NameExpired.into())
+        );
+        env.set_caller(bob);
+        assert_eq!(
+            registry
+                .with_tokens(U512::from(FEE))
+                .try_register(name.clone(), bob, 1),
+            Err(Error::NameUnavailable.into())
+        );
+        assert_eq!(
+            registry
+                .with_tokens(U512::from(FEE))
+                .try_renew(name.clone(), 1),
+            Err(Error::NotNameOwner.into())
+        );
+
+        // A renewal counts from the expiry, not from the block time
+        env.set_caller(alice);
+        registry.with_tokens(U512::from(FEE)).renew(name.clone(), 1);
+        assert_eq!(registry.state_of(name.clone()), NameState::Active);
+        assert_eq!(
+            registry.get_record(name.clone()).unwrap().expires_at,
+            2 * YEAR
+        );
+
+        // Once the grace period is over, anyone can register the name
+        env.advance_block_time(YEAR + GRACE_PERIOD);
+        assert_eq!(registry.state_of(name.clone()), NameState::Available);
+        env.set_caller(bob);
+        registry
+            .with_tokens(U512::from(FEE))
+            .register(name.clone(), bob, 1);
+        assert_eq!(registry.get_record(name.clone()).unwrap().owner, bob);
+        assert_eq!(registry.resolve(name), Some(bob));
+    }
+}
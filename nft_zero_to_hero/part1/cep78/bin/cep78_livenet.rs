@@ -1,48 +1,150 @@
 //! Deploys a CEP-78 contract, mints an nft token and transfers it to another address.
+//!
+//! Each run performs a single action, e.g.:
+//! `cargo run --bin cep78_livenet --features=livenet -- --action mint --contract hash-...`
+use std::fmt::Debug;
 use std::str::FromStr;
 
+use clap::{Parser, ValueEnum};
 use odra::args::Maybe;
-use odra::casper_types::U256;
 use odra::host::{Deployer, HostEnv, HostRef, HostRefLoader};
-use odra::Address;
+use odra::{Address, EventError};
+use odra_modules::cep78::events::{Mint, Transfer};
 use odra_modules::cep78::modalities::{
     EventsMode, MetadataMutability, NFTIdentifierMode, NFTKind, NFTMetadataKind, OwnershipMode,
 };
 use odra_modules::cep78::token::{Cep78HostRef, Cep78InitArgs};
 use odra_modules::cep78::utils::InitArgsBuilder;
+use tutorial_utils::gas::{self, with_gas};
+use tutorial_utils::manifest::{load_from_manifest, record_deployment};
+use tutorial_utils::network;
 
 const CEP78_METADATA: &str = r#"{
     "name": "John Doe",
     "token_uri": "https://www.barfoo.com",
     "checksum": "940bffb3f2bba35f84313aa26da09ece3ad47045c6a1292c2bbd2df4ab1a55fb"
 }"#;
-const CASPER_CONTRACT_ADDRESS: &str = "hash-"; // change to a deployed contract
-const RECIPIENT_ADDRESS: &str = "hash-"; // change to a desired recipient address
+const CONTRACT_NAME: &str = "Cep78";
+const COLLECTION_NAME: &str = "CEP-78 Example Deployment with CES";
+const COLLECTION_SYMBOL: &str = "CEP78-EXAMPLE-CES";
+const TOTAL_TOKEN_SUPPLY: u64 = 100;
+const RECEIPT_NAME: &str = "Example_NFT_Receipt";
+
+#[derive(Parser)]
+#[command(
+    about = "Deploys a CEP-78 contract, mints an nft token and transfers it to another address."
+)]
+struct Args {
+    /// What to do on the network.
+    #[arg(long, value_enum, default_value_t = Action::Deploy)]
+    action: Action,
+    /// Address of a deployed contract, defaults to the latest deployment recorded in `deployments.json`.
+    #[arg(long)]
+    contract: Option<String>,
+    /// Address receiving the token, required by `transfer`.
+    #[arg(long)]
+    recipient: Option<String>,
+    /// Gas limit, defaults to a budget suited to the action.
+    #[arg(long)]
+    gas: Option<u64>,
+    /// Network from `networks.toml` to use instead of the `.env` configuration.
+    #[arg(long)]
+    network: Option<String>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Action {
+    /// Deploys a new contract.
+    Deploy,
+    /// Loads a deployed contract and prints its name.
+    Load,
+    /// Mints a token to the caller.
+    Mint,
+    /// Transfers the last minted token from the caller to the recipient.
+    Transfer,
+    /// Prints all events emitted by a deployed contract.
+    Events,
+}
 
 fn main() {
+    let args = Args::parse();
+    if let Some(name) = &args.network {
+        network::configure(name);
+    }
     let env = odra_casper_livenet_env::env();
 
-    // Deploy new contract.
-    let mut token = deploy_contract(&env);
-    println!("Token address: {}", token.address().to_string());
+    match args.action {
+        Action::Deploy => {
+            let token = deploy_contract(&env, args.gas.unwrap_or(gas::DEPLOY));
+            println!("Token address: {}", token.address().to_string());
+        }
+        Action::Load => {
+            let token = load(&env, &args);
+            println!("Token name: {}", token.get_collection_name());
+        }
+        Action::Mint => {
+            let mut token = load(&env, &args);
+            let owner = env.caller();
+            let events_before = env.events_count(&token);
+            // casper contract may return a result or not, so deserialization may fail and it's better to use `try_transfer`/`try_mint`/`try_burn` methods
+            let _ = with_gas(&env, args.gas.unwrap_or(gas::MINT), || {
+                token.try_mint(owner, CEP78_METADATA.to_string(), Maybe::None)
+            });
+            println!("Owner's balance: {:?}", token.balance_of(owner));
+            print_events(&env, &token, events_before);
+        }
+        Action::Transfer => {
+            let mut token = load(&env, &args);
+            let recipient = args
+                .recipient
+                .as_deref()
+                .expect("--recipient is required for the transfer action");
+            let recipient =
+                Address::from_str(recipient).expect("Should be a valid recipient address");
+            let owner = env.caller();
+            let token_id = token.get_number_of_minted_tokens() - 1;
+            let events_before = env.events_count(&token);
+            let _ = with_gas(&env, args.gas.unwrap_or(gas::TRANSFER), || {
+                token.try_transfer(Maybe::Some(token_id), Maybe::None, owner, recipient)
+            });
+            println!("Owner's balance: {:?}", token.balance_of(owner));
+            println!("Recipient's balance: {:?}", token.balance_of(recipient));
+            print_events(&env, &token, events_before);
+        }
+        Action::Events => {
+            let token = load(&env, &args);
+            print_events(&env, &token, 0);
+        }
+    }
+}
 
-    // Uncomment to load existing contract.
-    // let mut token = load_contract(&env, CASPER_CONTRACT_ADDRESS);
-    // println!("Token name: {}", token.get_collection_name());
+/// Reads back and pretty-prints the CES events the contract emitted, starting at index `from`.
+fn print_events(env: &HostEnv, token: &Cep78HostRef, from: u32) {
+    let names = env.event_names(token);
+    for (index, name) in names.iter().enumerate().skip(from as usize) {
+        let index = index as i32;
+        match name.as_str() {
+            "Mint" => print_event(index, env.get_event::<Mint, _>(token, index)),
+            "Transfer" => print_event(index, env.get_event::<Transfer, _>(token, index)),
+            other => println!("Event #{}: {}", index, other),
+        }
+    }
+}
 
-    env.set_gas(3_000_000_000u64);
-    let owner = env.caller();
-    let recipient =
-        Address::from_str(RECIPIENT_ADDRESS).expect("Should be a valid recipient address");
-    // casper contract may return a result or not, so deserialization may fail and it's better to use `try_transfer`/`try_mint`/`try_burn` methods
-    let _ = token.try_mint(owner, CEP78_METADATA.to_string(), Maybe::None);
-    println!("Owner's balance: {:?}", token.balance_of(owner));
-    println!("Recipient's balance: {:?}", token.balance_of(recipient));
-    let token_id = token.get_number_of_minted_tokens() - 1;
-    let _ = token.try_transfer(Maybe::Some(token_id), Maybe::None, owner, recipient);
+fn print_event<T: Debug>(index: i32, event: Result<T, EventError>) {
+    match event {
+        Ok(event) => println!("Event #{}: {:#?}", index, event),
+        Err(error) => println!("Event #{}: couldn't be parsed ({:?})", index, error),
+    }
+}
 
-    println!("Owner's balance: {:?}", token.balance_of(owner));
-    println!("Recipient's balance: {:?}", token.balance_of(recipient));
+/// Loads the contract passed with `--contract`, or the latest one recorded in the manifest.
+fn load(env: &HostEnv, args: &Args) -> Cep78HostRef {
+    match &args.contract {
+        Some(address) => load_contract(env, address),
+        None => load_from_manifest(env, CONTRACT_NAME)
+            .expect("Pass --contract or deploy the contract first"),
+    }
 }
 
 /// Loads a Cep78 contract.
@@ -51,25 +153,36 @@ pub fn load_contract(env: &HostEnv, address: &str) -> Cep78HostRef {
     Cep78HostRef::load(env, address)
 }
 
-/// Deploys a Cep78 contract.
-pub fn deploy_contract(env: &HostEnv) -> Cep78HostRef {
-    let name: String = String::from("CEP-78 Example Deployment with CES");
-    let symbol = String::from("CEP78-EXAMPLE-CES");
-    let receipt_name = String::from("Example_NFT_Receipt");
+/// Deploys a Cep78 contract, retrying with more gas if `gas` isn't enough.
+pub fn deploy_contract(env: &HostEnv, gas: u64) -> Cep78HostRef {
+    let token = with_gas(env, gas, || Cep78HostRef::try_deploy(env, init_args()))
+        .expect("Should deploy the contract");
+    record_deployment(
+        CONTRACT_NAME,
+        &token,
+        None,
+        &[
+            ("collection_name", COLLECTION_NAME.to_string()),
+            ("collection_symbol", COLLECTION_SYMBOL.to_string()),
+            ("total_token_supply", TOTAL_TOKEN_SUPPLY.to_string()),
+            ("receipt_name", RECEIPT_NAME.to_string()),
+        ],
+    );
+    token
+}
 
-    let init_args = InitArgsBuilder::default()
-        .collection_name(name)
-        .collection_symbol(symbol)
-        .total_token_supply(100)
+/// Init args of the example collection.
+fn init_args() -> Cep78InitArgs {
+    InitArgsBuilder::default()
+        .collection_name(COLLECTION_NAME.to_string())
+        .collection_symbol(COLLECTION_SYMBOL.to_string())
+        .total_token_supply(TOTAL_TOKEN_SUPPLY)
         .ownership_mode(OwnershipMode::Transferable)
         .nft_metadata_kind(NFTMetadataKind::CEP78)
         .identifier_mode(NFTIdentifierMode::Ordinal)
         .nft_kind(NFTKind::Digital)
         .metadata_mutability(MetadataMutability::Mutable)
-        .receipt_name(receipt_name)
+        .receipt_name(RECEIPT_NAME.to_string())
         .events_mode(EventsMode::CES)
-        .build();
-
-    env.set_gas(400_000_000_000u64);
-    Cep78HostRef::deploy(env, init_args)
+        .build()
 }
@@ -10,6 +10,8 @@ use odra_modules::cep78::modalities::{
 };
 use odra_modules::cep78::token::{Cep78HostRef, Cep78InitArgs};
 use odra_modules::cep78::utils::InitArgsBuilder;
+use tutorial_utils::gas::{self, with_gas};
+use tutorial_utils::network;
 
 const CEP78_METADATA: &str = r#"{
     "name": "John Doe",
@@ -20,6 +22,7 @@ const CASPER_CONTRACT_ADDRESS: &str = "hash-"; // change to a deployed contract
 const RECIPIENT_ADDRESS: &str = "hash-"; // change to a desired recipient address
 
 fn main() {
+    network::configure_from_args();
     let env = odra_casper_livenet_env::env();
 
     // Deploy new contract.
@@ -30,16 +33,19 @@ fn main() {
     // let mut token = load_contract(&env, CASPER_CONTRACT_ADDRESS);
     // println!("Token name: {}", token.get_collection_name());
 
-    env.set_gas(3_000_000_000u64);
     let owner = env.caller();
     let recipient =
         Address::from_str(RECIPIENT_ADDRESS).expect("Should be a valid recipient address");
     // casper contract may return a result or not, so deserialization may fail and it's better to use `try_transfer`/`try_mint`/`try_burn` methods
-    let _ = token.try_mint(owner, CEP78_METADATA.to_string(), Maybe::None);
+    let _ = with_gas(&env, gas::MINT, || {
+        token.try_mint(owner, CEP78_METADATA.to_string(), Maybe::None)
+    });
     println!("Owner's balance: {:?}", token.balance_of(owner));
     println!("Recipient's balance: {:?}", token.balance_of(recipient));
     let token_id = token.get_number_of_minted_tokens() - 1;
-    let _ = token.try_transfer(Maybe::Some(token_id), Maybe::None, owner, recipient);
+    let _ = with_gas(&env, gas::TRANSFER, || {
+        token.try_transfer(Maybe::Some(token_id), Maybe::None, owner, recipient)
+    });
 
     println!("Owner's balance: {:?}", token.balance_of(owner));
     println!("Recipient's balance: {:?}", token.balance_of(recipient));
@@ -53,11 +59,19 @@ pub fn load_contract(env: &HostEnv, address: &str) -> Cep78HostRef {
 
 /// Deploys a Cep78 contract.
 pub fn deploy_contract(env: &HostEnv) -> Cep78HostRef {
+    with_gas(env, gas::DEPLOY, || {
+        Cep78HostRef::try_deploy(env, init_args())
+    })
+    .expect("Should deploy the contract")
+}
+
+/// Init args of the example collection.
+fn init_args() -> Cep78InitArgs {
     let name: String = String::from("CEP-78 Example Deployment with CES");
     let symbol = String::from("CEP78-EXAMPLE-CES");
     let receipt_name = String::from("Example_NFT_Receipt");
 
-    let init_args = InitArgsBuilder::default()
+    InitArgsBuilder::default()
         .collection_name(name)
         .collection_symbol(symbol)
         .total_token_supply(100)
@@ -68,8 +82,5 @@ pub fn deploy_contract(env: &HostEnv) -> Cep78HostRef {
         .metadata_mutability(MetadataMutability::Mutable)
         .receipt_name(receipt_name)
         .events_mode(EventsMode::CES)
-        .build();
-
-    env.set_gas(400_000_000_000u64);
-    Cep78HostRef::deploy(env, init_args)
+        .build()
 }
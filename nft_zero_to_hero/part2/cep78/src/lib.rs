@@ -1,18 +1,143 @@
 #![cfg_attr(not(test), no_std)]
 #![cfg_attr(not(test), no_main)]
 extern crate alloc;
-use odra::{args::Maybe, module::SubModule, prelude::*, Address};
+use odra::{
+    args::Maybe,
+    casper_types::{
+        bytesrepr::{Bytes, ToBytes},
+        PublicKey, U512,
+    },
+    module::SubModule,
+    prelude::*,
+    Address, Mapping, Var,
+};
 use odra_modules::cep78::{
-    modalities::{MetadataMutability, NFTIdentifierMode, NFTKind, NFTMetadataKind, OwnershipMode},
+    modalities::{
+        BurnMode, EventsMode, MetadataMutability, MintingMode, NFTHolderMode, NFTIdentifierMode,
+        NFTKind, NFTMetadataKind, OwnerReverseLookupMode, OwnershipMode, WhitelistMode,
+    },
     token::Cep78,
 };
+use odra_modules::{access::Ownable, security::Pauseable};
 
 pub type MintReceipt = (String, Address, String);
 pub type TransferReceipt = (String, Address);
 
-#[odra::module]
+const MAX_BPS: u16 = 10_000;
+
+// Optional CEP-78 modalities; anything left as `None` falls back to the tutorial's defaults:
+// a transferable, ordinal collection with immutable CEP-78 metadata, and the module's own
+// defaults for everything else.
+#[odra::odra_type]
+#[derive(Default)]
+pub struct ExtendedCEP78Options {
+    pub ownership_mode: Option<OwnershipMode>,
+    pub identifier_mode: Option<NFTIdentifierMode>,
+    pub nft_metadata_kind: Option<NFTMetadataKind>,
+    pub metadata_mutability: Option<MetadataMutability>,
+    pub allow_minting: Option<bool>,
+    pub minting_mode: Option<MintingMode>,
+    pub holder_mode: Option<NFTHolderMode>,
+    pub whitelist_mode: Option<WhitelistMode>,
+    pub acl_white_list: Option<Vec<Address>>,
+    pub json_schema: Option<String>,
+    pub burn_mode: Option<BurnMode>,
+    pub operator_burn_mode: Option<bool>,
+    pub owner_reverse_lookup_mode: Option<OwnerReverseLookupMode>,
+    pub events_mode: Option<EventsMode>,
+    pub transfer_filter_contract: Option<Address>,
+    pub additional_required_metadata: Option<Vec<NFTMetadataKind>>,
+    pub optional_metadata: Option<Vec<NFTMetadataKind>>,
+}
+
+// Numbered past the error codes of the embedded CEP-78 module.
+#[odra::odra_error]
+pub enum Error {
+    IncorrectPayment = 40_000,
+    NotAllowlisted = 40_001,
+    PresaleCapExceeded = 40_002,
+    TokenLocked = 40_003,
+    AlreadyRevealed = 40_004,
+    NothingToReveal = 40_005,
+    MintingPaused = 40_006,
+    MintLimitExceeded = 40_007,
+    InvalidRoyalty = 40_008,
+    InvalidVoucherSignature = 40_009,
+    VoucherAlreadyUsed = 40_010,
+    VoucherSignerNotSet = 40_011,
+    TokenNotFound = 40_012,
+    MintingStarted = 40_013,
+    ChecksumMismatch = 40_014,
+}
+
+// A mint signed off-chain by the voucher signer and redeemed on-chain by anyone paying `price`.
+#[odra::odra_type]
+pub struct Voucher {
+    pub recipient: Address,
+    pub token_meta_data: String,
+    pub price: U512,
+    pub nonce: u64,
+}
+
+#[odra::event]
+pub struct TransfersSkipped {
+    pub token_ids: Vec<u64>,
+}
+
+#[odra::event]
+pub struct BurnsSkipped {
+    pub token_ids: Vec<u64>,
+}
+
+#[odra::event]
+pub struct MetadataRevealed {
+    pub base_checksum: String,
+}
+
+#[odra::event]
+pub struct BatchApproved {
+    pub owner: Address,
+    pub spender: Address,
+    pub token_ids: Vec<u64>,
+}
+
+#[odra::event]
+pub struct OperatorRevoked {
+    pub owner: Address,
+    pub operator: Address,
+}
+
+#[odra::module(
+    events = [TransfersSkipped, BurnsSkipped, MetadataRevealed, BatchApproved, OperatorRevoked],
+    errors = Error
+)]
 pub struct ExtendedCEP78 {
     cep78: SubModule<Cep78>,
+    ownable: SubModule<Ownable>,
+    pausable: SubModule<Pauseable>,
+    burnt_tokens: Mapping<u64, bool>,
+    mint_price: Var<U512>,
+    proceeds: Var<U512>,
+    allowlist: Mapping<Address, bool>,
+    presale_minted: Mapping<Address, u64>,
+    presale_ends_at: Var<u64>,
+    presale_cap: Var<u64>,
+    owned_tokens: Mapping<(Address, u64), u64>,
+    owned_token_index: Mapping<u64, u64>,
+    owned_token_count: Mapping<Address, u64>,
+    hash_identifiers: Var<bool>,
+    token_index_by_hash: Mapping<String, u64>,
+    locked_tokens: Mapping<u64, bool>,
+    placeholder_metadata: Var<Option<String>>,
+    reveal_checksum: Var<Option<String>>,
+    revealed: Var<bool>,
+    revealed_metadata: Mapping<u64, String>,
+    max_per_wallet: Var<u64>,
+    minted_per_wallet: Mapping<Address, u64>,
+    default_royalty: Var<(Address, u16)>,
+    token_royalties: Mapping<u64, (Address, u16)>,
+    voucher_signer: Var<PublicKey>,
+    used_vouchers: Mapping<u64, bool>,
 }
 
 #[odra::module]
@@ -24,49 +149,46 @@ impl ExtendedCEP78 {
         total_token_supply: u64,
         nft_kind: NFTKind,
         receipt_name: String,
+        options: ExtendedCEP78Options,
     ) {
+        self.ownable.init();
+        let identifier_mode = options
+            .identifier_mode
+            .unwrap_or(NFTIdentifierMode::Ordinal);
+        self.hash_identifiers
+            .set(matches!(identifier_mode, NFTIdentifierMode::Hash));
         self.cep78.init(
             collection_name,
             collection_symbol,
             total_token_supply,
-            OwnershipMode::Transferable,
+            options
+                .ownership_mode
+                .unwrap_or(OwnershipMode::Transferable),
             nft_kind,
-            NFTIdentifierMode::Ordinal,
-            NFTMetadataKind::CEP78,
-            MetadataMutability::Immutable,
+            identifier_mode,
+            options.nft_metadata_kind.unwrap_or(NFTMetadataKind::CEP78),
+            options
+                .metadata_mutability
+                .unwrap_or(MetadataMutability::Immutable),
             receipt_name,
-            Maybe::Some(true),
-            Maybe::None,
-            Maybe::None,
-            Maybe::None,
-            Maybe::None,
-            Maybe::None,
-            Maybe::None,
-            Maybe::None,
-            Maybe::None,
-            Maybe::None,
-            Maybe::None,
-            Maybe::None,
-            Maybe::None,
+            Maybe::Some(options.allow_minting.unwrap_or(true)),
+            maybe(options.minting_mode),
+            maybe(options.holder_mode),
+            maybe(options.whitelist_mode),
+            maybe(options.acl_white_list),
+            maybe(options.json_schema),
+            maybe(options.burn_mode),
+            maybe(options.operator_burn_mode),
+            maybe(options.owner_reverse_lookup_mode),
+            maybe(options.events_mode),
+            maybe(options.transfer_filter_contract),
+            maybe(options.additional_required_metadata),
+            maybe(options.optional_metadata),
         );
     }
 
     delegate! {
         to self.cep78 {
-            fn mint(
-                &mut self,
-                token_owner: Address,
-                token_meta_data: String,
-                token_hash: Maybe<String>
-            ) -> MintReceipt;
-            fn burn(&mut self, token_id: Maybe<u64>, token_hash: Maybe<String>);
-            fn transfer(
-                &mut self,
-                token_id: Maybe<u64>,
-                token_hash: Maybe<String>,
-                source_key: Address,
-                target_key: Address
-            ) -> TransferReceipt;
             fn approve(&mut self, spender: Address, token_id: Maybe<u64>, token_hash: Maybe<String>);
             fn revoke(&mut self, token_id: Maybe<u64>, token_hash: Maybe<String>);
             fn set_approval_for_all(&mut self, approve_all: bool, operator: Address);
@@ -77,7 +199,6 @@ impl ExtendedCEP78 {
                 token_id: Maybe<u64>,
                 token_hash: Maybe<String>
             ) -> Option<Address>;
-            fn metadata(&self, token_id: Maybe<u64>, token_hash: Maybe<String>) -> String;
             fn set_token_metadata(
                 &mut self,
                 token_id: Maybe<u64>,
@@ -89,24 +210,477 @@ impl ExtendedCEP78 {
         }
     }
 
+    #[odra(payable)]
+    pub fn mint(
+        &mut self,
+        token_owner: Address,
+        token_meta_data: String,
+        token_hash: Maybe<String>,
+    ) -> MintReceipt {
+        self.before_mint(token_owner, 1);
+        self.mint_token(token_owner, token_meta_data, token_hash)
+    }
+
+    #[odra(payable)]
     pub fn batch_mint(
         &mut self,
         token_owner: Address,
         token_meta_data: Vec<String>,
     ) -> Vec<MintReceipt> {
+        self.before_mint(token_owner, token_meta_data.len() as u64);
         let mut mint_receipts: Vec<MintReceipt> = Vec::new();
         for t in token_meta_data.iter() {
-            let receipt = self.cep78.mint(token_owner, t.clone(), Maybe::None);
+            let receipt = self.mint_token(token_owner, t.clone(), Maybe::None);
             mint_receipts.push(receipt);
         }
         mint_receipts
     }
+
+    pub fn transfer(
+        &mut self,
+        token_id: Maybe<u64>,
+        token_hash: Maybe<String>,
+        source_key: Address,
+        target_key: Address,
+    ) -> TransferReceipt {
+        let index = self.token_index(&token_id, &token_hash);
+        if self.is_token_locked(index) {
+            self.env().revert(Error::TokenLocked);
+        }
+        let receipt = self
+            .cep78
+            .transfer(token_id, token_hash, source_key, target_key);
+        self.remove_owned_token(source_key, index);
+        self.add_owned_token(target_key, index);
+        receipt
+    }
+
+    pub fn burn(&mut self, token_id: Maybe<u64>, token_hash: Maybe<String>) {
+        let index = self.token_index(&token_id, &token_hash);
+        let owner = self.cep78.owner_of(token_id.clone(), token_hash.clone());
+        self.cep78.burn(token_id, token_hash);
+        self.burnt_tokens.set(&index, true);
+        self.remove_owned_token(owner, index);
+    }
+
+    // With `skip_failures` set, tokens that can't be transferred are skipped and reported
+    // in a `TransfersSkipped` event; otherwise the first failure reverts the whole batch.
+    pub fn batch_transfer(
+        &mut self,
+        token_ids: Vec<u64>,
+        source: Address,
+        target: Address,
+        skip_failures: bool,
+    ) -> Vec<TransferReceipt> {
+        let mut transfer_receipts: Vec<TransferReceipt> = Vec::new();
+        let mut skipped: Vec<u64> = Vec::new();
+        for token_id in token_ids {
+            if skip_failures && !self.can_transfer(token_id, source) {
+                skipped.push(token_id);
+                continue;
+            }
+            let receipt = self.transfer(Maybe::Some(token_id), Maybe::None, source, target);
+            transfer_receipts.push(receipt);
+        }
+        if !skipped.is_empty() {
+            self.env()
+                .emit_event(TransfersSkipped { token_ids: skipped });
+        }
+        transfer_receipts
+    }
+
+    // Same failure handling as `batch_transfer`, reporting skipped tokens in a `BurnsSkipped` event.
+    pub fn batch_burn(&mut self, token_ids: Vec<u64>, skip_failures: bool) {
+        let caller = self.env().caller();
+        let mut skipped: Vec<u64> = Vec::new();
+        for token_id in token_ids {
+            if skip_failures && !self.is_live_token_of(token_id, caller) {
+                skipped.push(token_id);
+                continue;
+            }
+            self.burn(Maybe::Some(token_id), Maybe::None);
+        }
+        if !skipped.is_empty() {
+            self.env().emit_event(BurnsSkipped { token_ids: skipped });
+        }
+    }
+
+    // Approves `spender` for every listed token in one deploy; any failed approval reverts the batch.
+    pub fn batch_approve(&mut self, spender: Address, token_ids: Vec<u64>) {
+        for token_id in token_ids.iter() {
+            self.cep78
+                .approve(spender, Maybe::Some(*token_id), Maybe::None);
+        }
+        self.env().emit_event(BatchApproved {
+            owner: self.env().caller(),
+            spender,
+            token_ids,
+        });
+    }
+
+    // Withdraws the operator rights granted to `operator` with `set_approval_for_all`.
+    pub fn revoke_all(&mut self, operator: Address) {
+        self.cep78.set_approval_for_all(false, operator);
+        self.env().emit_event(OperatorRevoked {
+            owner: self.env().caller(),
+            operator,
+        });
+    }
+
+    // Tokens of a hidden drop hold the placeholder in CEP-78, which only lets token owners
+    // update metadata, so their revealed metadata is kept here.
+    pub fn metadata(&self, token_id: Maybe<u64>, token_hash: Maybe<String>) -> String {
+        let index = self.token_index(&token_id, &token_hash);
+        match self.revealed_metadata.get(&index) {
+            Some(metadata) => metadata,
+            None => self.cep78.metadata(token_id, token_hash),
+        }
+    }
+
+    // Hides the drop before minting starts: tokens are minted with the placeholder, and
+    // `base_checksum` commits to the metadata the reveal has to present, in mint order.
+    pub fn hide_metadata(&mut self, placeholder: String, base_checksum: String) {
+        self.ownable.assert_owner(&self.env().caller());
+        if self.revealed.get_or_default() {
+            self.env().revert(Error::AlreadyRevealed);
+        }
+        if self.cep78.get_number_of_minted_tokens() > 0 {
+            self.env().revert(Error::MintingStarted);
+        }
+        self.placeholder_metadata.set(Some(placeholder));
+        self.reveal_checksum.set(Some(base_checksum));
+    }
+
+    // `token_meta_data` must hash to the committed checksum, so the owner can't swap the
+    // metadata after seeing who minted which token.
+    pub fn reveal(&mut self, token_meta_data: Vec<String>) {
+        self.ownable.assert_owner(&self.env().caller());
+        if self.placeholder_metadata.get_or_default().is_none() {
+            self.env().revert(Error::NothingToReveal);
+        }
+        let base_checksum = self.reveal_checksum.get_or_default().unwrap_or_default();
+        let serialized = token_meta_data.to_bytes().unwrap_or_revert(&self.env());
+        if hex(&self.env().hash(serialized)) != base_checksum {
+            self.env().revert(Error::ChecksumMismatch);
+        }
+        let minted = self.cep78.get_number_of_minted_tokens();
+        for (index, metadata) in (0..minted).zip(token_meta_data) {
+            self.revealed_metadata.set(&index, metadata);
+        }
+        self.placeholder_metadata.set(None);
+        self.revealed.set(true);
+        self.env().emit_event(MetadataRevealed { base_checksum });
+    }
+
+    pub fn reveal_checksum(&self) -> Option<String> {
+        self.reveal_checksum.get_or_default()
+    }
+
+    // Locked (soulbound) tokens can't be transferred until the owner unlocks them.
+    pub fn lock_token(&mut self, token_id: u64) {
+        self.ownable.assert_owner(&self.env().caller());
+        self.locked_tokens.set(&token_id, true);
+    }
+
+    pub fn unlock_token(&mut self, token_id: u64) {
+        self.ownable.assert_owner(&self.env().caller());
+        self.locked_tokens.set(&token_id, false);
+    }
+
+    pub fn is_token_locked(&self, token_id: u64) -> bool {
+        self.locked_tokens.get_or_default(&token_id)
+    }
+
+    // Lists the owner's tokens page by page. Tokens are listed by their mint order, which is
+    // also their id in Ordinal mode.
+    pub fn tokens_of_owner(&self, owner: Address, offset: u64, limit: u64) -> Vec<u64> {
+        let count = self.owned_token_count.get_or_default(&owner);
+        let end = count.min(offset.saturating_add(limit));
+        (offset..end)
+            .map(|index| self.owned_tokens.get_or_default(&(owner, index)))
+            .collect()
+    }
+
+    pub fn total_minted(&self) -> u64 {
+        self.cep78.get_number_of_minted_tokens()
+    }
+
+    pub fn set_mint_price(&mut self, price: U512) {
+        self.ownable.assert_owner(&self.env().caller());
+        self.mint_price.set(price);
+    }
+
+    pub fn mint_price(&self) -> U512 {
+        self.mint_price.get_or_default()
+    }
+
+    pub fn withdraw_proceeds(&mut self, to: Address) {
+        self.ownable.assert_owner(&self.env().caller());
+        let amount = self.proceeds.get_or_default();
+        self.proceeds.set(U512::zero());
+        self.env().transfer_tokens(&to, &amount);
+    }
+
+    // Until the presale ends only allowlisted accounts can mint, each up to `cap_per_address` tokens.
+    pub fn start_presale(&mut self, duration: u64, cap_per_address: u64) {
+        self.ownable.assert_owner(&self.env().caller());
+        self.presale_ends_at
+            .set(self.env().get_block_time() + duration);
+        self.presale_cap.set(cap_per_address);
+    }
+
+    pub fn add_to_allowlist(&mut self, addresses: Vec<Address>) {
+        self.ownable.assert_owner(&self.env().caller());
+        for address in addresses {
+            self.allowlist.set(&address, true);
+        }
+    }
+
+    pub fn is_allowlisted(&self, address: Address) -> bool {
+        self.allowlist.get_or_default(&address)
+    }
+
+    pub fn is_presale_active(&self) -> bool {
+        self.env().get_block_time() < self.presale_ends_at.get_or_default()
+    }
+
+    pub fn pause_minting(&mut self) {
+        self.ownable.assert_owner(&self.env().caller());
+        self.pausable.pause();
+    }
+
+    pub fn unpause_minting(&mut self) {
+        self.ownable.assert_owner(&self.env().caller());
+        self.pausable.unpause();
+    }
+
+    // Zero means wallets can hold any number of minted tokens.
+    pub fn set_max_per_wallet(&mut self, max_per_wallet: u64) {
+        self.ownable.assert_owner(&self.env().caller());
+        self.max_per_wallet.set(max_per_wallet);
+    }
+
+    pub fn max_per_wallet(&self) -> u64 {
+        self.max_per_wallet.get_or_default()
+    }
+
+    pub fn set_voucher_signer(&mut self, public_key: PublicKey) {
+        self.ownable.assert_owner(&self.env().caller());
+        self.voucher_signer.set(public_key);
+    }
+
+    // The signature covers this contract's address followed by the serialized voucher, so a
+    // voucher can't be redeemed on another collection with the same signer; each nonce can be
+    // redeemed once.
+    #[odra(payable)]
+    pub fn mint_with_voucher(&mut self, voucher: Voucher, signature: Bytes) -> MintReceipt {
+        let signer = self
+            .voucher_signer
+            .get()
+            .unwrap_or_revert_with(&self.env(), Error::VoucherSignerNotSet);
+        let message = Bytes::from(
+            (self.env().self_address(), voucher.clone())
+                .to_bytes()
+                .unwrap_or_revert(&self.env()),
+        );
+        if !self.env().verify_signature(&message, &signature, &signer) {
+            self.env().revert(Error::InvalidVoucherSignature);
+        }
+        if self.used_vouchers.get_or_default(&voucher.nonce) {
+            self.env().revert(Error::VoucherAlreadyUsed);
+        }
+        if self.env().attached_value() != voucher.price {
+            self.env().revert(Error::IncorrectPayment);
+        }
+        if self.pausable.is_paused() {
+            self.env().revert(Error::MintingPaused);
+        }
+        self.check_mint_limit(voucher.recipient, 1);
+        self.used_vouchers.set(&voucher.nonce, true);
+        self.proceeds.add(voucher.price);
+        self.mint_token(voucher.recipient, voucher.token_meta_data, Maybe::None)
+    }
+
+    pub fn set_default_royalty(&mut self, receiver: Address, bps: u16) {
+        self.ownable.assert_owner(&self.env().caller());
+        self.assert_valid_royalty(bps);
+        self.default_royalty.set((receiver, bps));
+    }
+
+    pub fn set_token_royalty(&mut self, token_id: u64, receiver: Address, bps: u16) {
+        self.ownable.assert_owner(&self.env().caller());
+        self.assert_valid_royalty(bps);
+        self.token_royalties.set(&token_id, (receiver, bps));
+    }
+
+    // EIP-2981 style: the token's own royalty wins over the default, and without either
+    // the owner receives nothing.
+    pub fn royalty_info(&self, token_id: u64, sale_price: U512) -> (Address, U512) {
+        let (receiver, bps) = self
+            .token_royalties
+            .get(&token_id)
+            .or_else(|| self.default_royalty.get())
+            .unwrap_or_else(|| (self.ownable.get_owner(), 0));
+        (receiver, sale_price * U512::from(bps) / U512::from(MAX_BPS))
+    }
+
+    fn assert_valid_royalty(&self, bps: u16) {
+        if bps > MAX_BPS {
+            self.env().revert(Error::InvalidRoyalty);
+        }
+    }
+
+    fn before_mint(&mut self, token_owner: Address, count: u64) {
+        if self.pausable.is_paused() {
+            self.env().revert(Error::MintingPaused);
+        }
+        self.check_mint_limit(token_owner, count);
+        self.check_presale(count);
+        self.collect_mint_payment(count);
+    }
+
+    fn check_mint_limit(&mut self, token_owner: Address, count: u64) {
+        let minted = self.minted_per_wallet.get_or_default(&token_owner) + count;
+        let max_per_wallet = self.max_per_wallet.get_or_default();
+        if max_per_wallet > 0 && minted > max_per_wallet {
+            self.env().revert(Error::MintLimitExceeded);
+        }
+        self.minted_per_wallet.set(&token_owner, minted);
+    }
+
+    fn check_presale(&mut self, count: u64) {
+        if !self.is_presale_active() {
+            return;
+        }
+        let minter = self.env().caller();
+        if !self.is_allowlisted(minter) {
+            self.env().revert(Error::NotAllowlisted);
+        }
+        let minted = self.presale_minted.get_or_default(&minter) + count;
+        if minted > self.presale_cap.get_or_default() {
+            self.env().revert(Error::PresaleCapExceeded);
+        }
+        self.presale_minted.set(&minter, minted);
+    }
+
+    fn mint_token(
+        &mut self,
+        token_owner: Address,
+        token_meta_data: String,
+        token_hash: Maybe<String>,
+    ) -> MintReceipt {
+        // Ordinal ids are assigned sequentially, so the next id is the number of minted tokens
+        let token_id = self.cep78.get_number_of_minted_tokens();
+        // Until the reveal, the real metadata never reaches the chain
+        let token_meta_data = self
+            .placeholder_metadata
+            .get_or_default()
+            .unwrap_or(token_meta_data);
+        let receipt = self.cep78.mint(token_owner, token_meta_data, token_hash);
+        if self.hash_identifiers.get_or_default() {
+            // The receipt ends with the token's identifier, its hash in Hash mode
+            self.token_index_by_hash.set(&receipt.2, token_id);
+        }
+        self.add_owned_token(token_owner, token_id);
+        receipt
+    }
+
+    // Resolves either identifier to the token's mint order, the key of the owner index,
+    // locks and burns.
+    fn token_index(&self, token_id: &Maybe<u64>, token_hash: &Maybe<String>) -> u64 {
+        match (token_id, token_hash) {
+            (Maybe::Some(id), _) => *id,
+            (Maybe::None, Maybe::Some(hash)) => self
+                .token_index_by_hash
+                .get(hash)
+                .unwrap_or_revert_with(&self.env(), Error::TokenNotFound),
+            (Maybe::None, Maybe::None) => self.env().revert(Error::TokenNotFound),
+        }
+    }
+
+    fn add_owned_token(&mut self, owner: Address, token_id: u64) {
+        let count = self.owned_token_count.get_or_default(&owner);
+        self.owned_tokens.set(&(owner, count), token_id);
+        self.owned_token_index.set(&token_id, count);
+        self.owned_token_count.set(&owner, count + 1);
+    }
+
+    // Moves the owner's last token into the removed token's slot to keep the list dense.
+    fn remove_owned_token(&mut self, owner: Address, token_id: u64) {
+        let last_index = self.owned_token_count.get_or_default(&owner) - 1;
+        let index = self.owned_token_index.get_or_default(&token_id);
+        if index != last_index {
+            let last_token = self.owned_tokens.get_or_default(&(owner, last_index));
+            self.owned_tokens.set(&(owner, index), last_token);
+            self.owned_token_index.set(&last_token, index);
+        }
+        self.owned_token_count.set(&owner, last_index);
+    }
+
+    // Every minted token costs the current mint price, paid in full with the call.
+    fn collect_mint_payment(&mut self, count: u64) {
+        let price = self.mint_price.get_or_default() * U512::from(count);
+        if self.env().attached_value() != price {
+            self.env().revert(Error::IncorrectPayment);
+        }
+        self.proceeds.add(price);
+    }
+
+    fn can_transfer(&mut self, token_id: u64, source: Address) -> bool {
+        if !self.is_live_token_of(token_id, source) || self.is_token_locked(token_id) {
+            return false;
+        }
+        let caller = self.env().caller();
+        caller == source
+            || self.cep78.get_approved(Maybe::Some(token_id), Maybe::None) == Some(caller)
+            || self.cep78.is_approved_for_all(source, caller)
+    }
+
+    // Checks the token has been minted, hasn't been burnt and belongs to `owner`.
+    fn is_live_token_of(&self, token_id: u64, owner: Address) -> bool {
+        token_id < self.cep78.get_number_of_minted_tokens()
+            && !self.burnt_tokens.get_or_default(&token_id)
+            && self.cep78.owner_of(Maybe::Some(token_id), Maybe::None) == owner
+    }
+}
+
+fn maybe<T>(value: Option<T>) -> Maybe<T> {
+    match value {
+        Some(v) => Maybe::Some(v),
+        None => Maybe::None,
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use odra::host::Deployer;
+    use odra::casper_types::crypto::blake2b;
+    use odra::host::{Deployer, HostRef};
+
+    const CEP78_METADATA: &str = r#"{
+        "name": "Batch collection",
+        "token_uri": "https://www.batch-collection.io",
+        "checksum": "940bffb3f2bba35f84313aa26da09ece3ad47045c6a1292c2bbd2df4ab1a55fb"
+    }"#;
+
+    fn deploy() -> (odra::host::HostEnv, ExtendedCEP78HostRef) {
+        let env = odra_test::env();
+        let init_args = ExtendedCEP78InitArgs {
+            collection_name: "Batch Collection".to_string(),
+            collection_symbol: "BC".to_string(),
+            total_token_supply: 20,
+            nft_kind: NFTKind::Digital,
+            receipt_name: "receipt".to_string(),
+            options: Default::default(),
+        };
+        let contract = ExtendedCEP78HostRef::deploy(&env, init_args);
+        (env, contract)
+    }
+
     #[test]
     fn batch_mint() {
         // Deploy the contract
@@ -117,6 +691,7 @@ mod tests {
             total_token_supply: 20,
             nft_kind: NFTKind::Digital,
             receipt_name: "receipt".to_string(),
+            options: Default::default(),
         };
 
         let mut contract = ExtendedCEP78HostRef::deploy(&env, init_args);
@@ -140,4 +715,490 @@ mod tests {
         contract.batch_mint(alice, metadata);
         assert_eq!(contract.balance_of(alice), 20);
     }
+
+    #[test]
+    fn batch_transfer() {
+        let (env, mut contract) = deploy();
+        let alice = env.get_account(1);
+        let bob = env.get_account(2);
+        contract.batch_mint(alice, vec![CEP78_METADATA.to_string(); 5]);
+
+        // All or nothing: a single foreign token reverts the batch
+        env.set_caller(alice);
+        contract.batch_transfer(vec![0, 1], alice, bob, false);
+        assert!(contract
+            .try_batch_transfer(vec![2, 0], alice, bob, false)
+            .is_err());
+        assert_eq!(contract.balance_of(bob), 2);
+
+        // Skip and report: foreign and unknown tokens are skipped
+        let receipts = contract.batch_transfer(vec![2, 0, 99, 3], alice, bob, true);
+        assert_eq!(receipts.len(), 2);
+        assert!(env.emitted_event(
+            contract.address(),
+            &TransfersSkipped {
+                token_ids: vec![0, 99]
+            },
+        ));
+        assert_eq!(contract.balance_of(alice), 1);
+        assert_eq!(contract.balance_of(bob), 4);
+    }
+
+    #[test]
+    fn batch_burn() {
+        let (env, mut contract) = deploy();
+        let alice = env.get_account(1);
+        let bob = env.get_account(2);
+        contract.batch_mint(alice, vec![CEP78_METADATA.to_string(); 3]);
+        contract.batch_mint(bob, vec![CEP78_METADATA.to_string(); 2]);
+
+        env.set_caller(alice);
+        assert!(contract.try_batch_burn(vec![0, 3], false).is_err());
+
+        // Bob's tokens and already burnt tokens are skipped
+        contract.batch_burn(vec![0, 3, 1], true);
+        contract.batch_burn(vec![1, 2], true);
+        assert!(env.emitted_event(contract.address(), &BurnsSkipped { token_ids: vec![1] },));
+        assert_eq!(contract.balance_of(alice), 0);
+        assert_eq!(contract.balance_of(bob), 2);
+    }
+
+    #[test]
+    fn batch_approve_and_revoke_all() {
+        let (env, mut contract) = deploy();
+        let alice = env.get_account(1);
+        let bob = env.get_account(2);
+        let carol = env.get_account(3);
+        contract.batch_mint(alice, vec![CEP78_METADATA.to_string(); 3]);
+
+        env.set_caller(alice);
+        contract.batch_approve(bob, vec![0, 2]);
+        assert_eq!(
+            contract.get_approved(Maybe::Some(0), Maybe::None),
+            Some(bob)
+        );
+        assert_eq!(contract.get_approved(Maybe::Some(1), Maybe::None), None);
+        assert_eq!(
+            contract.get_approved(Maybe::Some(2), Maybe::None),
+            Some(bob)
+        );
+        assert!(env.emitted_event(
+            contract.address(),
+            &BatchApproved {
+                owner: alice,
+                spender: bob,
+                token_ids: vec![0, 2]
+            },
+        ));
+
+        contract.set_approval_for_all(true, carol);
+        contract.revoke_all(carol);
+        assert!(!contract.is_approved_for_all(alice, carol));
+        assert!(env.emitted_event(
+            contract.address(),
+            &OperatorRevoked {
+                owner: alice,
+                operator: carol
+            },
+        ));
+
+        // Approving someone else's token reverts the whole batch
+        env.set_caller(bob);
+        assert!(contract.try_batch_approve(carol, vec![1]).is_err());
+    }
+
+    #[test]
+    fn configurable_modalities() {
+        let env = odra_test::env();
+        let alice = env.get_account(1);
+        let init_args = ExtendedCEP78InitArgs {
+            collection_name: "Badges".to_string(),
+            collection_symbol: "BDG".to_string(),
+            total_token_supply: 10,
+            nft_kind: NFTKind::Digital,
+            receipt_name: "badges".to_string(),
+            options: ExtendedCEP78Options {
+                minting_mode: Some(MintingMode::Public),
+                burn_mode: Some(BurnMode::NonBurnable),
+                ..Default::default()
+            },
+        };
+        let mut contract = ExtendedCEP78HostRef::deploy(&env, init_args);
+
+        // Public minting lets anyone mint, and non-burnable tokens stay around
+        env.set_caller(alice);
+        contract.mint(alice, CEP78_METADATA.to_string(), Maybe::None);
+        assert_eq!(contract.balance_of(alice), 1);
+        assert!(contract.try_burn(Maybe::Some(0), Maybe::None).is_err());
+        assert_eq!(contract.balance_of(alice), 1);
+    }
+
+    #[test]
+    fn paid_minting() {
+        let env = odra_test::env();
+        let owner = env.get_account(0);
+        let alice = env.get_account(1);
+        let treasury = env.get_account(2);
+        let init_args = ExtendedCEP78InitArgs {
+            collection_name: "Paid Collection".to_string(),
+            collection_symbol: "PAID".to_string(),
+            total_token_supply: 10,
+            nft_kind: NFTKind::Digital,
+            receipt_name: "paid".to_string(),
+            options: ExtendedCEP78Options {
+                minting_mode: Some(MintingMode::Public),
+                ..Default::default()
+            },
+        };
+        let mut contract = ExtendedCEP78HostRef::deploy(&env, init_args);
+        contract.set_mint_price(U512::from(100));
+
+        // Only the owner sets the price
+        env.set_caller(alice);
+        assert!(contract.try_set_mint_price(U512::zero()).is_err());
+
+        assert_eq!(
+            contract.with_tokens(U512::from(50)).try_mint(
+                alice,
+                CEP78_METADATA.to_string(),
+                Maybe::None
+            ),
+            Err(Error::IncorrectPayment.into())
+        );
+        contract
+            .with_tokens(U512::from(100))
+            .mint(alice, CEP78_METADATA.to_string(), Maybe::None);
+        assert_eq!(
+            contract
+                .with_tokens(U512::from(200))
+                .try_batch_mint(alice, vec![CEP78_METADATA.to_string(); 3]),
+            Err(Error::IncorrectPayment.into())
+        );
+        contract
+            .with_tokens(U512::from(300))
+            .batch_mint(alice, vec![CEP78_METADATA.to_string(); 3]);
+        assert_eq!(contract.balance_of(alice), 4);
+
+        // The owner withdraws the proceeds
+        assert!(contract.try_withdraw_proceeds(alice).is_err());
+        let treasury_balance = env.balance_of(&treasury);
+        env.set_caller(owner);
+        contract.withdraw_proceeds(treasury);
+        assert_eq!(
+            env.balance_of(&treasury),
+            treasury_balance + U512::from(400)
+        );
+    }
+
+    #[test]
+    fn allowlist_presale() {
+        let env = odra_test::env();
+        let alice = env.get_account(1);
+        let bob = env.get_account(2);
+        let init_args = ExtendedCEP78InitArgs {
+            collection_name: "Presale Collection".to_string(),
+            collection_symbol: "PRE".to_string(),
+            total_token_supply: 10,
+            nft_kind: NFTKind::Digital,
+            receipt_name: "presale".to_string(),
+            options: ExtendedCEP78Options {
+                minting_mode: Some(MintingMode::Public),
+                ..Default::default()
+            },
+        };
+        let mut contract = ExtendedCEP78HostRef::deploy(&env, init_args);
+        contract.start_presale(1_000, 2);
+        contract.add_to_allowlist(vec![alice]);
+
+        // During the presale, only allowlisted accounts mint, up to the cap
+        env.set_caller(alice);
+        contract.batch_mint(alice, vec![CEP78_METADATA.to_string(); 2]);
+        assert_eq!(
+            contract.try_mint(alice, CEP78_METADATA.to_string(), Maybe::None),
+            Err(Error::PresaleCapExceeded.into())
+        );
+        env.set_caller(bob);
+        assert_eq!(
+            contract.try_mint(bob, CEP78_METADATA.to_string(), Maybe::None),
+            Err(Error::NotAllowlisted.into())
+        );
+
+        // Afterwards, minting is open to everyone
+        env.advance_block_time(1_000);
+        assert!(!contract.is_presale_active());
+        contract.mint(bob, CEP78_METADATA.to_string(), Maybe::None);
+        env.set_caller(alice);
+        contract.mint(alice, CEP78_METADATA.to_string(), Maybe::None);
+        assert_eq!(contract.balance_of(alice), 3);
+        assert_eq!(contract.balance_of(bob), 1);
+    }
+
+    #[test]
+    fn enumerable_tokens() {
+        let (env, mut contract) = deploy();
+        let alice = env.get_account(1);
+        let bob = env.get_account(2);
+        contract.batch_mint(alice, vec![CEP78_METADATA.to_string(); 4]);
+        contract.mint(bob, CEP78_METADATA.to_string(), Maybe::None);
+        assert_eq!(contract.total_minted(), 5);
+        assert_eq!(contract.tokens_of_owner(alice, 0, 10), vec![0, 1, 2, 3]);
+        assert_eq!(contract.tokens_of_owner(alice, 1, 2), vec![1, 2]);
+        assert_eq!(contract.tokens_of_owner(bob, 0, 10), vec![4]);
+
+        // Transfers and burns keep the index up to date
+        env.set_caller(alice);
+        contract.transfer(Maybe::Some(1), Maybe::None, alice, bob);
+        contract.burn(Maybe::Some(0), Maybe::None);
+        assert_eq!(contract.tokens_of_owner(alice, 0, 10), vec![2, 3]);
+        assert_eq!(contract.tokens_of_owner(bob, 0, 10), vec![4, 1]);
+        assert!(contract.tokens_of_owner(alice, 5, 10).is_empty());
+        assert_eq!(contract.total_minted(), 5);
+    }
+
+    #[test]
+    fn enumerable_hash_tokens() {
+        let env = odra_test::env();
+        let alice = env.get_account(1);
+        let bob = env.get_account(2);
+        let init_args = ExtendedCEP78InitArgs {
+            collection_name: "Hashed Collection".to_string(),
+            collection_symbol: "HC".to_string(),
+            total_token_supply: 10,
+            nft_kind: NFTKind::Digital,
+            receipt_name: "hashed".to_string(),
+            options: ExtendedCEP78Options {
+                identifier_mode: Some(NFTIdentifierMode::Hash),
+                ..Default::default()
+            },
+        };
+        let mut contract = ExtendedCEP78HostRef::deploy(&env, init_args);
+        // Hashes are derived from the metadata, so every token needs its own
+        let hashes: Vec<String> = ["First", "Second", "Third"]
+            .iter()
+            .map(|name| {
+                let metadata = CEP78_METADATA.replace("Batch collection", name);
+                contract.mint(alice, metadata, Maybe::None).2
+            })
+            .collect();
+
+        // Transfers and burns by hash keep the index up to date
+        env.set_caller(alice);
+        contract.transfer(Maybe::None, Maybe::Some(hashes[0].clone()), alice, bob);
+        contract.burn(Maybe::None, Maybe::Some(hashes[2].clone()));
+        assert_eq!(contract.tokens_of_owner(alice, 0, 10), vec![1]);
+        assert_eq!(contract.tokens_of_owner(bob, 0, 10), vec![0]);
+        assert_eq!(
+            contract.try_burn(Maybe::None, Maybe::Some("unknown".to_string())),
+            Err(Error::TokenNotFound.into())
+        );
+    }
+
+    #[test]
+    fn soulbound_tokens() {
+        let (env, mut contract) = deploy();
+        let alice = env.get_account(1);
+        let bob = env.get_account(2);
+        contract.batch_mint(alice, vec![CEP78_METADATA.to_string(); 2]);
+        contract.lock_token(0);
+
+        // Only the contract owner manages locks
+        env.set_caller(alice);
+        assert!(contract.try_unlock_token(0).is_err());
+        assert_eq!(
+            contract.try_transfer(Maybe::Some(0), Maybe::None, alice, bob),
+            Err(Error::TokenLocked.into())
+        );
+        contract.batch_transfer(vec![0, 1], alice, bob, true);
+        assert!(env.emitted_event(contract.address(), &TransfersSkipped { token_ids: vec![0] },));
+
+        env.set_caller(env.get_account(0));
+        contract.unlock_token(0);
+        env.set_caller(alice);
+        contract.transfer(Maybe::Some(0), Maybe::None, alice, bob);
+        assert_eq!(contract.balance_of(bob), 2);
+    }
+
+    #[test]
+    fn metadata_reveal() {
+        let (env, mut contract) = deploy();
+        let alice = env.get_account(1);
+        let placeholder = r#"{
+            "name": "Mystery box",
+            "token_uri": "https://www.batch-collection.io/hidden",
+            "checksum": "0000000000000000000000000000000000000000000000000000000000000000"
+        }"#;
+        let revealed: Vec<String> = ["First", "Second"]
+            .iter()
+            .map(|name| CEP78_METADATA.replace("Batch collection", name))
+            .collect();
+        let base_checksum = hex(&blake2b(revealed.to_bytes().unwrap()));
+        assert_eq!(
+            contract.try_reveal(revealed.clone()),
+            Err(Error::NothingToReveal.into())
+        );
+        contract.hide_metadata(placeholder.to_string(), base_checksum.clone());
+        contract.batch_mint(alice, revealed.clone());
+        // Only the placeholder is minted, so nothing gives the drop away
+        assert!(contract
+            .metadata(Maybe::Some(1), Maybe::None)
+            .contains("Mystery box"));
+
+        // Only the owner reveals, and only the committed metadata
+        env.set_caller(alice);
+        assert!(contract.try_reveal(revealed.clone()).is_err());
+        env.set_caller(env.get_account(0));
+        let swapped = vec![revealed[1].clone(), revealed[0].clone()];
+        assert_eq!(
+            contract.try_reveal(swapped),
+            Err(Error::ChecksumMismatch.into())
+        );
+        contract.reveal(revealed.clone());
+        assert_eq!(contract.metadata(Maybe::Some(1), Maybe::None), revealed[1]);
+        assert_eq!(contract.reveal_checksum(), Some(base_checksum.clone()));
+        assert_eq!(
+            contract.try_hide_metadata(placeholder.to_string(), base_checksum.clone()),
+            Err(Error::AlreadyRevealed.into())
+        );
+
+        // A drop can't be hidden once tokens carry their real metadata
+        let (_, mut contract) = deploy();
+        contract.mint(alice, CEP78_METADATA.to_string(), Maybe::None);
+        assert_eq!(
+            contract.try_hide_metadata(placeholder.to_string(), base_checksum),
+            Err(Error::MintingStarted.into())
+        );
+    }
+
+    #[test]
+    fn paused_minting_and_wallet_limit() {
+        let (env, mut contract) = deploy();
+        let alice = env.get_account(1);
+        contract.set_max_per_wallet(3);
+        contract.pause_minting();
+        assert_eq!(
+            contract.try_mint(alice, CEP78_METADATA.to_string(), Maybe::None),
+            Err(Error::MintingPaused.into())
+        );
+
+        contract.unpause_minting();
+        contract.batch_mint(alice, vec![CEP78_METADATA.to_string(); 2]);
+        assert_eq!(
+            contract.try_batch_mint(alice, vec![CEP78_METADATA.to_string(); 2]),
+            Err(Error::MintLimitExceeded.into())
+        );
+        contract.mint(alice, CEP78_METADATA.to_string(), Maybe::None);
+
+        // Transferring tokens away doesn't free up the limit
+        env.set_caller(alice);
+        contract.transfer(Maybe::Some(0), Maybe::None, alice, env.get_account(2));
+        env.set_caller(env.get_account(0));
+        assert_eq!(
+            contract.try_mint(alice, CEP78_METADATA.to_string(), Maybe::None),
+            Err(Error::MintLimitExceeded.into())
+        );
+
+        // Only the owner pauses minting
+        env.set_caller(alice);
+        assert!(contract.try_pause_minting().is_err());
+    }
+
+    #[test]
+    fn royalty_registry() {
+        let (env, mut contract) = deploy();
+        let owner = env.get_account(0);
+        let artist = env.get_account(1);
+        let collaborator = env.get_account(2);
+        contract.batch_mint(artist, vec![CEP78_METADATA.to_string(); 2]);
+        assert_eq!(
+            contract.royalty_info(0, U512::from(1_000)),
+            (owner, U512::zero())
+        );
+
+        assert_eq!(
+            contract.try_set_default_royalty(artist, 10_001),
+            Err(Error::InvalidRoyalty.into())
+        );
+        contract.set_default_royalty(artist, 500);
+        contract.set_token_royalty(1, collaborator, 1_000);
+        assert_eq!(
+            contract.royalty_info(0, U512::from(1_000)),
+            (artist, U512::from(50))
+        );
+        assert_eq!(
+            contract.royalty_info(1, U512::from(1_000)),
+            (collaborator, U512::from(100))
+        );
+
+        env.set_caller(artist);
+        assert!(contract.try_set_default_royalty(artist, 10_000).is_err());
+    }
+
+    #[test]
+    fn voucher_minting() {
+        let env = odra_test::env();
+        let init_args = ExtendedCEP78InitArgs {
+            collection_name: "Voucher Collection".to_string(),
+            collection_symbol: "VCH".to_string(),
+            total_token_supply: 10,
+            nft_kind: NFTKind::Digital,
+            receipt_name: "voucher".to_string(),
+            // Vouchers are redeemed by the buyers themselves
+            options: ExtendedCEP78Options {
+                minting_mode: Some(MintingMode::Public),
+                ..Default::default()
+            },
+        };
+        let mut contract = ExtendedCEP78HostRef::deploy(&env, init_args);
+        let owner = env.get_account(0);
+        let alice = env.get_account(1);
+        let voucher = Voucher {
+            recipient: alice,
+            token_meta_data: CEP78_METADATA.to_string(),
+            price: U512::from(100),
+            nonce: 1,
+        };
+        let message = Bytes::from((*contract.address(), voucher.clone()).to_bytes().unwrap());
+        let signature = env.sign_message(&message, &owner);
+        assert_eq!(
+            contract.try_mint_with_voucher(voucher.clone(), signature.clone()),
+            Err(Error::VoucherSignerNotSet.into())
+        );
+        contract.set_voucher_signer(env.public_key(&owner));
+
+        // Vouchers signed by someone else are rejected
+        env.set_caller(alice);
+        let forged = env.sign_message(&message, &alice);
+        assert_eq!(
+            contract
+                .with_tokens(U512::from(100))
+                .try_mint_with_voucher(voucher.clone(), forged),
+            Err(Error::InvalidVoucherSignature.into())
+        );
+        assert_eq!(
+            contract
+                .with_tokens(U512::from(50))
+                .try_mint_with_voucher(voucher.clone(), signature.clone()),
+            Err(Error::IncorrectPayment.into())
+        );
+
+        // Vouchers signed for any other contract address are rejected too
+        let elsewhere = Bytes::from((alice, voucher.clone()).to_bytes().unwrap());
+        assert_eq!(
+            contract
+                .with_tokens(U512::from(100))
+                .try_mint_with_voucher(voucher.clone(), env.sign_message(&elsewhere, &owner)),
+            Err(Error::InvalidVoucherSignature.into())
+        );
+
+        contract
+            .with_tokens(U512::from(100))
+            .mint_with_voucher(voucher.clone(), signature.clone());
+        assert_eq!(contract.balance_of(alice), 1);
+        assert_eq!(
+            contract
+                .with_tokens(U512::from(100))
+                .try_mint_with_voucher(voucher, signature),
+            Err(Error::VoucherAlreadyUsed.into())
+        );
+    }
 }
@@ -0,0 +1,196 @@
+//! Runs a full CSPR auction on a live network: deploys a CEP-78 collection and the auctions
+//! contract, mints and auctions a token, places bids from two accounts and ends the auction.
+//!
+//! The bidders are read from `ODRA_CASPER_LIVENET_KEY_1` and `ODRA_CASPER_LIVENET_KEY_2`.
+use std::fmt::Debug;
+use std::thread::sleep;
+use std::time::Duration;
+
+use auctions::auctions::{AuctionsHostRef, AuctionsInitArgs};
+use auctions::custody::NftId;
+use odra::args::Maybe;
+use odra::casper_types::U512;
+use odra::host::{Deployer, HostEnv, HostRef};
+use odra::prelude::OdraResult;
+use odra_modules::cep78::modalities::{
+    EventsMode, MetadataMutability, NFTIdentifierMode, NFTKind, NFTMetadataKind, OwnershipMode,
+};
+use odra_modules::cep78::token::{Cep78HostRef, Cep78InitArgs};
+use odra_modules::cep78::utils::InitArgsBuilder;
+use tutorial_utils::gas::{self, with_gas};
+use tutorial_utils::manifest::record_deployment;
+use tutorial_utils::network;
+
+const CEP78_METADATA: &str = r#"{
+    "name": "John Doe",
+    "token_uri": "https://www.barfoo.com",
+    "checksum": "940bffb3f2bba35f84313aa26da09ece3ad47045c6a1292c2bbd2df4ab1a55fb"
+}"#;
+const COLLECTION_NAME: &str = "Auctioned Collection";
+const COLLECTION_SYMBOL: &str = "AUC";
+const RECEIPT_NAME: &str = "auction_receipt";
+const DURATION: u64 = 5 * 60 * 1000; // five minutes
+const CSPR: u64 = 1_000_000_000;
+
+fn main() {
+    network::configure_from_args();
+    let env = odra_casper_livenet_env::env();
+    let seller = env.caller();
+    let alice = env.get_account(1);
+    let bob = env.get_account(2);
+
+    let mut nft = deploy_collection(&env);
+    let mut auctions = deploy_auctions(&env);
+    println!("Collection address: {}", nft.address().to_string());
+    println!("Auctions address: {}", auctions.address().to_string());
+
+    check(
+        "Allow the collection",
+        with_gas(&env, gas::CALL, || {
+            auctions.try_allow_collection(*nft.address())
+        }),
+    );
+
+    // casper contract may return a result or not, so deserialization may fail - the mint is verified by querying the contract
+    let _ = with_gas(&env, gas::MINT, || {
+        nft.try_mint(seller, CEP78_METADATA.to_string(), Maybe::None)
+    });
+    let token_id = nft.get_number_of_minted_tokens() - 1;
+    assert_eq!(nft.owner_of(Maybe::Some(token_id), Maybe::None), seller);
+    println!("Minted token #{}", token_id);
+
+    check(
+        "Approve the auctions contract",
+        with_gas(&env, gas::CALL, || {
+            nft.try_approve(*auctions.address(), Maybe::Some(token_id), Maybe::None)
+        }),
+    );
+    let _ = with_gas(&env, gas::TRANSFER, || {
+        auctions.try_create_auction(
+            *nft.address(),
+            NftId::Ordinal(token_id),
+            U512::from(CSPR),
+            U512::zero(),
+            U512::zero(),
+            None,
+            None,
+            None,
+            DURATION,
+        )
+    });
+    let ends_at = env.block_time() + DURATION;
+    let auction_id = auctions.latest_auction_id();
+    assert_eq!(
+        nft.owner_of(Maybe::Some(token_id), Maybe::None),
+        *auctions.address()
+    );
+    println!("Created auction #{}", auction_id);
+
+    env.set_caller(alice);
+    check(
+        "Alice bids 2 CSPR",
+        with_gas(&env, gas::BID, || {
+            auctions
+                .with_tokens(U512::from(2 * CSPR))
+                .try_bid(auction_id)
+        }),
+    );
+    env.set_caller(bob);
+    check(
+        "Bob bids 3 CSPR",
+        with_gas(&env, gas::BID, || {
+            auctions
+                .with_tokens(U512::from(3 * CSPR))
+                .try_bid(auction_id)
+        }),
+    );
+    println!("Auction: {:?}", auctions.get_auction(auction_id));
+
+    // Block time can't be advanced on a live network, so wait for the auction to end
+    while env.block_time() <= ends_at {
+        println!("Waiting for the auction to end...");
+        sleep(Duration::from_secs(30));
+    }
+
+    env.set_caller(seller);
+    check(
+        "End the auction",
+        with_gas(&env, gas::TRANSFER, || auctions.try_end_auction(auction_id)),
+    );
+    assert_eq!(nft.owner_of(Maybe::Some(token_id), Maybe::None), bob);
+    println!("Token #{} now belongs to Bob", token_id);
+
+    env.set_caller(alice);
+    check(
+        "Alice withdraws her refund",
+        with_gas(&env, gas::TRANSFER, || auctions.try_withdraw_refund(None)),
+    );
+}
+
+/// Prints the receipt of a step and stops the script if it failed.
+fn check<T: Debug>(step: &str, result: OdraResult<T>) -> T {
+    match result {
+        Ok(value) => {
+            println!("{}: ok {:?}", step, value);
+            value
+        }
+        Err(error) => panic!("{}: failed with {:?}", step, error),
+    }
+}
+
+/// Deploys a CEP-78 collection with transferable tokens.
+pub fn deploy_collection(env: &HostEnv) -> Cep78HostRef {
+    let nft = with_gas(env, gas::DEPLOY, || {
+        Cep78HostRef::try_deploy(env, collection_init_args())
+    })
+    .expect("Should deploy the collection");
+    record_deployment(
+        "Cep78",
+        &nft,
+        None,
+        &[
+            ("collection_name", COLLECTION_NAME.to_string()),
+            ("collection_symbol", COLLECTION_SYMBOL.to_string()),
+            ("total_token_supply", "100".to_string()),
+            ("receipt_name", RECEIPT_NAME.to_string()),
+        ],
+    );
+    nft
+}
+
+/// Init args of the auctioned collection.
+fn collection_init_args() -> Cep78InitArgs {
+    InitArgsBuilder::default()
+        .collection_name(COLLECTION_NAME.to_string())
+        .collection_symbol(COLLECTION_SYMBOL.to_string())
+        .total_token_supply(100)
+        .ownership_mode(OwnershipMode::Transferable)
+        .nft_metadata_kind(NFTMetadataKind::CEP78)
+        .identifier_mode(NFTIdentifierMode::Ordinal)
+        .nft_kind(NFTKind::Digital)
+        .metadata_mutability(MetadataMutability::Immutable)
+        .receipt_name(RECEIPT_NAME.to_string())
+        .events_mode(EventsMode::CES)
+        .build()
+}
+
+/// Deploys the auctions contract, owned by the deployer.
+pub fn deploy_auctions(env: &HostEnv) -> AuctionsHostRef {
+    let auctions = with_gas(env, gas::DEPLOY, || {
+        AuctionsHostRef::try_deploy(
+            env,
+            AuctionsInitArgs {
+                admin: None,
+                min_auction_duration: DURATION,
+            },
+        )
+    })
+    .expect("Should deploy the auctions contract");
+    record_deployment(
+        "Auctions",
+        &auctions,
+        None,
+        &[("min_auction_duration", DURATION.to_string())],
+    );
+    auctions
+}
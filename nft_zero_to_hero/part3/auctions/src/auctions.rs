@@ -1,13 +1,17 @@
+use crate::custody::{self, NftId};
+use crate::payouts;
 use odra::{
-    args::Maybe,
     casper_types::{U256, U512},
     module::Module,
+    prelude::*,
     Address, ContractRef, Mapping, SubModule, Var,
 };
-use odra_modules::cep78::token::Cep78ContractRef;
-use odra_modules::{access::Ownable, security::Pauseable};
+use odra_modules::{access::Ownable, cep18_token::Cep18ContractRef, security::Pauseable};
 
-#[odra::module]
+#[odra::module(
+    events = [AuctionCreated, BidPlaced, AuctionEnded, AuctionCancelled, RoyaltyPaid],
+    errors = Error
+)]
 /// This contract facilitates NFT auctions, allowing users to create and participate in auctions for CEP-78 NFTs.
 pub struct Auctions {
     /// Ownable submodule for managing contract ownership and permissions.
@@ -20,6 +24,18 @@ pub struct Auctions {
     auction_counter: Var<U256>,
     /// Minimum allowed duration for an auction, set by the contract owner.
     min_auction_duration: Var<u64>,
+    /// Outbid amounts waiting to be withdrawn, per bidder and currency (None for CSPR).
+    pending_returns: Mapping<(Address, Option<Address>), U512>,
+    /// NFTs the contract holds for active auctions.
+    nfts_in_custody: Mapping<(Address, NftId), bool>,
+    /// CSPR the contract owes to bidders and sellers.
+    cspr_in_custody: Var<U512>,
+    /// CEP-78 contracts the owner allows to be auctioned.
+    allowed_collections: Mapping<Address, bool>,
+    /// IDs of the auctions created by each seller.
+    auctions_by_seller: Mapping<Address, Vec<U256>>,
+    /// IDs of the auctions each bidder has bid on.
+    auctions_by_bidder: Mapping<Address, Vec<U256>>,
 }
 
 #[odra::odra_error]
@@ -33,6 +49,123 @@ pub enum Error {
     AuctionHasEnded = 3,
     /// Attempted to end an auction that is still in progress.
     AuctionStillInProgress = 4,
+    /// The caller has no outbid amounts to withdraw.
+    NothingToWithdraw = 5,
+    /// The bid doesn't exceed the highest bid by the minimum increment.
+    BidIncrementTooLow = 6,
+    /// The auction has no buy-now price.
+    BuyNowNotAvailable = 7,
+    /// The attached amount doesn't match the buy-now price.
+    IncorrectBuyNowAmount = 8,
+    /// Only the seller can perform this action.
+    NotSeller = 9,
+    /// The auction already has bids and can no longer be cancelled.
+    AuctionHasBids = 10,
+    /// The auction has already been settled or cancelled.
+    AuctionNotActive = 11,
+    /// The bid was paid in a different currency than the auction accepts.
+    WrongCurrency = 12,
+    /// The amount doesn't fit into a CEP-18 token amount.
+    AmountTooLarge = 13,
+    /// The royalty exceeds 100% of the winning bid.
+    InvalidRoyalty = 14,
+    /// The NFT contract isn't on the marketplace's allowlist.
+    CollectionNotAllowed = 15,
+    /// The NFT is held for an active auction and can't be rescued.
+    NftInCustody = 16,
+    /// The amount exceeds the CSPR that isn't owed to bidders or sellers.
+    AmountInCustody = 17,
+    /// There is no auction with the given ID.
+    AuctionNotFound = 18,
+}
+
+#[odra::odra_type]
+/// Lifecycle of an auction.
+pub enum AuctionStatus {
+    /// The auction accepts bids until it ends.
+    Active,
+    /// The auction has ended and the NFT and funds have been distributed.
+    Settled,
+    /// The seller withdrew the NFT before any bid was placed.
+    Cancelled,
+}
+
+#[odra::event]
+/// Emitted when a seller puts an NFT up for auction.
+pub struct AuctionCreated {
+    /// ID of the new auction.
+    pub auction_id: U256,
+    /// Seller who created the auction.
+    pub seller: Address,
+    /// Address of the CEP-78 NFT contract.
+    pub nft_contract: Address,
+    /// ID of the auctioned NFT.
+    pub nft_id: NftId,
+}
+
+#[odra::event]
+/// Emitted when a bid becomes the highest bid of an auction.
+pub struct BidPlaced {
+    /// ID of the auction.
+    pub auction_id: U256,
+    /// Bidder who placed the bid.
+    pub bidder: Address,
+    /// Amount of the bid in the auction's payment currency.
+    pub amount: U512,
+}
+
+#[odra::event]
+/// Emitted when an auction is settled.
+pub struct AuctionEnded {
+    /// ID of the settled auction.
+    pub auction_id: U256,
+    /// Winner of the NFT, or None if it went back to the seller.
+    pub winner: Option<Address>,
+    /// Winning amount, split between the seller and the royalty receiver (if any).
+    pub amount: U512,
+}
+
+#[odra::event]
+/// Emitted when a seller cancels an auction and takes the NFT back.
+pub struct AuctionCancelled {
+    /// ID of the cancelled auction.
+    pub auction_id: U256,
+    /// Seller who received the NFT back.
+    pub seller: Address,
+}
+
+#[odra::event]
+/// Emitted when part of the winning amount is paid out as a creator royalty.
+pub struct RoyaltyPaid {
+    /// ID of the settled auction.
+    pub auction_id: U256,
+    /// Receiver of the royalty.
+    pub receiver: Address,
+    /// Royalty amount in the auction's payment currency.
+    pub amount: U512,
+}
+
+#[odra::odra_type]
+/// Parameters of a single auction, as passed to `create_auctions`.
+pub struct AuctionRequest {
+    /// Address of the CEP-78 NFT contract.
+    pub nft_contract: Address,
+    /// ID of the NFT to auction.
+    pub nft_id: NftId,
+    /// Starting price in the payment currency.
+    pub starting_price: U512,
+    /// Lowest winning bid the seller accepts.
+    pub reserve_price: U512,
+    /// Minimum amount by which a bid has to exceed the current highest bid.
+    pub min_increment: U512,
+    /// Optional price at which the NFT can be bought immediately.
+    pub buy_now_price: Option<U512>,
+    /// Optional CEP-18 token the auction is paid in (None for CSPR).
+    pub payment_token: Option<Address>,
+    /// Optional creator royalty as a receiver and its share in basis points.
+    pub royalty: Option<(Address, u16)>,
+    /// Duration of the auction.
+    pub duration: u64,
 }
 
 #[odra::odra_type]
@@ -42,16 +175,28 @@ pub struct Auction {
     seller: Address,
     /// Address of the CEP-78 NFT contract.
     nft_contract: Address,
-    /// ID of the NFT being auctioned.
-    nft_id: u64,
-    /// Starting price of the auction in CSPR.
+    /// ID of the NFT being auctioned, either ordinal or hash-based.
+    nft_id: NftId,
+    /// Starting price of the auction in the payment currency.
     starting_price: U512,
+    /// Lowest winning bid the seller accepts; below it the NFT goes back to the seller.
+    reserve_price: U512,
+    /// Minimum amount by which a bid has to exceed the current highest bid.
+    min_increment: U512,
+    /// Optional price at which the NFT can be bought immediately.
+    buy_now_price: Option<U512>,
+    /// Optional CEP-18 token the auction is paid in (None for CSPR).
+    payment_token: Option<Address>,
+    /// Optional creator royalty as a receiver and its share in basis points.
+    royalty: Option<(Address, u16)>,
     /// Timestamp of when the auction ends.
     ends_at: u64,
     /// Optional address of the highest bidder (None if no bids yet).
     highest_bidder: Option<Address>,
-    /// Amount of the highest bid in CSPR.
+    /// Amount of the highest bid in the payment currency.
     highest_bid: U512,
+    /// Current status of the auction.
+    status: AuctionStatus,
 }
 
 #[odra::module]
@@ -70,120 +215,1339 @@ impl Auctions {
      * TRANSACTIONS
      **********/
 
-    /// Creates a new auction for a CEP-78 NFT.
+    /// Creates a new auction for a CEP-78 NFT, paid in CSPR or in the given CEP-18 token.
     pub fn create_auction(
         &mut self,
         nft_contract: Address,
-        nft_id: u64,
+        nft_id: NftId,
         starting_price: U512,
+        reserve_price: U512,
+        min_increment: U512,
+        buy_now_price: Option<U512>,
+        payment_token: Option<Address>,
+        royalty: Option<(Address, u16)>,
         duration: u64,
     ) {
+        self.open_auction(AuctionRequest {
+            nft_contract,
+            nft_id,
+            starting_price,
+            reserve_price,
+            min_increment,
+            buy_now_price,
+            payment_token,
+            royalty,
+            duration,
+        });
+    }
+
+    /// Creates an auction for each request in one call and returns the assigned IDs.
+    ///
+    /// Every request is validated like in `create_auction`; if any of them fails, none is created.
+    pub fn create_auctions(&mut self, items: Vec<AuctionRequest>) -> Vec<U256> {
+        let mut auction_ids: Vec<U256> = Vec::new();
+        for item in items {
+            auction_ids.push(self.open_auction(item));
+        }
+        auction_ids
+    }
+
+    /// Places a bid on an active auction paid in CSPR.
+    #[odra(payable)] // Indicates this function accepts CSPR payments
+    pub fn bid(&mut self, auction_id: U256) {
+        let amount = self.env().attached_value(); // Get the attached CSPR amount
+        self.place_bid(auction_id, amount, false);
+    }
+
+    /// Places a bid on an active auction paid in a CEP-18 token.
+    ///
+    /// The bidder has to approve the contract for `amount` beforehand.
+    pub fn bid_with_token(&mut self, auction_id: U256, amount: U512) {
+        self.place_bid(auction_id, amount, true);
+    }
+
+    /// Buys the NFT at the buy-now price, ending the auction immediately.
+    ///
+    /// CSPR auctions take the attached amount, token auctions pull the price from the buyer's allowance.
+    /// Once a bid reaches the buy-now price, the NFT can only be won by bidding.
+    #[odra(payable)]
+    pub fn buy_now(&mut self, auction_id: U256) {
+        self.pausable.require_not_paused();
+
+        let buyer = self.env().caller();
+        let mut auction = self
+            .auctions
+            .get(&auction_id)
+            .unwrap_or_revert_with(&self.env(), Error::AuctionNotFound);
+        self.assert_active(&auction);
+        let now = self.env().get_block_time();
+
+        if now > auction.ends_at {
+            self.env().revert(Error::AuctionHasEnded);
+        }
+        let price = auction
+            .buy_now_price
+            .unwrap_or_revert_with(&self.env(), Error::BuyNowNotAvailable);
+        if auction.highest_bidder.is_some() && auction.highest_bid >= price {
+            self.env().revert(Error::BuyNowNotAvailable);
+        }
+        match auction.payment_token {
+            // CSPR attached to a token auction would be stuck in the contract
+            Some(_) if !self.env().attached_value().is_zero() => {
+                self.env().revert(Error::WrongCurrency)
+            }
+            None if self.env().attached_value() != price => {
+                self.env().revert(Error::IncorrectBuyNowAmount)
+            }
+            _ => {}
+        }
+
+        // The outstanding highest bidder (if any) can withdraw their bid
+        if let Some(highest_bidder) = auction.highest_bidder {
+            self.credit_refund(highest_bidder, auction.payment_token, auction.highest_bid);
+        }
+
+        // Close the auction with the buyer as the winner
+        auction.highest_bid = price;
+        auction.highest_bidder = Some(buyer);
+        auction.ends_at = now;
+        auction.status = AuctionStatus::Settled;
+        self.auctions.set(&auction_id, auction.clone());
+
+        self.collect_payment(&auction, buyer, price);
+        self.transfer_nft(&auction, buyer);
+        self.pay_seller(auction_id, &auction, price);
+        self.env().emit_event(AuctionEnded {
+            auction_id,
+            winner: Some(buyer),
+            amount: price,
+        });
+    }
+
+    /// Ends an auction and distributes the NFT and funds accordingly.
+    pub fn end_auction(&mut self, auction_id: U256) {
+        self.pausable.require_not_paused();
+        let mut auction = self.auctions.get(&auction_id).unwrap();
+        self.assert_active(&auction);
+
+        // Check if auction has ended
+        if self.env().get_block_time() < auction.ends_at {
+            self.env().revert(Error::AuctionStillInProgress);
+        }
+
+        // Mark the auction as settled before moving any assets
+        auction.status = AuctionStatus::Settled;
+        self.auctions.set(&auction_id, auction.clone());
+
+        // Transfer the NFT and funds
+        let (winner, amount) = match auction.highest_bidder {
+            Some(winner) if auction.highest_bid >= auction.reserve_price => {
+                self.transfer_nft(&auction, winner);
+                self.pay_seller(auction_id, &auction, auction.highest_bid);
+                (Some(winner), auction.highest_bid)
+            }
+            Some(highest_bidder) => {
+                // The reserve price wasn't met, refund the bid and return the NFT to the seller
+                self.credit_refund(highest_bidder, auction.payment_token, auction.highest_bid);
+                self.transfer_nft(&auction, auction.seller);
+                (None, U512::zero())
+            }
+            None => {
+                // No bids were placed, return the NFT to the seller
+                self.transfer_nft(&auction, auction.seller);
+                (None, U512::zero())
+            }
+        };
+        self.env().emit_event(AuctionEnded {
+            auction_id,
+            winner,
+            amount,
+        });
+    }
+
+    /// Cancels an auction that has no bids yet and returns the NFT to the seller.
+    pub fn cancel_auction(&mut self, auction_id: U256) {
+        self.pausable.require_not_paused();
+        let mut auction = self
+            .auctions
+            .get(&auction_id)
+            .unwrap_or_revert_with(&self.env(), Error::AuctionNotFound);
+        self.assert_active(&auction);
+
+        if self.env().caller() != auction.seller {
+            self.env().revert(Error::NotSeller);
+        }
+        if auction.highest_bidder.is_some() {
+            self.env().revert(Error::AuctionHasBids);
+        }
+
+        auction.status = AuctionStatus::Cancelled;
+        self.auctions.set(&auction_id, auction.clone());
+        self.transfer_nft(&auction, auction.seller);
+        self.env().emit_event(AuctionCancelled {
+            auction_id,
+            seller: auction.seller,
+        });
+    }
+
+    /// Transfers all outbid amounts owed to the caller in the given currency (None for CSPR).
+    pub fn withdraw_refund(&mut self, payment_token: Option<Address>) {
+        let caller = self.env().caller();
+        let amount = self
+            .pending_returns
+            .get_or_default(&(caller, payment_token));
+        if amount.is_zero() {
+            self.env().revert(Error::NothingToWithdraw);
+        }
+        self.pending_returns
+            .set(&(caller, payment_token), U512::zero()); // Clear before transferring
+        self.pay(payment_token, caller, amount);
+    }
+
+    /**********
+     * QUERIES
+     **********/
+
+    /// Returns the outbid amount the given bidder can withdraw in the given currency (None for CSPR).
+    pub fn pending_refund(&self, bidder: Address, payment_token: Option<Address>) -> U512 {
+        self.pending_returns
+            .get_or_default(&(bidder, payment_token))
+    }
+
+    /// Returns the auction with the given ID, if it exists.
+    pub fn get_auction(&self, auction_id: U256) -> Option<Auction> {
+        self.auctions.get(&auction_id)
+    }
+
+    /// Returns whether NFTs from the given CEP-78 contract can be auctioned.
+    pub fn is_collection_allowed(&self, nft_contract: Address) -> bool {
+        self.allowed_collections.get_or_default(&nft_contract)
+    }
+
+    /// Returns the IDs of all auctions created by the given seller.
+    pub fn auctions_of_seller(&self, seller: Address) -> Vec<U256> {
+        self.auctions_by_seller.get_or_default(&seller)
+    }
+
+    /// Returns the IDs of all auctions the given bidder has bid on.
+    pub fn auctions_bid_by(&self, bidder: Address) -> Vec<U256> {
+        self.auctions_by_bidder.get_or_default(&bidder)
+    }
+
+    /// Returns the ID of the most recently created auction, or zero if there are none.
+    pub fn latest_auction_id(&self) -> U256 {
+        self.auction_counter
+            .get_or_default()
+            .saturating_sub(U256::one())
+    }
+
+    /// Returns up to `limit` auctions that are still accepting bids, skipping the first `offset` of them.
+    pub fn get_active_auctions(&self, offset: u32, limit: u32) -> Vec<(U256, Auction)> {
+        let now = self.env().get_block_time();
+        let mut active = Vec::new();
+        let mut skipped = 0;
+        let mut auction_id = U256::one();
+        while auction_id <= self.latest_auction_id() && active.len() < limit as usize {
+            if let Some(auction) = self.auctions.get(&auction_id) {
+                if auction.status == AuctionStatus::Active && auction.ends_at >= now {
+                    if skipped < offset {
+                        skipped += 1;
+                    } else {
+                        active.push((auction_id, auction));
+                    }
+                }
+            }
+            auction_id += U256::one();
+        }
+        active
+    }
+
+    /**********
+     * ADMIN
+     **********/
+
+    /// Pauses the contract, preventing further interactions.
+    pub fn pause(&mut self) {
+        self.ownable.assert_owner(&self.env().caller());
+        self.pausable.pause();
+    }
+
+    /// Unpauses the contract, resuming normal operation.
+    pub fn unpause(&mut self) {
+        self.ownable.assert_owner(&self.env().caller());
+        self.pausable.unpause();
+    }
+
+    /// Sends an NFT that isn't part of an active auction, e.g. one transferred here by mistake, to `to`.
+    pub fn rescue(&mut self, nft_contract: Address, nft_id: NftId, to: Address) {
+        self.ownable.assert_owner(&self.env().caller());
+        if self
+            .nfts_in_custody
+            .get_or_default(&(nft_contract, nft_id.clone()))
+        {
+            self.env().revert(Error::NftInCustody);
+        }
+        custody::release_custody(self.env(), nft_contract, &nft_id, to);
+    }
+
+    /// Sends CSPR that isn't owed to bidders or sellers to `to`.
+    pub fn rescue_cspr(&mut self, amount: U512, to: Address) {
+        self.ownable.assert_owner(&self.env().caller());
+        let rescuable = self.env().self_balance() - self.cspr_in_custody.get_or_default();
+        if amount > rescuable {
+            self.env().revert(Error::AmountInCustody);
+        }
+        self.env().transfer_tokens(&to, &amount);
+    }
+
+    /// Allows NFTs from the given CEP-78 contract to be auctioned.
+    pub fn allow_collection(&mut self, nft_contract: Address) {
+        self.ownable.assert_owner(&self.env().caller());
+        self.allowed_collections.set(&nft_contract, true);
+    }
+
+    /// Stops new auctions for NFTs from the given CEP-78 contract; running auctions are unaffected.
+    pub fn revoke_collection(&mut self, nft_contract: Address) {
+        self.ownable.assert_owner(&self.env().caller());
+        self.allowed_collections.set(&nft_contract, false);
+    }
+
+    /**********
+     * INTERNAL
+     **********/
+
+    /// Reverts if the auction has been settled or cancelled.
+    fn assert_active(&self, auction: &Auction) {
+        if auction.status != AuctionStatus::Active {
+            self.env().revert(Error::AuctionNotActive);
+        }
+    }
+
+    /// Transfers the auctioned NFT out of the contract's custody.
+    fn transfer_nft(&mut self, auction: &Auction, to: Address) {
+        self.nfts_in_custody
+            .set(&(auction.nft_contract, auction.nft_id.clone()), false);
+        custody::release_custody(self.env(), auction.nft_contract, &auction.nft_id, to);
+    }
+
+    /// Validates an auction request, takes the NFT into custody and stores the auction.
+    fn open_auction(&mut self, request: AuctionRequest) -> U256 {
         self.pausable.require_not_paused(); // Ensure contract is not paused
 
-        if duration < self.min_auction_duration.get_or_default() {
+        if request.duration < self.min_auction_duration.get_or_default() {
             self.env().revert(Error::InvalidAuctionDuration) // Revert if duration is too short
         }
+        if !self.is_collection_allowed(request.nft_contract) {
+            self.env().revert(Error::CollectionNotAllowed);
+        }
+        if !payouts::is_valid(request.royalty, 0) {
+            self.env().revert(Error::InvalidRoyalty);
+        }
 
         let seller = self.env().caller();
+        let nft_contract = request.nft_contract;
+        let nft_id = request.nft_id;
 
         // Transfer the NFT to the auction contract
-        Cep78ContractRef::new(self.env(), nft_contract).transfer(
-            Maybe::Some(nft_id),
-            Maybe::None,
-            seller,
-            self.env().self_address(),
-        );
+        custody::take_custody(self.env(), nft_contract, &nft_id, seller);
+        self.nfts_in_custody
+            .set(&(nft_contract, nft_id.clone()), true);
 
         // Create and store the auction details
+        let auction_id = self.auction_counter.get_or_default();
         let auction = Auction {
             nft_contract,
-            nft_id,
+            nft_id: nft_id.clone(),
             seller,
-            starting_price,
+            starting_price: request.starting_price,
+            reserve_price: request.reserve_price,
+            min_increment: request.min_increment,
+            buy_now_price: request.buy_now_price,
+            payment_token: request.payment_token,
+            royalty: request.royalty,
             highest_bid: U512::zero(),
             highest_bidder: None,
-            ends_at: self.env().get_block_time() + duration,
+            ends_at: self.env().get_block_time() + request.duration,
+            status: AuctionStatus::Active,
         };
-        self.auctions
-            .set(&self.auction_counter.get_or_default(), auction);
+        self.auctions.set(&auction_id, auction);
         self.auction_counter.add(U256::one()); // Increment auction counter
+
+        let mut seller_auctions = self.auctions_by_seller.get_or_default(&seller);
+        seller_auctions.push(auction_id);
+        self.auctions_by_seller.set(&seller, seller_auctions);
+        self.env().emit_event(AuctionCreated {
+            auction_id,
+            seller,
+            nft_contract,
+            nft_id,
+        });
+        auction_id
     }
 
-    /// Places a bid on an active auction.
-    #[odra(payable)] // Indicates this function accepts CSPR payments
-    pub fn bid(&mut self, auction_id: U256) {
+    /// Validates and records a bid, taking the payment in the auction's currency.
+    fn place_bid(&mut self, auction_id: U256, amount: U512, paid_in_token: bool) {
         self.pausable.require_not_paused();
 
         let bidder = self.env().caller();
-        let amount = self.env().attached_value(); // Get the attached CSPR amount
         let mut auction = self.auctions.get(&auction_id).unwrap();
+        self.assert_active(&auction);
+        if auction.payment_token.is_some() != paid_in_token {
+            self.env().revert(Error::WrongCurrency);
+        }
 
         // Validate bid amount
-        if amount < auction.starting_price || amount < auction.highest_bid {
+        if amount < auction.starting_price {
             self.env().revert(Error::InvalidBid);
         }
+        if auction.highest_bidder.is_some() && amount < auction.highest_bid + auction.min_increment
+        {
+            self.env().revert(Error::BidIncrementTooLow);
+        }
 
         // Check if auction is still ongoing
         if self.env().get_block_time() > auction.ends_at {
             self.env().revert(Error::AuctionHasEnded);
         }
 
-        // Refund the previous highest bidder (if any)
+        // Let the previous highest bidder (if any) withdraw their bid later
         if let Some(highest_bidder) = auction.highest_bidder {
-            self.env()
-                .transfer_tokens(&highest_bidder, &auction.highest_bid);
+            self.credit_refund(highest_bidder, auction.payment_token, auction.highest_bid);
         }
 
         // Update the auction with the new highest bid and bidder
         auction.highest_bid = amount;
         auction.highest_bidder = Some(bidder);
-        self.auctions.set(&auction_id, auction);
+        self.auctions.set(&auction_id, auction.clone());
+        self.collect_payment(&auction, bidder, amount);
+
+        // Index the auction for the bidder, once no matter how often they bid
+        let mut bidder_auctions = self.auctions_by_bidder.get_or_default(&bidder);
+        if !bidder_auctions.contains(&auction_id) {
+            bidder_auctions.push(auction_id);
+            self.auctions_by_bidder.set(&bidder, bidder_auctions);
+        }
+        self.env().emit_event(BidPlaced {
+            auction_id,
+            bidder,
+            amount,
+        });
     }
 
-    /// Ends an auction and distributes the NFT and funds accordingly.
-    pub fn end_auction(&mut self, auction_id: U256) {
-        self.pausable.require_not_paused();
-        let auction = self.auctions.get(&auction_id).unwrap();
+    /// Pulls `amount` of the auction's token from the payer; CSPR payments come attached to the call.
+    fn collect_payment(&mut self, auction: &Auction, from: Address, amount: U512) {
+        if auction.payment_token.is_none() {
+            self.cspr_in_custody.add(amount);
+        }
+        if let Some(token) = auction.payment_token {
+            let contract = self.env().self_address();
+            Cep18ContractRef::new(self.env(), token).transfer_from(
+                &from,
+                &contract,
+                &self.to_token_amount(amount),
+            );
+        }
+    }
 
-        // Check if auction has ended
-        if self.env().get_block_time() < auction.ends_at {
-            self.env().revert(Error::AuctionStillInProgress);
+    /// Pays the winning amount to the seller, minus the royalty (if any).
+    fn pay_seller(&mut self, auction_id: U256, auction: &Auction, amount: U512) {
+        // Auctions don't charge a platform fee
+        let split = payouts::split(amount, auction.royalty, 0);
+        if let Some((receiver, _)) = auction.royalty {
+            if !split.royalty.is_zero() {
+                self.pay(auction.payment_token, receiver, split.royalty);
+                self.env().emit_event(RoyaltyPaid {
+                    auction_id,
+                    receiver,
+                    amount: split.royalty,
+                });
+            }
         }
+        self.pay(auction.payment_token, auction.seller, split.seller);
+    }
 
-        // Transfer the NFT and funds
-        if let Some(winner) = auction.highest_bidder {
-            Cep78ContractRef::new(self.env(), auction.nft_contract).transfer(
-                Maybe::Some(auction.nft_id),
-                Maybe::None,
-                self.env().self_address(),
-                winner,
+    /// Sends `amount` in the given currency (None for CSPR) to `to`.
+    fn pay(&mut self, payment_token: Option<Address>, to: Address, amount: U512) {
+        match payment_token {
+            Some(token) => Cep18ContractRef::new(self.env(), token)
+                .transfer(&to, &self.to_token_amount(amount)),
+            None => {
+                self.cspr_in_custody.subtract(amount);
+                self.env().transfer_tokens(&to, &amount);
+            }
+        }
+    }
+
+    /// Converts an amount to a CEP-18 token amount, reverting if it doesn't fit.
+    fn to_token_amount(&self, amount: U512) -> U256 {
+        if amount.bits() > 256 {
+            self.env().revert(Error::AmountTooLarge);
+        }
+        let mut bytes = [0u8; 64];
+        amount.to_little_endian(&mut bytes);
+        U256::from_little_endian(&bytes[..32])
+    }
+
+    /// Adds `amount` to the bidder's withdrawable refunds in the given currency.
+    fn credit_refund(&mut self, bidder: Address, payment_token: Option<Address>, amount: U512) {
+        let pending = self
+            .pending_returns
+            .get_or_default(&(bidder, payment_token));
+        self.pending_returns
+            .set(&(bidder, payment_token), pending + amount);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use odra::args::Maybe;
+    use odra::host::{Deployer, HostEnv, HostRef};
+    use odra_modules::cep18_token::{Cep18HostRef, Cep18InitArgs};
+    use odra_modules::cep78::modalities::{
+        MetadataMutability, NFTIdentifierMode, NFTKind, NFTMetadataKind, OwnershipMode,
+    };
+    use odra_modules::cep78::token::Cep78HostRef;
+    use odra_modules::cep78::utils::InitArgsBuilder;
+
+    const CEP78_METADATA: &str = r#"{
+        "name": "John Doe",
+        "token_uri": "https://www.barfoo.com",
+        "checksum": "940bffb3f2bba35f84313aa26da09ece3ad47045c6a1292c2bbd2df4ab1a55fb"
+    }"#;
+    const DURATION: u64 = 60 * 60 * 1000; // one hour
+
+    /// Deploys the auctions contract and an NFT collection, and puts the seller's token up for auction.
+    fn setup() -> (HostEnv, AuctionsHostRef, Cep78HostRef) {
+        let env = odra_test::env();
+        let seller = env.get_account(1);
+        let mut auctions = AuctionsHostRef::deploy(
+            &env,
+            AuctionsInitArgs {
+                admin: None,
+                min_auction_duration: DURATION,
+            },
+        );
+        let init_args = InitArgsBuilder::default()
+            .collection_name("Auctioned Collection".to_string())
+            .collection_symbol("AUC".to_string())
+            .total_token_supply(100)
+            .ownership_mode(OwnershipMode::Transferable)
+            .nft_metadata_kind(NFTMetadataKind::CEP78)
+            .identifier_mode(NFTIdentifierMode::Ordinal)
+            .nft_kind(NFTKind::Digital)
+            .metadata_mutability(MetadataMutability::Immutable)
+            .receipt_name("auction_receipt".to_string())
+            .build();
+        let mut nft = Cep78HostRef::deploy(&env, init_args);
+        nft.mint(seller, CEP78_METADATA.to_string(), Maybe::None);
+        auctions.allow_collection(*nft.address());
+
+        env.set_caller(seller);
+        nft.approve(*auctions.address(), Maybe::Some(0), Maybe::None);
+        auctions.create_auction(
+            *nft.address(),
+            NftId::Ordinal(0),
+            U512::from(100),
+            U512::zero(),
+            U512::zero(),
+            None,
+            None,
+            None,
+            DURATION,
+        );
+        (env, auctions, nft)
+    }
+
+    #[test]
+    fn stacked_outbids_are_withdrawn() {
+        let (env, mut auctions, nft) = setup();
+        let alice = env.get_account(2);
+        let bob = env.get_account(3);
+        let auction_id = auctions.latest_auction_id();
+
+        env.set_caller(alice);
+        auctions.with_tokens(U512::from(100)).bid(auction_id);
+        env.set_caller(bob);
+        auctions.with_tokens(U512::from(200)).bid(auction_id);
+        env.set_caller(alice);
+        auctions.with_tokens(U512::from(300)).bid(auction_id);
+        env.set_caller(bob);
+        auctions.with_tokens(U512::from(400)).bid(auction_id);
+
+        // Outbid amounts accumulate instead of being pushed back
+        assert_eq!(auctions.pending_refund(alice, None), U512::from(400));
+        assert_eq!(auctions.pending_refund(bob, None), U512::from(200));
+
+        let alice_balance = env.balance_of(&alice);
+        env.set_caller(alice);
+        auctions.withdraw_refund(None);
+        assert_eq!(env.balance_of(&alice), alice_balance + U512::from(400));
+        assert_eq!(auctions.pending_refund(alice, None), U512::zero());
+        assert_eq!(
+            auctions.try_withdraw_refund(None),
+            Err(Error::NothingToWithdraw.into())
+        );
+
+        // The winning bid is not refundable
+        env.advance_block_time(DURATION + 1);
+        auctions.end_auction(auction_id);
+        assert_eq!(nft.owner_of(Maybe::Some(0), Maybe::None), bob);
+        assert_eq!(auctions.pending_refund(bob, None), U512::from(200));
+    }
+
+    #[test]
+    fn reserve_price_and_increment() {
+        let (env, mut auctions, mut nft) = setup();
+        let seller = env.get_account(1);
+        let alice = env.get_account(2);
+        let bob = env.get_account(3);
+
+        // Put a second token up with a reserve of 1000 and increments of 100
+        env.set_caller(env.get_account(0));
+        nft.mint(seller, CEP78_METADATA.to_string(), Maybe::None);
+        env.set_caller(seller);
+        nft.approve(*auctions.address(), Maybe::Some(1), Maybe::None);
+        auctions.create_auction(
+            *nft.address(),
+            NftId::Ordinal(1),
+            U512::from(100),
+            U512::from(1_000),
+            U512::from(100),
+            None,
+            None,
+            None,
+            DURATION,
+        );
+        let auction_id = auctions.latest_auction_id();
+
+        env.set_caller(alice);
+        auctions.with_tokens(U512::from(500)).bid(auction_id);
+        env.set_caller(bob);
+        assert_eq!(
+            auctions.with_tokens(U512::from(599)).try_bid(auction_id),
+            Err(Error::BidIncrementTooLow.into())
+        );
+        auctions.with_tokens(U512::from(600)).bid(auction_id);
+
+        // The highest bid is below the reserve, so nobody wins
+        let seller_balance = env.balance_of(&seller);
+        env.advance_block_time(DURATION + 1);
+        auctions.end_auction(auction_id);
+        assert_eq!(nft.owner_of(Maybe::Some(1), Maybe::None), seller);
+        assert_eq!(env.balance_of(&seller), seller_balance);
+        assert_eq!(auctions.pending_refund(alice, None), U512::from(500));
+        assert_eq!(auctions.pending_refund(bob, None), U512::from(600));
+    }
+
+    #[test]
+    fn buy_now() {
+        let (env, mut auctions, mut nft) = setup();
+        let seller = env.get_account(1);
+        let alice = env.get_account(2);
+        let bob = env.get_account(3);
+
+        // Auctions without a buy-now price can only be bid on
+        env.set_caller(bob);
+        assert_eq!(
+            auctions
+                .with_tokens(U512::from(1_000))
+                .try_buy_now(auctions.latest_auction_id()),
+            Err(Error::BuyNowNotAvailable.into())
+        );
+
+        env.set_caller(env.get_account(0));
+        nft.mint(seller, CEP78_METADATA.to_string(), Maybe::None);
+        env.set_caller(seller);
+        nft.approve(*auctions.address(), Maybe::Some(1), Maybe::None);
+        auctions.create_auction(
+            *nft.address(),
+            NftId::Ordinal(1),
+            U512::from(100),
+            U512::zero(),
+            U512::zero(),
+            Some(U512::from(1_000)),
+            None,
+            None,
+            DURATION,
+        );
+        let auction_id = auctions.latest_auction_id();
+
+        env.set_caller(alice);
+        auctions.with_tokens(U512::from(300)).bid(auction_id);
+
+        env.set_caller(bob);
+        assert_eq!(
+            auctions
+                .with_tokens(U512::from(999))
+                .try_buy_now(auction_id),
+            Err(Error::IncorrectBuyNowAmount.into())
+        );
+        let seller_balance = env.balance_of(&seller);
+        auctions.with_tokens(U512::from(1_000)).buy_now(auction_id);
+
+        // The buyer gets the NFT, the seller the price, and the outbid bidder a refund
+        assert_eq!(nft.owner_of(Maybe::Some(1), Maybe::None), bob);
+        assert_eq!(env.balance_of(&seller), seller_balance + U512::from(1_000));
+        assert_eq!(auctions.pending_refund(alice, None), U512::from(300));
+
+        // The auction is over
+        env.advance_block_time(1);
+        env.set_caller(alice);
+        assert_eq!(
+            auctions.with_tokens(U512::from(2_000)).try_bid(auction_id),
+            Err(Error::AuctionHasEnded.into())
+        );
+    }
+
+    #[test]
+    fn buy_now_restrictions() {
+        let (env, mut auctions, mut nft) = setup();
+        let seller = env.get_account(1);
+        let alice = env.get_account(2);
+        let bob = env.get_account(3);
+        assert_eq!(
+            auctions
+                .with_tokens(U512::from(1_000))
+                .try_buy_now(U256::from(99)),
+            Err(Error::AuctionNotFound.into())
+        );
+
+        // Put up a token auction and a CSPR auction, both with a buy-now price
+        env.set_caller(env.get_account(0));
+        let token = Cep18HostRef::deploy(
+            &env,
+            Cep18InitArgs {
+                symbol: "BID".to_string(),
+                name: "Bidding Token".to_string(),
+                decimals: 9,
+                initial_supply: U256::from(2_000),
+                admin_list: vec![],
+                minter_list: vec![],
+                modality: None,
+            },
+        );
+        nft.mint(seller, CEP78_METADATA.to_string(), Maybe::None);
+        nft.mint(seller, CEP78_METADATA.to_string(), Maybe::None);
+        env.set_caller(seller);
+        for (nft_id, payment_token) in [(1, Some(*token.address())), (2, None)] {
+            nft.approve(*auctions.address(), Maybe::Some(nft_id), Maybe::None);
+            auctions.create_auction(
+                *nft.address(),
+                NftId::Ordinal(nft_id),
+                U512::from(100),
+                U512::zero(),
+                U512::zero(),
+                Some(U512::from(1_000)),
+                payment_token,
+                None,
+                DURATION,
             );
-            self.env()
-                .transfer_tokens(&auction.seller, &auction.highest_bid);
-        } else {
-            // No bids were placed, return the NFT to the seller
-            Cep78ContractRef::new(self.env(), auction.nft_contract).transfer(
-                Maybe::Some(auction.nft_id),
-                Maybe::None,
-                self.env().self_address(),
-                auction.seller,
+        }
+        let cspr_auction_id = auctions.latest_auction_id();
+        let token_auction_id = cspr_auction_id - U256::one();
+
+        // CSPR attached to a token auction is rejected instead of being lost
+        env.set_caller(bob);
+        assert_eq!(
+            auctions
+                .with_tokens(U512::from(1_000))
+                .try_buy_now(token_auction_id),
+            Err(Error::WrongCurrency.into())
+        );
+
+        // Once a bid reaches the buy-now price, nobody can buy the NFT for less
+        env.set_caller(alice);
+        auctions.with_tokens(U512::from(1_000)).bid(cspr_auction_id);
+        env.set_caller(bob);
+        assert_eq!(
+            auctions
+                .with_tokens(U512::from(1_000))
+                .try_buy_now(cspr_auction_id),
+            Err(Error::BuyNowNotAvailable.into())
+        );
+    }
+
+    #[test]
+    fn cancel_auction() {
+        let (env, mut auctions, mut nft) = setup();
+        let seller = env.get_account(1);
+        let alice = env.get_account(2);
+        let auction_id = auctions.latest_auction_id();
+
+        // Only the seller can cancel, and only existing auctions
+        env.set_caller(alice);
+        assert_eq!(
+            auctions.try_cancel_auction(auction_id),
+            Err(Error::NotSeller.into())
+        );
+        assert_eq!(
+            auctions.try_cancel_auction(U256::from(99)),
+            Err(Error::AuctionNotFound.into())
+        );
+
+        env.set_caller(seller);
+        auctions.cancel_auction(auction_id);
+        assert!(env.emitted_event(auctions.address(), &AuctionCancelled { auction_id, seller },));
+        assert_eq!(nft.owner_of(Maybe::Some(0), Maybe::None), seller);
+
+        // A cancelled auction takes no bids and can't be ended
+        env.set_caller(alice);
+        assert_eq!(
+            auctions.with_tokens(U512::from(100)).try_bid(auction_id),
+            Err(Error::AuctionNotActive.into())
+        );
+        env.advance_block_time(DURATION + 1);
+        assert_eq!(
+            auctions.try_end_auction(auction_id),
+            Err(Error::AuctionNotActive.into())
+        );
+
+        // Auctions with bids can't be cancelled
+        env.set_caller(seller);
+        nft.approve(*auctions.address(), Maybe::Some(0), Maybe::None);
+        auctions.create_auction(
+            *nft.address(),
+            NftId::Ordinal(0),
+            U512::from(100),
+            U512::zero(),
+            U512::zero(),
+            None,
+            None,
+            None,
+            DURATION,
+        );
+        let auction_id = auctions.latest_auction_id();
+        env.set_caller(alice);
+        auctions.with_tokens(U512::from(100)).bid(auction_id);
+        env.set_caller(seller);
+        assert_eq!(
+            auctions.try_cancel_auction(auction_id),
+            Err(Error::AuctionHasBids.into())
+        );
+    }
+
+    #[test]
+    fn auction_can_only_end_once() {
+        let (env, mut auctions, _) = setup();
+        let seller = env.get_account(1);
+        let alice = env.get_account(2);
+        let auction_id = auctions.latest_auction_id();
+
+        env.set_caller(alice);
+        auctions.with_tokens(U512::from(500)).bid(auction_id);
+        env.advance_block_time(DURATION + 1);
+
+        let seller_balance = env.balance_of(&seller);
+        auctions.end_auction(auction_id);
+        assert!(env.emitted_event(
+            auctions.address(),
+            &AuctionEnded {
+                auction_id,
+                winner: Some(alice),
+                amount: U512::from(500),
+            },
+        ));
+
+        // The second call is rejected and the seller is paid only once
+        assert_eq!(
+            auctions.try_end_auction(auction_id),
+            Err(Error::AuctionNotActive.into())
+        );
+        assert_eq!(env.balance_of(&seller), seller_balance + U512::from(500));
+        assert_eq!(
+            auctions.get_auction(auction_id).unwrap().status,
+            AuctionStatus::Settled
+        );
+    }
+
+    #[test]
+    fn auction_without_bids_can_only_end_once() {
+        let (env, mut auctions, nft) = setup();
+        let seller = env.get_account(1);
+        let auction_id = auctions.latest_auction_id();
+
+        env.advance_block_time(DURATION + 1);
+        auctions.end_auction(auction_id);
+        assert_eq!(nft.owner_of(Maybe::Some(0), Maybe::None), seller);
+        assert_eq!(
+            auctions.try_end_auction(auction_id),
+            Err(Error::AuctionNotActive.into())
+        );
+        assert_eq!(
+            auctions.try_cancel_auction(auction_id),
+            Err(Error::AuctionNotActive.into())
+        );
+    }
+
+    #[test]
+    fn token_auction() {
+        let (env, mut auctions, mut nft) = setup();
+        let seller = env.get_account(1);
+        let alice = env.get_account(2);
+        let bob = env.get_account(3);
+        env.set_caller(env.get_account(0));
+        let mut token = Cep18HostRef::deploy(
+            &env,
+            Cep18InitArgs {
+                symbol: "BID".to_string(),
+                name: "Bidding Token".to_string(),
+                decimals: 9,
+                initial_supply: U256::from(2_000),
+                admin_list: vec![],
+                minter_list: vec![],
+                modality: None,
+            },
+        );
+        token.transfer(&alice, &U256::from(1_000));
+        token.transfer(&bob, &U256::from(1_000));
+
+        nft.mint(seller, CEP78_METADATA.to_string(), Maybe::None);
+        env.set_caller(seller);
+        nft.approve(*auctions.address(), Maybe::Some(1), Maybe::None);
+        auctions.create_auction(
+            *nft.address(),
+            NftId::Ordinal(1),
+            U512::from(100),
+            U512::zero(),
+            U512::zero(),
+            None,
+            Some(*token.address()),
+            None,
+            DURATION,
+        );
+        let auction_id = auctions.latest_auction_id();
+
+        // CSPR bids are rejected
+        env.set_caller(alice);
+        assert_eq!(
+            auctions.with_tokens(U512::from(300)).try_bid(auction_id),
+            Err(Error::WrongCurrency.into())
+        );
+
+        // Bids are pulled from the bidders' allowances
+        token.approve(auctions.address(), &U256::from(300));
+        auctions.bid_with_token(auction_id, U512::from(300));
+        env.set_caller(bob);
+        token.approve(auctions.address(), &U256::from(400));
+        auctions.bid_with_token(auction_id, U512::from(400));
+        assert_eq!(token.balance_of(auctions.address()), U256::from(700));
+
+        // Refunds are kept per currency
+        assert_eq!(
+            auctions.pending_refund(alice, Some(*token.address())),
+            U512::from(300)
+        );
+        assert_eq!(auctions.pending_refund(alice, None), U512::zero());
+        env.set_caller(alice);
+        auctions.withdraw_refund(Some(*token.address()));
+        assert_eq!(token.balance_of(&alice), U256::from(1_000));
+
+        env.advance_block_time(DURATION + 1);
+        auctions.end_auction(auction_id);
+        assert_eq!(nft.owner_of(Maybe::Some(1), Maybe::None), bob);
+        assert_eq!(token.balance_of(&seller), U256::from(400));
+        assert_eq!(token.balance_of(auctions.address()), U256::zero());
+    }
+
+    #[test]
+    fn royalty_is_split_from_the_winning_bid() {
+        let (env, mut auctions, mut nft) = setup();
+        let seller = env.get_account(1);
+        let alice = env.get_account(2);
+        let creator = env.get_account(4);
+
+        env.set_caller(env.get_account(0));
+        nft.mint(seller, CEP78_METADATA.to_string(), Maybe::None);
+        env.set_caller(seller);
+        nft.approve(*auctions.address(), Maybe::Some(1), Maybe::None);
+        assert_eq!(
+            auctions.try_create_auction(
+                *nft.address(),
+                NftId::Ordinal(1),
+                U512::from(100),
+                U512::zero(),
+                U512::zero(),
+                None,
+                None,
+                Some((creator, 10_001)),
+                DURATION,
+            ),
+            Err(Error::InvalidRoyalty.into())
+        );
+        auctions.create_auction(
+            *nft.address(),
+            NftId::Ordinal(1),
+            U512::from(100),
+            U512::zero(),
+            U512::zero(),
+            None,
+            None,
+            Some((creator, 1_000)),
+            DURATION,
+        );
+        let auction_id = auctions.latest_auction_id();
+
+        env.set_caller(alice);
+        auctions.with_tokens(U512::from(500)).bid(auction_id);
+        env.advance_block_time(DURATION + 1);
+
+        // 10% of the winning bid goes to the creator
+        let seller_balance = env.balance_of(&seller);
+        let creator_balance = env.balance_of(&creator);
+        auctions.end_auction(auction_id);
+        assert!(env.emitted_event(
+            auctions.address(),
+            &RoyaltyPaid {
+                auction_id,
+                receiver: creator,
+                amount: U512::from(50),
+            },
+        ));
+        assert_eq!(env.balance_of(&seller), seller_balance + U512::from(450));
+        assert_eq!(env.balance_of(&creator), creator_balance + U512::from(50));
+    }
+
+    #[test]
+    fn hash_identified_nft() {
+        let (env, mut auctions, _) = setup();
+        let seller = env.get_account(1);
+        let alice = env.get_account(2);
+        let token_hash = "2c26b46b68ffc68ff99b453c1d30413413422d706483bfa0f98a5e886266e7ae";
+
+        env.set_caller(env.get_account(0));
+        let init_args = InitArgsBuilder::default()
+            .collection_name("Hashed Collection".to_string())
+            .collection_symbol("HSH".to_string())
+            .total_token_supply(10)
+            .ownership_mode(OwnershipMode::Transferable)
+            .nft_metadata_kind(NFTMetadataKind::CEP78)
+            .identifier_mode(NFTIdentifierMode::Hash)
+            .nft_kind(NFTKind::Digital)
+            .metadata_mutability(MetadataMutability::Immutable)
+            .receipt_name("hashed_receipt".to_string())
+            .build();
+        let mut nft = Cep78HostRef::deploy(&env, init_args);
+        nft.mint(
+            seller,
+            CEP78_METADATA.to_string(),
+            Maybe::Some(token_hash.to_string()),
+        );
+        auctions.allow_collection(*nft.address());
+
+        env.set_caller(seller);
+        nft.approve(
+            *auctions.address(),
+            Maybe::None,
+            Maybe::Some(token_hash.to_string()),
+        );
+        auctions.create_auction(
+            *nft.address(),
+            NftId::Hash(token_hash.to_string()),
+            U512::from(100),
+            U512::zero(),
+            U512::zero(),
+            None,
+            None,
+            None,
+            DURATION,
+        );
+        let auction_id = auctions.latest_auction_id();
+        assert_eq!(
+            nft.owner_of(Maybe::None, Maybe::Some(token_hash.to_string())),
+            *auctions.address()
+        );
+
+        env.set_caller(alice);
+        auctions.with_tokens(U512::from(100)).bid(auction_id);
+        env.advance_block_time(DURATION + 1);
+        auctions.end_auction(auction_id);
+        assert_eq!(
+            nft.owner_of(Maybe::None, Maybe::Some(token_hash.to_string())),
+            alice
+        );
+    }
+
+    #[test]
+    fn auction_lifecycle_emits_events() {
+        let (env, mut auctions, nft) = setup();
+        let seller = env.get_account(1);
+        let alice = env.get_account(2);
+        let bob = env.get_account(3);
+        let auction_id = auctions.latest_auction_id();
+        assert!(env.emitted_event(
+            auctions.address(),
+            &AuctionCreated {
+                auction_id,
+                seller,
+                nft_contract: *nft.address(),
+                nft_id: NftId::Ordinal(0),
+            },
+        ));
+        assert_eq!(
+            nft.owner_of(Maybe::Some(0), Maybe::None),
+            *auctions.address()
+        );
+
+        // Each bid is escrowed by the contract
+        env.set_caller(alice);
+        auctions.with_tokens(U512::from(150)).bid(auction_id);
+        env.set_caller(bob);
+        auctions.with_tokens(U512::from(250)).bid(auction_id);
+        assert!(env.emitted_event(
+            auctions.address(),
+            &BidPlaced {
+                auction_id,
+                bidder: alice,
+                amount: U512::from(150),
+            },
+        ));
+        assert!(env.emitted_event(
+            auctions.address(),
+            &BidPlaced {
+                auction_id,
+                bidder: bob,
+                amount: U512::from(250),
+            },
+        ));
+        assert_eq!(env.balance_of(auctions.address()), U512::from(400));
+
+        // The winner gets the NFT, the seller the winning bid, and the outbid bidder keeps a refund
+        let seller_balance = env.balance_of(&seller);
+        env.advance_block_time(DURATION + 1);
+        auctions.end_auction(auction_id);
+        assert!(env.emitted_event(
+            auctions.address(),
+            &AuctionEnded {
+                auction_id,
+                winner: Some(bob),
+                amount: U512::from(250),
+            },
+        ));
+        assert_eq!(nft.owner_of(Maybe::Some(0), Maybe::None), bob);
+        assert_eq!(env.balance_of(&seller), seller_balance + U512::from(250));
+        assert_eq!(env.balance_of(auctions.address()), U512::from(150));
+        assert_eq!(auctions.pending_refund(alice, None), U512::from(150));
+    }
+
+    #[test]
+    fn seller_and_bidder_indexes() {
+        let (env, mut auctions, mut nft) = setup();
+        let seller = env.get_account(1);
+        let alice = env.get_account(2);
+        let bob = env.get_account(3);
+        let first = auctions.latest_auction_id();
+
+        env.set_caller(env.get_account(0));
+        nft.mint(seller, CEP78_METADATA.to_string(), Maybe::None);
+        env.set_caller(seller);
+        nft.approve(*auctions.address(), Maybe::Some(1), Maybe::None);
+        auctions.create_auction(
+            *nft.address(),
+            NftId::Ordinal(1),
+            U512::from(100),
+            U512::zero(),
+            U512::zero(),
+            None,
+            None,
+            None,
+            DURATION,
+        );
+        let second = auctions.latest_auction_id();
+        assert_eq!(auctions.auctions_of_seller(seller), vec![first, second]);
+        assert!(auctions.auctions_of_seller(alice).is_empty());
+
+        // Repeated bids on the same auction are indexed once
+        env.set_caller(alice);
+        auctions.with_tokens(U512::from(100)).bid(first);
+        env.set_caller(bob);
+        auctions.with_tokens(U512::from(200)).bid(first);
+        env.set_caller(alice);
+        auctions.with_tokens(U512::from(300)).bid(first);
+        auctions.with_tokens(U512::from(100)).bid(second);
+        assert_eq!(auctions.auctions_bid_by(alice), vec![first, second]);
+        assert_eq!(auctions.auctions_bid_by(bob), vec![first]);
+    }
+
+    #[test]
+    fn active_auctions_are_paginated() {
+        let (env, mut auctions, mut nft) = setup();
+        let seller = env.get_account(1);
+
+        // Put three more tokens up, the last one for twice as long
+        for token_id in 1..4u64 {
+            env.set_caller(env.get_account(0));
+            nft.mint(seller, CEP78_METADATA.to_string(), Maybe::None);
+            env.set_caller(seller);
+            nft.approve(*auctions.address(), Maybe::Some(token_id), Maybe::None);
+            let duration = if token_id == 3 {
+                2 * DURATION
+            } else {
+                DURATION
+            };
+            auctions.create_auction(
+                *nft.address(),
+                NftId::Ordinal(token_id),
+                U512::from(100),
+                U512::zero(),
+                U512::zero(),
+                None,
+                None,
+                None,
+                duration,
             );
         }
+        assert_eq!(auctions.latest_auction_id(), U256::from(4));
+
+        let active_ids = |auctions: &AuctionsHostRef, offset: u32, limit: u32| {
+            auctions
+                .get_active_auctions(offset, limit)
+                .into_iter()
+                .map(|(auction_id, _)| auction_id.as_u32())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(active_ids(&auctions, 0, 10), vec![1, 2, 3, 4]);
+        assert_eq!(active_ids(&auctions, 1, 2), vec![2, 3]);
+        assert_eq!(active_ids(&auctions, 3, 2), vec![4]);
+        assert_eq!(active_ids(&auctions, 4, 2), Vec::<u32>::new());
+
+        // The offset counts active auctions only, so cancelled ones don't shift the pages
+        auctions.cancel_auction(U256::from(2));
+        assert_eq!(active_ids(&auctions, 0, 10), vec![1, 3, 4]);
+        assert_eq!(active_ids(&auctions, 1, 1), vec![3]);
+
+        // Auctions are listed as long as they accept bids, up to and including their end
+        env.advance_block_time(DURATION);
+        assert_eq!(active_ids(&auctions, 0, 10), vec![1, 3, 4]);
+        env.set_caller(env.get_account(2));
+        auctions.with_tokens(U512::from(100)).bid(U256::one());
+        env.advance_block_time(1);
+        assert_eq!(active_ids(&auctions, 0, 10), vec![4]);
     }
 
-    /**********
-     * ADMIN
-     **********/
+    #[test]
+    fn only_allowed_collections_can_be_auctioned() {
+        let (env, mut auctions, mut nft) = setup();
+        let seller = env.get_account(1);
+        assert!(auctions.is_collection_allowed(*nft.address()));
 
-    /// Pauses the contract, preventing further interactions.
-    pub fn pause(&mut self) {
-        self.ownable.assert_owner(&self.env().caller());
-        self.pausable.pause();
+        // Only the owner manages the allowlist
+        env.set_caller(seller);
+        assert!(auctions.try_revoke_collection(*nft.address()).is_err());
+
+        env.set_caller(env.get_account(0));
+        auctions.revoke_collection(*nft.address());
+        assert!(!auctions.is_collection_allowed(*nft.address()));
+        nft.mint(seller, CEP78_METADATA.to_string(), Maybe::None);
+
+        env.set_caller(seller);
+        nft.approve(*auctions.address(), Maybe::Some(1), Maybe::None);
+        assert_eq!(
+            auctions.try_create_auction(
+                *nft.address(),
+                NftId::Ordinal(1),
+                U512::from(100),
+                U512::zero(),
+                U512::zero(),
+                None,
+                None,
+                None,
+                DURATION,
+            ),
+            Err(Error::CollectionNotAllowed.into())
+        );
+
+        // Auctions created before the revocation still run
+        let auction_id = auctions.latest_auction_id();
+        env.set_caller(env.get_account(2));
+        auctions.with_tokens(U512::from(100)).bid(auction_id);
     }
 
-    /// Unpauses the contract, resuming normal operation.
-    pub fn unpause(&mut self) {
-        self.ownable.assert_owner(&self.env().caller());
-        self.pausable.unpause();
+    #[test]
+    fn batch_auction_creation() {
+        let (env, mut auctions, mut nft) = setup();
+        let seller = env.get_account(1);
+        let first = auctions.latest_auction_id();
+
+        env.set_caller(env.get_account(0));
+        for _ in 0..3 {
+            nft.mint(seller, CEP78_METADATA.to_string(), Maybe::None);
+        }
+        env.set_caller(seller);
+        nft.set_approval_for_all(true, *auctions.address());
+        let request = |token_id: u64, duration: u64| AuctionRequest {
+            nft_contract: *nft.address(),
+            nft_id: NftId::Ordinal(token_id),
+            starting_price: U512::from(100),
+            reserve_price: U512::zero(),
+            min_increment: U512::zero(),
+            buy_now_price: None,
+            payment_token: None,
+            royalty: None,
+            duration,
+        };
+
+        // One invalid request fails the whole batch
+        assert_eq!(
+            auctions.try_create_auctions(vec![request(1, DURATION), request(2, 1)]),
+            Err(Error::InvalidAuctionDuration.into())
+        );
+        assert_eq!(nft.owner_of(Maybe::Some(1), Maybe::None), seller);
+
+        let auction_ids = auctions.create_auctions(vec![
+            request(1, DURATION),
+            request(2, DURATION),
+            request(3, DURATION),
+        ]);
+        assert_eq!(auction_ids, vec![first + 1, first + 2, first + 3]);
+        for token_id in 1..=3 {
+            assert_eq!(
+                nft.owner_of(Maybe::Some(token_id), Maybe::None),
+                *auctions.address()
+            );
+        }
+        assert_eq!(auctions.auctions_of_seller(seller).len(), 4);
+    }
+
+    #[test]
+    fn rescue_stranded_assets() {
+        let (env, mut auctions, mut nft) = setup();
+        let owner = env.get_account(0);
+        let seller = env.get_account(1);
+        let alice = env.get_account(2);
+        let auction_id = auctions.latest_auction_id();
+
+        // The seller sends a second token straight to the contract by mistake
+        env.set_caller(owner);
+        nft.mint(seller, CEP78_METADATA.to_string(), Maybe::None);
+        env.set_caller(seller);
+        nft.transfer(Maybe::Some(1), Maybe::None, seller, *auctions.address());
+
+        // Only the owner can rescue assets
+        assert!(auctions
+            .try_rescue(*nft.address(), NftId::Ordinal(1), seller)
+            .is_err());
+
+        // Auctioned NFTs and bids can't be touched
+        env.set_caller(alice);
+        auctions.with_tokens(U512::from(500)).bid(auction_id);
+        env.set_caller(owner);
+        assert_eq!(
+            auctions.try_rescue(*nft.address(), NftId::Ordinal(0), owner),
+            Err(Error::NftInCustody.into())
+        );
+        assert_eq!(
+            auctions.try_rescue_cspr(U512::one(), owner),
+            Err(Error::AmountInCustody.into())
+        );
+
+        auctions.rescue(*nft.address(), NftId::Ordinal(1), seller);
+        assert_eq!(nft.owner_of(Maybe::Some(1), Maybe::None), seller);
+
+        // Once the auction settles, its NFT is no longer in custody, but nothing stranded remains
+        env.advance_block_time(DURATION + 1);
+        auctions.end_auction(auction_id);
+        assert_eq!(nft.owner_of(Maybe::Some(0), Maybe::None), alice);
+        assert_eq!(env.balance_of(auctions.address()), U512::zero());
     }
 }
@@ -0,0 +1,54 @@
+//! Helpers for holding CEP-78 NFTs in a contract's custody while they are on sale.
+use odra::{args::Maybe, prelude::*, Address, ContractEnv, ContractRef};
+use odra_modules::cep78::token::Cep78ContractRef;
+
+#[odra::odra_type]
+/// Identifies a CEP-78 token, matching the collection's `NFTIdentifierMode`.
+pub enum NftId {
+    /// Token index, for `NFTIdentifierMode::Ordinal` collections.
+    Ordinal(u64),
+    /// Token hash, for `NFTIdentifierMode::Hash` collections.
+    Hash(String),
+}
+
+impl NftId {
+    /// Returns the `token_id` argument CEP-78 expects for this identifier.
+    fn token_id(&self) -> Maybe<u64> {
+        match self {
+            NftId::Ordinal(id) => Maybe::Some(*id),
+            NftId::Hash(_) => Maybe::None,
+        }
+    }
+
+    /// Returns the `token_hash` argument CEP-78 expects for this identifier.
+    fn token_hash(&self) -> Maybe<String> {
+        match self {
+            NftId::Ordinal(_) => Maybe::None,
+            NftId::Hash(hash) => Maybe::Some(hash.clone()),
+        }
+    }
+}
+
+/// Moves the NFT from its owner to the calling contract.
+///
+/// The owner has to approve the contract for the token beforehand.
+pub fn take_custody(env: Rc<ContractEnv>, nft_contract: Address, nft_id: &NftId, owner: Address) {
+    let contract = env.self_address();
+    Cep78ContractRef::new(env, nft_contract).transfer(
+        nft_id.token_id(),
+        nft_id.token_hash(),
+        owner,
+        contract,
+    );
+}
+
+/// Moves the NFT held by the calling contract to `to`.
+pub fn release_custody(env: Rc<ContractEnv>, nft_contract: Address, nft_id: &NftId, to: Address) {
+    let contract = env.self_address();
+    Cep78ContractRef::new(env, nft_contract).transfer(
+        nft_id.token_id(),
+        nft_id.token_hash(),
+        contract,
+        to,
+    );
+}
@@ -0,0 +1,289 @@
+use crate::auctions::AuctionStatus;
+use crate::custody::{self, NftId};
+use odra::{
+    casper_types::{U256, U512},
+    prelude::*,
+    Address, Mapping, Var,
+};
+
+#[odra::module(events = [DutchAuctionSold], errors = Error)]
+/// This contract sells CEP-78 NFTs in Dutch auctions, where the price drops over time until someone buys.
+pub struct DutchAuctions {
+    /// Storage for Dutch auctions, indexed by a unique auction ID.
+    auctions: Mapping<U256, DutchAuction>,
+    /// Counter to track the total number of auctions created.
+    auction_counter: Var<U256>,
+}
+
+#[odra::odra_error]
+/// Errors that may occur during the contract execution.
+pub enum Error {
+    /// The floor price is higher than the start price.
+    InvalidPrices = 1,
+    /// The price has to decay over a non-zero duration.
+    InvalidDuration = 2,
+    /// The attached amount is lower than the current price.
+    PriceNotMet = 3,
+    /// The auction has already been sold or cancelled.
+    AuctionNotActive = 4,
+    /// Only the seller can perform this action.
+    NotSeller = 5,
+    /// No auction exists with the given ID.
+    AuctionNotFound = 6,
+}
+
+#[odra::odra_type]
+/// Represents a Dutch auction for an NFT.
+pub struct DutchAuction {
+    /// Address of the seller who initiated the auction.
+    seller: Address,
+    /// Address of the CEP-78 NFT contract.
+    nft_contract: Address,
+    /// ID of the NFT being auctioned.
+    nft_id: u64,
+    /// Price in CSPR when the auction starts.
+    start_price: U512,
+    /// Lowest price in CSPR the auction decays to.
+    floor_price: U512,
+    /// Timestamp of when the auction was created.
+    starts_at: u64,
+    /// Time it takes for the price to decay from the start price to the floor.
+    duration: u64,
+    /// Current status of the auction.
+    status: AuctionStatus,
+}
+
+#[odra::event]
+/// Emitted when an NFT is bought from a Dutch auction.
+pub struct DutchAuctionSold {
+    /// ID of the sold auction.
+    pub auction_id: U256,
+    /// Buyer of the NFT.
+    pub buyer: Address,
+    /// Price paid to the seller.
+    pub price: U512,
+}
+
+#[odra::module]
+impl DutchAuctions {
+    /**********
+     * TRANSACTIONS
+     **********/
+
+    /// Creates a Dutch auction whose price decays linearly from `start_price` to `floor_price` over `duration`.
+    pub fn create_auction(
+        &mut self,
+        nft_contract: Address,
+        nft_id: u64,
+        start_price: U512,
+        floor_price: U512,
+        duration: u64,
+    ) -> U256 {
+        if floor_price > start_price {
+            self.env().revert(Error::InvalidPrices);
+        }
+        if duration == 0 {
+            self.env().revert(Error::InvalidDuration);
+        }
+
+        let seller = self.env().caller();
+        custody::take_custody(self.env(), nft_contract, &NftId::Ordinal(nft_id), seller);
+
+        let auction_id = self.auction_counter.get_or_default() + U256::one(); // IDs start from 1
+        let auction = DutchAuction {
+            seller,
+            nft_contract,
+            nft_id,
+            start_price,
+            floor_price,
+            starts_at: self.env().get_block_time(),
+            duration,
+            status: AuctionStatus::Active,
+        };
+        self.auctions.set(&auction_id, auction);
+        self.auction_counter.set(auction_id);
+        auction_id
+    }
+
+    /// Buys the NFT at the current price; any amount above it is returned to the buyer.
+    #[odra(payable)]
+    pub fn buy(&mut self, auction_id: U256) {
+        let mut auction = self.get_active_auction(auction_id);
+        let buyer = self.env().caller();
+        let amount = self.env().attached_value();
+        let price = self.price_at(&auction, self.env().get_block_time());
+
+        if amount < price {
+            self.env().revert(Error::PriceNotMet);
+        }
+
+        auction.status = AuctionStatus::Settled;
+        self.auctions.set(&auction_id, auction.clone());
+
+        custody::release_custody(
+            self.env(),
+            auction.nft_contract,
+            &NftId::Ordinal(auction.nft_id),
+            buyer,
+        );
+        self.env().transfer_tokens(&auction.seller, &price);
+        if amount > price {
+            self.env().transfer_tokens(&buyer, &(amount - price));
+        }
+        self.env().emit_event(DutchAuctionSold {
+            auction_id,
+            buyer,
+            price,
+        });
+    }
+
+    /// Cancels an unsold auction and returns the NFT to the seller.
+    pub fn cancel_auction(&mut self, auction_id: U256) {
+        let mut auction = self.get_active_auction(auction_id);
+        if self.env().caller() != auction.seller {
+            self.env().revert(Error::NotSeller);
+        }
+
+        auction.status = AuctionStatus::Cancelled;
+        self.auctions.set(&auction_id, auction.clone());
+        custody::release_custody(
+            self.env(),
+            auction.nft_contract,
+            &NftId::Ordinal(auction.nft_id),
+            auction.seller,
+        );
+    }
+
+    /**********
+     * QUERIES
+     **********/
+
+    /// Returns the auction with the given ID, if it exists.
+    pub fn get_auction(&self, auction_id: U256) -> Option<DutchAuction> {
+        self.auctions.get(&auction_id)
+    }
+
+    /// Returns the price at which the NFT can be bought right now.
+    pub fn current_price(&self, auction_id: U256) -> U512 {
+        let auction = self.get_active_auction(auction_id);
+        self.price_at(&auction, self.env().get_block_time())
+    }
+
+    /**********
+     * INTERNAL
+     **********/
+
+    /// Reads an auction, reverting if it doesn't exist or is no longer active.
+    fn get_active_auction(&self, auction_id: U256) -> DutchAuction {
+        let auction = self
+            .auctions
+            .get(&auction_id)
+            .unwrap_or_revert_with(&self.env(), Error::AuctionNotFound);
+        if auction.status != AuctionStatus::Active {
+            self.env().revert(Error::AuctionNotActive);
+        }
+        auction
+    }
+
+    /// Linearly interpolates between the start and floor price.
+    fn price_at(&self, auction: &DutchAuction, now: u64) -> U512 {
+        let elapsed = now.saturating_sub(auction.starts_at).min(auction.duration);
+        let decay = (auction.start_price - auction.floor_price) * U512::from(elapsed)
+            / U512::from(auction.duration);
+        auction.start_price - decay
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use odra::args::Maybe;
+    use odra::host::{Deployer, HostRef, NoArgs};
+    use odra_modules::cep78::modalities::{
+        MetadataMutability, NFTIdentifierMode, NFTKind, NFTMetadataKind, OwnershipMode,
+    };
+    use odra_modules::cep78::token::Cep78HostRef;
+    use odra_modules::cep78::utils::InitArgsBuilder;
+
+    const CEP78_METADATA: &str = r#"{
+        "name": "John Doe",
+        "token_uri": "https://www.barfoo.com",
+        "checksum": "940bffb3f2bba35f84313aa26da09ece3ad47045c6a1292c2bbd2df4ab1a55fb"
+    }"#;
+    const DURATION: u64 = 10 * 60 * 1000; // ten minutes
+
+    #[test]
+    fn price_decays_until_bought() {
+        let env = odra_test::env();
+        let seller = env.get_account(1);
+        let buyer = env.get_account(2);
+        let mut auctions = DutchAuctionsHostRef::deploy(&env, NoArgs);
+        let init_args = InitArgsBuilder::default()
+            .collection_name("Dutch Collection".to_string())
+            .collection_symbol("DUT".to_string())
+            .total_token_supply(10)
+            .ownership_mode(OwnershipMode::Transferable)
+            .nft_metadata_kind(NFTMetadataKind::CEP78)
+            .identifier_mode(NFTIdentifierMode::Ordinal)
+            .nft_kind(NFTKind::Digital)
+            .metadata_mutability(MetadataMutability::Immutable)
+            .receipt_name("dutch_receipt".to_string())
+            .build();
+        let mut nft = Cep78HostRef::deploy(&env, init_args);
+        nft.mint(seller, CEP78_METADATA.to_string(), Maybe::None);
+
+        env.set_caller(seller);
+        nft.approve(*auctions.address(), Maybe::Some(0), Maybe::None);
+        assert_eq!(
+            auctions.try_create_auction(
+                *nft.address(),
+                0,
+                U512::from(100),
+                U512::from(1_000),
+                DURATION
+            ),
+            Err(Error::InvalidPrices.into())
+        );
+        let auction_id = auctions.create_auction(
+            *nft.address(),
+            0,
+            U512::from(1_000),
+            U512::from(200),
+            DURATION,
+        );
+        assert_eq!(auctions.current_price(auction_id), U512::from(1_000));
+
+        // Halfway through, the price is halfway between the start and the floor
+        env.advance_block_time(DURATION / 2);
+        assert_eq!(auctions.current_price(auction_id), U512::from(600));
+
+        env.set_caller(buyer);
+        assert_eq!(
+            auctions.with_tokens(U512::from(599)).try_buy(auction_id),
+            Err(Error::PriceNotMet.into())
+        );
+
+        // Overpaying returns the difference to the buyer
+        let seller_balance = env.balance_of(&seller);
+        let buyer_balance = env.balance_of(&buyer);
+        auctions.with_tokens(U512::from(700)).buy(auction_id);
+        assert!(env.emitted_event(
+            auctions.address(),
+            &DutchAuctionSold {
+                auction_id,
+                buyer,
+                price: U512::from(600),
+            },
+        ));
+        assert_eq!(nft.owner_of(Maybe::Some(0), Maybe::None), buyer);
+        assert_eq!(env.balance_of(&seller), seller_balance + U512::from(600));
+        assert_eq!(env.balance_of(&buyer), buyer_balance - U512::from(600));
+
+        // Only the first buyer wins
+        env.advance_block_time(DURATION);
+        assert_eq!(
+            auctions.with_tokens(U512::from(200)).try_buy(auction_id),
+            Err(Error::AuctionNotActive.into())
+        );
+    }
+}
@@ -3,3 +3,8 @@
 extern crate alloc;
 
 pub mod auctions;
+pub mod custody;
+pub mod dutch_auctions;
+pub mod payouts;
+pub mod reverse_auctions;
+pub mod sealed_bid_auctions;
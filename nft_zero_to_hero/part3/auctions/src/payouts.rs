@@ -0,0 +1,38 @@
+//! Helpers for splitting the proceeds of a sale between the seller, the creator and the platform.
+use odra::{casper_types::U512, Address};
+
+/// Basis points that make up 100% of a payment.
+pub const MAX_BPS: u16 = 10_000;
+
+/// Shares of a sale's proceeds.
+pub struct Split {
+    /// Part paid to the royalty receiver, if any.
+    pub royalty: U512,
+    /// Part kept by the platform.
+    pub fee: U512,
+    /// What's left for the seller.
+    pub seller: U512,
+}
+
+/// Returns whether the royalty and the platform fee together fit into the proceeds.
+pub fn is_valid(royalty: Option<(Address, u16)>, fee_bps: u16) -> bool {
+    let royalty_bps = royalty.map_or(0, |(_, bps)| bps);
+    royalty_bps as u32 + fee_bps as u32 <= MAX_BPS as u32
+}
+
+/// Splits `amount` into the royalty, the platform fee and the seller's part.
+///
+/// Both shares are rounded down, so any remainder goes to the seller.
+pub fn split(amount: U512, royalty: Option<(Address, u16)>, fee_bps: u16) -> Split {
+    let royalty = royalty.map_or(U512::zero(), |(_, bps)| share(amount, bps));
+    let fee = share(amount, fee_bps);
+    Split {
+        royalty,
+        fee,
+        seller: amount - royalty - fee,
+    }
+}
+
+fn share(amount: U512, bps: u16) -> U512 {
+    amount * U512::from(bps) / U512::from(MAX_BPS)
+}
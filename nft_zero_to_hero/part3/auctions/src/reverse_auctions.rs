@@ -0,0 +1,390 @@
+use crate::auctions::AuctionStatus;
+use odra::{
+    casper_types::{U256, U512},
+    prelude::*,
+    Address, Mapping, Var,
+};
+
+#[odra::module(
+    events = [JobPosted, OfferPlaced, ReverseAuctionEnded, JobCancelled],
+    errors = Error
+)]
+/// This contract runs reverse (procurement) auctions, where sellers bid the price of a job down.
+///
+/// The buyer locks the maximum budget when posting the job. The lowest bid at the close wins,
+/// and is paid once the buyer confirms the job was delivered.
+pub struct ReverseAuctions {
+    /// Storage for jobs, indexed by a unique job ID.
+    jobs: Mapping<U256, Job>,
+    /// Counter to track the total number of jobs posted.
+    job_counter: Var<U256>,
+    /// Unspent budgets waiting to be withdrawn by buyers.
+    pending_returns: Mapping<Address, U512>,
+}
+
+#[odra::odra_error]
+/// Errors that may occur during the contract execution.
+pub enum Error {
+    /// Bidding must last longer than zero.
+    InvalidDuration = 1,
+    /// A job has to come with a non-zero budget.
+    EmptyBudget = 2,
+    /// No job exists with the given ID.
+    JobNotFound = 3,
+    /// The job has already been settled or cancelled.
+    JobNotActive = 4,
+    /// The bidding phase is over.
+    BiddingClosed = 5,
+    /// The bidding phase is still running.
+    BiddingStillOpen = 6,
+    /// The buyer can't bid on their own job.
+    BuyerCannotBid = 7,
+    /// The bid is zero or above the job's budget.
+    InvalidBid = 8,
+    /// The bid isn't lower than the lowest bid so far.
+    BidNotLower = 9,
+    /// Only the buyer can perform this action.
+    NotBuyer = 10,
+    /// Nobody bid on the job.
+    NoBids = 11,
+    /// The job already has bids and can no longer be cancelled.
+    JobHasBids = 12,
+    /// The caller has no unspent budget to withdraw.
+    NothingToWithdraw = 13,
+}
+
+#[odra::odra_type]
+/// Represents a job put out to tender.
+pub struct Job {
+    /// Address of the buyer who posted the job.
+    buyer: Address,
+    /// What the buyer needs done.
+    description: String,
+    /// Most the buyer is willing to pay, locked in the contract.
+    max_budget: U512,
+    /// Timestamp after which no more bids are accepted.
+    ends_at: u64,
+    /// Seller with the lowest bid so far, if any.
+    lowest_bidder: Option<Address>,
+    /// Lowest bid so far.
+    lowest_bid: U512,
+    /// Current status of the job.
+    status: AuctionStatus,
+}
+
+#[odra::event]
+/// Emitted when a buyer posts a job.
+pub struct JobPosted {
+    /// ID of the new job.
+    pub job_id: U256,
+    /// Buyer who posted the job.
+    pub buyer: Address,
+    /// Budget locked for the job.
+    pub max_budget: U512,
+}
+
+#[odra::event]
+/// Emitted when a bid becomes the lowest bid for a job.
+pub struct OfferPlaced {
+    /// ID of the job.
+    pub job_id: U256,
+    /// Seller who placed the bid.
+    pub seller: Address,
+    /// Price the seller asks.
+    pub price: U512,
+}
+
+#[odra::event]
+/// Emitted when the buyer confirms the delivery and the winning seller is paid.
+pub struct ReverseAuctionEnded {
+    /// ID of the settled job.
+    pub job_id: U256,
+    /// Seller who won the job.
+    pub seller: Address,
+    /// Price paid to the seller.
+    pub price: U512,
+}
+
+#[odra::event]
+/// Emitted when a buyer cancels a job nobody bid on.
+pub struct JobCancelled {
+    /// ID of the cancelled job.
+    pub job_id: U256,
+}
+
+#[odra::module]
+impl ReverseAuctions {
+    /**********
+     * TRANSACTIONS
+     **********/
+
+    /// Posts a job, locking the attached CSPR as its maximum budget, and opens bidding for `duration`.
+    #[odra(payable)]
+    pub fn post_job(&mut self, description: String, duration: u64) -> U256 {
+        if duration == 0 {
+            self.env().revert(Error::InvalidDuration);
+        }
+        let max_budget = self.env().attached_value();
+        if max_budget.is_zero() {
+            self.env().revert(Error::EmptyBudget);
+        }
+
+        let buyer = self.env().caller();
+        let job_id = self.job_counter.get_or_default() + U256::one(); // IDs start from 1
+        let job = Job {
+            buyer,
+            description,
+            max_budget,
+            ends_at: self.env().get_block_time() + duration,
+            lowest_bidder: None,
+            lowest_bid: U512::zero(),
+            status: AuctionStatus::Active,
+        };
+        self.jobs.set(&job_id, job);
+        self.job_counter.set(job_id);
+        self.env().emit_event(JobPosted {
+            job_id,
+            buyer,
+            max_budget,
+        });
+        job_id
+    }
+
+    /// Offers to do the job for `price`, which has to undercut the lowest bid so far.
+    ///
+    /// Sellers lock nothing: the buyer only pays the winner once the job is delivered.
+    pub fn bid(&mut self, job_id: U256, price: U512) {
+        let mut job = self.get_active_job(job_id);
+        let seller = self.env().caller();
+
+        if self.env().get_block_time() > job.ends_at {
+            self.env().revert(Error::BiddingClosed);
+        }
+        if seller == job.buyer {
+            self.env().revert(Error::BuyerCannotBid);
+        }
+        if price.is_zero() || price > job.max_budget {
+            self.env().revert(Error::InvalidBid);
+        }
+        // The inverse of a regular auction: a bid has to be lower, not higher
+        if job.lowest_bidder.is_some() && price >= job.lowest_bid {
+            self.env().revert(Error::BidNotLower);
+        }
+
+        job.lowest_bidder = Some(seller);
+        job.lowest_bid = price;
+        self.jobs.set(&job_id, job);
+        self.env().emit_event(OfferPlaced {
+            job_id,
+            seller,
+            price,
+        });
+    }
+
+    /// Pays the winning seller once the buyer confirms the job was delivered.
+    ///
+    /// The rest of the budget becomes withdrawable by the buyer.
+    pub fn confirm_delivery(&mut self, job_id: U256) {
+        let mut job = self.get_active_job(job_id);
+        if self.env().caller() != job.buyer {
+            self.env().revert(Error::NotBuyer);
+        }
+        if self.env().get_block_time() <= job.ends_at {
+            self.env().revert(Error::BiddingStillOpen);
+        }
+        let seller = job
+            .lowest_bidder
+            .unwrap_or_revert_with(&self.env(), Error::NoBids);
+
+        job.status = AuctionStatus::Settled;
+        self.jobs.set(&job_id, job.clone());
+        self.credit_refund(job.buyer, job.max_budget - job.lowest_bid);
+        self.env().transfer_tokens(&seller, &job.lowest_bid);
+        self.env().emit_event(ReverseAuctionEnded {
+            job_id,
+            seller,
+            price: job.lowest_bid,
+        });
+    }
+
+    /// Cancels a job nobody bid on, making its whole budget withdrawable by the buyer.
+    pub fn cancel_job(&mut self, job_id: U256) {
+        let mut job = self.get_active_job(job_id);
+        if self.env().caller() != job.buyer {
+            self.env().revert(Error::NotBuyer);
+        }
+        if job.lowest_bidder.is_some() {
+            self.env().revert(Error::JobHasBids);
+        }
+
+        job.status = AuctionStatus::Cancelled;
+        self.jobs.set(&job_id, job.clone());
+        self.credit_refund(job.buyer, job.max_budget);
+        self.env().emit_event(JobCancelled { job_id });
+    }
+
+    /// Transfers all unspent budgets owed to the caller.
+    pub fn withdraw_refund(&mut self) {
+        let caller = self.env().caller();
+        let amount = self.pending_returns.get_or_default(&caller);
+        if amount.is_zero() {
+            self.env().revert(Error::NothingToWithdraw);
+        }
+        self.pending_returns.set(&caller, U512::zero()); // Clear before transferring
+        self.env().transfer_tokens(&caller, &amount);
+    }
+
+    /**********
+     * QUERIES
+     **********/
+
+    /// Returns the job with the given ID, if it exists.
+    pub fn get_job(&self, job_id: U256) -> Option<Job> {
+        self.jobs.get(&job_id)
+    }
+
+    /// Returns the unspent budget the given buyer can withdraw.
+    pub fn pending_refund(&self, buyer: Address) -> U512 {
+        self.pending_returns.get_or_default(&buyer)
+    }
+
+    /**********
+     * INTERNAL
+     **********/
+
+    /// Reads a job, reverting if it doesn't exist or is no longer active.
+    fn get_active_job(&self, job_id: U256) -> Job {
+        let job = self
+            .jobs
+            .get(&job_id)
+            .unwrap_or_revert_with(&self.env(), Error::JobNotFound);
+        if job.status != AuctionStatus::Active {
+            self.env().revert(Error::JobNotActive);
+        }
+        job
+    }
+
+    /// Adds `amount` to the buyer's withdrawable refunds.
+    fn credit_refund(&mut self, buyer: Address, amount: U512) {
+        let pending = self.pending_returns.get_or_default(&buyer);
+        self.pending_returns.set(&buyer, pending + amount);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use odra::host::{Deployer, HostRef, NoArgs};
+
+    const DURATION: u64 = 24 * 60 * 60 * 1000; // one day
+
+    #[test]
+    fn lowest_bid_wins() {
+        let env = odra_test::env();
+        let buyer = env.get_account(1);
+        let (alice, bob) = (env.get_account(2), env.get_account(3));
+        let mut auctions = ReverseAuctionsHostRef::deploy(&env, NoArgs);
+
+        env.set_caller(buyer);
+        let job_id = auctions
+            .with_tokens(U512::from(1_000))
+            .post_job("Translate the user manual".to_string(), DURATION);
+        assert_eq!(
+            auctions.try_bid(job_id, U512::from(900)),
+            Err(Error::BuyerCannotBid.into())
+        );
+
+        // Sellers undercut each other, within the budget
+        env.set_caller(alice);
+        assert_eq!(
+            auctions.try_bid(job_id, U512::from(1_001)),
+            Err(Error::InvalidBid.into())
+        );
+        auctions.bid(job_id, U512::from(800));
+        env.set_caller(bob);
+        assert_eq!(
+            auctions.try_bid(job_id, U512::from(800)),
+            Err(Error::BidNotLower.into())
+        );
+        auctions.bid(job_id, U512::from(650));
+        env.set_caller(alice);
+        auctions.bid(job_id, U512::from(600));
+
+        env.set_caller(buyer);
+        assert_eq!(
+            auctions.try_confirm_delivery(job_id),
+            Err(Error::BiddingStillOpen.into())
+        );
+        env.advance_block_time(DURATION + 1);
+        env.set_caller(bob);
+        assert_eq!(
+            auctions.try_bid(job_id, U512::from(500)),
+            Err(Error::BiddingClosed.into())
+        );
+        assert_eq!(
+            auctions.try_confirm_delivery(job_id),
+            Err(Error::NotBuyer.into())
+        );
+
+        // Once the work is delivered, the winner is paid and the buyer gets the rest back
+        let alice_balance = env.balance_of(&alice);
+        env.set_caller(buyer);
+        auctions.confirm_delivery(job_id);
+        assert_eq!(env.balance_of(&alice), alice_balance + U512::from(600));
+        assert!(env.emitted_event(
+            auctions.address(),
+            &ReverseAuctionEnded {
+                job_id,
+                seller: alice,
+                price: U512::from(600),
+            },
+        ));
+        assert_eq!(auctions.pending_refund(buyer), U512::from(400));
+        let buyer_balance = env.balance_of(&buyer);
+        auctions.withdraw_refund();
+        assert_eq!(env.balance_of(&buyer), buyer_balance + U512::from(400));
+        assert_eq!(
+            auctions.try_withdraw_refund(),
+            Err(Error::NothingToWithdraw.into())
+        );
+        assert_eq!(
+            auctions.try_confirm_delivery(job_id),
+            Err(Error::JobNotActive.into())
+        );
+    }
+
+    #[test]
+    fn unanswered_job_is_cancelled() {
+        let env = odra_test::env();
+        let buyer = env.get_account(1);
+        let mut auctions = ReverseAuctionsHostRef::deploy(&env, NoArgs);
+
+        env.set_caller(buyer);
+        assert_eq!(
+            auctions.try_post_job("Paint the fence".to_string(), DURATION),
+            Err(Error::EmptyBudget.into())
+        );
+        let job_id = auctions
+            .with_tokens(U512::from(500))
+            .post_job("Paint the fence".to_string(), DURATION);
+        env.advance_block_time(DURATION + 1);
+        assert_eq!(
+            auctions.try_confirm_delivery(job_id),
+            Err(Error::NoBids.into())
+        );
+        auctions.cancel_job(job_id);
+        assert_eq!(auctions.pending_refund(buyer), U512::from(500));
+
+        // A job with bids can't be cancelled
+        let job_id = auctions
+            .with_tokens(U512::from(500))
+            .post_job("Mow the lawn".to_string(), DURATION);
+        env.set_caller(env.get_account(2));
+        auctions.bid(job_id, U512::from(300));
+        env.set_caller(buyer);
+        assert_eq!(
+            auctions.try_cancel_job(job_id),
+            Err(Error::JobHasBids.into())
+        );
+    }
+}
@@ -0,0 +1,449 @@
+use crate::auctions::AuctionStatus;
+use crate::custody::{self, NftId};
+use odra::{
+    casper_types::{bytesrepr::ToBytes, U256, U512},
+    prelude::*,
+    Address, Mapping, Var,
+};
+
+#[odra::module(events = [BidCommitted, BidRevealed, SealedBidAuctionEnded], errors = Error)]
+/// This contract runs sealed-bid second-price (Vickrey) auctions for CEP-78 NFTs.
+///
+/// Bidders commit to a hidden bid while the auction is open and reveal it once bidding closes.
+/// The highest bidder wins but only pays the second-highest revealed bid (or the reserve price).
+pub struct SealedBidAuctions {
+    /// Storage for sealed-bid auctions, indexed by a unique auction ID.
+    auctions: Mapping<U256, SealedBidAuction>,
+    /// Counter to track the total number of auctions created.
+    auction_counter: Var<U256>,
+    /// Hash each bidder committed to, per auction.
+    commitments: Mapping<(U256, Address), [u8; 32]>,
+    /// CSPR each bidder locked together with their commitment, per auction.
+    deposits: Mapping<(U256, Address), U512>,
+    /// Whether a bidder has already revealed their bid, per auction.
+    revealed: Mapping<(U256, Address), bool>,
+}
+
+#[odra::odra_error]
+/// Errors that may occur during the contract execution.
+pub enum Error {
+    /// Bidding or reveal phases must last longer than zero.
+    InvalidDuration = 1,
+    /// No auction exists with the given ID.
+    AuctionNotFound = 2,
+    /// The bidding phase is over.
+    BiddingClosed = 3,
+    /// The bidding phase is still running.
+    BiddingStillOpen = 4,
+    /// The reveal phase is over.
+    RevealClosed = 5,
+    /// The reveal phase is still running.
+    RevealStillOpen = 6,
+    /// The bidder has already committed to a bid in this auction.
+    BidAlreadyCommitted = 7,
+    /// The bidder has no commitment in this auction.
+    NoCommitment = 8,
+    /// The revealed bid doesn't match the commitment.
+    InvalidReveal = 9,
+    /// The revealed bid is larger than the locked deposit.
+    InsufficientDeposit = 10,
+    /// The bid has already been revealed.
+    BidAlreadyRevealed = 11,
+    /// A commitment has to come with a non-zero deposit.
+    EmptyDeposit = 12,
+    /// The auction has already been settled.
+    AuctionNotActive = 13,
+    /// The auction has to be settled before deposits are withdrawn.
+    AuctionNotSettled = 14,
+    /// The caller has nothing to withdraw.
+    NothingToWithdraw = 15,
+}
+
+#[odra::odra_type]
+/// Represents a sealed-bid auction for an NFT.
+pub struct SealedBidAuction {
+    /// Address of the seller who initiated the auction.
+    seller: Address,
+    /// Address of the CEP-78 NFT contract.
+    nft_contract: Address,
+    /// ID of the NFT being auctioned.
+    nft_id: u64,
+    /// Lowest price the seller accepts.
+    reserve_price: U512,
+    /// Timestamp after which no more commitments are accepted.
+    bidding_ends_at: u64,
+    /// Timestamp after which no more bids can be revealed.
+    reveal_ends_at: u64,
+    /// Highest revealed bidder, if any bid met the reserve price.
+    highest_bidder: Option<Address>,
+    /// Highest revealed bid.
+    highest_bid: U512,
+    /// Second-highest revealed bid, which sets the price the winner pays.
+    second_highest_bid: U512,
+    /// Current status of the auction.
+    status: AuctionStatus,
+}
+
+#[odra::event]
+/// Emitted when a bidder commits to a sealed bid.
+pub struct BidCommitted {
+    /// ID of the auction.
+    pub auction_id: U256,
+    /// Bidder who committed.
+    pub bidder: Address,
+    /// Deposit locked with the commitment.
+    pub deposit: U512,
+}
+
+#[odra::event]
+/// Emitted when a bidder reveals their bid.
+pub struct BidRevealed {
+    /// ID of the auction.
+    pub auction_id: U256,
+    /// Bidder who revealed.
+    pub bidder: Address,
+    /// Revealed bid amount.
+    pub amount: U512,
+}
+
+#[odra::event]
+/// Emitted when a sealed-bid auction is settled.
+pub struct SealedBidAuctionEnded {
+    /// ID of the settled auction.
+    pub auction_id: U256,
+    /// Winner of the NFT, or None if no revealed bid met the reserve price.
+    pub winner: Option<Address>,
+    /// Price paid to the seller.
+    pub price: U512,
+}
+
+/// Builds the preimage a bidder hashes to commit to `amount`.
+///
+/// The bidder's address is part of it, so nobody can copy someone else's commitment.
+pub fn commitment_preimage(amount: U512, salt: u64, bidder: Address) -> Vec<u8> {
+    let mut preimage = amount.to_bytes().unwrap_or_default();
+    preimage.extend(salt.to_bytes().unwrap_or_default());
+    preimage.extend(bidder.to_bytes().unwrap_or_default());
+    preimage
+}
+
+#[odra::module]
+impl SealedBidAuctions {
+    /**********
+     * TRANSACTIONS
+     **********/
+
+    /// Creates a sealed-bid auction with a bidding phase followed by a reveal phase.
+    pub fn create_auction(
+        &mut self,
+        nft_contract: Address,
+        nft_id: u64,
+        reserve_price: U512,
+        bidding_duration: u64,
+        reveal_duration: u64,
+    ) -> U256 {
+        if bidding_duration == 0 || reveal_duration == 0 {
+            self.env().revert(Error::InvalidDuration);
+        }
+
+        let seller = self.env().caller();
+        custody::take_custody(self.env(), nft_contract, &NftId::Ordinal(nft_id), seller);
+
+        let now = self.env().get_block_time();
+        let auction_id = self.auction_counter.get_or_default() + U256::one(); // IDs start from 1
+        let auction = SealedBidAuction {
+            seller,
+            nft_contract,
+            nft_id,
+            reserve_price,
+            bidding_ends_at: now + bidding_duration,
+            reveal_ends_at: now + bidding_duration + reveal_duration,
+            highest_bidder: None,
+            highest_bid: U512::zero(),
+            second_highest_bid: U512::zero(),
+            status: AuctionStatus::Active,
+        };
+        self.auctions.set(&auction_id, auction);
+        self.auction_counter.set(auction_id);
+        auction_id
+    }
+
+    /// Commits to a hidden bid, locking the attached CSPR as a deposit.
+    ///
+    /// The deposit may exceed the bid to avoid leaking its value.
+    #[odra(payable)]
+    pub fn commit_bid(&mut self, auction_id: U256, commitment: [u8; 32]) {
+        let auction = self.get_active_auction(auction_id);
+        let bidder = self.env().caller();
+        let deposit = self.env().attached_value();
+
+        if self.env().get_block_time() > auction.bidding_ends_at {
+            self.env().revert(Error::BiddingClosed);
+        }
+        if self.commitments.get(&(auction_id, bidder)).is_some() {
+            self.env().revert(Error::BidAlreadyCommitted);
+        }
+        if deposit.is_zero() {
+            self.env().revert(Error::EmptyDeposit);
+        }
+
+        self.commitments.set(&(auction_id, bidder), commitment);
+        self.deposits.set(&(auction_id, bidder), deposit);
+        self.env().emit_event(BidCommitted {
+            auction_id,
+            bidder,
+            deposit,
+        });
+    }
+
+    /// Reveals a previously committed bid during the reveal phase.
+    pub fn reveal_bid(&mut self, auction_id: U256, amount: U512, salt: u64) {
+        let mut auction = self.get_active_auction(auction_id);
+        let bidder = self.env().caller();
+        let now = self.env().get_block_time();
+
+        if now <= auction.bidding_ends_at {
+            self.env().revert(Error::BiddingStillOpen);
+        }
+        if now > auction.reveal_ends_at {
+            self.env().revert(Error::RevealClosed);
+        }
+        let commitment = self
+            .commitments
+            .get(&(auction_id, bidder))
+            .unwrap_or_revert_with(&self.env(), Error::NoCommitment);
+        if self.revealed.get_or_default(&(auction_id, bidder)) {
+            self.env().revert(Error::BidAlreadyRevealed);
+        }
+        if self.env().hash(commitment_preimage(amount, salt, bidder)) != commitment {
+            self.env().revert(Error::InvalidReveal);
+        }
+        if amount > self.deposits.get_or_default(&(auction_id, bidder)) {
+            self.env().revert(Error::InsufficientDeposit);
+        }
+        self.revealed.set(&(auction_id, bidder), true);
+
+        // Only bids meeting the reserve take part in the ranking
+        if amount >= auction.reserve_price {
+            if auction.highest_bidder.is_none() || amount > auction.highest_bid {
+                auction.second_highest_bid = auction.highest_bid;
+                auction.highest_bid = amount;
+                auction.highest_bidder = Some(bidder);
+            } else if amount > auction.second_highest_bid {
+                auction.second_highest_bid = amount;
+            }
+            self.auctions.set(&auction_id, auction);
+        }
+
+        self.env().emit_event(BidRevealed {
+            auction_id,
+            bidder,
+            amount,
+        });
+    }
+
+    /// Settles the auction once the reveal phase is over.
+    ///
+    /// The winner pays the second-highest bid, or the reserve price if nobody else bid above it.
+    pub fn end_auction(&mut self, auction_id: U256) {
+        let mut auction = self.get_active_auction(auction_id);
+        if self.env().get_block_time() <= auction.reveal_ends_at {
+            self.env().revert(Error::RevealStillOpen);
+        }
+
+        auction.status = AuctionStatus::Settled;
+        self.auctions.set(&auction_id, auction.clone());
+
+        let (winner, price) = match auction.highest_bidder {
+            Some(winner) => {
+                let price = auction.second_highest_bid.max(auction.reserve_price);
+                // The price comes out of the winner's deposit, the rest stays withdrawable
+                let deposit = self.deposits.get_or_default(&(auction_id, winner));
+                self.deposits.set(&(auction_id, winner), deposit - price);
+                custody::release_custody(
+                    self.env(),
+                    auction.nft_contract,
+                    &NftId::Ordinal(auction.nft_id),
+                    winner,
+                );
+                self.env().transfer_tokens(&auction.seller, &price);
+                (Some(winner), price)
+            }
+            None => {
+                custody::release_custody(
+                    self.env(),
+                    auction.nft_contract,
+                    &NftId::Ordinal(auction.nft_id),
+                    auction.seller,
+                );
+                (None, U512::zero())
+            }
+        };
+        self.env().emit_event(SealedBidAuctionEnded {
+            auction_id,
+            winner,
+            price,
+        });
+    }
+
+    /// Returns the caller's deposit (minus the price paid, for the winner) after settlement.
+    pub fn withdraw_deposit(&mut self, auction_id: U256) {
+        let auction = self
+            .auctions
+            .get(&auction_id)
+            .unwrap_or_revert_with(&self.env(), Error::AuctionNotFound);
+        if auction.status != AuctionStatus::Settled {
+            self.env().revert(Error::AuctionNotSettled);
+        }
+
+        let bidder = self.env().caller();
+        let deposit = self.deposits.get_or_default(&(auction_id, bidder));
+        if deposit.is_zero() {
+            self.env().revert(Error::NothingToWithdraw);
+        }
+        self.deposits.set(&(auction_id, bidder), U512::zero()); // Clear before transferring
+        self.env().transfer_tokens(&bidder, &deposit);
+    }
+
+    /**********
+     * QUERIES
+     **********/
+
+    /// Returns the auction with the given ID, if it exists.
+    pub fn get_auction(&self, auction_id: U256) -> Option<SealedBidAuction> {
+        self.auctions.get(&auction_id)
+    }
+
+    /// Returns the deposit the bidder currently has locked in the auction.
+    pub fn deposit_of(&self, auction_id: U256, bidder: Address) -> U512 {
+        self.deposits.get_or_default(&(auction_id, bidder))
+    }
+
+    /**********
+     * INTERNAL
+     **********/
+
+    /// Reads an auction, reverting if it doesn't exist or has been settled.
+    fn get_active_auction(&self, auction_id: U256) -> SealedBidAuction {
+        let auction = self
+            .auctions
+            .get(&auction_id)
+            .unwrap_or_revert_with(&self.env(), Error::AuctionNotFound);
+        if auction.status != AuctionStatus::Active {
+            self.env().revert(Error::AuctionNotActive);
+        }
+        auction
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use odra::args::Maybe;
+    use odra::casper_types::crypto::blake2b;
+    use odra::host::{Deployer, HostRef, NoArgs};
+    use odra_modules::cep78::modalities::{
+        MetadataMutability, NFTIdentifierMode, NFTKind, NFTMetadataKind, OwnershipMode,
+    };
+    use odra_modules::cep78::token::Cep78HostRef;
+    use odra_modules::cep78::utils::InitArgsBuilder;
+
+    const CEP78_METADATA: &str = r#"{
+        "name": "John Doe",
+        "token_uri": "https://www.barfoo.com",
+        "checksum": "940bffb3f2bba35f84313aa26da09ece3ad47045c6a1292c2bbd2df4ab1a55fb"
+    }"#;
+    const PHASE: u64 = 60 * 60 * 1000; // one hour
+
+    fn commitment(amount: u64, salt: u64, bidder: Address) -> [u8; 32] {
+        blake2b(commitment_preimage(U512::from(amount), salt, bidder))
+    }
+
+    #[test]
+    fn winner_pays_second_highest_bid() {
+        let env = odra_test::env();
+        let seller = env.get_account(1);
+        let alice = env.get_account(2);
+        let bob = env.get_account(3);
+        let carol = env.get_account(4);
+        let mut auctions = SealedBidAuctionsHostRef::deploy(&env, NoArgs);
+        let init_args = InitArgsBuilder::default()
+            .collection_name("Sealed Collection".to_string())
+            .collection_symbol("SLD".to_string())
+            .total_token_supply(10)
+            .ownership_mode(OwnershipMode::Transferable)
+            .nft_metadata_kind(NFTMetadataKind::CEP78)
+            .identifier_mode(NFTIdentifierMode::Ordinal)
+            .nft_kind(NFTKind::Digital)
+            .metadata_mutability(MetadataMutability::Immutable)
+            .receipt_name("sealed_receipt".to_string())
+            .build();
+        let mut nft = Cep78HostRef::deploy(&env, init_args);
+        nft.mint(seller, CEP78_METADATA.to_string(), Maybe::None);
+
+        env.set_caller(seller);
+        nft.approve(*auctions.address(), Maybe::Some(0), Maybe::None);
+        let auction_id = auctions.create_auction(*nft.address(), 0, U512::from(100), PHASE, PHASE);
+
+        // Deposits hide the actual bids
+        env.set_caller(alice);
+        auctions
+            .with_tokens(U512::from(1_000))
+            .commit_bid(auction_id, commitment(700, 1, alice));
+        env.set_caller(bob);
+        auctions
+            .with_tokens(U512::from(1_000))
+            .commit_bid(auction_id, commitment(500, 2, bob));
+        env.set_caller(carol);
+        auctions
+            .with_tokens(U512::from(1_000))
+            .commit_bid(auction_id, commitment(900, 3, carol));
+        assert_eq!(
+            auctions.try_reveal_bid(auction_id, U512::from(900), 3),
+            Err(Error::BiddingStillOpen.into())
+        );
+
+        env.advance_block_time(PHASE + 1);
+        env.set_caller(alice);
+        assert_eq!(
+            auctions
+                .with_tokens(U512::from(1_000))
+                .try_commit_bid(auction_id, commitment(800, 4, alice)),
+            Err(Error::BiddingClosed.into())
+        );
+        assert_eq!(
+            auctions.try_reveal_bid(auction_id, U512::from(800), 1),
+            Err(Error::InvalidReveal.into())
+        );
+        auctions.reveal_bid(auction_id, U512::from(700), 1);
+        env.set_caller(bob);
+        auctions.reveal_bid(auction_id, U512::from(500), 2);
+        // Carol never reveals, so her bid doesn't count
+
+        env.advance_block_time(PHASE);
+        let seller_balance = env.balance_of(&seller);
+        auctions.end_auction(auction_id);
+        assert!(env.emitted_event(
+            auctions.address(),
+            &SealedBidAuctionEnded {
+                auction_id,
+                winner: Some(alice),
+                price: U512::from(500),
+            },
+        ));
+        assert_eq!(nft.owner_of(Maybe::Some(0), Maybe::None), alice);
+        assert_eq!(env.balance_of(&seller), seller_balance + U512::from(500));
+
+        // Everyone withdraws what they don't owe
+        for (bidder, refund) in [(alice, 500), (bob, 1_000), (carol, 1_000)] {
+            let balance = env.balance_of(&bidder);
+            env.set_caller(bidder);
+            auctions.withdraw_deposit(auction_id);
+            assert_eq!(env.balance_of(&bidder), balance + U512::from(refund));
+        }
+        assert_eq!(
+            auctions.try_withdraw_deposit(auction_id),
+            Err(Error::NothingToWithdraw.into())
+        );
+    }
+}
@@ -0,0 +1,579 @@
+use auctions::custody::{self, NftId};
+use auctions::payouts;
+use odra::{
+    casper_types::{U256, U512},
+    prelude::*,
+    Address, Mapping, SubModule, Var,
+};
+use odra_modules::access::Ownable;
+
+#[odra::module(
+    events = [Listed, Sold, Delisted, OfferMade, OfferWithdrawn, RoyaltyPaid],
+    errors = Error
+)]
+/// This contract sells CEP-78 NFTs at fixed prices, and lets buyers make offers below the asking price.
+pub struct Marketplace {
+    /// Ownable submodule for managing contract ownership and permissions.
+    ownable: SubModule<Ownable>,
+    /// Platform fee in basis points, taken from every sale.
+    fee_bps: Var<u16>,
+    /// Fees collected from sales that the owner hasn't withdrawn yet.
+    collected_fees: Var<U512>,
+    /// Storage for listings, indexed by a unique listing ID.
+    listings: Mapping<U256, Listing>,
+    /// Counter to track the total number of listings created.
+    listing_counter: Var<U256>,
+    /// CSPR offered per listing and buyer, held until the offer is accepted or withdrawn.
+    offers: Mapping<(U256, Address), U512>,
+}
+
+#[odra::odra_error]
+/// Errors that may occur during the contract execution.
+pub enum Error {
+    /// No listing exists with the given ID.
+    ListingNotFound = 1,
+    /// The listing has already been sold or delisted.
+    ListingNotActive = 2,
+    /// Only the seller can perform this action.
+    NotSeller = 3,
+    /// Listings need a positive price.
+    InvalidPrice = 4,
+    /// The attached amount doesn't match the asking price.
+    IncorrectPayment = 5,
+    /// Offers have to be positive and below the asking price.
+    InvalidOffer = 6,
+    /// The buyer has no offer on the listing.
+    NoOffer = 7,
+    /// The royalty and the platform fee together exceed 100% of the price.
+    InvalidFees = 8,
+    /// Sellers can't buy or make offers on their own listings.
+    SellerCannotBuy = 9,
+    /// There are no collected fees to withdraw.
+    NothingToWithdraw = 10,
+}
+
+#[odra::odra_type]
+/// Lifecycle of a listing.
+pub enum ListingStatus {
+    /// The NFT can be bought, and offers can be made.
+    Active,
+    /// The NFT was bought at the asking price or through an accepted offer.
+    Sold,
+    /// The seller took the NFT back.
+    Delisted,
+}
+
+#[odra::odra_type]
+/// Represents an NFT listed for sale.
+pub struct Listing {
+    /// Address of the seller who listed the NFT.
+    seller: Address,
+    /// Address of the CEP-78 NFT contract.
+    nft_contract: Address,
+    /// ID of the listed NFT, either ordinal or hash-based.
+    nft_id: NftId,
+    /// Asking price in CSPR.
+    price: U512,
+    /// Optional creator royalty as a receiver and its share in basis points.
+    royalty: Option<(Address, u16)>,
+    /// Current status of the listing.
+    status: ListingStatus,
+}
+
+#[odra::event]
+/// Emitted when a seller lists an NFT.
+pub struct Listed {
+    /// ID of the new listing.
+    pub listing_id: U256,
+    /// Seller who listed the NFT.
+    pub seller: Address,
+    /// Asking price in CSPR.
+    pub price: U512,
+}
+
+#[odra::event]
+/// Emitted when a listed NFT is sold.
+pub struct Sold {
+    /// ID of the sold listing.
+    pub listing_id: U256,
+    /// Buyer of the NFT.
+    pub buyer: Address,
+    /// Price paid, split between the seller, the royalty receiver and the platform.
+    pub price: U512,
+}
+
+#[odra::event]
+/// Emitted when a seller takes a listed NFT back.
+pub struct Delisted {
+    /// ID of the listing.
+    pub listing_id: U256,
+}
+
+#[odra::event]
+/// Emitted when a buyer makes or raises an offer.
+pub struct OfferMade {
+    /// ID of the listing.
+    pub listing_id: U256,
+    /// Buyer who made the offer.
+    pub buyer: Address,
+    /// Total amount offered.
+    pub amount: U512,
+}
+
+#[odra::event]
+/// Emitted when a buyer takes an offer back.
+pub struct OfferWithdrawn {
+    /// ID of the listing.
+    pub listing_id: U256,
+    /// Buyer who withdrew the offer.
+    pub buyer: Address,
+    /// Amount returned to the buyer.
+    pub amount: U512,
+}
+
+#[odra::event]
+/// Emitted when part of a sale is paid out as a creator royalty.
+pub struct RoyaltyPaid {
+    /// ID of the sold listing.
+    pub listing_id: U256,
+    /// Receiver of the royalty.
+    pub receiver: Address,
+    /// Royalty amount in CSPR.
+    pub amount: U512,
+}
+
+#[odra::module]
+impl Marketplace {
+    /// Initializes the contract with the platform fee, in basis points.
+    pub fn init(&mut self, fee_bps: u16) {
+        self.ownable.init();
+        if !payouts::is_valid(None, fee_bps) {
+            self.env().revert(Error::InvalidFees);
+        }
+        self.fee_bps.set(fee_bps);
+    }
+
+    /**********
+     * TRANSACTIONS
+     **********/
+
+    /// Lists a CEP-78 NFT at a fixed price and returns the listing ID.
+    ///
+    /// The seller has to approve the contract for the token beforehand.
+    pub fn list(
+        &mut self,
+        nft_contract: Address,
+        nft_id: NftId,
+        price: U512,
+        royalty: Option<(Address, u16)>,
+    ) -> U256 {
+        if price.is_zero() {
+            self.env().revert(Error::InvalidPrice);
+        }
+        if !payouts::is_valid(royalty, self.fee_bps.get_or_default()) {
+            self.env().revert(Error::InvalidFees);
+        }
+
+        let seller = self.env().caller();
+        custody::take_custody(self.env(), nft_contract, &nft_id, seller);
+
+        let listing_id = self.listing_counter.get_or_default() + U256::one(); // IDs start from 1
+        let listing = Listing {
+            seller,
+            nft_contract,
+            nft_id,
+            price,
+            royalty,
+            status: ListingStatus::Active,
+        };
+        self.listings.set(&listing_id, listing);
+        self.listing_counter.set(listing_id);
+        self.env().emit_event(Listed {
+            listing_id,
+            seller,
+            price,
+        });
+        listing_id
+    }
+
+    /// Buys the NFT at the asking price, which has to be attached exactly.
+    #[odra(payable)]
+    pub fn buy(&mut self, listing_id: U256) {
+        let listing = self.get_active_listing(listing_id);
+        let buyer = self.env().caller();
+        if buyer == listing.seller {
+            self.env().revert(Error::SellerCannotBuy);
+        }
+        if self.env().attached_value() != listing.price {
+            self.env().revert(Error::IncorrectPayment);
+        }
+        let price = listing.price;
+        self.sell(listing_id, listing, buyer, price);
+    }
+
+    /// Takes the NFT back from an unsold listing. Open offers stay withdrawable.
+    pub fn delist(&mut self, listing_id: U256) {
+        let mut listing = self.get_active_listing(listing_id);
+        if self.env().caller() != listing.seller {
+            self.env().revert(Error::NotSeller);
+        }
+
+        listing.status = ListingStatus::Delisted;
+        self.listings.set(&listing_id, listing.clone());
+        custody::release_custody(
+            self.env(),
+            listing.nft_contract,
+            &listing.nft_id,
+            listing.seller,
+        );
+        self.env().emit_event(Delisted { listing_id });
+    }
+
+    /// Offers the attached CSPR for the NFT, adding to the caller's previous offer (if any).
+    ///
+    /// The total has to stay below the asking price; at the asking price, the NFT can simply be bought.
+    #[odra(payable)]
+    pub fn make_offer(&mut self, listing_id: U256) {
+        let listing = self.get_active_listing(listing_id);
+        let buyer = self.env().caller();
+        if buyer == listing.seller {
+            self.env().revert(Error::SellerCannotBuy);
+        }
+        let attached = self.env().attached_value();
+        let amount = self.offers.get_or_default(&(listing_id, buyer)) + attached;
+        if attached.is_zero() || amount >= listing.price {
+            self.env().revert(Error::InvalidOffer);
+        }
+
+        self.offers.set(&(listing_id, buyer), amount);
+        self.env().emit_event(OfferMade {
+            listing_id,
+            buyer,
+            amount,
+        });
+    }
+
+    /// Returns the caller's offer. Works on any listing, so offers can be recovered after a sale.
+    pub fn withdraw_offer(&mut self, listing_id: U256) {
+        let buyer = self.env().caller();
+        let amount = self.offers.get_or_default(&(listing_id, buyer));
+        if amount.is_zero() {
+            self.env().revert(Error::NoOffer);
+        }
+
+        self.offers.set(&(listing_id, buyer), U512::zero()); // Clear before transferring
+        self.env().transfer_tokens(&buyer, &amount);
+        self.env().emit_event(OfferWithdrawn {
+            listing_id,
+            buyer,
+            amount,
+        });
+    }
+
+    /// Sells the NFT to `buyer` for the amount they offered.
+    pub fn accept_offer(&mut self, listing_id: U256, buyer: Address) {
+        let listing = self.get_active_listing(listing_id);
+        if self.env().caller() != listing.seller {
+            self.env().revert(Error::NotSeller);
+        }
+        let amount = self.offers.get_or_default(&(listing_id, buyer));
+        if amount.is_zero() {
+            self.env().revert(Error::NoOffer);
+        }
+
+        self.offers.set(&(listing_id, buyer), U512::zero());
+        self.sell(listing_id, listing, buyer, amount);
+    }
+
+    /**********
+     * QUERIES
+     **********/
+
+    /// Returns the listing with the given ID, if it exists.
+    pub fn get_listing(&self, listing_id: U256) -> Option<Listing> {
+        self.listings.get(&listing_id)
+    }
+
+    /// Returns the amount the given buyer offers for a listing.
+    pub fn offer_of(&self, listing_id: U256, buyer: Address) -> U512 {
+        self.offers.get_or_default(&(listing_id, buyer))
+    }
+
+    /// Returns the platform fee in basis points.
+    pub fn get_fee_bps(&self) -> u16 {
+        self.fee_bps.get_or_default()
+    }
+
+    /// Returns the fees collected from sales that haven't been withdrawn.
+    pub fn get_collected_fees(&self) -> U512 {
+        self.collected_fees.get_or_default()
+    }
+
+    /**********
+     * ADMIN
+     **********/
+
+    /// Sends the collected fees to the owner.
+    pub fn withdraw_fees(&mut self) {
+        let owner = self.env().caller();
+        self.ownable.assert_owner(&owner);
+        let amount = self.collected_fees.get_or_default();
+        if amount.is_zero() {
+            self.env().revert(Error::NothingToWithdraw);
+        }
+        self.collected_fees.set(U512::zero());
+        self.env().transfer_tokens(&owner, &amount);
+    }
+
+    /**********
+     * INTERNAL
+     **********/
+
+    /// Reads a listing, reverting if it doesn't exist or is no longer active.
+    fn get_active_listing(&self, listing_id: U256) -> Listing {
+        let listing = self
+            .listings
+            .get(&listing_id)
+            .unwrap_or_revert_with(&self.env(), Error::ListingNotFound);
+        if listing.status != ListingStatus::Active {
+            self.env().revert(Error::ListingNotActive);
+        }
+        listing
+    }
+
+    /// Closes the listing, hands the NFT to the buyer and splits `price` between the seller,
+    /// the royalty receiver and the platform.
+    fn sell(&mut self, listing_id: U256, mut listing: Listing, buyer: Address, price: U512) {
+        listing.status = ListingStatus::Sold;
+        self.listings.set(&listing_id, listing.clone());
+        custody::release_custody(self.env(), listing.nft_contract, &listing.nft_id, buyer);
+
+        let split = payouts::split(price, listing.royalty, self.fee_bps.get_or_default());
+        if let Some((receiver, _)) = listing.royalty {
+            if !split.royalty.is_zero() {
+                self.env().transfer_tokens(&receiver, &split.royalty);
+                self.env().emit_event(RoyaltyPaid {
+                    listing_id,
+                    receiver,
+                    amount: split.royalty,
+                });
+            }
+        }
+        self.collected_fees.add(split.fee);
+        self.env().transfer_tokens(&listing.seller, &split.seller);
+        self.env().emit_event(Sold {
+            listing_id,
+            buyer,
+            price,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use odra::args::Maybe;
+    use odra::host::{Deployer, HostEnv, HostRef};
+    use odra_modules::cep78::modalities::{
+        MetadataMutability, NFTIdentifierMode, NFTKind, NFTMetadataKind, OwnershipMode,
+    };
+    use odra_modules::cep78::token::Cep78HostRef;
+    use odra_modules::cep78::utils::InitArgsBuilder;
+
+    const CEP78_METADATA: &str = r#"{
+        "name": "John Doe",
+        "token_uri": "https://www.barfoo.com",
+        "checksum": "940bffb3f2bba35f84313aa26da09ece3ad47045c6a1292c2bbd2df4ab1a55fb"
+    }"#;
+
+    /// Deploys the marketplace with a 2.5% fee and an NFT collection, and lists the seller's
+    /// token for 1,000 motes with a 10% royalty.
+    fn setup() -> (HostEnv, MarketplaceHostRef, Cep78HostRef, U256) {
+        let env = odra_test::env();
+        let seller = env.get_account(1);
+        let creator = env.get_account(4);
+        let mut marketplace =
+            MarketplaceHostRef::deploy(&env, MarketplaceInitArgs { fee_bps: 250 });
+        let init_args = InitArgsBuilder::default()
+            .collection_name("Market Collection".to_string())
+            .collection_symbol("MKT".to_string())
+            .total_token_supply(10)
+            .ownership_mode(OwnershipMode::Transferable)
+            .nft_metadata_kind(NFTMetadataKind::CEP78)
+            .identifier_mode(NFTIdentifierMode::Ordinal)
+            .nft_kind(NFTKind::Digital)
+            .metadata_mutability(MetadataMutability::Immutable)
+            .receipt_name("market_receipt".to_string())
+            .build();
+        let mut nft = Cep78HostRef::deploy(&env, init_args);
+        nft.mint(seller, CEP78_METADATA.to_string(), Maybe::None);
+
+        env.set_caller(seller);
+        nft.approve(*marketplace.address(), Maybe::Some(0), Maybe::None);
+        let listing_id = marketplace.list(
+            *nft.address(),
+            NftId::Ordinal(0),
+            U512::from(1_000),
+            Some((creator, 1_000)),
+        );
+        (env, marketplace, nft, listing_id)
+    }
+
+    #[test]
+    fn buy_at_asking_price() {
+        let (env, mut marketplace, nft, listing_id) = setup();
+        let seller = env.get_account(1);
+        let buyer = env.get_account(2);
+        let creator = env.get_account(4);
+        assert_eq!(
+            nft.owner_of(Maybe::Some(0), Maybe::None),
+            *marketplace.address()
+        );
+        assert_eq!(
+            marketplace
+                .with_tokens(U512::from(1_000))
+                .try_buy(listing_id),
+            Err(Error::SellerCannotBuy.into())
+        );
+
+        env.set_caller(buyer);
+        assert_eq!(
+            marketplace.with_tokens(U512::from(999)).try_buy(listing_id),
+            Err(Error::IncorrectPayment.into())
+        );
+
+        // The price is split between the creator, the platform and the seller
+        let seller_balance = env.balance_of(&seller);
+        let creator_balance = env.balance_of(&creator);
+        marketplace.with_tokens(U512::from(1_000)).buy(listing_id);
+        assert_eq!(nft.owner_of(Maybe::Some(0), Maybe::None), buyer);
+        assert_eq!(env.balance_of(&creator), creator_balance + U512::from(100));
+        assert_eq!(env.balance_of(&seller), seller_balance + U512::from(875));
+        assert_eq!(marketplace.get_collected_fees(), U512::from(25));
+        assert!(env.emitted_event(
+            marketplace.address(),
+            &Sold {
+                listing_id,
+                buyer,
+                price: U512::from(1_000),
+            },
+        ));
+        assert_eq!(
+            marketplace
+                .with_tokens(U512::from(1_000))
+                .try_buy(listing_id),
+            Err(Error::ListingNotActive.into())
+        );
+
+        // Only the owner collects the fees
+        assert!(marketplace.try_withdraw_fees().is_err());
+        env.set_caller(env.get_account(0));
+        marketplace.withdraw_fees();
+        assert_eq!(marketplace.get_collected_fees(), U512::zero());
+        assert_eq!(env.balance_of(marketplace.address()), U512::zero());
+    }
+
+    #[test]
+    fn accept_offer() {
+        let (env, mut marketplace, nft, listing_id) = setup();
+        let seller = env.get_account(1);
+        let alice = env.get_account(2);
+        let bob = env.get_account(3);
+
+        env.set_caller(alice);
+        assert_eq!(
+            marketplace
+                .with_tokens(U512::from(1_000))
+                .try_make_offer(listing_id),
+            Err(Error::InvalidOffer.into())
+        );
+        marketplace
+            .with_tokens(U512::from(600))
+            .make_offer(listing_id);
+        marketplace
+            .with_tokens(U512::from(200))
+            .make_offer(listing_id);
+        assert_eq!(marketplace.offer_of(listing_id, alice), U512::from(800));
+        env.set_caller(bob);
+        marketplace
+            .with_tokens(U512::from(700))
+            .make_offer(listing_id);
+
+        env.set_caller(alice);
+        assert_eq!(
+            marketplace.try_accept_offer(listing_id, alice),
+            Err(Error::NotSeller.into())
+        );
+
+        // The seller picks an offer, and the NFT is sold for it
+        env.set_caller(seller);
+        assert_eq!(
+            marketplace.try_accept_offer(listing_id, env.get_account(5)),
+            Err(Error::NoOffer.into())
+        );
+        let seller_balance = env.balance_of(&seller);
+        marketplace.accept_offer(listing_id, alice);
+        assert_eq!(nft.owner_of(Maybe::Some(0), Maybe::None), alice);
+        // 700 minus the 10% royalty and the 2.5% fee, rounded down
+        assert_eq!(env.balance_of(&seller), seller_balance + U512::from(613));
+        assert_eq!(marketplace.offer_of(listing_id, alice), U512::zero());
+
+        // The other offers can be withdrawn after the sale
+        env.set_caller(bob);
+        let bob_balance = env.balance_of(&bob);
+        marketplace.withdraw_offer(listing_id);
+        assert_eq!(env.balance_of(&bob), bob_balance + U512::from(700));
+        assert_eq!(
+            marketplace.try_withdraw_offer(listing_id),
+            Err(Error::NoOffer.into())
+        );
+    }
+
+    #[test]
+    fn delist() {
+        let (env, mut marketplace, nft, listing_id) = setup();
+        let seller = env.get_account(1);
+
+        env.set_caller(env.get_account(2));
+        assert_eq!(
+            marketplace.try_delist(listing_id),
+            Err(Error::NotSeller.into())
+        );
+
+        env.set_caller(seller);
+        marketplace.delist(listing_id);
+        assert_eq!(nft.owner_of(Maybe::Some(0), Maybe::None), seller);
+        assert_eq!(
+            marketplace.try_delist(listing_id),
+            Err(Error::ListingNotActive.into())
+        );
+        assert_eq!(
+            marketplace.try_delist(listing_id + U256::one()),
+            Err(Error::ListingNotFound.into())
+        );
+    }
+
+    #[test]
+    fn royalty_and_fee_cannot_exceed_the_price() {
+        let (env, mut marketplace, mut nft, _) = setup();
+        let seller = env.get_account(1);
+
+        env.set_caller(env.get_account(0));
+        nft.mint(seller, CEP78_METADATA.to_string(), Maybe::None);
+        env.set_caller(seller);
+        nft.approve(*marketplace.address(), Maybe::Some(1), Maybe::None);
+        assert_eq!(
+            marketplace.try_list(
+                *nft.address(),
+                NftId::Ordinal(1),
+                U512::from(1_000),
+                Some((env.get_account(4), 9_751)),
+            ),
+            Err(Error::InvalidFees.into())
+        );
+        assert_eq!(
+            marketplace.try_list(*nft.address(), NftId::Ordinal(1), U512::zero(), None),
+            Err(Error::InvalidPrice.into())
+        );
+    }
+}
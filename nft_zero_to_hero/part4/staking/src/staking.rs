@@ -0,0 +1,343 @@
+use odra::{
+    args::Maybe,
+    casper_types::{U256, U512},
+    prelude::*,
+    Address, ContractRef, Mapping, Var,
+};
+use odra_modules::{cep18_token::Cep18ContractRef, cep78::token::Cep78ContractRef};
+
+#[odra::module(events = [Staked, Unstaked, RewardsClaimed], errors = Error)]
+/// This contract lets holders stake CEP-78 NFTs and earn CSPR or CEP-18 rewards for every unit of time they stay staked.
+pub struct NftStaking {
+    /// Address of the CEP-78 collection that can be staked.
+    nft_contract: Var<Address>,
+    /// Optional CEP-18 token rewards are paid in (None for CSPR).
+    reward_token: Var<Option<Address>>,
+    /// Reward earned by each staked NFT per millisecond of block time.
+    reward_rate: Var<U512>,
+    /// Staker of each staked NFT, indexed by token ID (None once unstaked).
+    stakers: Mapping<u64, Option<Address>>,
+    /// IDs of the NFTs each staker has staked.
+    staked_tokens: Mapping<Address, Vec<u64>>,
+    /// Rewards accrued by each staker up to their last update.
+    accrued_rewards: Mapping<Address, U512>,
+    /// Block time each staker's rewards were last accrued at.
+    last_update: Mapping<Address, u64>,
+}
+
+#[odra::odra_error]
+/// Errors that may occur during the contract execution.
+pub enum Error {
+    /// The NFT isn't staked in this contract.
+    TokenNotStaked = 1,
+    /// Only the staker of the NFT can unstake it.
+    NotStaker = 2,
+    /// The caller has no rewards to claim.
+    NothingToClaim = 3,
+    /// The reward amount doesn't fit into a CEP-18 token amount.
+    AmountTooLarge = 4,
+}
+
+#[odra::event]
+/// Emitted when an NFT is staked.
+pub struct Staked {
+    /// Owner who staked the NFT.
+    pub staker: Address,
+    /// ID of the staked NFT.
+    pub token_id: u64,
+}
+
+#[odra::event]
+/// Emitted when an NFT is returned to its staker.
+pub struct Unstaked {
+    /// Staker who got the NFT back.
+    pub staker: Address,
+    /// ID of the unstaked NFT.
+    pub token_id: u64,
+}
+
+#[odra::event]
+/// Emitted when a staker claims their rewards.
+pub struct RewardsClaimed {
+    /// Staker who claimed the rewards.
+    pub staker: Address,
+    /// Amount of rewards paid out.
+    pub amount: U512,
+}
+
+#[odra::module]
+impl NftStaking {
+    /// Initializes the contract with the stakeable collection, the reward currency and the reward rate.
+    pub fn init(
+        &mut self,
+        nft_contract: Address,
+        reward_token: Option<Address>,
+        reward_rate: U512,
+    ) {
+        self.nft_contract.set(nft_contract);
+        self.reward_token.set(reward_token);
+        self.reward_rate.set(reward_rate);
+    }
+
+    /**********
+     * TRANSACTIONS
+     **********/
+
+    /// Stakes an NFT; the owner has to approve the contract for the token beforehand.
+    pub fn stake(&mut self, token_id: u64) {
+        let staker = self.env().caller();
+        self.accrue_rewards(staker);
+
+        // Transfer the NFT to the staking contract
+        let contract = self.env().self_address();
+        self.nft()
+            .transfer(Maybe::Some(token_id), Maybe::None, staker, contract);
+
+        self.stakers.set(&token_id, Some(staker));
+        let mut tokens = self.staked_tokens.get_or_default(&staker);
+        tokens.push(token_id);
+        self.staked_tokens.set(&staker, tokens);
+        self.env().emit_event(Staked { staker, token_id });
+    }
+
+    /// Returns a staked NFT to its staker; accrued rewards stay claimable.
+    pub fn unstake(&mut self, token_id: u64) {
+        let staker = self.env().caller();
+        let owner = self
+            .stakers
+            .get_or_default(&token_id)
+            .unwrap_or_revert_with(&self.env(), Error::TokenNotStaked);
+        if owner != staker {
+            self.env().revert(Error::NotStaker);
+        }
+        self.accrue_rewards(staker);
+
+        self.stakers.set(&token_id, None);
+        let mut tokens = self.staked_tokens.get_or_default(&staker);
+        tokens.retain(|id| *id != token_id);
+        self.staked_tokens.set(&staker, tokens);
+
+        let contract = self.env().self_address();
+        self.nft()
+            .transfer(Maybe::Some(token_id), Maybe::None, contract, staker);
+        self.env().emit_event(Unstaked { staker, token_id });
+    }
+
+    /// Pays out all rewards the caller has earned so far.
+    pub fn claim_rewards(&mut self) {
+        let staker = self.env().caller();
+        self.accrue_rewards(staker);
+
+        let amount = self.accrued_rewards.get_or_default(&staker);
+        if amount.is_zero() {
+            self.env().revert(Error::NothingToClaim);
+        }
+        self.accrued_rewards.set(&staker, U512::zero()); // Clear before transferring
+        match self.reward_token.get_or_default() {
+            Some(token) => {
+                Cep18ContractRef::new(self.env(), token)
+                    .transfer(&staker, &self.to_token_amount(amount));
+            }
+            None => self.env().transfer_tokens(&staker, &amount),
+        }
+        self.env().emit_event(RewardsClaimed { staker, amount });
+    }
+
+    /// Adds the attached CSPR to the reward pool; CEP-18 rewards are funded by transferring tokens to the contract.
+    #[odra(payable)]
+    pub fn fund_rewards(&mut self) {}
+
+    /**********
+     * QUERIES
+     **********/
+
+    /// Returns the rewards the staker could claim right now.
+    pub fn pending_rewards(&self, staker: Address) -> U512 {
+        self.accrued_rewards.get_or_default(&staker) + self.unaccrued_rewards(staker)
+    }
+
+    /// Returns the IDs of the NFTs the staker has staked.
+    pub fn staked_tokens_of(&self, staker: Address) -> Vec<u64> {
+        self.staked_tokens.get_or_default(&staker)
+    }
+
+    /// Returns the staker of the given NFT, if it's staked.
+    pub fn staker_of(&self, token_id: u64) -> Option<Address> {
+        self.stakers.get_or_default(&token_id)
+    }
+
+    /**********
+     * INTERNAL
+     **********/
+
+    /// Returns a reference to the stakeable CEP-78 collection.
+    fn nft(&self) -> Cep78ContractRef {
+        Cep78ContractRef::new(
+            self.env(),
+            self.nft_contract.get().unwrap_or_revert(&self.env()),
+        )
+    }
+
+    /// Rewards earned since the staker's last update, at the current number of staked NFTs.
+    fn unaccrued_rewards(&self, staker: Address) -> U512 {
+        let staked = self.staked_tokens.get_or_default(&staker).len() as u64;
+        let elapsed = self.env().get_block_time() - self.last_update.get_or_default(&staker);
+        self.reward_rate.get_or_default() * U512::from(staked) * U512::from(elapsed)
+    }
+
+    /// Adds the rewards earned since the last update to the staker's balance.
+    fn accrue_rewards(&mut self, staker: Address) {
+        let earned = self.unaccrued_rewards(staker);
+        self.accrued_rewards.set(
+            &staker,
+            self.accrued_rewards.get_or_default(&staker) + earned,
+        );
+        self.last_update.set(&staker, self.env().get_block_time());
+    }
+
+    /// Converts a reward amount to a CEP-18 token amount, reverting if it doesn't fit.
+    fn to_token_amount(&self, amount: U512) -> U256 {
+        if amount.bits() > 256 {
+            self.env().revert(Error::AmountTooLarge);
+        }
+        let mut bytes = [0u8; 64];
+        amount.to_little_endian(&mut bytes);
+        U256::from_little_endian(&bytes[..32])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use odra::host::{Deployer, HostEnv, HostRef};
+    use odra_modules::cep18_token::{Cep18HostRef, Cep18InitArgs};
+    use odra_modules::cep78::modalities::{
+        MetadataMutability, NFTIdentifierMode, NFTKind, NFTMetadataKind, OwnershipMode,
+    };
+    use odra_modules::cep78::token::Cep78HostRef;
+    use odra_modules::cep78::utils::InitArgsBuilder;
+
+    const CEP78_METADATA: &str = r#"{
+        "name": "John Doe",
+        "token_uri": "https://www.barfoo.com",
+        "checksum": "940bffb3f2bba35f84313aa26da09ece3ad47045c6a1292c2bbd2df4ab1a55fb"
+    }"#;
+
+    /// Deploys an NFT collection with two tokens minted to the staker, and the staking contract.
+    fn setup(env: &HostEnv, reward_token: Option<Address>) -> (NftStakingHostRef, Cep78HostRef) {
+        let staker = env.get_account(1);
+        let init_args = InitArgsBuilder::default()
+            .collection_name("Staked Collection".to_string())
+            .collection_symbol("STK".to_string())
+            .total_token_supply(100)
+            .ownership_mode(OwnershipMode::Transferable)
+            .nft_metadata_kind(NFTMetadataKind::CEP78)
+            .identifier_mode(NFTIdentifierMode::Ordinal)
+            .nft_kind(NFTKind::Digital)
+            .metadata_mutability(MetadataMutability::Immutable)
+            .receipt_name("staking_receipt".to_string())
+            .build();
+        let mut nft = Cep78HostRef::deploy(env, init_args);
+        nft.mint(staker, CEP78_METADATA.to_string(), Maybe::None);
+        nft.mint(staker, CEP78_METADATA.to_string(), Maybe::None);
+
+        let staking = NftStakingHostRef::deploy(
+            env,
+            NftStakingInitArgs {
+                nft_contract: *nft.address(),
+                reward_token,
+                reward_rate: U512::one(),
+            },
+        );
+
+        env.set_caller(staker);
+        nft.approve(*staking.address(), Maybe::Some(0), Maybe::None);
+        nft.approve(*staking.address(), Maybe::Some(1), Maybe::None);
+        (staking, nft)
+    }
+
+    #[test]
+    fn cspr_rewards() {
+        let env = odra_test::env();
+        let (mut staking, nft) = setup(&env, None);
+        let staker = env.get_account(1);
+        staking.with_tokens(U512::from(10_000)).fund_rewards();
+
+        staking.stake(0);
+        staking.stake(1);
+        assert_eq!(
+            nft.owner_of(Maybe::Some(0), Maybe::None),
+            *staking.address()
+        );
+        assert_eq!(staking.staked_tokens_of(staker), vec![0, 1]);
+
+        // Two NFTs earn twice the rate
+        env.advance_block_time(1_000);
+        assert_eq!(staking.pending_rewards(staker), U512::from(2_000));
+
+        staking.unstake(0);
+        assert_eq!(nft.owner_of(Maybe::Some(0), Maybe::None), staker);
+        assert_eq!(staking.staker_of(0), None);
+        assert_eq!(staking.staked_tokens_of(staker), vec![1]);
+
+        // Rewards accrued before unstaking are kept
+        env.advance_block_time(1_000);
+        assert_eq!(staking.pending_rewards(staker), U512::from(3_000));
+
+        let balance = env.balance_of(&staker);
+        staking.claim_rewards();
+        assert_eq!(env.balance_of(&staker), balance + U512::from(3_000));
+        assert_eq!(staking.pending_rewards(staker), U512::zero());
+        assert!(env.emitted_event(
+            staking.address(),
+            &RewardsClaimed {
+                staker,
+                amount: U512::from(3_000)
+            }
+        ));
+        assert_eq!(
+            staking.try_claim_rewards(),
+            Err(Error::NothingToClaim.into())
+        );
+    }
+
+    #[test]
+    fn only_the_staker_can_unstake() {
+        let env = odra_test::env();
+        let (mut staking, _) = setup(&env, None);
+        staking.stake(0);
+
+        env.set_caller(env.get_account(2));
+        assert_eq!(staking.try_unstake(0), Err(Error::NotStaker.into()));
+        assert_eq!(staking.try_unstake(1), Err(Error::TokenNotStaked.into()));
+    }
+
+    #[test]
+    fn token_rewards() {
+        let env = odra_test::env();
+        let mut token = Cep18HostRef::deploy(
+            &env,
+            Cep18InitArgs {
+                symbol: "RWD".to_string(),
+                name: "Reward Token".to_string(),
+                decimals: 9,
+                initial_supply: U256::from(10_000),
+                admin_list: vec![],
+                minter_list: vec![],
+                modality: None,
+            },
+        );
+        let (mut staking, _) = setup(&env, Some(*token.address()));
+        let staker = env.get_account(1);
+
+        // Token rewards are funded with a plain transfer
+        env.set_caller(env.get_account(0));
+        token.transfer(staking.address(), &U256::from(10_000));
+
+        env.set_caller(staker);
+        staking.stake(0);
+        env.advance_block_time(500);
+        staking.claim_rewards();
+        assert_eq!(token.balance_of(&staker), U256::from(500));
+    }
+}
@@ -0,0 +1,194 @@
+use odra::casper_types::U256;
+use odra::module::SubModule;
+use odra::prelude::*;
+use odra::{Address, Mapping};
+use odra_modules::access::Ownable;
+
+/// Prices are fixed-point numbers with this many decimals.
+pub const PRICE_DECIMALS: u8 = 8;
+
+#[odra::odra_error]
+/// Errors that may occur during the contract execution.
+pub enum Error {
+    /// Only authorized reporters can submit prices.
+    NotReporter = 1,
+    /// Prices must be positive.
+    InvalidPrice = 2,
+    /// The timestamp is in the future, or not newer than the current price's.
+    InvalidTimestamp = 3,
+    /// No price has been reported for the pair.
+    PriceNotFound = 4,
+    /// The latest price is older than the reader accepts.
+    StalePrice = 5,
+}
+
+#[odra::odra_type]
+pub struct PriceData {
+    /// Price with `PRICE_DECIMALS` decimals.
+    pub price: U256,
+    /// Block time the price was observed at.
+    pub timestamp: u64,
+    pub reporter: Address,
+}
+
+#[odra::event]
+/// Emitted when a reporter submits a price.
+pub struct PriceUpdated {
+    pub pair: String,
+    pub price: U256,
+    pub timestamp: u64,
+    pub reporter: Address,
+}
+
+#[odra::event]
+/// Emitted when the owner authorizes or revokes a reporter.
+pub struct ReporterUpdated {
+    pub reporter: Address,
+    pub authorized: bool,
+}
+
+#[odra::module(events = [PriceUpdated, ReporterUpdated], errors = Error)]
+/// Prices of currency pairs, pushed by authorized reporters.
+pub struct PriceFeed {
+    ownable: SubModule<Ownable>,
+    reporters: Mapping<Address, bool>,
+    prices: Mapping<String, PriceData>,
+}
+
+#[odra::module]
+impl PriceFeed {
+    /// Initializes the feed, owned by the deployer.
+    pub fn init(&mut self) {
+        self.ownable.init();
+    }
+
+    /**********
+     * TRANSACTIONS
+     **********/
+
+    /// Records the price of `pair`, e.g. "CSPR/USD", observed at `timestamp`.
+    pub fn submit(&mut self, pair: String, price: U256, timestamp: u64) {
+        let reporter = self.env().caller();
+        if !self.reporters.get_or_default(&reporter) {
+            self.env().revert(Error::NotReporter);
+        }
+        if price.is_zero() {
+            self.env().revert(Error::InvalidPrice);
+        }
+        // Prices from the future, or not newer than the one stored, are rejected
+        let outdated =
+            matches!(self.prices.get(&pair), Some(latest) if timestamp <= latest.timestamp);
+        if timestamp > self.env().get_block_time() || outdated {
+            self.env().revert(Error::InvalidTimestamp);
+        }
+
+        self.prices.set(
+            &pair,
+            PriceData {
+                price,
+                timestamp,
+                reporter,
+            },
+        );
+        self.env().emit_event(PriceUpdated {
+            pair,
+            price,
+            timestamp,
+            reporter,
+        });
+    }
+
+    /// Authorizes or revokes a reporter. Only the owner can.
+    pub fn set_reporter(&mut self, reporter: Address, authorized: bool) {
+        self.ownable.assert_owner(&self.env().caller());
+        self.reporters.set(&reporter, authorized);
+        self.env().emit_event(ReporterUpdated {
+            reporter,
+            authorized,
+        });
+    }
+
+    /**********
+     * QUERIES
+     **********/
+
+    /// Returns the price of `pair`, reverting if it's older than `max_age`.
+    pub fn get_price(&self, pair: String, max_age: u64) -> U256 {
+        let data = self
+            .prices
+            .get(&pair)
+            .unwrap_or_revert_with(&self.env(), Error::PriceNotFound);
+        if self.env().get_block_time() - data.timestamp > max_age {
+            self.env().revert(Error::StalePrice);
+        }
+        data.price
+    }
+
+    /// Returns the latest price data of `pair`, however old.
+    pub fn latest(&self, pair: String) -> Option<PriceData> {
+        self.prices.get(&pair)
+    }
+
+    /// Returns whether `account` can submit prices.
+    pub fn is_reporter(&self, account: Address) -> bool {
+        self.reporters.get_or_default(&account)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use odra::host::{Deployer, HostRef, NoArgs};
+
+    const PAIR: &str = "CSPR/USD";
+
+    #[test]
+    fn reporting_and_reading() {
+        let env = odra_test::env();
+        let mut feed = PriceFeedHostRef::deploy(&env, NoArgs);
+        let reporter = env.get_account(1);
+        let pair = PAIR.to_string();
+        env.advance_block_time(1_000);
+
+        env.set_caller(reporter);
+        assert_eq!(
+            feed.try_submit(pair.clone(), U256::from(5_000_000), 1_000),
+            Err(Error::NotReporter.into())
+        );
+        env.set_caller(env.get_account(0));
+        feed.set_reporter(reporter, true);
+
+        env.set_caller(reporter);
+        assert_eq!(
+            feed.try_get_price(pair.clone(), 100),
+            Err(Error::PriceNotFound.into())
+        );
+        assert_eq!(
+            feed.try_submit(pair.clone(), U256::from(5_000_000), 1_001),
+            Err(Error::InvalidTimestamp.into())
+        );
+        feed.submit(pair.clone(), U256::from(5_000_000), 900);
+        assert_eq!(feed.get_price(pair.clone(), 100), U256::from(5_000_000));
+        assert_eq!(
+            feed.try_submit(pair.clone(), U256::from(4_000_000), 900),
+            Err(Error::InvalidTimestamp.into())
+        );
+
+        // Readers choose how old a price they accept
+        env.advance_block_time(50);
+        assert_eq!(
+            feed.try_get_price(pair.clone(), 100),
+            Err(Error::StalePrice.into())
+        );
+        assert_eq!(feed.get_price(pair.clone(), 200), U256::from(5_000_000));
+
+        // A revoked reporter can't submit anymore
+        env.set_caller(env.get_account(0));
+        feed.set_reporter(reporter, false);
+        env.set_caller(reporter);
+        assert_eq!(
+            feed.try_submit(pair, U256::from(4_000_000), 1_050),
+            Err(Error::NotReporter.into())
+        );
+    }
+}
@@ -0,0 +1,238 @@
+use crate::price_feed::PriceFeedContractRef;
+use odra::casper_types::{U256, U512};
+use odra::prelude::*;
+use odra::uints::ToU512;
+use odra::{Address, ContractRef, Var};
+
+/// Motes in one CSPR.
+const MOTES_PER_CSPR: u64 = 1_000_000_000;
+/// Collateral the payer has to keep while the payout is pending, as a percentage of its value.
+const COLLATERAL_RATIO_PERCENT: u64 = 150;
+
+#[odra::odra_error]
+/// Errors that may occur during the contract execution.
+pub enum Error {
+    /// Only the payer can withdraw collateral.
+    NotPayer = 1,
+    /// Only the beneficiary can claim the payout.
+    NotBeneficiary = 2,
+    /// The payout has already been claimed.
+    AlreadyClaimed = 3,
+    /// The withdrawal would leave less collateral than required.
+    Undercollateralized = 4,
+    /// The contract doesn't hold the amount.
+    InsufficientBalance = 5,
+}
+
+#[odra::event]
+/// Emitted when the beneficiary claims the payout.
+pub struct PayoutClaimed {
+    pub beneficiary: Address,
+    /// Dollar price of CSPR the payout was converted at.
+    pub price: U256,
+    pub amount: U512,
+}
+
+#[odra::module(events = [PayoutClaimed], errors = Error)]
+/// Pays a dollar amount in CSPR out of collateral, at the price reported by a price feed.
+pub struct StablePayout {
+    feed: Var<Address>,
+    pair: Var<String>,
+    /// Oldest price the contract accepts, in milliseconds.
+    max_age: Var<u64>,
+    payer: Var<Address>,
+    beneficiary: Var<Address>,
+    /// Amount owed in dollars, with the feed's decimals.
+    usd_amount: Var<U256>,
+    claimed: Var<bool>,
+}
+
+#[odra::module]
+impl StablePayout {
+    /// Initializes a payout of `usd_amount` from the deployer to `beneficiary`, converted at the
+    /// `pair` price of `feed`.
+    pub fn init(
+        &mut self,
+        feed: Address,
+        pair: String,
+        max_age: u64,
+        beneficiary: Address,
+        usd_amount: U256,
+    ) {
+        self.feed.set(feed);
+        self.pair.set(pair);
+        self.max_age.set(max_age);
+        self.payer.set(self.env().caller());
+        self.beneficiary.set(beneficiary);
+        self.usd_amount.set(usd_amount);
+    }
+
+    /**********
+     * TRANSACTIONS
+     **********/
+
+    /// Adds the attached CSPR to the collateral.
+    #[odra(payable)]
+    pub fn fund(&mut self) {}
+
+    /// Pays the beneficiary the dollar amount in CSPR at the current price, or the whole
+    /// collateral if it isn't enough.
+    pub fn claim(&mut self) {
+        let beneficiary = self.env().caller();
+        if Some(beneficiary) != self.beneficiary.get() {
+            self.env().revert(Error::NotBeneficiary);
+        }
+        if self.claimed.get_or_default() {
+            self.env().revert(Error::AlreadyClaimed);
+        }
+
+        let price = self.price();
+        let amount = self.cspr_value(price).min(self.env().self_balance());
+        self.claimed.set(true);
+        self.env().transfer_tokens(&beneficiary, &amount);
+        self.env().emit_event(PayoutClaimed {
+            beneficiary,
+            price,
+            amount,
+        });
+    }
+
+    /// Sends `amount` of collateral back to the payer. While the payout is pending, the rest has
+    /// to stay worth `COLLATERAL_RATIO_PERCENT` of it.
+    pub fn withdraw(&mut self, amount: U512) {
+        let payer = self.env().caller();
+        if Some(payer) != self.payer.get() {
+            self.env().revert(Error::NotPayer);
+        }
+        let balance = self.env().self_balance();
+        if amount > balance {
+            self.env().revert(Error::InsufficientBalance);
+        }
+        if !self.claimed.get_or_default() && balance - amount < self.required_collateral() {
+            self.env().revert(Error::Undercollateralized);
+        }
+        self.env().transfer_tokens(&payer, &amount);
+    }
+
+    /**********
+     * QUERIES
+     **********/
+
+    /// Returns the payout in motes at the current price.
+    pub fn payout_value(&self) -> U512 {
+        self.cspr_value(self.price())
+    }
+
+    /// Returns the collateral the payer has to keep at the current price.
+    pub fn required_collateral(&self) -> U512 {
+        self.payout_value() * COLLATERAL_RATIO_PERCENT / 100
+    }
+
+    /// Returns whether the payout has been claimed.
+    pub fn is_claimed(&self) -> bool {
+        self.claimed.get_or_default()
+    }
+}
+
+impl StablePayout {
+    /// Reads the price from the feed, which reverts if it's older than `max_age`.
+    fn price(&self) -> U256 {
+        let feed = self.feed.get().unwrap_or_revert(&self.env());
+        PriceFeedContractRef::new(self.env(), feed)
+            .get_price(self.pair.get_or_default(), self.max_age.get_or_default())
+    }
+
+    /// Converts the dollar amount to motes at `price` dollars per CSPR.
+    fn cspr_value(&self, price: U256) -> U512 {
+        // Dollar amounts and prices have the same decimals, which cancel out
+        self.usd_amount.get_or_default().to_u512() * MOTES_PER_CSPR / price.to_u512()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::price_feed::{self, PriceFeedHostRef};
+    use odra::host::{Deployer, HostEnv, HostRef, NoArgs};
+
+    const PAIR: &str = "CSPR/USD";
+    const MAX_AGE: u64 = 60_000;
+    const CSPR: u64 = MOTES_PER_CSPR;
+
+    /// Deploys a feed reporting $0.05 per CSPR, and a payout of $100 from account 1 to
+    /// account 2, funded with 4,000 CSPR.
+    fn setup(env: &HostEnv) -> (PriceFeedHostRef, StablePayoutHostRef) {
+        let mut feed = PriceFeedHostRef::deploy(env, NoArgs);
+        feed.set_reporter(env.get_account(0), true);
+        feed.submit(PAIR.to_string(), U256::from(5_000_000), 0);
+
+        env.set_caller(env.get_account(1));
+        let mut payout = StablePayoutHostRef::deploy(
+            env,
+            StablePayoutInitArgs {
+                feed: *feed.address(),
+                pair: PAIR.to_string(),
+                max_age: MAX_AGE,
+                beneficiary: env.get_account(2),
+                usd_amount: U256::from(100 * 100_000_000u64),
+            },
+        );
+        payout.with_tokens(U512::from(4_000 * CSPR)).fund();
+        (feed, payout)
+    }
+
+    #[test]
+    fn collateral_follows_the_price() {
+        let env = odra_test::env();
+        let (mut feed, mut payout) = setup(&env);
+        let (payer, beneficiary) = (env.get_account(1), env.get_account(2));
+
+        // $100 is 2,000 CSPR at $0.05, and 3,000 CSPR have to stay locked
+        assert_eq!(payout.payout_value(), U512::from(2_000 * CSPR));
+        assert_eq!(payout.required_collateral(), U512::from(3_000 * CSPR));
+        assert_eq!(
+            payout.try_withdraw(U512::from(1_000 * CSPR + 1)),
+            Err(Error::Undercollateralized.into())
+        );
+        payout.withdraw(U512::from(1_000 * CSPR));
+
+        // When CSPR falls to $0.04, the same dollars take 2,500 CSPR
+        env.advance_block_time(1_000);
+        env.set_caller(env.get_account(0));
+        feed.submit(PAIR.to_string(), U256::from(4_000_000), 1_000);
+        env.set_caller(payer);
+        assert_eq!(
+            payout.try_withdraw(U512::one()),
+            Err(Error::Undercollateralized.into())
+        );
+
+        env.set_caller(beneficiary);
+        let balance = env.balance_of(&beneficiary);
+        payout.claim();
+        assert_eq!(
+            env.balance_of(&beneficiary),
+            balance + U512::from(2_500 * CSPR)
+        );
+        assert_eq!(payout.try_claim(), Err(Error::AlreadyClaimed.into()));
+
+        // Once the payout is claimed, the payer takes the rest
+        env.set_caller(payer);
+        payout.withdraw(U512::from(500 * CSPR));
+        assert_eq!(env.balance_of(payout.address()), U512::zero());
+    }
+
+    #[test]
+    fn stale_price_blocks_the_payout() {
+        let env = odra_test::env();
+        let (_, mut payout) = setup(&env);
+
+        env.advance_block_time(MAX_AGE + 1);
+        env.set_caller(env.get_account(2));
+        assert_eq!(
+            payout.try_claim(),
+            Err(price_feed::Error::StalePrice.into())
+        );
+        env.set_caller(env.get_account(3));
+        assert_eq!(payout.try_claim(), Err(Error::NotBeneficiary.into()));
+    }
+}
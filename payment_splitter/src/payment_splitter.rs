@@ -0,0 +1,231 @@
+use odra::casper_types::U512;
+use odra::prelude::*;
+use odra::{Address, Mapping, Var};
+
+#[odra::odra_error]
+/// Errors that may occur during the contract execution.
+pub enum Error {
+    /// At least one payee is needed.
+    NoPayees = 1,
+    /// Every payee needs exactly one number of shares.
+    LengthMismatch = 2,
+    /// Every payee needs at least one share.
+    ZeroShares = 3,
+    /// Each payee can be listed only once.
+    DuplicatePayee = 4,
+    /// The address isn't one of the payees.
+    NotPayee = 5,
+    /// The payee has already been paid everything they're due.
+    NothingDue = 6,
+}
+
+#[odra::event]
+/// Emitted when the splitter receives CSPR.
+pub struct PaymentReceived {
+    pub from: Address,
+    pub amount: U512,
+}
+
+#[odra::event]
+/// Emitted when a payee is paid.
+pub struct PaymentReleased {
+    pub to: Address,
+    pub amount: U512,
+}
+
+#[odra::module(events = [PaymentReceived, PaymentReleased], errors = Error)]
+/// Splits the CSPR it receives among payees in proportion to their shares.
+pub struct PaymentSplitter {
+    payees: Var<Vec<Address>>,
+    shares: Mapping<Address, u64>,
+    total_shares: Var<u64>,
+    /// CSPR paid out to each payee so far.
+    released: Mapping<Address, U512>,
+    total_released: Var<U512>,
+}
+
+#[odra::module]
+impl PaymentSplitter {
+    /// Initializes the splitter with the payees and their shares, which can't change afterwards.
+    pub fn init(&mut self, payees: Vec<Address>, shares: Vec<u64>) {
+        if payees.is_empty() {
+            self.env().revert(Error::NoPayees);
+        }
+        if payees.len() != shares.len() {
+            self.env().revert(Error::LengthMismatch);
+        }
+        for (payee, shares) in payees.iter().zip(shares) {
+            if shares == 0 {
+                self.env().revert(Error::ZeroShares);
+            }
+            if self.shares.get_or_default(payee) > 0 {
+                self.env().revert(Error::DuplicatePayee);
+            }
+            self.shares.set(payee, shares);
+            self.total_shares.add(shares);
+        }
+        self.payees.set(payees);
+    }
+
+    /**********
+     * TRANSACTIONS
+     **********/
+
+    /// Adds the attached CSPR to the amount being split.
+    #[odra(payable)]
+    pub fn deposit(&mut self) {
+        self.env().emit_event(PaymentReceived {
+            from: self.env().caller(),
+            amount: self.env().attached_value(),
+        });
+    }
+
+    /// Pays the payee everything they're due; anyone can call it on the payee's behalf.
+    pub fn release(&mut self, payee: Address) {
+        let amount = self.releasable(payee);
+        if amount.is_zero() {
+            self.env().revert(Error::NothingDue);
+        }
+        self.released
+            .set(&payee, self.released.get_or_default(&payee) + amount);
+        self.total_released.add(amount);
+        self.env().transfer_tokens(&payee, &amount);
+        self.env().emit_event(PaymentReleased { to: payee, amount });
+    }
+
+    /**********
+     * QUERIES
+     **********/
+
+    /// Returns the CSPR the payee could be paid right now.
+    pub fn releasable(&self, payee: Address) -> U512 {
+        let shares = self.shares.get_or_default(&payee);
+        if shares == 0 {
+            self.env().revert(Error::NotPayee);
+        }
+
+        // Everything ever received, whether it's still here or already paid out
+        let total_received = self.env().self_balance() + self.total_released.get_or_default();
+        total_received * U512::from(shares) / U512::from(self.total_shares.get_or_default())
+            - self.released.get_or_default(&payee)
+    }
+
+    /// Returns the payees.
+    pub fn get_payees(&self) -> Vec<Address> {
+        self.payees.get_or_default()
+    }
+
+    /// Returns the shares of a payee.
+    pub fn shares_of(&self, payee: Address) -> u64 {
+        self.shares.get_or_default(&payee)
+    }
+
+    /// Returns the sum of all shares.
+    pub fn get_total_shares(&self) -> u64 {
+        self.total_shares.get_or_default()
+    }
+
+    /// Returns the CSPR paid out to a payee so far.
+    pub fn released_to(&self, payee: Address) -> U512 {
+        self.released.get_or_default(&payee)
+    }
+
+    /// Returns the CSPR paid out to all payees so far.
+    pub fn get_total_released(&self) -> U512 {
+        self.total_released.get_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use odra::host::{Deployer, HostEnv, HostRef};
+
+    fn setup(env: &HostEnv, shares: Vec<u64>) -> PaymentSplitterHostRef {
+        let payees = (1..=shares.len()).map(|i| env.get_account(i)).collect();
+        PaymentSplitterHostRef::deploy(env, PaymentSplitterInitArgs { payees, shares })
+    }
+
+    #[test]
+    fn split_by_shares() {
+        let env = odra_test::env();
+        let mut splitter = setup(&env, vec![1, 3]);
+        let (alice, bob) = (env.get_account(1), env.get_account(2));
+        splitter.with_tokens(U512::from(1_000)).deposit();
+        assert_eq!(splitter.releasable(alice), U512::from(250));
+        assert_eq!(splitter.releasable(bob), U512::from(750));
+
+        // Anyone can trigger a payout, the CSPR always goes to the payee
+        let balance = env.balance_of(&alice);
+        splitter.release(alice);
+        assert_eq!(env.balance_of(&alice), balance + U512::from(250));
+        assert_eq!(splitter.releasable(alice), U512::zero());
+        assert_eq!(splitter.try_release(alice), Err(Error::NothingDue.into()));
+
+        // Later payments are split the same way, on top of what was paid out
+        splitter.with_tokens(U512::from(400)).deposit();
+        assert_eq!(splitter.releasable(alice), U512::from(100));
+        assert_eq!(splitter.releasable(bob), U512::from(1_050));
+        splitter.release(bob);
+        assert_eq!(splitter.released_to(bob), U512::from(1_050));
+        assert_eq!(splitter.get_total_released(), U512::from(1_300));
+        assert!(env.emitted_event(
+            splitter.address(),
+            &PaymentReleased {
+                to: bob,
+                amount: U512::from(1_050)
+            }
+        ));
+    }
+
+    #[test]
+    fn rounding() {
+        let env = odra_test::env();
+        let mut splitter = setup(&env, vec![1, 1, 1]);
+        let (alice, bob, carol) = (env.get_account(1), env.get_account(2), env.get_account(3));
+
+        // The remainder of a division stays in the contract
+        splitter.with_tokens(U512::from(100)).deposit();
+        for payee in [alice, bob, carol] {
+            assert_eq!(splitter.releasable(payee), U512::from(33));
+        }
+        splitter.release(alice);
+        assert_eq!(env.balance_of(splitter.address()), U512::from(67));
+
+        // ...and is paid out once it adds up to a whole unit per payee
+        splitter.with_tokens(U512::from(2)).deposit();
+        assert_eq!(splitter.releasable(alice), U512::one());
+        assert_eq!(splitter.releasable(bob), U512::from(34));
+        for payee in [alice, bob, carol] {
+            splitter.release(payee);
+        }
+        assert_eq!(env.balance_of(splitter.address()), U512::zero());
+        assert_eq!(splitter.get_total_released(), U512::from(102));
+    }
+
+    #[test]
+    fn invalid_setup() {
+        let env = odra_test::env();
+        let (alice, bob) = (env.get_account(1), env.get_account(2));
+        let deploy = |payees: Vec<Address>, shares: Vec<u64>| {
+            PaymentSplitterHostRef::try_deploy(&env, PaymentSplitterInitArgs { payees, shares })
+                .err()
+        };
+        assert_eq!(deploy(vec![], vec![]), Some(Error::NoPayees.into()));
+        assert_eq!(
+            deploy(vec![alice, bob], vec![1]),
+            Some(Error::LengthMismatch.into())
+        );
+        assert_eq!(
+            deploy(vec![alice, bob], vec![1, 0]),
+            Some(Error::ZeroShares.into())
+        );
+        assert_eq!(
+            deploy(vec![alice, alice], vec![1, 1]),
+            Some(Error::DuplicatePayee.into())
+        );
+
+        let splitter = setup(&env, vec![1]);
+        assert_eq!(splitter.try_releasable(bob), Err(Error::NotPayee.into()));
+    }
+}
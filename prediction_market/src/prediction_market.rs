@@ -0,0 +1,435 @@
+use odra::casper_types::U512;
+use odra::prelude::*;
+use odra::{Address, Mapping, Var};
+
+#[odra::odra_error]
+/// Errors that may occur during the contract execution.
+pub enum Error {
+    /// Markets need a question and a betting period.
+    InvalidMarket = 1,
+    /// There's no market with the given ID.
+    MarketNotFound = 2,
+    /// The market isn't in a state that allows this.
+    InvalidState = 3,
+    /// Bets must be positive.
+    ZeroAmount = 4,
+    /// Only the market's oracle can resolve it.
+    NotOracle = 5,
+    /// The account has nothing to claim from the market.
+    NothingToClaim = 6,
+}
+
+#[odra::odra_type]
+#[derive(Copy)]
+pub enum Outcome {
+    Yes,
+    No,
+}
+
+#[odra::odra_type]
+pub enum MarketState {
+    /// Accepting bets until the deadline.
+    Open,
+    /// Waiting for the oracle to report the outcome.
+    Closed,
+    /// The outcome is known; winners can claim their payouts.
+    Resolved,
+    /// The oracle didn't report in time, or nobody bet on the outcome; everyone can claim a refund.
+    Cancelled,
+}
+
+#[odra::odra_type]
+pub struct Market {
+    pub question: String,
+    /// Account that reports the outcome.
+    pub oracle: Address,
+    /// Block time bets are accepted until.
+    pub deadline: u64,
+    pub yes_pool: U512,
+    pub no_pool: U512,
+    pub outcome: Option<Outcome>,
+    /// Part of the pool the winners haven't claimed.
+    pub unclaimed_pool: U512,
+    /// Part of the winning stakes that hasn't been claimed.
+    pub unclaimed_stakes: U512,
+}
+
+impl Market {
+    fn pool(&self, outcome: Outcome) -> U512 {
+        match outcome {
+            Outcome::Yes => self.yes_pool,
+            Outcome::No => self.no_pool,
+        }
+    }
+}
+
+#[odra::odra_type]
+#[derive(Default)]
+pub struct Position {
+    pub yes: U512,
+    pub no: U512,
+}
+
+impl Position {
+    fn stake(&self, outcome: Outcome) -> U512 {
+        match outcome {
+            Outcome::Yes => self.yes,
+            Outcome::No => self.no,
+        }
+    }
+}
+
+#[odra::event]
+/// Emitted when a market is created.
+pub struct MarketCreated {
+    pub market_id: u32,
+    pub question: String,
+    pub oracle: Address,
+    pub deadline: u64,
+}
+
+#[odra::event]
+/// Emitted when a bet is placed.
+pub struct BetPlaced {
+    pub market_id: u32,
+    pub bettor: Address,
+    pub outcome: Outcome,
+    pub amount: U512,
+}
+
+#[odra::event]
+/// Emitted when the oracle reports the outcome of a market.
+pub struct MarketResolved {
+    pub market_id: u32,
+    pub outcome: Outcome,
+}
+
+#[odra::event]
+/// Emitted when a winner claims a payout, or a bettor a refund.
+pub struct Claimed {
+    pub market_id: u32,
+    pub account: Address,
+    pub amount: U512,
+}
+
+#[odra::module(
+    events = [MarketCreated, BetPlaced, MarketResolved, Claimed],
+    errors = Error
+)]
+/// Binary prediction markets where the winners split the pool.
+pub struct PredictionMarket {
+    /// How long the oracle has to report an outcome after the deadline.
+    resolution_period: Var<u64>,
+    markets: Mapping<u32, Market>,
+    market_count: Var<u32>,
+    positions: Mapping<(u32, Address), Position>,
+}
+
+#[odra::module]
+impl PredictionMarket {
+    /// Initializes the contract with the time oracles have to report outcomes.
+    pub fn init(&mut self, resolution_period: u64) {
+        self.resolution_period.set(resolution_period);
+    }
+
+    /**********
+     * TRANSACTIONS
+     **********/
+
+    /// Creates a market on `question`, taking bets for `duration` and resolved by `oracle`.
+    /// Returns the ID of the market.
+    pub fn create_market(&mut self, question: String, duration: u64, oracle: Address) -> u32 {
+        if question.is_empty() || duration == 0 {
+            self.env().revert(Error::InvalidMarket);
+        }
+
+        let market_id = self.market_count.get_or_default();
+        let deadline = self.env().get_block_time() + duration;
+        self.markets.set(
+            &market_id,
+            Market {
+                question: question.clone(),
+                oracle,
+                deadline,
+                yes_pool: U512::zero(),
+                no_pool: U512::zero(),
+                outcome: None,
+                unclaimed_pool: U512::zero(),
+                unclaimed_stakes: U512::zero(),
+            },
+        );
+        self.market_count.set(market_id + 1);
+        self.env().emit_event(MarketCreated {
+            market_id,
+            question,
+            oracle,
+            deadline,
+        });
+        market_id
+    }
+
+    /// Bets the attached CSPR on `outcome`.
+    #[odra(payable)]
+    pub fn bet(&mut self, market_id: u32, outcome: Outcome) {
+        let mut market = self.market(market_id);
+        self.assert_state(&market, MarketState::Open);
+        let amount = self.env().attached_value();
+        if amount.is_zero() {
+            self.env().revert(Error::ZeroAmount);
+        }
+
+        let bettor = self.env().caller();
+        let mut position = self.positions.get_or_default(&(market_id, bettor));
+        match outcome {
+            Outcome::Yes => {
+                market.yes_pool += amount;
+                position.yes += amount;
+            }
+            Outcome::No => {
+                market.no_pool += amount;
+                position.no += amount;
+            }
+        }
+        self.markets.set(&market_id, market);
+        self.positions.set(&(market_id, bettor), position);
+        self.env().emit_event(BetPlaced {
+            market_id,
+            bettor,
+            outcome,
+            amount,
+        });
+    }
+
+    /// Reports the outcome of a market. Only the oracle can, between the deadline and the end
+    /// of the resolution period.
+    pub fn resolve(&mut self, market_id: u32, outcome: Outcome) {
+        let mut market = self.market(market_id);
+        if self.env().caller() != market.oracle {
+            self.env().revert(Error::NotOracle);
+        }
+        self.assert_state(&market, MarketState::Closed);
+
+        market.outcome = Some(outcome);
+        market.unclaimed_pool = market.yes_pool + market.no_pool;
+        market.unclaimed_stakes = market.pool(outcome);
+        self.markets.set(&market_id, market);
+        self.env().emit_event(MarketResolved { market_id, outcome });
+    }
+
+    /// Pays the caller's winnings from a resolved market, or refunds their bets on a cancelled one.
+    pub fn claim(&mut self, market_id: u32) {
+        let mut market = self.market(market_id);
+        let account = self.env().caller();
+        let position = self.positions.get_or_default(&(market_id, account));
+        let amount = match (self.state_of(&market), market.outcome) {
+            (MarketState::Resolved, Some(outcome)) => {
+                let stake = position.stake(outcome);
+                if stake.is_zero() {
+                    self.env().revert(Error::NothingToClaim);
+                }
+                // The pool and the stakes shrink together, so the last winner gets what's left
+                let amount = market.unclaimed_pool * stake / market.unclaimed_stakes;
+                market.unclaimed_pool -= amount;
+                market.unclaimed_stakes -= stake;
+                self.markets.set(&market_id, market);
+                amount
+            }
+            (MarketState::Cancelled, _) => position.yes + position.no,
+            _ => self.env().revert(Error::InvalidState),
+        };
+        if amount.is_zero() {
+            self.env().revert(Error::NothingToClaim);
+        }
+
+        self.positions
+            .set(&(market_id, account), Position::default());
+        self.env().transfer_tokens(&account, &amount);
+        self.env().emit_event(Claimed {
+            market_id,
+            account,
+            amount,
+        });
+    }
+
+    /**********
+     * QUERIES
+     **********/
+
+    /// Returns a market.
+    pub fn get_market(&self, market_id: u32) -> Option<Market> {
+        self.markets.get(&market_id)
+    }
+
+    /// Returns the state of a market.
+    pub fn get_state(&self, market_id: u32) -> MarketState {
+        self.state_of(&self.market(market_id))
+    }
+
+    /// Returns the bets of an account on a market that haven't been claimed.
+    pub fn position_of(&self, market_id: u32, account: Address) -> Position {
+        self.positions.get_or_default(&(market_id, account))
+    }
+
+    /// Returns the number of markets.
+    pub fn get_market_count(&self) -> u32 {
+        self.market_count.get_or_default()
+    }
+}
+
+impl PredictionMarket {
+    fn market(&self, market_id: u32) -> Market {
+        self.markets
+            .get(&market_id)
+            .unwrap_or_revert_with(&self.env(), Error::MarketNotFound)
+    }
+
+    fn state_of(&self, market: &Market) -> MarketState {
+        let now = self.env().get_block_time();
+        match market.outcome {
+            // Nobody can win if nobody bet on the outcome
+            Some(outcome) if market.pool(outcome).is_zero() => MarketState::Cancelled,
+            Some(_) => MarketState::Resolved,
+            None if now < market.deadline => MarketState::Open,
+            None if now < market.deadline + self.resolution_period.get_or_default() => {
+                MarketState::Closed
+            }
+            None => MarketState::Cancelled,
+        }
+    }
+
+    fn assert_state(&self, market: &Market, state: MarketState) {
+        if self.state_of(market) != state {
+            self.env().revert(Error::InvalidState);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use odra::host::{Deployer, HostEnv, HostRef};
+
+    const DURATION: u64 = 10_000;
+    const RESOLUTION_PERIOD: u64 = 1_000;
+
+    /// Creates a market resolved by account 1, with bets of 300 on YES from account 2,
+    /// 100 on NO from account 3 and 100 on YES from account 4.
+    fn setup(env: &HostEnv) -> (PredictionMarketHostRef, u32) {
+        let mut markets = PredictionMarketHostRef::deploy(
+            env,
+            PredictionMarketInitArgs {
+                resolution_period: RESOLUTION_PERIOD,
+            },
+        );
+        let id = markets.create_market(
+            "Will it rain tomorrow?".to_string(),
+            DURATION,
+            env.get_account(1),
+        );
+        for (account, outcome, amount) in [
+            (2, Outcome::Yes, 300),
+            (3, Outcome::No, 100),
+            (4, Outcome::Yes, 100),
+        ] {
+            env.set_caller(env.get_account(account));
+            markets.with_tokens(U512::from(amount)).bet(id, outcome);
+        }
+        (markets, id)
+    }
+
+    #[test]
+    fn winners_split_the_pool() {
+        let env = odra_test::env();
+        let (mut markets, id) = setup(&env);
+        let oracle = env.get_account(1);
+        let (alice, bob, carol) = (env.get_account(2), env.get_account(3), env.get_account(4));
+
+        env.set_caller(oracle);
+        assert_eq!(
+            markets.try_resolve(id, Outcome::Yes),
+            Err(Error::InvalidState.into())
+        );
+        env.advance_block_time(DURATION);
+        assert_eq!(markets.get_state(id), MarketState::Closed);
+        env.set_caller(alice);
+        assert_eq!(
+            markets.with_tokens(U512::one()).try_bet(id, Outcome::No),
+            Err(Error::InvalidState.into())
+        );
+        assert_eq!(
+            markets.try_resolve(id, Outcome::Yes),
+            Err(Error::NotOracle.into())
+        );
+        env.set_caller(oracle);
+        markets.resolve(id, Outcome::Yes);
+        assert_eq!(markets.get_state(id), MarketState::Resolved);
+
+        // The 500 in the pool go to the YES bettors, 3:1
+        for (account, amount) in [(alice, 375), (carol, 125)] {
+            env.set_caller(account);
+            let balance = env.balance_of(&account);
+            markets.claim(id);
+            assert_eq!(env.balance_of(&account), balance + U512::from(amount));
+            assert_eq!(markets.try_claim(id), Err(Error::NothingToClaim.into()));
+        }
+        env.set_caller(bob);
+        assert_eq!(markets.try_claim(id), Err(Error::NothingToClaim.into()));
+        assert_eq!(env.balance_of(markets.address()), U512::zero());
+    }
+
+    #[test]
+    fn unresolved_market_is_refunded() {
+        let env = odra_test::env();
+        let (mut markets, id) = setup(&env);
+        let alice = env.get_account(2);
+
+        env.set_caller(alice);
+        assert_eq!(markets.try_claim(id), Err(Error::InvalidState.into()));
+
+        // The oracle lets the resolution period pass
+        env.advance_block_time(DURATION + RESOLUTION_PERIOD);
+        assert_eq!(markets.get_state(id), MarketState::Cancelled);
+        env.set_caller(env.get_account(1));
+        assert_eq!(
+            markets.try_resolve(id, Outcome::No),
+            Err(Error::InvalidState.into())
+        );
+
+        for (account, amount) in [(2, 300), (3, 100), (4, 100)] {
+            let account = env.get_account(account);
+            env.set_caller(account);
+            let balance = env.balance_of(&account);
+            markets.claim(id);
+            assert_eq!(env.balance_of(&account), balance + U512::from(amount));
+        }
+    }
+
+    #[test]
+    fn outcome_nobody_bet_on_is_refunded() {
+        let env = odra_test::env();
+        let mut markets = PredictionMarketHostRef::deploy(
+            &env,
+            PredictionMarketInitArgs {
+                resolution_period: RESOLUTION_PERIOD,
+            },
+        );
+        let (oracle, alice) = (env.get_account(1), env.get_account(2));
+        assert_eq!(
+            markets.try_create_market(String::new(), DURATION, oracle),
+            Err(Error::InvalidMarket.into())
+        );
+        let id = markets.create_market("Will it snow?".to_string(), DURATION, oracle);
+        env.set_caller(alice);
+        markets.with_tokens(U512::from(100)).bet(id, Outcome::Yes);
+
+        env.advance_block_time(DURATION);
+        env.set_caller(oracle);
+        markets.resolve(id, Outcome::No);
+        assert_eq!(markets.get_state(id), MarketState::Cancelled);
+
+        env.set_caller(alice);
+        let balance = env.balance_of(&alice);
+        markets.claim(id);
+        assert_eq!(env.balance_of(&alice), balance + U512::from(100));
+    }
+}
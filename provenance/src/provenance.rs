@@ -0,0 +1,321 @@
+use odra::prelude::*;
+use odra::{Address, List, Mapping, Var};
+
+/// Most records a history query returns at once.
+pub const MAX_PAGE_SIZE: u32 = 50;
+
+#[odra::odra_error]
+/// Errors that may occur during the contract execution.
+pub enum Error {
+    /// There's no item with the given ID.
+    ItemNotFound = 1,
+    /// An item with the same data hash is already registered.
+    AlreadyRegistered = 2,
+    /// Only the item's current custodian can hand it over.
+    NotCustodian = 3,
+    /// Items can't be handed over to their current custodian.
+    InvalidRecipient = 4,
+    /// Only the account the item is being handed over to can accept it.
+    NotRecipient = 5,
+}
+
+#[odra::odra_type]
+pub struct Item {
+    /// Hash of the item's off-chain records, e.g. a certificate of origin.
+    pub data_hash: [u8; 32],
+    pub registered_by: Address,
+    pub custodian: Address,
+    /// Account the custodian is handing the item over to.
+    pub pending_custodian: Option<Address>,
+}
+
+#[odra::odra_type]
+pub struct CustodyRecord {
+    pub custodian: Address,
+    /// The previous custodian, or `None` for the registration.
+    pub handed_over_by: Option<Address>,
+    /// Block time the custodian took the item.
+    pub timestamp: u64,
+}
+
+#[odra::event]
+/// Emitted when an item is registered.
+pub struct ItemRegistered {
+    pub item_id: u32,
+    pub data_hash: [u8; 32],
+    pub registered_by: Address,
+}
+
+#[odra::event]
+/// Emitted when a custodian starts handing an item over.
+pub struct HandoverStarted {
+    pub item_id: u32,
+    pub from: Address,
+    pub to: Address,
+}
+
+#[odra::event]
+/// Emitted when an item changes custodian.
+pub struct CustodyTransferred {
+    pub item_id: u32,
+    pub from: Address,
+    pub to: Address,
+}
+
+#[odra::module]
+/// Custody records of a single item, oldest first.
+pub struct CustodyHistory {
+    records: List<CustodyRecord>,
+}
+
+impl CustodyHistory {
+    fn push(&mut self, record: CustodyRecord) {
+        self.records.push(record);
+    }
+
+    fn len(&self) -> u32 {
+        self.records.len()
+    }
+
+    fn get(&self, index: u32) -> Option<CustodyRecord> {
+        self.records.get(index)
+    }
+}
+
+#[odra::module(events = [ItemRegistered, HandoverStarted, CustodyTransferred], errors = Error)]
+/// Items with an append-only history of their custodians.
+pub struct Provenance {
+    items: Mapping<u32, Item>,
+    item_count: Var<u32>,
+    /// Item registered with each data hash.
+    item_ids: Mapping<[u8; 32], u32>,
+    histories: Mapping<u32, CustodyHistory>,
+}
+
+#[odra::module]
+impl Provenance {
+    /**********
+     * TRANSACTIONS
+     **********/
+
+    /// Registers an item described by the off-chain records hashed to `data_hash`. The caller
+    /// becomes its first custodian. Returns the ID of the item.
+    pub fn register(&mut self, data_hash: [u8; 32]) -> u32 {
+        if self.item_ids.get(&data_hash).is_some() {
+            self.env().revert(Error::AlreadyRegistered);
+        }
+
+        let registered_by = self.env().caller();
+        let item_id = self.item_count.get_or_default();
+        self.items.set(
+            &item_id,
+            Item {
+                data_hash,
+                registered_by,
+                custodian: registered_by,
+                pending_custodian: None,
+            },
+        );
+        self.item_count.set(item_id + 1);
+        self.item_ids.set(&data_hash, item_id);
+        self.histories.module(&item_id).push(CustodyRecord {
+            custodian: registered_by,
+            handed_over_by: None,
+            timestamp: self.env().get_block_time(),
+        });
+        self.env().emit_event(ItemRegistered {
+            item_id,
+            data_hash,
+            registered_by,
+        });
+        item_id
+    }
+
+    /// Starts handing an item over to `to`, who has to accept it. Calling it again replaces
+    /// the recipient. Only the current custodian can call it.
+    pub fn hand_over(&mut self, item_id: u32, to: Address) {
+        let mut item = self.item(item_id);
+        let from = self.env().caller();
+        if from != item.custodian {
+            self.env().revert(Error::NotCustodian);
+        }
+        if to == from {
+            self.env().revert(Error::InvalidRecipient);
+        }
+
+        item.pending_custodian = Some(to);
+        self.items.set(&item_id, item);
+        self.env().emit_event(HandoverStarted { item_id, from, to });
+    }
+
+    /// Takes custody of an item being handed over to the caller, and records it in the
+    /// item's history.
+    pub fn accept(&mut self, item_id: u32) {
+        let mut item = self.item(item_id);
+        let to = self.env().caller();
+        if item.pending_custodian != Some(to) {
+            self.env().revert(Error::NotRecipient);
+        }
+
+        let from = item.custodian;
+        item.custodian = to;
+        item.pending_custodian = None;
+        self.items.set(&item_id, item);
+        self.histories.module(&item_id).push(CustodyRecord {
+            custodian: to,
+            handed_over_by: Some(from),
+            timestamp: self.env().get_block_time(),
+        });
+        self.env()
+            .emit_event(CustodyTransferred { item_id, from, to });
+    }
+
+    /**********
+     * QUERIES
+     **********/
+
+    /// Returns an item.
+    pub fn get_item(&self, item_id: u32) -> Option<Item> {
+        self.items.get(&item_id)
+    }
+
+    /// Returns the number of registered items.
+    pub fn get_item_count(&self) -> u32 {
+        self.item_count.get_or_default()
+    }
+
+    /// Returns the item registered with `data_hash`.
+    pub fn find_item(&self, data_hash: [u8; 32]) -> Option<u32> {
+        self.item_ids.get(&data_hash)
+    }
+
+    /// Returns whether `data_hash` matches the records an item was registered with.
+    pub fn verify(&self, item_id: u32, data_hash: [u8; 32]) -> bool {
+        matches!(self.items.get(&item_id), Some(item) if item.data_hash == data_hash)
+    }
+
+    /// Returns the number of records in an item's history, including its registration.
+    pub fn history_length(&self, item_id: u32) -> u32 {
+        self.histories.module(&item_id).len()
+    }
+
+    /// Returns up to `limit` records of an item's history, starting at `offset`. At most
+    /// `MAX_PAGE_SIZE` records are returned at once.
+    pub fn history(&self, item_id: u32, offset: u32, limit: u32) -> Vec<CustodyRecord> {
+        let history = self.histories.module(&item_id);
+        let end = history
+            .len()
+            .min(offset.saturating_add(limit.min(MAX_PAGE_SIZE)));
+        (offset..end)
+            .filter_map(|index| history.get(index))
+            .collect()
+    }
+}
+
+impl Provenance {
+    fn item(&self, item_id: u32) -> Item {
+        self.items
+            .get(&item_id)
+            .unwrap_or_revert_with(&self.env(), Error::ItemNotFound)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use odra::casper_types::crypto::blake2b;
+    use odra::host::{Deployer, HostRef, NoArgs};
+
+    #[test]
+    fn chain_of_custody() {
+        let env = odra_test::env();
+        let mut provenance = ProvenanceHostRef::deploy(&env, NoArgs);
+        let (farm, shipper, roaster) = (env.get_account(1), env.get_account(2), env.get_account(3));
+        let data_hash = blake2b(b"Lot 42: 60 kg of green coffee, harvested in March");
+
+        env.set_caller(farm);
+        let id = provenance.register(data_hash);
+        assert_eq!(
+            provenance.try_register(data_hash),
+            Err(Error::AlreadyRegistered.into())
+        );
+        assert_eq!(provenance.find_item(data_hash), Some(id));
+        assert!(provenance.verify(id, data_hash));
+        assert!(!provenance.verify(id, blake2b(b"Lot 42: 60 kg of decaf")));
+
+        // A handover needs both the custodian and the recipient
+        assert_eq!(
+            provenance.try_hand_over(id, farm),
+            Err(Error::InvalidRecipient.into())
+        );
+        provenance.hand_over(id, shipper);
+        env.set_caller(roaster);
+        assert_eq!(provenance.try_accept(id), Err(Error::NotRecipient.into()));
+        assert_eq!(
+            provenance.try_hand_over(id, roaster),
+            Err(Error::NotCustodian.into())
+        );
+        env.advance_block_time(1_000);
+        env.set_caller(shipper);
+        provenance.accept(id);
+
+        provenance.hand_over(id, roaster);
+        env.advance_block_time(2_000);
+        env.set_caller(roaster);
+        provenance.accept(id);
+        assert_eq!(provenance.get_item(id).unwrap().custodian, roaster);
+        assert_eq!(provenance.try_accept(id), Err(Error::NotRecipient.into()));
+
+        assert_eq!(
+            provenance.history(id, 0, 10),
+            vec![
+                CustodyRecord {
+                    custodian: farm,
+                    handed_over_by: None,
+                    timestamp: 0,
+                },
+                CustodyRecord {
+                    custodian: shipper,
+                    handed_over_by: Some(farm),
+                    timestamp: 1_000,
+                },
+                CustodyRecord {
+                    custodian: roaster,
+                    handed_over_by: Some(shipper),
+                    timestamp: 3_000,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn history_pages() {
+        let env = odra_test::env();
+        let mut provenance = ProvenanceHostRef::deploy(&env, NoArgs);
+        let id = provenance.register(blake2b(b"A long journey"));
+
+        // The item goes back and forth between accounts 0 and 1
+        for i in 0..(MAX_PAGE_SIZE + 9) as usize {
+            let (from, to) = (env.get_account(i % 2), env.get_account((i + 1) % 2));
+            env.set_caller(from);
+            provenance.hand_over(id, to);
+            env.set_caller(to);
+            provenance.accept(id);
+        }
+        assert_eq!(provenance.history_length(id), MAX_PAGE_SIZE + 10);
+
+        let page = provenance.history(id, 5, 3);
+        assert_eq!(page.len(), 3);
+        assert_eq!(page[0].custodian, env.get_account(1));
+        assert_eq!(page[1].handed_over_by, Some(env.get_account(1)));
+
+        // Pages are capped, and end with the history
+        assert_eq!(
+            provenance.history(id, 0, u32::MAX).len(),
+            MAX_PAGE_SIZE as usize
+        );
+        assert_eq!(provenance.history(id, MAX_PAGE_SIZE, 50).len(), 10);
+        assert!(provenance.history(id, MAX_PAGE_SIZE + 10, 50).is_empty());
+        assert!(provenance.history(id + 1, 0, 50).is_empty());
+    }
+}
@@ -0,0 +1,504 @@
+use odra::casper_types::U512;
+use odra::prelude::*;
+use odra::{Address, Mapping, Var};
+
+/// Most projects a round accepts, so that finalizing it fits in a single call.
+pub const MAX_PROJECTS: u32 = 50;
+
+#[odra::odra_error]
+/// Errors that may occur during the contract execution.
+pub enum Error {
+    /// Contributions have to start before the round ends.
+    InvalidSchedule = 1,
+    /// Projects can only register before contributions start.
+    RegistrationClosed = 2,
+    /// The round already has `MAX_PROJECTS` projects.
+    TooManyProjects = 3,
+    /// There's no project with the given ID.
+    ProjectNotFound = 4,
+    /// Contributions are only accepted between the start and the end of the round.
+    ContributionsClosed = 5,
+    /// Contributions and sponsorships need some CSPR attached.
+    ZeroAmount = 6,
+    /// Project owners can't contribute to their own project.
+    OwnProject = 7,
+    /// The round hasn't ended yet.
+    RoundNotEnded = 8,
+    /// The round has already been finalized.
+    AlreadyFinalized = 9,
+    /// The round hasn't been finalized yet.
+    NotFinalized = 10,
+    /// Only the project's owner can claim its funds.
+    NotProjectOwner = 11,
+    /// The project's funds have already been claimed.
+    AlreadyClaimed = 12,
+    /// Only the round's admin can withdraw what's left of the matching pool.
+    NotAdmin = 13,
+    /// Nothing is left of the matching pool.
+    NothingToWithdraw = 14,
+}
+
+#[odra::odra_type]
+pub struct Project {
+    pub owner: Address,
+    pub name: String,
+    /// CSPR contributed by donors.
+    pub contributions: U512,
+    /// Sum of the square roots of each donor's total contribution.
+    pub sqrt_sum: U512,
+    /// Share of the matching pool, set when the round is finalized.
+    pub matched: U512,
+    pub claimed: bool,
+}
+
+#[odra::event]
+/// Emitted when a project registers for the round.
+pub struct ProjectRegistered {
+    pub project_id: u32,
+    pub owner: Address,
+    pub name: String,
+}
+
+#[odra::event]
+/// Emitted when a donor contributes to a project.
+pub struct Contributed {
+    pub project_id: u32,
+    pub donor: Address,
+    pub amount: U512,
+}
+
+#[odra::event]
+/// Emitted when a sponsor adds CSPR to the matching pool.
+pub struct PoolFunded {
+    pub sponsor: Address,
+    pub amount: U512,
+}
+
+#[odra::event]
+/// Emitted when a project's owner claims its contributions and match.
+pub struct FundsClaimed {
+    pub project_id: u32,
+    pub amount: U512,
+}
+
+#[odra::module(
+    events = [ProjectRegistered, Contributed, PoolFunded, FundsClaimed],
+    errors = Error
+)]
+/// A funding round whose matching pool is divided among projects by quadratic funding.
+pub struct QfRound {
+    admin: Var<Address>,
+    /// Block time contributions open at, which also closes the registration.
+    contributions_start: Var<u64>,
+    /// Block time contributions close at.
+    ends_at: Var<u64>,
+    projects: Mapping<u32, Project>,
+    project_count: Var<u32>,
+    /// Total contributed by each donor to each project.
+    contributions: Mapping<(u32, Address), U512>,
+    matching_pool: Var<U512>,
+    finalized: Var<bool>,
+    /// Part of the matching pool that isn't matched to any project.
+    leftover: Var<U512>,
+}
+
+#[odra::module]
+impl QfRound {
+    /// Initializes the round; the deployer becomes its admin. Projects register until
+    /// `contributions_start`, and donors contribute from then until `ends_at`.
+    pub fn init(&mut self, contributions_start: u64, ends_at: u64) {
+        if contributions_start >= ends_at {
+            self.env().revert(Error::InvalidSchedule);
+        }
+        self.admin.set(self.env().caller());
+        self.contributions_start.set(contributions_start);
+        self.ends_at.set(ends_at);
+    }
+
+    /**********
+     * TRANSACTIONS
+     **********/
+
+    /// Registers a project owned by the caller. Returns the ID of the project.
+    pub fn register_project(&mut self, name: String) -> u32 {
+        if self.env().get_block_time() >= self.contributions_start.get_or_default() {
+            self.env().revert(Error::RegistrationClosed);
+        }
+        let project_id = self.project_count.get_or_default();
+        if project_id >= MAX_PROJECTS {
+            self.env().revert(Error::TooManyProjects);
+        }
+
+        let owner = self.env().caller();
+        self.projects.set(
+            &project_id,
+            Project {
+                owner,
+                name: name.clone(),
+                contributions: U512::zero(),
+                sqrt_sum: U512::zero(),
+                matched: U512::zero(),
+                claimed: false,
+            },
+        );
+        self.project_count.set(project_id + 1);
+        self.env().emit_event(ProjectRegistered {
+            project_id,
+            owner,
+            name,
+        });
+        project_id
+    }
+
+    /// Adds the attached CSPR to the matching pool. Anyone can sponsor the round until it's
+    /// finalized.
+    #[odra(payable)]
+    pub fn fund_pool(&mut self) {
+        if self.finalized.get_or_default() {
+            self.env().revert(Error::AlreadyFinalized);
+        }
+        let amount = self.env().attached_value();
+        if amount.is_zero() {
+            self.env().revert(Error::ZeroAmount);
+        }
+        self.matching_pool
+            .set(self.matching_pool.get_or_default() + amount);
+        self.env().emit_event(PoolFunded {
+            sponsor: self.env().caller(),
+            amount,
+        });
+    }
+
+    /// Contributes the attached CSPR to a project, while the round is open.
+    #[odra(payable)]
+    pub fn contribute(&mut self, project_id: u32) {
+        let now = self.env().get_block_time();
+        if now < self.contributions_start.get_or_default() || now >= self.ends_at.get_or_default() {
+            self.env().revert(Error::ContributionsClosed);
+        }
+        let mut project = self.project(project_id);
+        let donor = self.env().caller();
+        if donor == project.owner {
+            self.env().revert(Error::OwnProject);
+        }
+        let amount = self.env().attached_value();
+        if amount.is_zero() {
+            self.env().revert(Error::ZeroAmount);
+        }
+
+        // A donor's contributions to a project count as one, so the square root is taken of
+        // their total
+        let key = (project_id, donor);
+        let previous = self.contributions.get_or_default(&key);
+        let total = previous + amount;
+        project.sqrt_sum = project.sqrt_sum - previous.integer_sqrt() + total.integer_sqrt();
+        project.contributions += amount;
+        self.projects.set(&project_id, project);
+        self.contributions.set(&key, total);
+        self.env().emit_event(Contributed {
+            project_id,
+            donor,
+            amount,
+        });
+    }
+
+    /// Divides the matching pool among the projects, once the round has ended. Anyone can
+    /// call it.
+    pub fn finalize(&mut self) {
+        if self.env().get_block_time() < self.ends_at.get_or_default() {
+            self.env().revert(Error::RoundNotEnded);
+        }
+        if self.finalized.get_or_default() {
+            self.env().revert(Error::AlreadyFinalized);
+        }
+
+        let pool = self.matching_pool.get_or_default();
+        let count = self.project_count.get_or_default();
+        let ideal: Vec<U512> = (0..count)
+            .map(|project_id| self.ideal_match(&self.project(project_id)))
+            .collect();
+        let total_ideal = ideal.iter().fold(U512::zero(), |sum, value| sum + value);
+
+        let mut leftover = pool;
+        if !total_ideal.is_zero() {
+            for (project_id, ideal) in (0..count).zip(ideal) {
+                let mut project = self.project(project_id);
+                project.matched = pool * ideal / total_ideal;
+                leftover -= project.matched;
+                self.projects.set(&project_id, project);
+            }
+        }
+        self.leftover.set(leftover);
+        self.finalized.set(true);
+    }
+
+    /// Sends a project's contributions and match to its owner, once the round is finalized.
+    pub fn claim(&mut self, project_id: u32) {
+        if !self.finalized.get_or_default() {
+            self.env().revert(Error::NotFinalized);
+        }
+        let mut project = self.project(project_id);
+        if self.env().caller() != project.owner {
+            self.env().revert(Error::NotProjectOwner);
+        }
+        if project.claimed {
+            self.env().revert(Error::AlreadyClaimed);
+        }
+
+        let amount = project.contributions + project.matched;
+        project.claimed = true;
+        let owner = project.owner;
+        self.projects.set(&project_id, project);
+        if !amount.is_zero() {
+            self.env().transfer_tokens(&owner, &amount);
+        }
+        self.env().emit_event(FundsClaimed { project_id, amount });
+    }
+
+    /// Sends the part of the matching pool no project was matched with to the admin, e.g. to
+    /// return it to the sponsors.
+    pub fn withdraw_leftover(&mut self) {
+        let admin = self.env().caller();
+        if Some(admin) != self.admin.get() {
+            self.env().revert(Error::NotAdmin);
+        }
+        if !self.finalized.get_or_default() {
+            self.env().revert(Error::NotFinalized);
+        }
+        let amount = self.leftover.get_or_default();
+        if amount.is_zero() {
+            self.env().revert(Error::NothingToWithdraw);
+        }
+        self.leftover.set(U512::zero());
+        self.env().transfer_tokens(&admin, &amount);
+    }
+
+    /**********
+     * QUERIES
+     **********/
+
+    /// Returns a project.
+    pub fn get_project(&self, project_id: u32) -> Option<Project> {
+        self.projects.get(&project_id)
+    }
+
+    /// Returns the number of registered projects.
+    pub fn get_project_count(&self) -> u32 {
+        self.project_count.get_or_default()
+    }
+
+    /// Returns the total a donor contributed to a project.
+    pub fn contribution_of(&self, project_id: u32, donor: Address) -> U512 {
+        self.contributions.get_or_default(&(project_id, donor))
+    }
+
+    /// Returns the CSPR in the matching pool.
+    pub fn get_matching_pool(&self) -> U512 {
+        self.matching_pool.get_or_default()
+    }
+
+    /// Returns the match a project would get with an unlimited matching pool: the square of
+    /// the sum of the square roots of its contributions, minus the contributions themselves.
+    pub fn ideal_match_of(&self, project_id: u32) -> U512 {
+        self.ideal_match(&self.project(project_id))
+    }
+
+    /// Returns whether the round has been finalized.
+    pub fn is_finalized(&self) -> bool {
+        self.finalized.get_or_default()
+    }
+}
+
+impl QfRound {
+    fn project(&self, project_id: u32) -> Project {
+        self.projects
+            .get(&project_id)
+            .unwrap_or_revert_with(&self.env(), Error::ProjectNotFound)
+    }
+
+    fn ideal_match(&self, project: &Project) -> U512 {
+        // Square roots are rounded down, so the square can fall short of the contributions
+        (project.sqrt_sum * project.sqrt_sum).saturating_sub(project.contributions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use odra::host::{Deployer, HostEnv, HostRef};
+
+    const START: u64 = 1_000;
+    const END: u64 = 2_000;
+
+    /// Deploys a round administered by account 0, with a project of account 1 and one of
+    /// account 2, and opens the contributions.
+    fn setup(env: &HostEnv) -> QfRoundHostRef {
+        let mut round = QfRoundHostRef::deploy(
+            env,
+            QfRoundInitArgs {
+                contributions_start: START,
+                ends_at: END,
+            },
+        );
+        for i in 1..=2 {
+            env.set_caller(env.get_account(i));
+            round.register_project(format!("Project {}", i));
+        }
+        env.set_caller(env.get_account(0));
+        env.advance_block_time(START);
+        round
+    }
+
+    fn contribute(
+        env: &HostEnv,
+        round: &mut QfRoundHostRef,
+        donor: usize,
+        project_id: u32,
+        amount: u64,
+    ) {
+        env.set_caller(env.get_account(donor));
+        round.with_tokens(U512::from(amount)).contribute(project_id);
+    }
+
+    #[test]
+    fn many_small_donors_beat_a_few_large_ones() {
+        let env = odra_test::env();
+        let mut round = setup(&env);
+        round.with_tokens(U512::from(1_001)).fund_pool();
+
+        // Four donors give project 0 a hundred each: (4 * 10)^2 - 400 = 1200
+        for donor in 3..=6 {
+            contribute(&env, &mut round, donor, 0, 100);
+        }
+        // Two donors give project 1 more in total: (10 + 20)^2 - 500 = 400
+        contribute(&env, &mut round, 3, 1, 100);
+        contribute(&env, &mut round, 4, 1, 400);
+        assert_eq!(round.ideal_match_of(0), U512::from(1_200));
+        assert_eq!(round.ideal_match_of(1), U512::from(400));
+
+        assert_eq!(round.try_finalize(), Err(Error::RoundNotEnded.into()));
+        env.advance_block_time(END - START);
+        assert_eq!(
+            round.try_contribute(0),
+            Err(Error::ContributionsClosed.into())
+        );
+        round.finalize();
+        assert_eq!(round.try_finalize(), Err(Error::AlreadyFinalized.into()));
+        assert_eq!(round.get_project(0).unwrap().matched, U512::from(750));
+        assert_eq!(round.get_project(1).unwrap().matched, U512::from(250));
+
+        // Owners get the contributions and the match
+        let owner = env.get_account(2);
+        let balance = env.balance_of(&owner);
+        assert_eq!(round.try_claim(1), Err(Error::NotProjectOwner.into()));
+        env.set_caller(owner);
+        round.claim(1);
+        assert_eq!(env.balance_of(&owner), balance + U512::from(750));
+        assert_eq!(round.try_claim(1), Err(Error::AlreadyClaimed.into()));
+
+        // The rounding leftover goes back through the admin
+        assert_eq!(round.try_withdraw_leftover(), Err(Error::NotAdmin.into()));
+        env.set_caller(env.get_account(0));
+        round.withdraw_leftover();
+        assert_eq!(
+            round.try_withdraw_leftover(),
+            Err(Error::NothingToWithdraw.into())
+        );
+        env.set_caller(env.get_account(1));
+        round.claim(0);
+        assert_eq!(env.balance_of(round.address()), U512::zero());
+    }
+
+    #[test]
+    fn contributions_of_a_donor_count_once() {
+        let env = odra_test::env();
+        let mut round = setup(&env);
+        round.with_tokens(U512::from(1_000)).fund_pool();
+
+        // Splitting a contribution doesn't make it count as several donors
+        for _ in 0..4 {
+            contribute(&env, &mut round, 3, 0, 100);
+        }
+        assert_eq!(
+            round.contribution_of(0, env.get_account(3)),
+            U512::from(400)
+        );
+        assert_eq!(round.ideal_match_of(0), U512::zero());
+        contribute(&env, &mut round, 4, 1, 100);
+        contribute(&env, &mut round, 5, 1, 100);
+        assert_eq!(round.ideal_match_of(1), U512::from(200));
+
+        // Owners can't back their own project
+        env.set_caller(env.get_account(1));
+        assert_eq!(
+            round.with_tokens(U512::one()).try_contribute(0),
+            Err(Error::OwnProject.into())
+        );
+
+        env.advance_block_time(END - START);
+        round.finalize();
+        assert_eq!(round.get_project(0).unwrap().matched, U512::zero());
+        assert_eq!(round.get_project(1).unwrap().matched, U512::from(1_000));
+    }
+
+    #[test]
+    fn round_phases() {
+        let env = odra_test::env();
+        assert_eq!(
+            QfRoundHostRef::try_deploy(
+                &env,
+                QfRoundInitArgs {
+                    contributions_start: END,
+                    ends_at: END,
+                },
+            )
+            .err(),
+            Some(Error::InvalidSchedule.into())
+        );
+
+        let mut round = QfRoundHostRef::deploy(
+            &env,
+            QfRoundInitArgs {
+                contributions_start: START,
+                ends_at: END,
+            },
+        );
+        assert_eq!(
+            round.with_tokens(U512::one()).try_contribute(0),
+            Err(Error::ContributionsClosed.into())
+        );
+        for i in 0..MAX_PROJECTS {
+            round.register_project(format!("Project {}", i));
+        }
+        assert_eq!(
+            round.try_register_project("One too many".to_string()),
+            Err(Error::TooManyProjects.into())
+        );
+
+        env.advance_block_time(START);
+        assert_eq!(
+            round.try_register_project("Too late".to_string()),
+            Err(Error::RegistrationClosed.into())
+        );
+        assert_eq!(
+            round.with_tokens(U512::one()).try_contribute(MAX_PROJECTS),
+            Err(Error::ProjectNotFound.into())
+        );
+        assert_eq!(round.try_claim(0), Err(Error::NotFinalized.into()));
+
+        // Without any matches, the whole pool is left over
+        round.with_tokens(U512::from(500)).fund_pool();
+        env.advance_block_time(END - START);
+        round.finalize();
+        assert_eq!(
+            round.with_tokens(U512::one()).try_fund_pool(),
+            Err(Error::AlreadyFinalized.into())
+        );
+        let balance = env.balance_of(&env.get_account(0));
+        round.withdraw_leftover();
+        assert_eq!(
+            env.balance_of(&env.get_account(0)),
+            balance + U512::from(500)
+        );
+    }
+}
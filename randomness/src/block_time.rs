@@ -0,0 +1,73 @@
+use crate::number_in_range;
+use odra::prelude::*;
+use odra::Var;
+
+#[odra::odra_error]
+/// Errors that may occur during the contract execution.
+pub enum Error {
+    /// There's no number in an empty range.
+    EmptyRange = 1,
+}
+
+#[odra::event]
+/// Emitted when a number is drawn.
+pub struct Drawn {
+    pub number: u32,
+    pub upper_bound: u32,
+}
+
+#[odra::module(events = [Drawn], errors = Error)]
+/// Draws numbers by hashing the block time. DO NOT USE for anything worth cheating: whoever
+/// calls `draw` knows the block time, and can compute the number before calling.
+pub struct BlockTimeRandomness {
+    /// Number of draws so far, so draws in the same block differ.
+    draws: Var<u32>,
+}
+
+#[odra::module]
+impl BlockTimeRandomness {
+    /// Draws a number in `0..upper_bound`.
+    pub fn draw(&mut self, upper_bound: u32) -> u32 {
+        if upper_bound == 0 {
+            self.env().revert(Error::EmptyRange);
+        }
+        let draws = self.draws.get_or_default();
+        let mut preimage = self.env().get_block_time().to_le_bytes().to_vec();
+        preimage.extend(draws.to_le_bytes());
+        let number = number_in_range(self.env().hash(preimage), upper_bound);
+
+        self.draws.set(draws + 1);
+        self.env().emit_event(Drawn {
+            number,
+            upper_bound,
+        });
+        number
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use odra::casper_types::crypto::blake2b;
+    use odra::host::{Deployer, HostRef, NoArgs};
+
+    #[test]
+    fn draws_are_predictable() {
+        let env = odra_test::env();
+        let mut randomness = BlockTimeRandomnessHostRef::deploy(&env, NoArgs);
+        assert_eq!(randomness.try_draw(0), Err(Error::EmptyRange.into()));
+
+        // Anyone can compute the next number from public data
+        let mut preimage = env.block_time().to_le_bytes().to_vec();
+        preimage.extend(0u32.to_le_bytes());
+        let predicted = number_in_range(blake2b(preimage), 100);
+        assert_eq!(randomness.draw(100), predicted);
+        assert!(env.emitted_event(
+            randomness.address(),
+            &Drawn {
+                number: predicted,
+                upper_bound: 100,
+            }
+        ));
+    }
+}
@@ -0,0 +1,247 @@
+use crate::number_in_range;
+use odra::casper_types::bytesrepr::ToBytes;
+use odra::prelude::*;
+use odra::{Address, Mapping, Var};
+
+#[odra::odra_error]
+/// Errors that may occur during the contract execution.
+pub enum Error {
+    /// There's no number in an empty range.
+    EmptyRange = 1,
+    /// Commitments are no longer accepted.
+    CommitPhaseOver = 2,
+    /// Each participant commits once.
+    AlreadyCommitted = 3,
+    /// Secrets are only accepted between the two deadlines.
+    RevealPhaseNotActive = 4,
+    /// The caller didn't commit.
+    NoCommitment = 5,
+    /// The caller has already revealed their secret.
+    AlreadyRevealed = 6,
+    /// The secret doesn't match the caller's commitment.
+    InvalidSecret = 7,
+    /// Numbers can only be drawn once the reveal phase is over.
+    RevealPhaseNotOver = 8,
+    /// Nobody revealed a secret.
+    NoReveals = 9,
+}
+
+#[odra::event]
+/// Emitted when a number is drawn.
+pub struct Drawn {
+    pub number: u32,
+    pub upper_bound: u32,
+}
+
+#[odra::module(events = [Drawn], errors = Error)]
+/// Draws numbers from secrets the participants commit to, and then reveal. As long as one
+/// participant keeps their secret until the reveal phase, nobody can predict the numbers.
+pub struct CommitReveal {
+    commit_deadline: Var<u64>,
+    reveal_deadline: Var<u64>,
+    /// Hash of each participant's address and secret.
+    commitments: Mapping<Address, [u8; 32]>,
+    revealed: Mapping<Address, bool>,
+    /// XOR of the revealed secrets.
+    seed: Var<[u8; 32]>,
+    reveal_count: Var<u32>,
+    /// Number of draws so far, so each draw gives a different number.
+    draws: Var<u32>,
+}
+
+#[odra::module]
+impl CommitReveal {
+    /// Opens the commit phase for `commit_period`, followed by the reveal phase for
+    /// `reveal_period`.
+    pub fn init(&mut self, commit_period: u64, reveal_period: u64) {
+        let commit_deadline = self.env().get_block_time() + commit_period;
+        self.commit_deadline.set(commit_deadline);
+        self.reveal_deadline.set(commit_deadline + reveal_period);
+    }
+
+    /**********
+     * TRANSACTIONS
+     **********/
+
+    /// Commits to a secret. `commitment` is the hash of the caller's address followed by the
+    /// secret.
+    pub fn commit(&mut self, commitment: [u8; 32]) {
+        if self.env().get_block_time() > self.commit_deadline.get_or_default() {
+            self.env().revert(Error::CommitPhaseOver);
+        }
+        let participant = self.env().caller();
+        if self.commitments.get(&participant).is_some() {
+            self.env().revert(Error::AlreadyCommitted);
+        }
+        self.commitments.set(&participant, commitment);
+    }
+
+    /// Reveals the secret the caller committed to, mixing it into the seed.
+    pub fn reveal(&mut self, secret: [u8; 32]) {
+        let now = self.env().get_block_time();
+        if now <= self.commit_deadline.get_or_default()
+            || now > self.reveal_deadline.get_or_default()
+        {
+            self.env().revert(Error::RevealPhaseNotActive);
+        }
+        let participant = self.env().caller();
+        let commitment = self
+            .commitments
+            .get(&participant)
+            .unwrap_or_revert_with(&self.env(), Error::NoCommitment);
+        if self.revealed.get_or_default(&participant) {
+            self.env().revert(Error::AlreadyRevealed);
+        }
+        if self.commitment(&participant, &secret) != commitment {
+            self.env().revert(Error::InvalidSecret);
+        }
+
+        // XOR gives the same seed whatever the order of the reveals
+        let mut seed = self.seed.get_or_default();
+        for (byte, secret_byte) in seed.iter_mut().zip(secret) {
+            *byte ^= secret_byte;
+        }
+        self.seed.set(seed);
+        self.revealed.set(&participant, true);
+        self.reveal_count.set(self.reveal_count() + 1);
+    }
+
+    /// Draws a number in `0..upper_bound` from the revealed secrets.
+    pub fn draw(&mut self, upper_bound: u32) -> u32 {
+        if upper_bound == 0 {
+            self.env().revert(Error::EmptyRange);
+        }
+        if self.env().get_block_time() <= self.reveal_deadline.get_or_default() {
+            self.env().revert(Error::RevealPhaseNotOver);
+        }
+        if self.reveal_count() == 0 {
+            self.env().revert(Error::NoReveals);
+        }
+        let draws = self.draws.get_or_default();
+        let mut preimage = self.seed.get_or_default().to_vec();
+        preimage.extend(draws.to_le_bytes());
+        let number = number_in_range(self.env().hash(preimage), upper_bound);
+
+        self.draws.set(draws + 1);
+        self.env().emit_event(Drawn {
+            number,
+            upper_bound,
+        });
+        number
+    }
+
+    /**********
+     * QUERIES
+     **********/
+
+    pub fn reveal_count(&self) -> u32 {
+        self.reveal_count.get_or_default()
+    }
+}
+
+impl CommitReveal {
+    fn commitment(&self, participant: &Address, secret: &[u8; 32]) -> [u8; 32] {
+        // The address is part of the hash, so nobody can copy another participant's commitment
+        let mut preimage = participant.to_bytes().unwrap_or_revert(&self.env());
+        preimage.extend(secret);
+        self.env().hash(preimage)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use odra::casper_types::crypto::blake2b;
+    use odra::host::{Deployer, HostRef};
+
+    const COMMIT_PERIOD: u64 = 1_000;
+    const REVEAL_PERIOD: u64 = 1_000;
+
+    fn commitment(participant: Address, secret: [u8; 32]) -> [u8; 32] {
+        let mut preimage = participant.to_bytes().unwrap();
+        preimage.extend(secret);
+        blake2b(preimage)
+    }
+
+    #[test]
+    fn revealed_secrets_make_the_seed() {
+        let env = odra_test::env();
+        let (alice, bob, carol) = (env.get_account(1), env.get_account(2), env.get_account(3));
+        let (alice_secret, bob_secret) = ([1u8; 32], [2u8; 32]);
+        let mut randomness = CommitRevealHostRef::deploy(
+            &env,
+            CommitRevealInitArgs {
+                commit_period: COMMIT_PERIOD,
+                reveal_period: REVEAL_PERIOD,
+            },
+        );
+
+        env.set_caller(alice);
+        randomness.commit(commitment(alice, alice_secret));
+        assert_eq!(
+            randomness.try_commit(commitment(alice, alice_secret)),
+            Err(Error::AlreadyCommitted.into())
+        );
+        assert_eq!(
+            randomness.try_reveal(alice_secret),
+            Err(Error::RevealPhaseNotActive.into())
+        );
+        env.set_caller(bob);
+        randomness.commit(commitment(bob, bob_secret));
+        // Carol copies Alice's commitment, hoping to cancel out her secret
+        env.set_caller(carol);
+        randomness.commit(commitment(alice, alice_secret));
+
+        env.advance_block_time(COMMIT_PERIOD + 1);
+        assert_eq!(
+            randomness.try_commit([0u8; 32]),
+            Err(Error::CommitPhaseOver.into())
+        );
+        assert_eq!(
+            randomness.try_reveal(alice_secret),
+            Err(Error::InvalidSecret.into())
+        );
+        env.set_caller(alice);
+        randomness.reveal(alice_secret);
+        assert_eq!(
+            randomness.try_reveal(alice_secret),
+            Err(Error::AlreadyRevealed.into())
+        );
+        assert_eq!(
+            randomness.try_draw(100),
+            Err(Error::RevealPhaseNotOver.into())
+        );
+        env.set_caller(bob);
+        randomness.reveal(bob_secret);
+
+        env.advance_block_time(REVEAL_PERIOD);
+        assert_eq!(randomness.reveal_count(), 2);
+        let mut preimage = [3u8; 32].to_vec(); // 1 XOR 2
+        preimage.extend(0u32.to_le_bytes());
+        assert_eq!(
+            randomness.draw(100),
+            number_in_range(blake2b(preimage), 100)
+        );
+    }
+
+    #[test]
+    fn nobody_revealed() {
+        let env = odra_test::env();
+        let mut randomness = CommitRevealHostRef::deploy(
+            &env,
+            CommitRevealInitArgs {
+                commit_period: COMMIT_PERIOD,
+                reveal_period: REVEAL_PERIOD,
+            },
+        );
+
+        env.set_caller(env.get_account(1));
+        randomness.commit(commitment(env.get_account(1), [1u8; 32]));
+        env.advance_block_time(COMMIT_PERIOD + REVEAL_PERIOD + 1);
+        assert_eq!(
+            randomness.try_reveal([1u8; 32]),
+            Err(Error::RevealPhaseNotActive.into())
+        );
+        assert_eq!(randomness.try_draw(100), Err(Error::NoReveals.into()));
+    }
+}
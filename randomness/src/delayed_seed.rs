@@ -0,0 +1,175 @@
+use crate::number_in_range;
+use odra::module::SubModule;
+use odra::prelude::*;
+use odra::Var;
+use odra_modules::access::Ownable;
+
+#[odra::odra_error]
+/// Errors that may occur during the contract execution.
+pub enum Error {
+    /// There's no number in an empty range.
+    EmptyRange = 1,
+    /// The owner has already committed to a seed.
+    AlreadyCommitted = 2,
+    /// The owner hasn't committed to a seed yet.
+    NoCommitment = 3,
+    /// The delay since the commitment hasn't passed yet.
+    TooEarly = 4,
+    /// The seed has already been revealed.
+    AlreadyRevealed = 5,
+    /// The seed doesn't match the commitment.
+    InvalidSeed = 6,
+    /// Numbers can only be drawn once the seed is revealed.
+    SeedNotRevealed = 7,
+}
+
+#[odra::event]
+/// Emitted when a number is drawn.
+pub struct Drawn {
+    pub number: u32,
+    pub upper_bound: u32,
+}
+
+#[odra::module(events = [Drawn], errors = Error)]
+/// Draws numbers from a seed the owner commits to, and may only reveal after a delay. The owner
+/// can't change the seed once committed, but knows the numbers in advance and could refuse to
+/// reveal.
+pub struct DelayedSeed {
+    ownable: SubModule<Ownable>,
+    /// Minimum time between the commitment and the reveal.
+    delay: Var<u64>,
+    /// Hash of the seed.
+    commitment: Var<[u8; 32]>,
+    /// Block time from which the seed can be revealed.
+    reveal_at: Var<u64>,
+    seed: Var<[u8; 32]>,
+    /// Number of draws so far, so each draw gives a different number.
+    draws: Var<u32>,
+}
+
+#[odra::module]
+impl DelayedSeed {
+    /// Initializes the contract; the deployer becomes the owner, who provides the seed.
+    pub fn init(&mut self, delay: u64) {
+        self.ownable.init();
+        self.delay.set(delay);
+    }
+
+    /**********
+     * TRANSACTIONS
+     **********/
+
+    /// Commits to a seed by its hash. Only the owner can call it.
+    pub fn commit_seed(&mut self, commitment: [u8; 32]) {
+        self.ownable.assert_owner(&self.env().caller());
+        if self.commitment.get().is_some() {
+            self.env().revert(Error::AlreadyCommitted);
+        }
+        self.commitment.set(commitment);
+        self.reveal_at
+            .set(self.env().get_block_time() + self.delay.get_or_default());
+    }
+
+    /// Reveals the committed seed, once the delay has passed. Only the owner can call it.
+    pub fn reveal_seed(&mut self, seed: [u8; 32]) {
+        self.ownable.assert_owner(&self.env().caller());
+        let commitment = self
+            .commitment
+            .get()
+            .unwrap_or_revert_with(&self.env(), Error::NoCommitment);
+        if self.env().get_block_time() < self.reveal_at.get_or_default() {
+            self.env().revert(Error::TooEarly);
+        }
+        if self.seed.get().is_some() {
+            self.env().revert(Error::AlreadyRevealed);
+        }
+        if self.env().hash(seed) != commitment {
+            self.env().revert(Error::InvalidSeed);
+        }
+        self.seed.set(seed);
+    }
+
+    /// Draws a number in `0..upper_bound` from the revealed seed.
+    pub fn draw(&mut self, upper_bound: u32) -> u32 {
+        if upper_bound == 0 {
+            self.env().revert(Error::EmptyRange);
+        }
+        let seed = self
+            .seed
+            .get()
+            .unwrap_or_revert_with(&self.env(), Error::SeedNotRevealed);
+        let draws = self.draws.get_or_default();
+        let mut preimage = seed.to_vec();
+        preimage.extend(draws.to_le_bytes());
+        let number = number_in_range(self.env().hash(preimage), upper_bound);
+
+        self.draws.set(draws + 1);
+        self.env().emit_event(Drawn {
+            number,
+            upper_bound,
+        });
+        number
+    }
+
+    /**********
+     * QUERIES
+     **********/
+
+    /// Returns the block time from which the seed can be revealed, once committed.
+    pub fn reveal_at(&self) -> Option<u64> {
+        self.reveal_at.get()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use odra::casper_types::crypto::blake2b;
+    use odra::host::{Deployer, HostRef};
+
+    const DELAY: u64 = 60 * 60 * 1_000; // one hour
+    const SEED: [u8; 32] = [7u8; 32];
+
+    #[test]
+    fn seed_is_revealed_after_the_delay() {
+        let env = odra_test::env();
+        let mut randomness = DelayedSeedHostRef::deploy(&env, DelayedSeedInitArgs { delay: DELAY });
+
+        assert_eq!(
+            randomness.try_reveal_seed(SEED),
+            Err(Error::NoCommitment.into())
+        );
+        randomness.commit_seed(blake2b(SEED));
+        assert_eq!(
+            randomness.try_commit_seed(blake2b([8u8; 32])),
+            Err(Error::AlreadyCommitted.into())
+        );
+        assert_eq!(randomness.reveal_at(), Some(env.block_time() + DELAY));
+        assert_eq!(randomness.try_draw(10), Err(Error::SeedNotRevealed.into()));
+        assert_eq!(
+            randomness.try_reveal_seed(SEED),
+            Err(Error::TooEarly.into())
+        );
+
+        env.advance_block_time(DELAY);
+        env.set_caller(env.get_account(1));
+        assert!(randomness.try_reveal_seed(SEED).is_err());
+        env.set_caller(env.get_account(0));
+        assert_eq!(
+            randomness.try_reveal_seed([8u8; 32]),
+            Err(Error::InvalidSeed.into())
+        );
+        randomness.reveal_seed(SEED);
+
+        // Two draws from the same seed give independent numbers
+        let numbers: Vec<u32> = (0..2u32)
+            .map(|draw| {
+                let mut preimage = SEED.to_vec();
+                preimage.extend(draw.to_le_bytes());
+                number_in_range(blake2b(preimage), 10)
+            })
+            .collect();
+        assert_eq!(randomness.draw(10), numbers[0]);
+        assert_eq!(randomness.draw(10), numbers[1]);
+    }
+}
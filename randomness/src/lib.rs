@@ -0,0 +1,26 @@
+#![cfg_attr(not(test), no_std)]
+#![cfg_attr(not(test), no_main)]
+extern crate alloc;
+
+pub mod block_time;
+pub mod commit_reveal;
+pub mod delayed_seed;
+pub mod raffle;
+
+/// The entry point every randomness strategy exposes: `BlockTimeRandomness`, `CommitReveal` and
+/// `DelayedSeed`. Contracts that draw through it work with any of them.
+#[odra::external_contract]
+pub trait RandomnessSource {
+    /// Draws a number in `0..upper_bound`.
+    fn draw(&mut self, upper_bound: u32) -> u32;
+}
+
+/// Maps 32 random bytes to a number in `0..upper_bound`, which must not be zero.
+///
+/// The modulo slightly favors small numbers, by less than `upper_bound / 2^64`, which doesn't
+/// matter for the ranges lotteries draw from.
+pub fn number_in_range(randomness: [u8; 32], upper_bound: u32) -> u32 {
+    let mut number = [0u8; 8];
+    number.copy_from_slice(&randomness[..8]);
+    (u64::from_le_bytes(number) % upper_bound as u64) as u32
+}
@@ -0,0 +1,161 @@
+use crate::RandomnessSourceContractRef;
+use odra::module::SubModule;
+use odra::prelude::*;
+use odra::{Address, ContractRef, List, Var};
+use odra_modules::access::Ownable;
+
+#[odra::odra_error]
+/// Errors that may occur during the contract execution.
+pub enum Error {
+    /// There's nobody to pick.
+    NoEntries = 1,
+    /// The raffle already has a winner.
+    WinnerAlreadyPicked = 2,
+}
+
+#[odra::event]
+/// Emitted when the winner is picked.
+pub struct WinnerPicked {
+    pub winner: Address,
+    pub entries: u32,
+}
+
+#[odra::module(events = [WinnerPicked], errors = Error)]
+/// Picks one of its entrants as the winner. It doesn't know how the winner is drawn: it calls
+/// whichever `RandomnessSource` it was deployed with.
+pub struct Raffle {
+    ownable: SubModule<Ownable>,
+    /// Address of the randomness strategy.
+    source: Var<Address>,
+    entries: List<Address>,
+    winner: Var<Address>,
+}
+
+#[odra::module]
+impl Raffle {
+    /// Initializes the raffle with the contract it draws from; the deployer becomes the owner.
+    pub fn init(&mut self, source: Address) {
+        self.ownable.init();
+        self.source.set(source);
+    }
+
+    /**********
+     * TRANSACTIONS
+     **********/
+
+    /// Enters the caller into the raffle.
+    pub fn enter(&mut self) {
+        if self.winner.get().is_some() {
+            self.env().revert(Error::WinnerAlreadyPicked);
+        }
+        self.entries.push(self.env().caller());
+    }
+
+    /// Draws the winner from the randomness source. Only the owner can call it.
+    pub fn pick_winner(&mut self) -> Address {
+        self.ownable.assert_owner(&self.env().caller());
+        if self.winner.get().is_some() {
+            self.env().revert(Error::WinnerAlreadyPicked);
+        }
+        let entries = self.entries.len();
+        if entries == 0 {
+            self.env().revert(Error::NoEntries);
+        }
+        let mut source = RandomnessSourceContractRef::new(self.env(), self.source());
+        let winner = self
+            .entries
+            .get(source.draw(entries))
+            .unwrap_or_revert(&self.env());
+
+        self.winner.set(winner);
+        self.env().emit_event(WinnerPicked { winner, entries });
+        winner
+    }
+
+    /**********
+     * QUERIES
+     **********/
+
+    pub fn source(&self) -> Address {
+        self.source.get().unwrap_or_revert(&self.env())
+    }
+
+    pub fn winner(&self) -> Option<Address> {
+        self.winner.get()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block_time::BlockTimeRandomnessHostRef;
+    use crate::delayed_seed::{self, DelayedSeedHostRef, DelayedSeedInitArgs};
+    use crate::number_in_range;
+    use odra::casper_types::crypto::blake2b;
+    use odra::host::{Deployer, HostEnv, HostRef, NoArgs};
+
+    /// Enters accounts 1 to 3, and switches back to the owner.
+    fn enter_three(env: &HostEnv, raffle: &mut RaffleHostRef) {
+        for account in 1..4 {
+            env.set_caller(env.get_account(account));
+            raffle.enter();
+        }
+        env.set_caller(env.get_account(0));
+    }
+
+    #[test]
+    fn winner_from_the_block_time() {
+        let env = odra_test::env();
+        let source = BlockTimeRandomnessHostRef::deploy(&env, NoArgs);
+        let mut raffle = RaffleHostRef::deploy(
+            &env,
+            RaffleInitArgs {
+                source: *source.address(),
+            },
+        );
+        assert_eq!(raffle.try_pick_winner(), Err(Error::NoEntries.into()));
+        enter_three(&env, &mut raffle);
+
+        env.set_caller(env.get_account(1));
+        assert!(raffle.try_pick_winner().is_err());
+        env.set_caller(env.get_account(0));
+        let mut preimage = env.block_time().to_le_bytes().to_vec();
+        preimage.extend(0u32.to_le_bytes());
+        let winner = env.get_account(1 + number_in_range(blake2b(preimage), 3) as usize);
+        assert_eq!(raffle.pick_winner(), winner);
+        assert!(env.emitted_event(raffle.address(), &WinnerPicked { winner, entries: 3 }));
+        assert_eq!(
+            raffle.try_pick_winner(),
+            Err(Error::WinnerAlreadyPicked.into())
+        );
+    }
+
+    #[test]
+    fn winner_from_a_delayed_seed() {
+        let env = odra_test::env();
+        let seed = [7u8; 32];
+        let mut source = DelayedSeedHostRef::deploy(&env, DelayedSeedInitArgs { delay: 1_000 });
+        source.commit_seed(blake2b(seed));
+        // The same raffle, drawing from another strategy
+        let mut raffle = RaffleHostRef::deploy(
+            &env,
+            RaffleInitArgs {
+                source: *source.address(),
+            },
+        );
+        enter_three(&env, &mut raffle);
+
+        // The source's errors reach the raffle's caller
+        assert_eq!(
+            raffle.try_pick_winner(),
+            Err(delayed_seed::Error::SeedNotRevealed.into())
+        );
+        env.advance_block_time(1_000);
+        source.reveal_seed(seed);
+        let mut preimage = seed.to_vec();
+        preimage.extend(0u32.to_le_bytes());
+        let winner = env.get_account(1 + number_in_range(blake2b(preimage), 3) as usize);
+        assert_eq!(raffle.pick_winner(), winner);
+        assert_eq!(raffle.winner(), Some(winner));
+    }
+}
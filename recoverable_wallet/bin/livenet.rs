@@ -0,0 +1,71 @@
+//! Deploys the Wallet contract, funds it and recovers the funds with the guardians' votes.
+use odra::casper_types::U512;
+use odra::host::{Deployer, HostEnv, HostRef};
+use recoverable_wallet::recoverable_wallet::{
+    RecoveryMode, RoundingMode, WalletHostRef, WalletInitArgs,
+};
+
+use tutorial_utils::gas::{self, with_gas};
+use tutorial_utils::manifest::record_deployment;
+use tutorial_utils::network;
+
+const DEPOSIT: u64 = 5_000_000_000; // 5 CSPR
+
+fn main() {
+    network::configure_from_args();
+    let env = odra_casper_livenet_env::env();
+    let guardians = vec![env.get_account(1), env.get_account(2)];
+    let recovery_address = env.get_account(3);
+
+    let mut wallet = deploy_contract(&env, guardians.clone());
+    println!("Wallet address: {}", wallet.address().to_string());
+
+    // casper contract may return a result or not, so deserialization may fail and it's better to use `try_` methods
+    let _ = with_gas(&env, gas::TRANSFER, || {
+        wallet.with_tokens(U512::from(DEPOSIT)).try_deposit()
+    });
+    println!("Wallet balance: {}", wallet.balance());
+
+    for guardian in guardians {
+        env.set_caller(guardian);
+        let _ = with_gas(&env, gas::CALL, || wallet.try_recover_to(recovery_address));
+    }
+    println!("Recovery status: {:?}", wallet.recovery_status());
+
+    let _ = with_gas(&env, gas::TRANSFER, || wallet.try_execute_recovery());
+    println!("Wallet balance after recovery: {}", wallet.balance());
+    println!(
+        "Recovery address balance: {}",
+        env.balance_of(&recovery_address)
+    );
+}
+
+/// Deploys a Wallet contract guarded by the given accounts, with no recovery delay.
+pub fn deploy_contract(env: &HostEnv, guardians: Vec<odra::Address>) -> WalletHostRef {
+    let recorded_args = [
+        (
+            "recovery_guardians",
+            guardians
+                .iter()
+                .map(|g| g.to_string())
+                .collect::<Vec<_>>()
+                .join(","),
+        ),
+        ("recovery_delay", "0".to_string()),
+        ("rounding_mode", "Ceil".to_string()),
+        ("recovery_mode", "TransferFunds".to_string()),
+    ];
+    let init_args = || WalletInitArgs {
+        recovery_guardians: guardians.clone(),
+        recovery_threshold: None,
+        recovery_delay: 0,
+        rounding_mode: RoundingMode::Ceil,
+        recovery_mode: RecoveryMode::TransferFunds,
+    };
+    let wallet = with_gas(env, gas::DEPLOY, || {
+        WalletHostRef::try_deploy(env, init_args())
+    })
+    .expect("Should deploy the contract");
+    record_deployment("Wallet", &wallet, None, &recorded_args);
+    wallet
+}
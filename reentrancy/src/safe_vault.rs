@@ -0,0 +1,152 @@
+use crate::vulnerable_vault::WithdrawalReceiverContractRef;
+use odra::casper_types::U512;
+use odra::prelude::*;
+use odra::{Address, ContractRef, Mapping, Var};
+
+#[odra::odra_error]
+/// Errors that may occur during the contract execution.
+pub enum Error {
+    /// A deposit has to come with CSPR.
+    ZeroDeposit = 1,
+    /// The caller has nothing deposited.
+    NothingToWithdraw = 2,
+    /// The vault was called again while a withdrawal was running.
+    Locked = 3,
+}
+
+#[odra::module(errors = Error)]
+/// The fixed `VulnerableVault`. It clears a balance before paying it out, and refuses to be
+/// re-entered while a withdrawal runs.
+pub struct SafeVault {
+    balances: Mapping<Address, U512>,
+    /// Set while `deposit` or `withdraw` runs.
+    locked: Var<bool>,
+}
+
+#[odra::module]
+impl SafeVault {
+    /**********
+     * TRANSACTIONS
+     **********/
+
+    /// Deposits the attached CSPR for `owner`.
+    #[odra(payable)]
+    pub fn deposit(&mut self, owner: Address) {
+        self.lock();
+        let amount = self.env().attached_value();
+        if amount.is_zero() {
+            self.env().revert(Error::ZeroDeposit);
+        }
+        let balance = self.balances.get_or_default(&owner);
+        self.balances.set(&owner, balance + amount);
+        self.unlock();
+    }
+
+    /// Pays out the caller's whole balance. Contracts are notified of the withdrawal.
+    pub fn withdraw(&mut self) {
+        self.lock();
+        let caller = self.env().caller();
+        // Checks
+        let amount = self.balances.get_or_default(&caller);
+        if amount.is_zero() {
+            self.env().revert(Error::NothingToWithdraw);
+        }
+        // Effects, before any other contract runs
+        self.balances.set(&caller, U512::zero());
+        // Interactions
+        self.env().transfer_tokens(&caller, &amount);
+        if caller.is_contract() {
+            WithdrawalReceiverContractRef::new(self.env(), caller).on_withdrawal(amount);
+        }
+        self.unlock();
+    }
+
+    /**********
+     * QUERIES
+     **********/
+
+    pub fn balance_of(&self, owner: Address) -> U512 {
+        self.balances.get_or_default(&owner)
+    }
+
+    /// Returns the CSPR the vault holds for all its users.
+    pub fn total_balance(&self) -> U512 {
+        self.env().self_balance()
+    }
+}
+
+impl SafeVault {
+    /// Reverts if another call is running, and blocks further calls until `unlock`.
+    ///
+    /// A revert undoes the lock too, so it can't be left locked by a failed call.
+    fn lock(&mut self) {
+        if self.locked.get_or_default() {
+            self.env().revert(Error::Locked);
+        }
+        self.locked.set(true);
+    }
+
+    fn unlock(&mut self) {
+        self.locked.set(false);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::attacker::{AttackerHostRef, AttackerInitArgs};
+    use odra::host::{Deployer, HostRef, NoArgs};
+
+    #[test]
+    fn attack_fails() {
+        let env = odra_test::env();
+        let (alice, mallory) = (env.get_account(1), env.get_account(3));
+        let mut vault = SafeVaultHostRef::deploy(&env, NoArgs);
+        let mut attacker = AttackerHostRef::deploy(
+            &env,
+            AttackerInitArgs {
+                vault: *vault.address(),
+            },
+        );
+
+        env.set_caller(alice);
+        vault.with_tokens(U512::from(300)).deposit(alice);
+        env.set_caller(mallory);
+        vault
+            .with_tokens(U512::from(100))
+            .deposit(*attacker.address());
+
+        // The callback runs into the lock, which reverts the whole attack
+        assert_eq!(attacker.try_attack(), Err(Error::Locked.into()));
+        assert_eq!(env.balance_of(attacker.address()), U512::zero());
+        assert_eq!(vault.total_balance(), U512::from(400));
+
+        // Honest users withdraw as usual
+        let balance = env.balance_of(&alice);
+        env.set_caller(alice);
+        vault.withdraw();
+        assert_eq!(env.balance_of(&alice), balance + U512::from(300));
+        assert_eq!(vault.balance_of(alice), U512::zero());
+        assert_eq!(vault.try_withdraw(), Err(Error::NothingToWithdraw.into()));
+    }
+
+    #[test]
+    fn contracts_are_paid_once() {
+        let env = odra_test::env();
+        let mut vault = SafeVaultHostRef::deploy(&env, NoArgs);
+        let mut attacker = AttackerHostRef::deploy(
+            &env,
+            AttackerInitArgs {
+                vault: *vault.address(),
+            },
+        );
+
+        // With nothing else in the vault, the attacker doesn't call back and gets its own deposit
+        vault
+            .with_tokens(U512::from(100))
+            .deposit(*attacker.address());
+        attacker.attack();
+        assert_eq!(env.balance_of(attacker.address()), U512::from(100));
+        assert_eq!(vault.total_balance(), U512::zero());
+    }
+}
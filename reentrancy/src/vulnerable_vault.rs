@@ -0,0 +1,112 @@
+use odra::casper_types::U512;
+use odra::prelude::*;
+use odra::{Address, ContractRef, Mapping};
+
+/// The entry point a contract implements to be notified of its withdrawals.
+#[odra::external_contract]
+pub trait WithdrawalReceiver {
+    fn on_withdrawal(&mut self, amount: U512);
+}
+
+#[odra::odra_error]
+/// Errors that may occur during the contract execution.
+pub enum Error {
+    /// A deposit has to come with CSPR.
+    ZeroDeposit = 1,
+    /// The caller has nothing deposited.
+    NothingToWithdraw = 2,
+}
+
+#[odra::module(errors = Error)]
+/// A vault holding CSPR for its users. DO NOT USE: it updates a balance only after paying it
+/// out, so a receiver that calls back into `withdraw` is paid again.
+pub struct VulnerableVault {
+    balances: Mapping<Address, U512>,
+}
+
+#[odra::module]
+impl VulnerableVault {
+    /**********
+     * TRANSACTIONS
+     **********/
+
+    /// Deposits the attached CSPR for `owner`.
+    #[odra(payable)]
+    pub fn deposit(&mut self, owner: Address) {
+        let amount = self.env().attached_value();
+        if amount.is_zero() {
+            self.env().revert(Error::ZeroDeposit);
+        }
+        let balance = self.balances.get_or_default(&owner);
+        self.balances.set(&owner, balance + amount);
+    }
+
+    /// Pays out the caller's whole balance. Contracts are notified of the withdrawal.
+    pub fn withdraw(&mut self) {
+        let caller = self.env().caller();
+        // Checks
+        let amount = self.balances.get_or_default(&caller);
+        if amount.is_zero() {
+            self.env().revert(Error::NothingToWithdraw);
+        }
+        // Interactions, while the balance still holds the amount
+        self.env().transfer_tokens(&caller, &amount);
+        if caller.is_contract() {
+            WithdrawalReceiverContractRef::new(self.env(), caller).on_withdrawal(amount);
+        }
+        // Effects, too late
+        self.balances.set(&caller, U512::zero());
+    }
+
+    /**********
+     * QUERIES
+     **********/
+
+    pub fn balance_of(&self, owner: Address) -> U512 {
+        self.balances.get_or_default(&owner)
+    }
+
+    /// Returns the CSPR the vault holds for all its users.
+    pub fn total_balance(&self) -> U512 {
+        self.env().self_balance()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::attacker::{AttackerHostRef, AttackerInitArgs};
+    use odra::host::{Deployer, HostRef, NoArgs};
+
+    #[test]
+    fn attacker_drains_the_vault() {
+        let env = odra_test::env();
+        let (alice, bob, mallory) = (env.get_account(1), env.get_account(2), env.get_account(3));
+        let mut vault = VulnerableVaultHostRef::deploy(&env, NoArgs);
+        let mut attacker = AttackerHostRef::deploy(
+            &env,
+            AttackerInitArgs {
+                vault: *vault.address(),
+            },
+        );
+
+        env.set_caller(alice);
+        vault.with_tokens(U512::from(200)).deposit(alice);
+        env.set_caller(bob);
+        vault.with_tokens(U512::from(100)).deposit(bob);
+        env.set_caller(mallory);
+        vault
+            .with_tokens(U512::from(100))
+            .deposit(*attacker.address());
+
+        // Each payout calls back into the attacker, which withdraws again
+        attacker.attack();
+        assert_eq!(env.balance_of(attacker.address()), U512::from(400));
+        assert_eq!(vault.total_balance(), U512::zero());
+
+        // The vault still owes Alice and Bob, but has nothing left to pay them with
+        assert_eq!(vault.balance_of(alice), U512::from(200));
+        env.set_caller(alice);
+        assert!(vault.try_withdraw().is_err());
+    }
+}
@@ -0,0 +1,327 @@
+use odra::args::Maybe;
+use odra::casper_types::U512;
+use odra::prelude::*;
+use odra::{Address, ContractRef, Mapping};
+use odra_modules::cep78::token::Cep78ContractRef;
+
+/// Length of a rental day in milliseconds.
+const DAY: u64 = 24 * 60 * 60 * 1_000;
+/// Longest rental, in days.
+const MAX_RENTAL_DAYS: u32 = 365;
+
+#[odra::odra_error]
+/// Errors that may occur during the contract execution.
+pub enum Error {
+    /// The token hasn't been deposited for rent.
+    NotDeposited = 1,
+    /// Only the owner who deposited the token can do this.
+    NotOwner = 2,
+    /// The token is rented until its rental expires.
+    CurrentlyRented = 3,
+    /// Rentals last between one day and `MAX_RENTAL_DAYS`.
+    InvalidDuration = 4,
+    /// The attached CSPR doesn't match the price of the rental.
+    IncorrectPayment = 5,
+    /// The daily price must be positive.
+    InvalidPrice = 6,
+    /// Owners can't rent their own tokens.
+    OwnerCannotRent = 7,
+}
+
+#[odra::odra_type]
+pub struct RentalTerms {
+    /// Account that deposited the token and gets it back.
+    pub owner: Address,
+    pub price_per_day: U512,
+    /// The latest renter, who is the token's user until `expires_at`.
+    pub user: Option<Address>,
+    pub expires_at: u64,
+}
+
+#[odra::event]
+/// Emitted when an owner deposits a token for rent.
+pub struct Deposited {
+    pub nft_contract: Address,
+    pub token_id: u64,
+    pub owner: Address,
+    pub price_per_day: U512,
+}
+
+#[odra::event]
+/// Emitted when a token is rented.
+pub struct Rented {
+    pub nft_contract: Address,
+    pub token_id: u64,
+    pub user: Address,
+    pub expires_at: u64,
+}
+
+#[odra::event]
+/// Emitted when an owner takes a token back.
+pub struct Reclaimed {
+    pub nft_contract: Address,
+    pub token_id: u64,
+    pub owner: Address,
+}
+
+#[odra::module(events = [Deposited, Rented, Reclaimed], errors = Error)]
+/// Holds CEP-78 tokens and rents out the right to use them by the day.
+pub struct Rental {
+    /// Terms per deposited token, cleared when the owner takes the token back.
+    terms: Mapping<(Address, u64), Option<RentalTerms>>,
+}
+
+#[odra::module]
+impl Rental {
+    /**********
+     * TRANSACTIONS
+     **********/
+
+    /// Takes the caller's token into custody and offers it for rent at `price_per_day`.
+    ///
+    /// The owner has to approve the contract for the token beforehand.
+    pub fn deposit(&mut self, nft_contract: Address, token_id: u64, price_per_day: U512) {
+        if price_per_day.is_zero() {
+            self.env().revert(Error::InvalidPrice);
+        }
+        let owner = self.env().caller();
+        let contract = self.env().self_address();
+        Cep78ContractRef::new(self.env(), nft_contract).transfer(
+            Maybe::Some(token_id),
+            Maybe::None,
+            owner,
+            contract,
+        );
+
+        self.terms.set(
+            &(nft_contract, token_id),
+            Some(RentalTerms {
+                owner,
+                price_per_day,
+                user: None,
+                expires_at: 0,
+            }),
+        );
+        self.env().emit_event(Deposited {
+            nft_contract,
+            token_id,
+            owner,
+            price_per_day,
+        });
+    }
+
+    /// Rents the token for `days`, paying the attached price to the owner up front.
+    #[odra(payable)]
+    pub fn rent(&mut self, nft_contract: Address, token_id: u64, days: u32) {
+        let mut terms = self.terms_of(nft_contract, token_id);
+        let user = self.env().caller();
+        let now = self.env().get_block_time();
+        if user == terms.owner {
+            self.env().revert(Error::OwnerCannotRent);
+        }
+        if now < terms.expires_at {
+            self.env().revert(Error::CurrentlyRented);
+        }
+        if days == 0 || days > MAX_RENTAL_DAYS {
+            self.env().revert(Error::InvalidDuration);
+        }
+        if self.env().attached_value() != terms.price_per_day * days {
+            self.env().revert(Error::IncorrectPayment);
+        }
+
+        terms.user = Some(user);
+        terms.expires_at = now + days as u64 * DAY;
+        let (owner, price, expires_at) =
+            (terms.owner, terms.price_per_day * days, terms.expires_at);
+        self.terms.set(&(nft_contract, token_id), Some(terms));
+        self.env().transfer_tokens(&owner, &price);
+        self.env().emit_event(Rented {
+            nft_contract,
+            token_id,
+            user,
+            expires_at,
+        });
+    }
+
+    /// Changes the daily price of future rentals.
+    pub fn set_price(&mut self, nft_contract: Address, token_id: u64, price_per_day: U512) {
+        let mut terms = self.owned_terms(nft_contract, token_id);
+        if price_per_day.is_zero() {
+            self.env().revert(Error::InvalidPrice);
+        }
+        terms.price_per_day = price_per_day;
+        self.terms.set(&(nft_contract, token_id), Some(terms));
+    }
+
+    /// Returns the token to its owner, once it isn't rented.
+    pub fn reclaim(&mut self, nft_contract: Address, token_id: u64) {
+        let terms = self.owned_terms(nft_contract, token_id);
+        if self.env().get_block_time() < terms.expires_at {
+            self.env().revert(Error::CurrentlyRented);
+        }
+
+        self.terms.set(&(nft_contract, token_id), None);
+        let contract = self.env().self_address();
+        Cep78ContractRef::new(self.env(), nft_contract).transfer(
+            Maybe::Some(token_id),
+            Maybe::None,
+            contract,
+            terms.owner,
+        );
+        self.env().emit_event(Reclaimed {
+            nft_contract,
+            token_id,
+            owner: terms.owner,
+        });
+    }
+
+    /**********
+     * QUERIES
+     **********/
+
+    /// Returns the current user of the token: its renter, until the rental expires.
+    pub fn user_of(&self, nft_contract: Address, token_id: u64) -> Option<Address> {
+        self.get_terms(nft_contract, token_id)
+            .filter(|terms| self.env().get_block_time() < terms.expires_at)
+            .and_then(|terms| terms.user)
+    }
+
+    /// Returns the rental terms of a deposited token.
+    pub fn get_terms(&self, nft_contract: Address, token_id: u64) -> Option<RentalTerms> {
+        self.terms.get(&(nft_contract, token_id)).flatten()
+    }
+}
+
+impl Rental {
+    fn terms_of(&self, nft_contract: Address, token_id: u64) -> RentalTerms {
+        self.get_terms(nft_contract, token_id)
+            .unwrap_or_revert_with(&self.env(), Error::NotDeposited)
+    }
+
+    fn owned_terms(&self, nft_contract: Address, token_id: u64) -> RentalTerms {
+        let terms = self.terms_of(nft_contract, token_id);
+        if self.env().caller() != terms.owner {
+            self.env().revert(Error::NotOwner);
+        }
+        terms
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use odra::host::{Deployer, HostEnv, HostRef, NoArgs};
+    use odra_modules::cep78::modalities::{
+        MetadataMutability, NFTIdentifierMode, NFTKind, NFTMetadataKind, OwnershipMode,
+    };
+    use odra_modules::cep78::token::Cep78HostRef;
+    use odra_modules::cep78::utils::InitArgsBuilder;
+
+    const CEP78_METADATA: &str = r#"{
+        "name": "John Doe",
+        "token_uri": "https://www.barfoo.com",
+        "checksum": "940bffb3f2bba35f84313aa26da09ece3ad47045c6a1292c2bbd2df4ab1a55fb"
+    }"#;
+    const PRICE: u64 = 100;
+
+    /// Deploys the contract and a collection, and deposits account 1's token 0 for rent.
+    fn setup(env: &HostEnv) -> (RentalHostRef, Cep78HostRef) {
+        let mut rental = RentalHostRef::deploy(env, NoArgs);
+        let init_args = InitArgsBuilder::default()
+            .collection_name("Rented Collection".to_string())
+            .collection_symbol("RNT".to_string())
+            .total_token_supply(10)
+            .ownership_mode(OwnershipMode::Transferable)
+            .nft_metadata_kind(NFTMetadataKind::CEP78)
+            .identifier_mode(NFTIdentifierMode::Ordinal)
+            .nft_kind(NFTKind::Digital)
+            .metadata_mutability(MetadataMutability::Immutable)
+            .receipt_name("rental_receipt".to_string())
+            .build();
+        let mut nft = Cep78HostRef::deploy(env, init_args);
+        let owner = env.get_account(1);
+        nft.mint(owner, CEP78_METADATA.to_string(), Maybe::None);
+
+        env.set_caller(owner);
+        nft.approve(*rental.address(), Maybe::Some(0), Maybe::None);
+        rental.deposit(*nft.address(), 0, U512::from(PRICE));
+        (rental, nft)
+    }
+
+    #[test]
+    fn renting() {
+        let env = odra_test::env();
+        let (mut rental, nft) = setup(&env);
+        let collection = *nft.address();
+        let (owner, alice, bob) = (env.get_account(1), env.get_account(2), env.get_account(3));
+        assert_eq!(nft.owner_of(Maybe::Some(0), Maybe::None), *rental.address());
+        assert_eq!(rental.user_of(collection, 0), None);
+
+        env.set_caller(alice);
+        assert_eq!(
+            rental
+                .with_tokens(U512::from(PRICE))
+                .try_rent(collection, 0, 3),
+            Err(Error::IncorrectPayment.into())
+        );
+        assert_eq!(
+            rental.try_rent(collection, 0, 0),
+            Err(Error::InvalidDuration.into())
+        );
+
+        // The owner is paid up front, and the renter is the user for three days
+        let owner_balance = env.balance_of(&owner);
+        rental
+            .with_tokens(U512::from(3 * PRICE))
+            .rent(collection, 0, 3);
+        assert_eq!(
+            env.balance_of(&owner),
+            owner_balance + U512::from(3 * PRICE)
+        );
+        assert_eq!(rental.user_of(collection, 0), Some(alice));
+
+        env.set_caller(bob);
+        assert_eq!(
+            rental
+                .with_tokens(U512::from(PRICE))
+                .try_rent(collection, 0, 1),
+            Err(Error::CurrentlyRented.into())
+        );
+
+        // Once the rental expires, the token has no user and can be rented again
+        env.advance_block_time(3 * DAY);
+        assert_eq!(rental.user_of(collection, 0), None);
+        rental.with_tokens(U512::from(PRICE)).rent(collection, 0, 1);
+        assert_eq!(rental.user_of(collection, 0), Some(bob));
+    }
+
+    #[test]
+    fn reclaiming() {
+        let env = odra_test::env();
+        let (mut rental, nft) = setup(&env);
+        let collection = *nft.address();
+        let owner = env.get_account(1);
+
+        env.set_caller(env.get_account(2));
+        rental.with_tokens(U512::from(PRICE)).rent(collection, 0, 1);
+        assert_eq!(
+            rental.try_reclaim(collection, 0),
+            Err(Error::NotOwner.into())
+        );
+
+        // The owner has to wait for the rental to expire
+        env.set_caller(owner);
+        assert_eq!(
+            rental.try_reclaim(collection, 0),
+            Err(Error::CurrentlyRented.into())
+        );
+        env.advance_block_time(DAY);
+        rental.reclaim(collection, 0);
+        assert_eq!(nft.owner_of(Maybe::Some(0), Maybe::None), owner);
+        assert_eq!(rental.get_terms(collection, 0), None);
+        assert_eq!(
+            rental.try_reclaim(collection, 0),
+            Err(Error::NotDeposited.into())
+        );
+    }
+}
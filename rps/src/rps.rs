@@ -0,0 +1,591 @@
+use odra::casper_types::bytesrepr::{Bytes, ToBytes};
+use odra::casper_types::U512;
+use odra::prelude::*;
+use odra::{Address, Mapping, Var};
+
+#[odra::odra_error]
+/// Errors that may occur during the contract execution.
+pub enum Error {
+    /// There's no game with the given ID.
+    GameNotFound = 1,
+    /// The game isn't in a state that allows this.
+    InvalidState = 2,
+    /// The attached CSPR doesn't match the game's stake.
+    WrongStake = 3,
+    /// Players can't join their own games.
+    CannotJoinOwnGame = 4,
+    /// Only the player who created the game can cancel it.
+    NotCreator = 5,
+    /// The caller isn't playing the game.
+    NotPlayer = 6,
+    /// The player has already revealed their move.
+    AlreadyRevealed = 7,
+    /// The move and salt don't match the player's commitment.
+    InvalidReveal = 8,
+    /// Moves can no longer be revealed.
+    RevealPeriodOver = 9,
+    /// Players can still reveal their moves.
+    RevealPeriodNotOver = 10,
+}
+
+#[odra::odra_type]
+#[derive(Copy)]
+pub enum Move {
+    Rock,
+    Paper,
+    Scissors,
+}
+
+impl Move {
+    fn beats(self, other: Move) -> bool {
+        matches!(
+            (self, other),
+            (Move::Rock, Move::Scissors)
+                | (Move::Paper, Move::Rock)
+                | (Move::Scissors, Move::Paper)
+        )
+    }
+}
+
+#[odra::odra_type]
+pub enum GameStatus {
+    /// Waiting for a second player to commit.
+    WaitingForOpponent,
+    /// Both players have committed, and are revealing their moves.
+    Revealing,
+    /// The pot has been paid out.
+    Settled,
+    /// The creator cancelled the game before anyone joined.
+    Cancelled,
+}
+
+#[odra::odra_type]
+pub struct Hand {
+    pub player: Address,
+    /// Hash of the player's address, move and salt.
+    pub commitment: [u8; 32],
+    pub revealed: Option<Move>,
+}
+
+#[odra::odra_type]
+pub struct Game {
+    /// The creator's hand.
+    pub first: Hand,
+    pub second: Option<Hand>,
+    /// Amount each player puts in the pot.
+    pub stake: U512,
+    /// Block time moves can be revealed until.
+    pub reveal_deadline: u64,
+    pub status: GameStatus,
+    /// The player who took the pot, if the game wasn't a draw.
+    pub winner: Option<Address>,
+}
+
+#[odra::event]
+/// Emitted when a player creates a game.
+pub struct GameCreated {
+    pub game_id: u32,
+    pub player: Address,
+    pub stake: U512,
+}
+
+#[odra::event]
+/// Emitted when a second player commits to a game.
+pub struct GameJoined {
+    pub game_id: u32,
+    pub player: Address,
+    pub reveal_deadline: u64,
+}
+
+#[odra::event]
+/// Emitted when a player reveals their move.
+pub struct MoveRevealed {
+    pub game_id: u32,
+    pub player: Address,
+    pub choice: Move,
+}
+
+#[odra::event]
+/// Emitted when the pot of a game is paid out.
+pub struct GameSettled {
+    pub game_id: u32,
+    pub winner: Option<Address>,
+}
+
+#[odra::event]
+/// Emitted when the creator cancels a game.
+pub struct GameCancelled {
+    pub game_id: u32,
+}
+
+#[odra::module(
+    events = [GameCreated, GameJoined, MoveRevealed, GameSettled, GameCancelled],
+    errors = Error
+)]
+/// Rock-paper-scissors games for a stake, with moves committed before they're revealed.
+pub struct RockPaperScissors {
+    /// How long players have to reveal once both have committed.
+    reveal_period: Var<u64>,
+    games: Mapping<u32, Game>,
+    game_count: Var<u32>,
+}
+
+#[odra::module]
+impl RockPaperScissors {
+    /// Initializes the contract with the time players have to reveal their moves.
+    pub fn init(&mut self, reveal_period: u64) {
+        self.reveal_period.set(reveal_period);
+    }
+
+    /**********
+     * TRANSACTIONS
+     **********/
+
+    /// Creates a game with the caller's committed move, and the attached CSPR as the stake.
+    /// Returns the ID of the game.
+    #[odra(payable)]
+    pub fn create_game(&mut self, commitment: [u8; 32]) -> u32 {
+        let player = self.env().caller();
+        let stake = self.env().attached_value();
+
+        let game_id = self.game_count.get_or_default();
+        self.games.set(
+            &game_id,
+            Game {
+                first: Hand {
+                    player,
+                    commitment,
+                    revealed: None,
+                },
+                second: None,
+                stake,
+                reveal_deadline: 0,
+                status: GameStatus::WaitingForOpponent,
+                winner: None,
+            },
+        );
+        self.game_count.set(game_id + 1);
+        self.env().emit_event(GameCreated {
+            game_id,
+            player,
+            stake,
+        });
+        game_id
+    }
+
+    /// Joins a game with the caller's committed move, attaching the same stake as the creator.
+    /// Starts the reveal period.
+    #[odra(payable)]
+    pub fn join(&mut self, game_id: u32, commitment: [u8; 32]) {
+        let mut game = self.game(game_id);
+        self.assert_status(&game, GameStatus::WaitingForOpponent);
+        let player = self.env().caller();
+        if player == game.first.player {
+            self.env().revert(Error::CannotJoinOwnGame);
+        }
+        if self.env().attached_value() != game.stake {
+            self.env().revert(Error::WrongStake);
+        }
+
+        let reveal_deadline = self.env().get_block_time() + self.reveal_period.get_or_default();
+        game.second = Some(Hand {
+            player,
+            commitment,
+            revealed: None,
+        });
+        game.reveal_deadline = reveal_deadline;
+        game.status = GameStatus::Revealing;
+        self.games.set(&game_id, game);
+        self.env().emit_event(GameJoined {
+            game_id,
+            player,
+            reveal_deadline,
+        });
+    }
+
+    /// Cancels a game nobody has joined, and refunds the stake.
+    pub fn cancel(&mut self, game_id: u32) {
+        let mut game = self.game(game_id);
+        if self.env().caller() != game.first.player {
+            self.env().revert(Error::NotCreator);
+        }
+        self.assert_status(&game, GameStatus::WaitingForOpponent);
+
+        game.status = GameStatus::Cancelled;
+        self.pay(&game.first.player, game.stake);
+        self.games.set(&game_id, game);
+        self.env().emit_event(GameCancelled { game_id });
+    }
+
+    /// Reveals the caller's move, with the salt it was committed with. Once both moves are
+    /// revealed, the winner takes the pot, or both stakes are refunded on a draw.
+    pub fn reveal(&mut self, game_id: u32, choice: Move, salt: Bytes) {
+        let mut game = self.game(game_id);
+        self.assert_status(&game, GameStatus::Revealing);
+        if self.env().get_block_time() >= game.reveal_deadline {
+            self.env().revert(Error::RevealPeriodOver);
+        }
+
+        let player = self.env().caller();
+        let commitment = self.commitment(&player, choice, &salt);
+        let hand = if player == game.first.player {
+            &mut game.first
+        } else {
+            match game.second.as_mut() {
+                Some(hand) if player == hand.player => hand,
+                _ => self.env().revert(Error::NotPlayer),
+            }
+        };
+        if hand.revealed.is_some() {
+            self.env().revert(Error::AlreadyRevealed);
+        }
+        if commitment != hand.commitment {
+            self.env().revert(Error::InvalidReveal);
+        }
+        hand.revealed = Some(choice);
+        self.env().emit_event(MoveRevealed {
+            game_id,
+            player,
+            choice,
+        });
+
+        let both_revealed = game.first.revealed.is_some()
+            && matches!(&game.second, Some(hand) if hand.revealed.is_some());
+        if both_revealed {
+            self.settle_game(game_id, game);
+        } else {
+            self.games.set(&game_id, game);
+        }
+    }
+
+    /// Settles a game after the reveal period. A player who revealed takes the pot from one
+    /// who didn't, and if neither did, both stakes are refunded. Anyone can call it.
+    pub fn settle(&mut self, game_id: u32) {
+        let game = self.game(game_id);
+        self.assert_status(&game, GameStatus::Revealing);
+        if self.env().get_block_time() < game.reveal_deadline {
+            self.env().revert(Error::RevealPeriodNotOver);
+        }
+        self.settle_game(game_id, game);
+    }
+
+    /**********
+     * QUERIES
+     **********/
+
+    /// Returns a game.
+    pub fn get_game(&self, game_id: u32) -> Option<Game> {
+        self.games.get(&game_id)
+    }
+
+    /// Returns the number of games.
+    pub fn get_game_count(&self) -> u32 {
+        self.game_count.get_or_default()
+    }
+
+    /// Returns the time players have to reveal their moves.
+    pub fn get_reveal_period(&self) -> u64 {
+        self.reveal_period.get_or_default()
+    }
+}
+
+impl RockPaperScissors {
+    fn game(&self, game_id: u32) -> Game {
+        self.games
+            .get(&game_id)
+            .unwrap_or_revert_with(&self.env(), Error::GameNotFound)
+    }
+
+    fn assert_status(&self, game: &Game, status: GameStatus) {
+        if game.status != status {
+            self.env().revert(Error::InvalidState);
+        }
+    }
+
+    fn commitment(&self, player: &Address, choice: Move, salt: &Bytes) -> [u8; 32] {
+        // The player's address is part of the hash, so the opponent can't copy the commitment
+        let mut preimage = player.to_bytes().unwrap_or_revert(&self.env());
+        preimage.extend(choice.to_bytes().unwrap_or_revert(&self.env()));
+        preimage.extend(salt.iter());
+        self.env().hash(preimage)
+    }
+
+    fn settle_game(&mut self, game_id: u32, mut game: Game) {
+        let second = game.second.clone().unwrap_or_revert(&self.env());
+        let winner = match (game.first.revealed, second.revealed) {
+            (Some(x), Some(y)) if x.beats(y) => Some(game.first.player),
+            (Some(x), Some(y)) if y.beats(x) => Some(second.player),
+            // A player who didn't reveal forfeits
+            (Some(_), None) => Some(game.first.player),
+            (None, Some(_)) => Some(second.player),
+            _ => None,
+        };
+
+        match winner {
+            Some(winner) => self.pay(&winner, game.stake * 2),
+            None => {
+                self.pay(&game.first.player, game.stake);
+                self.pay(&second.player, game.stake);
+            }
+        }
+        game.status = GameStatus::Settled;
+        game.winner = winner;
+        self.games.set(&game_id, game);
+        self.env().emit_event(GameSettled { game_id, winner });
+    }
+
+    fn pay(&self, account: &Address, amount: U512) {
+        // Games without a stake have nothing to pay
+        if !amount.is_zero() {
+            self.env().transfer_tokens(account, &amount);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use odra::casper_types::crypto::blake2b;
+    use odra::host::{Deployer, HostEnv, HostRef};
+
+    const REVEAL_PERIOD: u64 = 1_000;
+    const STAKE: u64 = 100;
+    const SALT: &[u8] = b"a secret salt";
+
+    fn commitment(player: Address, choice: Move) -> [u8; 32] {
+        let mut preimage = player.to_bytes().unwrap();
+        preimage.extend(choice.to_bytes().unwrap());
+        preimage.extend(SALT);
+        blake2b(preimage)
+    }
+
+    /// Starts a game where account 1 commits to `first` and account 2 to `second`.
+    fn setup(env: &HostEnv, first: Move, second: Move) -> (RockPaperScissorsHostRef, u32) {
+        let mut contract = RockPaperScissorsHostRef::deploy(
+            env,
+            RockPaperScissorsInitArgs {
+                reveal_period: REVEAL_PERIOD,
+            },
+        );
+        let (alice, bob) = (env.get_account(1), env.get_account(2));
+        env.set_caller(alice);
+        let id = contract
+            .with_tokens(U512::from(STAKE))
+            .create_game(commitment(alice, first));
+        env.set_caller(bob);
+        contract
+            .with_tokens(U512::from(STAKE))
+            .join(id, commitment(bob, second));
+        (contract, id)
+    }
+
+    fn reveal(
+        env: &HostEnv,
+        contract: &mut RockPaperScissorsHostRef,
+        id: u32,
+        account: usize,
+        choice: Move,
+    ) {
+        env.set_caller(env.get_account(account));
+        contract.reveal(id, choice, Bytes::from(SALT.to_vec()));
+    }
+
+    #[test]
+    fn winner_takes_the_pot() {
+        let env = odra_test::env();
+        let (mut contract, id) = setup(&env, Move::Rock, Move::Scissors);
+        let (alice, bob) = (env.get_account(1), env.get_account(2));
+        let salt = Bytes::from(SALT.to_vec());
+
+        env.set_caller(env.get_account(3));
+        assert_eq!(
+            contract.try_reveal(id, Move::Rock, salt.clone()),
+            Err(Error::NotPlayer.into())
+        );
+        env.set_caller(alice);
+        assert_eq!(
+            contract.try_reveal(id, Move::Paper, salt.clone()),
+            Err(Error::InvalidReveal.into())
+        );
+        assert_eq!(
+            contract.try_reveal(id, Move::Rock, Bytes::from(b"another salt".to_vec())),
+            Err(Error::InvalidReveal.into())
+        );
+        contract.reveal(id, Move::Rock, salt.clone());
+        assert_eq!(
+            contract.try_reveal(id, Move::Rock, salt),
+            Err(Error::AlreadyRevealed.into())
+        );
+        assert_eq!(
+            contract.try_settle(id),
+            Err(Error::RevealPeriodNotOver.into())
+        );
+
+        let balance = env.balance_of(&alice);
+        reveal(&env, &mut contract, id, 2, Move::Scissors);
+        let game = contract.get_game(id).unwrap();
+        assert_eq!(game.status, GameStatus::Settled);
+        assert_eq!(game.winner, Some(alice));
+        assert_eq!(env.balance_of(&alice), balance + U512::from(2 * STAKE));
+        assert_eq!(env.balance_of(contract.address()), U512::zero());
+
+        env.set_caller(bob);
+        assert_eq!(contract.try_settle(id), Err(Error::InvalidState.into()));
+    }
+
+    #[test]
+    fn every_combination() {
+        let env = odra_test::env();
+        let moves = [Move::Rock, Move::Paper, Move::Scissors];
+        for (i, first) in moves.into_iter().enumerate() {
+            for (j, second) in moves.into_iter().enumerate() {
+                let (mut contract, id) = setup(&env, first, second);
+                reveal(&env, &mut contract, id, 1, first);
+                reveal(&env, &mut contract, id, 2, second);
+
+                // Each move beats the one before it, cyclically
+                let winner = match (j + 3 - i) % 3 {
+                    0 => None,
+                    1 => Some(env.get_account(2)),
+                    _ => Some(env.get_account(1)),
+                };
+                assert_eq!(contract.get_game(id).unwrap().winner, winner);
+            }
+        }
+    }
+
+    #[test]
+    fn draw_refunds_both_stakes() {
+        let env = odra_test::env();
+        let (mut contract, id) = setup(&env, Move::Paper, Move::Paper);
+        let (alice, bob) = (env.get_account(1), env.get_account(2));
+        let (alice_balance, bob_balance) = (env.balance_of(&alice), env.balance_of(&bob));
+
+        reveal(&env, &mut contract, id, 1, Move::Paper);
+        reveal(&env, &mut contract, id, 2, Move::Paper);
+        assert_eq!(contract.get_game(id).unwrap().winner, None);
+        assert_eq!(env.balance_of(&alice), alice_balance + U512::from(STAKE));
+        assert_eq!(env.balance_of(&bob), bob_balance + U512::from(STAKE));
+    }
+
+    #[test]
+    fn non_revealer_forfeits() {
+        let env = odra_test::env();
+        let (mut contract, id) = setup(&env, Move::Scissors, Move::Rock);
+        let bob = env.get_account(2);
+
+        // Alice sees that she lost, and doesn't reveal
+        reveal(&env, &mut contract, id, 2, Move::Rock);
+        env.advance_block_time(REVEAL_PERIOD);
+        env.set_caller(env.get_account(1));
+        assert_eq!(
+            contract.try_reveal(id, Move::Scissors, Bytes::from(SALT.to_vec())),
+            Err(Error::RevealPeriodOver.into())
+        );
+
+        let balance = env.balance_of(&bob);
+        env.set_caller(env.get_account(3));
+        contract.settle(id);
+        assert_eq!(contract.get_game(id).unwrap().winner, Some(bob));
+        assert_eq!(env.balance_of(&bob), balance + U512::from(2 * STAKE));
+    }
+
+    #[test]
+    fn nobody_revealed() {
+        let env = odra_test::env();
+        let (mut contract, id) = setup(&env, Move::Rock, Move::Paper);
+        let (alice, bob) = (env.get_account(1), env.get_account(2));
+        let (alice_balance, bob_balance) = (env.balance_of(&alice), env.balance_of(&bob));
+
+        env.advance_block_time(REVEAL_PERIOD);
+        contract.settle(id);
+        let game = contract.get_game(id).unwrap();
+        assert_eq!(game.status, GameStatus::Settled);
+        assert_eq!(game.winner, None);
+        assert_eq!(env.balance_of(&alice), alice_balance + U512::from(STAKE));
+        assert_eq!(env.balance_of(&bob), bob_balance + U512::from(STAKE));
+    }
+
+    #[test]
+    fn copied_commitment_cannot_be_revealed() {
+        let env = odra_test::env();
+        let mut contract = RockPaperScissorsHostRef::deploy(
+            &env,
+            RockPaperScissorsInitArgs {
+                reveal_period: REVEAL_PERIOD,
+            },
+        );
+        let (alice, bob) = (env.get_account(1), env.get_account(2));
+        env.set_caller(alice);
+        let id = contract
+            .with_tokens(U512::from(STAKE))
+            .create_game(commitment(alice, Move::Rock));
+
+        // Bob commits to Alice's hash, and replays her reveal to force a draw
+        env.set_caller(bob);
+        contract
+            .with_tokens(U512::from(STAKE))
+            .join(id, commitment(alice, Move::Rock));
+        reveal(&env, &mut contract, id, 1, Move::Rock);
+        env.set_caller(bob);
+        assert_eq!(
+            contract.try_reveal(id, Move::Rock, Bytes::from(SALT.to_vec())),
+            Err(Error::InvalidReveal.into())
+        );
+
+        env.advance_block_time(REVEAL_PERIOD);
+        contract.settle(id);
+        assert_eq!(contract.get_game(id).unwrap().winner, Some(alice));
+    }
+
+    #[test]
+    fn joining_and_cancelling() {
+        let env = odra_test::env();
+        let mut contract = RockPaperScissorsHostRef::deploy(
+            &env,
+            RockPaperScissorsInitArgs {
+                reveal_period: REVEAL_PERIOD,
+            },
+        );
+        let (alice, bob) = (env.get_account(1), env.get_account(2));
+        assert_eq!(
+            contract.try_join(0, commitment(bob, Move::Rock)),
+            Err(Error::GameNotFound.into())
+        );
+
+        env.set_caller(alice);
+        let id = contract
+            .with_tokens(U512::from(STAKE))
+            .create_game(commitment(alice, Move::Rock));
+        assert_eq!(
+            contract
+                .with_tokens(U512::from(STAKE))
+                .try_join(id, commitment(alice, Move::Paper)),
+            Err(Error::CannotJoinOwnGame.into())
+        );
+        env.set_caller(bob);
+        assert_eq!(
+            contract
+                .with_tokens(U512::from(STAKE + 1))
+                .try_join(id, commitment(bob, Move::Paper)),
+            Err(Error::WrongStake.into())
+        );
+        assert_eq!(
+            contract.try_reveal(id, Move::Paper, Bytes::from(SALT.to_vec())),
+            Err(Error::InvalidState.into())
+        );
+        assert_eq!(contract.try_cancel(id), Err(Error::NotCreator.into()));
+
+        env.set_caller(alice);
+        let balance = env.balance_of(&alice);
+        contract.cancel(id);
+        assert_eq!(env.balance_of(&alice), balance + U512::from(STAKE));
+        env.set_caller(bob);
+        assert_eq!(
+            contract
+                .with_tokens(U512::from(STAKE))
+                .try_join(id, commitment(bob, Move::Paper)),
+            Err(Error::InvalidState.into())
+        );
+    }
+}
@@ -0,0 +1,214 @@
+use odra::casper_types::bytesrepr::{Bytes, ToBytes};
+use odra::casper_types::{PublicKey, U512};
+use odra::prelude::*;
+use odra::{Address, Mapping, Var};
+
+#[odra::odra_error]
+/// Errors that may occur during the contract execution.
+pub enum Error {
+    /// The claim was signed for another contract.
+    WrongContract = 1,
+    /// Only the recipient of a claim can redeem it.
+    NotRecipient = 2,
+    /// Each nonce can be redeemed once.
+    ClaimAlreadyUsed = 3,
+    /// The signature doesn't match the claim and the signer's key.
+    InvalidSignature = 4,
+    /// The contract doesn't hold enough CSPR to pay the claim.
+    InsufficientFunds = 5,
+}
+
+#[odra::odra_type]
+/// What the signer signs: a permission for `recipient` to take `amount` from `contract`.
+pub struct Claim {
+    /// The contract the claim is for, so it can't be replayed against another one.
+    pub contract: Address,
+    pub recipient: Address,
+    pub amount: U512,
+    /// Makes each claim unique, so it can only be redeemed once.
+    pub nonce: u64,
+}
+
+#[odra::event]
+/// Emitted when a claim is paid out.
+pub struct ClaimPaid {
+    pub recipient: Address,
+    pub amount: U512,
+    pub nonce: u64,
+}
+
+#[odra::module(events = [ClaimPaid], errors = Error)]
+/// Pays out CSPR to whoever presents a claim signed by the signer. The signer only signs
+/// off chain, and never sends a transaction.
+pub struct SignedClaims {
+    /// Public key whose signatures the contract accepts.
+    signer: Var<PublicKey>,
+    /// Nonces of the claims already paid.
+    used_nonces: Mapping<u64, bool>,
+}
+
+#[odra::module]
+impl SignedClaims {
+    /// Initializes the contract with the public key of the signer, ed25519 or secp256k1.
+    pub fn init(&mut self, signer: PublicKey) {
+        self.signer.set(signer);
+    }
+
+    /**********
+     * TRANSACTIONS
+     **********/
+
+    /// Adds the attached CSPR to the funds claims are paid from.
+    #[odra(payable)]
+    pub fn fund(&mut self) {}
+
+    /// Pays out `claim` to the caller, who must be its recipient. `signature` is the signer's
+    /// signature of the serialized claim.
+    pub fn claim(&mut self, claim: Claim, signature: Bytes) {
+        if claim.contract != self.env().self_address() {
+            self.env().revert(Error::WrongContract);
+        }
+        if claim.recipient != self.env().caller() {
+            self.env().revert(Error::NotRecipient);
+        }
+        if self.is_used(claim.nonce) {
+            self.env().revert(Error::ClaimAlreadyUsed);
+        }
+        let message = Bytes::from(claim.to_bytes().unwrap_or_revert(&self.env()));
+        if !self
+            .env()
+            .verify_signature(&message, &signature, &self.signer())
+        {
+            self.env().revert(Error::InvalidSignature);
+        }
+        if claim.amount > self.env().self_balance() {
+            self.env().revert(Error::InsufficientFunds);
+        }
+
+        self.used_nonces.set(&claim.nonce, true);
+        self.env().transfer_tokens(&claim.recipient, &claim.amount);
+        self.env().emit_event(ClaimPaid {
+            recipient: claim.recipient,
+            amount: claim.amount,
+            nonce: claim.nonce,
+        });
+    }
+
+    /**********
+     * QUERIES
+     **********/
+
+    pub fn signer(&self) -> PublicKey {
+        self.signer.get().unwrap_or_revert(&self.env())
+    }
+
+    pub fn is_used(&self, nonce: u64) -> bool {
+        self.used_nonces.get_or_default(&nonce)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use odra::host::{Deployer, HostRef};
+
+    #[test]
+    fn claims_need_the_signature() {
+        let env = odra_test::env();
+        let (signer, alice, bob) = (env.get_account(0), env.get_account(1), env.get_account(2));
+        let mut claims = SignedClaimsHostRef::deploy(
+            &env,
+            SignedClaimsInitArgs {
+                signer: env.public_key(&signer),
+            },
+        );
+        claims.with_tokens(U512::from(1_000)).fund();
+
+        let claim = Claim {
+            contract: *claims.address(),
+            recipient: alice,
+            amount: U512::from(300),
+            nonce: 1,
+        };
+        let sign = |claim: &Claim, by: Address| {
+            env.sign_message(&Bytes::from(claim.to_bytes().unwrap()), &by)
+        };
+        let signature = sign(&claim, signer);
+
+        // Only the recipient can redeem it, and only with the signer's signature
+        env.set_caller(bob);
+        assert_eq!(
+            claims.try_claim(claim.clone(), signature.clone()),
+            Err(Error::NotRecipient.into())
+        );
+        env.set_caller(alice);
+        assert_eq!(
+            claims.try_claim(claim.clone(), sign(&claim, alice)),
+            Err(Error::InvalidSignature.into())
+        );
+        // Changing the signed claim breaks the signature
+        let inflated = Claim {
+            amount: U512::from(900),
+            ..claim.clone()
+        };
+        assert_eq!(
+            claims.try_claim(inflated, signature.clone()),
+            Err(Error::InvalidSignature.into())
+        );
+
+        let balance = env.balance_of(&alice);
+        claims.claim(claim.clone(), signature.clone());
+        assert_eq!(env.balance_of(&alice), balance + U512::from(300));
+        assert!(claims.is_used(1));
+        assert!(env.emitted_event(
+            claims.address(),
+            &ClaimPaid {
+                recipient: alice,
+                amount: U512::from(300),
+                nonce: 1,
+            }
+        ));
+        assert_eq!(
+            claims.try_claim(claim, signature),
+            Err(Error::ClaimAlreadyUsed.into())
+        );
+    }
+
+    #[test]
+    fn claims_are_bound_to_the_contract() {
+        let env = odra_test::env();
+        let signer = env.get_account(0);
+        let alice = env.get_account(1);
+        let init_args = || SignedClaimsInitArgs {
+            signer: env.public_key(&signer),
+        };
+        let mut claims = SignedClaimsHostRef::deploy(&env, init_args());
+        let other = SignedClaimsHostRef::deploy(&env, init_args());
+        claims.with_tokens(U512::from(100)).fund();
+
+        // A claim signed for another contract of the same signer can't be replayed here
+        let claim = Claim {
+            contract: *other.address(),
+            recipient: alice,
+            amount: U512::from(100),
+            nonce: 1,
+        };
+        let signature = env.sign_message(&Bytes::from(claim.to_bytes().unwrap()), &signer);
+        env.set_caller(alice);
+        assert_eq!(
+            claims.try_claim(claim.clone(), signature.clone()),
+            Err(Error::WrongContract.into())
+        );
+
+        let claim = Claim {
+            contract: *claims.address(),
+            amount: U512::from(101),
+            ..claim
+        };
+        let signature = env.sign_message(&Bytes::from(claim.to_bytes().unwrap()), &signer);
+        assert_eq!(
+            claims.try_claim(claim, signature),
+            Err(Error::InsufficientFunds.into())
+        );
+    }
+}
@@ -0,0 +1,94 @@
+use odra::casper_types::bytesrepr::Bytes;
+use odra::casper_types::PublicKey;
+use odra::prelude::*;
+use odra::Address;
+
+#[odra::module]
+/// Checks signatures of arbitrary messages. Works with both key types Casper accounts use,
+/// ed25519 and secp256k1.
+pub struct Verifier;
+
+#[odra::module]
+impl Verifier {
+    /// Returns whether `signature` is a signature of `message` by the owner of `public_key`.
+    ///
+    /// The signature is serialized with its algorithm tag, the way `casper_types` does it.
+    pub fn verify(&self, message: Bytes, signature: Bytes, public_key: PublicKey) -> bool {
+        self.env()
+            .verify_signature(&message, &signature, &public_key)
+    }
+
+    /// Returns whether `signature` is a signature of `message` by the account `signer`, whose
+    /// public key is `public_key`.
+    pub fn verify_account(
+        &self,
+        message: Bytes,
+        signature: Bytes,
+        public_key: PublicKey,
+        signer: Address,
+    ) -> bool {
+        // An account's address is the hash of its public key
+        Address::from(public_key.clone()) == signer && self.verify(message, signature, public_key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use odra::casper_types::bytesrepr::ToBytes;
+    use odra::casper_types::crypto::{sign, SecretKey};
+    use odra::host::{Deployer, NoArgs};
+
+    #[test]
+    fn ed25519_signatures() {
+        let env = odra_test::env();
+        let verifier = VerifierHostRef::deploy(&env, NoArgs);
+        let (alice, bob) = (env.get_account(1), env.get_account(2));
+        let message = Bytes::from(b"Pay Bob 100 CSPR".to_vec());
+
+        // The test accounts have ed25519 keys
+        let signature = env.sign_message(&message, &alice);
+        let alice_key = env.public_key(&alice);
+        assert!(verifier.verify(message.clone(), signature.clone(), alice_key.clone()));
+        assert!(verifier.verify_account(
+            message.clone(),
+            signature.clone(),
+            alice_key.clone(),
+            alice
+        ));
+        assert!(!verifier.verify_account(
+            message.clone(),
+            signature.clone(),
+            alice_key.clone(),
+            bob
+        ));
+
+        // Neither another key nor another message passes
+        assert!(!verifier.verify(message, signature.clone(), env.public_key(&bob)));
+        let tampered = Bytes::from(b"Pay Bob 900 CSPR".to_vec());
+        assert!(!verifier.verify(tampered, signature, alice_key));
+    }
+
+    #[test]
+    fn secp256k1_signatures() {
+        let env = odra_test::env();
+        let verifier = VerifierHostRef::deploy(&env, NoArgs);
+        let message = Bytes::from(b"Pay Bob 100 CSPR".to_vec());
+
+        // A key generated off chain, e.g. by a wallet
+        let secret_key = SecretKey::secp256k1_from_bytes([7u8; 32]).unwrap();
+        let public_key = PublicKey::from(&secret_key);
+        let signature = sign(&message, &secret_key, &public_key);
+        let signature = Bytes::from(signature.to_bytes().unwrap());
+        assert!(verifier.verify(message.clone(), signature.clone(), public_key.clone()));
+        assert!(verifier.verify_account(
+            message.clone(),
+            signature.clone(),
+            public_key.clone(),
+            Address::from(public_key.clone())
+        ));
+
+        let other_key = PublicKey::from(&SecretKey::secp256k1_from_bytes([8u8; 32]).unwrap());
+        assert!(!verifier.verify(message, signature, other_key));
+    }
+}
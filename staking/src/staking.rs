@@ -0,0 +1,443 @@
+use odra::casper_types::{U256, U512};
+use odra::module::SubModule;
+use odra::prelude::*;
+use odra::{Address, ContractRef, Mapping, Var};
+use odra_modules::access::Ownable;
+use odra_modules::cep18_token::Cep18ContractRef;
+
+/// Scale of `acc_reward_per_share`, so rewards smaller than the total stake aren't rounded away.
+const PRECISION: u64 = 1_000_000_000_000;
+
+#[odra::odra_error]
+/// Errors that may occur during the contract execution.
+pub enum Error {
+    /// Staked, unstaked and funded amounts must be positive.
+    ZeroAmount = 1,
+    /// The attached CSPR doesn't match the amount.
+    IncorrectDepositAmount = 2,
+    /// The caller hasn't staked that much.
+    InsufficientStake = 3,
+    /// The caller has no rewards to claim.
+    NothingToClaim = 4,
+    /// The amount doesn't fit into a CEP-18 token amount.
+    AmountTooLarge = 5,
+}
+
+#[odra::event]
+/// Emitted when a user stakes.
+pub struct Staked {
+    pub staker: Address,
+    pub amount: U512,
+}
+
+#[odra::event]
+/// Emitted when a user withdraws their stake.
+pub struct Unstaked {
+    pub staker: Address,
+    pub amount: U512,
+}
+
+#[odra::event]
+/// Emitted when a user claims their rewards.
+pub struct RewardsClaimed {
+    pub staker: Address,
+    pub amount: U512,
+}
+
+#[odra::event]
+/// Emitted when the owner tops up the reward pool.
+pub struct RewardsFunded {
+    pub amount: U512,
+}
+
+#[odra::module(events = [Staked, Unstaked, RewardsClaimed, RewardsFunded], errors = Error)]
+/// Users stake CSPR or a CEP-18 token and share the rewards paid out of an owner-funded pool.
+pub struct Staking {
+    /// CEP-18 token that is staked and paid out (None for CSPR).
+    token: Var<Option<Address>>,
+    /// Rewards distributed among all stakers per millisecond.
+    reward_rate: Var<U512>,
+    /// Rewards funded by the owner that haven't been distributed yet.
+    reward_pool: Var<U512>,
+    /// Sum of all stakes.
+    total_staked: Var<U512>,
+    /// Rewards earned by a unit of stake since deployment, scaled by `PRECISION`.
+    acc_reward_per_share: Var<U512>,
+    /// Block time the rewards were last distributed at.
+    last_update: Var<u64>,
+    /// Stake of each user.
+    stakes: Mapping<Address, U512>,
+    /// Part of `stake * acc_reward_per_share` that was already settled for each user.
+    reward_debt: Mapping<Address, U512>,
+    /// Settled rewards each user hasn't claimed yet.
+    unclaimed: Mapping<Address, U512>,
+    ownable: SubModule<Ownable>,
+}
+
+#[odra::module]
+impl Staking {
+    /// Initializes the contract with the staked currency and the reward rate; the deployer becomes the owner.
+    pub fn init(&mut self, token: Option<Address>, reward_rate: U512) {
+        self.token.set(token);
+        self.reward_rate.set(reward_rate);
+        self.last_update.set(self.env().get_block_time());
+        self.ownable.init();
+    }
+
+    /**********
+     * TRANSACTIONS
+     **********/
+
+    /// Stakes `amount`, attached as CSPR or approved beforehand for CEP-18 tokens.
+    #[odra(payable)]
+    pub fn stake(&mut self, amount: U512) {
+        if amount.is_zero() {
+            self.env().revert(Error::ZeroAmount);
+        }
+        let staker = self.env().caller();
+        self.receive_funds(&staker, amount);
+
+        self.update_pool();
+        self.settle(staker);
+        let stake = self.stakes.get_or_default(&staker) + amount;
+        self.set_stake(staker, stake);
+        self.total_staked.add(amount);
+        self.env().emit_event(Staked { staker, amount });
+    }
+
+    /// Withdraws `amount` of the caller's stake; rewards earned so far stay claimable.
+    pub fn unstake(&mut self, amount: U512) {
+        if amount.is_zero() {
+            self.env().revert(Error::ZeroAmount);
+        }
+        let staker = self.env().caller();
+        let stake = self.stakes.get_or_default(&staker);
+        if amount > stake {
+            self.env().revert(Error::InsufficientStake);
+        }
+
+        self.update_pool();
+        self.settle(staker);
+        self.set_stake(staker, stake - amount);
+        self.total_staked.subtract(amount);
+        self.transfer_funds(&staker, amount);
+        self.env().emit_event(Unstaked { staker, amount });
+    }
+
+    /// Pays out all rewards the caller has earned so far.
+    pub fn claim(&mut self) {
+        let staker = self.env().caller();
+        self.update_pool();
+        self.settle(staker);
+
+        let amount = self.unclaimed.get_or_default(&staker);
+        if amount.is_zero() {
+            self.env().revert(Error::NothingToClaim);
+        }
+        self.unclaimed.set(&staker, U512::zero()); // Clear before transferring
+        self.transfer_funds(&staker, amount);
+        self.env().emit_event(RewardsClaimed { staker, amount });
+    }
+
+    /// Adds `amount` to the reward pool. Only the owner can call it.
+    #[odra(payable)]
+    pub fn fund_rewards(&mut self, amount: U512) {
+        let caller = self.env().caller();
+        self.ownable.assert_owner(&caller);
+        if amount.is_zero() {
+            self.env().revert(Error::ZeroAmount);
+        }
+        self.receive_funds(&caller, amount);
+
+        // Rewards for the time the pool was empty are not paid retroactively
+        self.update_pool();
+        self.reward_pool.add(amount);
+        self.env().emit_event(RewardsFunded { amount });
+    }
+
+    /// Changes the reward rate from now on. Only the owner can call it.
+    pub fn set_reward_rate(&mut self, reward_rate: U512) {
+        self.ownable.assert_owner(&self.env().caller());
+        self.update_pool();
+        self.reward_rate.set(reward_rate);
+    }
+
+    /**********
+     * QUERIES
+     **********/
+
+    /// Returns the user's stake.
+    pub fn stake_of(&self, staker: Address) -> U512 {
+        self.stakes.get_or_default(&staker)
+    }
+
+    /// Returns the rewards the user could claim right now.
+    pub fn pending_rewards(&self, staker: Address) -> U512 {
+        let (acc_reward_per_share, _) = self.current_pool();
+        self.unclaimed.get_or_default(&staker)
+            + self.stakes.get_or_default(&staker) * acc_reward_per_share / PRECISION
+            - self.reward_debt.get_or_default(&staker)
+    }
+
+    /// Returns the sum of all stakes.
+    pub fn total_staked(&self) -> U512 {
+        self.total_staked.get_or_default()
+    }
+
+    /// Returns the rewards that haven't been distributed yet.
+    pub fn reward_pool(&self) -> U512 {
+        self.current_pool().1
+    }
+
+    /// Returns the rewards distributed per millisecond.
+    pub fn reward_rate(&self) -> U512 {
+        self.reward_rate.get_or_default()
+    }
+
+    /**********
+     * INTERNAL
+     **********/
+
+    /// Returns the reward per share and the remaining pool as if they were updated now.
+    fn current_pool(&self) -> (U512, U512) {
+        let acc_reward_per_share = self.acc_reward_per_share.get_or_default();
+        let pool = self.reward_pool.get_or_default();
+        let total_staked = self.total_staked.get_or_default();
+        if total_staked.is_zero() {
+            return (acc_reward_per_share, pool);
+        }
+
+        // The pool never pays out more than it holds
+        let elapsed = self.env().get_block_time() - self.last_update.get_or_default();
+        let reward = (self.reward_rate.get_or_default() * U512::from(elapsed)).min(pool);
+        (
+            acc_reward_per_share + reward * PRECISION / total_staked,
+            pool - reward,
+        )
+    }
+
+    /// Distributes the rewards earned since the last update among the current stakes.
+    fn update_pool(&mut self) {
+        let (acc_reward_per_share, pool) = self.current_pool();
+        self.acc_reward_per_share.set(acc_reward_per_share);
+        self.reward_pool.set(pool);
+        self.last_update.set(self.env().get_block_time());
+    }
+
+    /// Moves the rewards the user earned since their last settlement to `unclaimed`.
+    fn settle(&mut self, staker: Address) {
+        let earned = self.stakes.get_or_default(&staker)
+            * self.acc_reward_per_share.get_or_default()
+            / PRECISION
+            - self.reward_debt.get_or_default(&staker);
+        if !earned.is_zero() {
+            self.unclaimed
+                .set(&staker, self.unclaimed.get_or_default(&staker) + earned);
+        }
+    }
+
+    /// Stores the new stake; rewards up to now count as already settled.
+    fn set_stake(&mut self, staker: Address, stake: U512) {
+        self.stakes.set(&staker, stake);
+        self.reward_debt.set(
+            &staker,
+            stake * self.acc_reward_per_share.get_or_default() / PRECISION,
+        );
+    }
+
+    /// Takes `amount` from the caller, either attached CSPR or approved CEP-18 tokens.
+    fn receive_funds(&self, from: &Address, amount: U512) {
+        match self.token.get_or_default() {
+            Some(token) => {
+                if !self.env().attached_value().is_zero() {
+                    self.env().revert(Error::IncorrectDepositAmount);
+                }
+                Cep18ContractRef::new(self.env(), token).transfer_from(
+                    from,
+                    &self.env().self_address(),
+                    &self.to_token_amount(amount),
+                );
+            }
+            None => {
+                if self.env().attached_value() != amount {
+                    self.env().revert(Error::IncorrectDepositAmount);
+                }
+            }
+        }
+    }
+
+    /// Pays out either CSPR or the configured CEP-18 token.
+    fn transfer_funds(&self, to: &Address, amount: U512) {
+        match self.token.get_or_default() {
+            Some(token) => {
+                Cep18ContractRef::new(self.env(), token)
+                    .transfer(to, &self.to_token_amount(amount));
+            }
+            None => self.env().transfer_tokens(to, &amount),
+        }
+    }
+
+    /// Converts an amount to a CEP-18 token amount, reverting if it doesn't fit.
+    fn to_token_amount(&self, amount: U512) -> U256 {
+        if amount.bits() > 256 {
+            self.env().revert(Error::AmountTooLarge);
+        }
+        let mut bytes = [0u8; 64];
+        amount.to_little_endian(&mut bytes);
+        U256::from_little_endian(&bytes[..32])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use odra::host::{Deployer, HostEnv, HostRef};
+    use odra_modules::cep18_token::{Cep18HostRef, Cep18InitArgs};
+
+    /// Deploys a CSPR staking contract distributing 10 motes per millisecond from a funded pool.
+    fn setup(env: &HostEnv, pool: u64) -> StakingHostRef {
+        let mut staking = StakingHostRef::deploy(
+            env,
+            StakingInitArgs {
+                token: None,
+                reward_rate: U512::from(10),
+            },
+        );
+        staking
+            .with_tokens(U512::from(pool))
+            .fund_rewards(U512::from(pool));
+        staking
+    }
+
+    #[test]
+    fn rewards_are_shared_by_stake() {
+        let env = odra_test::env();
+        let mut staking = setup(&env, 1_000_000);
+        let (alice, bob) = (env.get_account(1), env.get_account(2));
+
+        env.set_caller(alice);
+        staking.with_tokens(U512::from(100)).stake(U512::from(100));
+        env.advance_block_time(1_000);
+        assert_eq!(staking.pending_rewards(alice), U512::from(10_000));
+
+        // Bob's stake is three times Alice's, so he earns three quarters from now on
+        env.set_caller(bob);
+        staking.with_tokens(U512::from(300)).stake(U512::from(300));
+        env.advance_block_time(1_000);
+        assert_eq!(staking.pending_rewards(alice), U512::from(12_500));
+        assert_eq!(staking.pending_rewards(bob), U512::from(7_500));
+        assert_eq!(staking.total_staked(), U512::from(400));
+
+        env.set_caller(alice);
+        let balance = env.balance_of(&alice);
+        staking.claim();
+        assert_eq!(env.balance_of(&alice), balance + U512::from(12_500));
+        assert!(env.emitted_event(
+            staking.address(),
+            &RewardsClaimed {
+                staker: alice,
+                amount: U512::from(12_500)
+            }
+        ));
+        assert_eq!(staking.try_claim(), Err(Error::NothingToClaim.into()));
+    }
+
+    #[test]
+    fn unstaking_keeps_earned_rewards() {
+        let env = odra_test::env();
+        let mut staking = setup(&env, 1_000_000);
+        let alice = env.get_account(1);
+
+        env.set_caller(alice);
+        staking.with_tokens(U512::from(100)).stake(U512::from(100));
+        env.advance_block_time(1_000);
+        assert_eq!(
+            staking.try_unstake(U512::from(101)),
+            Err(Error::InsufficientStake.into())
+        );
+        let balance = env.balance_of(&alice);
+        staking.unstake(U512::from(100));
+        assert_eq!(env.balance_of(&alice), balance + U512::from(100));
+        assert_eq!(staking.stake_of(alice), U512::zero());
+
+        // Nothing accrues without a stake
+        env.advance_block_time(1_000);
+        assert_eq!(staking.pending_rewards(alice), U512::from(10_000));
+        assert_eq!(staking.reward_pool(), U512::from(990_000));
+    }
+
+    #[test]
+    fn rewards_are_capped_by_the_pool() {
+        let env = odra_test::env();
+        let mut staking = setup(&env, 5_000);
+        let alice = env.get_account(1);
+
+        env.set_caller(alice);
+        staking.with_tokens(U512::from(100)).stake(U512::from(100));
+        env.advance_block_time(1_000);
+        assert_eq!(staking.pending_rewards(alice), U512::from(5_000));
+        assert_eq!(staking.reward_pool(), U512::zero());
+    }
+
+    #[test]
+    fn invalid_calls() {
+        let env = odra_test::env();
+        let mut staking = setup(&env, 1_000);
+
+        env.set_caller(env.get_account(1));
+        assert!(staking
+            .with_tokens(U512::from(100))
+            .try_fund_rewards(U512::from(100))
+            .is_err());
+        assert!(staking.try_set_reward_rate(U512::from(1)).is_err());
+        assert_eq!(
+            staking.try_stake(U512::zero()),
+            Err(Error::ZeroAmount.into())
+        );
+        assert_eq!(
+            staking
+                .with_tokens(U512::from(99))
+                .try_stake(U512::from(100)),
+            Err(Error::IncorrectDepositAmount.into())
+        );
+    }
+
+    #[test]
+    fn token_staking() {
+        let env = odra_test::env();
+        let (owner, alice) = (env.get_account(0), env.get_account(1));
+        let mut token = Cep18HostRef::deploy(
+            &env,
+            Cep18InitArgs {
+                symbol: "STK".to_string(),
+                name: "Staked Token".to_string(),
+                decimals: 9,
+                initial_supply: U256::from(20_000),
+                admin_list: vec![],
+                minter_list: vec![],
+                modality: None,
+            },
+        );
+        let mut staking = StakingHostRef::deploy(
+            &env,
+            StakingInitArgs {
+                token: Some(*token.address()),
+                reward_rate: U512::from(1),
+            },
+        );
+        token.transfer(&alice, &U256::from(1_000));
+
+        // The owner funds the pool with an approved transfer
+        token.approve(staking.address(), &U256::from(10_000));
+        staking.fund_rewards(U512::from(10_000));
+
+        env.set_caller(alice);
+        token.approve(staking.address(), &U256::from(1_000));
+        staking.stake(U512::from(1_000));
+        env.advance_block_time(500);
+        staking.claim();
+        staking.unstake(U512::from(1_000));
+        assert_eq!(token.balance_of(&alice), U256::from(1_500));
+        assert_eq!(token.balance_of(&owner), U256::from(9_000));
+    }
+}
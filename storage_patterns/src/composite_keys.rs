@@ -0,0 +1,78 @@
+use odra::casper_types::U256;
+use odra::prelude::*;
+use odra::{Address, Mapping};
+
+#[odra::event]
+/// Emitted when an owner sets a spender's allowance.
+pub struct Approval {
+    pub owner: Address,
+    pub spender: Address,
+    pub amount: U256,
+}
+
+#[odra::module(events = [Approval])]
+/// Allowances of spenders, stored under a key made of two addresses.
+pub struct Allowances {
+    /// Allowance of each (owner, spender) pair.
+    allowances: Mapping<(Address, Address), U256>,
+}
+
+#[odra::module]
+impl Allowances {
+    /// Lets `spender` spend up to `amount` on behalf of the caller, replacing any previous
+    /// allowance. An allowance of zero is the same as none.
+    pub fn approve(&mut self, spender: Address, amount: U256) {
+        let owner = self.env().caller();
+        self.allowances.set(&(owner, spender), amount);
+        self.env().emit_event(Approval {
+            owner,
+            spender,
+            amount,
+        });
+    }
+
+    pub fn allowance(&self, owner: Address, spender: Address) -> U256 {
+        self.allowances.get_or_default(&(owner, spender))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gas::{is_metered, last_call_gas};
+    use odra::host::{Deployer, NoArgs};
+
+    #[test]
+    fn keys_are_pairs() {
+        let env = odra_test::env();
+        let (alice, bob, carol) = (env.get_account(1), env.get_account(2), env.get_account(3));
+        let mut allowances = AllowancesHostRef::deploy(&env, NoArgs);
+
+        env.set_caller(alice);
+        allowances.approve(bob, U256::from(100));
+        allowances.approve(carol, U256::from(50));
+
+        // The order of the addresses matters
+        assert_eq!(allowances.allowance(alice, bob), U256::from(100));
+        assert_eq!(allowances.allowance(alice, carol), U256::from(50));
+        assert_eq!(allowances.allowance(bob, alice), U256::zero());
+
+        allowances.approve(bob, U256::from(70));
+        assert_eq!(allowances.allowance(alice, bob), U256::from(70));
+    }
+
+    #[test]
+    fn overwriting_costs_no_more_than_creating() {
+        if !is_metered() {
+            return;
+        }
+        let env = odra_test::env();
+        let mut allowances = AllowancesHostRef::deploy(&env, NoArgs);
+
+        env.set_caller(env.get_account(1));
+        allowances.approve(env.get_account(2), U256::from(100));
+        let create = last_call_gas(&allowances);
+        allowances.approve(env.get_account(2), U256::from(70));
+        assert!(last_call_gas(&allowances) <= create);
+    }
+}
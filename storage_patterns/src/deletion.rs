@@ -0,0 +1,109 @@
+use odra::prelude::*;
+use odra::{Address, Mapping, Var};
+
+#[odra::odra_error]
+/// Errors that may occur during the contract execution.
+pub enum Error {
+    /// The caller has no profile.
+    ProfileNotFound = 1,
+}
+
+#[odra::odra_type]
+pub struct Profile {
+    pub name: String,
+    pub bio: String,
+}
+
+#[odra::module(errors = Error)]
+/// User profiles that can be deleted. A `Mapping` can't remove an entry, so a deleted profile
+/// is overwritten with `None`.
+pub struct Profiles {
+    profiles: Mapping<Address, Option<Profile>>,
+    /// Number of profiles that exist, kept up to date on every write.
+    profile_count: Var<u32>,
+}
+
+#[odra::module]
+impl Profiles {
+    /// Creates or overwrites the caller's profile.
+    pub fn set_profile(&mut self, name: String, bio: String) {
+        let owner = self.env().caller();
+        if self.profile(owner).is_none() {
+            self.profile_count.set(self.profile_count() + 1);
+        }
+        self.profiles.set(&owner, Some(Profile { name, bio }));
+    }
+
+    /// Deletes the caller's profile.
+    pub fn delete_profile(&mut self) {
+        let owner = self.env().caller();
+        if self.profile(owner).is_none() {
+            self.env().revert(Error::ProfileNotFound);
+        }
+        self.profiles.set(&owner, None);
+        self.profile_count.set(self.profile_count() - 1);
+    }
+
+    pub fn profile(&self, owner: Address) -> Option<Profile> {
+        // `None` if the entry was never written, `Some(None)` if it was deleted
+        self.profiles.get(&owner).flatten()
+    }
+
+    pub fn profile_count(&self) -> u32 {
+        self.profile_count.get_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gas::{is_metered, last_call_gas};
+    use odra::host::{Deployer, NoArgs};
+
+    #[test]
+    fn deleting_and_overwriting() {
+        let env = odra_test::env();
+        let alice = env.get_account(1);
+        let mut profiles = ProfilesHostRef::deploy(&env, NoArgs);
+
+        env.set_caller(alice);
+        profiles.set_profile("Alice".to_string(), "Builds on Casper".to_string());
+        profiles.set_profile("Alice".to_string(), "Writes tutorials".to_string());
+        assert_eq!(profiles.profile_count(), 1);
+        assert_eq!(
+            profiles.profile(alice),
+            Some(Profile {
+                name: "Alice".to_string(),
+                bio: "Writes tutorials".to_string(),
+            })
+        );
+
+        profiles.delete_profile();
+        assert_eq!(profiles.profile(alice), None);
+        assert_eq!(profiles.profile_count(), 0);
+        assert_eq!(
+            profiles.try_delete_profile(),
+            Err(Error::ProfileNotFound.into())
+        );
+
+        // A deleted profile can be created again
+        profiles.set_profile("Alice".to_string(), "Is back".to_string());
+        assert_eq!(profiles.profile_count(), 1);
+    }
+
+    #[test]
+    fn deleting_costs_no_more_than_writing() {
+        if !is_metered() {
+            return;
+        }
+        let env = odra_test::env();
+        let mut profiles = ProfilesHostRef::deploy(&env, NoArgs);
+
+        env.set_caller(env.get_account(1));
+        profiles.set_profile("Alice".to_string(), "Builds on Casper".to_string());
+        let write = last_call_gas(&profiles);
+        // Deleting writes a `None`, which is smaller than a profile
+        profiles.delete_profile();
+        assert!(last_call_gas(&profiles) <= write);
+    }
+}
@@ -0,0 +1,84 @@
+use odra::prelude::*;
+use odra::{Address, Mapping};
+
+#[odra::event]
+/// Emitted when a ticket is issued.
+pub struct TicketIssued {
+    pub ticket_id: u32,
+    pub owner: Address,
+}
+
+#[odra::module(events = [TicketIssued])]
+/// Tickets numbered by a `Sequence`, which hands out IDs 0, 1, 2 and so on.
+pub struct Tickets {
+    ids: Sequence<u32>,
+    owners: Mapping<u32, Address>,
+}
+
+#[odra::module]
+impl Tickets {
+    /// Issues a ticket to the caller and returns its ID.
+    pub fn issue(&mut self) -> u32 {
+        let owner = self.env().caller();
+        let ticket_id = self.ids.next_value();
+        self.owners.set(&ticket_id, owner);
+        self.env().emit_event(TicketIssued { ticket_id, owner });
+        ticket_id
+    }
+
+    pub fn owner_of(&self, ticket_id: u32) -> Option<Address> {
+        self.owners.get(&ticket_id)
+    }
+
+    /// Returns the number of tickets issued.
+    pub fn ticket_count(&self) -> u32 {
+        // The current value is 0 both before and after the first ticket, so the first ticket
+        // tells them apart
+        if self.owners.get(&0).is_none() {
+            return 0;
+        }
+        self.ids.get_current_value() + 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gas::{is_metered, last_call_gas};
+    use odra::host::{Deployer, NoArgs};
+
+    #[test]
+    fn ids_count_from_zero() {
+        let env = odra_test::env();
+        let (alice, bob) = (env.get_account(1), env.get_account(2));
+        let mut tickets = TicketsHostRef::deploy(&env, NoArgs);
+        assert_eq!(tickets.ticket_count(), 0);
+
+        env.set_caller(alice);
+        assert_eq!(tickets.issue(), 0);
+        assert_eq!(tickets.ticket_count(), 1);
+
+        env.set_caller(bob);
+        assert_eq!(tickets.issue(), 1);
+        assert_eq!(tickets.issue(), 2);
+        assert_eq!(tickets.ticket_count(), 3);
+        assert_eq!(tickets.owner_of(0), Some(alice));
+        assert_eq!(tickets.owner_of(2), Some(bob));
+        assert_eq!(tickets.owner_of(3), None);
+    }
+
+    #[test]
+    fn later_ids_cost_no_more_than_the_first() {
+        if !is_metered() {
+            return;
+        }
+        let env = odra_test::env();
+        let mut tickets = TicketsHostRef::deploy(&env, NoArgs);
+
+        tickets.issue();
+        let first = last_call_gas(&tickets);
+        // Later tickets update the sequence instead of creating it
+        tickets.issue();
+        assert!(last_call_gas(&tickets) <= first);
+    }
+}
@@ -0,0 +1,114 @@
+use odra::module::SubModule;
+use odra::prelude::*;
+use odra::{Address, Mapping, Var};
+use odra_modules::access::Ownable;
+
+/// Credits every account starts with, without anything being stored for it.
+pub const WELCOME_CREDITS: u64 = 100;
+/// Fee charged per spend until the owner sets one.
+pub const DEFAULT_FEE: u64 = 1;
+
+#[odra::odra_error]
+/// Errors that may occur during the contract execution.
+pub enum Error {
+    /// The account doesn't have enough credits.
+    InsufficientCredits = 1,
+}
+
+#[odra::module(errors = Error)]
+/// Credits that every account has from the start. Instead of storing each balance, the
+/// contract stores what accounts have spent, which is zero for an account it has never seen.
+pub struct Credits {
+    ownable: SubModule<Ownable>,
+    /// Credits spent by each account, fees included.
+    spent: Mapping<Address, u64>,
+    /// Fee charged per spend, if the owner has set one.
+    fee: Var<u64>,
+}
+
+#[odra::module]
+impl Credits {
+    /// Initializes the contract; the deployer becomes the owner who sets the fee.
+    pub fn init(&mut self) {
+        self.ownable.init();
+    }
+
+    /// Sets the fee charged per spend. Only the owner can call it.
+    pub fn set_fee(&mut self, fee: u64) {
+        self.ownable.assert_owner(&self.env().caller());
+        self.fee.set(fee);
+    }
+
+    /// Spends `amount` of the caller's credits, plus the fee.
+    pub fn spend(&mut self, amount: u64) {
+        let owner = self.env().caller();
+        let cost = amount + self.fee();
+        if cost > self.credits(owner) {
+            self.env().revert(Error::InsufficientCredits);
+        }
+        self.spent
+            .set(&owner, self.spent.get_or_default(&owner) + cost);
+    }
+
+    /// Returns the credits `owner` has left.
+    pub fn credits(&self, owner: Address) -> u64 {
+        WELCOME_CREDITS - self.spent.get_or_default(&owner)
+    }
+
+    pub fn fee(&self) -> u64 {
+        self.fee.get().unwrap_or(DEFAULT_FEE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gas::{is_metered, last_call_gas};
+    use odra::host::{Deployer, NoArgs};
+
+    #[test]
+    fn defaults_are_not_stored() {
+        let env = odra_test::env();
+        let (owner, alice) = (env.get_account(0), env.get_account(1));
+        let mut credits = CreditsHostRef::deploy(&env, NoArgs);
+
+        // Nothing was stored, yet everyone has credits and there's a fee
+        assert_eq!(credits.credits(env.get_account(9)), WELCOME_CREDITS);
+        assert_eq!(credits.fee(), DEFAULT_FEE);
+
+        env.set_caller(alice);
+        credits.spend(10);
+        assert_eq!(credits.credits(alice), WELCOME_CREDITS - 10 - DEFAULT_FEE);
+        credits.spend(10);
+
+        assert!(credits.try_set_fee(5).is_err());
+        env.set_caller(owner);
+        credits.set_fee(5);
+        env.set_caller(alice);
+        credits.spend(10);
+        assert_eq!(
+            credits.credits(alice),
+            WELCOME_CREDITS - 30 - 2 * DEFAULT_FEE - 5
+        );
+        assert_eq!(
+            credits.try_spend(70),
+            Err(Error::InsufficientCredits.into())
+        );
+    }
+
+    #[test]
+    fn first_spend_costs_the_most() {
+        if !is_metered() {
+            return;
+        }
+        let env = odra_test::env();
+        let mut credits = CreditsHostRef::deploy(&env, NoArgs);
+
+        env.set_caller(env.get_account(1));
+        credits.spend(10);
+        let first_spend = last_call_gas(&credits);
+        // The balance is stored now, instead of created from the default
+        credits.spend(10);
+        assert!(last_call_gas(&credits) <= first_spend);
+    }
+}
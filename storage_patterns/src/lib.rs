@@ -0,0 +1,28 @@
+#![cfg_attr(not(test), no_std)]
+#![cfg_attr(not(test), no_main)]
+extern crate alloc;
+
+pub mod composite_keys;
+pub mod deletion;
+pub mod id_sequence;
+pub mod lazy_defaults;
+pub mod nested_modules;
+pub mod paginated_list;
+
+#[cfg(test)]
+mod gas {
+    use odra::host::HostRef;
+
+    /// Returns whether the tests run on the Casper VM, e.g. with `cargo odra test -b casper`.
+    ///
+    /// The OdraVM doesn't meter gas and reports zero for every call, so the tests comparing
+    /// costs only run on the Casper VM.
+    pub fn is_metered() -> bool {
+        std::env::var("ODRA_BACKEND").as_deref() == Ok("casper")
+    }
+
+    /// Returns the gas used by the last call to `contract`.
+    pub fn last_call_gas<R: HostRef>(contract: &R) -> u64 {
+        contract.last_call().callee_contract_gas_used()
+    }
+}
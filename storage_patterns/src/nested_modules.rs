@@ -0,0 +1,100 @@
+use odra::prelude::*;
+use odra::{Address, List, Mapping};
+
+#[odra::odra_type]
+pub struct Message {
+    pub from: Address,
+    pub text: String,
+}
+
+#[odra::module]
+/// The messages sent to a single address, oldest first.
+pub struct Inbox {
+    messages: List<Message>,
+}
+
+impl Inbox {
+    fn push(&mut self, message: Message) {
+        self.messages.push(message);
+    }
+
+    fn len(&self) -> u32 {
+        self.messages.len()
+    }
+
+    fn get(&self, index: u32) -> Option<Message> {
+        self.messages.get(index)
+    }
+}
+
+#[odra::module]
+/// An inbox for every address. Each key of the mapping gets a whole module, with its own
+/// `List`, instead of a single value.
+pub struct Inboxes {
+    inboxes: Mapping<Address, Inbox>,
+}
+
+#[odra::module]
+impl Inboxes {
+    /// Appends a message to the inbox of `to`.
+    pub fn send(&mut self, to: Address, text: String) {
+        let from = self.env().caller();
+        self.inboxes.module(&to).push(Message { from, text });
+    }
+
+    pub fn inbox_size(&self, owner: Address) -> u32 {
+        self.inboxes.module(&owner).len()
+    }
+
+    pub fn message(&self, owner: Address, index: u32) -> Option<Message> {
+        self.inboxes.module(&owner).get(index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gas::{is_metered, last_call_gas};
+    use odra::host::{Deployer, NoArgs};
+
+    #[test]
+    fn inboxes_are_separate() {
+        let env = odra_test::env();
+        let (alice, bob, carol) = (env.get_account(1), env.get_account(2), env.get_account(3));
+        let mut inboxes = InboxesHostRef::deploy(&env, NoArgs);
+
+        env.set_caller(alice);
+        inboxes.send(bob, "Hi Bob".to_string());
+        inboxes.send(bob, "Are you there?".to_string());
+        env.set_caller(carol);
+        inboxes.send(alice, "Hi Alice".to_string());
+
+        assert_eq!(inboxes.inbox_size(bob), 2);
+        assert_eq!(inboxes.inbox_size(alice), 1);
+        assert_eq!(inboxes.inbox_size(carol), 0);
+        assert_eq!(
+            inboxes.message(bob, 1),
+            Some(Message {
+                from: alice,
+                text: "Are you there?".to_string(),
+            })
+        );
+        assert_eq!(inboxes.message(bob, 2), None);
+    }
+
+    #[test]
+    fn first_message_costs_the_most() {
+        if !is_metered() {
+            return;
+        }
+        let env = odra_test::env();
+        let bob = env.get_account(2);
+        let mut inboxes = InboxesHostRef::deploy(&env, NoArgs);
+
+        env.set_caller(env.get_account(1));
+        inboxes.send(bob, "Hi Bob".to_string());
+        let new_inbox = last_call_gas(&inboxes);
+        inboxes.send(bob, "Hi again".to_string());
+        assert!(last_call_gas(&inboxes) <= new_inbox);
+    }
+}
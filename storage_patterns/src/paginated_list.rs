@@ -0,0 +1,178 @@
+use odra::prelude::*;
+use odra::{Address, List};
+
+/// Most entries returned by a single query.
+pub const MAX_PAGE_SIZE: u32 = 20;
+
+#[odra::odra_error]
+/// Errors that may occur during the contract execution.
+pub enum Error {
+    /// There's no entry at the given index.
+    EntryNotFound = 1,
+    /// Only the author of an entry can change it.
+    NotAuthor = 2,
+}
+
+#[odra::odra_type]
+pub struct Entry {
+    pub author: Address,
+    pub text: String,
+}
+
+#[odra::module(errors = Error)]
+/// A guestbook whose entries are read a page at a time.
+pub struct Guestbook {
+    entries: List<Entry>,
+}
+
+#[odra::module]
+impl Guestbook {
+    /**********
+     * TRANSACTIONS
+     **********/
+
+    /// Appends an entry and returns its index.
+    pub fn sign(&mut self, text: String) -> u32 {
+        let index = self.entries.len();
+        self.entries.push(Entry {
+            author: self.env().caller(),
+            text,
+        });
+        index
+    }
+
+    /// Overwrites the text of one of the caller's entries.
+    pub fn edit(&mut self, index: u32, text: String) {
+        let author = self.assert_author(index);
+        self.entries.replace(index, Entry { author, text });
+    }
+
+    /// Removes one of the caller's entries. The last entry takes its place, so removing costs
+    /// the same wherever the entry is, but the order of the entries changes.
+    pub fn remove(&mut self, index: u32) {
+        self.assert_author(index);
+        let last = self.entries.pop().unwrap_or_revert(&self.env());
+        if index < self.entries.len() {
+            self.entries.replace(index, last);
+        }
+    }
+
+    /**********
+     * QUERIES
+     **********/
+
+    pub fn entry_count(&self) -> u32 {
+        self.entries.len()
+    }
+
+    /// Returns up to `limit` entries starting at `offset`, oldest first. At most
+    /// `MAX_PAGE_SIZE` entries are returned at once.
+    pub fn page(&self, offset: u32, limit: u32) -> Vec<Entry> {
+        // Skipping doesn't read the skipped entries, so only the page is loaded
+        self.entries
+            .iter()
+            .skip(offset as usize)
+            .take(limit.min(MAX_PAGE_SIZE) as usize)
+            .collect()
+    }
+
+    /// Returns up to `limit` of the newest entries, newest first.
+    pub fn latest(&self, limit: u32) -> Vec<Entry> {
+        self.entries
+            .iter()
+            .rev()
+            .take(limit.min(MAX_PAGE_SIZE) as usize)
+            .collect()
+    }
+}
+
+impl Guestbook {
+    /// Reverts unless the caller wrote the entry at `index`, and returns the caller.
+    fn assert_author(&self, index: u32) -> Address {
+        let entry = self
+            .entries
+            .get(index)
+            .unwrap_or_revert_with(&self.env(), Error::EntryNotFound);
+        let caller = self.env().caller();
+        if entry.author != caller {
+            self.env().revert(Error::NotAuthor);
+        }
+        caller
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gas::{is_metered, last_call_gas};
+    use odra::host::{Deployer, HostEnv, NoArgs};
+
+    fn texts(entries: Vec<Entry>) -> Vec<String> {
+        entries.into_iter().map(|entry| entry.text).collect()
+    }
+
+    fn signed_guestbook(env: &HostEnv, count: u32) -> GuestbookHostRef {
+        let mut guestbook = GuestbookHostRef::deploy(env, NoArgs);
+        for i in 0..count {
+            env.set_caller(env.get_account(i as usize % 3));
+            guestbook.sign(format!("Entry {}", i));
+        }
+        guestbook
+    }
+
+    #[test]
+    fn reading_in_pages() {
+        let env = odra_test::env();
+        let guestbook = signed_guestbook(&env, 50);
+
+        assert_eq!(
+            texts(guestbook.page(10, 3)),
+            vec!["Entry 10", "Entry 11", "Entry 12"]
+        );
+        assert_eq!(guestbook.page(0, 100).len(), MAX_PAGE_SIZE as usize);
+        assert_eq!(texts(guestbook.page(48, 5)), vec!["Entry 48", "Entry 49"]);
+        assert!(guestbook.page(50, 5).is_empty());
+        assert_eq!(texts(guestbook.latest(2)), vec!["Entry 49", "Entry 48"]);
+    }
+
+    #[test]
+    fn bigger_pages_cost_more() {
+        if !is_metered() {
+            return;
+        }
+        let env = odra_test::env();
+        let guestbook = signed_guestbook(&env, 50);
+
+        // A small page far into the list is still cheaper than a big one at its start
+        guestbook.page(40, 3);
+        let small_page = last_call_gas(&guestbook);
+        guestbook.page(0, MAX_PAGE_SIZE);
+        assert!(last_call_gas(&guestbook) > small_page);
+    }
+
+    #[test]
+    fn editing_and_removing() {
+        let env = odra_test::env();
+        let mut guestbook = signed_guestbook(&env, 4);
+
+        // Entry 1 was signed by account 1
+        env.set_caller(env.get_account(2));
+        assert_eq!(
+            guestbook.try_edit(1, "Spam".to_string()),
+            Err(Error::NotAuthor.into())
+        );
+        assert_eq!(guestbook.try_remove(4), Err(Error::EntryNotFound.into()));
+        env.set_caller(env.get_account(1));
+        guestbook.edit(1, "Entry 1, edited".to_string());
+
+        // The last entry moves into the gap
+        guestbook.remove(1);
+        assert_eq!(
+            texts(guestbook.page(0, 10)),
+            vec!["Entry 0", "Entry 3", "Entry 2"]
+        );
+        env.set_caller(env.get_account(2));
+        guestbook.remove(2);
+        assert_eq!(guestbook.entry_count(), 2);
+    }
+}
@@ -0,0 +1,386 @@
+use odra::casper_types::U512;
+use odra::prelude::*;
+use odra::{Address, Mapping, Var};
+
+#[odra::odra_error]
+/// Errors that may occur during the contract execution.
+pub enum Error {
+    /// Streams can't pay the sender or the contract itself.
+    InvalidRecipient = 1,
+    /// The attached CSPR doesn't match the deposit, or the deposit is zero.
+    IncorrectDepositAmount = 2,
+    /// A stream can't start in the past, and has to stop after it starts.
+    InvalidTimeRange = 3,
+    /// There's no stream with the given ID.
+    StreamNotFound = 4,
+    /// Only the recipient can withdraw from a stream.
+    NotRecipient = 5,
+    /// Only the sender or the recipient can cancel a stream.
+    NotParty = 6,
+    /// Withdrawn amounts must be positive.
+    ZeroAmount = 7,
+    /// The recipient can't withdraw more than has been streamed.
+    AmountExceedsBalance = 8,
+    /// The stream has been cancelled or paid out in full.
+    StreamClosed = 9,
+}
+
+#[odra::odra_type]
+pub struct Stream {
+    pub sender: Address,
+    pub recipient: Address,
+    pub deposit: U512,
+    /// Block time the stream starts paying at.
+    pub start: u64,
+    /// Block time the whole deposit has been streamed at.
+    pub stop: u64,
+    /// Part of the streamed CSPR the recipient has withdrawn.
+    pub withdrawn: U512,
+    pub closed: bool,
+}
+
+#[odra::event]
+/// Emitted when a stream is created.
+pub struct StreamCreated {
+    pub stream_id: u32,
+    pub sender: Address,
+    pub recipient: Address,
+    pub deposit: U512,
+    pub start: u64,
+    pub stop: u64,
+}
+
+#[odra::event]
+/// Emitted when the recipient withdraws from a stream.
+pub struct WithdrawnFromStream {
+    pub stream_id: u32,
+    pub recipient: Address,
+    pub amount: U512,
+}
+
+#[odra::event]
+/// Emitted when a stream is cancelled and its balance is split.
+pub struct StreamCancelled {
+    pub stream_id: u32,
+    pub sender_balance: U512,
+    pub recipient_balance: U512,
+}
+
+#[odra::module(events = [StreamCreated, WithdrawnFromStream, StreamCancelled], errors = Error)]
+/// Streams CSPR from senders to recipients over time.
+pub struct Streams {
+    streams: Mapping<u32, Stream>,
+    stream_count: Var<u32>,
+}
+
+#[odra::module]
+impl Streams {
+    /**********
+     * TRANSACTIONS
+     **********/
+
+    /// Creates a stream paying the attached `deposit` to `recipient` between `start` and `stop`,
+    /// and returns its ID.
+    #[odra(payable)]
+    pub fn create_stream(
+        &mut self,
+        recipient: Address,
+        deposit: U512,
+        start: u64,
+        stop: u64,
+    ) -> u32 {
+        let sender = self.env().caller();
+        if recipient == sender || recipient == self.env().self_address() {
+            self.env().revert(Error::InvalidRecipient);
+        }
+        if deposit.is_zero() || self.env().attached_value() != deposit {
+            self.env().revert(Error::IncorrectDepositAmount);
+        }
+        if start < self.env().get_block_time() || stop <= start {
+            self.env().revert(Error::InvalidTimeRange);
+        }
+
+        let stream_id = self.stream_count.get_or_default();
+        self.streams.set(
+            &stream_id,
+            Stream {
+                sender,
+                recipient,
+                deposit,
+                start,
+                stop,
+                withdrawn: U512::zero(),
+                closed: false,
+            },
+        );
+        self.stream_count.set(stream_id + 1);
+        self.env().emit_event(StreamCreated {
+            stream_id,
+            sender,
+            recipient,
+            deposit,
+            start,
+            stop,
+        });
+        stream_id
+    }
+
+    /// Sends `amount` of the CSPR streamed so far to the recipient.
+    pub fn withdraw_from_stream(&mut self, stream_id: u32, amount: U512) {
+        let mut stream = self.open_stream(stream_id);
+        if self.env().caller() != stream.recipient {
+            self.env().revert(Error::NotRecipient);
+        }
+        if amount.is_zero() {
+            self.env().revert(Error::ZeroAmount);
+        }
+        if amount > self.recipient_balance(&stream) {
+            self.env().revert(Error::AmountExceedsBalance);
+        }
+
+        stream.withdrawn += amount;
+        // Nothing is left once the whole deposit has been withdrawn
+        stream.closed = stream.withdrawn == stream.deposit;
+        let recipient = stream.recipient;
+        self.streams.set(&stream_id, stream);
+        self.env().transfer_tokens(&recipient, &amount);
+        self.env().emit_event(WithdrawnFromStream {
+            stream_id,
+            recipient,
+            amount,
+        });
+    }
+
+    /// Stops the stream, paying the recipient what has been streamed and refunding the rest
+    /// to the sender.
+    pub fn cancel_stream(&mut self, stream_id: u32) {
+        let mut stream = self.open_stream(stream_id);
+        let caller = self.env().caller();
+        if caller != stream.sender && caller != stream.recipient {
+            self.env().revert(Error::NotParty);
+        }
+
+        let recipient_balance = self.recipient_balance(&stream);
+        let sender_balance = stream.deposit - stream.withdrawn - recipient_balance;
+        stream.closed = true;
+        let (sender, recipient) = (stream.sender, stream.recipient);
+        self.streams.set(&stream_id, stream);
+        if !recipient_balance.is_zero() {
+            self.env().transfer_tokens(&recipient, &recipient_balance);
+        }
+        if !sender_balance.is_zero() {
+            self.env().transfer_tokens(&sender, &sender_balance);
+        }
+        self.env().emit_event(StreamCancelled {
+            stream_id,
+            sender_balance,
+            recipient_balance,
+        });
+    }
+
+    /**********
+     * QUERIES
+     **********/
+
+    /// Returns a stream.
+    pub fn get_stream(&self, stream_id: u32) -> Option<Stream> {
+        self.streams.get(&stream_id)
+    }
+
+    /// Returns what `account` would get if the stream was cancelled now: the streamed CSPR
+    /// that hasn't been withdrawn for the recipient, the rest of the deposit for the sender.
+    pub fn balance_of(&self, stream_id: u32, account: Address) -> U512 {
+        let stream = self.stream(stream_id);
+        if stream.closed {
+            return U512::zero();
+        }
+        let recipient_balance = self.recipient_balance(&stream);
+        if account == stream.recipient {
+            recipient_balance
+        } else if account == stream.sender {
+            stream.deposit - stream.withdrawn - recipient_balance
+        } else {
+            U512::zero()
+        }
+    }
+
+    /// Returns the number of streams.
+    pub fn get_stream_count(&self) -> u32 {
+        self.stream_count.get_or_default()
+    }
+}
+
+impl Streams {
+    fn stream(&self, stream_id: u32) -> Stream {
+        self.streams
+            .get(&stream_id)
+            .unwrap_or_revert_with(&self.env(), Error::StreamNotFound)
+    }
+
+    fn open_stream(&self, stream_id: u32) -> Stream {
+        let stream = self.stream(stream_id);
+        if stream.closed {
+            self.env().revert(Error::StreamClosed);
+        }
+        stream
+    }
+
+    /// Returns the part of the deposit streamed by `time`, growing linearly from `start` to `stop`.
+    fn streamed_at(stream: &Stream, time: u64) -> U512 {
+        if time <= stream.start {
+            U512::zero()
+        } else if time >= stream.stop {
+            stream.deposit
+        } else {
+            // Multiply first, so the result is only rounded once
+            stream.deposit * (time - stream.start) / (stream.stop - stream.start)
+        }
+    }
+
+    fn recipient_balance(&self, stream: &Stream) -> U512 {
+        Self::streamed_at(stream, self.env().get_block_time()) - stream.withdrawn
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use odra::host::{Deployer, HostEnv, HostRef, NoArgs};
+
+    const START: u64 = 1_000;
+    const STOP: u64 = 2_000;
+
+    /// Creates a stream of 1,000 motes from account 1 to account 2, between `START` and `STOP`.
+    fn setup(env: &HostEnv) -> (StreamsHostRef, u32) {
+        let mut streams = StreamsHostRef::deploy(env, NoArgs);
+        env.set_caller(env.get_account(1));
+        let id = streams.with_tokens(U512::from(1_000)).create_stream(
+            env.get_account(2),
+            U512::from(1_000),
+            START,
+            STOP,
+        );
+        (streams, id)
+    }
+
+    #[test]
+    fn streaming() {
+        let env = odra_test::env();
+        let (mut streams, id) = setup(&env);
+        let (sender, recipient) = (env.get_account(1), env.get_account(2));
+        assert_eq!(streams.balance_of(id, recipient), U512::zero());
+        assert_eq!(streams.balance_of(id, sender), U512::from(1_000));
+
+        // A quarter of the way through, a quarter of the deposit is streamed
+        env.advance_block_time(START + 250);
+        assert_eq!(streams.balance_of(id, recipient), U512::from(250));
+        assert_eq!(streams.balance_of(id, sender), U512::from(750));
+        assert_eq!(
+            streams.try_withdraw_from_stream(id, U512::from(100)),
+            Err(Error::NotRecipient.into())
+        );
+        env.set_caller(recipient);
+        let balance = env.balance_of(&recipient);
+        streams.withdraw_from_stream(id, U512::from(200));
+        assert_eq!(env.balance_of(&recipient), balance + U512::from(200));
+        assert_eq!(streams.balance_of(id, recipient), U512::from(50));
+        assert_eq!(
+            streams.try_withdraw_from_stream(id, U512::from(51)),
+            Err(Error::AmountExceedsBalance.into())
+        );
+
+        // After the stop time, the rest of the deposit is the recipient's
+        env.advance_block_time(STOP);
+        assert_eq!(streams.balance_of(id, recipient), U512::from(800));
+        streams.withdraw_from_stream(id, U512::from(800));
+        assert!(streams.get_stream(id).unwrap().closed);
+        assert_eq!(env.balance_of(streams.address()), U512::zero());
+        assert_eq!(
+            streams.try_withdraw_from_stream(id, U512::one()),
+            Err(Error::StreamClosed.into())
+        );
+    }
+
+    #[test]
+    fn rounding() {
+        let env = odra_test::env();
+        let mut streams = StreamsHostRef::deploy(&env, NoArgs);
+        let recipient = env.get_account(1);
+
+        // 10 motes over 3 milliseconds are rounded down until the stream stops
+        let id = streams
+            .with_tokens(U512::from(10))
+            .create_stream(recipient, U512::from(10), 0, 3);
+        for streamed in [3, 6, 10] {
+            env.advance_block_time(1);
+            assert_eq!(streams.balance_of(id, recipient), U512::from(streamed));
+        }
+    }
+
+    #[test]
+    fn cancellation() {
+        let env = odra_test::env();
+        let (mut streams, id) = setup(&env);
+        let (sender, recipient) = (env.get_account(1), env.get_account(2));
+        env.advance_block_time(START + 400);
+        env.set_caller(recipient);
+        streams.withdraw_from_stream(id, U512::from(100));
+
+        env.set_caller(env.get_account(3));
+        assert_eq!(streams.try_cancel_stream(id), Err(Error::NotParty.into()));
+
+        // Each side gets its share at the time of cancellation
+        let (sender_balance, recipient_balance) =
+            (env.balance_of(&sender), env.balance_of(&recipient));
+        env.set_caller(sender);
+        streams.cancel_stream(id);
+        assert_eq!(env.balance_of(&sender), sender_balance + U512::from(600));
+        assert_eq!(
+            env.balance_of(&recipient),
+            recipient_balance + U512::from(300)
+        );
+        assert!(env.emitted_event(
+            streams.address(),
+            &StreamCancelled {
+                stream_id: id,
+                sender_balance: U512::from(600),
+                recipient_balance: U512::from(300)
+            }
+        ));
+        assert_eq!(streams.balance_of(id, recipient), U512::zero());
+        assert_eq!(
+            streams.try_cancel_stream(id),
+            Err(Error::StreamClosed.into())
+        );
+    }
+
+    #[test]
+    fn invalid_streams() {
+        let env = odra_test::env();
+        let (mut streams, _) = setup(&env);
+        let recipient = env.get_account(2);
+        let mut create = |amount: u64, recipient: Address, start: u64, stop: u64| {
+            streams
+                .with_tokens(U512::from(amount))
+                .try_create_stream(recipient, U512::from(1_000), start, stop)
+                .err()
+        };
+        assert_eq!(
+            create(1_000, env.get_account(1), START, STOP),
+            Some(Error::InvalidRecipient.into())
+        );
+        assert_eq!(
+            create(999, recipient, START, STOP),
+            Some(Error::IncorrectDepositAmount.into())
+        );
+        assert_eq!(
+            create(1_000, recipient, STOP, START),
+            Some(Error::InvalidTimeRange.into())
+        );
+        env.advance_block_time(START);
+        assert_eq!(
+            create(1_000, recipient, START - 1, STOP),
+            Some(Error::InvalidTimeRange.into())
+        );
+    }
+}
@@ -0,0 +1,451 @@
+use odra::casper_types::U512;
+use odra::prelude::*;
+use odra::{Address, Mapping, Var};
+
+/// Basis points in a whole charge.
+const MAX_FEE_BPS: u16 = 10_000;
+/// Most subscriptions a subscriber can hold, so processing them stays cheap.
+const MAX_SUBSCRIPTIONS: usize = 8;
+
+#[odra::odra_error]
+/// Errors that may occur during the contract execution.
+pub enum Error {
+    /// Plans need a positive price and period.
+    InvalidPlan = 1,
+    /// There's no plan with the given ID.
+    PlanNotFound = 2,
+    /// The merchant has closed the plan.
+    PlanClosed = 3,
+    /// Only the merchant can close the plan.
+    NotMerchant = 4,
+    /// The subscriber is already subscribed to the plan.
+    AlreadySubscribed = 5,
+    /// The subscriber isn't subscribed to the plan.
+    NotSubscribed = 6,
+    /// The subscriber holds the maximum number of subscriptions.
+    TooManySubscriptions = 7,
+    /// The balance doesn't cover the amount.
+    InsufficientBalance = 8,
+    /// Deposited and withdrawn amounts must be positive.
+    ZeroAmount = 9,
+    /// The fee can't exceed the whole charge.
+    InvalidFee = 10,
+    /// None of the subscriber's subscriptions is due.
+    NothingDue = 11,
+}
+
+#[odra::odra_type]
+pub struct Plan {
+    pub merchant: Address,
+    /// Price of one billing period.
+    pub price: U512,
+    /// Length of a billing period.
+    pub period: u64,
+    pub active: bool,
+}
+
+#[odra::odra_type]
+pub struct Subscription {
+    pub plan_id: u32,
+    /// Block time the subscription has been paid until.
+    pub paid_until: u64,
+}
+
+#[odra::event]
+/// Emitted when a merchant creates a plan.
+pub struct PlanCreated {
+    pub plan_id: u32,
+    pub merchant: Address,
+    pub price: U512,
+    pub period: u64,
+}
+
+#[odra::event]
+/// Emitted when a merchant closes a plan.
+pub struct PlanClosed {
+    pub plan_id: u32,
+}
+
+#[odra::event]
+/// Emitted when a user subscribes to a plan.
+pub struct Subscribed {
+    pub subscriber: Address,
+    pub plan_id: u32,
+    pub paid_until: u64,
+}
+
+#[odra::event]
+/// Emitted when a subscription ends, either cancelled by the subscriber or lapsed.
+pub struct Unsubscribed {
+    pub subscriber: Address,
+    pub plan_id: u32,
+}
+
+#[odra::event]
+/// Emitted when due periods are charged.
+pub struct Charged {
+    pub subscriber: Address,
+    pub plan_id: u32,
+    pub periods: u64,
+    pub amount: U512,
+    /// The caller who processed the charge and earned the fee.
+    pub keeper: Address,
+}
+
+#[odra::module(
+    events = [PlanCreated, PlanClosed, Subscribed, Unsubscribed, Charged],
+    errors = Error
+)]
+/// Charges prepaid balances for recurring subscriptions.
+pub struct Subscriptions {
+    /// Part of each charge paid to whoever processes it.
+    keeper_fee_bps: Var<u16>,
+    plans: Mapping<u32, Plan>,
+    plan_count: Var<u32>,
+    subscriptions: Mapping<Address, Vec<Subscription>>,
+    /// CSPR of subscribers, merchants and keepers held by the contract.
+    balances: Mapping<Address, U512>,
+}
+
+#[odra::module]
+impl Subscriptions {
+    /// Initializes the contract with the fee paid to keepers.
+    pub fn init(&mut self, keeper_fee_bps: u16) {
+        if keeper_fee_bps > MAX_FEE_BPS {
+            self.env().revert(Error::InvalidFee);
+        }
+        self.keeper_fee_bps.set(keeper_fee_bps);
+    }
+
+    /**********
+     * TRANSACTIONS
+     **********/
+
+    /// Creates a plan charging `price` every `period`, paid to the caller. Returns its ID.
+    pub fn create_plan(&mut self, price: U512, period: u64) -> u32 {
+        if price.is_zero() || period == 0 {
+            self.env().revert(Error::InvalidPlan);
+        }
+        let plan_id = self.plan_count.get_or_default();
+        let merchant = self.env().caller();
+        self.plans.set(
+            &plan_id,
+            Plan {
+                merchant,
+                price,
+                period,
+                active: true,
+            },
+        );
+        self.plan_count.set(plan_id + 1);
+        self.env().emit_event(PlanCreated {
+            plan_id,
+            merchant,
+            price,
+            period,
+        });
+        plan_id
+    }
+
+    /// Closes a plan; its subscriptions end the next time they're processed.
+    pub fn close_plan(&mut self, plan_id: u32) {
+        let mut plan = self.plan(plan_id);
+        if plan.merchant != self.env().caller() {
+            self.env().revert(Error::NotMerchant);
+        }
+        plan.active = false;
+        self.plans.set(&plan_id, plan);
+        self.env().emit_event(PlanClosed { plan_id });
+    }
+
+    /// Adds the attached CSPR to the caller's balance.
+    #[odra(payable)]
+    pub fn deposit(&mut self) {
+        let amount = self.env().attached_value();
+        if amount.is_zero() {
+            self.env().revert(Error::ZeroAmount);
+        }
+        self.balances.add(&self.env().caller(), amount);
+    }
+
+    /// Sends `amount` of the caller's balance to the caller.
+    pub fn withdraw(&mut self, amount: U512) {
+        if amount.is_zero() {
+            self.env().revert(Error::ZeroAmount);
+        }
+        let caller = self.env().caller();
+        if amount > self.balances.get_or_default(&caller) {
+            self.env().revert(Error::InsufficientBalance);
+        }
+        self.balances.subtract(&caller, amount);
+        self.env().transfer_tokens(&caller, &amount);
+    }
+
+    /// Subscribes the caller to a plan, paying the first period from their balance.
+    pub fn subscribe(&mut self, plan_id: u32) {
+        let plan = self.plan(plan_id);
+        if !plan.active {
+            self.env().revert(Error::PlanClosed);
+        }
+        let subscriber = self.env().caller();
+        let mut subscriptions = self.subscriptions.get_or_default(&subscriber);
+        if subscriptions.iter().any(|s| s.plan_id == plan_id) {
+            self.env().revert(Error::AlreadySubscribed);
+        }
+        if subscriptions.len() >= MAX_SUBSCRIPTIONS {
+            self.env().revert(Error::TooManySubscriptions);
+        }
+        if plan.price > self.balances.get_or_default(&subscriber) {
+            self.env().revert(Error::InsufficientBalance);
+        }
+
+        // Periods are paid in advance
+        self.balances.subtract(&subscriber, plan.price);
+        self.balances.add(&plan.merchant, plan.price);
+        let paid_until = self.env().get_block_time() + plan.period;
+        subscriptions.push(Subscription {
+            plan_id,
+            paid_until,
+        });
+        self.subscriptions.set(&subscriber, subscriptions);
+        self.env().emit_event(Subscribed {
+            subscriber,
+            plan_id,
+            paid_until,
+        });
+    }
+
+    /// Ends the caller's subscription. The period that has been paid isn't refunded.
+    pub fn unsubscribe(&mut self, plan_id: u32) {
+        let subscriber = self.env().caller();
+        let mut subscriptions = self.subscriptions.get_or_default(&subscriber);
+        let count = subscriptions.len();
+        subscriptions.retain(|s| s.plan_id != plan_id);
+        if subscriptions.len() == count {
+            self.env().revert(Error::NotSubscribed);
+        }
+        self.subscriptions.set(&subscriber, subscriptions);
+        self.env().emit_event(Unsubscribed {
+            subscriber,
+            plan_id,
+        });
+    }
+
+    /// Charges every period that's due on the subscriber's subscriptions. Anyone can call it,
+    /// and earns the keeper fee out of the charges. A subscription the balance can't pay for,
+    /// or whose plan was closed, ends.
+    pub fn process_due(&mut self, subscriber: Address) {
+        let now = self.env().get_block_time();
+        let keeper = self.env().caller();
+        let fee_bps = self.keeper_fee_bps.get_or_default();
+        let mut processed = false;
+        let mut kept = vec![];
+
+        for mut subscription in self.subscriptions.get_or_default(&subscriber) {
+            let plan = self.plan(subscription.plan_id);
+            if plan.active && now < subscription.paid_until {
+                kept.push(subscription);
+                continue;
+            }
+            processed = true;
+
+            // Every period that started since the last payment is due
+            let due = if plan.active {
+                (now - subscription.paid_until) / plan.period + 1
+            } else {
+                0
+            };
+            let affordable = self.balances.get_or_default(&subscriber) / plan.price;
+            let periods = affordable.min(U512::from(due)).as_u64();
+            if periods > 0 {
+                let amount = plan.price * periods;
+                let fee = amount * fee_bps / MAX_FEE_BPS;
+                self.balances.subtract(&subscriber, amount);
+                self.balances.add(&plan.merchant, amount - fee);
+                self.balances.add(&keeper, fee);
+                subscription.paid_until += periods * plan.period;
+                self.env().emit_event(Charged {
+                    subscriber,
+                    plan_id: subscription.plan_id,
+                    periods,
+                    amount,
+                    keeper,
+                });
+            }
+
+            if plan.active && periods == due {
+                kept.push(subscription);
+            } else {
+                self.env().emit_event(Unsubscribed {
+                    subscriber,
+                    plan_id: subscription.plan_id,
+                });
+            }
+        }
+
+        if !processed {
+            self.env().revert(Error::NothingDue);
+        }
+        self.subscriptions.set(&subscriber, kept);
+    }
+
+    /**********
+     * QUERIES
+     **********/
+
+    /// Returns a plan.
+    pub fn get_plan(&self, plan_id: u32) -> Option<Plan> {
+        self.plans.get(&plan_id)
+    }
+
+    /// Returns the number of plans.
+    pub fn get_plan_count(&self) -> u32 {
+        self.plan_count.get_or_default()
+    }
+
+    /// Returns the subscriptions of a subscriber.
+    pub fn subscriptions_of(&self, subscriber: Address) -> Vec<Subscription> {
+        self.subscriptions.get_or_default(&subscriber)
+    }
+
+    /// Returns the CSPR an account holds in the contract.
+    pub fn balance_of(&self, account: Address) -> U512 {
+        self.balances.get_or_default(&account)
+    }
+
+    /// Returns the part of each charge paid to keepers, in basis points.
+    pub fn get_keeper_fee_bps(&self) -> u16 {
+        self.keeper_fee_bps.get_or_default()
+    }
+}
+
+impl Subscriptions {
+    fn plan(&self, plan_id: u32) -> Plan {
+        self.plans
+            .get(&plan_id)
+            .unwrap_or_revert_with(&self.env(), Error::PlanNotFound)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use odra::host::{Deployer, HostEnv, HostRef};
+
+    const PERIOD: u64 = 1_000;
+
+    /// Deploys the contract with a 1% keeper fee, and creates a plan of account 1
+    /// charging 100 motes per period.
+    fn setup(env: &HostEnv) -> (SubscriptionsHostRef, u32) {
+        let mut subscriptions = SubscriptionsHostRef::deploy(
+            env,
+            SubscriptionsInitArgs {
+                keeper_fee_bps: 100,
+            },
+        );
+        env.set_caller(env.get_account(1));
+        let plan_id = subscriptions.create_plan(U512::from(100), PERIOD);
+        (subscriptions, plan_id)
+    }
+
+    #[test]
+    fn billing() {
+        let env = odra_test::env();
+        let (mut subscriptions, plan_id) = setup(&env);
+        let (merchant, alice, keeper) =
+            (env.get_account(1), env.get_account(2), env.get_account(3));
+
+        // Subscribing pays the first period right away
+        env.set_caller(alice);
+        subscriptions.with_tokens(U512::from(350)).deposit();
+        subscriptions.subscribe(plan_id);
+        assert_eq!(subscriptions.balance_of(alice), U512::from(250));
+        assert_eq!(subscriptions.balance_of(merchant), U512::from(100));
+
+        env.set_caller(keeper);
+        assert_eq!(
+            subscriptions.try_process_due(alice),
+            Err(Error::NothingDue.into())
+        );
+
+        // Every period that started since is charged at once, minus the keeper's fee
+        env.advance_block_time(2_500);
+        subscriptions.process_due(alice);
+        assert_eq!(subscriptions.balance_of(alice), U512::from(50));
+        assert_eq!(subscriptions.balance_of(merchant), U512::from(298));
+        assert_eq!(subscriptions.balance_of(keeper), U512::from(2));
+        assert_eq!(
+            subscriptions.subscriptions_of(alice)[0].paid_until,
+            3 * PERIOD
+        );
+        assert!(env.emitted_event(
+            subscriptions.address(),
+            &Charged {
+                subscriber: alice,
+                plan_id,
+                periods: 2,
+                amount: U512::from(200),
+                keeper
+            }
+        ));
+
+        // A subscription the balance can't pay for ends
+        env.advance_block_time(500);
+        subscriptions.process_due(alice);
+        assert!(subscriptions.subscriptions_of(alice).is_empty());
+        assert_eq!(subscriptions.balance_of(alice), U512::from(50));
+
+        env.set_caller(merchant);
+        let balance = env.balance_of(&merchant);
+        subscriptions.withdraw(U512::from(298));
+        assert_eq!(env.balance_of(&merchant), balance + U512::from(298));
+    }
+
+    #[test]
+    fn managing_subscriptions() {
+        let env = odra_test::env();
+        let (mut subscriptions, plan_id) = setup(&env);
+        let alice = env.get_account(2);
+
+        env.set_caller(alice);
+        subscriptions.with_tokens(U512::from(50)).deposit();
+        assert_eq!(
+            subscriptions.try_subscribe(plan_id),
+            Err(Error::InsufficientBalance.into())
+        );
+        subscriptions.with_tokens(U512::from(1_000)).deposit();
+        subscriptions.subscribe(plan_id);
+        assert_eq!(
+            subscriptions.try_subscribe(plan_id),
+            Err(Error::AlreadySubscribed.into())
+        );
+        subscriptions.unsubscribe(plan_id);
+        assert_eq!(
+            subscriptions.try_unsubscribe(plan_id),
+            Err(Error::NotSubscribed.into())
+        );
+        subscriptions.subscribe(plan_id);
+
+        // Closing a plan ends its subscriptions without charging them
+        assert_eq!(
+            subscriptions.try_close_plan(plan_id),
+            Err(Error::NotMerchant.into())
+        );
+        env.set_caller(env.get_account(1));
+        subscriptions.close_plan(plan_id);
+        env.set_caller(alice);
+        assert_eq!(
+            subscriptions.try_subscribe(plan_id),
+            Err(Error::PlanClosed.into())
+        );
+        let balance = subscriptions.balance_of(alice);
+        env.advance_block_time(PERIOD);
+        subscriptions.process_due(alice);
+        assert!(subscriptions.subscriptions_of(alice).is_empty());
+        assert_eq!(subscriptions.balance_of(alice), balance);
+        assert_eq!(
+            subscriptions.try_withdraw(balance + U512::one()),
+            Err(Error::InsufficientBalance.into())
+        );
+    }
+}
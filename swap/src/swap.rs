@@ -0,0 +1,545 @@
+use odra::casper_types::U256;
+use odra::prelude::*;
+use odra::{Address, ContractRef, Mapping, Var};
+use odra_modules::cep18_token::Cep18ContractRef;
+
+/// Shares burned by the first deposit, so the price of a share can't be pushed so high
+/// that later deposits are rounded down to nothing.
+const MINIMUM_LIQUIDITY: u64 = 1_000;
+/// Swap fee in thousandths of the input amount (0.3%).
+const FEE_PER_MILLE: u64 = 3;
+
+#[odra::odra_error]
+/// Errors that may occur during the contract execution.
+pub enum Error {
+    /// The pool needs two different tokens.
+    IdenticalTokens = 1,
+    /// The token isn't traded in this pool.
+    UnknownToken = 2,
+    /// Deposited, withdrawn and swapped amounts must be positive.
+    ZeroAmount = 3,
+    /// The pool has no liquidity yet.
+    EmptyPool = 4,
+    /// The deposit is too small to mint any shares.
+    InsufficientLiquidityMinted = 5,
+    /// The withdrawal is too small to return both tokens.
+    InsufficientLiquidityBurned = 6,
+    /// The swap is too small to return anything.
+    InsufficientOutput = 7,
+    /// The caller doesn't own that many shares.
+    InsufficientShares = 8,
+    /// The result is worse than the caller's minimum.
+    SlippageExceeded = 9,
+}
+
+#[odra::event]
+/// Emitted when a provider deposits liquidity.
+pub struct LiquidityAdded {
+    pub provider: Address,
+    pub amount_a: U256,
+    pub amount_b: U256,
+    pub liquidity: U256,
+}
+
+#[odra::event]
+/// Emitted when a provider withdraws liquidity.
+pub struct LiquidityRemoved {
+    pub provider: Address,
+    pub amount_a: U256,
+    pub amount_b: U256,
+    pub liquidity: U256,
+}
+
+#[odra::event]
+/// Emitted when a trader swaps one token for the other.
+pub struct Swapped {
+    pub trader: Address,
+    pub token_in: Address,
+    pub amount_in: U256,
+    pub amount_out: U256,
+}
+
+#[odra::module(events = [LiquidityAdded, LiquidityRemoved, Swapped], errors = Error)]
+/// A constant-product pool trading two CEP-18 tokens.
+pub struct Pool {
+    token_a: Var<Address>,
+    token_b: Var<Address>,
+    /// Tokens held by the pool. Tracked separately from the token balances, so tokens sent
+    /// to the pool directly don't affect the price.
+    reserve_a: Var<U256>,
+    reserve_b: Var<U256>,
+    /// Shares of each liquidity provider.
+    liquidity: Mapping<Address, U256>,
+    /// Sum of all shares, including the burned `MINIMUM_LIQUIDITY`.
+    total_liquidity: Var<U256>,
+}
+
+#[odra::module]
+impl Pool {
+    /// Initializes the pool with the two traded tokens.
+    pub fn init(&mut self, token_a: Address, token_b: Address) {
+        if token_a == token_b {
+            self.env().revert(Error::IdenticalTokens);
+        }
+        self.token_a.set(token_a);
+        self.token_b.set(token_b);
+    }
+
+    /**********
+     * TRANSACTIONS
+     **********/
+
+    /// Deposits up to the desired amounts of both tokens at the current price, approved
+    /// beforehand, and returns the minted shares. The first deposit sets the price.
+    pub fn add_liquidity(
+        &mut self,
+        amount_a_desired: U256,
+        amount_b_desired: U256,
+        min_liquidity: U256,
+    ) -> U256 {
+        if amount_a_desired.is_zero() || amount_b_desired.is_zero() {
+            self.env().revert(Error::ZeroAmount);
+        }
+        let (reserve_a, reserve_b) = self.get_reserves();
+        let total = self.total_liquidity.get_or_default();
+
+        let (amount_a, amount_b, liquidity) = if total.is_zero() {
+            let liquidity = (amount_a_desired * amount_b_desired).integer_sqrt();
+            if liquidity <= U256::from(MINIMUM_LIQUIDITY) {
+                self.env().revert(Error::InsufficientLiquidityMinted);
+            }
+            self.total_liquidity.add(U256::from(MINIMUM_LIQUIDITY));
+            (
+                amount_a_desired,
+                amount_b_desired,
+                liquidity - MINIMUM_LIQUIDITY,
+            )
+        } else {
+            // Take as much of both amounts as the current price allows
+            let amount_b_optimal = amount_a_desired * reserve_b / reserve_a;
+            let (amount_a, amount_b) = if amount_b_optimal <= amount_b_desired {
+                (amount_a_desired, amount_b_optimal)
+            } else {
+                (amount_b_desired * reserve_a / reserve_b, amount_b_desired)
+            };
+            let liquidity = (amount_a * total / reserve_a).min(amount_b * total / reserve_b);
+            (amount_a, amount_b, liquidity)
+        };
+        if liquidity.is_zero() {
+            self.env().revert(Error::InsufficientLiquidityMinted);
+        }
+        if liquidity < min_liquidity {
+            self.env().revert(Error::SlippageExceeded);
+        }
+
+        let provider = self.env().caller();
+        let pool = self.env().self_address();
+        self.token_ref(self.token_a())
+            .transfer_from(&provider, &pool, &amount_a);
+        self.token_ref(self.token_b())
+            .transfer_from(&provider, &pool, &amount_b);
+        self.reserve_a.add(amount_a);
+        self.reserve_b.add(amount_b);
+        self.liquidity.add(&provider, liquidity);
+        self.total_liquidity.add(liquidity);
+
+        self.env().emit_event(LiquidityAdded {
+            provider,
+            amount_a,
+            amount_b,
+            liquidity,
+        });
+        liquidity
+    }
+
+    /// Burns `liquidity` of the caller's shares and returns their part of both reserves.
+    pub fn remove_liquidity(
+        &mut self,
+        liquidity: U256,
+        min_amount_a: U256,
+        min_amount_b: U256,
+    ) -> (U256, U256) {
+        if liquidity.is_zero() {
+            self.env().revert(Error::ZeroAmount);
+        }
+        let provider = self.env().caller();
+        if liquidity > self.liquidity.get_or_default(&provider) {
+            self.env().revert(Error::InsufficientShares);
+        }
+
+        let (reserve_a, reserve_b) = self.get_reserves();
+        let total = self.total_liquidity.get_or_default();
+        let amount_a = liquidity * reserve_a / total;
+        let amount_b = liquidity * reserve_b / total;
+        if amount_a.is_zero() || amount_b.is_zero() {
+            self.env().revert(Error::InsufficientLiquidityBurned);
+        }
+        if amount_a < min_amount_a || amount_b < min_amount_b {
+            self.env().revert(Error::SlippageExceeded);
+        }
+
+        self.liquidity.subtract(&provider, liquidity);
+        self.total_liquidity.subtract(liquidity);
+        self.reserve_a.subtract(amount_a);
+        self.reserve_b.subtract(amount_b);
+        self.token_ref(self.token_a())
+            .transfer(&provider, &amount_a);
+        self.token_ref(self.token_b())
+            .transfer(&provider, &amount_b);
+
+        self.env().emit_event(LiquidityRemoved {
+            provider,
+            amount_a,
+            amount_b,
+            liquidity,
+        });
+        (amount_a, amount_b)
+    }
+
+    /// Swaps exactly `amount_in` of `token_in`, approved beforehand, for the other token,
+    /// and returns the amount received.
+    pub fn swap_exact_in(
+        &mut self,
+        token_in: Address,
+        amount_in: U256,
+        min_amount_out: U256,
+    ) -> U256 {
+        let amount_out = self.get_amount_out(token_in, amount_in);
+        if amount_out < min_amount_out {
+            self.env().revert(Error::SlippageExceeded);
+        }
+
+        let trader = self.env().caller();
+        self.token_ref(token_in)
+            .transfer_from(&trader, &self.env().self_address(), &amount_in);
+        let token_out = if token_in == self.token_a() {
+            self.reserve_a.add(amount_in);
+            self.reserve_b.subtract(amount_out);
+            self.token_b()
+        } else {
+            self.reserve_b.add(amount_in);
+            self.reserve_a.subtract(amount_out);
+            self.token_a()
+        };
+        self.token_ref(token_out).transfer(&trader, &amount_out);
+
+        self.env().emit_event(Swapped {
+            trader,
+            token_in,
+            amount_in,
+            amount_out,
+        });
+        amount_out
+    }
+
+    /**********
+     * QUERIES
+     **********/
+
+    /// Returns the amount of the other token a swap of `amount_in` of `token_in` would return.
+    pub fn get_amount_out(&self, token_in: Address, amount_in: U256) -> U256 {
+        if amount_in.is_zero() {
+            self.env().revert(Error::ZeroAmount);
+        }
+        let (reserve_a, reserve_b) = self.get_reserves();
+        let (reserve_in, reserve_out) = if token_in == self.token_a() {
+            (reserve_a, reserve_b)
+        } else if token_in == self.token_b() {
+            (reserve_b, reserve_a)
+        } else {
+            self.env().revert(Error::UnknownToken)
+        };
+        if reserve_in.is_zero() || reserve_out.is_zero() {
+            self.env().revert(Error::EmptyPool);
+        }
+
+        // The fee is taken from the input, and the rest keeps reserve_in * reserve_out constant
+        let amount_in_with_fee = amount_in * (1_000 - FEE_PER_MILLE);
+        let amount_out =
+            amount_in_with_fee * reserve_out / (reserve_in * 1_000 + amount_in_with_fee);
+        if amount_out.is_zero() {
+            self.env().revert(Error::InsufficientOutput);
+        }
+        amount_out
+    }
+
+    /// Returns the reserves of both tokens.
+    pub fn get_reserves(&self) -> (U256, U256) {
+        (
+            self.reserve_a.get_or_default(),
+            self.reserve_b.get_or_default(),
+        )
+    }
+
+    /// Returns the addresses of both tokens.
+    pub fn get_tokens(&self) -> (Address, Address) {
+        (self.token_a(), self.token_b())
+    }
+
+    /// Returns the shares of a liquidity provider.
+    pub fn liquidity_of(&self, provider: Address) -> U256 {
+        self.liquidity.get_or_default(&provider)
+    }
+
+    /// Returns the sum of all shares.
+    pub fn get_total_liquidity(&self) -> U256 {
+        self.total_liquidity.get_or_default()
+    }
+}
+
+impl Pool {
+    fn token_a(&self) -> Address {
+        self.token_a.get().unwrap_or_revert(&self.env())
+    }
+
+    fn token_b(&self) -> Address {
+        self.token_b.get().unwrap_or_revert(&self.env())
+    }
+
+    fn token_ref(&self, token: Address) -> Cep18ContractRef {
+        Cep18ContractRef::new(self.env(), token)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use odra::host::{Deployer, HostEnv, HostRef};
+    use odra_modules::cep18_token::{Cep18HostRef, Cep18InitArgs};
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+
+    /// Deploys two tokens held by accounts 0 to 2 (10,000,000 each) and a pool trading them.
+    fn setup(env: &HostEnv) -> (PoolHostRef, Cep18HostRef, Cep18HostRef) {
+        let deploy_token = |symbol: &str| {
+            let mut token = Cep18HostRef::deploy(
+                env,
+                Cep18InitArgs {
+                    symbol: symbol.to_string(),
+                    name: format!("Token {}", symbol),
+                    decimals: 0,
+                    initial_supply: U256::from(30_000_000),
+                    admin_list: vec![],
+                    minter_list: vec![],
+                    modality: None,
+                },
+            );
+            token.transfer(&env.get_account(1), &U256::from(10_000_000));
+            token.transfer(&env.get_account(2), &U256::from(10_000_000));
+            token
+        };
+        let token_a = deploy_token("TKA");
+        let token_b = deploy_token("TKB");
+        let pool = PoolHostRef::deploy(
+            env,
+            PoolInitArgs {
+                token_a: *token_a.address(),
+                token_b: *token_b.address(),
+            },
+        );
+        (pool, token_a, token_b)
+    }
+
+    fn add_liquidity(
+        pool: &mut PoolHostRef,
+        token_a: &mut Cep18HostRef,
+        token_b: &mut Cep18HostRef,
+        amount_a: u64,
+        amount_b: u64,
+    ) -> U256 {
+        token_a.approve(pool.address(), &U256::from(amount_a));
+        token_b.approve(pool.address(), &U256::from(amount_b));
+        pool.add_liquidity(U256::from(amount_a), U256::from(amount_b), U256::zero())
+    }
+
+    fn swap(pool: &mut PoolHostRef, token_in: &mut Cep18HostRef, amount_in: u64) -> U256 {
+        token_in.approve(pool.address(), &U256::from(amount_in));
+        pool.swap_exact_in(*token_in.address(), U256::from(amount_in), U256::zero())
+    }
+
+    #[test]
+    fn liquidity() {
+        let env = odra_test::env();
+        let (mut pool, mut token_a, mut token_b) = setup(&env);
+        let (alice, bob) = (env.get_account(0), env.get_account(1));
+
+        // The first deposit mints sqrt(a * b) shares, minus the burned minimum
+        let minted = add_liquidity(&mut pool, &mut token_a, &mut token_b, 10_000, 40_000);
+        assert_eq!(minted, U256::from(19_000));
+        assert_eq!(pool.get_total_liquidity(), U256::from(20_000));
+
+        // Later deposits only take what matches the current price
+        env.set_caller(bob);
+        let minted = add_liquidity(&mut pool, &mut token_a, &mut token_b, 1_000, 5_000);
+        assert_eq!(minted, U256::from(2_000));
+        assert_eq!(token_b.balance_of(&bob), U256::from(10_000_000 - 4_000));
+        assert_eq!(
+            pool.get_reserves(),
+            (U256::from(11_000), U256::from(44_000))
+        );
+
+        // Providers can ask for a minimum number of shares
+        token_a.approve(pool.address(), &U256::from(1_000));
+        token_b.approve(pool.address(), &U256::from(4_000));
+        assert_eq!(
+            pool.try_add_liquidity(U256::from(1_000), U256::from(4_000), U256::from(2_001)),
+            Err(Error::SlippageExceeded.into())
+        );
+
+        // Withdrawals return a proportional part of both reserves
+        assert_eq!(
+            pool.try_remove_liquidity(U256::from(2_001), U256::zero(), U256::zero()),
+            Err(Error::InsufficientShares.into())
+        );
+        assert_eq!(
+            pool.remove_liquidity(U256::from(2_000), U256::zero(), U256::zero()),
+            (U256::from(1_000), U256::from(4_000))
+        );
+        assert!(env.emitted_event(
+            pool.address(),
+            &LiquidityRemoved {
+                provider: bob,
+                amount_a: U256::from(1_000),
+                amount_b: U256::from(4_000),
+                liquidity: U256::from(2_000)
+            }
+        ));
+
+        // The burned shares keep a part of the reserves in the pool forever
+        env.set_caller(alice);
+        pool.remove_liquidity(U256::from(19_000), U256::zero(), U256::zero());
+        assert_eq!(pool.get_reserves(), (U256::from(500), U256::from(2_000)));
+        assert_eq!(pool.get_total_liquidity(), U256::from(MINIMUM_LIQUIDITY));
+    }
+
+    #[test]
+    fn swaps() {
+        let env = odra_test::env();
+        let (mut pool, mut token_a, mut token_b) = setup(&env);
+        let trader = env.get_account(1);
+        assert_eq!(
+            pool.try_get_amount_out(*token_a.address(), U256::from(10_000)),
+            Err(Error::EmptyPool.into())
+        );
+        add_liquidity(&mut pool, &mut token_a, &mut token_b, 1_000_000, 1_000_000);
+
+        // 10,000 A is worth 9,871 B: the price moves along the curve, and 0.3% is kept as a fee
+        env.set_caller(trader);
+        let quote = pool.get_amount_out(*token_a.address(), U256::from(10_000));
+        assert_eq!(quote, U256::from(9_871));
+        token_a.approve(pool.address(), &U256::from(10_000));
+        assert_eq!(
+            pool.try_swap_exact_in(*token_a.address(), U256::from(10_000), U256::from(9_872)),
+            Err(Error::SlippageExceeded.into())
+        );
+        assert_eq!(swap(&mut pool, &mut token_a, 10_000), quote);
+        assert_eq!(token_b.balance_of(&trader), U256::from(10_009_871));
+        assert_eq!(
+            pool.get_reserves(),
+            (U256::from(1_010_000), U256::from(990_129))
+        );
+        assert!(env.emitted_event(
+            pool.address(),
+            &Swapped {
+                trader,
+                token_in: *token_a.address(),
+                amount_in: U256::from(10_000),
+                amount_out: quote
+            }
+        ));
+
+        // Swapping back returns less than was paid
+        let amount_out = swap(&mut pool, &mut token_b, 9_871);
+        assert!(amount_out < U256::from(10_000));
+        assert_eq!(
+            token_a.balance_of(&trader),
+            U256::from(10_000_000) - 10_000 + amount_out
+        );
+
+        assert_eq!(
+            pool.try_get_amount_out(*pool.address(), U256::from(10_000)),
+            Err(Error::UnknownToken.into())
+        );
+        assert_eq!(
+            pool.try_get_amount_out(*token_a.address(), U256::one()),
+            Err(Error::InsufficientOutput.into())
+        );
+    }
+
+    /// What one of the accounts does to the pool.
+    #[derive(Debug, Clone)]
+    enum Action {
+        SwapA(u64),
+        SwapB(u64),
+        AddLiquidity(u64, u64),
+        /// Removes a share of the account's liquidity: all of it, a half, a third or a quarter.
+        RemoveLiquidity(u64),
+    }
+
+    fn action() -> impl Strategy<Value = (usize, Action)> {
+        let amount = || 1_000..51_000u64;
+        let action = prop_oneof![
+            amount().prop_map(Action::SwapA),
+            amount().prop_map(Action::SwapB),
+            (amount(), amount()).prop_map(|(a, b)| Action::AddLiquidity(a, b)),
+            (1..5u64).prop_map(Action::RemoveLiquidity),
+        ];
+        (0..3usize, action)
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(16))]
+
+        #[test]
+        fn invariants(actions in vec(action(), 1..60)) {
+            let env = odra_test::env();
+            let (mut pool, mut token_a, mut token_b) = setup(&env);
+            add_liquidity(&mut pool, &mut token_a, &mut token_b, 1_000_000, 1_000_000);
+
+            let (mut reserve_a, mut reserve_b) = pool.get_reserves();
+            let mut total = pool.get_total_liquidity();
+            for (account, action) in actions {
+                let account = env.get_account(account);
+                env.set_caller(account);
+                match action {
+                    Action::SwapA(amount) => {
+                        swap(&mut pool, &mut token_a, amount);
+                    }
+                    Action::SwapB(amount) => {
+                        swap(&mut pool, &mut token_b, amount);
+                    }
+                    Action::AddLiquidity(amount_a, amount_b) => {
+                        add_liquidity(&mut pool, &mut token_a, &mut token_b, amount_a, amount_b);
+                    }
+                    Action::RemoveLiquidity(fraction) => {
+                        let shares = pool.liquidity_of(account);
+                        if shares < U256::from(1_000) {
+                            continue;
+                        }
+                        pool.remove_liquidity(shares / fraction, U256::zero(), U256::zero());
+                    }
+                }
+
+                // The reserves are backed by the pool's balances
+                let (new_reserve_a, new_reserve_b) = pool.get_reserves();
+                assert_eq!(token_a.balance_of(pool.address()), new_reserve_a);
+                assert_eq!(token_b.balance_of(pool.address()), new_reserve_b);
+
+                // The shares add up to the total
+                let new_total = pool.get_total_liquidity();
+                let shares = (0..3)
+                    .map(|i| pool.liquidity_of(env.get_account(i)))
+                    .fold(U256::from(MINIMUM_LIQUIDITY), |sum, shares| sum + shares);
+                assert_eq!(shares, new_total);
+
+                // reserve_a * reserve_b per share squared never decreases: swaps grow it with
+                // the fee, and deposits and withdrawals are rounded in the pool's favour
+                assert!(
+                    reserve_a * reserve_b * new_total * new_total
+                        <= new_reserve_a * new_reserve_b * total * total
+                );
+                (reserve_a, reserve_b, total) = (new_reserve_a, new_reserve_b, new_total);
+            }
+        }
+    }
+}
@@ -0,0 +1,523 @@
+use odra::args::Maybe;
+use odra::casper_types::U512;
+use odra::module::SubModule;
+use odra::prelude::*;
+use odra::{Address, Mapping, Var};
+use odra_modules::cep78::modalities::{
+    MetadataMutability, MintingMode, NFTIdentifierMode, NFTKind, NFTMetadataKind,
+    OwnerReverseLookupMode, OwnershipMode,
+};
+use odra_modules::cep78::token::Cep78;
+
+// Numbered past the error codes of the embedded CEP-78 module.
+#[odra::odra_error]
+/// Errors that may occur during the contract execution.
+pub enum Error {
+    /// There's no event with the given ID.
+    EventNotFound = 40_000,
+    /// Events need a name, a capacity and a start in the future.
+    InvalidEvent = 40_001,
+    /// Only the event's organizer can do this.
+    NotOrganizer = 40_002,
+    /// The event has started, so tickets are no longer sold.
+    SalesClosed = 40_003,
+    /// Every ticket to the event has been sold.
+    SoldOut = 40_004,
+    /// The attached CSPR doesn't match the ticket price.
+    IncorrectPayment = 40_005,
+    /// The token isn't a ticket sold by this contract.
+    TicketNotFound = 40_006,
+    /// The ticket has already been used.
+    AlreadyCheckedIn = 40_007,
+    /// The event has been cancelled.
+    EventCancelled = 40_008,
+    /// Refunds are only paid for cancelled events.
+    EventNotCancelled = 40_009,
+    /// Only the holder of the ticket can do this.
+    NotTicketHolder = 40_010,
+    /// Proceeds are only paid out once the event has started.
+    EventNotStarted = 40_011,
+    /// The proceeds have already been paid out.
+    NothingToWithdraw = 40_012,
+}
+
+#[odra::odra_type]
+pub struct EventInfo {
+    pub organizer: Address,
+    pub name: String,
+    pub price: U512,
+    /// Number of tickets for sale.
+    pub capacity: u32,
+    pub sold: u32,
+    /// Block time the event starts at, which ends ticket sales.
+    pub starts_at: u64,
+    pub cancelled: bool,
+    /// Ticket sales the organizer hasn't withdrawn, and refunds haven't paid back.
+    pub proceeds: U512,
+}
+
+#[odra::event]
+/// Emitted when an organizer creates an event.
+pub struct EventCreated {
+    pub event_id: u32,
+    pub organizer: Address,
+    pub price: U512,
+    pub capacity: u32,
+    pub starts_at: u64,
+}
+
+#[odra::event]
+/// Emitted when a ticket is bought.
+pub struct TicketSold {
+    pub event_id: u32,
+    pub token_id: u64,
+    pub buyer: Address,
+}
+
+#[odra::event]
+/// Emitted when a ticket is used to enter an event.
+pub struct CheckedIn {
+    pub event_id: u32,
+    pub token_id: u64,
+    pub holder: Address,
+}
+
+#[odra::event]
+/// Emitted when an organizer cancels an event.
+pub struct EventCancelled {
+    pub event_id: u32,
+}
+
+#[odra::event]
+/// Emitted when the holder of a ticket to a cancelled event gets its price back.
+pub struct TicketRefunded {
+    pub event_id: u32,
+    pub token_id: u64,
+    pub holder: Address,
+    pub amount: U512,
+}
+
+#[odra::module(
+    events = [EventCreated, TicketSold, CheckedIn, EventCancelled, TicketRefunded],
+    errors = Error
+)]
+/// Tickets to events, sold as CEP-78 tokens of a single collection.
+pub struct Ticketing {
+    tickets: SubModule<Cep78>,
+    events: Mapping<u32, EventInfo>,
+    event_count: Var<u32>,
+    /// Event each ticket gives access to.
+    ticket_events: Mapping<u64, u32>,
+    checked_in: Mapping<u64, bool>,
+}
+
+#[odra::module]
+impl Ticketing {
+    /// Initializes the ticket collection.
+    pub fn init(
+        &mut self,
+        collection_name: String,
+        collection_symbol: String,
+        total_token_supply: u64,
+    ) {
+        self.tickets.init(
+            collection_name,
+            collection_symbol,
+            total_token_supply,
+            OwnershipMode::Transferable,
+            NFTKind::Digital,
+            NFTIdentifierMode::Ordinal,
+            NFTMetadataKind::Raw,
+            MetadataMutability::Immutable,
+            "ticketing_receipt".to_string(),
+            Maybe::Some(true),
+            // Minting isn't an entry point, so only `buy` can mint
+            Maybe::Some(MintingMode::Public),
+            Maybe::None,
+            Maybe::None,
+            Maybe::None,
+            Maybe::None,
+            Maybe::None,
+            Maybe::None,
+            Maybe::Some(OwnerReverseLookupMode::NoLookUp),
+            Maybe::None,
+            Maybe::None,
+            Maybe::None,
+            Maybe::None,
+        );
+    }
+
+    delegate! {
+        to self.tickets {
+            fn approve(&mut self, spender: Address, token_id: Maybe<u64>, token_hash: Maybe<String>);
+            fn transfer(
+                &mut self,
+                token_id: Maybe<u64>,
+                token_hash: Maybe<String>,
+                source_key: Address,
+                target_key: Address
+            ) -> (String, Address);
+            fn owner_of(&self, token_id: Maybe<u64>, token_hash: Maybe<String>) -> Address;
+            fn balance_of(&mut self, token_owner: Address) -> u64;
+            fn metadata(&self, token_id: Maybe<u64>, token_hash: Maybe<String>) -> String;
+        }
+    }
+
+    /**********
+     * TRANSACTIONS
+     **********/
+
+    /// Creates an event selling `capacity` tickets at `price` until it starts. Returns the ID
+    /// of the event.
+    pub fn create_event(
+        &mut self,
+        name: String,
+        price: U512,
+        capacity: u32,
+        starts_at: u64,
+    ) -> u32 {
+        if name.is_empty() || capacity == 0 || starts_at <= self.env().get_block_time() {
+            self.env().revert(Error::InvalidEvent);
+        }
+
+        let organizer = self.env().caller();
+        let event_id = self.event_count.get_or_default();
+        self.events.set(
+            &event_id,
+            EventInfo {
+                organizer,
+                name,
+                price,
+                capacity,
+                sold: 0,
+                starts_at,
+                cancelled: false,
+                proceeds: U512::zero(),
+            },
+        );
+        self.event_count.set(event_id + 1);
+        self.env().emit_event(EventCreated {
+            event_id,
+            organizer,
+            price,
+            capacity,
+            starts_at,
+        });
+        event_id
+    }
+
+    /// Sells a ticket to the caller, who attaches its price. Returns the token ID.
+    #[odra(payable)]
+    pub fn buy(&mut self, event_id: u32) -> u64 {
+        let mut event = self.event(event_id);
+        self.assert_not_cancelled(&event);
+        if self.env().get_block_time() >= event.starts_at {
+            self.env().revert(Error::SalesClosed);
+        }
+        if event.sold == event.capacity {
+            self.env().revert(Error::SoldOut);
+        }
+        if self.env().attached_value() != event.price {
+            self.env().revert(Error::IncorrectPayment);
+        }
+
+        // Ordinal ids are assigned sequentially, so the next id is the number of minted tokens
+        let token_id = self.tickets.get_number_of_minted_tokens();
+        let buyer = self.env().caller();
+        self.tickets.mint(buyer, event.name.clone(), Maybe::None);
+        self.ticket_events.set(&token_id, event_id);
+        event.sold += 1;
+        event.proceeds += event.price;
+        self.events.set(&event_id, event);
+        self.env().emit_event(TicketSold {
+            event_id,
+            token_id,
+            buyer,
+        });
+        token_id
+    }
+
+    /// Marks a ticket as used, once its holder is at the door. Only the organizer can.
+    pub fn check_in(&mut self, token_id: u64) {
+        let event_id = self.ticket_event(token_id);
+        let event = self.event(event_id);
+        self.assert_organizer(&event);
+        self.assert_not_cancelled(&event);
+        if self.checked_in.get_or_default(&token_id) {
+            self.env().revert(Error::AlreadyCheckedIn);
+        }
+
+        self.checked_in.set(&token_id, true);
+        self.env().emit_event(CheckedIn {
+            event_id,
+            token_id,
+            holder: self.tickets.owner_of(Maybe::Some(token_id), Maybe::None),
+        });
+    }
+
+    /// Cancels an event that hasn't started. Only the organizer can.
+    pub fn cancel_event(&mut self, event_id: u32) {
+        let mut event = self.event(event_id);
+        self.assert_organizer(&event);
+        self.assert_not_cancelled(&event);
+        if self.env().get_block_time() >= event.starts_at {
+            self.env().revert(Error::SalesClosed);
+        }
+
+        event.cancelled = true;
+        self.events.set(&event_id, event);
+        self.env().emit_event(EventCancelled { event_id });
+    }
+
+    /// Burns a ticket to a cancelled event, and pays its price back to the holder.
+    pub fn refund(&mut self, token_id: u64) {
+        let event_id = self.ticket_event(token_id);
+        let mut event = self.event(event_id);
+        if !event.cancelled {
+            self.env().revert(Error::EventNotCancelled);
+        }
+        let holder = self.env().caller();
+        if self.tickets.owner_of(Maybe::Some(token_id), Maybe::None) != holder {
+            self.env().revert(Error::NotTicketHolder);
+        }
+
+        self.tickets.burn(Maybe::Some(token_id), Maybe::None);
+        event.proceeds -= event.price;
+        let amount = event.price;
+        self.events.set(&event_id, event);
+        self.env().transfer_tokens(&holder, &amount);
+        self.env().emit_event(TicketRefunded {
+            event_id,
+            token_id,
+            holder,
+            amount,
+        });
+    }
+
+    /// Pays the ticket sales of an event to the organizer, once it has started.
+    pub fn withdraw_proceeds(&mut self, event_id: u32) {
+        let mut event = self.event(event_id);
+        self.assert_organizer(&event);
+        self.assert_not_cancelled(&event);
+        if self.env().get_block_time() < event.starts_at {
+            self.env().revert(Error::EventNotStarted);
+        }
+        if event.proceeds.is_zero() {
+            self.env().revert(Error::NothingToWithdraw);
+        }
+
+        let amount = event.proceeds;
+        event.proceeds = U512::zero();
+        let organizer = event.organizer;
+        self.events.set(&event_id, event);
+        self.env().transfer_tokens(&organizer, &amount);
+    }
+
+    /**********
+     * QUERIES
+     **********/
+
+    /// Returns an event.
+    pub fn get_event(&self, event_id: u32) -> Option<EventInfo> {
+        self.events.get(&event_id)
+    }
+
+    /// Returns the number of events.
+    pub fn get_event_count(&self) -> u32 {
+        self.event_count.get_or_default()
+    }
+
+    /// Returns the event a ticket gives access to.
+    pub fn event_of(&self, token_id: u64) -> Option<u32> {
+        self.ticket_events.get(&token_id)
+    }
+
+    /// Returns whether a ticket has been used.
+    pub fn is_checked_in(&self, token_id: u64) -> bool {
+        self.checked_in.get_or_default(&token_id)
+    }
+}
+
+impl Ticketing {
+    fn event(&self, event_id: u32) -> EventInfo {
+        self.events
+            .get(&event_id)
+            .unwrap_or_revert_with(&self.env(), Error::EventNotFound)
+    }
+
+    fn ticket_event(&self, token_id: u64) -> u32 {
+        self.ticket_events
+            .get(&token_id)
+            .unwrap_or_revert_with(&self.env(), Error::TicketNotFound)
+    }
+
+    fn assert_organizer(&self, event: &EventInfo) {
+        if self.env().caller() != event.organizer {
+            self.env().revert(Error::NotOrganizer);
+        }
+    }
+
+    fn assert_not_cancelled(&self, event: &EventInfo) {
+        if event.cancelled {
+            self.env().revert(Error::EventCancelled);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use odra::host::{Deployer, HostEnv, HostRef};
+
+    const PRICE: u64 = 100;
+    const STARTS_AT: u64 = 10_000;
+
+    /// Deploys the contract, and has account 1 organize an event with two tickets, bought by
+    /// accounts 2 and 3.
+    fn setup(env: &HostEnv) -> (TicketingHostRef, u32) {
+        let mut ticketing = TicketingHostRef::deploy(
+            env,
+            TicketingInitArgs {
+                collection_name: "Tickets".to_string(),
+                collection_symbol: "TIX".to_string(),
+                total_token_supply: 100,
+            },
+        );
+        env.set_caller(env.get_account(1));
+        let id = ticketing.create_event("Odra Conf".to_string(), U512::from(PRICE), 2, STARTS_AT);
+        for account in [2, 3] {
+            env.set_caller(env.get_account(account));
+            ticketing.with_tokens(U512::from(PRICE)).buy(id);
+        }
+        (ticketing, id)
+    }
+
+    #[test]
+    fn selling_and_checking_in() {
+        let env = odra_test::env();
+        let (mut ticketing, id) = setup(&env);
+        let (organizer, alice, bob) = (env.get_account(1), env.get_account(2), env.get_account(3));
+
+        assert_eq!(
+            ticketing.with_tokens(U512::from(PRICE)).try_buy(id),
+            Err(Error::SoldOut.into())
+        );
+        assert_eq!(ticketing.owner_of(Maybe::Some(0), Maybe::None), alice);
+        assert_eq!(ticketing.event_of(1), Some(id));
+        assert_eq!(
+            ticketing.metadata(Maybe::Some(1), Maybe::None),
+            "Odra Conf".to_string()
+        );
+
+        // Tickets are tokens, and can be passed on
+        let carol = env.get_account(4);
+        env.set_caller(alice);
+        ticketing.transfer(Maybe::Some(0), Maybe::None, alice, carol);
+
+        env.set_caller(bob);
+        assert_eq!(ticketing.try_check_in(0), Err(Error::NotOrganizer.into()));
+        assert_eq!(
+            ticketing.try_refund(1),
+            Err(Error::EventNotCancelled.into())
+        );
+        env.set_caller(organizer);
+        ticketing.check_in(0);
+        assert!(ticketing.is_checked_in(0));
+        assert_eq!(
+            ticketing.try_check_in(0),
+            Err(Error::AlreadyCheckedIn.into())
+        );
+        assert_eq!(ticketing.try_check_in(2), Err(Error::TicketNotFound.into()));
+
+        // The organizer is paid once the event starts
+        assert_eq!(
+            ticketing.try_withdraw_proceeds(id),
+            Err(Error::EventNotStarted.into())
+        );
+        env.advance_block_time(STARTS_AT);
+        let balance = env.balance_of(&organizer);
+        ticketing.withdraw_proceeds(id);
+        assert_eq!(env.balance_of(&organizer), balance + U512::from(2 * PRICE));
+        assert_eq!(
+            ticketing.try_withdraw_proceeds(id),
+            Err(Error::NothingToWithdraw.into())
+        );
+        assert_eq!(
+            ticketing.try_cancel_event(id),
+            Err(Error::SalesClosed.into())
+        );
+    }
+
+    #[test]
+    fn cancelled_event_is_refunded() {
+        let env = odra_test::env();
+        let (mut ticketing, id) = setup(&env);
+        let (organizer, alice, bob) = (env.get_account(1), env.get_account(2), env.get_account(3));
+
+        env.set_caller(alice);
+        assert_eq!(
+            ticketing.try_cancel_event(id),
+            Err(Error::NotOrganizer.into())
+        );
+        env.set_caller(organizer);
+        ticketing.cancel_event(id);
+        assert_eq!(ticketing.try_check_in(0), Err(Error::EventCancelled.into()));
+        assert_eq!(
+            ticketing.try_withdraw_proceeds(id),
+            Err(Error::EventCancelled.into())
+        );
+
+        // Refunds go to whoever holds the ticket now
+        let carol = env.get_account(4);
+        env.set_caller(bob);
+        ticketing.transfer(Maybe::Some(1), Maybe::None, bob, carol);
+        assert_eq!(ticketing.try_refund(1), Err(Error::NotTicketHolder.into()));
+        for (account, token_id) in [(alice, 0), (carol, 1)] {
+            env.set_caller(account);
+            let balance = env.balance_of(&account);
+            ticketing.refund(token_id);
+            assert_eq!(env.balance_of(&account), balance + U512::from(PRICE));
+            assert_eq!(ticketing.balance_of(account), 0);
+        }
+        assert!(ticketing.try_refund(1).is_err());
+        assert_eq!(env.balance_of(ticketing.address()), U512::zero());
+    }
+
+    #[test]
+    fn invalid_events_and_purchases() {
+        let env = odra_test::env();
+        let (mut ticketing, _) = setup(&env);
+        let organizer = env.get_account(1);
+        let price = U512::from(PRICE);
+
+        env.set_caller(organizer);
+        assert_eq!(
+            ticketing.try_create_event(String::new(), price, 10, STARTS_AT),
+            Err(Error::InvalidEvent.into())
+        );
+        assert_eq!(
+            ticketing.try_create_event("Meetup".to_string(), price, 0, STARTS_AT),
+            Err(Error::InvalidEvent.into())
+        );
+        assert_eq!(
+            ticketing.try_create_event("Meetup".to_string(), price, 10, 0),
+            Err(Error::InvalidEvent.into())
+        );
+        let id = ticketing.create_event("Meetup".to_string(), price, 10, STARTS_AT);
+
+        env.set_caller(env.get_account(2));
+        assert_eq!(
+            ticketing.with_tokens(price - 1).try_buy(id),
+            Err(Error::IncorrectPayment.into())
+        );
+        assert_eq!(
+            ticketing.with_tokens(price).try_buy(id + 1),
+            Err(Error::EventNotFound.into())
+        );
+        env.advance_block_time(STARTS_AT);
+        assert_eq!(
+            ticketing.with_tokens(price).try_buy(id),
+            Err(Error::SalesClosed.into())
+        );
+    }
+}
@@ -0,0 +1,574 @@
+use odra::casper_types::U512;
+use odra::prelude::*;
+use odra::{Address, Mapping, Var};
+
+/// Rows, columns and diagonals, as indices of board cells.
+const LINES: [[usize; 3]; 8] = [
+    [0, 1, 2],
+    [3, 4, 5],
+    [6, 7, 8],
+    [0, 3, 6],
+    [1, 4, 7],
+    [2, 5, 8],
+    [0, 4, 8],
+    [2, 4, 6],
+];
+
+#[odra::odra_error]
+/// Errors that may occur during the contract execution.
+pub enum Error {
+    /// There's no game with the given ID.
+    GameNotFound = 1,
+    /// The game isn't in a state that allows this.
+    InvalidState = 2,
+    /// The attached CSPR doesn't match the game's wager.
+    WrongWager = 3,
+    /// Players can't join their own games.
+    CannotJoinOwnGame = 4,
+    /// Only the player who created the game can cancel it.
+    NotCreator = 5,
+    /// The caller isn't playing the game.
+    NotPlayer = 6,
+    /// It's the other player's turn.
+    NotYourTurn = 7,
+    /// The coordinates are outside the board.
+    InvalidCell = 8,
+    /// The cell is already marked.
+    CellTaken = 9,
+    /// The player to move still has time.
+    TimeoutNotReached = 10,
+}
+
+#[odra::odra_type]
+#[derive(Copy)]
+pub enum Mark {
+    X,
+    O,
+}
+
+impl Mark {
+    fn other(self) -> Mark {
+        match self {
+            Mark::X => Mark::O,
+            Mark::O => Mark::X,
+        }
+    }
+}
+
+#[odra::odra_type]
+pub enum GameStatus {
+    /// Waiting for a second player to join.
+    WaitingForOpponent,
+    /// The players are taking turns.
+    InProgress,
+    /// A player completed a line, or the opponent ran out of time.
+    Won,
+    /// The board is full and nobody won.
+    Draw,
+    /// The creator cancelled the game before anyone joined.
+    Cancelled,
+}
+
+#[odra::odra_type]
+pub struct Game {
+    /// The creator, who plays X and moves first.
+    pub player_x: Address,
+    pub player_o: Option<Address>,
+    /// Amount each player puts in the pot.
+    pub wager: U512,
+    /// Cells row by row, top left first.
+    pub board: Vec<Option<Mark>>,
+    pub turn: Mark,
+    pub status: GameStatus,
+    pub winner: Option<Address>,
+    /// Block time of the last move, or of joining before the first one.
+    pub last_move_at: u64,
+}
+
+impl Game {
+    fn player(&self, mark: Mark) -> Option<Address> {
+        match mark {
+            Mark::X => Some(self.player_x),
+            Mark::O => self.player_o,
+        }
+    }
+
+    fn has_line(&self, mark: Mark) -> bool {
+        LINES
+            .iter()
+            .any(|line| line.iter().all(|&cell| self.board[cell] == Some(mark)))
+    }
+
+    fn is_full(&self) -> bool {
+        self.board.iter().all(Option::is_some)
+    }
+}
+
+#[odra::event]
+/// Emitted when a player creates a game.
+pub struct GameCreated {
+    pub game_id: u32,
+    pub player_x: Address,
+    pub wager: U512,
+}
+
+#[odra::event]
+/// Emitted when a second player joins a game.
+pub struct GameJoined {
+    pub game_id: u32,
+    pub player_o: Address,
+}
+
+#[odra::event]
+/// Emitted when a player marks a cell.
+pub struct MovePlayed {
+    pub game_id: u32,
+    pub player: Address,
+    pub x: u8,
+    pub y: u8,
+}
+
+#[odra::event]
+/// Emitted when a game is won, drawn or cancelled.
+pub struct GameFinished {
+    pub game_id: u32,
+    pub status: GameStatus,
+    pub winner: Option<Address>,
+}
+
+#[odra::module(
+    events = [GameCreated, GameJoined, MovePlayed, GameFinished],
+    errors = Error
+)]
+/// Tic-tac-toe games between two players, with an optional wager.
+pub struct TicTacToe {
+    /// How long a player has to move before the opponent can claim the game.
+    move_timeout: Var<u64>,
+    games: Mapping<u32, Game>,
+    game_count: Var<u32>,
+}
+
+#[odra::module]
+impl TicTacToe {
+    /// Initializes the contract with the time players have for each move.
+    pub fn init(&mut self, move_timeout: u64) {
+        self.move_timeout.set(move_timeout);
+    }
+
+    /**********
+     * TRANSACTIONS
+     **********/
+
+    /// Creates a game with the attached CSPR as the wager, which the opponent has to match.
+    /// The caller plays X. Returns the ID of the game.
+    #[odra(payable)]
+    pub fn create_game(&mut self) -> u32 {
+        let player_x = self.env().caller();
+        let wager = self.env().attached_value();
+
+        let game_id = self.game_count.get_or_default();
+        self.games.set(
+            &game_id,
+            Game {
+                player_x,
+                player_o: None,
+                wager,
+                board: vec![None; 9],
+                turn: Mark::X,
+                status: GameStatus::WaitingForOpponent,
+                winner: None,
+                last_move_at: 0,
+            },
+        );
+        self.game_count.set(game_id + 1);
+        self.env().emit_event(GameCreated {
+            game_id,
+            player_x,
+            wager,
+        });
+        game_id
+    }
+
+    /// Joins a game as O, attaching the same wager as the creator.
+    #[odra(payable)]
+    pub fn join(&mut self, game_id: u32) {
+        let mut game = self.game(game_id);
+        self.assert_status(&game, GameStatus::WaitingForOpponent);
+        let player_o = self.env().caller();
+        if player_o == game.player_x {
+            self.env().revert(Error::CannotJoinOwnGame);
+        }
+        if self.env().attached_value() != game.wager {
+            self.env().revert(Error::WrongWager);
+        }
+
+        game.player_o = Some(player_o);
+        game.status = GameStatus::InProgress;
+        game.last_move_at = self.env().get_block_time();
+        self.games.set(&game_id, game);
+        self.env().emit_event(GameJoined { game_id, player_o });
+    }
+
+    /// Cancels a game nobody has joined, and refunds the wager.
+    pub fn cancel(&mut self, game_id: u32) {
+        let mut game = self.game(game_id);
+        if self.env().caller() != game.player_x {
+            self.env().revert(Error::NotCreator);
+        }
+        self.assert_status(&game, GameStatus::WaitingForOpponent);
+
+        game.status = GameStatus::Cancelled;
+        self.pay(&game.player_x, game.wager);
+        self.finish(game_id, game);
+    }
+
+    /// Marks the cell in column `x` and row `y`, both counted from 0. Completing a line wins
+    /// the pot, and filling the board without one refunds both wagers.
+    pub fn play(&mut self, game_id: u32, x: u8, y: u8) {
+        let mut game = self.game(game_id);
+        self.assert_status(&game, GameStatus::InProgress);
+        let player = self.env().caller();
+        if Some(player) != game.player(game.turn) {
+            if Some(player) == game.player(game.turn.other()) {
+                self.env().revert(Error::NotYourTurn);
+            }
+            self.env().revert(Error::NotPlayer);
+        }
+        if x > 2 || y > 2 {
+            self.env().revert(Error::InvalidCell);
+        }
+        let cell = usize::from(y * 3 + x);
+        if game.board[cell].is_some() {
+            self.env().revert(Error::CellTaken);
+        }
+
+        game.board[cell] = Some(game.turn);
+        self.env().emit_event(MovePlayed {
+            game_id,
+            player,
+            x,
+            y,
+        });
+
+        if game.has_line(game.turn) {
+            game.status = GameStatus::Won;
+            game.winner = Some(player);
+            self.pay(&player, game.wager * 2);
+            self.finish(game_id, game);
+        } else if game.is_full() {
+            game.status = GameStatus::Draw;
+            for mark in [Mark::X, Mark::O] {
+                let account = game.player(mark).unwrap_or_revert(&self.env());
+                self.pay(&account, game.wager);
+            }
+            self.finish(game_id, game);
+        } else {
+            game.turn = game.turn.other();
+            game.last_move_at = self.env().get_block_time();
+            self.games.set(&game_id, game);
+        }
+    }
+
+    /// Ends a game whose player to move ran out of time, and pays the pot to the opponent.
+    /// Anyone can call it.
+    pub fn claim_timeout(&mut self, game_id: u32) {
+        let mut game = self.game(game_id);
+        self.assert_status(&game, GameStatus::InProgress);
+        let deadline = game.last_move_at + self.move_timeout.get_or_default();
+        if self.env().get_block_time() < deadline {
+            self.env().revert(Error::TimeoutNotReached);
+        }
+
+        let winner = game.player(game.turn.other()).unwrap_or_revert(&self.env());
+        game.status = GameStatus::Won;
+        game.winner = Some(winner);
+        self.pay(&winner, game.wager * 2);
+        self.finish(game_id, game);
+    }
+
+    /**********
+     * QUERIES
+     **********/
+
+    /// Returns a game.
+    pub fn get_game(&self, game_id: u32) -> Option<Game> {
+        self.games.get(&game_id)
+    }
+
+    /// Returns the number of games.
+    pub fn get_game_count(&self) -> u32 {
+        self.game_count.get_or_default()
+    }
+
+    /// Returns the time players have for each move.
+    pub fn get_move_timeout(&self) -> u64 {
+        self.move_timeout.get_or_default()
+    }
+}
+
+impl TicTacToe {
+    fn game(&self, game_id: u32) -> Game {
+        self.games
+            .get(&game_id)
+            .unwrap_or_revert_with(&self.env(), Error::GameNotFound)
+    }
+
+    fn assert_status(&self, game: &Game, status: GameStatus) {
+        if game.status != status {
+            self.env().revert(Error::InvalidState);
+        }
+    }
+
+    fn pay(&self, account: &Address, amount: U512) {
+        // Games without a wager have nothing to pay
+        if !amount.is_zero() {
+            self.env().transfer_tokens(account, &amount);
+        }
+    }
+
+    fn finish(&mut self, game_id: u32, game: Game) {
+        self.env().emit_event(GameFinished {
+            game_id,
+            status: game.status.clone(),
+            winner: game.winner,
+        });
+        self.games.set(&game_id, game);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use odra::host::{Deployer, HostEnv, HostRef};
+    use proptest::prelude::*;
+
+    const MOVE_TIMEOUT: u64 = 1_000;
+    const WAGER: u64 = 100;
+
+    /// Starts a game between account 1 as X and account 2 as O.
+    fn setup(env: &HostEnv, wager: u64) -> (TicTacToeHostRef, u32) {
+        let mut contract = TicTacToeHostRef::deploy(
+            env,
+            TicTacToeInitArgs {
+                move_timeout: MOVE_TIMEOUT,
+            },
+        );
+        env.set_caller(env.get_account(1));
+        let id = contract.with_tokens(U512::from(wager)).create_game();
+        env.set_caller(env.get_account(2));
+        contract.with_tokens(U512::from(wager)).join(id);
+        (contract, id)
+    }
+
+    /// Plays cells by index, alternating between X and O.
+    fn play_cells(env: &HostEnv, contract: &mut TicTacToeHostRef, id: u32, cells: &[u8]) {
+        for (i, cell) in cells.iter().enumerate() {
+            env.set_caller(env.get_account(1 + i % 2));
+            contract.play(id, cell % 3, cell / 3);
+        }
+    }
+
+    #[test]
+    fn winner_takes_the_pot() {
+        let env = odra_test::env();
+        let (mut contract, id) = setup(&env, WAGER);
+        let (x, o) = (env.get_account(1), env.get_account(2));
+        assert_eq!(env.balance_of(contract.address()), U512::from(2 * WAGER));
+
+        env.set_caller(o);
+        assert_eq!(contract.try_play(id, 0, 0), Err(Error::NotYourTurn.into()));
+        env.set_caller(env.get_account(3));
+        assert_eq!(contract.try_play(id, 0, 0), Err(Error::NotPlayer.into()));
+        env.set_caller(x);
+        assert_eq!(contract.try_play(id, 3, 0), Err(Error::InvalidCell.into()));
+        contract.play(id, 1, 1);
+        env.set_caller(o);
+        assert_eq!(contract.try_play(id, 1, 1), Err(Error::CellTaken.into()));
+
+        // X completes the diagonal from the top left
+        contract.play(id, 1, 0);
+        let balance = env.balance_of(&x);
+        play_cells(&env, &mut contract, id, &[0, 5, 8]);
+        let game = contract.get_game(id).unwrap();
+        assert_eq!(game.status, GameStatus::Won);
+        assert_eq!(game.winner, Some(x));
+        assert_eq!(env.balance_of(&x), balance + U512::from(2 * WAGER));
+        assert_eq!(env.balance_of(contract.address()), U512::zero());
+
+        env.set_caller(o);
+        assert_eq!(contract.try_play(id, 2, 2), Err(Error::InvalidState.into()));
+        assert_eq!(
+            contract.try_claim_timeout(id),
+            Err(Error::InvalidState.into())
+        );
+    }
+
+    #[test]
+    fn every_line_wins() {
+        let env = odra_test::env();
+        let mut contract = TicTacToeHostRef::deploy(
+            &env,
+            TicTacToeInitArgs {
+                move_timeout: MOVE_TIMEOUT,
+            },
+        );
+        for (mark, player) in [(Mark::X, 1), (Mark::O, 2)] {
+            for line in LINES {
+                env.set_caller(env.get_account(1));
+                let id = contract.create_game();
+                env.set_caller(env.get_account(2));
+                contract.join(id);
+
+                // The loser marks cells off the line that don't make a line of their own
+                let mut losing = Vec::new();
+                for cell in (0..9).filter(|cell| !line.contains(cell)) {
+                    let mut cells = losing.clone();
+                    cells.push(cell);
+                    if !LINES.iter().any(|l| l.iter().all(|c| cells.contains(c))) {
+                        losing.push(cell);
+                    }
+                }
+                // X moves first, so X wins on the fifth move and O on the sixth
+                let cells: Vec<u8> = match mark {
+                    Mark::X => vec![line[0], losing[0], line[1], losing[1], line[2]],
+                    Mark::O => vec![losing[0], line[0], losing[1], line[1], losing[2], line[2]],
+                }
+                .into_iter()
+                .map(|cell| cell as u8)
+                .collect();
+                play_cells(&env, &mut contract, id, &cells);
+
+                let game = contract.get_game(id).unwrap();
+                assert_eq!(game.status, GameStatus::Won);
+                assert_eq!(game.winner, Some(env.get_account(player)));
+            }
+        }
+    }
+
+    #[test]
+    fn draw_refunds_both_wagers() {
+        let env = odra_test::env();
+        let (mut contract, id) = setup(&env, WAGER);
+        let (x, o) = (env.get_account(1), env.get_account(2));
+        let (x_balance, o_balance) = (env.balance_of(&x), env.balance_of(&o));
+
+        // X O X
+        // X O O
+        // O X X
+        play_cells(&env, &mut contract, id, &[0, 1, 2, 4, 3, 5, 7, 6, 8]);
+        let game = contract.get_game(id).unwrap();
+        assert_eq!(game.status, GameStatus::Draw);
+        assert_eq!(game.winner, None);
+        assert_eq!(env.balance_of(&x), x_balance + U512::from(WAGER));
+        assert_eq!(env.balance_of(&o), o_balance + U512::from(WAGER));
+    }
+
+    #[test]
+    fn joining_and_cancelling() {
+        let env = odra_test::env();
+        let mut contract = TicTacToeHostRef::deploy(
+            &env,
+            TicTacToeInitArgs {
+                move_timeout: MOVE_TIMEOUT,
+            },
+        );
+        let (x, o) = (env.get_account(1), env.get_account(2));
+        assert_eq!(contract.try_join(0), Err(Error::GameNotFound.into()));
+
+        env.set_caller(x);
+        let id = contract.with_tokens(U512::from(WAGER)).create_game();
+        assert_eq!(
+            contract.with_tokens(U512::from(WAGER)).try_join(id),
+            Err(Error::CannotJoinOwnGame.into())
+        );
+        env.set_caller(o);
+        assert_eq!(
+            contract.with_tokens(U512::from(WAGER - 1)).try_join(id),
+            Err(Error::WrongWager.into())
+        );
+        assert_eq!(contract.try_play(id, 0, 0), Err(Error::InvalidState.into()));
+        assert_eq!(contract.try_cancel(id), Err(Error::NotCreator.into()));
+
+        env.set_caller(x);
+        let balance = env.balance_of(&x);
+        contract.cancel(id);
+        assert_eq!(env.balance_of(&x), balance + U512::from(WAGER));
+        assert_eq!(contract.get_game(id).unwrap().status, GameStatus::Cancelled);
+        env.set_caller(o);
+        assert_eq!(
+            contract.with_tokens(U512::from(WAGER)).try_join(id),
+            Err(Error::InvalidState.into())
+        );
+    }
+
+    #[test]
+    fn abandoned_game_is_claimed() {
+        let env = odra_test::env();
+        let (mut contract, id) = setup(&env, WAGER);
+        let o = env.get_account(2);
+        play_cells(&env, &mut contract, id, &[4, 0]);
+
+        // X stops moving, and anyone can end the game once the timeout passes
+        env.advance_block_time(MOVE_TIMEOUT - 1);
+        env.set_caller(env.get_account(3));
+        assert_eq!(
+            contract.try_claim_timeout(id),
+            Err(Error::TimeoutNotReached.into())
+        );
+        env.advance_block_time(1);
+        let balance = env.balance_of(&o);
+        contract.claim_timeout(id);
+        let game = contract.get_game(id).unwrap();
+        assert_eq!(game.status, GameStatus::Won);
+        assert_eq!(game.winner, Some(o));
+        assert_eq!(env.balance_of(&o), balance + U512::from(2 * WAGER));
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        /// Plays the cells in a random order, alternating players, until the game ends.
+        #[test]
+        fn random_games_match_a_referee(
+            cells in Just((0..9).collect::<Vec<usize>>()).prop_shuffle()
+        ) {
+            let env = odra_test::env();
+            let mut contract = TicTacToeHostRef::deploy(
+                &env,
+                TicTacToeInitArgs {
+                    move_timeout: MOVE_TIMEOUT,
+                },
+            );
+            env.set_caller(env.get_account(1));
+            let id = contract.create_game();
+            env.set_caller(env.get_account(2));
+            contract.join(id);
+
+            // The referee plays the same moves on its own board, and stops at the first line
+            let mut board = [None; 9];
+            let mut winner = None;
+            for (turn, cell) in cells.into_iter().enumerate() {
+                board[cell] = Some(turn % 2);
+                env.set_caller(env.get_account(1 + turn % 2));
+                contract.play(id, (cell % 3) as u8, (cell / 3) as u8);
+
+                let won = LINES
+                    .iter()
+                    .any(|line| line.iter().all(|&c| board[c] == Some(turn % 2)));
+                if won {
+                    winner = Some(env.get_account(1 + turn % 2));
+                    break;
+                }
+            }
+
+            let game = contract.get_game(id).unwrap();
+            assert_eq!(game.winner, winner);
+            let status = if winner.is_some() {
+                GameStatus::Won
+            } else {
+                GameStatus::Draw
+            };
+            assert_eq!(game.status, status);
+        }
+    }
+}
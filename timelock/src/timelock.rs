@@ -0,0 +1,367 @@
+use odra::casper_types::bytesrepr::{deserialize_from_slice, Bytes};
+use odra::casper_types::{RuntimeArgs, U512};
+use odra::module::SubModule;
+use odra::prelude::*;
+use odra::{Address, CallDef, Mapping, Var};
+use odra_modules::access::{AccessControl, Role, DEFAULT_ADMIN_ROLE};
+
+// Role ids only have to differ from each other and from `DEFAULT_ADMIN_ROLE`, which is all zeros.
+/// Role allowed to queue and cancel calls.
+pub const PROPOSER_ROLE: Role = [1; 32];
+/// Role allowed to execute calls once they're ready.
+pub const EXECUTOR_ROLE: Role = [2; 32];
+
+#[odra::odra_error]
+/// Errors that may occur during the contract execution.
+pub enum Error {
+    /// The call has to wait at least the minimum delay.
+    InsufficientDelay = 1,
+    /// There's no call with the given ID.
+    OperationNotFound = 2,
+    /// The call has already been executed or cancelled.
+    NotQueued = 3,
+    /// The delay of the call hasn't passed yet.
+    NotReady = 4,
+    /// Only the timelock itself can change its settings.
+    NotTimelock = 5,
+    /// The call arguments aren't serialized `RuntimeArgs`.
+    InvalidArgs = 6,
+}
+
+#[odra::odra_type]
+pub enum OperationState {
+    Queued,
+    Executed,
+    Cancelled,
+}
+
+#[odra::odra_type]
+/// A delayed call to an entry point that returns nothing.
+pub struct Operation {
+    pub target: Address,
+    pub entry_point: String,
+    /// Serialized `RuntimeArgs` of the call.
+    pub args: Bytes,
+    /// CSPR attached to the call, paid from the timelock's balance.
+    pub value: U512,
+    /// Block time the call can be executed at.
+    pub ready_at: u64,
+    pub state: OperationState,
+}
+
+#[odra::event]
+/// Emitted when a call is queued.
+pub struct CallQueued {
+    pub id: u32,
+    pub target: Address,
+    pub entry_point: String,
+    pub ready_at: u64,
+}
+
+#[odra::event]
+/// Emitted when a queued call is executed.
+pub struct CallExecuted {
+    pub id: u32,
+}
+
+#[odra::event]
+/// Emitted when a queued call is cancelled.
+pub struct CallCancelled {
+    pub id: u32,
+}
+
+#[odra::event]
+/// Emitted when the minimum delay changes.
+pub struct MinDelayChanged {
+    pub old_delay: u64,
+    pub new_delay: u64,
+}
+
+#[odra::module(events = [CallQueued, CallExecuted, CallCancelled, MinDelayChanged], errors = Error)]
+/// Delays contract calls, so everyone has time to react before they take effect.
+pub struct Timelock {
+    /// Shortest delay, in milliseconds, a call can be queued with.
+    min_delay: Var<u64>,
+    operations: Mapping<u32, Operation>,
+    /// Number of queued calls, used as the next call ID.
+    operation_count: Var<u32>,
+    access_control: SubModule<AccessControl>,
+}
+
+#[odra::module]
+impl Timelock {
+    /// Initializes the timelock; the deployer becomes the admin who can grant and revoke roles.
+    pub fn init(&mut self, min_delay: u64, proposers: Vec<Address>, executors: Vec<Address>) {
+        self.min_delay.set(min_delay);
+        let admin = self.env().caller();
+        self.access_control
+            .unchecked_grant_role(&DEFAULT_ADMIN_ROLE, &admin);
+        for proposer in proposers {
+            self.access_control
+                .unchecked_grant_role(&PROPOSER_ROLE, &proposer);
+        }
+        for executor in executors {
+            self.access_control
+                .unchecked_grant_role(&EXECUTOR_ROLE, &executor);
+        }
+    }
+
+    /**********
+     * TRANSACTIONS
+     **********/
+
+    /// Queues a call that can be executed after `delay` milliseconds. Only proposers can call it.
+    pub fn queue(
+        &mut self,
+        target: Address,
+        entry_point: String,
+        args: Bytes,
+        value: U512,
+        delay: u64,
+    ) -> u32 {
+        self.access_control
+            .check_role(&PROPOSER_ROLE, &self.env().caller());
+        if delay < self.min_delay.get_or_default() {
+            self.env().revert(Error::InsufficientDelay);
+        }
+
+        let id = self.operation_count.get_or_default();
+        self.operation_count.set(id + 1);
+        let ready_at = self.env().get_block_time() + delay;
+        self.operations.set(
+            &id,
+            Operation {
+                target,
+                entry_point: entry_point.clone(),
+                args,
+                value,
+                ready_at,
+                state: OperationState::Queued,
+            },
+        );
+        self.env().emit_event(CallQueued {
+            id,
+            target,
+            entry_point,
+            ready_at,
+        });
+        id
+    }
+
+    /// Executes a queued call whose delay has passed. Only executors can call it.
+    pub fn execute(&mut self, id: u32) {
+        self.access_control
+            .check_role(&EXECUTOR_ROLE, &self.env().caller());
+        let mut operation = self.queued_operation(id);
+        if self.env().get_block_time() < operation.ready_at {
+            self.env().revert(Error::NotReady);
+        }
+
+        // Mark the call as executed before making it
+        operation.state = OperationState::Executed;
+        self.operations.set(&id, operation.clone());
+        let args: RuntimeArgs = deserialize_from_slice(operation.args)
+            .unwrap_or_revert_with(&self.env(), Error::InvalidArgs);
+        self.env().call_contract::<()>(
+            operation.target,
+            CallDef::new(operation.entry_point, true, args).with_amount(operation.value),
+        );
+        self.env().emit_event(CallExecuted { id });
+    }
+
+    /// Cancels a queued call. Only proposers can call it.
+    pub fn cancel(&mut self, id: u32) {
+        self.access_control
+            .check_role(&PROPOSER_ROLE, &self.env().caller());
+        let mut operation = self.queued_operation(id);
+        operation.state = OperationState::Cancelled;
+        self.operations.set(&id, operation);
+        self.env().emit_event(CallCancelled { id });
+    }
+
+    /// Changes the minimum delay. It has to go through the timelock itself, i.e. be queued and executed.
+    pub fn update_delay(&mut self, min_delay: u64) {
+        if self.env().caller() != self.env().self_address() {
+            self.env().revert(Error::NotTimelock);
+        }
+        self.env().emit_event(MinDelayChanged {
+            old_delay: self.min_delay.get_or_default(),
+            new_delay: min_delay,
+        });
+        self.min_delay.set(min_delay);
+    }
+
+    /// Adds the attached CSPR to the balance queued calls are paid from.
+    #[odra(payable)]
+    pub fn deposit(&mut self) {}
+
+    delegate! {
+        to self.access_control {
+            fn has_role(&self, role: &Role, address: &Address) -> bool;
+            fn grant_role(&mut self, role: &Role, address: &Address);
+            fn revoke_role(&mut self, role: &Role, address: &Address);
+            fn renounce_role(&mut self, role: &Role, address: &Address);
+        }
+    }
+
+    /**********
+     * QUERIES
+     **********/
+
+    /// Returns a queued call.
+    pub fn get_operation(&self, id: u32) -> Option<Operation> {
+        self.operations.get(&id)
+    }
+
+    /// Returns the number of queued calls.
+    pub fn get_operation_count(&self) -> u32 {
+        self.operation_count.get_or_default()
+    }
+
+    /// Returns the shortest delay a call can be queued with.
+    pub fn get_min_delay(&self) -> u64 {
+        self.min_delay.get_or_default()
+    }
+
+    /**********
+     * INTERNAL
+     **********/
+
+    /// Returns a call that's still waiting to be executed.
+    fn queued_operation(&self, id: u32) -> Operation {
+        let operation = self
+            .operations
+            .get(&id)
+            .unwrap_or_revert_with(&self.env(), Error::OperationNotFound);
+        if operation.state != OperationState::Queued {
+            self.env().revert(Error::NotQueued);
+        }
+        operation
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use odra::casper_types::bytesrepr::ToBytes;
+    use odra::casper_types::runtime_args;
+    use odra::host::{Deployer, HostEnv, HostRef};
+
+    const MIN_DELAY: u64 = 1_000;
+
+    /// Deploys a timelock with account 1 as the proposer and account 2 as the executor.
+    fn setup(env: &HostEnv) -> TimelockHostRef {
+        TimelockHostRef::deploy(
+            env,
+            TimelockInitArgs {
+                min_delay: MIN_DELAY,
+                proposers: vec![env.get_account(1)],
+                executors: vec![env.get_account(2)],
+            },
+        )
+    }
+
+    /// Queues a call changing the timelock's own minimum delay.
+    fn queue_update_delay(timelock: &mut TimelockHostRef, min_delay: u64, delay: u64) -> u32 {
+        let args = runtime_args! { "min_delay" => min_delay };
+        timelock.queue(
+            *timelock.address(),
+            "update_delay".to_string(),
+            Bytes::from(args.to_bytes().unwrap()),
+            U512::zero(),
+            delay,
+        )
+    }
+
+    #[test]
+    fn delayed_execution() {
+        let env = odra_test::env();
+        let mut timelock = setup(&env);
+
+        env.set_caller(env.get_account(1));
+        let id = queue_update_delay(&mut timelock, 5_000, MIN_DELAY);
+
+        env.set_caller(env.get_account(2));
+        assert_eq!(timelock.try_execute(id), Err(Error::NotReady.into()));
+        env.advance_block_time(MIN_DELAY);
+        timelock.execute(id);
+        assert_eq!(timelock.get_min_delay(), 5_000);
+        assert!(env.emitted_event(
+            timelock.address(),
+            &MinDelayChanged {
+                old_delay: MIN_DELAY,
+                new_delay: 5_000
+            }
+        ));
+        assert_eq!(
+            timelock.get_operation(id).unwrap().state,
+            OperationState::Executed
+        );
+        assert_eq!(timelock.try_execute(id), Err(Error::NotQueued.into()));
+    }
+
+    #[test]
+    fn cancelled_calls_cant_be_executed() {
+        let env = odra_test::env();
+        let mut timelock = setup(&env);
+
+        env.set_caller(env.get_account(1));
+        let id = queue_update_delay(&mut timelock, 5_000, MIN_DELAY);
+        timelock.cancel(id);
+        assert!(env.emitted_event(timelock.address(), &CallCancelled { id }));
+        assert_eq!(timelock.try_cancel(id), Err(Error::NotQueued.into()));
+        assert_eq!(timelock.try_cancel(7), Err(Error::OperationNotFound.into()));
+
+        env.advance_block_time(MIN_DELAY);
+        env.set_caller(env.get_account(2));
+        assert_eq!(timelock.try_execute(id), Err(Error::NotQueued.into()));
+        assert_eq!(timelock.get_min_delay(), MIN_DELAY);
+    }
+
+    #[test]
+    fn roles_and_delays() {
+        let env = odra_test::env();
+        let mut timelock = setup(&env);
+        let (admin, proposer, executor) =
+            (env.get_account(0), env.get_account(1), env.get_account(2));
+
+        // Only proposers queue, with at least the minimum delay
+        env.set_caller(executor);
+        assert!(timelock
+            .try_queue(
+                *timelock.address(),
+                "update_delay".to_string(),
+                Bytes::new(),
+                U512::zero(),
+                MIN_DELAY
+            )
+            .is_err());
+        env.set_caller(proposer);
+        assert_eq!(
+            timelock.try_queue(
+                *timelock.address(),
+                "update_delay".to_string(),
+                Bytes::new(),
+                U512::zero(),
+                MIN_DELAY - 1
+            ),
+            Err(Error::InsufficientDelay.into())
+        );
+
+        // Only executors execute
+        let id = queue_update_delay(&mut timelock, 0, MIN_DELAY);
+        env.advance_block_time(MIN_DELAY);
+        assert!(timelock.try_execute(id).is_err());
+
+        // The settings can't be changed directly, not even by the admin
+        env.set_caller(admin);
+        assert_eq!(timelock.try_update_delay(0), Err(Error::NotTimelock.into()));
+
+        // The admin can make anyone an executor
+        timelock.grant_role(&EXECUTOR_ROLE, &proposer);
+        assert!(timelock.has_role(&EXECUTOR_ROLE, &proposer));
+        env.set_caller(proposer);
+        timelock.execute(id);
+        assert_eq!(timelock.get_min_delay(), 0);
+    }
+}
@@ -0,0 +1,47 @@
+//! Gas budgets for livenet calls, retried with a bigger budget when a call runs out of gas.
+use odra::host::HostEnv;
+use odra::prelude::{ExecutionError, OdraError, OdraResult};
+
+/// Installing a contract.
+pub const DEPLOY: u64 = 400_000_000_000;
+/// Minting a token.
+pub const MINT: u64 = 5_000_000_000;
+/// Transferring a token or CSPR.
+pub const TRANSFER: u64 = 3_000_000_000;
+/// Placing a bid in an auction.
+pub const BID: u64 = 5_000_000_000;
+/// Any other contract call.
+pub const CALL: u64 = 3_000_000_000;
+
+/// Number of times an action is attempted before its out-of-gas error is returned.
+const MAX_ATTEMPTS: u32 = 3;
+
+/// Runs `action` with a gas budget of `amount`, doubling the budget and retrying
+/// whenever it fails with an out-of-gas error.
+///
+/// The action may run more than once, so it should build its arguments itself.
+pub fn with_gas<T>(
+    env: &HostEnv,
+    amount: u64,
+    mut action: impl FnMut() -> OdraResult<T>,
+) -> OdraResult<T> {
+    let mut gas = amount;
+    let mut attempt = 1;
+    loop {
+        env.set_gas(gas);
+        let result = action();
+        if attempt == MAX_ATTEMPTS || !is_out_of_gas(&result) {
+            return result;
+        }
+        println!("Ran out of {} motes of gas, retrying with {}", gas, gas * 2);
+        gas *= 2;
+        attempt += 1;
+    }
+}
+
+fn is_out_of_gas<T>(result: &OdraResult<T>) -> bool {
+    matches!(
+        result,
+        Err(OdraError::ExecutionError(ExecutionError::OutOfGas))
+    )
+}
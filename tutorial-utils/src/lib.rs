@@ -0,0 +1,4 @@
+//! Helpers shared by the livenet binaries of the tutorials.
+pub mod gas;
+pub mod manifest;
+pub mod network;
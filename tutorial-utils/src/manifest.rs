@@ -0,0 +1,134 @@
+//! Records livenet deployments in a `deployments.json` manifest, so later runs
+//! can pick up contract addresses without copy-pasting hashes into the sources.
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use odra::host::{HostEnv, HostRef, HostRefLoader};
+use odra::Address;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Manifest file, written to the directory the binary is run from.
+pub const MANIFEST_PATH: &str = "deployments.json";
+
+/// Variable the livenet env reads the chain name from.
+const CHAIN_NAME_VAR: &str = "ODRA_CASPER_LIVENET_CHAIN_NAME";
+
+/// A single contract deployment.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Deployment {
+    /// Chain name of the network the contract was deployed to.
+    pub network: String,
+    /// Name the contract is looked up by.
+    pub contract_name: String,
+    /// Address of the contract package.
+    pub package_hash: String,
+    /// Hash of the deploy that installed the contract, if known.
+    /// Odra's `HostEnv` doesn't expose it, so it can be passed in by hand.
+    pub deploy_hash: Option<String>,
+    /// Unix timestamp (in seconds) of the deployment.
+    pub timestamp: u64,
+    /// Init args the contract was deployed with, rendered as strings.
+    pub init_args: Map<String, Value>,
+}
+
+/// Appends a deployment of `contract` to the manifest.
+///
+/// Call it after `odra_casper_livenet_env::env()`, which loads the `.env` file
+/// the network name is read from.
+pub fn record_deployment<R: HostRef>(
+    contract_name: &str,
+    contract: &R,
+    deploy_hash: Option<String>,
+    init_args: &[(&str, String)],
+) {
+    let deployment = Deployment {
+        network: network(),
+        contract_name: contract_name.to_string(),
+        package_hash: contract.address().to_string(),
+        deploy_hash,
+        timestamp: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("System time should be after the Unix epoch")
+            .as_secs(),
+        init_args: init_args
+            .iter()
+            .map(|(name, value)| (name.to_string(), Value::String(value.clone())))
+            .collect(),
+    };
+    write_deployment(Path::new(MANIFEST_PATH), deployment);
+}
+
+/// Loads the latest deployment of `contract_name` on the current network, if any.
+pub fn load_from_manifest<R: HostRef + HostRefLoader<R>>(
+    env: &HostEnv,
+    contract_name: &str,
+) -> Option<R> {
+    let deployment = latest_deployment(Path::new(MANIFEST_PATH), &network(), contract_name)?;
+    let address = Address::from_str(&deployment.package_hash)
+        .expect("The manifest should hold valid contract addresses");
+    Some(R::load(env, address))
+}
+
+/// Returns all deployments recorded in the manifest at `path`.
+pub fn read_manifest(path: &Path) -> Vec<Deployment> {
+    match fs::read_to_string(path) {
+        Ok(json) => serde_json::from_str(&json).expect("The manifest should be valid JSON"),
+        Err(_) => Vec::new(),
+    }
+}
+
+fn write_deployment(path: &Path, deployment: Deployment) {
+    let mut deployments = read_manifest(path);
+    deployments.push(deployment);
+    let json = serde_json::to_string_pretty(&deployments).expect("Deployments should serialize");
+    fs::write(path, json).expect("Should be able to write the manifest");
+    println!("Deployment recorded in {}", path.display());
+}
+
+fn latest_deployment(path: &Path, network: &str, contract_name: &str) -> Option<Deployment> {
+    read_manifest(path)
+        .into_iter()
+        .rev()
+        .find(|d| d.network == network && d.contract_name == contract_name)
+}
+
+fn network() -> String {
+    std::env::var(CHAIN_NAME_VAR).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deployment(network: &str, package_hash: &str) -> Deployment {
+        Deployment {
+            network: network.to_string(),
+            contract_name: "Flipper".to_string(),
+            package_hash: package_hash.to_string(),
+            deploy_hash: None,
+            timestamp: 0,
+            init_args: Map::new(),
+        }
+    }
+
+    #[test]
+    fn latest_deployment_per_network() {
+        let path = std::env::temp_dir().join("tutorial_utils_deployments.json");
+        let _ = fs::remove_file(&path);
+        assert_eq!(latest_deployment(&path, "casper-test", "Flipper"), None);
+
+        write_deployment(&path, deployment("casper-test", "hash-1"));
+        write_deployment(&path, deployment("casper-test", "hash-2"));
+        write_deployment(&path, deployment("cspr-dev-cctl", "hash-3"));
+        assert_eq!(read_manifest(&path).len(), 3);
+        assert_eq!(
+            latest_deployment(&path, "casper-test", "Flipper"),
+            Some(deployment("casper-test", "hash-2"))
+        );
+        assert_eq!(latest_deployment(&path, "casper-test", "Escrow"), None);
+        fs::remove_file(&path).unwrap();
+    }
+}
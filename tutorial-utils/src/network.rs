@@ -0,0 +1,143 @@
+//! Selects the network a livenet binary talks to from a `networks.toml` file,
+//! instead of relying on manually exported `ODRA_CASPER_LIVENET_*` variables.
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+/// Name of the networks file, looked up in the working directory and its ancestors.
+pub const NETWORKS_FILE: &str = "networks.toml";
+
+/// Connection details of a single network.
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct Network {
+    /// RPC address of the node the deploys are sent to.
+    pub node_address: String,
+    /// Chain name of the network, e.g. `casper-test`.
+    pub chain_name: String,
+    /// Path to the secret key of the deploying account (account 0).
+    pub secret_key_path: String,
+    /// Paths to the secret keys of the additional accounts (accounts 1, 2, ...).
+    #[serde(default)]
+    pub keys: Vec<String>,
+}
+
+/// Configures the livenet env for the network passed with `--network <name>`, if any.
+///
+/// Meant for binaries without their own argument parsing; call it before
+/// `odra_casper_livenet_env::env()`.
+pub fn configure_from_args() {
+    let args: Vec<String> = std::env::args().collect();
+    let name = args.iter().enumerate().find_map(|(i, arg)| {
+        if arg == "--network" {
+            args.get(i + 1).cloned()
+        } else {
+            arg.strip_prefix("--network=").map(str::to_string)
+        }
+    });
+    if let Some(name) = name {
+        configure(&name);
+    }
+}
+
+/// Sets the `ODRA_CASPER_LIVENET_*` variables for the named network.
+///
+/// Call it before `odra_casper_livenet_env::env()`. Variables set here take
+/// precedence over the ones in a `.env` file.
+pub fn configure(name: &str) {
+    let path =
+        find_networks_file().expect("networks.toml should exist in this or a parent directory");
+    let mut networks = read_networks(&path);
+    let network = networks.remove(name).unwrap_or_else(|| {
+        panic!(
+            "Unknown network {}, expected one of: {}",
+            name,
+            networks.keys().cloned().collect::<Vec<_>>().join(", ")
+        )
+    });
+    for (var, value) in env_vars(&network) {
+        std::env::set_var(var, value);
+    }
+    println!("Using the {} network ({})", name, network.chain_name);
+}
+
+/// Reads all networks defined in the file at `path`.
+pub fn read_networks(path: &Path) -> BTreeMap<String, Network> {
+    let toml = fs::read_to_string(path).expect("Should be able to read the networks file");
+    toml::from_str(&toml).expect("The networks file should be valid")
+}
+
+fn find_networks_file() -> Option<PathBuf> {
+    let cwd = std::env::current_dir().ok()?;
+    cwd.ancestors()
+        .map(|dir| dir.join(NETWORKS_FILE))
+        .find(|path| path.exists())
+}
+
+fn env_vars(network: &Network) -> Vec<(String, String)> {
+    let mut vars = vec![
+        (
+            "ODRA_CASPER_LIVENET_NODE_ADDRESS".to_string(),
+            network.node_address.clone(),
+        ),
+        (
+            "ODRA_CASPER_LIVENET_CHAIN_NAME".to_string(),
+            network.chain_name.clone(),
+        ),
+        (
+            "ODRA_CASPER_LIVENET_SECRET_KEY_PATH".to_string(),
+            network.secret_key_path.clone(),
+        ),
+    ];
+    for (i, key) in network.keys.iter().enumerate() {
+        vars.push((format!("ODRA_CASPER_LIVENET_KEY_{}", i + 1), key.clone()));
+    }
+    vars
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn network_env_vars() {
+        let networks: BTreeMap<String, Network> = toml::from_str(
+            r#"
+            [local]
+            node_address = "http://localhost/node-1"
+            chain_name = "cspr-dev-cctl"
+            secret_key_path = ".keys/secret_key_1.pem"
+            keys = [".keys/secret_key_2.pem"]
+
+            [testnet]
+            node_address = "http://localhost:7777"
+            chain_name = "casper-test"
+            secret_key_path = ".keys/secret_key.pem"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(networks["testnet"].keys, Vec::<String>::new());
+        assert_eq!(
+            env_vars(&networks["local"]),
+            vec![
+                (
+                    "ODRA_CASPER_LIVENET_NODE_ADDRESS".to_string(),
+                    "http://localhost/node-1".to_string()
+                ),
+                (
+                    "ODRA_CASPER_LIVENET_CHAIN_NAME".to_string(),
+                    "cspr-dev-cctl".to_string()
+                ),
+                (
+                    "ODRA_CASPER_LIVENET_SECRET_KEY_PATH".to_string(),
+                    ".keys/secret_key_1.pem".to_string()
+                ),
+                (
+                    "ODRA_CASPER_LIVENET_KEY_1".to_string(),
+                    ".keys/secret_key_2.pem".to_string()
+                ),
+            ]
+        );
+    }
+}
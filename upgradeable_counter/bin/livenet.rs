@@ -0,0 +1,171 @@
+//! Walks a counter through an upgrade on a live network: deploys V1, increments it, upgrades it
+//! to V2 and verifies the migrated state.
+//!
+//! Each run performs a single action, e.g.:
+//! `cargo run --bin livenet --features=livenet -- --action upgrade`
+use std::str::FromStr;
+
+use clap::{Parser, ValueEnum};
+use odra::host::{Deployer, HostEnv, HostRef, HostRefLoader, NoArgs, OdraConfig};
+use odra::Address;
+use tutorial_utils::gas::{self, with_gas};
+use tutorial_utils::manifest::{load_from_manifest, record_deployment};
+use tutorial_utils::network;
+use upgradeable_counter::counter_v1::CounterV1HostRef;
+use upgradeable_counter::counter_v2::{CounterV2HostRef, CounterV2InitArgs};
+
+const V1_NAME: &str = "CounterV1";
+const V2_NAME: &str = "CounterV2";
+
+/// Installs V1 into an unlocked package. The deploying account receives the package's access
+/// token, under `CounterV1_package_hash_access_token`, which allows adding versions to it.
+struct Upgradable;
+
+impl OdraConfig for Upgradable {
+    fn package_hash(&self) -> String {
+        V1_NAME.to_string()
+    }
+
+    fn is_upgradable(&self) -> bool {
+        true
+    }
+
+    fn allow_key_override(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Parser)]
+#[command(about = "Deploys a counter and upgrades it from V1 to V2.")]
+struct Args {
+    /// What to do on the network.
+    #[arg(long, value_enum, default_value_t = Action::Deploy)]
+    action: Action,
+    /// Address of a deployed V1 contract, defaults to the latest deployment recorded in `deployments.json`.
+    #[arg(long)]
+    contract: Option<String>,
+    /// Amount to increment a V2 counter by.
+    #[arg(long, default_value_t = 1)]
+    amount: u64,
+    /// Gas limit, defaults to a budget suited to the action.
+    #[arg(long)]
+    gas: Option<u64>,
+    /// Network from `networks.toml` to use instead of the `.env` configuration.
+    #[arg(long)]
+    network: Option<String>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Action {
+    /// Deploys a new V1 contract, in an upgradable package.
+    Deploy,
+    /// Increments the V1 contract by one.
+    Increment,
+    /// Retires the V1 contract, deploys V2 migrating its state and verifies it.
+    Upgrade,
+    /// Increments the V2 contract by `--amount`.
+    IncrementBy,
+    /// Checks the V2 contract took over the state of the V1 contract.
+    Verify,
+}
+
+fn main() {
+    let args = Args::parse();
+    if let Some(name) = &args.network {
+        network::configure(name);
+    }
+    let env = odra_casper_livenet_env::env();
+
+    match args.action {
+        Action::Deploy => {
+            let counter = with_gas(&env, args.gas.unwrap_or(gas::DEPLOY), || {
+                CounterV1HostRef::try_deploy_with_cfg(&env, NoArgs, Upgradable)
+            })
+            .expect("Should deploy the contract");
+            record_deployment(V1_NAME, &counter, None, &[]);
+            println!("CounterV1 address: {}", counter.address().to_string());
+        }
+        Action::Increment => {
+            let mut counter = load_v1(&env, &args);
+            let _ = with_gas(&env, args.gas.unwrap_or(gas::CALL), || {
+                counter.try_increment()
+            });
+            println!("Count: {}", counter.get());
+        }
+        Action::Upgrade => {
+            let mut counter_v1 = load_v1(&env, &args);
+            if !counter_v1.is_retired() {
+                let _ = with_gas(&env, gas::CALL, || counter_v1.try_retire());
+            }
+            let counter_v2 = deploy_v2(&env, &counter_v1, args.gas.unwrap_or(gas::DEPLOY));
+            println!("CounterV2 address: {}", counter_v2.address().to_string());
+            verify(&counter_v1, &counter_v2);
+        }
+        Action::IncrementBy => {
+            let mut counter = load_v2(&env);
+            let _ = with_gas(&env, args.gas.unwrap_or(gas::CALL), || {
+                counter.try_increment_by(args.amount)
+            });
+            println!("Count: {}", counter.get());
+        }
+        Action::Verify => verify(&load_v1(&env, &args), &load_v2(&env)),
+    }
+}
+
+/// Loads the V1 contract passed with `--contract`, or the latest one recorded in the manifest.
+fn load_v1(env: &HostEnv, args: &Args) -> CounterV1HostRef {
+    match &args.contract {
+        Some(address) => {
+            let address = Address::from_str(address).expect("Should be a valid contract address");
+            CounterV1HostRef::load(env, address)
+        }
+        None => {
+            load_from_manifest(env, V1_NAME).expect("Pass --contract or deploy the contract first")
+        }
+    }
+}
+
+/// Loads the latest V2 contract recorded in the manifest.
+fn load_v2(env: &HostEnv) -> CounterV2HostRef {
+    load_from_manifest(env, V2_NAME).expect("Upgrade the contract first")
+}
+
+/// Deploys a V2 contract migrating the state of `counter_v1`, which has to be retired.
+fn deploy_v2(env: &HostEnv, counter_v1: &CounterV1HostRef, gas: u64) -> CounterV2HostRef {
+    let previous = *counter_v1.address();
+    let counter_v2 = with_gas(env, gas, || {
+        CounterV2HostRef::try_deploy(env, CounterV2InitArgs { previous })
+    })
+    .expect("Should deploy the contract");
+    record_deployment(
+        V2_NAME,
+        &counter_v2,
+        None,
+        &[("previous", previous.to_string())],
+    );
+    counter_v2
+}
+
+/// Panics unless `counter_v2` is an upgrade of `counter_v1` that took over its count.
+fn verify(counter_v1: &CounterV1HostRef, counter_v2: &CounterV2HostRef) {
+    assert_eq!(counter_v2.version(), 2, "Should be a V2 contract");
+    assert!(counter_v1.is_retired(), "V1 should be retired");
+    assert_eq!(
+        counter_v2.previous_version(),
+        Some(*counter_v1.address()),
+        "V2 should be migrated from this V1 contract"
+    );
+    assert_eq!(
+        counter_v2.migrated_count(),
+        u64::from(counter_v1.get()),
+        "V2 should start from the final V1 count"
+    );
+    assert_eq!(
+        counter_v2.get_owner(),
+        counter_v1.get_owner(),
+        "V2 should keep the owner"
+    );
+    println!("V1 count: {}", counter_v1.get());
+    println!("V2 count: {}", counter_v2.get());
+    println!("Upgrade verified.");
+}
@@ -0,0 +1,118 @@
+use odra::module::SubModule;
+use odra::prelude::*;
+use odra::{Address, Var};
+use odra_modules::access::Ownable;
+
+#[odra::odra_error]
+/// Errors that may occur during the contract execution.
+pub enum Error {
+    /// The count reached `u32::MAX`.
+    CounterOverflow = 1,
+    /// The counter has been retired in favor of a newer version.
+    Retired = 2,
+}
+
+#[odra::event]
+/// Emitted when the owner retires the counter.
+pub struct CounterRetired {
+    pub final_count: u32,
+}
+
+#[odra::module(events = [CounterRetired], errors = Error)]
+/// The first version of the counter: anyone can increment it by one.
+pub struct CounterV1 {
+    ownable: SubModule<Ownable>,
+    count: Var<u32>,
+    /// Set once the owner hands the counter over to a newer version.
+    retired: Var<bool>,
+}
+
+#[odra::module]
+impl CounterV1 {
+    /// Initializes the counter at zero; the deployer becomes its owner.
+    pub fn init(&mut self) {
+        self.ownable.init();
+    }
+
+    delegate! {
+        to self.ownable {
+            fn get_owner(&self) -> Address;
+        }
+    }
+
+    /**********
+     * TRANSACTIONS
+     **********/
+
+    /// Increments the count by one.
+    pub fn increment(&mut self) {
+        self.assert_not_retired();
+        let count = self
+            .count
+            .get_or_default()
+            .checked_add(1)
+            .unwrap_or_revert_with(&self.env(), Error::CounterOverflow);
+        self.count.set(count);
+    }
+
+    /// Freezes the count, so a newer version can take it over. Only the owner can call it.
+    pub fn retire(&mut self) {
+        self.ownable.assert_owner(&self.env().caller());
+        self.assert_not_retired();
+        self.retired.set(true);
+        self.env().emit_event(CounterRetired {
+            final_count: self.count.get_or_default(),
+        });
+    }
+
+    /**********
+     * QUERIES
+     **********/
+
+    pub fn get(&self) -> u32 {
+        self.count.get_or_default()
+    }
+
+    pub fn is_retired(&self) -> bool {
+        self.retired.get_or_default()
+    }
+
+    pub fn version(&self) -> u32 {
+        1
+    }
+}
+
+impl CounterV1 {
+    fn assert_not_retired(&self) {
+        if self.is_retired() {
+            self.env().revert(Error::Retired);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use odra::host::{Deployer, HostRef, NoArgs};
+
+    #[test]
+    fn retiring_freezes_the_count() {
+        let env = odra_test::env();
+        let owner = env.get_account(0);
+        let mut counter = CounterV1HostRef::deploy(&env, NoArgs);
+
+        env.set_caller(env.get_account(1));
+        counter.increment();
+        counter.increment();
+        assert_eq!(counter.get(), 2);
+        assert!(counter.try_retire().is_err());
+
+        env.set_caller(owner);
+        counter.retire();
+        assert!(counter.is_retired());
+        assert!(env.emitted_event(counter.address(), &CounterRetired { final_count: 2 }));
+        assert_eq!(counter.try_increment(), Err(Error::Retired.into()));
+        assert_eq!(counter.try_retire(), Err(Error::Retired.into()));
+        assert_eq!(counter.get(), 2);
+    }
+}
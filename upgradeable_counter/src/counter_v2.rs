@@ -0,0 +1,180 @@
+use crate::counter_v1::CounterV1ContractRef;
+use odra::module::SubModule;
+use odra::prelude::*;
+use odra::{Address, Var};
+use odra_modules::access::Ownable;
+
+#[odra::odra_error]
+/// Errors that may occur during the contract execution.
+pub enum Error {
+    /// Only the owner of the previous version can upgrade it.
+    NotPreviousOwner = 1,
+    /// The previous version has to be retired before its state is migrated.
+    PreviousNotRetired = 2,
+    /// The count reached `u64::MAX`.
+    CounterOverflow = 3,
+    /// Incrementing by zero does nothing.
+    ZeroAmount = 4,
+}
+
+#[odra::event]
+/// Emitted when the state of the previous version is migrated.
+pub struct Migrated {
+    pub previous: Address,
+    pub count: u64,
+}
+
+#[odra::module(events = [Migrated], errors = Error)]
+/// The second version of the counter. It takes over the count of a retired `CounterV1`, widened
+/// to a `u64`, and can be incremented by any amount.
+pub struct CounterV2 {
+    ownable: SubModule<Ownable>,
+    count: Var<u64>,
+    /// Address of the `CounterV1` the state was migrated from.
+    previous: Var<Address>,
+    /// The count as it was migrated, kept to verify the upgrade.
+    migrated_count: Var<u64>,
+}
+
+#[odra::module]
+impl CounterV2 {
+    /// Upgrades the retired counter at `previous`, migrating its state. Only the owner of the
+    /// previous version can deploy it, and becomes the owner of this one.
+    pub fn init(&mut self, previous: Address) {
+        let counter_v1 = CounterV1ContractRef::new(self.env(), previous);
+        if counter_v1.get_owner() != self.env().caller() {
+            self.env().revert(Error::NotPreviousOwner);
+        }
+        // While V1 accepts increments, a count copied from it could already be stale
+        if !counter_v1.is_retired() {
+            self.env().revert(Error::PreviousNotRetired);
+        }
+        self.ownable.init();
+        self.migrate(previous, counter_v1.get());
+    }
+
+    delegate! {
+        to self.ownable {
+            fn get_owner(&self) -> Address;
+        }
+    }
+
+    /**********
+     * TRANSACTIONS
+     **********/
+
+    /// Increments the count by one.
+    pub fn increment(&mut self) {
+        self.add(1);
+    }
+
+    /// Increments the count by `amount`. New in V2.
+    pub fn increment_by(&mut self, amount: u64) {
+        if amount == 0 {
+            self.env().revert(Error::ZeroAmount);
+        }
+        self.add(amount);
+    }
+
+    /**********
+     * QUERIES
+     **********/
+
+    pub fn get(&self) -> u64 {
+        self.count.get_or_default()
+    }
+
+    pub fn previous_version(&self) -> Option<Address> {
+        self.previous.get()
+    }
+
+    pub fn migrated_count(&self) -> u64 {
+        self.migrated_count.get_or_default()
+    }
+
+    pub fn version(&self) -> u32 {
+        2
+    }
+}
+
+impl CounterV2 {
+    /// Converts the V1 state to the V2 layout: the `u32` count becomes a `u64`.
+    fn migrate(&mut self, previous: Address, count_v1: u32) {
+        let count = u64::from(count_v1);
+        self.count.set(count);
+        self.migrated_count.set(count);
+        self.previous.set(previous);
+        self.env().emit_event(Migrated { previous, count });
+    }
+
+    fn add(&mut self, amount: u64) {
+        let count = self
+            .count
+            .get_or_default()
+            .checked_add(amount)
+            .unwrap_or_revert_with(&self.env(), Error::CounterOverflow);
+        self.count.set(count);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::counter_v1::CounterV1HostRef;
+    use odra::host::{Deployer, HostRef, NoArgs};
+
+    #[test]
+    fn upgrade_preserves_the_count() {
+        let env = odra_test::env();
+        let owner = env.get_account(0);
+        let user = env.get_account(1);
+        let mut counter_v1 = CounterV1HostRef::deploy(&env, NoArgs);
+        env.set_caller(user);
+        for _ in 0..3 {
+            counter_v1.increment();
+        }
+
+        // V1 has to be retired first, and only by its owner
+        env.set_caller(owner);
+        let args = || CounterV2InitArgs {
+            previous: *counter_v1.address(),
+        };
+        assert_eq!(
+            CounterV2HostRef::try_deploy(&env, args()).err(),
+            Some(Error::PreviousNotRetired.into())
+        );
+        counter_v1.retire();
+        env.set_caller(user);
+        assert_eq!(
+            CounterV2HostRef::try_deploy(&env, args()).err(),
+            Some(Error::NotPreviousOwner.into())
+        );
+
+        env.set_caller(owner);
+        let mut counter_v2 = CounterV2HostRef::deploy(&env, args());
+        assert!(env.emitted_event(
+            counter_v2.address(),
+            &Migrated {
+                previous: *counter_v1.address(),
+                count: 3,
+            }
+        ));
+
+        // Post-upgrade verification
+        assert_eq!(counter_v2.version(), 2);
+        assert_eq!(counter_v2.get(), u64::from(counter_v1.get()));
+        assert_eq!(counter_v2.migrated_count(), 3);
+        assert_eq!(counter_v2.previous_version(), Some(*counter_v1.address()));
+        assert_eq!(counter_v2.get_owner(), owner);
+
+        env.set_caller(user);
+        counter_v2.increment();
+        counter_v2.increment_by(u64::from(u32::MAX));
+        assert_eq!(counter_v2.get(), 4 + u64::from(u32::MAX));
+        assert_eq!(
+            counter_v2.try_increment_by(0),
+            Err(Error::ZeroAmount.into())
+        );
+        assert_eq!(counter_v1.get(), 3);
+    }
+}
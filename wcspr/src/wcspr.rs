@@ -0,0 +1,207 @@
+use odra::casper_types::{U256, U512};
+use odra::module::SubModule;
+use odra::prelude::*;
+use odra::uints::{ToU256, ToU512};
+use odra::Address;
+use odra_modules::cep18_token::Cep18;
+
+// Numbered past the error codes of the embedded CEP-18 module.
+#[odra::odra_error]
+/// Errors that may occur during the contract execution.
+pub enum Error {
+    /// Deposited and withdrawn amounts must be positive.
+    ZeroAmount = 40_000,
+}
+
+#[odra::event]
+/// Emitted when CSPR is wrapped.
+pub struct Deposit {
+    pub account: Address,
+    pub amount: U512,
+}
+
+#[odra::event]
+/// Emitted when WCSPR is unwrapped.
+pub struct Withdrawal {
+    pub account: Address,
+    pub amount: U512,
+}
+
+#[odra::module(events = [Deposit, Withdrawal], errors = Error)]
+/// A CEP-18 token minted 1:1 for deposited CSPR and burned when it's withdrawn.
+pub struct Wcspr {
+    token: SubModule<Cep18>,
+}
+
+#[odra::module]
+impl Wcspr {
+    /// Initializes the token with no supply; it has the same 9 decimals as CSPR.
+    pub fn init(&mut self) {
+        self.token.init(
+            "WCSPR".to_string(),
+            "Wrapped CSPR".to_string(),
+            9,
+            U256::zero(),
+            vec![],
+            vec![],
+            None,
+        );
+    }
+
+    /**********
+     * TRANSACTIONS
+     **********/
+
+    /// Mints WCSPR for the attached CSPR to the caller.
+    #[odra(payable)]
+    pub fn deposit(&mut self) {
+        let amount = self.env().attached_value();
+        if amount.is_zero() {
+            self.env().revert(Error::ZeroAmount);
+        }
+        let account = self.env().caller();
+        self.token
+            .raw_mint(&account, &amount.to_u256().unwrap_or_revert(&self.env()));
+        self.env().emit_event(Deposit { account, amount });
+    }
+
+    /// Burns `amount` of the caller's WCSPR and sends them the same amount of CSPR.
+    pub fn withdraw(&mut self, amount: U256) {
+        if amount.is_zero() {
+            self.env().revert(Error::ZeroAmount);
+        }
+        let account = self.env().caller();
+        // Burn before transferring, so the supply never exceeds the CSPR held
+        self.token.raw_burn(&account, &amount);
+        let amount = amount.to_u512();
+        self.env().transfer_tokens(&account, &amount);
+        self.env().emit_event(Withdrawal { account, amount });
+    }
+
+    delegate! {
+        to self.token {
+            fn transfer(&mut self, recipient: &Address, amount: &U256);
+            fn transfer_from(&mut self, owner: &Address, recipient: &Address, amount: &U256);
+            fn approve(&mut self, spender: &Address, amount: &U256);
+            fn increase_allowance(&mut self, spender: &Address, inc_by: &U256);
+            fn decrease_allowance(&mut self, spender: &Address, decr_by: &U256);
+            fn name(&self) -> String;
+            fn symbol(&self) -> String;
+            fn decimals(&self) -> u8;
+            fn total_supply(&self) -> U256;
+            fn balance_of(&self, address: &Address) -> U256;
+            fn allowance(&self, owner: &Address, spender: &Address) -> U256;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use odra::host::{Deployer, HostEnv, HostRef, NoArgs};
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+
+    /// Checks that the WCSPR supply is exactly the CSPR held by the contract.
+    fn assert_backed(env: &HostEnv, wcspr: &WcsprHostRef) {
+        assert_eq!(
+            wcspr.total_supply().to_u512(),
+            env.balance_of(wcspr.address())
+        );
+    }
+
+    #[test]
+    fn wrap_and_unwrap() {
+        let env = odra_test::env();
+        let mut wcspr = WcsprHostRef::deploy(&env, NoArgs);
+        let (alice, bob) = (env.get_account(1), env.get_account(2));
+        assert_eq!(wcspr.symbol(), "WCSPR".to_string());
+        assert_eq!(wcspr.decimals(), 9);
+
+        env.set_caller(alice);
+        let balance = env.balance_of(&alice);
+        wcspr.with_tokens(U512::from(1_000)).deposit();
+        assert_eq!(wcspr.balance_of(&alice), U256::from(1_000));
+        assert_eq!(env.balance_of(&alice), balance - U512::from(1_000));
+        assert_backed(&env, &wcspr);
+        assert_eq!(wcspr.try_deposit(), Err(Error::ZeroAmount.into()));
+
+        // WCSPR is an ordinary CEP-18 token, whoever holds it can unwrap it
+        wcspr.transfer(&bob, &U256::from(400));
+        env.set_caller(bob);
+        let balance = env.balance_of(&bob);
+        wcspr.withdraw(U256::from(400));
+        assert_eq!(env.balance_of(&bob), balance + U512::from(400));
+        assert_eq!(wcspr.total_supply(), U256::from(600));
+        assert_backed(&env, &wcspr);
+        assert!(env.emitted_event(
+            wcspr.address(),
+            &Withdrawal {
+                account: bob,
+                amount: U512::from(400)
+            }
+        ));
+
+        // Nobody can withdraw more than they hold
+        assert!(wcspr.try_withdraw(U256::one()).is_err());
+        assert_eq!(
+            wcspr.try_withdraw(U256::zero()),
+            Err(Error::ZeroAmount.into())
+        );
+    }
+
+    /// What one of the accounts does with its WCSPR. Withdrawals and transfers take a per mille
+    /// of the account's balance, at least one token, so they are valid whatever the balance.
+    #[derive(Debug, Clone)]
+    enum Action {
+        Deposit(u64),
+        Withdraw(u64),
+        Transfer(usize, u64),
+    }
+
+    fn action() -> impl Strategy<Value = (usize, Action)> {
+        let action = prop_oneof![
+            (1..10_001u64).prop_map(Action::Deposit),
+            (1..1_001u64).prop_map(Action::Withdraw),
+            (0..3usize, 1..1_001u64).prop_map(|(to, per_mille)| Action::Transfer(to, per_mille)),
+        ];
+        (0..3usize, action)
+    }
+
+    fn part_of(balance: U256, per_mille: u64) -> U256 {
+        (balance * per_mille / 1_000).max(U256::one())
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(16))]
+
+        #[test]
+        fn supply_invariant(actions in vec(action(), 1..60)) {
+            let env = odra_test::env();
+            let mut wcspr = WcsprHostRef::deploy(&env, NoArgs);
+
+            for (account, action) in actions {
+                let account = env.get_account(account);
+                env.set_caller(account);
+                let balance = wcspr.balance_of(&account);
+                match action {
+                    Action::Deposit(amount) => wcspr.with_tokens(U512::from(amount)).deposit(),
+                    Action::Withdraw(per_mille) if !balance.is_zero() => {
+                        wcspr.withdraw(part_of(balance, per_mille));
+                    }
+                    Action::Transfer(to, per_mille) if !balance.is_zero() => {
+                        wcspr.transfer(&env.get_account(to), &part_of(balance, per_mille));
+                    }
+                    _ => {}
+                }
+
+                // Every WCSPR is backed by a mote, and the balances add up to the supply
+                assert_backed(&env, &wcspr);
+                let balances = (0..3)
+                    .map(|i| wcspr.balance_of(&env.get_account(i)))
+                    .fold(U256::zero(), |sum, balance| sum + balance);
+                assert_eq!(balances, wcspr.total_supply());
+            }
+        }
+    }
+}
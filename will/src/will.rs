@@ -0,0 +1,435 @@
+use odra::casper_types::U512;
+use odra::prelude::*;
+use odra::{Address, Var};
+
+#[odra::odra_error]
+/// Errors that may occur during the contract execution.
+pub enum Error {
+    /// Only the testator can manage the will.
+    NotTestator = 1,
+    /// Only the notary can freeze and unfreeze the will.
+    NotNotary = 2,
+    /// Only heirs can distribute the estate.
+    NotHeir = 3,
+    /// At least one heir is needed.
+    NoHeirs = 4,
+    /// Every heir needs exactly one share.
+    LengthMismatch = 5,
+    /// Each heir can be listed only once.
+    DuplicateHeir = 6,
+    /// The shares have to add up to 100 percent.
+    InvalidShares = 7,
+    /// The testator has pinged the will within the inactivity period.
+    TestatorStillActive = 8,
+    /// The notary has frozen the will.
+    Frozen = 9,
+    /// The estate has already been distributed.
+    AlreadyDistributed = 10,
+    /// The will doesn't hold enough CSPR.
+    InsufficientBalance = 11,
+}
+
+#[odra::odra_type]
+pub struct Heir {
+    pub address: Address,
+    /// Percentage of the estate the heir inherits.
+    pub share: u8,
+}
+
+#[odra::event]
+/// Emitted when the testator changes the heirs or their shares.
+pub struct HeirsUpdated {
+    pub heirs: Vec<Heir>,
+}
+
+#[odra::event]
+/// Emitted when the notary freezes the will.
+pub struct WillFrozen {
+    pub notary: Address,
+}
+
+#[odra::event]
+/// Emitted when the notary unfreezes the will.
+pub struct WillUnfrozen {
+    pub notary: Address,
+}
+
+#[odra::event]
+/// Emitted when an heir distributes the estate.
+pub struct EstateDistributed {
+    pub distributed_by: Address,
+    pub amount: U512,
+}
+
+#[odra::module(
+    events = [HeirsUpdated, WillFrozen, WillUnfrozen, EstateDistributed],
+    errors = Error
+)]
+/// A will holding the testator's CSPR, distributed among heirs once the testator stops pinging it.
+pub struct Will {
+    testator: Var<Address>,
+    notary: Var<Address>,
+    heirs: Var<Vec<Heir>>,
+    /// Time without a ping after which the heirs can distribute the estate.
+    inactivity_period: Var<u64>,
+    /// Block time of the testator's last ping.
+    last_ping: Var<u64>,
+    frozen: Var<bool>,
+    distributed: Var<bool>,
+}
+
+#[odra::module]
+impl Will {
+    /// Initializes the will; the deployer becomes the testator. `shares` are percentages, in
+    /// the order of the heirs, and have to add up to 100.
+    pub fn init(
+        &mut self,
+        heirs: Vec<Address>,
+        shares: Vec<u8>,
+        inactivity_period: u64,
+        notary: Address,
+    ) {
+        self.testator.set(self.env().caller());
+        self.notary.set(notary);
+        self.inactivity_period.set(inactivity_period);
+        self.set_heirs(heirs, shares);
+        self.last_ping.set(self.env().get_block_time());
+    }
+
+    /**********
+     * TRANSACTIONS
+     **********/
+
+    /// Adds the attached CSPR to the estate. Also counts as a ping. Only the testator can
+    /// call it.
+    #[odra(payable)]
+    pub fn deposit(&mut self) {
+        self.assert_testator();
+    }
+
+    /// Sends `amount` of the estate back to the testator. Also counts as a ping.
+    pub fn withdraw(&mut self, amount: U512) {
+        let testator = self.assert_testator();
+        if amount > self.env().self_balance() {
+            self.env().revert(Error::InsufficientBalance);
+        }
+        self.env().transfer_tokens(&testator, &amount);
+    }
+
+    /// Proves the testator is still around, postponing the distribution.
+    pub fn ping(&mut self) {
+        self.assert_testator();
+    }
+
+    /// Replaces the heirs and their shares. Also counts as a ping.
+    pub fn update_heirs(&mut self, heirs: Vec<Address>, shares: Vec<u8>) {
+        self.assert_testator();
+        self.set_heirs(heirs, shares);
+    }
+
+    /// Replaces the notary. Also counts as a ping.
+    pub fn set_notary(&mut self, notary: Address) {
+        self.assert_testator();
+        self.notary.set(notary);
+    }
+
+    /// Blocks the distribution until the will is unfrozen, e.g. while a dispute over it is
+    /// settled. Only the notary can call it.
+    pub fn freeze(&mut self) {
+        let notary = self.assert_notary();
+        self.frozen.set(true);
+        self.env().emit_event(WillFrozen { notary });
+    }
+
+    /// Allows the distribution again. Only the notary can call it.
+    pub fn unfreeze(&mut self) {
+        let notary = self.assert_notary();
+        self.frozen.set(false);
+        self.env().emit_event(WillUnfrozen { notary });
+    }
+
+    /// Sends each heir their share of the estate, once the testator hasn't pinged the will for
+    /// the inactivity period. Any heir can call it, unless the will is frozen.
+    pub fn distribute(&mut self) {
+        let heirs = self.heirs.get_or_default();
+        let caller = self.env().caller();
+        if !heirs.iter().any(|heir| heir.address == caller) {
+            self.env().revert(Error::NotHeir);
+        }
+        if self.env().get_block_time() < self.distributable_at() {
+            self.env().revert(Error::TestatorStillActive);
+        }
+        if self.frozen.get_or_default() {
+            self.env().revert(Error::Frozen);
+        }
+        if self.distributed.get_or_default() {
+            self.env().revert(Error::AlreadyDistributed);
+        }
+
+        let amount = self.env().self_balance();
+        self.distributed.set(true);
+        let mut remaining = amount;
+        for heir in heirs.iter().skip(1) {
+            let share = amount * U512::from(heir.share) / U512::from(100);
+            remaining -= share;
+            self.pay(heir.address, share);
+        }
+        // The first heir also gets the rounding dust
+        self.pay(heirs[0].address, remaining);
+        self.env().emit_event(EstateDistributed {
+            distributed_by: caller,
+            amount,
+        });
+    }
+
+    /**********
+     * QUERIES
+     **********/
+
+    /// Returns the testator.
+    pub fn get_testator(&self) -> Address {
+        self.testator.get().unwrap_or_revert(&self.env())
+    }
+
+    /// Returns the notary.
+    pub fn get_notary(&self) -> Address {
+        self.notary.get().unwrap_or_revert(&self.env())
+    }
+
+    /// Returns the heirs and their shares.
+    pub fn get_heirs(&self) -> Vec<Heir> {
+        self.heirs.get_or_default()
+    }
+
+    /// Returns the block time from which the heirs can distribute the estate, unless the
+    /// testator pings the will before.
+    pub fn distributable_at(&self) -> u64 {
+        self.last_ping.get_or_default() + self.inactivity_period.get_or_default()
+    }
+
+    /// Returns whether the notary has frozen the will.
+    pub fn is_frozen(&self) -> bool {
+        self.frozen.get_or_default()
+    }
+
+    /// Returns whether the estate has been distributed.
+    pub fn is_distributed(&self) -> bool {
+        self.distributed.get_or_default()
+    }
+
+    /// Returns the CSPR in the estate.
+    pub fn balance(&self) -> U512 {
+        self.env().self_balance()
+    }
+}
+
+impl Will {
+    /// Reverts unless the caller is the testator and the will is still in force, and records
+    /// the call as a ping.
+    fn assert_testator(&mut self) -> Address {
+        let caller = self.env().caller();
+        if Some(caller) != self.testator.get() {
+            self.env().revert(Error::NotTestator);
+        }
+        if self.distributed.get_or_default() {
+            self.env().revert(Error::AlreadyDistributed);
+        }
+        self.last_ping.set(self.env().get_block_time());
+        caller
+    }
+
+    fn assert_notary(&self) -> Address {
+        let caller = self.env().caller();
+        if Some(caller) != self.notary.get() {
+            self.env().revert(Error::NotNotary);
+        }
+        if self.distributed.get_or_default() {
+            self.env().revert(Error::AlreadyDistributed);
+        }
+        caller
+    }
+
+    fn set_heirs(&mut self, heirs: Vec<Address>, shares: Vec<u8>) {
+        if heirs.is_empty() {
+            self.env().revert(Error::NoHeirs);
+        }
+        if heirs.len() != shares.len() {
+            self.env().revert(Error::LengthMismatch);
+        }
+        for (i, heir) in heirs.iter().enumerate() {
+            if heirs[..i].contains(heir) {
+                self.env().revert(Error::DuplicateHeir);
+            }
+        }
+        if shares.iter().map(|share| *share as u32).sum::<u32>() != 100 {
+            self.env().revert(Error::InvalidShares);
+        }
+
+        let heirs: Vec<Heir> = heirs
+            .into_iter()
+            .zip(shares)
+            .map(|(address, share)| Heir { address, share })
+            .collect();
+        self.heirs.set(heirs.clone());
+        self.env().emit_event(HeirsUpdated { heirs });
+    }
+
+    fn pay(&self, to: Address, amount: U512) {
+        if !amount.is_zero() {
+            self.env().transfer_tokens(&to, &amount);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use odra::host::{Deployer, HostEnv, HostRef};
+
+    const INACTIVITY_PERIOD: u64 = 180 * 24 * 60 * 60 * 1_000; // about six months
+
+    /// Deploys a will by account 0, leaving 60% to account 1 and 40% to account 2, with account
+    /// 3 as the notary, and deposits 1001 motes.
+    fn setup(env: &HostEnv) -> WillHostRef {
+        let mut will = WillHostRef::deploy(
+            env,
+            WillInitArgs {
+                heirs: vec![env.get_account(1), env.get_account(2)],
+                shares: vec![60, 40],
+                inactivity_period: INACTIVITY_PERIOD,
+                notary: env.get_account(3),
+            },
+        );
+        will.with_tokens(U512::from(1_001)).deposit();
+        will
+    }
+
+    #[test]
+    fn estate_is_distributed_after_inactivity() {
+        let env = odra_test::env();
+        let mut will = setup(&env);
+        let (testator, alice, bob) = (env.get_account(0), env.get_account(1), env.get_account(2));
+        let balances = (env.balance_of(&alice), env.balance_of(&bob));
+
+        // Every call by the testator postpones the distribution
+        env.advance_block_time(INACTIVITY_PERIOD - 1);
+        will.ping();
+        env.advance_block_time(INACTIVITY_PERIOD - 2);
+        env.set_caller(alice);
+        assert_eq!(
+            will.try_distribute(),
+            Err(Error::TestatorStillActive.into())
+        );
+        env.set_caller(testator);
+        will.withdraw(U512::from(1));
+        assert_eq!(will.distributable_at(), 3 * INACTIVITY_PERIOD - 3);
+
+        // Only heirs can distribute the estate, once the testator goes quiet
+        env.advance_block_time(INACTIVITY_PERIOD);
+        env.set_caller(env.get_account(4));
+        assert_eq!(will.try_distribute(), Err(Error::NotHeir.into()));
+        env.set_caller(bob);
+        will.distribute();
+        assert_eq!(env.balance_of(&alice), balances.0 + U512::from(600));
+        assert_eq!(env.balance_of(&bob), balances.1 + U512::from(400));
+        assert_eq!(will.balance(), U512::zero());
+        assert!(will.is_distributed());
+        assert_eq!(will.try_distribute(), Err(Error::AlreadyDistributed.into()));
+
+        // The will is spent
+        env.set_caller(testator);
+        assert_eq!(will.try_ping(), Err(Error::AlreadyDistributed.into()));
+        assert!(env.emitted_event(
+            will.address(),
+            &EstateDistributed {
+                distributed_by: bob,
+                amount: U512::from(1_000),
+            }
+        ));
+    }
+
+    #[test]
+    fn notary_freezes_the_will() {
+        let env = odra_test::env();
+        let mut will = setup(&env);
+        let (alice, notary) = (env.get_account(1), env.get_account(3));
+        env.advance_block_time(INACTIVITY_PERIOD);
+
+        env.set_caller(alice);
+        assert_eq!(will.try_freeze(), Err(Error::NotNotary.into()));
+        env.set_caller(notary);
+        will.freeze();
+        assert!(will.is_frozen());
+        env.set_caller(alice);
+        assert_eq!(will.try_distribute(), Err(Error::Frozen.into()));
+
+        // Once the dispute is settled, the distribution goes ahead
+        env.set_caller(notary);
+        will.unfreeze();
+        env.set_caller(alice);
+        will.distribute();
+        assert_eq!(will.balance(), U512::zero());
+        env.set_caller(notary);
+        assert_eq!(will.try_freeze(), Err(Error::AlreadyDistributed.into()));
+    }
+
+    #[test]
+    fn testator_manages_the_will() {
+        let env = odra_test::env();
+        let mut will = setup(&env);
+        let (alice, bob, carol) = (env.get_account(1), env.get_account(2), env.get_account(4));
+
+        // Only the testator changes the heirs, and the shares have to add up
+        env.set_caller(alice);
+        assert_eq!(
+            will.try_update_heirs(vec![alice], vec![100]),
+            Err(Error::NotTestator.into())
+        );
+        env.set_caller(env.get_account(0));
+        assert_eq!(
+            will.try_update_heirs(vec![], vec![]),
+            Err(Error::NoHeirs.into())
+        );
+        assert_eq!(
+            will.try_update_heirs(vec![alice, bob], vec![100]),
+            Err(Error::LengthMismatch.into())
+        );
+        assert_eq!(
+            will.try_update_heirs(vec![alice, alice], vec![50, 50]),
+            Err(Error::DuplicateHeir.into())
+        );
+        assert_eq!(
+            will.try_update_heirs(vec![alice, bob], vec![60, 60]),
+            Err(Error::InvalidShares.into())
+        );
+        will.update_heirs(vec![carol, alice, bob], vec![34, 33, 33]);
+        assert_eq!(
+            will.try_withdraw(U512::from(1_002)),
+            Err(Error::InsufficientBalance.into())
+        );
+
+        // The first heir gets the rounding dust
+        let balance = env.balance_of(&carol);
+        env.advance_block_time(INACTIVITY_PERIOD);
+        env.set_caller(bob);
+        will.distribute();
+        assert_eq!(env.balance_of(&carol), balance + U512::from(341));
+        assert_eq!(
+            will.get_heirs(),
+            vec![
+                Heir {
+                    address: carol,
+                    share: 34
+                },
+                Heir {
+                    address: alice,
+                    share: 33
+                },
+                Heir {
+                    address: bob,
+                    share: 33
+                },
+            ]
+        );
+    }
+}